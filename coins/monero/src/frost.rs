@@ -10,7 +10,7 @@ use curve25519_dalek::{
 };
 
 use transcript::{Transcript, RecommendedTranscript};
-use frost::curve::{Curve, Ed25519};
+use frost::curve::{CurveError, Curve, Ed25519};
 use dalek_ff_group as dfg;
 
 use crate::random_scalar;
@@ -19,6 +19,8 @@ use crate::random_scalar;
 pub enum MultisigError {
   #[error("internal error ({0})")]
   InternalError(String),
+  #[error("invalid point from {0} ({1})")]
+  InvalidPoint(u16, CurveError),
   #[error("invalid discrete log equality proof")]
   InvalidDLEqProof(u16),
   #[error("invalid key image {0}")]
@@ -126,10 +128,11 @@ pub fn read_dleq(
   xG: &DPoint
 ) -> Result<dfg::EdwardsPoint, MultisigError> {
   // Not using G_from_slice here would enable non-canonical points and break blame
+  // It also bans torsioned points, which would otherwise be able to malleate key images/nonces
   // This does also ban identity points, yet those should never be a concern
   let other = <Ed25519 as Curve>::G_from_slice(
     &serialized[(start + 0) .. (start + 32)]
-  ).map_err(|_| MultisigError::InvalidDLEqProof(l))?;
+  ).map_err(|e| MultisigError::InvalidPoint(l, e))?;
 
   DLEqProof::deserialize(&serialized[(start + 32) .. (start + 96)])
     .ok_or(MultisigError::InvalidDLEqProof(l))?
@@ -15,6 +15,7 @@ pub mod frost;
 mod serialize;
 
 pub mod ringct;
+pub mod ring_signature;
 
 pub mod transaction;
 pub mod block;
@@ -26,10 +27,12 @@ pub mod wallet;
 mod tests;
 
 lazy_static! {
-  static ref H: EdwardsPoint = CompressedEdwardsY(
+  /// Monero's alternate generator, used for amounts within commitments
+  pub static ref H: EdwardsPoint = CompressedEdwardsY(
     hex::decode("8b655970153799af2aeadc9ff1add0ea6c7251d54154cfa92c173a0dd39c1f94").unwrap().try_into().unwrap()
   ).decompress().unwrap();
-  static ref H_TABLE: EdwardsBasepointTable = EdwardsBasepointTable::create(&*H);
+  /// Precomputed table for H, mirroring ED25519_BASEPOINT_TABLE, built on first use
+  pub static ref H_TABLE: EdwardsBasepointTable = EdwardsBasepointTable::create(&*H);
 }
 
 #[allow(non_snake_case)]
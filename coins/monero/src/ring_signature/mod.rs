@@ -0,0 +1,127 @@
+#![allow(non_snake_case)]
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  traits::Identity,
+  scalar::Scalar,
+  edwards::EdwardsPoint
+};
+
+use crate::{random_scalar, hash_to_scalar, hash_to_point, serialize::*};
+
+#[cfg(feature = "multisig")]
+mod multisig;
+#[cfg(feature = "multisig")]
+pub use multisig::RingSignatureMultisig;
+
+/// A CryptoNote ring signature, as used by version 1 transactions and spend proofs
+/// Every ring member has a challenge and response, with the challenges summing to the hash of the
+/// message and every member's commitments
+#[derive(Clone, PartialEq, Debug)]
+pub struct RingSignature {
+  /// (c, r) pairs, by ring member
+  pub sigs: Vec<(Scalar, Scalar)>
+}
+
+impl RingSignature {
+  // Sign with the specified nonce commitments, kG and kHp(P), returning the signature and the
+  // challenge for the real spend. The real spend's response is left as 0 for the caller to set to
+  // k - cx
+  pub(crate) fn sign_core<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    msg: &[u8; 32],
+    ring: &[EdwardsPoint],
+    image: &EdwardsPoint,
+    i: usize,
+    A: EdwardsPoint,
+    AH: EdwardsPoint
+  ) -> (RingSignature, Scalar) {
+    let mut sigs = Vec::with_capacity(ring.len());
+    let mut to_hash = Vec::with_capacity(32 * (1 + (2 * ring.len())));
+    to_hash.extend(msg);
+    let mut sum = Scalar::zero();
+    for (j, P) in ring.iter().enumerate() {
+      if j == i {
+        sigs.push((Scalar::zero(), Scalar::zero()));
+        to_hash.extend(A.compress().to_bytes());
+        to_hash.extend(AH.compress().to_bytes());
+        continue;
+      }
+
+      let (c, r) = (random_scalar(rng), random_scalar(rng));
+      sigs.push((c, r));
+      sum += c;
+      to_hash.extend(
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, P, &r).compress().to_bytes()
+      );
+      to_hash.extend(((r * hash_to_point(P)) + (c * image)).compress().to_bytes());
+    }
+
+    let c = hash_to_scalar(&to_hash) - sum;
+    sigs[i].0 = c;
+    (RingSignature { sigs }, c)
+  }
+
+  /// Sign for the ring member at index i, whose private key is secret
+  pub fn sign<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    msg: &[u8; 32],
+    ring: &[EdwardsPoint],
+    i: usize,
+    secret: &Scalar
+  ) -> RingSignature {
+    let image = secret * hash_to_point(&ring[i]);
+    let nonce = random_scalar(rng);
+    let (mut sig, c) = RingSignature::sign_core(
+      rng,
+      msg,
+      ring,
+      &image,
+      i,
+      &nonce * &ED25519_BASEPOINT_TABLE,
+      nonce * hash_to_point(&ring[i])
+    );
+    sig.sigs[i].1 = nonce - (c * secret);
+    sig
+  }
+
+  pub fn verify(&self, msg: &[u8; 32], ring: &[EdwardsPoint], image: &EdwardsPoint) -> bool {
+    if ring.is_empty() || (ring.len() != self.sigs.len()) {
+      return false;
+    }
+    if (*image == EdwardsPoint::identity()) || (!image.is_torsion_free()) {
+      return false;
+    }
+
+    let mut to_hash = Vec::with_capacity(32 * (1 + (2 * ring.len())));
+    to_hash.extend(msg);
+    let mut sum = Scalar::zero();
+    for (P, (c, r)) in ring.iter().zip(&self.sigs) {
+      sum += c;
+      to_hash.extend(
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(c, P, r).compress().to_bytes()
+      );
+      to_hash.extend(((r * hash_to_point(P)) + (c * image)).compress().to_bytes());
+    }
+    hash_to_scalar(&to_hash) == sum
+  }
+
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+    for (c, r) in &self.sigs {
+      write_scalar(c, w)?;
+      write_scalar(r, w)?;
+    }
+    Ok(())
+  }
+
+  pub fn deserialize<R: std::io::Read>(
+    ring_len: usize,
+    r: &mut R
+  ) -> std::io::Result<RingSignature> {
+    Ok(
+      RingSignature { sigs: read_raw_vec(|r| Ok((read_scalar(r)?, read_scalar(r)?)), ring_len, r)? }
+    )
+  }
+}
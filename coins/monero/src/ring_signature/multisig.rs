@@ -0,0 +1,187 @@
+use std::{io::{self, Read}, collections::BTreeMap};
+
+use rand_core::{RngCore, CryptoRng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  traits::Identity,
+  scalar::Scalar,
+  edwards::EdwardsPoint
+};
+
+use group::Group;
+
+use transcript::{Transcript, RecommendedTranscript};
+use frost::{curve::{Curve, Ed25519}, FrostError, MultisigView, algorithm::Algorithm};
+use dalek_ff_group as dfg;
+
+use crate::{frost::{DLEqProof, read_dleq}, ring_signature::RingSignature};
+
+// The key image share and H commitments, each with a DLEq proof
+const ADDENDUM_LEN: usize = 3 * (32 + 64);
+
+#[derive(Clone, PartialEq, Debug)]
+struct Interim {
+  c: Scalar,
+  sig: RingSignature
+}
+
+/// FROST algorithm for a ring signature, where the signing key is the multisig's
+/// As with CLSAG, the key image and the nonce's H commitments are summed from every participant's
+/// shares, each proven to have the same discrete log as its G counterpart
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct RingSignatureMultisig {
+  transcript: RecommendedTranscript,
+  ring: Vec<EdwardsPoint>,
+  i: usize,
+
+  H: EdwardsPoint,
+  image: EdwardsPoint,
+  AH: (dfg::EdwardsPoint, dfg::EdwardsPoint),
+
+  msg: Option<[u8; 32]>,
+  interim: Option<Interim>
+}
+
+impl RingSignatureMultisig {
+  /// Sign for the ring member at index i, which must be the key of the keys signing
+  pub fn new(
+    transcript: RecommendedTranscript,
+    ring: Vec<EdwardsPoint>,
+    i: usize
+  ) -> RingSignatureMultisig {
+    RingSignatureMultisig {
+      transcript,
+      ring,
+      i,
+
+      H: EdwardsPoint::identity(),
+      image: EdwardsPoint::identity(),
+      AH: (dfg::EdwardsPoint::identity(), dfg::EdwardsPoint::identity()),
+
+      msg: None,
+      interim: None
+    }
+  }
+}
+
+impl Algorithm<Ed25519> for RingSignatureMultisig {
+  type Transcript = RecommendedTranscript;
+  type Signature = (RingSignature, EdwardsPoint);
+
+  fn preprocess_addendum<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    view: &MultisigView<Ed25519>,
+    nonces: &[dfg::Scalar; 2]
+  ) -> Vec<u8> {
+    // Equivalent to Monero's hash_to_point, without calling into its C library
+    self.H = Ed25519::hash_to_G(b"", &view.group_key().0.compress().to_bytes()).0;
+
+    let mut serialized = Vec::with_capacity(ADDENDUM_LEN);
+    serialized.extend((view.secret_share().0 * self.H).compress().to_bytes());
+    serialized.extend(DLEqProof::prove(rng, &self.H, &view.secret_share().0).serialize());
+    for nonce in nonces {
+      serialized.extend((nonce.0 * self.H).compress().to_bytes());
+      serialized.extend(DLEqProof::prove(rng, &self.H, &nonce.0).serialize());
+    }
+    serialized
+  }
+
+  fn addendum_len() -> usize {
+    ADDENDUM_LEN
+  }
+
+  fn read_addendum<R: Read>(&self, reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut serialized = vec![0; ADDENDUM_LEN];
+    reader.read_exact(&mut serialized)?;
+    Ok(serialized)
+  }
+
+  fn process_addendum(
+    &mut self,
+    view: &MultisigView<Ed25519>,
+    l: u16,
+    commitments: &[dfg::EdwardsPoint; 2],
+    serialized: &[u8]
+  ) -> Result<(), FrostError> {
+    if serialized.len() != ADDENDUM_LEN {
+      Err(FrostError::InvalidCommitment(l))?;
+    }
+
+    if self.AH.0.is_identity().into() {
+      self.transcript.domain_separate(b"ring_signature");
+      self.transcript.append_message(
+        b"ring",
+        &self.ring.iter().flat_map(|key| key.compress().to_bytes()).collect::<Vec<_>>()
+      );
+      self.transcript.append_message(b"ring_index", &u64::try_from(self.i).unwrap().to_le_bytes());
+    }
+
+    let invalid = |_| FrostError::InvalidCommitment(l);
+    self.image += read_dleq(serialized, 0, &self.H, l, &view.verification_share(l).0)
+      .map_err(invalid)?.0;
+    self.AH.0 += read_dleq(serialized, 96, &self.H, l, &commitments[0]).map_err(invalid)?;
+    self.AH.1 += read_dleq(serialized, 192, &self.H, l, &commitments[1]).map_err(invalid)?;
+    Ok(())
+  }
+
+  fn transcript(&mut self) -> &mut Self::Transcript {
+    &mut self.transcript
+  }
+
+  fn sign_share(
+    &mut self,
+    view: &MultisigView<Ed25519>,
+    _: &BTreeMap<u16, [dfg::EdwardsPoint; 2]>,
+    nonce_sum: dfg::EdwardsPoint,
+    b: dfg::Scalar,
+    nonce: dfg::Scalar,
+    msg: &[u8]
+  ) -> dfg::Scalar {
+    // Apply the binding factor to the H variant of the nonce
+    self.AH.0 += self.AH.1 * b;
+
+    // The decoys' responses are derived from the transcript, so every participant agrees on them
+    let mut rng = ChaCha12Rng::from_seed(self.transcript.rng_seed(b"decoy_responses"));
+    self.msg = Some(msg.try_into().expect("ring signature message should be 32-bytes"));
+
+    let (sig, c) = RingSignature::sign_core(
+      &mut rng,
+      self.msg.as_ref().unwrap(),
+      &self.ring,
+      &self.image,
+      self.i,
+      nonce_sum.0,
+      self.AH.0.0
+    );
+    self.interim = Some(Interim { c, sig });
+
+    dfg::Scalar(nonce.0 - (c * view.secret_share().0))
+  }
+
+  fn verify(
+    &self,
+    _: dfg::EdwardsPoint,
+    _: dfg::EdwardsPoint,
+    sum: dfg::Scalar
+  ) -> Option<Self::Signature> {
+    let mut sig = self.interim.as_ref().unwrap().sig.clone();
+    sig.sigs[self.i].1 = sum.0;
+    Some((sig, self.image)).filter(
+      |(sig, image)| sig.verify(self.msg.as_ref().unwrap(), &self.ring, image)
+    )
+  }
+
+  fn verify_share(
+    &self,
+    verification_share: dfg::EdwardsPoint,
+    nonce: dfg::EdwardsPoint,
+    share: dfg::Scalar,
+  ) -> bool {
+    let c = self.interim.as_ref().unwrap().c;
+    (&share.0 * &ED25519_BASEPOINT_TABLE) == (nonce.0 - (c * verification_share.0))
+  }
+}
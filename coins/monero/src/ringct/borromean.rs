@@ -0,0 +1,94 @@
+#![allow(non_snake_case)]
+
+use lazy_static::lazy_static;
+
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{H, hash_to_scalar, serialize::*};
+
+lazy_static! {
+  // 2^i * H, the value each bit commitment commits to if its bit is set
+  static ref H_POW_2: [EdwardsPoint; 64] = {
+    let mut res = [*H; 64];
+    for i in 1 .. 64 {
+      res[i] = res[i - 1] + res[i - 1];
+    }
+    res
+  };
+}
+
+/// A Borromean ring signature over 64 rings of 2 members
+#[derive(Clone, PartialEq, Debug)]
+pub struct BorromeanSignature {
+  pub s0: [Scalar; 64],
+  pub s1: [Scalar; 64],
+  pub ee: Scalar
+}
+
+impl BorromeanSignature {
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+    write_raw_vec(write_scalar, &self.s0, w)?;
+    write_raw_vec(write_scalar, &self.s1, w)?;
+    write_scalar(&self.ee, w)
+  }
+
+  pub fn deserialize<R: std::io::Read>(r: &mut R) -> std::io::Result<BorromeanSignature> {
+    Ok(
+      BorromeanSignature {
+        s0: read_raw_vec(read_scalar, 64, r)?.try_into().unwrap(),
+        s1: read_raw_vec(read_scalar, 64, r)?.try_into().unwrap(),
+        ee: read_scalar(r)?
+      }
+    )
+  }
+
+  fn verify(&self, P1: &[EdwardsPoint; 64], P2: &[EdwardsPoint; 64]) -> bool {
+    let mut transcript = Vec::with_capacity(64 * 32);
+    for i in 0 .. 64 {
+      let LL = EdwardsPoint::vartime_double_scalar_mul_basepoint(&self.ee, &P1[i], &self.s0[i]);
+      let c = hash_to_scalar(&LL.compress().to_bytes());
+      let LV = EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, &P2[i], &self.s1[i]);
+      transcript.extend(LV.compress().to_bytes());
+    }
+    hash_to_scalar(&transcript) == self.ee
+  }
+}
+
+/// A Borromean range proof, proving a commitment is to a 64-bit amount
+/// Only used by transactions prior to Bulletproofs, and only supported for verification
+#[derive(Clone, PartialEq, Debug)]
+pub struct BorromeanRange {
+  pub sig: BorromeanSignature,
+  /// Commitments to each bit of the amount, which sum to the commitment being proven
+  pub bit_commitments: [EdwardsPoint; 64]
+}
+
+impl BorromeanRange {
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+    self.sig.serialize(w)?;
+    write_raw_vec(write_point, &self.bit_commitments, w)
+  }
+
+  pub fn deserialize<R: std::io::Read>(r: &mut R) -> std::io::Result<BorromeanRange> {
+    Ok(
+      BorromeanRange {
+        sig: BorromeanSignature::deserialize(r)?,
+        bit_commitments: read_raw_vec(read_point, 64, r)?.try_into().unwrap()
+      }
+    )
+  }
+
+  /// Verify this proves the specified commitment is to a 64-bit amount
+  pub fn verify(&self, commitment: &EdwardsPoint) -> bool {
+    if self.bit_commitments.iter().sum::<EdwardsPoint>() != *commitment {
+      return false;
+    }
+
+    // Each bit commitment is either to 0, so it's a multiple of G, or to 2^i, so it minus 2^i H is
+    let mut bit_commitments_sub_pow_2 = self.bit_commitments;
+    for i in 0 .. 64 {
+      bit_commitments_sub_pow_2[i] -= H_POW_2[i];
+    }
+    self.sig.verify(&self.bit_commitments, &bit_commitments_sub_pow_2)
+  }
+}
@@ -38,6 +38,9 @@ impl Bulletproofs {
     len + clawback
   }
 
+  /// Prove every output of a transaction within a single aggregate proof
+  /// The commitments must be in the same order as the transaction's outputs. Padding to a power
+  /// of two is handled by Monero's prover, as accounted for by fee_weight
   pub fn new<R: RngCore + CryptoRng>(rng: &mut R, outputs: &[Commitment]) -> Result<Bulletproofs, TransactionError> {
     if outputs.len() > MAX_OUTPUTS {
       return Err(TransactionError::TooManyOutputs)?;
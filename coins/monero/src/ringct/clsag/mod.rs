@@ -224,7 +224,9 @@ impl Clsag {
     )
   }
 
-  // Single signer CLSAG
+  /// Single signer CLSAG, signing every input with its private key and key image
+  /// sum_outputs is the sum of the output commitments' masks, which the pseudo-outs' masks are
+  /// chosen to sum to. Returns each input's CLSAG and pseudo-out, in order
   pub fn sign<R: RngCore + CryptoRng>(
     rng: &mut R,
     inputs: &[(Scalar, EdwardsPoint, ClsagInput)],
@@ -232,8 +234,6 @@ impl Clsag {
     msg: [u8; 32]
   ) -> Vec<(Clsag, EdwardsPoint)> {
     let nonce = random_scalar(rng);
-    let mut rand_source = [0; 64];
-    rng.fill_bytes(&mut rand_source);
 
     let mut res = Vec::with_capacity(inputs.len());
     let mut sum_pseudo_outs = Scalar::zero();
@@ -245,8 +245,6 @@ impl Clsag {
         sum_pseudo_outs += mask;
       }
 
-      let mut rand_source = [0; 64];
-      rng.fill_bytes(&mut rand_source);
       let (mut clsag, pseudo_out, p, c) = Clsag::sign_core(
         rng,
         &inputs[i].1,
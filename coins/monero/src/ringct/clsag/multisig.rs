@@ -1,5 +1,5 @@
 use core::fmt::Debug;
-use std::sync::{Arc, RwLock};
+use std::{io::{self, Read}, sync::{Arc, RwLock}, collections::BTreeMap};
 
 use rand_core::{RngCore, CryptoRng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
@@ -14,11 +14,10 @@ use curve25519_dalek::{
 use group::Group;
 
 use transcript::{Transcript, RecommendedTranscript};
-use frost::{curve::Ed25519, FrostError, MultisigView, algorithm::Algorithm};
+use frost::{curve::{Curve, Ed25519}, FrostError, MultisigView, algorithm::Algorithm};
 use dalek_ff_group as dfg;
 
 use crate::{
-  hash_to_point,
   frost::{MultisigError, DLEqProof, read_dleq},
   ringct::clsag::{ClsagInput, Clsag}
 };
@@ -129,7 +128,8 @@ impl Algorithm<Ed25519> for ClsagMultisig {
     view: &MultisigView<Ed25519>,
     nonces: &[dfg::Scalar; 2]
   ) -> Vec<u8> {
-    self.H = hash_to_point(&view.group_key().0);
+    // Equivalent to Monero's hash_to_point, without calling into its C library
+    self.H = Ed25519::hash_to_G(b"", &view.group_key().0.compress().to_bytes()).0;
 
     let mut serialized = Vec::with_capacity(ClsagMultisig::serialized_len());
     serialized.extend((view.secret_share().0 * self.H).compress().to_bytes());
@@ -142,6 +142,16 @@ impl Algorithm<Ed25519> for ClsagMultisig {
     serialized
   }
 
+  fn addendum_len() -> usize {
+    ClsagMultisig::serialized_len()
+  }
+
+  fn read_addendum<R: Read>(&self, reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut serialized = vec![0; ClsagMultisig::serialized_len()];
+    reader.read_exact(&mut serialized)?;
+    Ok(serialized)
+  }
+
   fn process_addendum(
     &mut self,
     view: &MultisigView<Ed25519>,
@@ -160,13 +170,9 @@ impl Algorithm<Ed25519> for ClsagMultisig {
       self.transcript.append_message(b"mask", &self.mask().to_bytes());
     }
 
-    // Uses the same format FROST does for the expected commitments (nonce * G where this is nonce * H)
-    // The following technically shouldn't need to be committed to, as we've committed to equivalents,
-    // yet it doesn't hurt and may resolve some unknown issues
-    self.transcript.append_message(b"participant", &l.to_be_bytes());
-
+    // FROST already appended the addendum, with the image share and H commitments, to the
+    // transcript, so they don't need to be committed to here
     let mut cursor = 0;
-    self.transcript.append_message(b"image_share", &serialized[cursor .. (cursor + 32)]);
     self.image += read_dleq(
       serialized,
       cursor,
@@ -176,11 +182,9 @@ impl Algorithm<Ed25519> for ClsagMultisig {
     ).map_err(|_| FrostError::InvalidCommitment(l))?.0;
     cursor += 96;
 
-    self.transcript.append_message(b"commitment_D_H", &serialized[cursor .. (cursor + 32)]);
     self.AH.0 += read_dleq(serialized, cursor, &self.H, l, &commitments[0]).map_err(|_| FrostError::InvalidCommitment(l))?;
     cursor += 96;
 
-    self.transcript.append_message(b"commitment_E_H", &serialized[cursor .. (cursor + 32)]);
     self.AH.1 += read_dleq(serialized, cursor, &self.H, l, &commitments[1]).map_err(|_| FrostError::InvalidCommitment(l))?;
 
     Ok(())
@@ -193,6 +197,7 @@ impl Algorithm<Ed25519> for ClsagMultisig {
   fn sign_share(
     &mut self,
     view: &MultisigView<Ed25519>,
+    _: &BTreeMap<u16, [dfg::EdwardsPoint; 2]>,
     nonce_sum: dfg::EdwardsPoint,
     b: dfg::Scalar,
     nonce: dfg::Scalar,
@@ -0,0 +1,157 @@
+#![allow(non_snake_case)]
+
+use thiserror::Error;
+
+use curve25519_dalek::{
+  traits::Identity,
+  scalar::Scalar,
+  edwards::EdwardsPoint
+};
+
+use crate::{H, hash_to_scalar, hash_to_point, serialize::*};
+
+#[derive(Clone, Error, Debug)]
+pub enum MlsagError {
+  #[error("invalid ring")]
+  InvalidRing,
+  #[error("invalid amount of key images")]
+  InvalidAmountOfKeyImages,
+  #[error("invalid key image")]
+  InvalidKeyImage,
+  #[error("invalid ss")]
+  InvalidSs,
+  #[error("invalid cc")]
+  InvalidCc
+}
+
+/// A MLSAG signature, as used by RingCT transactions prior to CLSAG
+/// Only supported for verification
+#[derive(Clone, PartialEq, Debug)]
+pub struct Mlsag {
+  /// Responses, indexed by ring member and then by key
+  pub ss: Vec<Vec<Scalar>>,
+  pub cc: Scalar
+}
+
+impl Mlsag {
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+    for ss in &self.ss {
+      write_raw_vec(write_scalar, ss, w)?;
+    }
+    write_scalar(&self.cc, w)
+  }
+
+  /// Deserialize a MLSAG for a ring of the specified size, where each member has the specified
+  /// amount of keys
+  pub fn deserialize<R: std::io::Read>(
+    decoys: usize,
+    keys: usize,
+    r: &mut R
+  ) -> std::io::Result<Mlsag> {
+    Ok(
+      Mlsag {
+        ss: (0 .. decoys).map(|_| read_raw_vec(read_scalar, keys, r)).collect::<Result<_, _>>()?,
+        cc: read_scalar(r)?
+      }
+    )
+  }
+
+  /// Verify a MLSAG over a matrix of keys, indexed by ring member and then by key
+  /// The first key_images.len() keys of the signer's row are linked by the key images, while the
+  /// rest are solely proven to be known
+  pub fn verify(
+    &self,
+    msg: &[u8; 32],
+    ring: &[Vec<EdwardsPoint>],
+    key_images: &[EdwardsPoint]
+  ) -> Result<(), MlsagError> {
+    if ring.is_empty() || (ring.len() != self.ss.len()) {
+      Err(MlsagError::InvalidRing)?;
+    }
+    let keys = ring[0].len();
+    if key_images.is_empty() || (keys < key_images.len()) {
+      Err(MlsagError::InvalidAmountOfKeyImages)?;
+    }
+    for (member, ss) in ring.iter().zip(&self.ss) {
+      if member.len() != keys {
+        Err(MlsagError::InvalidRing)?;
+      }
+      if ss.len() != keys {
+        Err(MlsagError::InvalidSs)?;
+      }
+    }
+    for I in key_images {
+      if (*I == EdwardsPoint::identity()) || (!I.is_torsion_free()) {
+        Err(MlsagError::InvalidKeyImage)?;
+      }
+    }
+
+    let mut c = self.cc;
+    let mut to_hash = Vec::with_capacity(32 * (1 + (3 * key_images.len()) + (2 * keys)));
+    for (member, ss) in ring.iter().zip(&self.ss) {
+      to_hash.clear();
+      to_hash.extend(msg);
+      for (k, (key, s)) in member.iter().zip(ss).enumerate() {
+        to_hash.extend(key.compress().to_bytes());
+        to_hash.extend(
+          EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, key, s).compress().to_bytes()
+        );
+        if let Some(I) = key_images.get(k) {
+          to_hash.extend(((s * hash_to_point(key)) + (c * I)).compress().to_bytes());
+        }
+      }
+      c = hash_to_scalar(&to_hash);
+    }
+
+    if c != self.cc {
+      Err(MlsagError::InvalidCc)?;
+    }
+    Ok(())
+  }
+
+  /// Verify a MLSAG for a single input, as used by RctTypeSimple and the Bulletproofs RctTypes
+  /// ring is the input's ring of [key, commitment] pairs
+  pub fn verify_simple(
+    &self,
+    msg: &[u8; 32],
+    ring: &[[EdwardsPoint; 2]],
+    I: &EdwardsPoint,
+    pseudo_out: &EdwardsPoint
+  ) -> Result<(), MlsagError> {
+    self.verify(
+      msg,
+      &ring.iter().map(|member| vec![member[0], member[1] - pseudo_out]).collect::<Vec<_>>(),
+      &[*I]
+    )
+  }
+
+  /// Verify the single MLSAG of a RctTypeFull transaction, signing for every input at once
+  /// rings is each input's ring of [key, commitment] pairs, with the real spends all sharing an
+  /// index. The commitments are proven to balance against the outputs and the fee
+  pub fn verify_full(
+    &self,
+    msg: &[u8; 32],
+    rings: &[Vec<[EdwardsPoint; 2]>],
+    key_images: &[EdwardsPoint],
+    outputs: &[EdwardsPoint],
+    fee: u64
+  ) -> Result<(), MlsagError> {
+    if rings.is_empty() || rings.iter().any(|ring| ring.len() != rings[0].len()) {
+      Err(MlsagError::InvalidRing)?;
+    }
+    if rings.len() != key_images.len() {
+      Err(MlsagError::InvalidAmountOfKeyImages)?;
+    }
+
+    let outputs = outputs.iter().sum::<EdwardsPoint>() + (Scalar::from(fee) * *H);
+    self.verify(
+      msg,
+      &(0 .. rings[0].len()).map(|m| {
+        let mut member = rings.iter().map(|ring| ring[m][0]).collect::<Vec<_>>();
+        member.push(rings.iter().map(|ring| ring[m][1]).sum::<EdwardsPoint>() - outputs);
+        member
+      }).collect::<Vec<_>>(),
+      key_images
+    )
+  }
+}
@@ -1,17 +1,56 @@
 use curve25519_dalek::edwards::EdwardsPoint;
 
+pub mod borromean;
+pub mod mlsag;
 pub mod bulletproofs;
 pub mod clsag;
 
 use crate::{
   serialize::*,
-  ringct::{clsag::Clsag, bulletproofs::Bulletproofs}
+  ringct::{borromean::BorromeanRange, mlsag::Mlsag, clsag::Clsag, bulletproofs::Bulletproofs}
 };
 
+/// An output's amount, encrypted to its recipient
+#[derive(Clone, PartialEq, Debug)]
+pub enum EncryptedAmount {
+  /// Used prior to RctTypeBulletproof2, where the mask was also encrypted instead of derived
+  Original { mask: [u8; 32], amount: [u8; 32] },
+  Compact { amount: [u8; 8] }
+}
+
+impl EncryptedAmount {
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+    match self {
+      EncryptedAmount::Original { mask, amount } => {
+        w.write_all(mask)?;
+        w.write_all(amount)
+      },
+      EncryptedAmount::Compact { amount } => w.write_all(amount)
+    }
+  }
+
+  pub fn deserialize<R: std::io::Read>(
+    rct_type: u8,
+    r: &mut R
+  ) -> std::io::Result<EncryptedAmount> {
+    Ok(
+      if rct_type <= 3 {
+        EncryptedAmount::Original { mask: read_32(r)?, amount: read_32(r)? }
+      } else {
+        let mut amount = [0; 8];
+        r.read_exact(&mut amount)?;
+        EncryptedAmount::Compact { amount }
+      }
+    )
+  }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct RctBase {
   pub fee: u64,
-  pub ecdh_info: Vec<[u8; 8]>,
+  /// Only present for RctTypeSimple, as later types moved these to the prunable data
+  pub pseudo_outs: Vec<EdwardsPoint>,
+  pub ecdh_info: Vec<EncryptedAmount>,
   pub commitments: Vec<EdwardsPoint>
 }
 
@@ -24,33 +63,38 @@ impl RctBase {
     w.write_all(&[rct_type])?;
     match rct_type {
       0 => Ok(()),
-      5 => {
+      1 ..= 5 => {
         write_varint(&self.fee, w)?;
-        for ecdh in &self.ecdh_info {
-          w.write_all(ecdh)?;
+        if rct_type == 2 {
+          write_raw_vec(write_point, &self.pseudo_outs, w)?;
         }
+        write_raw_vec(EncryptedAmount::serialize, &self.ecdh_info, w)?;
         write_raw_vec(write_point, &self.commitments, w)
       },
       _ => panic!("Serializing unknown RctType's Base")
     }
   }
 
-  pub fn deserialize<R: std::io::Read>(outputs: usize, r: &mut R) -> std::io::Result<(RctBase, u8)> {
+  pub fn deserialize<R: std::io::Read>(
+    inputs: usize,
+    outputs: usize,
+    r: &mut R
+  ) -> std::io::Result<(RctBase, u8)> {
     let mut rct_type = [0];
     r.read_exact(&mut rct_type)?;
+    let rct_type = rct_type[0];
     Ok((
-      if rct_type[0] == 0 {
-        RctBase { fee: 0, ecdh_info: vec![], commitments: vec![] }
-      } else {
-        RctBase {
+      match rct_type {
+        0 => RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+        1 ..= 5 => RctBase {
           fee: read_varint(r)?,
-          ecdh_info: (0 .. outputs).map(
-            |_| { let mut ecdh = [0; 8]; r.read_exact(&mut ecdh).map(|_| ecdh) }
-          ).collect::<Result<_, _>>()?,
+          pseudo_outs: if rct_type == 2 { read_raw_vec(read_point, inputs, r)? } else { vec![] },
+          ecdh_info: read_raw_vec(|r| EncryptedAmount::deserialize(rct_type, r), outputs, r)?,
           commitments: read_raw_vec(read_point, outputs, r)?
-        }
+        },
+        _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "Tried to deserialize unknown RCT type"))?
       },
-      rct_type[0]
+      rct_type
     ))
   }
 }
@@ -58,6 +102,17 @@ impl RctBase {
 #[derive(Clone, PartialEq, Debug)]
 pub enum RctPrunable {
   Null,
+  /// RctTypeFull, with a single MLSAG for every input, and RctTypeSimple, with one per input
+  MlsagBorromean {
+    borromean: Vec<BorromeanRange>,
+    mlsags: Vec<Mlsag>
+  },
+  /// RctTypeBulletproof and RctTypeBulletproof2
+  MlsagBulletproofs {
+    bulletproofs: Vec<Bulletproofs>,
+    mlsags: Vec<Mlsag>,
+    pseudo_outs: Vec<EdwardsPoint>
+  },
   Clsag {
     bulletproofs: Vec<Bulletproofs>,
     clsags: Vec<Clsag>,
@@ -66,20 +121,28 @@ pub enum RctPrunable {
 }
 
 impl RctPrunable {
-  pub fn rct_type(&self) -> u8 {
-    match self {
-      RctPrunable::Null => 0,
-      RctPrunable::Clsag { .. } => 5
-    }
-  }
-
   pub(crate) fn fee_weight(inputs: usize, outputs: usize) -> usize {
     1 + Bulletproofs::fee_weight(outputs) + (inputs * (Clsag::fee_weight() + 32))
   }
 
-  pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+  pub fn serialize<W: std::io::Write>(&self, w: &mut W, rct_type: u8) -> std::io::Result<()> {
     match self {
       RctPrunable::Null => Ok(()),
+      RctPrunable::MlsagBorromean { borromean, mlsags } => {
+        write_raw_vec(BorromeanRange::serialize, borromean, w)?;
+        write_raw_vec(Mlsag::serialize, mlsags, w)
+      },
+      RctPrunable::MlsagBulletproofs { bulletproofs, mlsags, pseudo_outs } => {
+        // RctTypeBulletproof used a fixed-width length
+        if rct_type == 3 {
+          w.write_all(&u32::try_from(bulletproofs.len()).unwrap().to_le_bytes())?;
+          write_raw_vec(Bulletproofs::serialize, bulletproofs, w)?;
+        } else {
+          write_vec(Bulletproofs::serialize, bulletproofs, w)?;
+        }
+        write_raw_vec(Mlsag::serialize, mlsags, w)?;
+        write_raw_vec(write_point, pseudo_outs, w)
+      },
       RctPrunable::Clsag { bulletproofs, clsags, pseudo_outs } => {
         write_vec(Bulletproofs::serialize, &bulletproofs, w)?;
         write_raw_vec(Clsag::serialize, &clsags, w)?;
@@ -91,11 +154,40 @@ impl RctPrunable {
   pub fn deserialize<R: std::io::Read>(
     rct_type: u8,
     decoys: &[usize],
+    outputs: usize,
     r: &mut R
   ) -> std::io::Result<RctPrunable> {
+    let simple_mlsags = |r: &mut R| decoys.iter().map(
+      |decoys| Mlsag::deserialize(*decoys, 2, r)
+    ).collect::<Result<_, _>>();
+
     Ok(
       match rct_type {
         0 => RctPrunable::Null,
+        1 | 2 => RctPrunable::MlsagBorromean {
+          borromean: read_raw_vec(BorromeanRange::deserialize, outputs, r)?,
+          mlsags: if rct_type == 1 {
+            // Every input's ring is the same size, and every input is signed for at once, along
+            // with the commitments' balance
+            let ring_len = *decoys.first().ok_or(
+              std::io::Error::new(std::io::ErrorKind::Other, "RctTypeFull without inputs")
+            )?;
+            vec![Mlsag::deserialize(ring_len, decoys.len() + 1, r)?]
+          } else {
+            simple_mlsags(r)?
+          }
+        },
+        3 | 4 => RctPrunable::MlsagBulletproofs {
+          bulletproofs: if rct_type == 3 {
+            let mut len = [0; 4];
+            r.read_exact(&mut len)?;
+            read_raw_vec(Bulletproofs::deserialize, u32::from_le_bytes(len).try_into().unwrap(), r)?
+          } else {
+            read_vec(Bulletproofs::deserialize, r)?
+          },
+          mlsags: simple_mlsags(r)?,
+          pseudo_outs: read_raw_vec(read_point, decoys.len(), r)?
+        },
         5 => RctPrunable::Clsag {
           // TODO: Can the amount of outputs be calculated from the BPs for any validly formed TX?
           bulletproofs: read_vec(Bulletproofs::deserialize, r)?,
@@ -110,7 +202,11 @@ impl RctPrunable {
   pub fn signature_serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
     match self {
       RctPrunable::Null => panic!("Serializing RctPrunable::Null for a signature"),
-      RctPrunable::Clsag { bulletproofs, .. } => bulletproofs.iter().map(|bp| bp.signature_serialize(w)).collect(),
+      RctPrunable::MlsagBorromean { borromean, .. } => {
+        borromean.iter().map(|range| range.serialize(w)).collect()
+      },
+      RctPrunable::MlsagBulletproofs { bulletproofs, .. } |
+        RctPrunable::Clsag { bulletproofs, .. } => bulletproofs.iter().map(|bp| bp.signature_serialize(w)).collect(),
     }
   }
 }
@@ -122,17 +218,36 @@ pub struct RctSignatures {
 }
 
 impl RctSignatures {
+  /// The RctType, which is only fully determined by the base and prunable data together
+  pub fn rct_type(&self) -> u8 {
+    match self.prunable {
+      RctPrunable::Null => 0,
+      RctPrunable::MlsagBorromean { .. } => if self.base.pseudo_outs.is_empty() { 1 } else { 2 },
+      RctPrunable::MlsagBulletproofs { .. } => match self.base.ecdh_info.first() {
+        Some(EncryptedAmount::Original { .. }) => 3,
+        _ => 4
+      },
+      RctPrunable::Clsag { .. } => 5
+    }
+  }
+
   pub(crate) fn fee_weight(inputs: usize, outputs: usize) -> usize {
     RctBase::fee_weight(outputs) + RctPrunable::fee_weight(inputs, outputs)
   }
 
   pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
-    self.base.serialize(w, self.prunable.rct_type())?;
-    self.prunable.serialize(w)
+    let rct_type = self.rct_type();
+    self.base.serialize(w, rct_type)?;
+    self.prunable.serialize(w, rct_type)
   }
 
   pub fn deserialize<R: std::io::Read>(decoys: Vec<usize>, outputs: usize, r: &mut R) -> std::io::Result<RctSignatures> {
-    let base = RctBase::deserialize(outputs, r)?;
-    Ok(RctSignatures { base: base.0, prunable: RctPrunable::deserialize(base.1, &decoys, r)? })
+    let base = RctBase::deserialize(decoys.len(), outputs, r)?;
+    Ok(
+      RctSignatures {
+        base: base.0,
+        prunable: RctPrunable::deserialize(base.1, &decoys, outputs, r)?
+      }
+    )
   }
 }
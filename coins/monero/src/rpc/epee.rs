@@ -0,0 +1,325 @@
+// epee's portable storage, the binary format used by the daemon's .bin endpoints
+// Values are typed and self-describing, with objects being lists of named fields
+
+use std::io::{self, Read};
+
+use crate::serialize::read_byte;
+
+const SIGNATURE_A: u32 = 0x01011101;
+const SIGNATURE_B: u32 = 0x01020101;
+const VERSION: u8 = 1;
+
+const ARRAY_FLAG: u8 = 0x80;
+
+// Matches the daemon's limit, preventing a malicious response from overflowing the stack
+const MAX_DEPTH: usize = 100;
+
+fn err<T>(msg: &str) -> io::Result<T> {
+  Err(io::Error::new(io::ErrorKind::Other, msg.to_string()))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Type {
+  I64 = 1,
+  I32 = 2,
+  I16 = 3,
+  I8 = 4,
+  U64 = 5,
+  U32 = 6,
+  U16 = 7,
+  U8 = 8,
+  F64 = 9,
+  String = 10,
+  Bool = 11,
+  Object = 12
+}
+
+impl Type {
+  fn from_byte(byte: u8) -> io::Result<Type> {
+    Ok(
+      match byte {
+        1 => Type::I64,
+        2 => Type::I32,
+        3 => Type::I16,
+        4 => Type::I8,
+        5 => Type::U64,
+        6 => Type::U32,
+        7 => Type::U16,
+        8 => Type::U8,
+        9 => Type::F64,
+        10 => Type::String,
+        11 => Type::Bool,
+        12 => Type::Object,
+        // Includes nested arrays, which the daemon never uses
+        _ => err("unknown epee type")?
+      }
+    )
+  }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+  I64(i64),
+  I32(i32),
+  I16(i16),
+  I8(i8),
+  U64(u64),
+  U32(u32),
+  U16(u16),
+  U8(u8),
+  F64(f64),
+  // Arbitrary bytes, used for both text and binary blobs
+  String(Vec<u8>),
+  Bool(bool),
+  Object(Section),
+  // Arrays are homogeneous, with their type specified even when empty
+  Array(Type, Vec<Value>)
+}
+
+impl Value {
+  fn kind(&self) -> Option<Type> {
+    Some(
+      match self {
+        Value::I64(_) => Type::I64,
+        Value::I32(_) => Type::I32,
+        Value::I16(_) => Type::I16,
+        Value::I8(_) => Type::I8,
+        Value::U64(_) => Type::U64,
+        Value::U32(_) => Type::U32,
+        Value::U16(_) => Type::U16,
+        Value::U8(_) => Type::U8,
+        Value::F64(_) => Type::F64,
+        Value::String(_) => Type::String,
+        Value::Bool(_) => Type::Bool,
+        Value::Object(_) => Type::Object,
+        Value::Array(..) => None?
+      }
+    )
+  }
+
+  /// The value as a u64, accepting any unsigned integer type as the daemon may use any width
+  pub fn as_u64(&self) -> Option<u64> {
+    match self {
+      Value::U64(value) => Some(*value),
+      Value::U32(value) => Some((*value).into()),
+      Value::U16(value) => Some((*value).into()),
+      Value::U8(value) => Some((*value).into()),
+      _ => None
+    }
+  }
+
+  pub fn as_bool(&self) -> Option<bool> {
+    match self {
+      Value::Bool(value) => Some(*value),
+      _ => None
+    }
+  }
+
+  pub fn as_bytes(&self) -> Option<&[u8]> {
+    match self {
+      Value::String(value) => Some(value),
+      _ => None
+    }
+  }
+
+  pub fn as_object(&self) -> Option<&Section> {
+    match self {
+      Value::Object(value) => Some(value),
+      _ => None
+    }
+  }
+
+  pub fn as_array(&self) -> Option<&[Value]> {
+    match self {
+      Value::Array(_, values) => Some(values),
+      _ => None
+    }
+  }
+
+  fn serialize<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    match self {
+      Value::I64(value) => w.write_all(&value.to_le_bytes()),
+      Value::I32(value) => w.write_all(&value.to_le_bytes()),
+      Value::I16(value) => w.write_all(&value.to_le_bytes()),
+      Value::I8(value) => w.write_all(&value.to_le_bytes()),
+      Value::U64(value) => w.write_all(&value.to_le_bytes()),
+      Value::U32(value) => w.write_all(&value.to_le_bytes()),
+      Value::U16(value) => w.write_all(&value.to_le_bytes()),
+      Value::U8(value) => w.write_all(&[*value]),
+      Value::F64(value) => w.write_all(&value.to_le_bytes()),
+      Value::String(value) => {
+        write_varint(value.len(), w)?;
+        w.write_all(value)
+      },
+      Value::Bool(value) => w.write_all(&[u8::from(*value)]),
+      Value::Object(section) => section.serialize_fields(w),
+      Value::Array(kind, values) => {
+        write_varint(values.len(), w)?;
+        for value in values {
+          if value.kind() != Some(*kind) {
+            err("epee array has an element of a different type")?;
+          }
+          value.serialize(w)?;
+        }
+        Ok(())
+      }
+    }
+  }
+
+  // Writes the value's type before the value itself, as done for fields
+  fn serialize_typed<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[
+      match self {
+        Value::Array(kind, _) => (*kind as u8) | ARRAY_FLAG,
+        _ => self.kind().unwrap() as u8
+      }
+    ])?;
+    self.serialize(w)
+  }
+
+  fn deserialize<R: io::Read>(kind: Type, depth: usize, r: &mut R) -> io::Result<Value> {
+    fn read<R: io::Read, const N: usize>(r: &mut R) -> io::Result<[u8; N]> {
+      let mut res = [0; N];
+      r.read_exact(&mut res)?;
+      Ok(res)
+    }
+
+    Ok(
+      match kind {
+        Type::I64 => Value::I64(i64::from_le_bytes(read(r)?)),
+        Type::I32 => Value::I32(i32::from_le_bytes(read(r)?)),
+        Type::I16 => Value::I16(i16::from_le_bytes(read(r)?)),
+        Type::I8 => Value::I8(i8::from_le_bytes(read(r)?)),
+        Type::U64 => Value::U64(u64::from_le_bytes(read(r)?)),
+        Type::U32 => Value::U32(u32::from_le_bytes(read(r)?)),
+        Type::U16 => Value::U16(u16::from_le_bytes(read(r)?)),
+        Type::U8 => Value::U8(read_byte(r)?),
+        Type::F64 => Value::F64(f64::from_le_bytes(read(r)?)),
+        Type::String => {
+          let len = read_varint(r)?;
+          let mut value = vec![];
+          // Don't allocate the claimed length upfront, as it's yet to be proven by the data
+          r.by_ref().take(len.try_into().unwrap()).read_to_end(&mut value)?;
+          if value.len() != len {
+            err("epee string was shorter than its length")?;
+          }
+          Value::String(value)
+        },
+        Type::Bool => Value::Bool(
+          match read_byte(r)? {
+            0 => false,
+            1 => true,
+            _ => err("invalid epee bool")?
+          }
+        ),
+        Type::Object => Value::Object(Section::deserialize_fields(depth + 1, r)?)
+      }
+    )
+  }
+
+  fn deserialize_typed<R: io::Read>(depth: usize, r: &mut R) -> io::Result<Value> {
+    if depth > MAX_DEPTH {
+      err("epee data was nested too deeply")?;
+    }
+
+    let byte = read_byte(r)?;
+    let kind = Type::from_byte(byte & (!ARRAY_FLAG))?;
+    if (byte & ARRAY_FLAG) == 0 {
+      return Value::deserialize(kind, depth, r);
+    }
+
+    let len = read_varint(r)?;
+    let mut values = vec![];
+    for _ in 0 .. len {
+      values.push(Value::deserialize(kind, depth, r)?);
+    }
+    Ok(Value::Array(kind, values))
+  }
+}
+
+// epee's varints store their width in their lowest two bits, and are distinct from Monero's
+fn write_varint<W: io::Write>(varint: usize, w: &mut W) -> io::Result<()> {
+  let varint = u64::try_from(varint).unwrap();
+  if varint < (1 << 6) {
+    w.write_all(&[u8::try_from(varint << 2).unwrap()])
+  } else if varint < (1 << 14) {
+    w.write_all(&(u16::try_from(varint << 2).unwrap() | 1).to_le_bytes())
+  } else if varint < (1 << 30) {
+    w.write_all(&(u32::try_from(varint << 2).unwrap() | 2).to_le_bytes())
+  } else if varint < (1 << 62) {
+    w.write_all(&((varint << 2) | 3).to_le_bytes())
+  } else {
+    err("value too large for an epee varint")
+  }
+}
+
+fn read_varint<R: io::Read>(r: &mut R) -> io::Result<usize> {
+  let first = read_byte(r)?;
+  let len = 1 << (first & 0b11);
+  let mut bytes = [0; 8];
+  bytes[0] = first;
+  r.read_exact(&mut bytes[1 .. len])?;
+  (u64::from_le_bytes(bytes) >> 2).try_into().or_else(|_| err("epee varint exceeded usize"))
+}
+
+/// An epee object, its fields kept in order
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Section(pub Vec<(String, Value)>);
+
+impl Section {
+  pub fn new() -> Section {
+    Section(vec![])
+  }
+
+  pub fn insert(mut self, name: &str, value: Value) -> Section {
+    self.0.push((name.to_string(), value));
+    self
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Value> {
+    self.0.iter().find(|field| field.0 == name).map(|field| &field.1)
+  }
+
+  fn serialize_fields<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(self.0.len(), w)?;
+    for (name, value) in &self.0 {
+      w.write_all(&[u8::try_from(name.len()).or_else(|_| err("epee field name was too long"))?])?;
+      w.write_all(name.as_bytes())?;
+      value.serialize_typed(w)?;
+    }
+    Ok(())
+  }
+
+  fn deserialize_fields<R: io::Read>(depth: usize, r: &mut R) -> io::Result<Section> {
+    let len = read_varint(r)?;
+    let mut fields = vec![];
+    for _ in 0 .. len {
+      let mut name = vec![0; usize::from(read_byte(r)?)];
+      r.read_exact(&mut name)?;
+      let name = String::from_utf8(name).or_else(|_| err("epee field name wasn't UTF-8"))?;
+      fields.push((name, Value::deserialize_typed(depth, r)?));
+    }
+    Ok(Section(fields))
+  }
+
+  /// Serialize as a complete epee document, with this section as the root object
+  pub fn serialize<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&SIGNATURE_A.to_le_bytes())?;
+    w.write_all(&SIGNATURE_B.to_le_bytes())?;
+    w.write_all(&[VERSION])?;
+    self.serialize_fields(w)
+  }
+
+  pub fn deserialize<R: io::Read>(r: &mut R) -> io::Result<Section> {
+    let mut header = [0; 9];
+    r.read_exact(&mut header)?;
+    if (header[.. 4] != SIGNATURE_A.to_le_bytes()) ||
+      (header[4 .. 8] != SIGNATURE_B.to_le_bytes()) {
+      err("invalid epee signature")?;
+    }
+    if header[8] != VERSION {
+      err("unsupported epee version")?;
+    }
+    Section::deserialize_fields(0, r)
+  }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use curve25519_dalek::{scalar::Scalar, edwards::{EdwardsPoint, CompressedEdwardsY}};
+
+use serde::{Deserialize, Deserializer, de::Error};
+use serde_json::json;
+
+use crate::{
+  generate_key_image,
+  transaction::Transaction,
+  wallet::{Address, SpendableOutput, Subaddresses},
+  rpc::{RpcError, Rpc}
+};
+
+// Light wallet servers return 64-bit values as either numbers or strings
+fn lws_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum U64 {
+    Number(u64),
+    String(String)
+  }
+
+  match U64::deserialize(deserializer)? {
+    U64::Number(value) => Ok(value),
+    U64::String(value) => value.parse().map_err(D::Error::custom)
+  }
+}
+
+fn lws_32(value: &str) -> Option<[u8; 32]> {
+  hex::decode(value).ok()?.try_into().ok()
+}
+
+fn lws_point(value: &str) -> Option<EdwardsPoint> {
+  CompressedEdwardsY(lws_32(value)?).decompress()
+}
+
+fn lws_hash<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+  lws_32(&String::deserialize(deserializer)?).ok_or_else(|| D::Error::custom("invalid hash"))
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Debug)]
+pub struct Login {
+  /// If the server created a new account for this address
+  pub new_address: bool,
+  /// Height the server started scanning from
+  pub start_height: Option<usize>
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Debug)]
+pub struct ImportRequest {
+  /// Fee the server charges to scan from the start of the chain, paid to payment_address
+  #[serde(deserialize_with = "lws_u64", default)]
+  pub import_fee: u64,
+  pub payment_address: Option<String>,
+  #[serde(default)]
+  pub new_request: bool,
+  #[serde(default)]
+  pub request_fulfilled: bool,
+  #[serde(default)]
+  pub status: String
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Debug)]
+pub struct AddressTransaction {
+  #[serde(deserialize_with = "lws_hash")]
+  pub hash: [u8; 32],
+  #[serde(deserialize_with = "lws_u64")]
+  pub total_received: u64,
+  #[serde(deserialize_with = "lws_u64")]
+  pub total_sent: u64,
+  #[serde(deserialize_with = "lws_u64", default)]
+  pub unlock_time: u64,
+  // Unconfirmed transactions don't have a height
+  pub height: Option<u64>,
+  #[serde(default)]
+  pub coinbase: bool,
+  #[serde(default)]
+  pub mempool: bool
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Debug)]
+pub struct AddressTransactions {
+  #[serde(deserialize_with = "lws_u64")]
+  pub total_received: u64,
+  pub scanned_height: u64,
+  pub scanned_block_height: u64,
+  pub start_height: u64,
+  pub blockchain_height: u64,
+  #[serde(default)]
+  pub transactions: Vec<AddressTransaction>
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct UnspentOutput {
+  index: u8,
+  tx_hash: String,
+  height: u64,
+  #[serde(default)]
+  spend_key_images: Vec<String>
+}
+
+/// An output returned by a light wallet server, verified to be spendable by this wallet
+#[derive(Clone, PartialEq, Debug)]
+pub struct LightWalletOutput {
+  pub output: SpendableOutput,
+  pub global_index: u64,
+  pub height: usize,
+  // Key images the server saw used, which may include this output's
+  key_images: Vec<EdwardsPoint>
+}
+
+impl LightWalletOutput {
+  /// If the server reported this output as spent, which requires the spend key as the server can't
+  /// tell for sure
+  /// The server may omit key images, so Rpc::is_key_image_spent should be used when this matters
+  pub fn spent(&self, spend: &Scalar) -> bool {
+    self.key_images.contains(&generate_key_image(&(spend + self.output.key_offset)))
+  }
+}
+
+impl UnspentOutput {
+  pub(crate) fn tx(&self) -> Option<[u8; 32]> {
+    lws_32(&self.tx_hash)
+  }
+
+  // The server is untrusted, so it's only used to locate outputs. They're then recovered by
+  // scanning the transaction, as fetched from a daemon, ignoring the server's keys and amounts
+  pub(crate) fn verify(
+    &self,
+    tx: &Transaction,
+    view: Scalar,
+    spend: EdwardsPoint,
+    subaddresses: &Subaddresses
+  ) -> Option<SpendableOutput> {
+    if Some(tx.hash()) != self.tx() {
+      None?;
+    }
+    tx.scan_subaddresses(view, spend, subaddresses)
+      .0
+      .into_iter()
+      .find(|output| output.o == self.index)
+  }
+}
+
+/// Client for the light wallet REST API, as implemented by MyMonero and monero-lws
+/// The server is given the view key, scanning on the wallet's behalf
+#[derive(Clone, Debug)]
+pub struct LightWallet {
+  rpc: Rpc,
+  address: Address,
+  view: Scalar
+}
+
+impl LightWallet {
+  pub fn new(server: String, address: Address, view: Scalar) -> LightWallet {
+    LightWallet { rpc: Rpc::new(server), address, view }
+  }
+
+  fn credentials(&self) -> serde_json::Value {
+    json!({ "address": self.address.to_string(), "view_key": hex::encode(self.view.to_bytes()) })
+  }
+
+  fn call_params(&self, params: serde_json::Value) -> serde_json::Value {
+    let mut credentials = self.credentials();
+    credentials.as_object_mut().unwrap().extend(params.as_object().unwrap().clone());
+    credentials
+  }
+
+  pub async fn login(&self, create_account: bool) -> Result<Login, RpcError> {
+    self.rpc.rpc_call("login", Some(self.call_params(json!({
+      "create_account": create_account,
+      "generated_locally": true
+    })))).await
+  }
+
+  pub async fn get_address_txs(&self) -> Result<AddressTransactions, RpcError> {
+    self.rpc.rpc_call("get_address_txs", Some(self.credentials())).await
+  }
+
+  /// Get the unspent outputs for this wallet, as would be found by scanning with
+  /// Transaction::scan_subaddresses
+  /// Each output's transaction, block, and global index are fetched from the daemon, so outputs the
+  /// server returns which don't verify are dropped
+  pub async fn get_unspent_outs(
+    &self,
+    daemon: &Rpc,
+    spend: EdwardsPoint,
+    subaddresses: &Subaddresses
+  ) -> Result<Vec<LightWalletOutput>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct UnspentOutputs {
+      #[serde(default)]
+      outputs: Vec<UnspentOutput>
+    }
+
+    let res: UnspentOutputs = self.rpc.rpc_call("get_unspent_outs", Some(self.call_params(json!({
+      "amount": "0",
+      "mixin": 0,
+      "use_dust": true,
+      "dust_threshold": "0"
+    })))).await?;
+
+    let mut hashes = res.outputs.iter().filter_map(UnspentOutput::tx).collect::<Vec<_>>();
+    hashes.sort();
+    hashes.dedup();
+    let txs = daemon.get_transactions_possible(&hashes).await?
+      .into_iter()
+      .map(|tx| (tx.hash(), tx))
+      .collect::<HashMap<_, _>>();
+
+    let mut outputs = vec![];
+    for out in &res.outputs {
+      let output = match out.tx().and_then(|hash| txs.get(&hash)).and_then(
+        |tx| out.verify(tx, self.view, spend, subaddresses)
+      ) {
+        Some(output) => output,
+        None => continue
+      };
+
+      // Check the transaction is in the block the server claims it is
+      let height = match usize::try_from(out.height) {
+        Ok(height) => height,
+        Err(_) => continue
+      };
+      let block = daemon.get_block(height).await?;
+      if (block.miner_tx.hash() != output.tx) && !block.txs.contains(&output.tx) {
+        continue;
+      }
+
+      let global_index = match daemon.get_o_indexes(output.tx).await?.get(usize::from(output.o)) {
+        Some(global_index) => *global_index,
+        None => continue
+      };
+
+      outputs.push(
+        LightWalletOutput {
+          output,
+          global_index,
+          height,
+          key_images: out.spend_key_images.iter().filter_map(|image| lws_point(image)).collect()
+        }
+      );
+    }
+    Ok(outputs)
+  }
+
+  /// Request the server scan from the start of the chain, instead of from the account's creation
+  pub async fn import_request(&self) -> Result<ImportRequest, RpcError> {
+    self.rpc.rpc_call("import_request", Some(self.credentials())).await
+  }
+}
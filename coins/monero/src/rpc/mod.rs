@@ -0,0 +1,692 @@
+use std::fmt::Debug;
+
+use thiserror::Error;
+
+use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
+
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use serde_json::json;
+
+use reqwest;
+
+use crate::{transaction::{Input, Transaction}, block::Block, wallet::{Fee, Priority}};
+
+pub mod epee;
+use epee::{Type, Value, Section};
+
+mod lws;
+#[cfg(test)]
+pub(crate) use lws::UnspentOutput;
+pub use lws::{
+  Login, ImportRequest, AddressTransaction, AddressTransactions, LightWalletOutput, LightWallet
+};
+
+mod zmq;
+pub use zmq::{PoolTransaction, Notification, ZmqSubscriber};
+
+mod pool;
+#[cfg(test)]
+pub(crate) use pool::healthy;
+pub use pool::RpcPool;
+
+// Amount of blocks whose timestamps are used to determine the chain's time
+const TIMESTAMP_WINDOW: usize = 60;
+
+#[derive(Deserialize, Debug)]
+pub struct EmptyResponse {}
+#[derive(Deserialize, Debug)]
+pub struct JsonRpcResponse<T> {
+  result: T
+}
+
+#[derive(Clone, Error, Debug)]
+pub enum RpcError {
+  #[error("internal error ({0})")]
+  InternalError(String),
+  #[error("connection error")]
+  ConnectionError,
+  #[error("transactions not found")]
+  TransactionsNotFound(Vec<[u8; 32]>),
+  #[error("invalid point ({0})")]
+  InvalidPoint(String),
+  #[error("pruned transaction")]
+  PrunedTransaction,
+  #[error("invalid transaction ({0:?})")]
+  InvalidTransaction([u8; 32]),
+  #[error("transaction rejected ({1:?})")]
+  TransactionRejected([u8; 32], Vec<Rejection>),
+  #[error("transaction wasn't relayed ({0:?})")]
+  TransactionNotRelayed([u8; 32]),
+  #[error("no healthy daemon")]
+  NoHealthyDaemon
+}
+
+/// Reason the daemon rejected a transaction
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Rejection {
+  DoubleSpend,
+  FeeTooLow,
+  InvalidInput,
+  InvalidOutput,
+  LowMixin,
+  Overspend,
+  TooBig,
+  TooFewOutputs,
+  SanityCheckFailed,
+  TxExtraTooBig,
+  NonzeroUnlockTime,
+  /// Rejected without any of the above being flagged, with the daemon's stated reason
+  Other(String)
+}
+
+// Older daemons don't return every flag
+#[derive(Deserialize, Debug)]
+pub(crate) struct SendRawResponse {
+  status: String,
+  #[serde(default)]
+  reason: String,
+  #[serde(default)]
+  not_relayed: bool,
+  #[serde(default)]
+  double_spend: bool,
+  #[serde(default)]
+  fee_too_low: bool,
+  #[serde(default)]
+  invalid_input: bool,
+  #[serde(default)]
+  invalid_output: bool,
+  #[serde(default)]
+  low_mixin: bool,
+  #[serde(default)]
+  overspend: bool,
+  #[serde(default)]
+  too_big: bool,
+  #[serde(default)]
+  too_few_outputs: bool,
+  #[serde(default)]
+  sanity_check_failed: bool,
+  #[serde(default)]
+  tx_extra_too_big: bool,
+  #[serde(default)]
+  nonzero_unlock_time: bool
+}
+
+impl SendRawResponse {
+  pub(crate) fn rejections(&self) -> Vec<Rejection> {
+    let mut res = [
+      (self.double_spend, Rejection::DoubleSpend),
+      (self.fee_too_low, Rejection::FeeTooLow),
+      (self.invalid_input, Rejection::InvalidInput),
+      (self.invalid_output, Rejection::InvalidOutput),
+      (self.low_mixin, Rejection::LowMixin),
+      (self.overspend, Rejection::Overspend),
+      (self.too_big, Rejection::TooBig),
+      (self.too_few_outputs, Rejection::TooFewOutputs),
+      (self.sanity_check_failed, Rejection::SanityCheckFailed),
+      (self.tx_extra_too_big, Rejection::TxExtraTooBig),
+      (self.nonzero_unlock_time, Rejection::NonzeroUnlockTime)
+    ].into_iter()
+      .filter(|(flagged, _)| *flagged)
+      .map(|(_, rejection)| rejection)
+      .collect::<Vec<_>>();
+
+    if res.is_empty() {
+      res.push(Rejection::Other(self.reason.clone()));
+    }
+    res
+  }
+}
+
+fn rpc_hex(value: &str) -> Result<Vec<u8>, RpcError> {
+  hex::decode(value).map_err(|_| RpcError::InternalError("Monero returned invalid hex".to_string()))
+}
+
+fn rpc_point(point: [u8; 32]) -> Result<EdwardsPoint, RpcError> {
+  CompressedEdwardsY(point).decompress().ok_or(RpcError::InvalidPoint(hex::encode(point)))
+}
+
+fn epee_invalid(field: &str) -> RpcError {
+  RpcError::InternalError(format!("Monero returned an invalid {}", field))
+}
+
+fn epee_field<'a>(section: &'a Section, field: &str) -> Result<&'a Value, RpcError> {
+  section.get(field)
+    .ok_or_else(|| RpcError::InternalError(format!("Monero didn't return {}", field)))
+}
+
+fn epee_u64(section: &Section, field: &str) -> Result<u64, RpcError> {
+  epee_field(section, field)?.as_u64().ok_or_else(|| epee_invalid(field))
+}
+
+fn epee_bytes<'a>(section: &'a Section, field: &str) -> Result<&'a [u8], RpcError> {
+  epee_field(section, field)?.as_bytes().ok_or_else(|| epee_invalid(field))
+}
+
+fn epee_32(section: &Section, field: &str) -> Result<[u8; 32], RpcError> {
+  epee_bytes(section, field)?.try_into().map_err(|_| epee_invalid(field))
+}
+
+// The daemon omits empty arrays entirely
+fn epee_array<'a>(section: &'a Section, field: &str) -> Result<&'a [Value], RpcError> {
+  section.get(field).map(
+    |array| array.as_array().ok_or_else(|| epee_invalid(field))
+  ).unwrap_or(Ok(&[]))
+}
+
+// Hashes are sent and received as a single string of their concatenation
+fn epee_hashes(hashes: &[[u8; 32]]) -> Value {
+  Value::String(hashes.concat())
+}
+
+/// A daemon's sync status and RPC version
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DaemonInfo {
+  pub height: usize,
+  /// Height of the network, as seen by the daemon. 0 if the daemon considers itself synced
+  pub target_height: usize,
+  pub synchronized: bool,
+  /// RPC version, with the major version in the upper 16 bits
+  pub version: u32
+}
+
+#[derive(Clone, Debug)]
+pub struct Rpc(String);
+
+impl Rpc {
+  pub fn new(daemon: String) -> Rpc {
+    Rpc(daemon)
+  }
+
+  pub async fn rpc_call<
+    Params: Serialize + Debug,
+    Response: DeserializeOwned + Debug
+  >(&self, method: &str, params: Option<Params>) -> Result<Response, RpcError> {
+    let client = reqwest::Client::new();
+    let mut builder = client.post(&(self.0.clone() + "/" + method));
+    if let Some(params) = params.as_ref() {
+      builder = builder.json(params);
+    }
+
+    let res = builder.send().await.map_err(|_| RpcError::ConnectionError)?;
+    serde_json::from_str(&res.text().await.map_err(|_| RpcError::ConnectionError)?)
+      .map_err(|_| RpcError::InternalError("Failed to parse JSON response".to_string()))
+  }
+
+  pub async fn bin_call(&self, method: &str, params: Section) -> Result<Section, RpcError> {
+    let mut body = vec![];
+    params.serialize(&mut body)
+      .map_err(|_| RpcError::InternalError("Failed to serialize request".to_string()))?;
+
+    let client = reqwest::Client::new();
+    let res = client.post(&(self.0.clone() + "/" + method))
+      .body(body)
+      .header("Content-Type", "application/octet-stream")
+      .send()
+      .await
+      .map_err(|_| RpcError::ConnectionError)?;
+
+    let res = Section::deserialize(
+      &mut res.bytes().await.map_err(|_| RpcError::ConnectionError)?.as_ref()
+    ).map_err(|_| RpcError::InternalError("Failed to parse binary response".to_string()))?;
+
+    if let Some(status) = res.get("status").and_then(Value::as_bytes) {
+      if status != b"OK" {
+        Err(
+          RpcError::InternalError(
+            format!("Monero returned status {}", String::from_utf8_lossy(status))
+          )
+        )?;
+      }
+    }
+    Ok(res)
+  }
+
+  pub async fn get_height(&self) -> Result<usize, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct HeightResponse {
+      height: usize
+    }
+    Ok(self.rpc_call::<Option<()>, HeightResponse>("get_height", None).await?.height)
+  }
+
+  /// Get the daemon's sync status and version, as needed to judge if it can be relied on
+  pub async fn get_info(&self) -> Result<DaemonInfo, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct InfoResponse {
+      height: usize,
+      target_height: usize,
+      // Older daemons don't specify if they're synchronized
+      synchronized: Option<bool>
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct VersionResponse {
+      version: u32
+    }
+
+    let info: JsonRpcResponse<InfoResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_info"
+    }))).await?;
+    let version: JsonRpcResponse<VersionResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_version"
+    }))).await?;
+
+    let info = info.result;
+    Ok(
+      DaemonInfo {
+        height: info.height,
+        target_height: info.target_height,
+        synchronized: info.synchronized.unwrap_or(info.target_height <= info.height),
+        version: version.result.version
+      }
+    )
+  }
+
+  async fn get_transactions_core(
+    &self,
+    hashes: &[[u8; 32]]
+  ) -> Result<(Vec<Result<Transaction, RpcError>>, Vec<[u8; 32]>), RpcError> {
+    if hashes.len() == 0 {
+      return Ok((vec![], vec![]));
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct TransactionResponse {
+      tx_hash: String,
+      as_hex: String,
+      pruned_as_hex: String
+    }
+    #[derive(Deserialize, Debug)]
+    struct TransactionsResponse {
+      #[serde(default)]
+      missed_tx: Vec<String>,
+      txs: Vec<TransactionResponse>
+    }
+
+    let txs: TransactionsResponse = self.rpc_call("get_transactions", Some(json!({
+      "txs_hashes": hashes.iter().map(|hash| hex::encode(&hash)).collect::<Vec<_>>()
+    }))).await?;
+
+    Ok((
+      txs.txs.iter().map(|res| {
+        let tx = Transaction::deserialize(
+          &mut std::io::Cursor::new(
+            rpc_hex(if res.as_hex.len() != 0 { &res.as_hex } else { &res.pruned_as_hex }).unwrap()
+          )
+        ).map_err(|_| RpcError::InvalidTransaction(hex::decode(&res.tx_hash).unwrap().try_into().unwrap()))?;
+
+        // https://github.com/monero-project/monero/issues/8311
+        if res.as_hex.len() == 0 {
+          match tx.prefix.inputs.get(0) {
+            Some(Input::Gen { .. }) => (),
+            _ => Err(RpcError::PrunedTransaction)?
+          }
+        }
+
+        Ok(tx)
+      }).collect(),
+
+      txs.missed_tx.iter().map(|hash| hex::decode(&hash).unwrap().try_into().unwrap()).collect()
+    ))
+  }
+
+  pub async fn get_transactions(&self, hashes: &[[u8; 32]]) -> Result<Vec<Transaction>, RpcError> {
+    let (txs, missed) = self.get_transactions_core(hashes).await?;
+    if missed.len() != 0 {
+      Err(RpcError::TransactionsNotFound(missed))?;
+    }
+    // This will clone several KB and is accordingly inefficient
+    // TODO: Optimize
+    txs.iter().cloned().collect::<Result<_, _>>()
+  }
+
+  pub async fn get_transactions_possible(&self, hashes: &[[u8; 32]]) -> Result<Vec<Transaction>, RpcError> {
+    let (txs, _) = self.get_transactions_core(hashes).await?;
+    Ok(txs.iter().cloned().filter_map(|tx| tx.ok()).collect())
+  }
+
+  /// Get the hashes of the transactions in the daemon's pool
+  pub async fn get_transaction_pool_hashes(&self) -> Result<Vec<[u8; 32]>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct PoolResponse {
+      // Omitted when the pool is empty
+      #[serde(default)]
+      tx_hashes: Vec<String>
+    }
+
+    let pool: PoolResponse = self.rpc_call::<Option<()>, _>("get_transaction_pool_hashes", None)
+      .await?;
+    pool.tx_hashes.iter().map(|hash| {
+      rpc_hex(hash)?.try_into()
+        .map_err(|_| RpcError::InternalError("Monero returned an invalid hash".to_string()))
+    }).collect()
+  }
+
+  pub async fn get_block(&self, height: usize) -> Result<Block, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct BlockResponse {
+      blob: String
+    }
+
+    let block: JsonRpcResponse<BlockResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_block",
+      "params": {
+        "height": height
+      }
+    }))).await?;
+
+    Ok(
+      Block::deserialize(
+        &mut std::io::Cursor::new(rpc_hex(&block.result.blob)?)
+      ).expect("Monero returned a block we couldn't deserialize")
+    )
+  }
+
+  pub async fn get_block_hash(&self, height: usize) -> Result<[u8; 32], RpcError> {
+    let hash: JsonRpcResponse<String> = self.rpc_call("json_rpc", Some(json!({
+      "method": "on_get_block_hash",
+      "params": [height]
+    }))).await?;
+    rpc_hex(&hash.result)?.try_into()
+      .map_err(|_| RpcError::InternalError("Monero returned an invalid hash".to_string()))
+  }
+
+  /// Get the median timestamp of the blocks before the specified height, over the window Monero
+  /// uses to judge time based lock times
+  /// As the median lags behind the latest block, outputs are considered unlocked slightly late,
+  /// never early
+  pub async fn get_median_timestamp(&self, height: usize) -> Result<u64, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct HeaderResponse {
+      timestamp: u64
+    }
+    #[derive(Deserialize, Debug)]
+    struct HeadersResponse {
+      headers: Vec<HeaderResponse>
+    }
+
+    if height == 0 {
+      Err(RpcError::InternalError("no blocks to take the timestamp of".to_string()))?;
+    }
+    let headers: JsonRpcResponse<HeadersResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_block_headers_range",
+      "params": {
+        "start_height": height.saturating_sub(TIMESTAMP_WINDOW),
+        "end_height": height - 1
+      }
+    }))).await?;
+
+    let mut timestamps = headers.result.headers.iter().map(|header| header.timestamp)
+      .collect::<Vec<_>>();
+    if timestamps.is_empty() {
+      Err(RpcError::InternalError("Monero didn't return any block headers".to_string()))?;
+    }
+    timestamps.sort_unstable();
+    Ok(timestamps[timestamps.len() / 2])
+  }
+
+  /// Get the hashes of the blocks after the most recent block in known which is on the daemon's
+  /// chain, along with the height of the first returned hash
+  /// known should be ordered from newest to oldest and end with the genesis block. The returned
+  /// hashes start with the block found in known, letting the caller detect reorganizations
+  pub async fn get_hashes(&self, known: &[[u8; 32]]) -> Result<(usize, Vec<[u8; 32]>), RpcError> {
+    let res = self.bin_call(
+      "get_hashes.bin",
+      Section::new().insert("block_ids", epee_hashes(known))
+    ).await?;
+
+    let hashes = epee_bytes(&res, "m_block_ids")?;
+    if (hashes.len() % 32) != 0 {
+      Err(epee_invalid("m_block_ids"))?;
+    }
+    Ok((
+      usize::try_from(epee_u64(&res, "start_height")?).unwrap(),
+      hashes.chunks(32).map(|hash| hash.try_into().unwrap()).collect()
+    ))
+  }
+
+  /// Get a batch of blocks, with their transactions, along with the height of the first block
+  /// Starts from start if it's non-zero, and otherwise from the most recent block in known which
+  /// is on the daemon's chain, as with get_hashes. The daemon decides how many blocks to return
+  pub async fn get_blocks(
+    &self,
+    known: &[[u8; 32]],
+    start: usize
+  ) -> Result<(usize, Vec<(Block, Vec<Transaction>)>), RpcError> {
+    let res = self.bin_call(
+      "get_blocks.bin",
+      Section::new()
+        .insert("block_ids", epee_hashes(known))
+        .insert("start_height", Value::U64(start.try_into().unwrap()))
+        .insert("prune", Value::Bool(false))
+        .insert("no_miner_tx", Value::Bool(false))
+    ).await?;
+
+    let blocks = epee_array(&res, "blocks")?.iter().map(|entry| {
+      let entry = entry.as_object().ok_or_else(|| epee_invalid("blocks"))?;
+      let block = Block::deserialize(&mut epee_bytes(entry, "block")?)
+        .map_err(|_| epee_invalid("block"))?;
+      let txs = epee_array(entry, "txs")?.iter().map(|tx| {
+        let tx = tx.as_bytes().ok_or_else(|| epee_invalid("txs"))?;
+        Transaction::deserialize(&mut std::io::Cursor::new(tx))
+          .map_err(|_| RpcError::InvalidTransaction(crate::hash(tx)))
+      }).collect::<Result<_, _>>()?;
+      Ok((block, txs))
+    }).collect::<Result<_, RpcError>>()?;
+
+    Ok((usize::try_from(epee_u64(&res, "start_height")?).unwrap(), blocks))
+  }
+
+  async fn get_block_transactions_core(
+    &self,
+    height: usize,
+    possible: bool
+  ) -> Result<Vec<Transaction>, RpcError> {
+    let block = self.get_block(height).await?;
+    let mut res = vec![block.miner_tx];
+    res.extend(
+      if possible {
+        self.get_transactions_possible(&block.txs).await?
+      } else {
+        self.get_transactions(&block.txs).await?
+      }
+    );
+    Ok(res)
+  }
+
+  pub async fn get_block_transactions(&self, height: usize) -> Result<Vec<Transaction>, RpcError> {
+    self.get_block_transactions_core(height, false).await
+  }
+
+  pub async fn get_block_transactions_possible(&self, height: usize) -> Result<Vec<Transaction>, RpcError> {
+    self.get_block_transactions_core(height, true).await
+  }
+
+  pub async fn get_o_indexes(&self, hash: [u8; 32]) -> Result<Vec<u64>, RpcError> {
+    let res = self.bin_call(
+      "get_o_indexes.bin",
+      Section::new().insert("txid", Value::String(hash.to_vec()))
+    ).await?;
+
+    epee_array(&res, "o_indexes")?.iter().map(
+      |index| index.as_u64().ok_or_else(|| epee_invalid("o_indexes"))
+    ).collect()
+  }
+
+  // from and to are inclusive
+  pub async fn get_output_distribution(&self, from: usize, to: usize) -> Result<Vec<u64>, RpcError> {
+    #[allow(dead_code)]
+    #[derive(Deserialize, Debug)]
+    pub struct Distribution {
+      distribution: Vec<u64>
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Debug)]
+    struct Distributions {
+      distributions: Vec<Distribution>
+    }
+
+    let mut distributions: JsonRpcResponse<Distributions> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_output_distribution",
+      "params": {
+        "binary": false,
+        "amounts": [0],
+        "cumulative": true,
+        "from_height": from,
+        "to_height": to
+      }
+    }))).await?;
+
+    Ok(distributions.result.distributions.swap_remove(0).distribution)
+  }
+
+  // Get the key, commitment, and transaction hash of the specified outputs, indexed within the
+  // outputs of the specified amount (0 for RingCT)
+  async fn get_outs(
+    &self,
+    amount: u64,
+    indexes: &[u64]
+  ) -> Result<Vec<([u8; 32], [u8; 32], [u8; 32])>, RpcError> {
+    let res = self.bin_call(
+      "get_outs.bin",
+      Section::new()
+        .insert(
+          "outputs",
+          Value::Array(
+            Type::Object,
+            indexes.iter().map(
+              |index| Value::Object(
+                Section::new()
+                  .insert("amount", Value::U64(amount))
+                  .insert("index", Value::U64(*index))
+              )
+            ).collect()
+          )
+        )
+        .insert("get_txid", Value::Bool(true))
+    ).await?;
+
+    epee_array(&res, "outs")?.iter().map(|out| {
+      let out = out.as_object().ok_or_else(|| epee_invalid("outs"))?;
+      Ok((epee_32(out, "key")?, epee_32(out, "mask")?, epee_32(out, "txid")?))
+    }).collect()
+  }
+
+  /// Get the keys of the specified outputs, regardless of if they're unlocked
+  /// Pre-RingCT outputs are indexed by their amount, while RingCT outputs use an amount of 0
+  pub async fn get_output_keys(
+    &self,
+    amount: u64,
+    indexes: &[u64]
+  ) -> Result<Vec<EdwardsPoint>, RpcError> {
+    self.get_outs(amount, indexes).await?.iter().map(|out| rpc_point(out.0)).collect()
+  }
+
+  pub async fn get_outputs(
+    &self,
+    indexes: &[u64],
+    height: usize
+  ) -> Result<Vec<Option<[EdwardsPoint; 2]>>, RpcError> {
+    let outs = self.get_outs(0, indexes).await?;
+
+    let txs = self.get_transactions(&outs.iter().map(|out| out.2).collect::<Vec<_>>()).await?;
+    // Decoys don't need to use time based lock times, so they're always considered locked, saving
+    // fetching the chain's time
+    outs.iter().enumerate().map(
+      |(i, out)| Ok(
+        Some([rpc_point(out.0)?, rpc_point(out.1)?])
+          .filter(|_| txs[i].prefix.timelock.unlocked(height, 0))
+      )
+    ).collect()
+  }
+
+  /// Check if the specified key images have been spent, either on chain or within the pool
+  pub async fn is_key_image_spent(&self, images: &[EdwardsPoint]) -> Result<Vec<bool>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct SpentResponse {
+      spent_status: Vec<u8>
+    }
+
+    let images_hex = images.iter()
+      .map(|image| hex::encode(image.compress().to_bytes()))
+      .collect::<Vec<_>>();
+    let res: SpentResponse = self.rpc_call(
+      "is_key_image_spent",
+      Some(json!({ "key_images": images_hex }))
+    ).await?;
+    if res.spent_status.len() != images.len() {
+      Err(RpcError::InternalError("Monero didn't return a status for every key image".to_string()))?;
+    }
+    // 0 is unspent, 1 is spent on chain, and 2 is spent within the pool
+    Ok(res.spent_status.iter().map(|status| *status != 0).collect())
+  }
+
+  pub async fn get_fee(&self) -> Result<Fee, RpcError> {
+    #[allow(dead_code)]
+    #[derive(Deserialize, Debug)]
+    struct FeeResponse {
+      fee: u64,
+      quantization_mask: u64
+    }
+
+    let res: JsonRpcResponse<FeeResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_fee_estimate"
+    }))).await?;
+
+    Ok(Fee { per_weight: res.result.fee, mask: res.result.quantization_mask })
+  }
+
+  /// Get the fee for the specified priority
+  /// Uses the per-priority fees returned by newer daemons, falling back to scaling the base fee
+  /// as wallet2 does. Never returns a fee below the daemon's minimum
+  pub async fn get_fee_estimate(&self, priority: Priority) -> Result<Fee, RpcError> {
+    #[allow(dead_code)]
+    #[derive(Deserialize, Debug)]
+    struct FeeResponse {
+      fee: u64,
+      #[serde(default)]
+      fees: Vec<u64>,
+      quantization_mask: u64
+    }
+
+    let res: JsonRpcResponse<FeeResponse> = self.rpc_call("json_rpc", Some(json!({
+      "method": "get_fee_estimate"
+    }))).await?;
+
+    let base = Fee { per_weight: res.result.fee, mask: res.result.quantization_mask };
+    Ok(
+      res.result.fees.get(priority.index()).map(
+        |per_weight| Fee { per_weight: (*per_weight).max(base.per_weight), mask: base.mask }
+      ).unwrap_or_else(|| base.prioritized(priority))
+    )
+  }
+
+  /// Submit a transaction, optionally having the daemon only add it to its own pool without
+  /// relaying it, as useful for testing
+  pub async fn send_raw_transaction(
+    &self,
+    tx: &Transaction,
+    do_not_relay: bool
+  ) -> Result<(), RpcError> {
+    let mut buf = Vec::with_capacity(2048);
+    tx.serialize(&mut buf).unwrap();
+    let res: SendRawResponse = self.rpc_call("send_raw_transaction", Some(json!({
+      "tx_as_hex": hex::encode(&buf),
+      "do_not_relay": do_not_relay
+    }))).await?;
+
+    if res.status != "OK" {
+      Err(RpcError::TransactionRejected(tx.hash(), res.rejections()))?;
+    }
+    if res.not_relayed && (!do_not_relay) {
+      Err(RpcError::TransactionNotRelayed(tx.hash()))?;
+    }
+
+    Ok(())
+  }
+
+  pub async fn publish_transaction(&self, tx: &Transaction) -> Result<(), RpcError> {
+    self.send_raw_transaction(tx, false).await
+  }
+}
@@ -0,0 +1,122 @@
+use std::{sync::Mutex, time::{Duration, Instant}, future::Future};
+
+use tokio::time::timeout;
+
+use crate::rpc::{RpcError, DaemonInfo, Rpc};
+
+// Time a daemon has to respond to a health check before it's considered down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Judge which daemons are healthy, given their info, or None if they didn't respond
+/// A daemon is healthy if it claims to be synchronized, it isn't below the minimum RPC version,
+/// and it's within max_lag blocks of both the median daemon and its own target height
+pub(crate) fn healthy(
+  infos: &[Option<DaemonInfo>],
+  min_version: u32,
+  max_lag: usize
+) -> Vec<bool> {
+  // The median, not the highest, so a single daemon lying about its height can't mark every
+  // other daemon as stale
+  // The lower median is used when there's an even amount, so one of two daemons can't either
+  let mut heights = infos.iter().flatten().map(|info| info.height).collect::<Vec<_>>();
+  heights.sort();
+  let best = heights.get(heights.len().saturating_sub(1) / 2).copied().unwrap_or(0);
+  infos.iter().map(|info| {
+    info.map(|info| {
+      info.synchronized &&
+        (info.version >= min_version) &&
+        ((info.height + max_lag) >= best) &&
+        ((info.height + max_lag) >= info.target_height)
+    }).unwrap_or(false)
+  }).collect()
+}
+
+#[derive(Debug)]
+struct Selection {
+  daemon: usize,
+  checked: Option<Instant>
+}
+
+/// A set of daemons, used with automatic failover
+/// The daemon in use is checked to still be healthy every interval, and on any connection error,
+/// switching to another healthy daemon when it isn't
+#[derive(Debug)]
+pub struct RpcPool {
+  daemons: Vec<Rpc>,
+  min_version: u32,
+  max_lag: usize,
+  interval: Duration,
+  selection: Mutex<Selection>
+}
+
+impl RpcPool {
+  /// Create a pool of the specified daemons, using the first while it's healthy
+  /// Daemons below min_version, or more than max_lag blocks behind, are considered unhealthy
+  pub fn new(
+    daemons: Vec<String>,
+    min_version: u32,
+    max_lag: usize,
+    interval: Duration
+  ) -> RpcPool {
+    assert!(!daemons.is_empty(), "no daemons specified");
+    RpcPool {
+      daemons: daemons.into_iter().map(Rpc::new).collect(),
+      min_version,
+      max_lag,
+      interval,
+      selection: Mutex::new(Selection { daemon: 0, checked: None })
+    }
+  }
+
+  /// Get the info of every daemon, in the order they were specified
+  pub async fn health(&self) -> Vec<Result<DaemonInfo, RpcError>> {
+    let mut res = Vec::with_capacity(self.daemons.len());
+    for daemon in &self.daemons {
+      res.push(
+        timeout(CHECK_TIMEOUT, daemon.get_info()).await.unwrap_or(Err(RpcError::ConnectionError))
+      );
+    }
+    res
+  }
+
+  /// Check the health of every daemon, selecting a healthy one
+  /// The daemon currently in use is kept if it's still healthy
+  pub async fn select(&self) -> Result<Rpc, RpcError> {
+    let infos = self.health().await.into_iter().map(Result::ok).collect::<Vec<_>>();
+    let healthy = healthy(&infos, self.min_version, self.max_lag);
+
+    let mut selection = self.selection.lock().unwrap();
+    selection.checked = Some(Instant::now());
+    if !healthy[selection.daemon] {
+      // Try the daemons after the current one first, so failures rotate through the set
+      selection.daemon = (1 .. self.daemons.len())
+        .map(|offset| (selection.daemon + offset) % self.daemons.len())
+        .find(|daemon| healthy[*daemon])
+        .ok_or(RpcError::NoHealthyDaemon)?;
+    }
+    Ok(self.daemons[selection.daemon].clone())
+  }
+
+  /// Get the daemon currently in use, checking its health if the interval has passed
+  pub async fn rpc(&self) -> Result<Rpc, RpcError> {
+    {
+      let selection = self.selection.lock().unwrap();
+      if selection.checked.map(|checked| checked.elapsed() < self.interval).unwrap_or(false) {
+        return Ok(self.daemons[selection.daemon].clone());
+      }
+    }
+    self.select().await
+  }
+
+  /// Perform a call against the daemon in use, failing over to another daemon if the connection
+  /// fails
+  pub async fn call<T, F: Fn(Rpc) -> Fut, Fut: Future<Output = Result<T, RpcError>>>(
+    &self,
+    call: F
+  ) -> Result<T, RpcError> {
+    match call(self.rpc().await?).await {
+      Err(RpcError::ConnectionError) => call(self.select().await?).await,
+      res => res
+    }
+  }
+}
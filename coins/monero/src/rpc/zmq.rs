@@ -0,0 +1,201 @@
+use serde::Deserialize;
+
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpStream};
+
+use crate::rpc::{RpcError, rpc_hex};
+
+// monerod's ZMQ-Pub topics. The minimal variants solely identify what's new, leaving the caller
+// to fetch what it's interested in over RPC
+const CHAIN_TOPIC: &[u8] = b"json-minimal-chain_main";
+const TXPOOL_TOPIC: &[u8] = b"json-minimal-txpool_add";
+
+// Maximum length of a message, across all of its frames. Minimal notifications are far smaller
+const MAX_MESSAGE: usize = 4 * 1024 * 1024;
+
+// ZMTP frame flags
+const MORE: u8 = 1;
+const LONG: u8 = 1 << 1;
+const COMMAND: u8 = 1 << 2;
+
+/// A transaction added to the daemon's pool
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PoolTransaction {
+  pub hash: [u8; 32],
+  pub weight: usize,
+  pub fee: u64
+}
+
+/// A notification published by monerod
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Notification {
+  /// Blocks added to the main chain, starting at the specified height
+  /// If prev isn't the hash of the block before height, a reorganization occurred
+  Blocks { height: usize, prev: [u8; 32], hashes: Vec<[u8; 32]> },
+  /// Transactions added to the pool
+  Transactions(Vec<PoolTransaction>)
+}
+
+fn zmq_hash(hash: &str) -> Result<[u8; 32], RpcError> {
+  rpc_hex(hash)?.try_into()
+    .map_err(|_| RpcError::InternalError("Monero published an invalid hash".to_string()))
+}
+
+impl Notification {
+  pub(crate) fn parse(message: &[u8]) -> Result<Option<Notification>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct ChainMain {
+      first_height: usize,
+      first_prev_id: String,
+      ids: Vec<String>
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct TxpoolAdd {
+      id: String,
+      weight: usize,
+      fee: u64
+    }
+
+    let invalid =
+      || RpcError::InternalError("Monero published an invalid notification".to_string());
+
+    // Messages are the topic, a colon, and then the JSON
+    let split = message.iter().position(|byte| *byte == b':').ok_or_else(invalid)?;
+    let (topic, json) = (&message[.. split], &message[(split + 1) ..]);
+    Ok(
+      if topic == CHAIN_TOPIC {
+        let chain: ChainMain = serde_json::from_slice(json).map_err(|_| invalid())?;
+        Some(
+          Notification::Blocks {
+            height: chain.first_height,
+            prev: zmq_hash(&chain.first_prev_id)?,
+            hashes: chain.ids.iter().map(|id| zmq_hash(id)).collect::<Result<_, _>>()?
+          }
+        )
+      } else if topic == TXPOOL_TOPIC {
+        let txs: Vec<TxpoolAdd> = serde_json::from_slice(json).map_err(|_| invalid())?;
+        Some(
+          Notification::Transactions(
+            txs.iter().map(
+              |tx| Ok(PoolTransaction { hash: zmq_hash(&tx.id)?, weight: tx.weight, fee: tx.fee })
+            ).collect::<Result<_, _>>()?
+          )
+        )
+      } else {
+        None
+      }
+    )
+  }
+}
+
+/// A subscriber to monerod's ZMQ-Pub endpoint, as enabled by --zmq-pub
+/// Notifies of new blocks and pool transactions, removing the need to poll the daemon
+#[derive(Debug)]
+pub struct ZmqSubscriber {
+  stream: TcpStream
+}
+
+impl ZmqSubscriber {
+  /// Connect to the specified endpoint, such as tcp://127.0.0.1:18083, and subscribe to new
+  /// blocks and pool transactions
+  pub async fn connect(endpoint: &str) -> Result<ZmqSubscriber, RpcError> {
+    let address = endpoint.strip_prefix("tcp://").unwrap_or(endpoint);
+    let stream = TcpStream::connect(address).await.map_err(|_| RpcError::ConnectionError)?;
+    let mut subscriber = ZmqSubscriber { stream };
+
+    // ZMTP 3.0 greeting, using the NULL mechanism as monerod doesn't support authentication
+    let mut greeting = [0; 64];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    greeting[10] = 3;
+    greeting[12 .. 16].copy_from_slice(b"NULL");
+    subscriber.write(&greeting).await?;
+
+    let mut peer = [0; 64];
+    subscriber.stream.read_exact(&mut peer).await.map_err(|_| RpcError::ConnectionError)?;
+    if (peer[0] != 0xff) || (peer[9] != 0x7f) || (peer[10] < 3) ||
+      (peer[12 .. 32] != greeting[12 .. 32]) {
+      Err(RpcError::InternalError("endpoint isn't ZMTP 3 with the NULL mechanism".to_string()))?;
+    }
+
+    let mut ready = vec![5];
+    ready.extend(b"READY");
+    ready.push(11);
+    ready.extend(b"Socket-Type");
+    ready.extend(3u32.to_be_bytes());
+    ready.extend(b"SUB");
+    subscriber.write_frame(COMMAND, &ready).await?;
+
+    let (command, ready, _) = subscriber.read_frame(MAX_MESSAGE).await?;
+    if (!command) || (!ready.starts_with(b"\x05READY")) {
+      Err(RpcError::InternalError("endpoint didn't send READY".to_string()))?;
+    }
+
+    // Under ZMTP 3.0, subscriptions are messages prefixed with 1
+    for topic in [CHAIN_TOPIC, TXPOOL_TOPIC] {
+      subscriber.write_frame(0, &[&[1], topic].concat()).await?;
+    }
+    Ok(subscriber)
+  }
+
+  async fn write(&mut self, data: &[u8]) -> Result<(), RpcError> {
+    self.stream.write_all(data).await.map_err(|_| RpcError::ConnectionError)
+  }
+
+  async fn write_frame(&mut self, flags: u8, body: &[u8]) -> Result<(), RpcError> {
+    let mut frame = vec![];
+    if let Ok(len) = u8::try_from(body.len()) {
+      frame.push(flags);
+      frame.push(len);
+    } else {
+      frame.push(flags | LONG);
+      frame.extend(u64::try_from(body.len()).unwrap().to_be_bytes());
+    }
+    frame.extend(body);
+    self.write(&frame).await
+  }
+
+  // Returns if the frame was a command, its body, and if more frames follow
+  // Frames longer than max are rejected before anything is allocated for them
+  async fn read_frame(&mut self, max: usize) -> Result<(bool, Vec<u8>, bool), RpcError> {
+    let flags = self.stream.read_u8().await.map_err(|_| RpcError::ConnectionError)?;
+    let len = if (flags & LONG) == LONG {
+      self.stream.read_u64().await.map_err(|_| RpcError::ConnectionError)?
+    } else {
+      self.stream.read_u8().await.map_err(|_| RpcError::ConnectionError)?.into()
+    };
+    if len > u64::try_from(max).unwrap() {
+      Err(RpcError::InternalError("frame exceeds the maximum message length".to_string()))?;
+    }
+
+    // Read incrementally, so the buffer only grows as the peer actually sends data
+    let mut body = vec![];
+    (&mut self.stream).take(len).read_to_end(&mut body).await
+      .map_err(|_| RpcError::ConnectionError)?;
+    if u64::try_from(body.len()).unwrap() != len {
+      Err(RpcError::ConnectionError)?;
+    }
+    Ok(((flags & COMMAND) == COMMAND, body, (flags & MORE) == MORE))
+  }
+
+  /// Wait for the next notification
+  pub async fn next(&mut self) -> Result<Notification, RpcError> {
+    loop {
+      let mut message = vec![];
+      loop {
+        let (command, body, more) = self.read_frame(MAX_MESSAGE - message.len()).await?;
+        // Commands don't carry notifications
+        if command {
+          break;
+        }
+        message.extend(body);
+        if !more {
+          if let Some(notification) = Notification::parse(&message)? {
+            return Ok(notification);
+          }
+          break;
+        }
+      }
+    }
+  }
+}
@@ -90,9 +90,8 @@ pub fn read_point<R: io::Read>(r: &mut R) -> io::Result<EdwardsPoint> {
 }
 
 pub fn read_raw_vec<R: io::Read, T, F: Fn(&mut R) -> io::Result<T>>(f: F, len: usize, r: &mut R) -> io::Result<Vec<T>> {
-  let mut res = Vec::with_capacity(
-    len.try_into().map_err(|_| io::Error::new(io::ErrorKind::Other, "length exceeds usize"))?
-  );
+  // Don't allocate the claimed length upfront, as it's yet to be proven by the data
+  let mut res = vec![];
   for _ in 0 .. len {
     res.push(f(r)?);
   }
@@ -100,5 +99,7 @@ pub fn read_raw_vec<R: io::Read, T, F: Fn(&mut R) -> io::Result<T>>(f: F, len: u
 }
 
 pub fn read_vec<R: io::Read, T, F: Fn(&mut R) -> io::Result<T>>(f: F, r: &mut R) -> io::Result<Vec<T>> {
-  read_raw_vec(f, read_varint(r)?.try_into().unwrap(), r)
+  let len = read_varint(r)?.try_into()
+    .map_err(|_| io::Error::new(io::ErrorKind::Other, "length exceeds usize"))?;
+  read_raw_vec(f, len, r)
 }
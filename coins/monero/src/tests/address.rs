@@ -0,0 +1,41 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use crate::{random_scalar, wallet::{Network, AddressType, AddressError, Address}};
+
+// The Monero general fund's address
+const ADDRESS: &str =
+  "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+
+#[test]
+fn address_vector() {
+  let address = ADDRESS.parse::<Address>().unwrap();
+  assert_eq!(address.network, Network::Mainnet);
+  assert_eq!(address.kind, AddressType::Standard);
+  assert_eq!(address.to_string(), ADDRESS);
+
+  assert!(Address::from_str_with_network(ADDRESS, Network::Mainnet).is_ok());
+  assert!(matches!(
+    Address::from_str_with_network(ADDRESS, Network::Testnet),
+    Err(AddressError::DifferentNetwork)
+  ));
+
+  let mut corrupted = ADDRESS.to_string();
+  corrupted.replace_range(94 .., "B");
+  assert!(matches!(corrupted.parse::<Address>(), Err(AddressError::InvalidChecksum)));
+  assert!(matches!(ADDRESS[.. 92].parse::<Address>(), Err(AddressError::InvalidEncoding)));
+  assert!(matches!("0".parse::<Address>(), Err(AddressError::InvalidEncoding)));
+}
+
+#[test]
+fn address_round_trip() {
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  let view = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  for network in [Network::Mainnet, Network::Testnet, Network::Stagenet] {
+    for kind in [AddressType::Standard, AddressType::Integrated([0xab; 8]), AddressType::Subaddress] {
+      let address = Address::new(network, kind, spend, view);
+      assert_eq!(address.to_string().parse::<Address>().unwrap(), address);
+    }
+  }
+}
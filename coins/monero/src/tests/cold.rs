@@ -0,0 +1,256 @@
+#[cfg(feature = "multisig")]
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+#[cfg(feature = "multisig")]
+use transcript::RecommendedTranscript;
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, tests::{key_gen, recover, sign}};
+
+use crate::{
+  Commitment,
+  random_scalar,
+  serialize::{write_varint, write_point},
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    Network, Address, Fee, SpendableOutput, TransactionError, SignableTransaction,
+    SignedKeyImage, UnsignedTransaction, SignedTransaction,
+    export_outputs, import_outputs, export_key_images, import_key_images,
+    import_unsigned, export_signed, import_signed
+  }
+};
+#[cfg(feature = "multisig")]
+use crate::{generate_key_image, wallet::import_key_image_shares};
+
+const FEE: Fee = Fee { per_weight: 20, mask: 10000 };
+const RING_LEN: usize = 11;
+
+fn address() -> Address {
+  Address::standard(
+    Network::Mainnet,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+  )
+}
+
+fn output(spend: &Scalar, amount: u64) -> SpendableOutput {
+  let key_offset = random_scalar(&mut OsRng);
+  SpendableOutput {
+    tx: [1; 32],
+    o: 1,
+    key: &(spend + key_offset) * &ED25519_BASEPOINT_TABLE,
+    key_offset,
+    commitment: Commitment::new(random_scalar(&mut OsRng), amount),
+    subaddress: None
+  }
+}
+
+#[test]
+fn outputs() {
+  let spend = random_scalar(&mut OsRng);
+  let outputs = vec![output(&spend, 1), output(&spend, 2)];
+  let exported = export_outputs(&outputs);
+  assert_eq!(import_outputs(&exported).unwrap(), outputs);
+
+  // Trailing data and other formats are rejected
+  let mut trailing = exported.clone();
+  trailing.push(0);
+  assert!(import_outputs(&trailing).is_none());
+  assert!(import_key_images(&exported).is_none());
+}
+
+#[test]
+fn key_images() {
+  let spend = random_scalar(&mut OsRng);
+  let outputs = vec![output(&spend, 1), output(&spend, 2)];
+  let images = outputs.iter()
+    .map(|output| SignedKeyImage::new(&mut OsRng, output, &spend).unwrap())
+    .collect::<Vec<_>>();
+  for (output, image) in outputs.iter().zip(&images) {
+    assert_eq!(image.key, output.key);
+    assert!(image.verify());
+  }
+
+  let exported = export_key_images(&images);
+  assert_eq!(import_key_images(&exported).unwrap(), images);
+
+  // Key images must be signed for by their outputs
+  let mut swapped = images.clone();
+  swapped[0].image = swapped[1].image;
+  assert!(import_key_images(&export_key_images(&swapped)).is_none());
+
+  assert!(matches!(
+    SignedKeyImage::new(&mut OsRng, &outputs[0], &random_scalar(&mut OsRng)),
+    Err(TransactionError::WrongPrivateKey)
+  ));
+}
+
+#[cfg(feature = "multisig")]
+#[test]
+fn key_image_multisig() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+  let private = recover(
+    &keys.iter().map(|(l, keys)| (*l, (**keys).clone())).collect::<HashMap<_, _>>()
+  ).0;
+
+  let mut output = output(&Scalar::zero(), 1);
+  output.key = keys[&1].group_key().0 + (&output.key_offset * &ED25519_BASEPOINT_TABLE);
+
+  let machines = included.iter().map(|l| (
+    *l,
+    SignedKeyImage::multisig(
+      &keys[l],
+      RecommendedTranscript::new(b"Monero Serai Key Image Export Test"),
+      &output,
+      &included
+    ).unwrap()
+  )).collect::<HashMap<_, _>>();
+
+  let image = SignedKeyImage::from_multisig(
+    output.key,
+    sign(&mut OsRng, machines, &SignedKeyImage::signature_hash(&output.key))
+  );
+  assert_eq!(image.image, generate_key_image(&(private + output.key_offset)));
+  assert!(image.verify());
+}
+
+fn signable(spend: &Scalar) -> SignableTransaction {
+  SignableTransaction::new(
+    vec![output(spend, 10_000_000), output(spend, 20_000_000)],
+    vec![(address(), 5_000_000), (address(), 6_000_000)],
+    Some(address()),
+    FEE
+  ).unwrap()
+}
+
+#[test]
+fn signable_transaction() {
+  let signable = signable(&random_scalar(&mut OsRng));
+  let mut serialized = vec![];
+  signable.serialize(&mut serialized).unwrap();
+  let deserialized = SignableTransaction::deserialize(&mut serialized.as_slice()).unwrap();
+  assert_eq!(deserialized.inputs(), signable.inputs());
+  assert_eq!(deserialized.payments(), signable.payments());
+  assert_eq!(deserialized.fee(), signable.fee());
+}
+
+// Serialize an unsigned transaction with the inputs at the specified indexes within their rings
+fn unsigned(signable: &SignableTransaction, indexes: &[u8]) -> Vec<u8> {
+  let mut serialized = vec![];
+  signable.serialize(&mut serialized).unwrap();
+  write_varint(&100, &mut serialized).unwrap();
+  for (input, i) in signable.inputs().iter().zip(indexes) {
+    serialized.push(*i);
+    write_varint(&RING_LEN.try_into().unwrap(), &mut serialized).unwrap();
+    for o in 0 .. RING_LEN {
+      write_varint(&u64::try_from(o + 1).unwrap(), &mut serialized).unwrap();
+    }
+    for o in 0 .. RING_LEN {
+      let member = if o == usize::from(*i) {
+        [input.key, input.commitment.calculate()]
+      } else {
+        [
+          &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+          &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+        ]
+      };
+      write_point(&member[0], &mut serialized).unwrap();
+      write_point(&member[1], &mut serialized).unwrap();
+    }
+  }
+  serialized
+}
+
+#[test]
+fn unsigned_transaction() {
+  let signable = signable(&random_scalar(&mut OsRng));
+
+  let serialized = unsigned(&signable, &[0, 7]);
+  let tx = UnsignedTransaction::deserialize(&mut serialized.as_slice()).unwrap();
+  assert_eq!(tx.signable(), &signable);
+  let mut reserialized = vec![];
+  tx.serialize(&mut reserialized).unwrap();
+  assert_eq!(reserialized, serialized);
+
+  let mut exported = b"Monero Serai unsigned transactions\x01".to_vec();
+  write_varint(&1, &mut exported).unwrap();
+  exported.extend(&serialized);
+  assert_eq!(import_unsigned(&exported).unwrap(), vec![tx]);
+
+  // Inputs must be within their rings, at the claimed index
+  let mut prefix = vec![];
+  signable.serialize(&mut prefix).unwrap();
+  let mut misindexed = serialized.clone();
+  // Skip the signable transaction and the height
+  misindexed[prefix.len() + 1] = 1;
+  assert!(UnsignedTransaction::deserialize(&mut misindexed.as_slice()).is_err());
+  assert!(
+    UnsignedTransaction::deserialize(&mut unsigned(&signable, &[0, 11]).as_slice()).is_err()
+  );
+  // Every input must have decoys
+  assert!(UnsignedTransaction::deserialize(&mut unsigned(&signable, &[0]).as_slice()).is_err());
+}
+
+#[test]
+fn signed_transaction() {
+  let tx = SignedTransaction {
+    tx: Transaction {
+      prefix: TransactionPrefix {
+        version: 2,
+        timelock: Timelock::None,
+        inputs: vec![Input::ToKey {
+          amount: 0,
+          key_offsets: vec![1, 2],
+          key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+        }],
+        outputs: vec![],
+        extra: vec![]
+      },
+      signatures: vec![],
+      rct_signatures: RctSignatures {
+        base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+        prunable: RctPrunable::Null
+      }
+    },
+    tx_keys: vec![random_scalar(&mut OsRng), random_scalar(&mut OsRng)]
+  };
+
+  let exported = export_signed(&[tx.clone(), tx.clone()]);
+  assert_eq!(import_signed(&exported).unwrap(), vec![tx.clone(), tx]);
+  assert!(import_signed(&exported[.. exported.len() - 1]).is_none());
+}
+
+#[test]
+fn huge_lengths() {
+  // Each import errors on a declared length far exceeding its data, instead of allocating for it
+  let huge = |magic: &[u8]| {
+    let mut serialized = magic.to_vec();
+    write_varint(&(1 << 62), &mut serialized).unwrap();
+    serialized
+  };
+  assert!(import_outputs(&huge(b"Monero Serai outputs\x01")).is_none());
+  assert!(import_key_images(&huge(b"Monero Serai key images\x01")).is_none());
+  assert!(import_unsigned(&huge(b"Monero Serai unsigned transactions\x01")).is_none());
+  assert!(import_signed(&huge(b"Monero Serai signed transactions\x01")).is_none());
+  #[cfg(feature = "multisig")]
+  assert!(import_key_image_shares(&huge(b"Monero Serai key image shares\x01\x01\x00")).is_none());
+
+  // As do the lengths within them, such as a ring's or a transaction's extra
+  let signable = signable(&random_scalar(&mut OsRng));
+  let mut serialized = vec![];
+  signable.serialize(&mut serialized).unwrap();
+  write_varint(&100, &mut serialized).unwrap();
+  serialized.push(0);
+  write_varint(&(1 << 62), &mut serialized).unwrap();
+  assert!(UnsignedTransaction::deserialize(&mut serialized.as_slice()).is_err());
+
+  // Version 2, no timelock, inputs, or outputs, and then the extra's length
+  let mut serialized = vec![2, 0, 0, 0];
+  write_varint(&(1 << 62), &mut serialized).unwrap();
+  assert!(SignedTransaction::deserialize(&mut serialized.as_slice()).is_err());
+}
@@ -0,0 +1,22 @@
+use rand::{RngCore, rngs::OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_POINT, scalar::Scalar};
+
+use crate::{H, H_TABLE, Commitment, random_scalar};
+
+#[test]
+fn h_table() {
+  let scalar = random_scalar(&mut OsRng);
+  assert_eq!(&scalar * &*H_TABLE, scalar * *H);
+  assert_eq!(H_TABLE.basepoint(), *H);
+}
+
+#[test]
+fn commitment() {
+  let mask = random_scalar(&mut OsRng);
+  let amount = OsRng.next_u64();
+  assert_eq!(
+    Commitment::new(mask, amount).calculate(),
+    (mask * ED25519_BASEPOINT_POINT) + (Scalar::from(amount) * *H)
+  );
+}
@@ -0,0 +1,99 @@
+use crate::rpc::epee::{Type, Value, Section};
+
+const HEADER: [u8; 9] = [0x01, 0x11, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01, 0x01];
+
+fn serialize(section: &Section) -> Vec<u8> {
+  let mut res = vec![];
+  section.serialize(&mut res).unwrap();
+  res
+}
+
+#[test]
+fn epee_vector() {
+  // { "status": "OK" }
+  let mut bytes = HEADER.to_vec();
+  bytes.extend([0x04, 0x06]);
+  bytes.extend(b"status");
+  bytes.extend([0x0a, 0x08]);
+  bytes.extend(b"OK");
+
+  let section = Section::new().insert("status", Value::String(b"OK".to_vec()));
+  assert_eq!(serialize(&section), bytes);
+  assert_eq!(Section::deserialize(&mut bytes.as_slice()).unwrap(), section);
+}
+
+#[test]
+fn epee_round_trip() {
+  let section = Section::new()
+    .insert("i64", Value::I64(-1))
+    .insert("i32", Value::I32(-2))
+    .insert("i16", Value::I16(-3))
+    .insert("i8", Value::I8(-4))
+    .insert("u64", Value::U64(u64::MAX))
+    .insert("u32", Value::U32(6))
+    .insert("u16", Value::U16(7))
+    .insert("u8", Value::U8(8))
+    .insert("f64", Value::F64(0.5))
+    .insert("bool", Value::Bool(true))
+    // Long enough to use each width of varint for its length
+    .insert("short", Value::String(vec![0xff; 63]))
+    .insert("medium", Value::String(vec![0xff; 64]))
+    .insert("long", Value::String(vec![0xff; 1 << 14]))
+    .insert("empty", Value::Array(Type::U64, vec![]))
+    .insert(
+      "blobs",
+      Value::Array(Type::String, vec![Value::String(vec![0, 1]), Value::String(vec![])])
+    )
+    .insert(
+      "objects",
+      Value::Array(
+        Type::Object,
+        vec![
+          Value::Object(Section::new().insert("index", Value::U64(1))),
+          Value::Object(Section::new())
+        ]
+      )
+    );
+
+  let bytes = serialize(&section);
+  assert_eq!(Section::deserialize(&mut bytes.as_slice()).unwrap(), section);
+
+  assert_eq!(section.get("u8").unwrap().as_u64(), Some(8));
+  assert_eq!(section.get("u64").unwrap().as_u64(), Some(u64::MAX));
+  assert_eq!(section.get("i64").unwrap().as_u64(), None);
+  assert_eq!(section.get("missing"), None);
+
+  // Arrays must only contain their specified type
+  let mut buf = vec![];
+  assert!(
+    Section::new()
+      .insert("invalid", Value::Array(Type::U64, vec![Value::U32(1)]))
+      .serialize(&mut buf)
+      .is_err()
+  );
+}
+
+#[test]
+fn epee_invalid() {
+  let bytes = serialize(&Section::new().insert("string", Value::String(vec![1; 16])));
+  for i in 0 .. bytes.len() {
+    assert!(Section::deserialize(&mut &bytes[.. i]).is_err());
+  }
+
+  let mut signature = bytes.clone();
+  signature[0] = 0;
+  assert!(Section::deserialize(&mut signature.as_slice()).is_err());
+
+  // A string claiming to be far longer than the data
+  let mut length = HEADER.to_vec();
+  length.extend([0x04, 0x01, b'a', 0x0a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f]);
+  assert!(Section::deserialize(&mut length.as_slice()).is_err());
+
+  // Objects nested beyond the daemon's limit
+  let mut nested = HEADER.to_vec();
+  for _ in 0 .. 128 {
+    nested.extend([0x04, 0x01, b'a', 0x0c]);
+  }
+  nested.push(0x00);
+  assert!(Section::deserialize(&mut nested.as_slice()).is_err());
+}
@@ -0,0 +1,16 @@
+use crate::wallet::{Fee, Priority};
+
+#[test]
+fn fee() {
+  let fee = Fee { per_weight: 20, mask: 10000 };
+  // Rounded up to the quantization mask
+  assert_eq!(fee.calculate(1000), 20000);
+  assert_eq!(fee.calculate(1001), 30000);
+  // A mask of 0 doesn't quantize
+  assert_eq!(Fee { per_weight: 20, mask: 0 }.calculate(1001), 20020);
+
+  assert_eq!(fee.prioritized(Priority::Low), fee);
+  assert_eq!(fee.prioritized(Priority::Normal).per_weight, 100);
+  assert_eq!(fee.prioritized(Priority::Elevated).per_weight, 500);
+  assert_eq!(fee.prioritized(Priority::Priority).per_weight, 20000);
+}
@@ -0,0 +1,83 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, tests::key_gen};
+
+use crate::{
+  random_scalar,
+  serialize::write_varint,
+  wallet::{base58, MultisigKexContent, MultisigKexMessage}
+};
+
+#[test]
+fn kex_message() {
+  for content in [
+    MultisigKexContent::Round1(random_scalar(&mut OsRng)),
+    MultisigKexContent::RoundN(
+      2,
+      (0 .. 3).map(|_| &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE).collect()
+    )
+  ] {
+    let message = MultisigKexMessage::new(&mut OsRng, content, &random_scalar(&mut OsRng));
+    assert!(message.verify());
+
+    let serialized = message.serialize();
+    assert!(serialized.starts_with(
+      if message.round() == 1 { "MultisigxV2R1" } else { "MultisigxV2Rn" }
+    ));
+    assert_eq!(MultisigKexMessage::deserialize(&serialized).unwrap(), message);
+
+    // Messages must be signed by their signing key
+    let mut forged = message.clone();
+    forged.signing_key = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+    assert!(!forged.verify());
+    assert!(MultisigKexMessage::deserialize(&forged.serialize()).is_none());
+
+    assert!(MultisigKexMessage::deserialize(&serialized[.. serialized.len() - 1]).is_none());
+  }
+
+  // Later rounds can't claim to be the first
+  let mut first = MultisigKexMessage::new(
+    &mut OsRng,
+    MultisigKexContent::RoundN(2, vec![]),
+    &random_scalar(&mut OsRng)
+  );
+  first.content = MultisigKexContent::RoundN(1, vec![]);
+  assert!(!first.verify());
+  assert!(MultisigKexMessage::deserialize(&first.serialize()).is_none());
+}
+
+#[test]
+fn kex_message_huge_length() {
+  // A later round declaring 2**62 keys errors, instead of panicking when allocating for them
+  let mut serialized = vec![];
+  write_varint(&2, &mut serialized).unwrap();
+  write_varint(&(1 << 62), &mut serialized).unwrap();
+  let message = "MultisigxV2Rn".to_string() + &base58::encode(&serialized);
+  assert!(MultisigKexMessage::deserialize(&message).is_none());
+}
+
+#[cfg(feature = "multisig")]
+#[test]
+fn kex_message_from_view() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+  let private_view = random_scalar(&mut OsRng);
+
+  for l in &included {
+    let view = keys[l].view(&included).unwrap();
+    let message = MultisigKexMessage::from_view(&mut OsRng, &view, &private_view);
+    let message = MultisigKexMessage::deserialize(&message.serialize()).unwrap();
+    assert_eq!(message.content, MultisigKexContent::Round1(private_view));
+    assert_eq!(message.participant(&keys[&1].view(&included).unwrap()), Some(*l));
+  }
+
+  let other = MultisigKexMessage::new(
+    &mut OsRng,
+    MultisigKexContent::Round1(private_view),
+    &random_scalar(&mut OsRng)
+  );
+  assert_eq!(other.participant(&keys[&1].view(&included).unwrap()), None);
+}
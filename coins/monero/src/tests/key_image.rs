@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use frost::{curve::{Curve, Ed25519}, tests::{key_gen, recover}};
+
+use crate::{
+  Commitment, random_scalar,
+  wallet::{TransactionError, SpendableOutput, KeyImage, KeyImageMachine}
+};
+
+#[allow(non_snake_case)]
+#[test]
+fn key_image() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+
+  let key_offset = random_scalar(&mut OsRng);
+  let output = SpendableOutput {
+    tx: [0; 32],
+    o: 0,
+    key: keys[&1].group_key().0 + (&key_offset * &ED25519_BASEPOINT_TABLE),
+    key_offset,
+    commitment: Commitment::new(Scalar::one(), 1),
+    subaddress: None
+  };
+
+  let mut machines = included.iter().map(
+    |l| (*l, KeyImageMachine::new(&keys[l], &output, &included).unwrap())
+  ).collect::<HashMap<_, _>>();
+  let shares = machines.iter_mut().map(
+    |(l, machine)| (*l, machine.share(&mut OsRng))
+  ).collect::<HashMap<_, _>>();
+
+  let images = machines.drain().map(|(l, machine)| {
+    let mut others = shares.clone();
+    others.remove(&l);
+    machine.complete(others).unwrap()
+  }).collect::<Vec<_>>();
+
+  let private = recover(
+    &keys.iter().map(|(l, keys)| (*l, (**keys).clone())).collect::<HashMap<_, _>>()
+  ).0 + key_offset;
+  let H = Ed25519::hash_to_G(b"", &output.key.compress().to_bytes()).0;
+  for image in &images {
+    assert_eq!(image, &images[0]);
+    assert_eq!(image.image, private * H);
+    image.verify(&keys[&1], &output).unwrap();
+    assert_eq!(KeyImage::deserialize(&image.serialize()).unwrap(), *image);
+  }
+
+  // A tampered share should be rejected
+  let mut tampered = shares.clone();
+  tampered.remove(&1);
+  tampered.get_mut(&2).unwrap()[0] ^= 1;
+  let mut machine = KeyImageMachine::new(&keys[&1], &output, &included).unwrap();
+  machine.share(&mut OsRng);
+  assert!(matches!(machine.complete(tampered), Err(TransactionError::MultisigError(_))));
+
+  // As should a key image which doesn't match its shares
+  let mut wrong = images[0].clone();
+  wrong.image += &ED25519_BASEPOINT_TABLE * &Scalar::one();
+  assert!(wrong.verify(&keys[&1], &output).is_err());
+}
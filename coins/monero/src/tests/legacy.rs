@@ -0,0 +1,324 @@
+use rand::{RngCore, rngs::OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  H, Commitment,
+  random_scalar, hash_to_scalar, hash_to_point,
+  ringct::{
+    borromean::{BorromeanSignature, BorromeanRange},
+    mlsag::Mlsag,
+    EncryptedAmount, RctBase, RctPrunable, RctSignatures
+  },
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{ExtraField, Extra, shared_key}
+};
+
+const RING_LEN: usize = 11;
+
+// Borromean range proofs are no longer created, so this only exists to test verification
+#[allow(non_snake_case)]
+fn borromean_prove(mask: Scalar, amount: u64) -> BorromeanRange {
+  let mut pow_2 = [*H; 64];
+  for i in 1 .. 64 {
+    pow_2[i] = pow_2[i - 1] + pow_2[i - 1];
+  }
+
+  let mut masks = [Scalar::zero(); 64];
+  let mut bit_commitments = [EdwardsPoint::default(); 64];
+  let mut P2 = [EdwardsPoint::default(); 64];
+  for i in 0 .. 64 {
+    masks[i] = if i == 63 {
+      mask - masks.iter().sum::<Scalar>()
+    } else {
+      random_scalar(&mut OsRng)
+    };
+    bit_commitments[i] = &masks[i] * &ED25519_BASEPOINT_TABLE;
+    if ((amount >> i) & 1) == 1 {
+      bit_commitments[i] += pow_2[i];
+    }
+    P2[i] = bit_commitments[i] - pow_2[i];
+  }
+
+  // For each bit, sign with the mask for bit_commitments[i] if it's 0, or for P2[i] if it's 1
+  let mut alpha = [Scalar::zero(); 64];
+  let mut s0 = [Scalar::zero(); 64];
+  let mut s1 = [Scalar::zero(); 64];
+  let mut transcript = vec![];
+  for i in 0 .. 64 {
+    alpha[i] = random_scalar(&mut OsRng);
+    let mut L = &alpha[i] * &ED25519_BASEPOINT_TABLE;
+    if ((amount >> i) & 1) == 0 {
+      s1[i] = random_scalar(&mut OsRng);
+      let c = hash_to_scalar(&L.compress().to_bytes());
+      L = EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, &P2[i], &s1[i]);
+    }
+    transcript.extend(L.compress().to_bytes());
+  }
+  let ee = hash_to_scalar(&transcript);
+
+  for i in 0 .. 64 {
+    if ((amount >> i) & 1) == 0 {
+      s0[i] = alpha[i] - (ee * masks[i]);
+    } else {
+      s0[i] = random_scalar(&mut OsRng);
+      let LL = EdwardsPoint::vartime_double_scalar_mul_basepoint(&ee, &bit_commitments[i], &s0[i]);
+      let c = hash_to_scalar(&LL.compress().to_bytes());
+      s1[i] = alpha[i] - (c * masks[i]);
+    }
+  }
+
+  BorromeanRange { sig: BorromeanSignature { s0, s1, ee }, bit_commitments }
+}
+
+// MLSAGs are also no longer created, solely being signed here to test verification
+#[allow(non_snake_case)]
+fn mlsag_sign(
+  msg: &[u8; 32],
+  ring: &[Vec<EdwardsPoint>],
+  secrets: &[Scalar],
+  linked: usize,
+  i: usize
+) -> Mlsag {
+  let keys = secrets.len();
+  let mut ss = vec![vec![Scalar::zero(); keys]; ring.len()];
+  let alpha = (0 .. keys).map(|_| random_scalar(&mut OsRng)).collect::<Vec<_>>();
+  let images = (0 .. linked).map(|k| secrets[k] * hash_to_point(&ring[i][k])).collect::<Vec<_>>();
+
+  let challenge = |m: usize, ss: Option<&[Scalar]>, c: Scalar| {
+    let mut to_hash = msg.to_vec();
+    for k in 0 .. keys {
+      let key = ring[m][k];
+      to_hash.extend(key.compress().to_bytes());
+      let (L, R) = match ss {
+        Some(ss) => (
+          EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, &key, &ss[k]),
+          (k < linked).then(|| (ss[k] * hash_to_point(&key)) + (c * images[k]))
+        ),
+        None => (
+          &alpha[k] * &ED25519_BASEPOINT_TABLE,
+          (k < linked).then(|| alpha[k] * hash_to_point(&key))
+        )
+      };
+      to_hash.extend(L.compress().to_bytes());
+      if let Some(R) = R {
+        to_hash.extend(R.compress().to_bytes());
+      }
+    }
+    hash_to_scalar(&to_hash)
+  };
+
+  let mut c = challenge(i, None, Scalar::zero());
+  let mut cc = None;
+  for offset in 1 .. ring.len() {
+    let m = (i + offset) % ring.len();
+    if m == 0 {
+      cc = Some(c);
+    }
+    ss[m] = (0 .. keys).map(|_| random_scalar(&mut OsRng)).collect();
+    c = challenge(m, Some(&ss[m]), c);
+  }
+  if i == 0 {
+    cc = Some(c);
+  }
+  ss[i] = (0 .. keys).map(|k| alpha[k] - (c * secrets[k])).collect();
+
+  Mlsag { ss, cc: cc.unwrap() }
+}
+
+fn random_point() -> EdwardsPoint {
+  &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+}
+
+#[test]
+fn borromean() {
+  let commitment = Commitment::new(random_scalar(&mut OsRng), OsRng.next_u64());
+  let range = borromean_prove(commitment.mask, commitment.amount);
+  assert!(range.verify(&commitment.calculate()));
+
+  // It shouldn't verify for any other commitment
+  assert!(!range.verify(&Commitment::new(commitment.mask, commitment.amount ^ 1).calculate()));
+
+  // Nor when tampered with
+  let mut tampered = range.clone();
+  tampered.sig.s1[5] += Scalar::one();
+  assert!(!tampered.verify(&commitment.calculate()));
+
+  let mut serialized = vec![];
+  range.serialize(&mut serialized).unwrap();
+  assert_eq!(BorromeanRange::deserialize(&mut serialized.as_slice()).unwrap(), range);
+}
+
+#[test]
+fn mlsag_simple() {
+  let msg = [1; 32];
+  let real = usize::try_from(OsRng.next_u64() % u64::try_from(RING_LEN).unwrap()).unwrap();
+
+  let secret = random_scalar(&mut OsRng);
+  let input = Commitment::new(random_scalar(&mut OsRng), 1337);
+  let pseudo_out = Commitment::new(random_scalar(&mut OsRng), 1337);
+
+  let mut ring = (0 .. RING_LEN).map(|_| [random_point(), random_point()]).collect::<Vec<_>>();
+  ring[real] = [&secret * &ED25519_BASEPOINT_TABLE, input.calculate()];
+
+  let mlsag = mlsag_sign(
+    &msg,
+    &ring.iter().map(
+      |member| vec![member[0], member[1] - pseudo_out.calculate()]
+    ).collect::<Vec<_>>(),
+    &[secret, input.mask - pseudo_out.mask],
+    1,
+    real
+  );
+  let image = secret * hash_to_point(&ring[real][0]);
+  mlsag.verify_simple(&msg, &ring, &image, &pseudo_out.calculate()).unwrap();
+
+  assert!(mlsag.verify_simple(&[2; 32], &ring, &image, &pseudo_out.calculate()).is_err());
+  assert!(mlsag.verify_simple(&msg, &ring, &random_point(), &pseudo_out.calculate()).is_err());
+  assert!(
+    mlsag.verify_simple(
+      &msg,
+      &ring,
+      &image,
+      &Commitment::new(pseudo_out.mask, 1338).calculate()
+    ).is_err()
+  );
+}
+
+#[test]
+fn mlsag_full() {
+  let msg = [1; 32];
+  let real = 3;
+  let fee = 10;
+
+  let secrets = [random_scalar(&mut OsRng), random_scalar(&mut OsRng)];
+  let inputs = [
+    Commitment::new(random_scalar(&mut OsRng), 100),
+    Commitment::new(random_scalar(&mut OsRng), 50)
+  ];
+  let outputs = [Commitment::new(random_scalar(&mut OsRng), 140)];
+
+  let rings = (0 .. 2).map(|i| {
+    let mut ring = (0 .. RING_LEN).map(|_| [random_point(), random_point()]).collect::<Vec<_>>();
+    ring[real] = [&secrets[i] * &ED25519_BASEPOINT_TABLE, inputs[i].calculate()];
+    ring
+  }).collect::<Vec<_>>();
+  let images = (0 .. 2).map(|i| secrets[i] * hash_to_point(&rings[i][real][0])).collect::<Vec<_>>();
+
+  let out = outputs[0].calculate() + (Scalar::from(fee) * *H);
+  let matrix = (0 .. RING_LEN).map(
+    |m| vec![rings[0][m][0], rings[1][m][0], rings[0][m][1] + rings[1][m][1] - out]
+  ).collect::<Vec<_>>();
+  let mlsag = mlsag_sign(
+    &msg,
+    &matrix,
+    &[secrets[0], secrets[1], inputs[0].mask + inputs[1].mask - outputs[0].mask],
+    2,
+    real
+  );
+
+  mlsag.verify_full(&msg, &rings, &images, &[outputs[0].calculate()], fee).unwrap();
+  // An unbalanced transaction shouldn't verify
+  assert!(mlsag.verify_full(&msg, &rings, &images, &[outputs[0].calculate()], fee + 1).is_err());
+}
+
+fn legacy_signatures(rct_type: u8, inputs: usize, outputs: usize) -> RctSignatures {
+  let mlsag = |keys| Mlsag {
+    ss: (0 .. RING_LEN).map(|_| (0 .. keys).map(|_| random_scalar(&mut OsRng)).collect()).collect(),
+    cc: random_scalar(&mut OsRng)
+  };
+  let range = || BorromeanRange {
+    sig: BorromeanSignature {
+      s0: [(); 64].map(|_| random_scalar(&mut OsRng)),
+      s1: [(); 64].map(|_| random_scalar(&mut OsRng)),
+      ee: random_scalar(&mut OsRng)
+    },
+    bit_commitments: [(); 64].map(|_| random_point())
+  };
+
+  RctSignatures {
+    base: RctBase {
+      fee: 1000,
+      pseudo_outs: if rct_type == 2 {
+        (0 .. inputs).map(|_| random_point()).collect()
+      } else {
+        vec![]
+      },
+      ecdh_info: (0 .. outputs).map(
+        |_| EncryptedAmount::Original { mask: [1; 32], amount: [2; 32] }
+      ).collect(),
+      commitments: (0 .. outputs).map(|_| random_point()).collect()
+    },
+    prunable: RctPrunable::MlsagBorromean {
+      borromean: (0 .. outputs).map(|_| range()).collect(),
+      mlsags: if rct_type == 1 {
+        vec![mlsag(inputs + 1)]
+      } else {
+        (0 .. inputs).map(|_| mlsag(2)).collect()
+      }
+    }
+  }
+}
+
+#[test]
+fn legacy_serialization() {
+  for rct_type in [1, 2] {
+    let signatures = legacy_signatures(rct_type, 2, 3);
+    assert_eq!(signatures.rct_type(), rct_type);
+
+    let mut serialized = vec![];
+    signatures.serialize(&mut serialized).unwrap();
+    assert_eq!(
+      RctSignatures::deserialize(vec![RING_LEN; 2], 3, &mut serialized.as_slice()).unwrap(),
+      signatures
+    );
+  }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn legacy_amount_scan() {
+  let view = random_scalar(&mut OsRng);
+  let spend = random_point();
+
+  let r = random_scalar(&mut OsRng);
+  let R = &r * &ED25519_BASEPOINT_TABLE;
+  let (_, shared) = shared_key(None, r, &(&view * &ED25519_BASEPOINT_TABLE), 0);
+
+  // Encrypt the amount as Monero originally did
+  let commitment = Commitment::new(random_scalar(&mut OsRng), 1337);
+  let mask_key = hash_to_scalar(&shared.to_bytes());
+  let amount_key = hash_to_scalar(&mask_key.to_bytes());
+
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey(R.compress())]).serialize(&mut extra).unwrap();
+
+  let mut signatures = legacy_signatures(2, 1, 1);
+  signatures.base.ecdh_info = vec![
+    EncryptedAmount::Original {
+      mask: (commitment.mask + mask_key).to_bytes(),
+      amount: (Scalar::from(commitment.amount) + amount_key).to_bytes()
+    }
+  ];
+  signatures.base.commitments = vec![commitment.calculate()];
+
+  let tx = Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs: vec![
+        Input::ToKey { amount: 0, key_offsets: vec![1; RING_LEN], key_image: random_point() }
+      ],
+      outputs: vec![
+        Output { amount: 0, key: spend + (&shared * &ED25519_BASEPOINT_TABLE), tag: None }
+      ],
+      extra
+    },
+    signatures: vec![],
+    rct_signatures: signatures
+  };
+
+  let outputs = tx.scan(view, spend).0;
+  assert_eq!(outputs.len(), 1);
+  assert_eq!(outputs[0].commitment, commitment);
+}
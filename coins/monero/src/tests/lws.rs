@@ -0,0 +1,119 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use serde_json::json;
+
+use crate::{
+  Commitment,
+  random_scalar,
+  ringct::{EncryptedAmount, RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    ExtraField, Extra, SubaddressIndex, subaddress_spend_key, Subaddresses,
+    uniqueness, shared_key, amount_encryption, commitment_mask
+  },
+  rpc::UnspentOutput
+};
+
+fn output(tx: [u8; 32], index: u8) -> UnspentOutput {
+  serde_json::from_value(json!({
+    // The server's claimed keys and amounts are ignored, so lies about them are irrelevant
+    "amount": "1000000",
+    "public_key": hex::encode([0; 32]),
+    "index": index,
+    "global_index": 100,
+    "rct": "",
+    "tx_hash": hex::encode(tx),
+    "tx_pub_key": hex::encode([0; 32]),
+    "height": 5,
+    "spend_key_images": []
+  })).unwrap()
+}
+
+#[test]
+fn lws_verify() {
+  let view = random_scalar(&mut OsRng);
+  let spend_scalar = random_scalar(&mut OsRng);
+  let spend = &spend_scalar * &ED25519_BASEPOINT_TABLE;
+
+  let index = SubaddressIndex::new(0, 1);
+  let subaddresses = Subaddresses::new(&view, &spend, &[index]);
+  let sub_spend = subaddress_spend_key(&view, &spend, index);
+
+  let r = random_scalar(&mut OsRng);
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey((&r * &ED25519_BASEPOINT_TABLE).compress())])
+    .serialize(&mut extra).unwrap();
+
+  // A RingCT transaction, whose outputs use the unique derivation
+  let inputs = vec![
+    Input::ToKey {
+      amount: 0,
+      key_offsets: vec![1],
+      key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+    }
+  ];
+  let unique = uniqueness(&inputs);
+  let mut outputs = vec![];
+  let mut ecdh_info = vec![];
+  let mut commitments = vec![];
+  let mut shared = vec![];
+  for (o, (key, amount)) in [(spend, 5), (sub_spend, 6)].iter().enumerate() {
+    let (_, shared_key) = shared_key(Some(unique), r, &(&view * &ED25519_BASEPOINT_TABLE), o);
+    let key = key + (&shared_key * &ED25519_BASEPOINT_TABLE);
+    outputs.push(Output { amount: 0, key, tag: None });
+    ecdh_info.push(EncryptedAmount::Compact { amount: amount_encryption(*amount, shared_key) });
+    commitments.push(Commitment::new(commitment_mask(shared_key), *amount).calculate());
+    shared.push(shared_key);
+  }
+
+  let tx = Transaction {
+    prefix: TransactionPrefix { version: 2, timelock: Timelock::None, inputs, outputs, extra },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info, commitments },
+      prunable: RctPrunable::Null
+    }
+  };
+  let hash = tx.hash();
+
+  let verified = output(hash, 0).verify(&tx, view, spend, &subaddresses).unwrap();
+  assert_eq!(verified.tx, hash);
+  assert_eq!(verified.o, 0);
+  assert_eq!(verified.key_offset, shared[0]);
+  assert_eq!(&(spend_scalar + verified.key_offset) * &ED25519_BASEPOINT_TABLE, verified.key);
+  assert_eq!(verified.commitment, Commitment::new(commitment_mask(shared[0]), 5));
+  assert_eq!(verified.subaddress, None);
+
+  // Outputs to subaddresses are recognized with the subaddress's offset
+  let verified = output(hash, 1).verify(&tx, view, spend, &subaddresses).unwrap();
+  assert_eq!(verified.commitment.amount, 6);
+  assert_eq!(verified.subaddress, Some(index));
+  assert_eq!(&(spend_scalar + verified.key_offset) * &ED25519_BASEPOINT_TABLE, verified.key);
+
+  // A server claiming an output which doesn't exist is caught
+  assert!(output(hash, 2).verify(&tx, view, spend, &subaddresses).is_none());
+  // As is one claiming an output is in a different transaction
+  assert!(output([0xaa; 32], 0).verify(&tx, view, spend, &subaddresses).is_none());
+  // As is one returning outputs which aren't ours
+  let other = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  assert!(output(hash, 0).verify(&tx, view, other, &Subaddresses::default()).is_none());
+
+  // Miner outputs have a clear amount and a mask of 1
+  let (_, shared_key) = shared_key(None, r, &(&view * &ED25519_BASEPOINT_TABLE), 0);
+  let mut miner = tx.clone();
+  miner.prefix.inputs = vec![Input::Gen(5)];
+  miner.prefix.outputs = vec![
+    Output { amount: 7, key: spend + (&shared_key * &ED25519_BASEPOINT_TABLE), tag: None }
+  ];
+  miner.rct_signatures.base = RctBase {
+    fee: 0,
+    pseudo_outs: vec![],
+    ecdh_info: vec![],
+    commitments: vec![]
+  };
+  let verified = output(miner.hash(), 0).verify(&miner, view, spend, &subaddresses).unwrap();
+  assert_eq!(verified.commitment, Commitment::new(Scalar::one(), 7));
+  assert_eq!(verified.key, spend + (&shared_key * &ED25519_BASEPOINT_TABLE));
+}
@@ -1 +1,23 @@
+mod commitment;
+mod fee;
+mod select;
+mod sweep;
+mod epee;
+mod rpc;
+mod zmq;
+mod lws;
+mod address;
+mod subaddress;
+mod view_tag;
 mod clsag;
+mod transaction;
+mod store;
+mod legacy;
+mod proof;
+mod reserve;
+mod cold;
+mod kex;
+mod polyseed;
+mod view;
+#[cfg(feature = "multisig")]
+mod key_image;
@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use rand::rngs::OsRng;
+
+use crate::{
+  hash_to_scalar,
+  wallet::{PolyseedError, Polyseed, polyseed::pbkdf2_sha256}
+};
+
+// November 1st, 2022
+const TIME: u64 = 1667260800;
+
+#[test]
+fn pbkdf2() {
+  // RFC 7914's PBKDF2-HMAC-SHA256 vectors, truncated to 32 bytes
+  assert_eq!(
+    hex::encode(pbkdf2_sha256(b"passwd", b"salt", 1)),
+    "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+  );
+  assert_eq!(
+    hex::encode(pbkdf2_sha256(b"Password", b"NaCl", 80000)),
+    "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"
+  );
+}
+
+#[test]
+fn polyseed_vector() {
+  // The example seed from the reference implementation, tevador/polyseed
+  let seed = Polyseed::from_str(
+    "raven tail swear infant grief assist regular lamp duck valid someone little harsh puppy \
+      airport language"
+  ).unwrap();
+  assert_eq!(
+    hex::encode(seed.secret),
+    "dd76e7359a0ded37cd0ff0f3c829a5ae01673300000000000000000000000000"
+  );
+  // The first month after the epoch
+  assert_eq!(seed.birthday(), 1635768000 + 2629746);
+  assert_eq!(seed.features(), 0);
+  assert!(!seed.encrypted());
+
+  // PBKDF2 over the secret, with polyseed_keygen's salt, reduced into the spend key
+  assert_eq!(
+    hex::encode(seed.spend_key().unwrap().to_bytes()),
+    "6dd6b2029bfdf1c44a36ce8b229f35dcaa5800b8d858da9facf4b0a778dc2800"
+  );
+  assert_eq!(seed.view_key().unwrap(), hash_to_scalar(&seed.spend_key().unwrap().to_bytes()));
+
+  // Encoding the decoded seed reproduces the phrase
+  assert_eq!(
+    seed.to_string(),
+    "raven tail swear infant grief assist regular lamp duck valid someone little harsh puppy \
+      airport language"
+  );
+}
+
+#[test]
+fn polyseed() {
+  let seed = Polyseed::new(&mut OsRng, 0b101, TIME).unwrap();
+  assert_eq!(seed.features(), 0b101);
+  assert!(!seed.encrypted());
+  // Birthdays are rounded down to a twelfth of a year
+  assert!(seed.birthday() <= TIME);
+  assert!((TIME - seed.birthday()) < 2629746);
+
+  let words = seed.to_string();
+  assert_eq!(words.split(' ').count(), 16);
+  let parsed = Polyseed::from_str(&words).unwrap();
+  assert_eq!(parsed, seed);
+  assert_eq!(parsed.to_string(), words);
+
+  let spend = seed.spend_key().unwrap();
+  assert_eq!(parsed.spend_key().unwrap(), spend);
+  assert_eq!(seed.view_key().unwrap(), hash_to_scalar(&spend.to_bytes()));
+  assert!(Polyseed::new(&mut OsRng, 0b101, TIME).unwrap().spend_key().unwrap() != spend);
+
+  // Words may be abbreviated and capitalized
+  let abbreviated = words.split(' ')
+    .map(|word| word.chars().take(4).collect::<String>().to_uppercase())
+    .collect::<Vec<_>>()
+    .join(" ");
+  assert_eq!(Polyseed::from_str(&abbreviated).unwrap(), seed);
+
+  let mut swapped = words.split(' ').collect::<Vec<_>>();
+  swapped.swap(1, 2);
+  if swapped[1] != swapped[2] {
+    assert_eq!(Polyseed::from_str(&swapped.join(" ")), Err(PolyseedError::InvalidChecksum));
+  }
+
+  assert_eq!(
+    Polyseed::from_str(&words.replacen(' ', " zzzz ", 1)),
+    Err(PolyseedError::InvalidWordCount(17))
+  );
+  assert_eq!(
+    Polyseed::from_str(&("zzzz".to_string() + &words[words.find(' ').unwrap() ..])),
+    Err(PolyseedError::UnknownWord(0))
+  );
+  assert_eq!(
+    Polyseed::new(&mut OsRng, 0b1000, TIME),
+    Err(PolyseedError::UnsupportedFeatures(0b1000))
+  );
+}
@@ -0,0 +1,320 @@
+#[cfg(feature = "multisig")]
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+#[cfg(feature = "multisig")]
+use transcript::RecommendedTranscript;
+#[cfg(feature = "multisig")]
+use frost::{
+  curve::Ed25519,
+  sign::{PreprocessMachine, SignMachine, SignatureMachine},
+  tests::{key_gen, recover, sign, clone_without}
+};
+
+use crate::{
+  Commitment,
+  random_scalar, generate_key_image,
+  ringct::{EncryptedAmount, RctBase, RctPrunable, RctSignatures},
+  ring_signature::RingSignature,
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    Network, AddressType, Address, SubaddressIndex, SpendableOutput, TransactionError,
+    ExtraField, Extra, TxProof, SpendProof,
+    subaddress, shared_key, commitment_mask, amount_encryption
+  }
+};
+
+const RING_LEN: usize = 11;
+
+fn transaction(inputs: Vec<Input>, outputs: Vec<Output>, extra: Extra) -> Transaction {
+  let mut serialized = vec![];
+  extra.serialize(&mut serialized).unwrap();
+  Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs,
+      outputs,
+      extra: serialized
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null
+    }
+  }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn tx_proof() {
+  let view = random_scalar(&mut OsRng);
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+
+  for address in [
+    subaddress(Network::Mainnet, &view, &spend, SubaddressIndex::new(0, 0)),
+    subaddress(Network::Mainnet, &view, &spend, SubaddressIndex::new(0, 1))
+  ] {
+    let r = random_scalar(&mut OsRng);
+    let R = if address.kind == AddressType::Subaddress {
+      r * address.spend
+    } else {
+      &r * &ED25519_BASEPOINT_TABLE
+    };
+    let (tag, shared) = shared_key(None, r, &address.view, 0);
+
+    let mut tx = transaction(
+      vec![Input::ToKey {
+        amount: 0,
+        key_offsets: vec![],
+        key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+      }],
+      vec![
+        Output {
+          amount: 0,
+          key: address.spend + (&shared * &ED25519_BASEPOINT_TABLE),
+          tag: Some(tag)
+        },
+        Output { amount: 0, key: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE, tag: None }
+      ],
+      Extra(vec![ExtraField::PublicKey(R.compress())])
+    );
+    tx.rct_signatures.base.ecdh_info = vec![
+      EncryptedAmount::Compact { amount: amount_encryption(5, shared) },
+      EncryptedAmount::Compact { amount: [0; 8] }
+    ];
+    tx.rct_signatures.base.commitments = vec![
+      Commitment::new(commitment_mask(shared), 5).calculate(),
+      Commitment::new(Scalar::one(), 3).calculate()
+    ];
+
+    let out = TxProof::out_proof(&mut OsRng, &tx, b"message", &[r], &address).unwrap();
+    let incoming = TxProof::in_proof(&mut OsRng, &tx, b"message", &view, &address).unwrap();
+    for proof in [out, incoming] {
+      assert_eq!(proof.verify(&tx, b"message", &address), Some(5));
+      assert_eq!(TxProof::deserialize(&proof.serialize()).unwrap(), proof);
+
+      // Proofs are bound to their message and address
+      assert_eq!(proof.verify(&tx, b"other", &address), None);
+      let other = Address::standard(
+        Network::Mainnet,
+        spend,
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+      );
+      assert_eq!(proof.verify(&tx, b"message", &other), None);
+    }
+
+    // An address which wasn't paid is proven to have received nothing
+    let unpaid = Address::standard(
+      Network::Mainnet,
+      &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+      &view * &ED25519_BASEPOINT_TABLE
+    );
+    if address.kind == AddressType::Standard {
+      let proof = TxProof::in_proof(&mut OsRng, &tx, b"message", &view, &unpaid).unwrap();
+      assert_eq!(proof.verify(&tx, b"message", &unpaid), Some(0));
+    }
+
+    assert!(matches!(
+      TxProof::out_proof(&mut OsRng, &tx, b"message", &[random_scalar(&mut OsRng)], &address),
+      Err(TransactionError::WrongPrivateKey)
+    ));
+    assert!(matches!(
+      TxProof::in_proof(&mut OsRng, &tx, b"message", &random_scalar(&mut OsRng), &address),
+      Err(TransactionError::WrongPrivateKey)
+    ));
+  }
+
+  assert!(TxProof::deserialize("OutProofV2").is_none());
+  assert!(TxProof::deserialize("SpendProofV1").is_none());
+}
+
+#[test]
+fn ring_signature() {
+  for real in 0 .. RING_LEN {
+    let msg = [1; 32];
+    let secret = random_scalar(&mut OsRng);
+    let ring = (0 .. RING_LEN).map(|i| if i == real {
+      &secret * &ED25519_BASEPOINT_TABLE
+    } else {
+      &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+    }).collect::<Vec<_>>();
+
+    let image = generate_key_image(&secret);
+    let sig = RingSignature::sign(&mut OsRng, &msg, &ring, real, &secret);
+    assert!(sig.verify(&msg, &ring, &image));
+    assert!(!sig.verify(&[2; 32], &ring, &image));
+    assert!(!sig.verify(&msg, &ring, &generate_key_image(&random_scalar(&mut OsRng))));
+
+    let mut serialized = vec![];
+    sig.serialize(&mut serialized).unwrap();
+    assert_eq!(RingSignature::deserialize(RING_LEN, &mut serialized.as_slice()).unwrap(), sig);
+  }
+}
+
+// A transaction spending each of the specified (key, image) pairs, with the key at the input's
+// index within its ring
+fn spending(outputs: &[(EdwardsPoint, EdwardsPoint)]) -> (Transaction, Vec<Vec<EdwardsPoint>>) {
+  let mut inputs = vec![];
+  let mut rings = vec![];
+  for (i, (key, image)) in outputs.iter().enumerate() {
+    inputs.push(Input::ToKey { amount: 0, key_offsets: vec![1; RING_LEN], key_image: *image });
+    rings.push((0 .. RING_LEN).map(|j| if j == i {
+      *key
+    } else {
+      &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+    }).collect());
+  }
+  (transaction(inputs, vec![], Extra(vec![])), rings)
+}
+
+#[test]
+fn spend_proof() {
+  let spend = random_scalar(&mut OsRng);
+  let outputs = (0 .. 2).map(|_| {
+    let key_offset = random_scalar(&mut OsRng);
+    SpendableOutput {
+      tx: [0; 32],
+      o: 0,
+      key: &(spend + key_offset) * &ED25519_BASEPOINT_TABLE,
+      key_offset,
+      commitment: Commitment::new(Scalar::one(), 1),
+      subaddress: None
+    }
+  }).collect::<Vec<_>>();
+
+  let (tx, rings) = spending(
+    &outputs.iter().map(
+      |output| (output.key, generate_key_image(&(spend + output.key_offset)))
+    ).collect::<Vec<_>>()
+  );
+
+  let proof = SpendProof::new(&mut OsRng, &tx, b"message", &rings, &spend, &outputs).unwrap();
+  assert!(proof.verify(&tx, b"message", &rings));
+  assert!(!proof.verify(&tx, b"other", &rings));
+  assert_eq!(SpendProof::deserialize(&proof.serialize(), &tx).unwrap(), proof);
+
+  let mut swapped = rings.clone();
+  swapped.swap(0, 1);
+  assert!(!proof.verify(&tx, b"message", &swapped));
+
+  assert!(matches!(
+    SpendProof::new(&mut OsRng, &tx, b"message", &rings, &random_scalar(&mut OsRng), &outputs),
+    Err(TransactionError::WrongPrivateKey)
+  ));
+}
+
+#[cfg(feature = "multisig")]
+#[test]
+fn spend_proof_multisig() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+
+  let key_offset = random_scalar(&mut OsRng);
+  let output = SpendableOutput {
+    tx: [0; 32],
+    o: 0,
+    key: keys[&1].group_key().0 + (&key_offset * &ED25519_BASEPOINT_TABLE),
+    key_offset,
+    commitment: Commitment::new(Scalar::one(), 1),
+    subaddress: None
+  };
+
+  let private = recover(
+    &keys.iter().map(|(l, keys)| (*l, (**keys).clone())).collect::<HashMap<_, _>>()
+  ).0 + key_offset;
+  let (tx, rings) = spending(&[(output.key, generate_key_image(&private))]);
+
+  let machines = included.iter().map(|l| (
+    *l,
+    SpendProof::multisig(
+      &keys[l],
+      RecommendedTranscript::new(b"Monero Serai Spend Proof Test"),
+      &tx,
+      &rings,
+      &[output.clone()],
+      &included
+    ).unwrap().swap_remove(0)
+  )).collect::<HashMap<_, _>>();
+
+  let (signature, image) = sign(
+    &mut OsRng,
+    machines,
+    &SpendProof::signature_hash(&tx, b"message")
+  );
+  assert_eq!(image, generate_key_image(&private));
+  assert!(SpendProof { signatures: vec![signature] }.verify(&tx, b"message", &rings));
+}
+
+#[cfg(feature = "multisig")]
+#[test]
+fn spend_proof_multisig_resumed() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+  let private = recover(
+    &keys.iter().map(|(l, keys)| (*l, (**keys).clone())).collect::<HashMap<_, _>>()
+  ).0;
+
+  let outputs = (0 .. 2).map(|_| {
+    let key_offset = random_scalar(&mut OsRng);
+    SpendableOutput {
+      tx: [0; 32],
+      o: 0,
+      key: keys[&1].group_key().0 + (&key_offset * &ED25519_BASEPOINT_TABLE),
+      key_offset,
+      commitment: Commitment::new(Scalar::one(), 1),
+      subaddress: None
+    }
+  }).collect::<Vec<_>>();
+  let (tx, rings) = spending(
+    &outputs.iter().map(
+      |output| (output.key, generate_key_image(&(private + output.key_offset)))
+    ).collect::<Vec<_>>()
+  );
+
+  let machines = |l: u16| SpendProof::multisig(
+    &keys[&l],
+    RecommendedTranscript::new(b"Monero Serai Spend Proof Test"),
+    &tx,
+    &rings,
+    &outputs,
+    &included
+  ).unwrap();
+  let msg = SpendProof::signature_hash(&tx, b"message");
+
+  // Sign every input, suspending and resuming every signer's machine before completing
+  let mut signatures = vec![];
+  for input in 0 .. outputs.len() {
+    let mut preprocesses = HashMap::new();
+    let sign_machines = included.iter().map(|l| {
+      let (machine, preprocess) = machines(*l).swap_remove(input).preprocess(&mut OsRng);
+      preprocesses.insert(*l, preprocess);
+      (*l, machine)
+    }).collect::<Vec<_>>();
+
+    let mut shares = HashMap::new();
+    let signature_machines = sign_machines.into_iter().map(|(l, machine)| {
+      let (machine, share) = machine.sign(clone_without(&preprocesses, &l), &msg).unwrap();
+      shares.insert(l, share);
+      (l, machine)
+    }).collect::<Vec<_>>();
+
+    let mut signature = None;
+    for (l, machine) in signature_machines {
+      let resumed = machines(l).swap_remove(input)
+        .resume_signature(&mut OsRng, &mut machine.suspend().as_slice())
+        .unwrap();
+      let (sig, image) = resumed.complete(clone_without(&shares, &l)).unwrap();
+      assert_eq!(image, generate_key_image(&(private + outputs[input].key_offset)));
+      assert_eq!(signature.get_or_insert_with(|| sig.clone()), &sig);
+    }
+    signatures.push(signature.unwrap());
+  }
+
+  let proof = SpendProof { signatures };
+  assert!(proof.verify(&tx, b"message", &rings));
+  assert!(!proof.verify(&tx, b"other", &rings));
+}
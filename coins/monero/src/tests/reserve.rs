@@ -0,0 +1,215 @@
+#[cfg(feature = "multisig")]
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+#[cfg(feature = "multisig")]
+use transcript::RecommendedTranscript;
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, tests::{key_gen, recover, sign}};
+
+use crate::{
+  Commitment,
+  random_scalar,
+  serialize::write_varint,
+  ringct::{EncryptedAmount, RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    Network, AddressType, Address, SubaddressIndex, Subaddresses, ExtraField, Extra, ReserveProof,
+    subaddress, shared_key, commitment_mask, amount_encryption, base58
+  }
+};
+#[cfg(feature = "multisig")]
+use crate::{generate_key_image, wallet::ReserveProofMultisig};
+
+// A transaction paying each address the specified amount
+#[allow(non_snake_case)]
+fn paying(payments: &[(Address, u64)]) -> Transaction {
+  let mut keys = vec![];
+  let mut outputs = vec![];
+  let mut ecdh_info = vec![];
+  let mut commitments = vec![];
+  for (o, (address, amount)) in payments.iter().enumerate() {
+    let r = random_scalar(&mut OsRng);
+    keys.push(
+      if address.kind == AddressType::Subaddress {
+        r * address.spend
+      } else {
+        &r * &ED25519_BASEPOINT_TABLE
+      }.compress()
+    );
+
+    let (tag, shared) = shared_key(None, r, &address.view, o);
+    outputs.push(Output {
+      amount: 0,
+      key: address.spend + (&shared * &ED25519_BASEPOINT_TABLE),
+      tag: Some(tag)
+    });
+    ecdh_info.push(EncryptedAmount::Compact { amount: amount_encryption(*amount, shared) });
+    commitments.push(Commitment::new(commitment_mask(shared), *amount).calculate());
+  }
+
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey(keys[0]), ExtraField::PublicKeys(keys)])
+    .serialize(&mut extra).unwrap();
+  Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs: vec![Input::ToKey {
+        amount: 0,
+        key_offsets: vec![],
+        key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+      }],
+      outputs,
+      extra
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info, commitments },
+      prunable: RctPrunable::Null
+    }
+  }
+}
+
+fn primary(view: &Scalar, spend: &EdwardsPoint) -> Address {
+  Address::standard(Network::Mainnet, *spend, view * &ED25519_BASEPOINT_TABLE)
+}
+
+#[test]
+fn reserve_proof() {
+  let view = random_scalar(&mut OsRng);
+  let spend = random_scalar(&mut OsRng);
+  let spend_pub = &spend * &ED25519_BASEPOINT_TABLE;
+  let address = primary(&view, &spend_pub);
+  let index = SubaddressIndex::new(0, 1);
+
+  let tx = paying(&[
+    (address, 5),
+    (subaddress(Network::Mainnet, &view, &spend_pub, index), 7),
+    // Another wallet's output, under the same spend key
+    (
+      Address::standard(
+        Network::Mainnet,
+        spend_pub,
+        &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+      ),
+      11
+    )
+  ]);
+  let outputs = tx.scan_subaddresses(
+    view,
+    spend_pub,
+    &Subaddresses::new(&view, &spend_pub, &[index])
+  ).0;
+  assert_eq!(outputs.len(), 2);
+
+  let proof = ReserveProof::new(
+    &mut OsRng,
+    b"message",
+    &view,
+    &spend,
+    &outputs.iter().map(|output| (&tx, output)).collect::<Vec<_>>()
+  ).unwrap();
+  // The primary address and subaddress which received outputs both signed
+  assert_eq!(proof.spend_keys.len(), 2);
+
+  let txs = [tx.clone(), tx.clone()];
+  assert_eq!(proof.verify(&address, b"message", &txs), Some(12));
+  assert_eq!(proof.amounts(&address, b"message", &txs), Some(vec![5, 7]));
+  assert_eq!(ReserveProof::deserialize(&proof.serialize()).unwrap(), proof);
+
+  assert_eq!(proof.verify(&address, b"other", &txs), None);
+  assert_eq!(proof.verify(&address, b"message", &txs[.. 1]), None);
+  let other = primary(&view, &(&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE));
+  assert_eq!(proof.verify(&other, b"message", &txs), None);
+
+  // Outputs can't be counted multiple times
+  let mut duplicated = proof.clone();
+  duplicated.entries.push(duplicated.entries[0].clone());
+  assert_eq!(
+    duplicated.verify(&address, b"message", &[tx.clone(), tx.clone(), tx.clone()]),
+    None
+  );
+
+  // Another wallet's outputs can't be proven
+  let stolen = ReserveProof::new(
+    &mut OsRng,
+    b"message",
+    &view,
+    &random_scalar(&mut OsRng),
+    &[(&tx, &outputs[0])]
+  ).unwrap();
+  assert!(stolen.verify(&address, b"message", &[tx.clone()]).is_none());
+}
+
+#[test]
+fn reserve_proof_length() {
+  // A proof declaring far more entries than it contains errors, instead of allocating for them
+  let mut huge = vec![];
+  write_varint(&(1 << 62), &mut huge).unwrap();
+  assert!(ReserveProof::deserialize_raw(&mut huge.as_slice()).is_err());
+  let encoded = "ReserveProofV2".to_string() + &base58::encode(&huge);
+  assert!(ReserveProof::deserialize(&encoded).is_none());
+
+  // As does one declaring no entries, yet far more spend keys
+  let mut keys = vec![0];
+  write_varint(&u64::MAX, &mut keys).unwrap();
+  assert!(ReserveProof::deserialize_raw(&mut keys.as_slice()).is_err());
+}
+
+#[cfg(feature = "multisig")]
+#[test]
+fn reserve_proof_multisig() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let included = (1 ..= keys[&1].params().t()).collect::<Vec<_>>();
+  let private = recover(
+    &keys.iter().map(|(l, keys)| (*l, (**keys).clone())).collect::<HashMap<_, _>>()
+  ).0;
+
+  let view = random_scalar(&mut OsRng);
+  let spend = keys[&1].group_key().0;
+  let address = primary(&view, &spend);
+  let tx = paying(&[(address, 3), (address, 4)]);
+  let outputs = tx.scan(view, spend).0;
+  assert_eq!(outputs.len(), 2);
+
+  let images = outputs.iter().map(|output| generate_key_image(&(private + output.key_offset)))
+    .collect::<Vec<_>>();
+  let proven = outputs.iter().zip(&images).map(|(output, image)| (&tx, output, *image))
+    .collect::<Vec<_>>();
+
+  let mut proofs = HashMap::new();
+  let mut machines = HashMap::new();
+  for l in &included {
+    let (proof, these) = ReserveProofMultisig::new(
+      &mut OsRng,
+      &keys[l],
+      RecommendedTranscript::new(b"Monero Serai Reserve Proof Test"),
+      &view,
+      b"message",
+      &proven,
+      &included
+    ).unwrap();
+    proofs.insert(*l, proof);
+    machines.insert(*l, these);
+  }
+
+  let proof = proofs.remove(&included[0]).unwrap();
+  let msg = proof.hash();
+  let key_images = (0 .. outputs.len()).map(|_| sign(
+    &mut OsRng,
+    machines.iter_mut().map(|(l, these)| (*l, these.key_images.remove(0))).collect(),
+    &msg
+  )).collect::<Vec<_>>();
+  let spend_keys = vec![sign(
+    &mut OsRng,
+    machines.iter_mut().map(|(l, these)| (*l, these.spend_keys.remove(0))).collect(),
+    &msg
+  )];
+
+  let proof = proof.complete(key_images, spend_keys).unwrap();
+  assert_eq!(proof.verify(&address, b"message", &[tx.clone(), tx]), Some(7));
+}
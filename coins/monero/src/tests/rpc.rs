@@ -0,0 +1,78 @@
+use serde_json::json;
+
+use crate::rpc::{Rejection, SendRawResponse, DaemonInfo, healthy};
+
+fn response(flags: serde_json::Value) -> SendRawResponse {
+  let mut res = json!({ "status": "Failed", "reason": "", "not_relayed": false });
+  res.as_object_mut().unwrap().extend(flags.as_object().unwrap().clone());
+  serde_json::from_value(res).unwrap()
+}
+
+#[test]
+fn rejections() {
+  assert_eq!(
+    response(json!({ "double_spend": true, "fee_too_low": false })).rejections(),
+    vec![Rejection::DoubleSpend]
+  );
+  assert_eq!(
+    response(json!({ "fee_too_low": true, "too_big": true, "tx_extra_too_big": true })).rejections(),
+    vec![Rejection::FeeTooLow, Rejection::TooBig, Rejection::TxExtraTooBig]
+  );
+  // Without any flags, the reason is still surfaced
+  assert_eq!(
+    response(json!({ "reason": "Failed to parse transaction" })).rejections(),
+    vec![Rejection::Other("Failed to parse transaction".to_string())]
+  );
+}
+
+#[test]
+fn daemon_health() {
+  let info = |height, target_height, synchronized, version| {
+    Some(DaemonInfo { height, target_height, synchronized, version })
+  };
+
+  assert_eq!(
+    healthy(
+      &[
+        info(100, 0, true, 0x30000),
+        // Within max_lag
+        info(98, 100, true, 0x30000),
+        // Stale
+        info(97, 0, true, 0x30000),
+        // Still syncing
+        info(100, 200, false, 0x30000),
+        // Too old
+        info(100, 0, true, 0x20000),
+        // Didn't respond
+        None
+      ],
+      0x30000,
+      2
+    ),
+    vec![true, true, false, false, false, false]
+  );
+
+  // A single daemon claiming an inflated height doesn't make the honest daemons stale
+  assert_eq!(
+    healthy(
+      &[
+        info(100, 0, true, 0x30000),
+        info(99, 0, true, 0x30000),
+        info(1_000_000, 0, true, 0x30000),
+        // Yet a daemon behind the median still is
+        info(90, 0, true, 0x30000)
+      ],
+      0x30000,
+      2
+    ),
+    vec![true, true, true, false]
+  );
+  assert_eq!(
+    healthy(&[info(100, 0, true, 0x30000), info(1_000_000, 0, true, 0x30000)], 0, 2),
+    vec![true, true]
+  );
+
+  // Synchronized daemons far behind their own target are also stale
+  assert_eq!(healthy(&[info(100, 110, true, 0x30000)], 0, 2), vec![false]);
+  assert_eq!(healthy(&[None, None], 0, 2), vec![false, false]);
+}
@@ -0,0 +1,93 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Commitment,
+  random_scalar,
+  transaction::Timelock,
+  wallet::{SpendableOutput, Candidate, InputSelector, LargestFirst, BranchAndBound, AgeAware}
+};
+
+fn candidates(amounts: &[(u64, usize)]) -> Vec<Candidate> {
+  amounts.iter().map(|(amount, height)| Candidate {
+    output: SpendableOutput {
+      tx: [0; 32],
+      o: 0,
+      key: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+      key_offset: Scalar::zero(),
+      commitment: Commitment::new(Scalar::one(), *amount),
+      subaddress: None
+    },
+    height: *height,
+    timelock: Timelock::None
+  }).collect()
+}
+
+// 10 per input, and 5 for a change output
+fn fee(inputs: usize, change: bool) -> u64 {
+  (u64::try_from(inputs).unwrap() * 10) + if change { 5 } else { 0 }
+}
+
+fn sorted(mut indexes: Vec<usize>) -> Vec<usize> {
+  indexes.sort();
+  indexes
+}
+
+#[test]
+fn largest_first() {
+  let candidates = candidates(&[(100, 0), (500, 0), (300, 0)]);
+  assert_eq!(LargestFirst.select(&candidates, 400, &fee), Some(vec![1]));
+  assert_eq!(sorted(LargestFirst.select(&candidates, 600, &fee).unwrap()), vec![1, 2]);
+  // The fee for each additional input is accounted for
+  assert_eq!(sorted(LargestFirst.select(&candidates, 775, &fee).unwrap()), vec![1, 2]);
+  assert_eq!(sorted(LargestFirst.select(&candidates, 780, &fee).unwrap()), vec![0, 1, 2]);
+  assert_eq!(LargestFirst.select(&candidates, 870, &fee), None);
+}
+
+#[test]
+fn branch_and_bound() {
+  let candidates = candidates(&[(500, 0), (320, 0), (215, 0), (90, 0)]);
+
+  // 215 + 90 exactly covers 285 and the fee for two inputs, where LargestFirst would have change
+  assert_eq!(sorted(BranchAndBound::default().select(&candidates, 285, &fee).unwrap()), vec![2, 3]);
+  assert_eq!(LargestFirst.select(&candidates, 285, &fee), Some(vec![0]));
+
+  // Within the cost of change of 310, but not exact, is preferred to anything with change
+  assert_eq!(BranchAndBound::default().select(&candidates, 306, &fee), Some(vec![1]));
+
+  // Without any changeless set, falls back to LargestFirst
+  assert_eq!(BranchAndBound::default().select(&candidates, 400, &fee), Some(vec![0]));
+  assert_eq!(BranchAndBound::default().select(&candidates, 2000, &fee), None);
+}
+
+#[test]
+fn age_aware() {
+  let candidates = candidates(&[(500, 95), (300, 50), (200, 10)]);
+  let selector = AgeAware::new(LargestFirst, 100, 10);
+  // The largest output is too young to be spent
+  assert_eq!(selector.select(&candidates, 200, &fee), Some(vec![1]));
+  assert_eq!(sorted(selector.select(&candidates, 450, &fee).unwrap()), vec![1, 2]);
+  assert_eq!(selector.select(&candidates, 600, &fee), None);
+}
+
+#[test]
+fn spendable() {
+  let mut candidate = candidates(&[(100, 50)]).swap_remove(0);
+  // Outputs must be buried under 10 blocks
+  assert!(!candidate.spendable_at(59, 0));
+  assert!(candidate.spendable_at(60, 0));
+
+  // Block timelocks must also have passed
+  candidate.timelock = Timelock::Block(70);
+  assert!(!candidate.spendable_at(69, 0));
+  assert!(candidate.spendable_at(70, 0));
+
+  // Time timelocks must have passed as of the chain's time
+  candidate.timelock = Timelock::Time(1_600_000_000);
+  assert!(!candidate.spendable_at(1_000_000, 1_599_999_999));
+  assert!(candidate.spendable_at(1_000_000, 1_600_000_000));
+  assert!(candidate.spendable_at(1_000_000, 1_700_000_000));
+  // Regardless of the time, outputs must still mature
+  assert!(!candidate.spendable_at(59, 1_700_000_000));
+}
@@ -0,0 +1,131 @@
+use rand::{RngCore, rngs::OsRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    ExtraField, Extra, Subaddresses, shared_key,
+    WalletStore, scan_into, MemWalletStore, FileWalletStore
+  }
+};
+
+// A miner transaction, with a clear amount, sending the specified amount to this wallet
+#[allow(non_snake_case)]
+fn receive(view: Scalar, spend: EdwardsPoint, amount: u64, inputs: Vec<Input>) -> Transaction {
+  let r = random_scalar(&mut OsRng);
+  let (_, shared) = shared_key(None, r, &(&view * &ED25519_BASEPOINT_TABLE), 0);
+
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey((&r * &ED25519_BASEPOINT_TABLE).compress())])
+    .serialize(&mut extra).unwrap();
+
+  Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs,
+      outputs: vec![
+        Output { amount, key: spend + (&shared * &ED25519_BASEPOINT_TABLE), tag: None }
+      ],
+      extra
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null
+    }
+  }
+}
+
+fn store<S: WalletStore>(mut store: S) -> S {
+  let view = random_scalar(&mut OsRng);
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  let subaddresses = Subaddresses::default();
+
+  let first = receive(view, spend, 5, vec![Input::Gen(1)]);
+  let recorded = scan_into(&mut store, &first, 1, view, spend, &subaddresses).unwrap();
+  assert_eq!(recorded.received, 5);
+  assert_eq!(recorded.spent, 0);
+  store.scanned_to_height(2);
+
+  // Transactions unrelated to the wallet aren't recorded
+  let other = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  assert!(
+    scan_into(&mut store, &receive(view, other, 3, vec![]), 2, view, spend, &subaddresses).is_none()
+  );
+
+  let unspent = store.unspent();
+  assert_eq!(unspent.len(), 1);
+  assert_eq!(unspent[0].height, 1);
+  // Outputs aren't spendable until they've matured
+  assert!(store.spendable(10, 0).is_empty());
+  assert_eq!(store.spendable(11, 0), unspent);
+  // Rescanning doesn't duplicate the output or the transaction
+  scan_into(&mut store, &first, 1, view, spend, &subaddresses).unwrap();
+  assert_eq!(store.unspent(), unspent);
+  assert_eq!(store.transactions().len(), 1);
+
+  // Spend it, receiving change
+  let key_image = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+  store.set_key_image(unspent[0].output.tx, unspent[0].output.o, key_image);
+  let second = receive(
+    view,
+    spend,
+    2,
+    vec![Input::ToKey { amount: 0, key_offsets: vec![1], key_image }]
+  );
+  let recorded = scan_into(&mut store, &second, 3, view, spend, &subaddresses).unwrap();
+  assert_eq!(recorded.received, 2);
+  assert_eq!(recorded.spent, 5);
+  store.scanned_to_height(4);
+
+  let unspent = store.unspent();
+  assert_eq!(unspent.len(), 1);
+  assert_eq!(unspent[0].output.commitment.amount, 2);
+  assert_eq!(store.transactions().len(), 2);
+  assert_eq!(store.scanned_height(), 4);
+
+  store
+}
+
+#[test]
+fn mem_store() {
+  let store = store(MemWalletStore::new());
+
+  let mut serialized = vec![];
+  store.serialize(&mut serialized).unwrap();
+  assert_eq!(MemWalletStore::deserialize(&mut serialized.as_slice()).unwrap(), store);
+}
+
+#[test]
+fn file_store() {
+  let path = std::env::temp_dir().join(format!("monero-serai-store-{}", OsRng.next_u64()));
+  let store = store(FileWalletStore::open(&path).unwrap());
+  store.save().unwrap();
+
+  let reopened = FileWalletStore::open(&path).unwrap();
+  assert_eq!(reopened, store);
+  assert_eq!(reopened.transactions().len(), 2);
+  std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn revert_store() {
+  let mut store = store(MemWalletStore::new());
+
+  // Reverting removes the change received in the latest block, yet the output it spent stays spent
+  let removed = store.revert(3);
+  assert_eq!(removed.len(), 1);
+  assert_eq!(removed[0].height, 3);
+  assert_eq!(removed[0].output.commitment.amount, 2);
+  assert!(store.unspent().is_empty());
+  assert_eq!(store.transactions().len(), 1);
+  assert_eq!(store.scanned_height(), 3);
+
+  // Reverting to a height after the scanned height doesn't advance it
+  assert!(store.revert(5).is_empty());
+  assert_eq!(store.scanned_height(), 3);
+}
@@ -0,0 +1,52 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Commitment,
+  random_scalar,
+  wallet::{
+    Network, SubaddressIndex, subaddress_spend_key, subaddress, Subaddresses, SpendableOutput
+  }
+};
+
+#[test]
+fn subaddresses() {
+  let view = random_scalar(&mut OsRng);
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+
+  // The primary index is the standard address
+  let primary = SubaddressIndex::new(0, 0);
+  assert_eq!(subaddress_spend_key(&view, &spend, primary), spend);
+  let address = subaddress(Network::Mainnet, &view, &spend, primary);
+  assert_eq!(address.spend, spend);
+  assert_eq!(address.view, &view * &ED25519_BASEPOINT_TABLE);
+
+  let index = SubaddressIndex::new(1, 2);
+  let key = subaddress_spend_key(&view, &spend, index);
+  assert!(key != spend);
+  assert!(key != subaddress_spend_key(&view, &spend, SubaddressIndex::new(2, 1)));
+  let address = subaddress(Network::Mainnet, &view, &spend, index);
+  assert_eq!(address.spend, key);
+  assert_eq!(address.view, view * key);
+
+  let subaddresses = Subaddresses::new(&view, &spend, &[primary, index]);
+  assert_eq!(subaddresses.get(&key), Some(index));
+  // The primary address is recognized without the table
+  assert_eq!(subaddresses.get(&spend), None);
+}
+
+#[test]
+fn spendable_output_serialization() {
+  for subaddress in [None, Some(SubaddressIndex::new(3, 4))] {
+    let output = SpendableOutput {
+      tx: [0xaa; 32],
+      o: 1,
+      key: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+      key_offset: random_scalar(&mut OsRng),
+      commitment: Commitment::new(Scalar::one(), 5),
+      subaddress
+    };
+    assert_eq!(SpendableOutput::deserialize(&mut output.serialize().as_slice()).unwrap(), output);
+  }
+}
@@ -0,0 +1,167 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  Commitment,
+  random_scalar,
+  transaction::Timelock,
+  wallet::{
+    Network, Address, Fee, SpendableOutput, Candidate, BranchAndBound, TransactionError,
+    SignableTransaction
+  }
+};
+
+const FEE: Fee = Fee { per_weight: 20, mask: 10000 };
+
+fn address() -> Address {
+  Address::standard(
+    Network::Mainnet,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+  )
+}
+
+fn output(amount: u64) -> SpendableOutput {
+  SpendableOutput {
+    tx: [0; 32],
+    o: 0,
+    key: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    key_offset: Scalar::zero(),
+    commitment: Commitment::new(Scalar::one(), amount),
+    subaddress: None
+  }
+}
+
+fn candidate(amount: u64, height: usize) -> Candidate {
+  Candidate { output: output(amount), height, timelock: Timelock::None }
+}
+
+#[test]
+fn sweep() {
+  let (destination, change) = (address(), address());
+
+  let single = SignableTransaction::sweep_single(output(10_000_000), destination, change, FEE)
+    .unwrap();
+  let double = SignableTransaction::sweep(
+    vec![output(10_000_000), output(10_000_000)],
+    destination,
+    change,
+    FEE
+  ).unwrap();
+  // The fee is taken from the amount swept, growing with the amount of inputs
+  assert!(single.fee() < double.fee());
+
+  assert!(matches!(
+    SignableTransaction::sweep_single(output(single.fee()), destination, change, FEE),
+    Err(TransactionError::NotEnoughFunds(..))
+  ));
+  assert!(matches!(
+    SignableTransaction::sweep(vec![], destination, change, FEE),
+    Err(TransactionError::NoInputs)
+  ));
+}
+
+#[test]
+fn excess() {
+  let payments = vec![(address(), 1_000_000), (address(), 1_000_000)];
+
+  // Without a change address, excess funds are an error instead of being burnt as the fee
+  let res = SignableTransaction::new(vec![output(10_000_000)], payments.clone(), None, FEE);
+  let excess = match res {
+    Err(TransactionError::ExcessFunds(excess)) => excess,
+    res => panic!("burnt excess funds: {:?}", res)
+  };
+  let fee = 8_000_000 - excess;
+
+  // Dust is still allowed to go to the fee
+  let tx = SignableTransaction::new(vec![output(2_000_001 + fee)], payments.clone(), None, FEE)
+    .unwrap();
+  assert_eq!(tx.fee(), fee + 1);
+
+  // With a change address, the excess is sent to it
+  let tx = SignableTransaction::new(vec![output(10_000_000)], payments, Some(address()), FEE)
+    .unwrap();
+  assert!(tx.fee() < excess);
+}
+
+#[test]
+fn select_without_change() {
+  let payments = vec![(address(), 1_000_000), (address(), 1_000_000)];
+  let fee = match SignableTransaction::new(vec![output(10_000_000)], payments.clone(), None, FEE) {
+    Err(TransactionError::ExcessFunds(excess)) => 8_000_000 - excess,
+    res => panic!("burnt excess funds: {:?}", res)
+  };
+
+  // Without a change address, the inputs selected must cover the payments without change
+  let candidates = [candidate(10_000_000, 0), candidate(2_000_001 + fee, 0)];
+  let tx = SignableTransaction::select(
+    &BranchAndBound::default(),
+    &candidates,
+    100,
+    0,
+    payments.clone(),
+    None,
+    FEE
+  ).unwrap();
+  assert_eq!(tx.inputs(), &[candidates[1].output.clone()]);
+  assert_eq!(tx.fee(), fee + 1);
+
+  // If no set of inputs avoids change, the excess is an error
+  assert!(matches!(
+    SignableTransaction::select(
+      &BranchAndBound::default(),
+      &candidates[.. 1],
+      100,
+      0,
+      payments,
+      None,
+      FEE
+    ),
+    Err(TransactionError::ExcessFunds(_))
+  ));
+}
+
+#[test]
+fn sweep_all() {
+  let (destination, change) = (address(), address());
+
+  // Immature outputs and outputs worth less than the fee to spend them are skipped
+  let txs = SignableTransaction::sweep_all(
+    &[candidate(10_000_000, 0), candidate(10_000_000, 95), candidate(1, 0)],
+    100,
+    0,
+    destination,
+    change,
+    FEE
+  ).unwrap();
+  assert_eq!(txs.len(), 1);
+  assert_eq!(
+    txs[0],
+    SignableTransaction::sweep_single(txs[0].inputs()[0].clone(), destination, change, FEE).unwrap()
+  );
+
+  assert!(matches!(
+    SignableTransaction::sweep_all(&[candidate(1, 0)], 100, 0, destination, change, FEE),
+    Err(TransactionError::NoInputs)
+  ));
+
+  // Too many inputs for a single transaction are spread evenly across several
+  let candidates = (0 .. 1000).map(|_| candidate(10_000_000, 0)).collect::<Vec<_>>();
+  let txs = SignableTransaction::sweep_all(&candidates, 100, 0, destination, change, FEE).unwrap();
+  assert!(txs.len() > 1);
+  assert_eq!(txs.iter().map(|tx| tx.inputs().len()).sum::<usize>(), 1000);
+  let max = txs.iter().map(|tx| tx.inputs().len()).max().unwrap();
+  assert!(txs.iter().all(|tx| (tx.inputs().len() + 1) >= max));
+
+  // Sweeping them all at once would exceed the weight limit
+  assert!(matches!(
+    SignableTransaction::sweep(
+      candidates.iter().map(|candidate| candidate.output.clone()).collect(),
+      destination,
+      change,
+      FEE
+    ),
+    Err(TransactionError::TooLarge)
+  ));
+}
@@ -0,0 +1,97 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY};
+
+use crate::{
+  random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{ExtraField, Extra}
+};
+
+fn random_key() -> CompressedEdwardsY {
+  (&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE).compress()
+}
+
+#[test]
+fn extra() {
+  let extra = Extra(vec![
+    ExtraField::PublicKey(random_key()),
+    ExtraField::Nonce(vec![0, 1, 2, 3]),
+    ExtraField::PublicKeys(vec![random_key(), random_key()]),
+    ExtraField::MergeMining(vec![5; 33]),
+    ExtraField::Padding(10)
+  ]);
+  let mut serialized = vec![];
+  extra.serialize(&mut serialized).unwrap();
+  assert_eq!(Extra::deserialize(&serialized), extra);
+
+  let keys = extra.keys();
+  assert_eq!(keys.len(), 3);
+  if let (ExtraField::PublicKey(key), ExtraField::PublicKeys(additional)) = (
+    &extra.0[0],
+    &extra.0[2]
+  ) {
+    assert_eq!(keys[0].compress(), *key);
+    assert_eq!(keys[1 ..].iter().map(|key| key.compress()).collect::<Vec<_>>(), *additional);
+  }
+
+  // Fields prior to a malformed field should still be parsed
+  let mut truncated = serialized[.. 33].to_vec();
+  truncated.extend([2, 10, 0]);
+  assert_eq!(Extra::deserialize(&truncated).0, vec![extra.0[0].clone()]);
+
+  // Padding must be zeroes, and at most 255 bytes in total
+  assert_eq!(Extra::deserialize(&[0; 255]).0, vec![ExtraField::Padding(255)]);
+  assert!(Extra::deserialize(&[0; 256]).0.is_empty());
+  assert!(Extra::deserialize(&[0, 0, 1]).0.is_empty());
+}
+
+#[test]
+fn v1_transaction() {
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey(random_key())]).serialize(&mut extra).unwrap();
+
+  let tx = Transaction {
+    prefix: TransactionPrefix {
+      version: 1,
+      timelock: Timelock::None,
+      inputs: vec![
+        Input::ToKey {
+          amount: 10_000,
+          key_offsets: vec![1, 2, 3],
+          key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+        },
+        Input::ToKey {
+          amount: 5_000,
+          key_offsets: vec![4],
+          key_image: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+        }
+      ],
+      outputs: vec![
+        Output {
+          amount: 14_000,
+          key: &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+          tag: None
+        }
+      ],
+      extra
+    },
+    signatures: vec![
+      (0 .. 3).map(|_| (random_scalar(&mut OsRng), random_scalar(&mut OsRng))).collect(),
+      vec![(random_scalar(&mut OsRng), random_scalar(&mut OsRng))]
+    ],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null
+    }
+  };
+
+  let mut serialized = vec![];
+  tx.serialize(&mut serialized).unwrap();
+  // Version 1 transactions have no RingCT data, not even its type byte
+  let mut prefix = vec![];
+  tx.prefix.serialize(&mut prefix).unwrap();
+  assert_eq!(serialized.len(), prefix.len() + (4 * 64));
+  assert_eq!(Transaction::deserialize(&mut serialized.as_slice()).unwrap(), tx);
+}
@@ -0,0 +1,310 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  random_scalar,
+  rpc::Rpc,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    Network, AddressType, Address, SubaddressIndex, Fee, TransactionError, ExtraField, Extra,
+    LargestFirst, MemWalletStore, WalletStore, SignedKeyImage, ScanningWallet, ViewWallet,
+    SpendWallet, ReceivedOutput, PaymentEvent, PaymentSubscription, MempoolEvent,
+    shared_key, subaddress
+  }
+};
+
+const FEE: Fee = Fee { per_weight: 20, mask: 10000 };
+
+// A miner transaction, with a clear amount, sending the specified amount to the address
+#[allow(non_snake_case)]
+fn receive(address: &Address, amount: u64, inputs: Vec<Input>) -> Transaction {
+  let r = random_scalar(&mut OsRng);
+  let R = if address.kind == AddressType::Subaddress {
+    r * address.spend
+  } else {
+    &r * &ED25519_BASEPOINT_TABLE
+  };
+  let (_, shared) = shared_key(None, r, &address.view, 0);
+
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey(R.compress())]).serialize(&mut extra).unwrap();
+
+  Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs,
+      outputs: vec![
+        Output { amount, key: address.spend + (&shared * &ED25519_BASEPOINT_TABLE), tag: None }
+      ],
+      extra
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null
+    }
+  }
+}
+
+fn spending(key_image: EdwardsPoint) -> Transaction {
+  let other = Address::standard(
+    Network::Mainnet,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
+  );
+  receive(&other, 1, vec![Input::ToKey { amount: 0, key_offsets: vec![1], key_image }])
+}
+
+fn keys() -> (Scalar, Scalar, Address) {
+  let (spend, view) = (random_scalar(&mut OsRng), random_scalar(&mut OsRng));
+  let address = Address::standard(
+    Network::Mainnet,
+    &spend * &ED25519_BASEPOINT_TABLE,
+    &view * &ED25519_BASEPOINT_TABLE
+  );
+  (spend, view, address)
+}
+
+#[test]
+fn view_wallet() {
+  let (spend, view, address) = keys();
+
+  assert!(matches!(
+    ViewWallet::new(address, random_scalar(&mut OsRng), MemWalletStore::new()),
+    Err(TransactionError::WrongPrivateKey)
+  ));
+  let index = SubaddressIndex::new(0, 1);
+  let sub = subaddress(Network::Mainnet, &view, &address.spend, index);
+  assert!(matches!(
+    ViewWallet::new(sub, view, MemWalletStore::new()),
+    Err(TransactionError::InvalidAddress)
+  ));
+
+  let mut wallet = ViewWallet::new(address, view, MemWalletStore::new()).unwrap();
+  wallet.add_subaddresses(&[index]);
+  let first = wallet.scan(&receive(&address, 10_000_000, vec![Input::Gen(1)]), 1).unwrap();
+  assert_eq!(first.received, 10_000_000);
+  let second = wallet.scan(&receive(&sub, 20_000_000, vec![Input::Gen(2)]), 2).unwrap();
+  assert_eq!(second.received, 20_000_000);
+
+  // Without key images, outputs can't be spent
+  assert_eq!(wallet.balance(20, 0), (0, 30_000_000));
+  assert!(matches!(
+    wallet.transaction(&LargestFirst, 20, 0, vec![(address, 1_000_000)], FEE),
+    Err(TransactionError::NotEnoughFunds(0, _))
+  ));
+
+  let outputs = wallet.store().unspent();
+  let images = outputs.iter()
+    .map(|candidate| SignedKeyImage::new(&mut OsRng, &candidate.output, &spend).unwrap())
+    .collect::<Vec<_>>();
+  // Key images which aren't validly signed are ignored
+  let mut invalid = images[0].clone();
+  invalid.image = images[1].image;
+  assert_eq!(wallet.import_key_images(&[invalid]), 0);
+  assert_eq!(wallet.import_key_images(&images), 2);
+  assert_eq!(wallet.balance(20, 0), (30_000_000, 30_000_000));
+  // Outputs must still mature
+  assert_eq!(wallet.balance(11, 0), (10_000_000, 30_000_000));
+
+  let tx = wallet.transaction(&LargestFirst, 20, 0, vec![(address, 15_000_000)], FEE).unwrap();
+  let largest = outputs.iter()
+    .position(|candidate| candidate.output.commitment.amount == 20_000_000)
+    .unwrap();
+  assert_eq!(tx.inputs(), &[outputs[largest].output.clone()]);
+
+  // Spends are detected once key images are known
+  assert_eq!(wallet.scan(&spending(images[largest].image), 21).unwrap().spent, 20_000_000);
+  assert_eq!(wallet.balance(30, 0), (10_000_000, 10_000_000));
+}
+
+#[test]
+fn spend_wallet() {
+  let (spend, view, address) = keys();
+  let mut wallet = SpendWallet::new(Network::Mainnet, spend, view, MemWalletStore::new());
+  assert_eq!(wallet.view().address(), address);
+
+  wallet.scan(&receive(&address, 10_000_000, vec![Input::Gen(1)]), 1).unwrap();
+  // Key images are calculated when scanning, making outputs immediately spendable
+  assert_eq!(wallet.view().balance(20, 0), (10_000_000, 10_000_000));
+  let output = wallet.view().store().unspent().swap_remove(0).output;
+  let image = wallet.view().store().key_image(output.tx, output.o).unwrap();
+  assert_eq!(wallet.scan(&spending(image), 2).unwrap().spent, 10_000_000);
+
+  let wallet = wallet.into_view_only();
+  assert_eq!(wallet.balance(20, 0), (0, 0));
+}
+
+// The next event, which must be an output received or a confirmation gained
+async fn next_received<W: ScanningWallet>(
+  subscription: &mut PaymentSubscription<'_, W>
+) -> ReceivedOutput {
+  match subscription.next().await.unwrap() {
+    PaymentEvent::Received(received) => received,
+    PaymentEvent::Revoked(output) => panic!("unexpected revocation of {:?}", output)
+  }
+}
+
+#[tokio::test]
+async fn subscription() {
+  let (_, view, address) = keys();
+  let mut wallet = ViewWallet::new(address, view, MemWalletStore::new()).unwrap();
+  wallet.store_mut().scanned_to_height(5);
+  let rpc = Rpc::new("http://127.0.0.1:18081".to_string());
+  let mut subscription = PaymentSubscription::new(&mut wallet, &rpc, 2);
+
+  let first = receive(&address, 5, vec![Input::Gen(5)]);
+  assert!(subscription.scan_block([5; 32], [4; 32], &[first.clone()]));
+  let received = next_received(&mut subscription).await;
+  assert_eq!(received.output.output.tx, first.hash());
+  assert_eq!(received.output.height, 5);
+  assert_eq!(received.confirmations, 1);
+
+  // Outputs are yielded as they gain confirmations, alongside newly received outputs
+  let second = receive(&address, 7, vec![Input::Gen(6)]);
+  assert!(subscription.scan_block([6; 32], [5; 32], &[second.clone()]));
+  let confirmed = next_received(&mut subscription).await;
+  assert_eq!(confirmed.output, received.output);
+  assert_eq!(confirmed.confirmations, 2);
+  let received = next_received(&mut subscription).await;
+  assert_eq!(received.output.output.tx, second.hash());
+  assert_eq!(received.confirmations, 1);
+
+  // Once an output has the requested confirmations, it's no longer yielded
+  assert!(subscription.scan_block([7; 32], [6; 32], &[]));
+  let confirmed = next_received(&mut subscription).await;
+  assert_eq!(confirmed.output, received.output);
+  assert_eq!(confirmed.confirmations, 2);
+  assert!(subscription.scan_block([8; 32], [7; 32], &[]));
+  drop(subscription);
+
+  assert_eq!(wallet.store().scanned_height(), 9);
+  assert_eq!(wallet.balance(20, 0).1, 12);
+}
+
+#[tokio::test]
+async fn subscription_reorganization() {
+  let (_, view, address) = keys();
+  let mut wallet = ViewWallet::new(address, view, MemWalletStore::new()).unwrap();
+  wallet.store_mut().scanned_to_height(5);
+  let rpc = Rpc::new("http://127.0.0.1:18081".to_string());
+  let mut subscription = PaymentSubscription::new(&mut wallet, &rpc, 3);
+
+  let first = receive(&address, 5, vec![Input::Gen(5)]);
+  let second = receive(&address, 7, vec![Input::Gen(6)]);
+  assert!(subscription.scan_block([5; 32], [4; 32], &[first.clone()]));
+  assert!(subscription.scan_block([6; 32], [5; 32], &[second.clone()]));
+  let second_hash = second.hash();
+  for _ in 0 .. 3 {
+    next_received(&mut subscription).await;
+  }
+
+  // A block which doesn't build on the last block scanned isn't scanned
+  assert!(!subscription.scan_block([0xff; 32], [0xfe; 32], &[]));
+
+  // Reverting to the fork point revokes the outputs received since, removing them from the wallet
+  subscription.revert(6);
+  let second = match subscription.next().await.unwrap() {
+    PaymentEvent::Revoked(output) => output,
+    event => panic!("expected a revocation, got {:?}", event)
+  };
+  assert_eq!(second.output.tx, second_hash);
+  assert_eq!(second.height, 6);
+
+  // The new chain builds on the block kept, and outputs received before the fork keep confirming
+  assert!(!subscription.scan_block([0xff; 32], [6; 32], &[]));
+  let third = receive(&address, 9, vec![Input::Gen(6)]);
+  assert!(subscription.scan_block([0xee; 32], [5; 32], &[third.clone()]));
+  let confirmed = next_received(&mut subscription).await;
+  assert_eq!(confirmed.output.output.tx, first.hash());
+  assert_eq!(confirmed.confirmations, 2);
+  let received = next_received(&mut subscription).await;
+  assert_eq!(received.output.output.tx, third.hash());
+  assert_eq!(received.output.height, 6);
+  drop(subscription);
+
+  assert_eq!(wallet.store().scanned_height(), 7);
+  assert_eq!(wallet.store().unspent().len(), 2);
+  assert!(wallet.store().transactions().iter().all(|tx| tx.hash != second_hash));
+  assert_eq!(wallet.balance(20, 0).1, 14);
+}
+
+#[test]
+fn mempool_scanner() {
+  let (_, view, address) = keys();
+  let mut wallet = ViewWallet::new(address, view, MemWalletStore::new()).unwrap();
+  let index = SubaddressIndex::new(0, 1);
+  wallet.add_subaddresses(&[index]);
+  let sub = subaddress(Network::Mainnet, &view, &address.spend, index);
+
+  let mut scanner = wallet.mempool_scanner();
+  let mined = receive(&sub, 10_000_000, vec![]);
+  let dropped = receive(&address, 20_000_000, vec![]);
+  let other = spending(&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE);
+  let pool = [mined.hash(), dropped.hash(), other.hash()];
+
+  // Only transactions which haven't been scanned are returned to be fetched
+  let (events, mut new) = scanner.pool(&pool);
+  assert!(events.is_empty());
+  new.sort();
+  let mut sorted = pool;
+  sorted.sort();
+  assert_eq!(new, sorted);
+
+  let received = scanner.scan(&mined).unwrap();
+  assert!(matches!(
+    &received,
+    MempoolEvent::Received(outputs) if outputs.len() == 1 &&
+      outputs[0].commitment.amount == 10_000_000 &&
+      outputs[0].subaddress == Some(index)
+  ));
+  // Transactions are only scanned once
+  assert!(scanner.scan(&mined).is_none());
+  assert!(scanner.scan(&dropped).is_some());
+  assert!(scanner.scan(&other).is_none());
+  assert_eq!(scanner.unconfirmed().len(), 2);
+  assert_eq!(scanner.pool(&pool), (vec![], vec![]));
+
+  // The first block is at the store's scanned height
+  assert_eq!(
+    scanner.block(&[other.hash(), mined.hash()]),
+    vec![MempoolEvent::Mined(mined.hash(), 0)]
+  );
+  assert_eq!(scanner.block(&[]), vec![]);
+  assert_eq!(scanner.unconfirmed().len(), 1);
+  assert_eq!(scanner.unconfirmed()[0].commitment.amount, 20_000_000);
+
+  assert_eq!(scanner.pool(&[]), (vec![MempoolEvent::Dropped(dropped.hash())], vec![]));
+  assert!(scanner.unconfirmed().is_empty());
+
+  // Transactions which return to the pool are scanned again
+  assert_eq!(scanner.pool(&[dropped.hash()]).1, vec![dropped.hash()]);
+  assert!(scanner.scan(&dropped).is_some());
+}
+
+#[test]
+fn mempool_mined_before_update() {
+  let (_, view, address) = keys();
+  let wallet = ViewWallet::new(address, view, MemWalletStore::new()).unwrap();
+  let mut scanner = wallet.mempool_scanner();
+  let tx = receive(&address, 10_000_000, vec![]);
+
+  assert_eq!(scanner.pool(&[tx.hash()]).1, vec![tx.hash()]);
+  assert!(scanner.scan(&tx).is_some());
+
+  // As polling does, fetch the pool, then check the blocks mined before updating with it
+  // The transaction was mined after the pool was fetched, so the pool still contains it
+  let pool = [tx.hash()];
+  assert_eq!(scanner.block(&[tx.hash()]), vec![MempoolEvent::Mined(tx.hash(), 0)]);
+  // It isn't scanned again, and later isn't considered dropped
+  assert_eq!(scanner.pool(&pool), (vec![], vec![]));
+  assert!(scanner.unconfirmed().is_empty());
+  assert_eq!(scanner.pool(&[]), (vec![], vec![]));
+
+  // If it's reorganized back into the pool, it's scanned again
+  assert_eq!(scanner.pool(&pool).1, vec![tx.hash()]);
+  assert!(scanner.scan(&tx).is_some());
+}
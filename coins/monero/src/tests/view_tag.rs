@@ -0,0 +1,62 @@
+use rand::rngs::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY};
+
+use crate::{
+  random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{ExtraField, Extra, view_tag, shared_key}
+};
+
+#[test]
+fn view_tag_vector() {
+  // From Monero's tests/crypto/tests.txt
+  let derivation = CompressedEdwardsY(
+    hex::decode("0fc47054f355ced4d67de73bfa12e4c78ff19089548fffa7d07a674741860f97")
+      .unwrap().try_into().unwrap()
+  ).decompress().unwrap();
+  assert_eq!(view_tag(&derivation, 0), 0x76);
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn view_tag_scan() {
+  let view = random_scalar(&mut OsRng);
+  let spend = &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE;
+
+  let r = random_scalar(&mut OsRng);
+  let R = &r * &ED25519_BASEPOINT_TABLE;
+  let (tag, shared) = shared_key(None, r, &(&view * &ED25519_BASEPOINT_TABLE), 0);
+
+  let mut extra = vec![];
+  Extra(vec![ExtraField::PublicKey(R.compress())]).serialize(&mut extra).unwrap();
+
+  let tx = |tag| Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs: vec![Input::Gen(1)],
+      outputs: vec![
+        Output { amount: 5, key: spend + (&shared * &ED25519_BASEPOINT_TABLE), tag }
+      ],
+      extra: extra.clone()
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null
+    }
+  };
+
+  // Outputs are found with and without view tags
+  for tag in [None, Some(tag)] {
+    let outputs = tx(tag).scan(view, spend).0;
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].key_offset, shared);
+    assert_eq!(outputs[0].commitment.amount, 5);
+  }
+
+  // Yet a mismatched view tag is rejected before the output key is checked
+  assert!(tx(Some(tag.wrapping_add(1))).scan(view, spend).0.is_empty());
+}
@@ -0,0 +1,132 @@
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+use crate::rpc::{PoolTransaction, Notification, ZmqSubscriber};
+
+async fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+  let flags = stream.read_u8().await.unwrap();
+  let mut body = vec![0; stream.read_u8().await.unwrap().into()];
+  stream.read_exact(&mut body).await.unwrap();
+  (flags, body)
+}
+
+async fn write_frame(stream: &mut TcpStream, flags: u8, body: &[u8]) {
+  let mut frame = vec![];
+  if body.len() < 256 {
+    frame.extend([flags, u8::try_from(body.len()).unwrap()]);
+  } else {
+    frame.push(flags | 2);
+    frame.extend(u64::try_from(body.len()).unwrap().to_be_bytes());
+  }
+  frame.extend(body);
+  stream.write_all(&frame).await.unwrap();
+}
+
+#[test]
+fn zmq_parse() {
+  let hash = |byte| hex::encode([byte; 32]);
+
+  assert_eq!(
+    Notification::parse(
+      format!(
+        r#"json-minimal-chain_main:{{"first_height":5,"first_prev_id":"{}","ids":["{}","{}"]}}"#,
+        hash(1),
+        hash(2),
+        hash(3)
+      ).as_bytes()
+    ).unwrap(),
+    Some(Notification::Blocks { height: 5, prev: [1; 32], hashes: vec![[2; 32], [3; 32]] })
+  );
+
+  assert_eq!(
+    Notification::parse(
+      format!(
+        r#"json-minimal-txpool_add:[{{"id":"{}","blob_size":1500,"weight":1500,"fee":30000}}]"#,
+        hash(4)
+      ).as_bytes()
+    ).unwrap(),
+    Some(
+      Notification::Transactions(vec![PoolTransaction { hash: [4; 32], weight: 1500, fee: 30000 }])
+    )
+  );
+
+  // Unknown topics are ignored, while malformed notifications error
+  assert_eq!(Notification::parse(b"json-full-chain_main:[]").unwrap(), None);
+  assert!(Notification::parse(b"json-minimal-chain_main:{}").is_err());
+  assert!(Notification::parse(b"json-minimal-txpool_add").is_err());
+}
+
+// Accept a subscriber, checking its handshake and subscriptions
+async fn accept(listener: &TcpListener) -> TcpStream {
+  let (mut stream, _) = listener.accept().await.unwrap();
+
+  let mut greeting = [0; 64];
+  stream.read_exact(&mut greeting).await.unwrap();
+  assert_eq!((greeting[0], greeting[9], greeting[10]), (0xff, 0x7f, 3));
+  assert_eq!(&greeting[12 .. 16], b"NULL");
+  // Claim ZMTP 3.1, as libzmq does
+  greeting[11] = 1;
+  stream.write_all(&greeting).await.unwrap();
+
+  let (flags, ready) = read_frame(&mut stream).await;
+  assert_eq!(flags, 4);
+  assert_eq!(ready, b"\x05READY\x0bSocket-Type\x00\x00\x00\x03SUB");
+  write_frame(&mut stream, 4, b"\x05READY\x0bSocket-Type\x00\x00\x00\x03PUB").await;
+
+  assert_eq!(read_frame(&mut stream).await, (0, b"\x01json-minimal-chain_main".to_vec()));
+  assert_eq!(read_frame(&mut stream).await, (0, b"\x01json-minimal-txpool_add".to_vec()));
+  stream
+}
+
+#[tokio::test]
+async fn zmq_subscribe() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+
+  let publisher = tokio::spawn(async move {
+    let mut stream = accept(&listener).await;
+
+    // A topic which wasn't subscribed to is skipped
+    write_frame(&mut stream, 0, b"json-full-txpool_add:[]").await;
+    // Multipart messages are joined, and long frames are supported
+    let ids = vec![format!("\"{}\"", hex::encode([2; 32])); 8].join(",");
+    let chain = format!(
+      r#"{{"first_height":10,"first_prev_id":"{}","ids":[{}]}}"#,
+      hex::encode([1; 32]),
+      ids
+    );
+    write_frame(&mut stream, 1, b"json-minimal-chain_main:").await;
+    write_frame(&mut stream, 0, chain.as_bytes()).await;
+  });
+
+  let mut subscriber = ZmqSubscriber::connect(&endpoint).await.unwrap();
+  assert_eq!(
+    subscriber.next().await.unwrap(),
+    Notification::Blocks { height: 10, prev: [1; 32], hashes: vec![[2; 32]; 8] }
+  );
+  publisher.await.unwrap();
+}
+
+#[tokio::test]
+async fn zmq_oversized() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+
+  let publisher = tokio::spawn(async move {
+    // A frame claiming to be 4 EiB is rejected without allocating for it
+    let mut stream = accept(&listener).await;
+    stream.write_all(&[&[2][..], &(1u64 << 62).to_be_bytes()].concat()).await.unwrap();
+
+    // As is a message whose frames are individually small, yet exceed the limit together
+    let mut stream = accept(&listener).await;
+    for _ in 0 .. 4 {
+      write_frame(&mut stream, 1, &vec![0; 1024 * 1024]).await;
+    }
+    stream.write_all(&[&[3][..], &(1024u64 * 1024).to_be_bytes()].concat()).await.unwrap();
+  });
+
+  for _ in 0 .. 2 {
+    let mut subscriber = ZmqSubscriber::connect(&endpoint).await.unwrap();
+    assert!(subscriber.next().await.is_err());
+  }
+  publisher.await.unwrap();
+}
@@ -1,6 +1,8 @@
-use curve25519_dalek::edwards::EdwardsPoint;
+use std::io::Read;
 
-use crate::{hash, serialize::*, ringct::{RctPrunable, RctSignatures}};
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{hash, serialize::*, ringct::{RctBase, RctPrunable, RctSignatures}};
 
 pub const RING_LEN: usize = 11;
 
@@ -105,7 +107,7 @@ pub enum Timelock {
 }
 
 impl Timelock {
-  fn from_raw(raw: u64) -> Timelock {
+  pub(crate) fn from_raw(raw: u64) -> Timelock {
     if raw == 0 {
       Timelock::None
     } else if raw < 500_000_000 {
@@ -119,7 +121,17 @@ impl Timelock {
     8
   }
 
-  fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+  /// If outputs with this timelock can be spent by a transaction in the block at this height, with
+  /// the chain's current time being the specified UNIX timestamp
+  pub fn unlocked(&self, height: usize, time: u64) -> bool {
+    match self {
+      Timelock::None => true,
+      Timelock::Block(block) => *block <= height,
+      Timelock::Time(unlock) => *unlock <= time
+    }
+  }
+
+  pub(crate) fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
     write_varint(
       &match self {
         Timelock::None => 0,
@@ -168,9 +180,12 @@ impl TransactionPrefix {
       extra: vec![]
     };
 
+    // Don't allocate the claimed length upfront, as it's yet to be proven by the data
     let len = read_varint(r)?;
-    prefix.extra.resize(len.try_into().unwrap(), 0);
-    r.read_exact(&mut prefix.extra)?;
+    r.by_ref().take(len).read_to_end(&mut prefix.extra)?;
+    if u64::try_from(prefix.extra.len()).unwrap() != len {
+      Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "extra shorter than its length"))?;
+    }
 
     Ok(prefix)
   }
@@ -179,6 +194,9 @@ impl TransactionPrefix {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Transaction {
   pub prefix: TransactionPrefix,
+  /// Ring signatures of version 1 transactions, a (c, r) pair per ring member for each input
+  /// Empty for later versions, which use rct_signatures
+  pub signatures: Vec<Vec<(Scalar, Scalar)>>,
   pub rct_signatures: RctSignatures
 }
 
@@ -189,24 +207,41 @@ impl Transaction {
 
   pub fn serialize<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
     self.prefix.serialize(w)?;
-    self.rct_signatures.serialize(w)
+    if self.prefix.version == 1 {
+      for signature in &self.signatures {
+        for (c, r) in signature {
+          write_scalar(c, w)?;
+          write_scalar(r, w)?;
+        }
+      }
+      Ok(())
+    } else {
+      self.rct_signatures.serialize(w)
+    }
   }
 
   pub fn deserialize<R: std::io::Read>(r: &mut R) -> std::io::Result<Transaction> {
     let prefix = TransactionPrefix::deserialize(r)?;
-    Ok(
-      Transaction {
-        rct_signatures: RctSignatures::deserialize(
-          prefix.inputs.iter().map(|input| match input {
-            Input::Gen(_) => 0,
-            Input::ToKey { key_offsets, .. } => key_offsets.len()
-          }).collect(),
-          prefix.outputs.len(),
-          r
-        )?,
-        prefix
+    let decoys = prefix.inputs.iter().map(|input| match input {
+      Input::Gen(_) => 0,
+      Input::ToKey { key_offsets, .. } => key_offsets.len()
+    }).collect::<Vec<_>>();
+
+    let mut signatures = vec![];
+    let rct_signatures = if prefix.version == 1 {
+      // Gen inputs, as used by miner transactions, have an empty signature
+      for decoys in &decoys {
+        signatures.push(read_raw_vec(|r| Ok((read_scalar(r)?, read_scalar(r)?)), *decoys, r)?);
       }
-    )
+      RctSignatures {
+        base: RctBase { fee: 0, pseudo_outs: vec![], ecdh_info: vec![], commitments: vec![] },
+        prunable: RctPrunable::Null
+      }
+    } else {
+      RctSignatures::deserialize(decoys, prefix.outputs.len(), r)?
+    };
+
+    Ok(Transaction { prefix, signatures, rct_signatures })
   }
 
   pub fn hash(&self) -> [u8; 32] {
@@ -221,17 +256,17 @@ impl Transaction {
       sig_hash.extend(hash(&serialized));
       serialized.clear();
 
-      self.rct_signatures.base.serialize(
-        &mut serialized,
-        self.rct_signatures.prunable.rct_type()
-      ).unwrap();
+      self.rct_signatures.base.serialize(&mut serialized, self.rct_signatures.rct_type()).unwrap();
       sig_hash.extend(hash(&serialized));
       serialized.clear();
 
       match self.rct_signatures.prunable {
         RctPrunable::Null => serialized.resize(32, 0),
         _ => {
-          self.rct_signatures.prunable.serialize(&mut serialized).unwrap();
+          self.rct_signatures.prunable.serialize(
+            &mut serialized,
+            self.rct_signatures.rct_type()
+          ).unwrap();
           serialized = hash(&serialized).to_vec();
         }
       }
@@ -249,7 +284,7 @@ impl Transaction {
     sig_hash.extend(hash(&serialized));
     serialized.clear();
 
-    self.rct_signatures.base.serialize(&mut serialized, self.rct_signatures.prunable.rct_type()).unwrap();
+    self.rct_signatures.base.serialize(&mut serialized, self.rct_signatures.rct_type()).unwrap();
     sig_hash.extend(hash(&serialized));
     serialized.clear();
 
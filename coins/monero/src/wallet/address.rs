@@ -0,0 +1,149 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
+
+use crate::{hash, wallet::base58};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Network {
+  Mainnet,
+  Testnet,
+  Stagenet
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressType {
+  Standard,
+  Integrated([u8; 8]),
+  Subaddress
+}
+
+impl AddressType {
+  fn byte(&self, network: Network) -> u8 {
+    match (network, self) {
+      (Network::Mainnet, AddressType::Standard) => 18,
+      (Network::Mainnet, AddressType::Integrated(_)) => 19,
+      (Network::Mainnet, AddressType::Subaddress) => 42,
+      (Network::Testnet, AddressType::Standard) => 53,
+      (Network::Testnet, AddressType::Integrated(_)) => 54,
+      (Network::Testnet, AddressType::Subaddress) => 63,
+      (Network::Stagenet, AddressType::Standard) => 24,
+      (Network::Stagenet, AddressType::Integrated(_)) => 25,
+      (Network::Stagenet, AddressType::Subaddress) => 36
+    }
+  }
+
+  // The network and type for a byte, with a placeholder payment ID for integrated addresses
+  fn from_byte(byte: u8) -> Option<(Network, AddressType)> {
+    for network in [Network::Mainnet, Network::Testnet, Network::Stagenet] {
+      for kind in [AddressType::Standard, AddressType::Integrated([0; 8]), AddressType::Subaddress] {
+        if kind.byte(network) == byte {
+          return Some((network, kind));
+        }
+      }
+    }
+    None
+  }
+}
+
+#[derive(Clone, Error, Debug)]
+pub enum AddressError {
+  #[error("invalid base58 encoding")]
+  InvalidEncoding,
+  #[error("invalid length")]
+  InvalidLength,
+  #[error("invalid checksum")]
+  InvalidChecksum,
+  #[error("unknown address type byte ({0})")]
+  InvalidTypeByte(u8),
+  #[error("invalid key")]
+  InvalidKey,
+  #[error("different network than expected")]
+  DifferentNetwork
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Address {
+  pub network: Network,
+  pub kind: AddressType,
+  pub spend: EdwardsPoint,
+  pub view: EdwardsPoint
+}
+
+fn read_key(bytes: &[u8]) -> Result<EdwardsPoint, AddressError> {
+  let compressed = CompressedEdwardsY(bytes.try_into().unwrap());
+  compressed.decompress().filter(
+    // Ban points which weren't canonically encoded, so every address has a single encoding
+    |point| point.compress() == compressed
+  ).ok_or(AddressError::InvalidKey)
+}
+
+impl Address {
+  pub fn new(network: Network, kind: AddressType, spend: EdwardsPoint, view: EdwardsPoint) -> Address {
+    Address { network, kind, spend, view }
+  }
+
+  pub fn standard(network: Network, spend: EdwardsPoint, view: EdwardsPoint) -> Address {
+    Address::new(network, AddressType::Standard, spend, view)
+  }
+
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut data = vec![self.kind.byte(self.network)];
+    data.extend(self.spend.compress().to_bytes());
+    data.extend(self.view.compress().to_bytes());
+    if let AddressType::Integrated(id) = self.kind {
+      data.extend(id);
+    }
+    let checksum = hash(&data);
+    data.extend(&checksum[.. 4]);
+    data
+  }
+
+  /// Decode an address, erroring if it's for a different network than the one specified
+  pub fn from_str_with_network(s: &str, network: Network) -> Result<Address, AddressError> {
+    let address = s.parse::<Address>()?;
+    if address.network != network {
+      Err(AddressError::DifferentNetwork)?;
+    }
+    Ok(address)
+  }
+}
+
+impl fmt::Display for Address {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", base58::encode(&self.to_bytes()))
+  }
+}
+
+impl FromStr for Address {
+  type Err = AddressError;
+
+  fn from_str(s: &str) -> Result<Address, AddressError> {
+    let data = base58::decode(s).ok_or(AddressError::InvalidEncoding)?;
+    if data.len() < (1 + 32 + 32 + 4) {
+      Err(AddressError::InvalidLength)?;
+    }
+
+    let (data, checksum) = data.split_at(data.len() - 4);
+    if hash(data)[.. 4] != *checksum {
+      Err(AddressError::InvalidChecksum)?;
+    }
+
+    let (network, mut kind) = AddressType::from_byte(data[0])
+      .ok_or(AddressError::InvalidTypeByte(data[0]))?;
+    let len = if let AddressType::Integrated(_) = kind { 1 + 32 + 32 + 8 } else { 1 + 32 + 32 };
+    if data.len() != len {
+      Err(AddressError::InvalidLength)?;
+    }
+
+    let spend = read_key(&data[1 .. 33])?;
+    let view = read_key(&data[33 .. 65])?;
+    if let AddressType::Integrated(ref mut id) = kind {
+      id.copy_from_slice(&data[65 ..]);
+    }
+
+    Ok(Address { network, kind, spend, view })
+  }
+}
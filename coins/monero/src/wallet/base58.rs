@@ -0,0 +1,44 @@
+// Monero's base58, which encodes data in blocks of 8 bytes, each becoming 11 characters, instead of
+// as a single big number
+
+const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const BLOCK_LEN: usize = 8;
+const ENCODED_BLOCK_LEN: usize = 11;
+// Length of an encoded block, by the length of the block
+const ENCODED_BLOCK_LENS: [usize; BLOCK_LEN + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+pub(crate) fn encode(data: &[u8]) -> String {
+  let mut res = String::with_capacity(data.len().div_ceil(BLOCK_LEN) * ENCODED_BLOCK_LEN);
+  for block in data.chunks(BLOCK_LEN) {
+    let mut value = block.iter().fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+
+    let mut encoded = vec![ALPHABET[0]; ENCODED_BLOCK_LENS[block.len()]];
+    for c in encoded.iter_mut().rev() {
+      *c = ALPHABET[usize::try_from(value % 58).unwrap()];
+      value /= 58;
+    }
+    res.push_str(std::str::from_utf8(&encoded).unwrap());
+  }
+  res
+}
+
+pub(crate) fn decode(data: &str) -> Option<Vec<u8>> {
+  let mut res = Vec::with_capacity(data.len().div_ceil(ENCODED_BLOCK_LEN) * BLOCK_LEN);
+  for block in data.as_bytes().chunks(ENCODED_BLOCK_LEN) {
+    let len = ENCODED_BLOCK_LENS.iter().position(|len| *len == block.len())?;
+
+    let mut value = 0u128;
+    for c in block {
+      let digit = ALPHABET.iter().position(|a| a == c)?;
+      value = (value * 58) + u128::try_from(digit).unwrap();
+    }
+    // Reject blocks which decode to more bytes than they're allowed to represent
+    if value >> (len * 8) != 0 {
+      None?;
+    }
+
+    res.extend(&value.to_be_bytes()[(16 - len) ..]);
+  }
+  Some(res)
+}
@@ -0,0 +1,325 @@
+use std::io::{self, Read, Write};
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+#[cfg(feature = "multisig")]
+use std::sync::Arc;
+#[cfg(feature = "multisig")]
+use transcript::{Transcript, RecommendedTranscript};
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, MultisigKeys, sign::AlgorithmMachine};
+
+use crate::{
+  hash, generate_key_image,
+  serialize::*,
+  ring_signature::RingSignature,
+  transaction::Transaction,
+  rpc::Rpc,
+  wallet::{SpendableOutput, Decoys, TransactionError, SignableTransaction}
+};
+#[cfg(feature = "multisig")]
+use crate::{
+  ring_signature::RingSignatureMultisig,
+  wallet::{TransactionMachine, key_image::SHARE_LEN}
+};
+
+// Prefixes for each format, versioned so they can evolve without being confused for one another
+const OUTPUTS_MAGIC: &[u8] = b"Monero Serai outputs\x01";
+const KEY_IMAGES_MAGIC: &[u8] = b"Monero Serai key images\x01";
+#[cfg(feature = "multisig")]
+const KEY_IMAGE_SHARES_MAGIC: &[u8] = b"Monero Serai key image shares\x01";
+const UNSIGNED_MAGIC: &[u8] = b"Monero Serai unsigned transactions\x01";
+const SIGNED_MAGIC: &[u8] = b"Monero Serai signed transactions\x01";
+
+fn export<T, F: Fn(&T, &mut Vec<u8>) -> io::Result<()>>(
+  magic: &[u8],
+  values: &[T],
+  f: F
+) -> Vec<u8> {
+  let mut res = magic.to_vec();
+  write_vec(f, values, &mut res).unwrap();
+  res
+}
+
+// Import an export, rejecting it if it has trailing data
+fn import<T, F: Fn(&mut &[u8]) -> io::Result<T>>(
+  magic: &[u8],
+  serialized: &[u8],
+  f: F
+) -> Option<Vec<T>> {
+  let mut reader = serialized.strip_prefix(magic)?;
+  let res = read_vec(f, &mut reader).ok()?;
+  Some(res).filter(|_| reader.is_empty())
+}
+
+fn invalid(msg: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::Other, msg)
+}
+
+/// Export outputs found by a scanning host, for an offline signer to produce key images for and
+/// spend
+pub fn export_outputs(outputs: &[SpendableOutput]) -> Vec<u8> {
+  export(OUTPUTS_MAGIC, outputs, |output, w| w.write_all(&output.serialize()))
+}
+
+pub fn import_outputs(serialized: &[u8]) -> Option<Vec<SpendableOutput>> {
+  import(OUTPUTS_MAGIC, serialized, |r| SpendableOutput::deserialize(r))
+}
+
+/// An output's key image, with a signature proving it's the output's, letting a host without the
+/// spend key detect when the output is spent
+#[derive(Clone, PartialEq, Debug)]
+pub struct SignedKeyImage {
+  /// The output's key
+  pub key: EdwardsPoint,
+  pub image: EdwardsPoint,
+  /// A ring signature, with the output as the only member
+  pub signature: RingSignature
+}
+
+impl SignedKeyImage {
+  /// The message signed for an output, as multisig signing must be provided with
+  pub fn signature_hash(key: &EdwardsPoint) -> [u8; 32] {
+    let mut data = b"key_image_export".to_vec();
+    data.extend(key.compress().to_bytes());
+    hash(&data)
+  }
+
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    output: &SpendableOutput,
+    spend: &Scalar
+  ) -> Result<SignedKeyImage, TransactionError> {
+    let key = spend + output.key_offset;
+    if (&key * &ED25519_BASEPOINT_TABLE) != output.key {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    Ok(
+      SignedKeyImage {
+        key: output.key,
+        image: generate_key_image(&key),
+        signature: RingSignature::sign(
+          rng,
+          &SignedKeyImage::signature_hash(&output.key),
+          &[output.key],
+          0,
+          &key
+        )
+      }
+    )
+  }
+
+  /// Create the machine to sign an output's key image with the rest of the multisig, signing
+  /// SignedKeyImage::signature_hash
+  #[cfg(feature = "multisig")]
+  pub fn multisig(
+    keys: &MultisigKeys<Ed25519>,
+    mut transcript: RecommendedTranscript,
+    output: &SpendableOutput,
+    included: &[u16]
+  ) -> Result<AlgorithmMachine<Ed25519, RingSignatureMultisig>, TransactionError> {
+    let offset = keys.offset(dalek_ff_group::Scalar(output.key_offset));
+    if offset.group_key().0 != output.key {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    transcript.domain_separate(b"monero_key_image_export");
+    AlgorithmMachine::new(
+      RingSignatureMultisig::new(transcript, vec![output.key], 0),
+      Arc::new(offset),
+      included
+    ).map_err(TransactionError::FrostError)
+  }
+
+  /// Create a signed key image from the result of a multisig's signing
+  #[cfg(feature = "multisig")]
+  pub fn from_multisig(
+    key: EdwardsPoint,
+    (signature, image): (RingSignature, EdwardsPoint)
+  ) -> SignedKeyImage {
+    SignedKeyImage { key, image, signature }
+  }
+
+  pub fn verify(&self) -> bool {
+    self.signature.verify(&SignedKeyImage::signature_hash(&self.key), &[self.key], &self.image)
+  }
+
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_point(&self.key, w)?;
+    write_point(&self.image, w)?;
+    self.signature.serialize(w)
+  }
+
+  pub fn deserialize<R: Read>(r: &mut R) -> io::Result<SignedKeyImage> {
+    Ok(
+      SignedKeyImage {
+        key: read_point(r)?,
+        image: read_point(r)?,
+        signature: RingSignature::deserialize(1, r)?
+      }
+    )
+  }
+}
+
+/// Export key images, for the scanning host to import
+pub fn export_key_images(images: &[SignedKeyImage]) -> Vec<u8> {
+  export(KEY_IMAGES_MAGIC, images, |image, w| image.serialize(w))
+}
+
+/// Import key images, only returning them if every one is validly signed
+pub fn import_key_images(serialized: &[u8]) -> Option<Vec<SignedKeyImage>> {
+  let images = import(KEY_IMAGES_MAGIC, serialized, |r| SignedKeyImage::deserialize(r))?;
+  Some(images).filter(|images| images.iter().all(SignedKeyImage::verify))
+}
+
+/// Export a participant's key image shares, as produced by KeyImageMachine::share, so offline
+/// participants can exchange them
+#[cfg(feature = "multisig")]
+pub fn export_key_image_shares(participant: u16, shares: &[Vec<u8>]) -> Vec<u8> {
+  assert!(shares.iter().all(|share| share.len() == SHARE_LEN), "invalid key image share");
+  let mut res = KEY_IMAGE_SHARES_MAGIC.to_vec();
+  res.extend(participant.to_le_bytes());
+  write_vec(|share, w| w.write_all(share), shares, &mut res).unwrap();
+  res
+}
+
+/// Import a participant's key image shares, returning the participant and their shares
+#[cfg(feature = "multisig")]
+pub fn import_key_image_shares(serialized: &[u8]) -> Option<(u16, Vec<Vec<u8>>)> {
+  let serialized = serialized.strip_prefix(KEY_IMAGE_SHARES_MAGIC)?;
+  let participant = u16::from_le_bytes(serialized.get(.. 2)?.try_into().unwrap());
+  let mut reader = &serialized[2 ..];
+  let shares = read_vec(
+    |r| {
+      let mut share = vec![0; SHARE_LEN];
+      r.read_exact(&mut share)?;
+      Ok(share)
+    },
+    &mut reader
+  ).ok()?;
+  Some((participant, shares)).filter(|_| reader.is_empty())
+}
+
+/// A transaction to be signed offline, with the decoys selected by the online host
+#[derive(Clone, PartialEq, Debug)]
+pub struct UnsignedTransaction {
+  signable: SignableTransaction,
+  height: usize,
+  decoys: Vec<Decoys>
+}
+
+impl UnsignedTransaction {
+  /// Select decoys for a transaction, as of the specified height
+  pub async fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    rpc: &Rpc,
+    signable: SignableTransaction,
+    height: usize
+  ) -> Result<UnsignedTransaction, TransactionError> {
+    let decoys = Decoys::select(rng, rpc, height, signable.inputs()).await
+      .map_err(TransactionError::RpcError)?;
+    Ok(UnsignedTransaction { signable, height, decoys })
+  }
+
+  /// The transaction to be signed, which should be reviewed before signing
+  pub fn signable(&self) -> &SignableTransaction {
+    &self.signable
+  }
+
+  pub fn sign<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    spend: &Scalar
+  ) -> Result<SignedTransaction, TransactionError> {
+    let tx = self.signable.sign_with_decoys(rng, self.decoys, spend)?;
+    Ok(SignedTransaction { tx, tx_keys: self.signable.tx_keys() })
+  }
+
+  /// Create the machine to sign this transaction with the rest of the multisig
+  /// The transaction's keys are available from TransactionSignatureMachine::tx_keys
+  #[cfg(feature = "multisig")]
+  pub fn multisig(
+    self,
+    keys: MultisigKeys<Ed25519>,
+    transcript: RecommendedTranscript,
+    included: Vec<u16>
+  ) -> Result<TransactionMachine, TransactionError> {
+    self.signable.multisig_with_decoys(keys, transcript, self.height, self.decoys, included)
+  }
+
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    self.signable.serialize(w)?;
+    write_varint(&self.height.try_into().unwrap(), w)?;
+    write_raw_vec(
+      |decoys, w| {
+        w.write_all(&[decoys.i])?;
+        write_vec(|offset, w| write_varint(offset, w), &decoys.offsets, w)?;
+        write_raw_vec(|member, w| write_raw_vec(write_point, member, w), &decoys.ring, w)
+      },
+      &self.decoys,
+      w
+    )
+  }
+
+  /// Deserialize an unsigned transaction, checking every input is within its ring
+  pub fn deserialize<R: Read>(r: &mut R) -> io::Result<UnsignedTransaction> {
+    let signable = SignableTransaction::deserialize(r)?;
+    let height = read_varint(r)?.try_into().map_err(|_| invalid("height exceeds usize"))?;
+
+    let mut decoys = Vec::with_capacity(signable.inputs().len());
+    for input in signable.inputs() {
+      let i = read_byte(r)?;
+      let offsets = read_vec(read_varint, r)?;
+      let ring = read_raw_vec(
+        |r| Ok([read_point(r)?, read_point(r)?]),
+        offsets.len(),
+        r
+      )?;
+      if ring.get(usize::from(i)) != Some(&[input.key, input.commitment.calculate()]) {
+        Err(invalid("input isn't within its ring"))?;
+      }
+      decoys.push(Decoys { i, offsets, ring });
+    }
+
+    Ok(UnsignedTransaction { signable, height, decoys })
+  }
+}
+
+/// Export transactions for an offline signer to sign
+pub fn export_unsigned(txs: &[UnsignedTransaction]) -> Vec<u8> {
+  export(UNSIGNED_MAGIC, txs, |tx, w| tx.serialize(w))
+}
+
+pub fn import_unsigned(serialized: &[u8]) -> Option<Vec<UnsignedTransaction>> {
+  import(UNSIGNED_MAGIC, serialized, |r| UnsignedTransaction::deserialize(r))
+}
+
+/// A transaction signed offline, with its private keys so the host can prove what it sent
+#[derive(Clone, PartialEq, Debug)]
+pub struct SignedTransaction {
+  pub tx: Transaction,
+  pub tx_keys: Vec<Scalar>
+}
+
+impl SignedTransaction {
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    self.tx.serialize(w)?;
+    write_vec(write_scalar, &self.tx_keys, w)
+  }
+
+  pub fn deserialize<R: Read>(r: &mut R) -> io::Result<SignedTransaction> {
+    Ok(SignedTransaction { tx: Transaction::deserialize(r)?, tx_keys: read_vec(read_scalar, r)? })
+  }
+}
+
+/// Export signed transactions, for the host to publish
+pub fn export_signed(txs: &[SignedTransaction]) -> Vec<u8> {
+  export(SIGNED_MAGIC, txs, |tx, w| tx.serialize(w))
+}
+
+pub fn import_signed(serialized: &[u8]) -> Option<Vec<SignedTransaction>> {
+  import(SIGNED_MAGIC, serialized, |r| SignedTransaction::deserialize(r))
+}
@@ -27,7 +27,7 @@ async fn select_n<R: RngCore + CryptoRng>(
   rng: &mut R,
   rpc: &Rpc,
   height: usize,
-  high: u64,
+  unlocked: u64,
   per_second: f64,
   used: &mut HashSet<u64>,
   count: usize
@@ -53,14 +53,16 @@ async fn select_n<R: RngCore + CryptoRng>(
         age = (rng.next_u64() % u64::try_from(RECENT_WINDOW * BLOCK_TIME).unwrap()) as f64;
       }
 
+      // Ages are measured from the most recent unlocked output, as done by wallet2's gamma picker
       let o = (age * per_second) as u64;
-      if o < high {
+      if o < unlocked {
         let distribution = DISTRIBUTION.lock().unwrap();
-        let i = distribution.partition_point(|s| *s < (high - 1 - o));
-        let prev = i.saturating_sub(1);
-        let n = distribution[i] - distribution[prev];
+        // Select a random output from the block containing the output with this age
+        let i = distribution.partition_point(|s| *s < (unlocked - 1 - o));
+        let first = if i == 0 { 0 } else { distribution[i - 1] };
+        let n = distribution[i] - first;
         if n != 0 {
-          let o = distribution[prev] + (rng.next_u64() % n);
+          let o = first + (rng.next_u64() % n);
           if !used.contains(&o) {
             // It will either actually be used, or is unusable and this prevents trying it again
             used.insert(o);
@@ -127,6 +129,7 @@ impl Decoys {
     }
 
     let high;
+    let unlocked;
     let per_second;
     {
       let mut distribution = DISTRIBUTION.lock().unwrap();
@@ -135,9 +138,15 @@ impl Decoys {
       distribution.truncate(height + 1); // height is inclusive, and 0 is a valid height
 
       high = distribution[distribution.len() - 1];
+      // Outputs within the lock window can't be spent, so they're never selected as decoys
+      unlocked = distribution[distribution.len().saturating_sub(LOCK_WINDOW + 1)];
       per_second = {
         let blocks = distribution.len().min(BLOCKS_PER_YEAR);
-        let outputs = high - distribution[distribution.len().saturating_sub(blocks + 1)];
+        let outputs = high - if blocks < distribution.len() {
+          distribution[distribution.len() - blocks - 1]
+        } else {
+          0
+        };
         (outputs as f64) / ((blocks * BLOCK_TIME) as f64)
       };
     };
@@ -159,7 +168,7 @@ impl Decoys {
       rng,
       rpc,
       height,
-      high,
+      unlocked,
       per_second,
       &mut used,
       inputs.len() * DECOYS
@@ -199,7 +208,15 @@ impl Decoys {
 
           // Select new outputs until we have a full sized ring again
           ring.extend(
-            select_n(rng, rpc, height, high, per_second, &mut used, RING_LEN - ring.len()).await?
+            select_n(
+              rng,
+              rpc,
+              height,
+              unlocked,
+              per_second,
+              &mut used,
+              RING_LEN - ring.len()
+            ).await?
           );
           ring.sort_by(|a, b| a.0.cmp(&b.0));
         }
@@ -0,0 +1,139 @@
+use std::io::{self, Read, Write};
+
+use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
+
+use crate::serialize::*;
+
+// Monero's limits on the padding and nonce fields
+const MAX_PADDING: usize = 255;
+const MAX_NONCE: usize = 255;
+
+/// A field within a transaction's extra
+/// Keys are left compressed, as Monero doesn't require they're valid points
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ExtraField {
+  /// Trailing zero bytes, solely its length
+  Padding(usize),
+  PublicKey(CompressedEdwardsY),
+  /// Arbitrary data, such as a payment ID
+  Nonce(Vec<u8>),
+  /// The merge mining depth and merkle root, left unparsed
+  MergeMining(Vec<u8>),
+  /// Additional public keys, one per output, as used when sending to subaddresses
+  PublicKeys(Vec<CompressedEdwardsY>),
+  MysteriousMinergate(Vec<u8>)
+}
+
+fn write_bytes<W: Write>(bytes: &[u8], w: &mut W) -> io::Result<()> {
+  write_varint(&bytes.len().try_into().unwrap(), w)?;
+  w.write_all(bytes)
+}
+
+fn read_bytes<R: Read>(max: Option<usize>, r: &mut R) -> io::Result<Vec<u8>> {
+  let len = usize::try_from(read_varint(r)?)
+    .map_err(|_| io::Error::new(io::ErrorKind::Other, "extra field length exceeds usize"))?;
+  if max.map(|max| len > max).unwrap_or(false) {
+    Err(io::Error::new(io::ErrorKind::Other, "extra field too long"))?;
+  }
+  let mut bytes = vec![];
+  r.by_ref().take(len.try_into().unwrap()).read_to_end(&mut bytes)?;
+  if bytes.len() != len {
+    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "extra field shorter than its length"))?;
+  }
+  Ok(bytes)
+}
+
+fn read_key<R: Read>(r: &mut R) -> io::Result<CompressedEdwardsY> {
+  Ok(CompressedEdwardsY(read_32(r)?))
+}
+
+impl ExtraField {
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    match self {
+      ExtraField::Padding(len) => w.write_all(&vec![0; *len]),
+      ExtraField::PublicKey(key) => {
+        w.write_all(&[1])?;
+        w.write_all(&key.to_bytes())
+      },
+      ExtraField::Nonce(nonce) => {
+        w.write_all(&[2])?;
+        write_bytes(nonce, w)
+      },
+      ExtraField::MergeMining(data) => {
+        w.write_all(&[3])?;
+        write_bytes(data, w)
+      },
+      ExtraField::PublicKeys(keys) => {
+        w.write_all(&[4])?;
+        write_vec(|key, w| w.write_all(&key.to_bytes()), keys, w)
+      },
+      ExtraField::MysteriousMinergate(data) => {
+        w.write_all(&[0xde])?;
+        write_bytes(data, w)
+      }
+    }
+  }
+
+  pub fn deserialize<R: Read>(r: &mut R) -> io::Result<ExtraField> {
+    Ok(
+      match read_byte(r)? {
+        // Padding must be the last field, with every byte being 0
+        0 => {
+          let mut padding = vec![];
+          r.by_ref().take(MAX_PADDING.try_into().unwrap()).read_to_end(&mut padding)?;
+          if (padding.len() == MAX_PADDING) || padding.iter().any(|byte| *byte != 0) {
+            Err(io::Error::new(io::ErrorKind::Other, "invalid padding"))?;
+          }
+          ExtraField::Padding(1 + padding.len())
+        },
+        1 => ExtraField::PublicKey(read_key(r)?),
+        2 => ExtraField::Nonce(read_bytes(Some(MAX_NONCE), r)?),
+        3 => ExtraField::MergeMining(read_bytes(None, r)?),
+        4 => ExtraField::PublicKeys(read_vec(read_key, r)?),
+        0xde => ExtraField::MysteriousMinergate(read_bytes(None, r)?),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "unknown extra field"))?
+      }
+    )
+  }
+}
+
+/// A transaction's extra, parsed into its fields
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Extra(pub Vec<ExtraField>);
+
+impl Extra {
+  /// The transaction's public key, followed by any additional public keys, skipping any which
+  /// aren't valid points
+  pub fn keys(&self) -> Vec<EdwardsPoint> {
+    let mut keys: Vec<CompressedEdwardsY> = vec![];
+    let mut additional: Vec<CompressedEdwardsY> = vec![];
+    for field in &self.0 {
+      match field {
+        // Only the first of each is used, as Monero does
+        ExtraField::PublicKey(key) if keys.is_empty() => keys.push(*key),
+        ExtraField::PublicKeys(these) if additional.is_empty() => additional.extend(these),
+        _ => ()
+      }
+    }
+    keys.extend(additional);
+    keys.iter().filter_map(|key| key.decompress()).collect()
+  }
+
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_raw_vec(ExtraField::serialize, &self.0, w)
+  }
+
+  /// Parse an extra into its fields
+  /// Like Monero, a malformed field ends parsing, yet the fields before it are still returned
+  pub fn deserialize(extra: &[u8]) -> Extra {
+    let mut r = extra;
+    let mut fields = vec![];
+    while !r.is_empty() {
+      match ExtraField::deserialize(&mut r) {
+        Ok(field) => fields.push(field),
+        Err(_) => break
+      }
+    }
+    Extra(fields)
+  }
+}
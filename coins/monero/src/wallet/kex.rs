@@ -0,0 +1,165 @@
+#![allow(non_snake_case)]
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, MultisigView};
+
+use crate::{hash, hash_to_scalar, random_scalar, serialize::*, wallet::base58};
+
+const ROUND_1: &str = "MultisigxV2R1";
+const ROUND_N: &str = "MultisigxV2Rn";
+
+// Monero's generate_signature, a Schnorr signature over H(msg || key || R)
+fn signature_challenge(msg: &[u8; 32], key: &EdwardsPoint, R: &EdwardsPoint) -> Scalar {
+  let mut to_hash = msg.to_vec();
+  to_hash.extend(key.compress().to_bytes());
+  to_hash.extend(R.compress().to_bytes());
+  hash_to_scalar(&to_hash)
+}
+
+/// The content of a wallet2 multisig key exchange message
+#[derive(Clone, PartialEq, Debug)]
+pub enum MultisigKexContent {
+  /// The first round, revealing the participant's private key for the shared view key
+  Round1(Scalar),
+  /// A later round, with its number and the public keys it contributes
+  RoundN(u32, Vec<EdwardsPoint>)
+}
+
+/// A multisig key exchange message, as exchanged by wallet2's make_multisig and
+/// exchange_multisig_keys, signed by the participant's signing key
+#[derive(Clone, PartialEq, Debug)]
+pub struct MultisigKexMessage {
+  pub content: MultisigKexContent,
+  pub signing_key: EdwardsPoint,
+  pub signature: (Scalar, Scalar)
+}
+
+impl MultisigKexMessage {
+  pub fn round(&self) -> u32 {
+    match self.content {
+      MultisigKexContent::Round1(_) => 1,
+      MultisigKexContent::RoundN(round, _) => round
+    }
+  }
+
+  // magic || round || signing key || (private key | public keys)
+  fn signature_hash(&self) -> [u8; 32] {
+    let mut data = match self.content {
+      MultisigKexContent::Round1(_) => ROUND_1,
+      MultisigKexContent::RoundN(..) => ROUND_N
+    }.as_bytes().to_vec();
+    data.extend(self.round().to_le_bytes());
+    data.extend(self.signing_key.compress().to_bytes());
+    match &self.content {
+      MultisigKexContent::Round1(key) => data.extend(key.to_bytes()),
+      MultisigKexContent::RoundN(_, keys) => for key in keys {
+        data.extend(key.compress().to_bytes());
+      }
+    }
+    hash(&data)
+  }
+
+  /// Create a signed message
+  /// Panics if a later round is numbered 1 or lower
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    content: MultisigKexContent,
+    signing_key: &Scalar
+  ) -> MultisigKexMessage {
+    if let MultisigKexContent::RoundN(round, _) = content {
+      assert!(round > 1, "later key exchange round numbered as the first");
+    }
+
+    let mut res = MultisigKexMessage {
+      content,
+      signing_key: signing_key * &ED25519_BASEPOINT_TABLE,
+      signature: (Scalar::zero(), Scalar::zero())
+    };
+    let k = random_scalar(rng);
+    let c = signature_challenge(
+      &res.signature_hash(),
+      &res.signing_key,
+      &(&k * &ED25519_BASEPOINT_TABLE)
+    );
+    res.signature = (c, k - (c * signing_key));
+    res
+  }
+
+  pub fn verify(&self) -> bool {
+    let (c, r) = self.signature;
+    let R = EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, &self.signing_key, &r);
+    // Later rounds must be numbered after the first
+    !matches!(self.content, MultisigKexContent::RoundN(round, _) if round <= 1) &&
+      (signature_challenge(&self.signature_hash(), &self.signing_key, &R) == c)
+  }
+
+  pub fn serialize(&self) -> String {
+    let mut serialized = vec![];
+    let magic = match &self.content {
+      MultisigKexContent::Round1(key) => {
+        serialized.extend(key.to_bytes());
+        ROUND_1
+      },
+      MultisigKexContent::RoundN(round, keys) => {
+        write_varint(&(*round).into(), &mut serialized).unwrap();
+        write_vec(write_point, keys, &mut serialized).unwrap();
+        ROUND_N
+      }
+    };
+    write_point(&self.signing_key, &mut serialized).unwrap();
+    serialized.extend(self.signature.0.to_bytes());
+    serialized.extend(self.signature.1.to_bytes());
+    magic.to_string() + &base58::encode(&serialized)
+  }
+
+  /// Deserialize a message, only returning it if it's validly signed
+  pub fn deserialize(message: &str) -> Option<MultisigKexMessage> {
+    let (round_1, encoded) = if let Some(encoded) = message.strip_prefix(ROUND_1) {
+      (true, encoded)
+    } else {
+      (false, message.strip_prefix(ROUND_N)?)
+    };
+    let serialized = base58::decode(encoded)?;
+    let mut reader = serialized.as_slice();
+
+    let content = if round_1 {
+      MultisigKexContent::Round1(read_scalar(&mut reader).ok()?)
+    } else {
+      MultisigKexContent::RoundN(
+        read_varint(&mut reader).ok()?.try_into().ok().filter(|round| *round > 1)?,
+        read_vec(read_point, &mut reader).ok()?
+      )
+    };
+    let res = MultisigKexMessage {
+      content,
+      signing_key: read_point(&mut reader).ok()?,
+      signature: (read_scalar(&mut reader).ok()?, read_scalar(&mut reader).ok()?)
+    };
+
+    Some(res).filter(|res| reader.is_empty() && res.verify())
+  }
+
+  /// Create a first round message for a FROST participant, signed by their share for the signing
+  /// set viewed and revealing the multisig's private view key
+  /// wallet2 aggregates keys differently, so this won't let it derive the same multisig, yet lets
+  /// its tooling attribute messages to participants
+  #[cfg(feature = "multisig")]
+  pub fn from_view<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    view: &MultisigView<Ed25519>,
+    private_view: &Scalar
+  ) -> MultisigKexMessage {
+    MultisigKexMessage::new(rng, MultisigKexContent::Round1(*private_view), &view.secret_share().0)
+  }
+
+  /// The FROST participant who signed this message, if it was signed by their share for the
+  /// signing set viewed
+  #[cfg(feature = "multisig")]
+  pub fn participant(&self, view: &MultisigView<Ed25519>) -> Option<u16> {
+    view.included().into_iter().find(|l| view.verification_share(*l).0 == self.signing_key)
+  }
+}
@@ -0,0 +1,166 @@
+use std::collections::{HashMap, BTreeMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{traits::Identity, edwards::EdwardsPoint};
+
+use frost::{curve::{Curve, Ed25519}, FrostError, MultisigKeys, MultisigView};
+
+use crate::{
+  frost::{MultisigError, DLEqProof, read_dleq},
+  wallet::{TransactionError, SpendableOutput}
+};
+
+// Size of a serialized share, the image share followed by its DLEq proof
+pub(crate) const SHARE_LEN: usize = 32 + 64;
+
+#[allow(non_snake_case)]
+fn view(
+  keys: &MultisigKeys<Ed25519>,
+  output: &SpendableOutput,
+  included: &[u16]
+) -> Result<(MultisigView<Ed25519>, EdwardsPoint), TransactionError> {
+  let offset = keys.offset(dalek_ff_group::Scalar(output.key_offset));
+  if offset.group_key().0 != output.key {
+    Err(TransactionError::WrongPrivateKey)?;
+  }
+
+  let view = offset.view(included).map_err(|e| TransactionError::FrostError(e))?;
+  // Equivalent to Monero's hash_to_point, without calling into its C library, as CLSAG does
+  let H = Ed25519::hash_to_G(b"", &output.key.compress().to_bytes()).0;
+  Ok((view, H))
+}
+
+/// An output's key image, along with every participant's share of it and the proof of each share
+/// The shares' proofs prove the key image is for the output without any single party having its
+/// private key, letting a threshold wallet detect its own spends
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyImage {
+  pub image: EdwardsPoint,
+  shares: BTreeMap<u16, Vec<u8>>
+}
+
+#[allow(non_snake_case)]
+impl KeyImage {
+  // Verify every share, summing them into the key image
+  fn from_shares(
+    view: &MultisigView<Ed25519>,
+    H: &EdwardsPoint,
+    shares: BTreeMap<u16, Vec<u8>>
+  ) -> Result<KeyImage, TransactionError> {
+    let included = view.included();
+    if shares.len() != included.len() {
+      Err(
+        TransactionError::FrostError(
+          FrostError::InvalidParticipantQuantity(included.len(), shares.len())
+        )
+      )?;
+    }
+    for l in &included {
+      if !shares.contains_key(l) {
+        Err(TransactionError::FrostError(FrostError::MissingParticipant(*l)))?;
+      }
+    }
+
+    let mut image = EdwardsPoint::identity();
+    for (l, share) in &shares {
+      if share.len() != SHARE_LEN {
+        Err(TransactionError::MultisigError(MultisigError::InvalidKeyImage(*l)))?;
+      }
+      image += read_dleq(share, 0, H, *l, &view.verification_share(*l).0)
+        .map_err(|e| TransactionError::MultisigError(e))?.0;
+    }
+
+    Ok(KeyImage { image, shares })
+  }
+
+  /// Verify this is the key image for the specified output, as spent by the participants whose
+  /// shares it contains
+  pub fn verify(
+    &self,
+    keys: &MultisigKeys<Ed25519>,
+    output: &SpendableOutput
+  ) -> Result<(), TransactionError> {
+    let (view, H) = view(keys, output, &self.shares.keys().copied().collect::<Vec<_>>())?;
+    if KeyImage::from_shares(&view, &H, self.shares.clone())?.image != self.image {
+      Err(TransactionError::MultisigError(MultisigError::InvalidKeyImage(view.included()[0])))?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut res = Vec::with_capacity(32 + 2 + (self.shares.len() * (2 + SHARE_LEN)));
+    res.extend(self.image.compress().to_bytes());
+    res.extend(u16::try_from(self.shares.len()).unwrap().to_le_bytes());
+    for (l, share) in &self.shares {
+      res.extend(l.to_le_bytes());
+      res.extend(share);
+    }
+    res
+  }
+
+  /// Deserialize a key image. Its shares are only checked by verify
+  pub fn deserialize(serialized: &[u8]) -> Option<KeyImage> {
+    let image = Ed25519::G_from_slice(serialized.get(.. 32)?).ok()?.0;
+    let len = u16::from_le_bytes(serialized.get(32 .. 34)?.try_into().unwrap());
+
+    let mut shares = BTreeMap::new();
+    let mut cursor = 34;
+    for _ in 0 .. len {
+      let l = u16::from_le_bytes(serialized.get(cursor .. (cursor + 2))?.try_into().unwrap());
+      cursor += 2;
+      if shares.insert(l, serialized.get(cursor .. (cursor + SHARE_LEN))?.to_vec()).is_some() {
+        None?;
+      }
+      cursor += SHARE_LEN;
+    }
+    if cursor != serialized.len() {
+      None?;
+    }
+
+    Some(KeyImage { image, shares })
+  }
+}
+
+/// Machine to compute an output's key image with the rest of the multisig, separately from
+/// signing. Takes a single round, where every participant sends their share
+#[allow(non_snake_case)]
+pub struct KeyImageMachine {
+  i: u16,
+  view: MultisigView<Ed25519>,
+  H: EdwardsPoint,
+  share: Option<Vec<u8>>
+}
+
+#[allow(non_snake_case)]
+impl KeyImageMachine {
+  pub fn new(
+    keys: &MultisigKeys<Ed25519>,
+    output: &SpendableOutput,
+    included: &[u16]
+  ) -> Result<KeyImageMachine, TransactionError> {
+    let (view, H) = view(keys, output, included)?;
+    Ok(KeyImageMachine { i: keys.params().i(), view, H, share: None })
+  }
+
+  /// Our share of the key image, x_i * Hp(P), with a proof it uses the same x_i as our
+  /// verification share
+  pub fn share<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Vec<u8> {
+    let secret = self.view.secret_share().0;
+    let mut share = Vec::with_capacity(SHARE_LEN);
+    share.extend((secret * self.H).compress().to_bytes());
+    share.extend(DLEqProof::prove(rng, &self.H, &secret).serialize());
+    self.share = Some(share.clone());
+    share
+  }
+
+  /// Combine the other participants' shares with our own into the key image
+  pub fn complete(self, shares: HashMap<u16, Vec<u8>>) -> Result<KeyImage, TransactionError> {
+    let mut shares = shares.into_iter().collect::<BTreeMap<_, _>>();
+    if shares.contains_key(&self.i) {
+      Err(TransactionError::FrostError(FrostError::DuplicatedIndex(self.i.into())))?;
+    }
+    shares.insert(self.i, self.share.expect("completing a key image without having shared"));
+    KeyImage::from_shares(&self.view, &self.H, shares)
+  }
+}
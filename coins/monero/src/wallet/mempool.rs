@@ -0,0 +1,142 @@
+use std::collections::{HashSet, HashMap};
+
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  transaction::Transaction,
+  rpc::{RpcError, Rpc},
+  wallet::{SpendableOutput, Subaddresses}
+};
+
+/// A change in the status of a pool transaction which sent outputs to the wallet
+#[derive(Clone, PartialEq, Debug)]
+pub enum MempoolEvent {
+  /// A transaction in the pool sent these outputs to the wallet, which are unconfirmed
+  Received(Vec<SpendableOutput>),
+  /// A transaction which sent outputs to the wallet was mined, in the block at this height
+  Mined([u8; 32], usize),
+  /// A transaction which sent outputs to the wallet left the pool without being mined
+  Dropped([u8; 32])
+}
+
+/// Scans the transactions in the daemon's pool for outputs sent to the wallet, tracking them until
+/// they're mined or dropped
+/// Pool transactions may be fetched by polling, or as notified by a ZmqSubscriber, with blocks
+/// passed to block so transactions which leave the pool can be identified as mined
+#[derive(Clone, Debug)]
+pub struct MempoolScanner {
+  view: Scalar,
+  spend: EdwardsPoint,
+  subaddresses: Subaddresses,
+  // Height of the next block to check for mined transactions
+  height: usize,
+  // Pool transactions which were scanned, so they're only fetched and scanned once
+  seen: HashSet<[u8; 32]>,
+  // Transactions mined in the blocks checked since the pool was last updated, which a pool fetched
+  // before those blocks may still contain
+  mined: HashSet<[u8; 32]>,
+  // Pool transactions which sent outputs to the wallet
+  unconfirmed: HashMap<[u8; 32], Vec<SpendableOutput>>
+}
+
+impl MempoolScanner {
+  /// Create a scanner, which will check blocks from the specified height onwards for mined
+  /// transactions
+  pub fn new(
+    view: Scalar,
+    spend: EdwardsPoint,
+    subaddresses: Subaddresses,
+    height: usize
+  ) -> MempoolScanner {
+    MempoolScanner {
+      view,
+      spend,
+      subaddresses,
+      height,
+      seen: HashSet::new(),
+      mined: HashSet::new(),
+      unconfirmed: HashMap::new()
+    }
+  }
+
+  /// Outputs sent to the wallet by transactions currently in the pool
+  pub fn unconfirmed(&self) -> Vec<SpendableOutput> {
+    self.unconfirmed.values().flatten().cloned().collect()
+  }
+
+  /// Scan a transaction in the pool
+  pub fn scan(&mut self, tx: &Transaction) -> Option<MempoolEvent> {
+    let hash = tx.hash();
+    if !self.seen.insert(hash) {
+      None?;
+    }
+
+    let outputs = tx.scan_subaddresses(self.view, self.spend, &self.subaddresses).0;
+    if outputs.is_empty() {
+      None?;
+    }
+    self.unconfirmed.insert(hash, outputs.clone());
+    Some(MempoolEvent::Received(outputs))
+  }
+
+  /// Check the next block for pool transactions which were mined
+  pub fn block(&mut self, txs: &[[u8; 32]]) -> Vec<MempoolEvent> {
+    let mut res = vec![];
+    for tx in txs {
+      self.seen.remove(tx);
+      self.mined.insert(*tx);
+      if self.unconfirmed.remove(tx).is_some() {
+        res.push(MempoolEvent::Mined(*tx, self.height));
+      }
+    }
+    self.height += 1;
+    res
+  }
+
+  /// Update the scanner with the pool's current transactions, returning the transactions which
+  /// were dropped and those which have yet to be scanned
+  /// All blocks mined before the pool was fetched must have been checked, so transactions mined
+  /// aren't considered dropped
+  /// Transactions mined in the blocks checked since the last update are ignored, so a pool fetched
+  /// before those blocks doesn't have them scanned again
+  pub fn pool(&mut self, pool: &[[u8; 32]]) -> (Vec<MempoolEvent>, Vec<[u8; 32]>) {
+    let pool =
+      pool.iter().filter(|tx| !self.mined.contains(*tx)).copied().collect::<HashSet<_>>();
+    self.mined.clear();
+    self.seen.retain(|tx| pool.contains(tx));
+
+    let mut dropped = self.unconfirmed.keys().filter(|tx| !pool.contains(*tx)).copied()
+      .collect::<Vec<_>>();
+    dropped.sort();
+    for tx in &dropped {
+      self.unconfirmed.remove(tx);
+    }
+
+    (
+      dropped.into_iter().map(MempoolEvent::Dropped).collect(),
+      pool.into_iter().filter(|tx| !self.seen.contains(tx)).collect()
+    )
+  }
+
+  /// Fetch the pool and any new blocks, returning what changed since the last poll
+  pub async fn poll(&mut self, rpc: &Rpc) -> Result<Vec<MempoolEvent>, RpcError> {
+    // Fetch the pool before the height, so any transaction which left the pool before it was
+    // fetched is either within the blocks checked or was dropped
+    // Transactions mined after the pool was fetched are within the blocks checked, and excluded
+    // from the pool when it's updated
+    let pool = rpc.get_transaction_pool_hashes().await?;
+
+    let mut res = vec![];
+    for height in self.height .. rpc.get_height().await? {
+      res.extend(self.block(&rpc.get_block(height).await?.txs));
+    }
+
+    let (dropped, new) = self.pool(&pool);
+    res.extend(dropped);
+    // Transactions may leave the pool before they're fetched
+    for tx in rpc.get_transactions_possible(&new).await? {
+      res.extend(self.scan(&tx));
+    }
+    Ok(res)
+  }
+}
@@ -1,11 +1,24 @@
 use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
 
 use crate::{
+  Commitment,
   hash, hash_to_scalar,
   serialize::write_varint,
+  ringct::EncryptedAmount,
   transaction::Input
 };
 
+pub(crate) mod base58;
+mod extra;
+pub use extra::{ExtraField, Extra};
+
+mod address;
+pub use address::{Network, AddressType, AddressError, Address};
+
+mod subaddress;
+pub(crate) use subaddress::subaddress_derivation;
+pub use subaddress::{SubaddressIndex, subaddress_spend_key, subaddress, Subaddresses};
+
 mod scan;
 pub use scan::SpendableOutput;
 
@@ -13,10 +26,54 @@ pub(crate) mod decoys;
 pub(crate) use decoys::Decoys;
 
 mod send;
-pub use send::{Fee, TransactionError, SignableTransaction};
+pub use send::{
+  Fee, Priority, TransactionError,
+  Candidate, InputSelector, LargestFirst, BranchAndBound, AgeAware,
+  SignableTransaction
+};
 #[cfg(feature = "multisig")]
 pub use send::TransactionMachine;
 
+mod store;
+pub use store::{WalletTransaction, WalletStore, scan_into, MemWalletStore, FileWalletStore};
+
+mod view;
+pub use view::{ScanningWallet, ViewWallet, SpendWallet};
+
+mod subscription;
+pub use subscription::{ReceivedOutput, PaymentEvent, PaymentSubscription};
+
+mod mempool;
+pub use mempool::{MempoolEvent, MempoolScanner};
+
+mod proof;
+pub use proof::{TxProof, SpendProof};
+
+mod reserve;
+pub use reserve::{ReserveProofEntry, ReserveProof};
+#[cfg(feature = "multisig")]
+pub use reserve::{ReserveProofHram, ReserveProofMachines, ReserveProofMultisig};
+
+#[cfg(feature = "multisig")]
+mod key_image;
+#[cfg(feature = "multisig")]
+pub use key_image::{KeyImage, KeyImageMachine};
+
+mod cold;
+pub use cold::{
+  export_outputs, import_outputs, SignedKeyImage, export_key_images, import_key_images,
+  UnsignedTransaction, export_unsigned, import_unsigned,
+  SignedTransaction, export_signed, import_signed
+};
+#[cfg(feature = "multisig")]
+pub use cold::{export_key_image_shares, import_key_image_shares};
+
+mod kex;
+pub use kex::{MultisigKexContent, MultisigKexMessage};
+
+pub(crate) mod polyseed;
+pub use polyseed::{PolyseedError, Polyseed};
+
 fn key_image_sort(x: &EdwardsPoint, y: &EdwardsPoint) -> std::cmp::Ordering {
   x.compress().to_bytes().cmp(&y.compress().to_bytes()).reverse()
 }
@@ -35,17 +92,42 @@ pub(crate) fn uniqueness(inputs: &[Input]) -> [u8; 32] {
   hash(&u)
 }
 
+// H("view_tag" || 8Ra || o)[0], letting scanners skip deriving the output key for almost every
+// output which isn't theirs
+pub(crate) fn view_tag(derivation: &EdwardsPoint, o: usize) -> u8 {
+  let mut tag = b"view_tag".to_vec();
+  tag.extend(derivation.compress().to_bytes());
+  write_varint(&o.try_into().unwrap(), &mut tag).unwrap();
+  hash(&tag)[0]
+}
+
 // Hs(8Ra || o) with https://github.com/monero-project/research-lab/issues/103 as an option
+// Also returns the output's view tag, which is independent of uniqueness
 #[allow(non_snake_case)]
-pub(crate) fn shared_key(uniqueness: Option<[u8; 32]>, s: Scalar, P: &EdwardsPoint, o: usize) -> Scalar {
+pub(crate) fn shared_key(
+  uniqueness: Option<[u8; 32]>,
+  s: Scalar,
+  P: &EdwardsPoint,
+  o: usize
+) -> (u8, Scalar) {
+  // 8Ra
+  derivation_shared_key(uniqueness, &(s * P).mul_by_cofactor(), o)
+}
+
+// shared_key, for an already calculated derivation
+pub(crate) fn derivation_shared_key(
+  uniqueness: Option<[u8; 32]>,
+  derivation: &EdwardsPoint,
+  o: usize
+) -> (u8, Scalar) {
   // uniqueness
   let mut shared = uniqueness.map_or(vec![], |uniqueness| uniqueness.to_vec());
   // || 8Ra
-  shared.extend((s * P).mul_by_cofactor().compress().to_bytes().to_vec());
+  shared.extend(derivation.compress().to_bytes().to_vec());
   // || o
   write_varint(&o.try_into().unwrap(), &mut shared).unwrap();
   // Hs()
-  hash_to_scalar(&shared)
+  (view_tag(derivation, o), hash_to_scalar(&shared))
 }
 
 pub(crate) fn amount_encryption(amount: u64, key: Scalar) -> [u8; 8] {
@@ -54,8 +136,31 @@ pub(crate) fn amount_encryption(amount: u64, key: Scalar) -> [u8; 8] {
   (amount ^ u64::from_le_bytes(hash(&amount_mask)[0 .. 8].try_into().unwrap())).to_le_bytes()
 }
 
-fn amount_decryption(amount: [u8; 8], key: Scalar) -> u64 {
-  u64::from_le_bytes(amount_encryption(u64::from_le_bytes(amount), key))
+// Decrypt an amount, returning the commitment it's expected to open
+fn amount_decryption(amount: &EncryptedAmount, key: Scalar) -> Option<Commitment> {
+  match amount {
+    // The original scheme added Hs(key) to the mask and Hs(Hs(key)) to the amount, as scalars
+    EncryptedAmount::Original { mask, amount } => {
+      let mask_key = hash_to_scalar(&key.to_bytes());
+      let amount_key = hash_to_scalar(&mask_key.to_bytes());
+      let amount = (Scalar::from_bytes_mod_order(*amount) - amount_key).to_bytes();
+      if amount[8 ..] != [0; 24] {
+        None?;
+      }
+      Some(
+        Commitment::new(
+          Scalar::from_bytes_mod_order(*mask) - mask_key,
+          u64::from_le_bytes(amount[.. 8].try_into().unwrap())
+        )
+      )
+    },
+    EncryptedAmount::Compact { amount } => Some(
+      Commitment::new(
+        commitment_mask(key),
+        u64::from_le_bytes(amount_encryption(u64::from_le_bytes(*amount), key))
+      )
+    )
+  }
 }
 
 pub(crate) fn commitment_mask(shared_key: Scalar) -> Scalar {
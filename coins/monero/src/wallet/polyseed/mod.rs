@@ -0,0 +1,244 @@
+use std::{fmt, str::FromStr};
+
+use lazy_static::lazy_static;
+use thiserror::Error;
+
+use rand_core::{RngCore, CryptoRng};
+
+use sha2::Sha256;
+use hmac::{Mac, Hmac};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::hash_to_scalar;
+
+// The BIP-39 English word list, which Polyseed uses for English seeds
+lazy_static! {
+  static ref ENGLISH: Vec<&'static str> = include_str!("english.txt").lines().collect();
+}
+
+const COIN_MONERO: u16 = 0;
+
+const WORDS: usize = 16;
+// Bits of the secret encoded by each word, after the checksum word
+const DATA_WORD_BITS: usize = 10;
+const SECRET_BITS: usize = 150;
+const SECRET_LEN: usize = 32;
+// Words can be abbreviated to this many characters, as each word's prefix is unique
+const PREFIX_LEN: usize = 4;
+
+const DATE_BITS: u16 = 10;
+const DATE_MASK: u16 = (1 << DATE_BITS) - 1;
+// Birthdays are in units of a twelfth of a year since November 1st, 2021
+const EPOCH: u64 = 1635768000;
+const TIME_STEP: u64 = 2629746;
+
+const USER_FEATURES_MASK: u8 = 0b111;
+const RESERVED_FEATURES_MASK: u8 = 0b1000;
+const ENCRYPTED_MASK: u8 = 0b10000;
+
+const KDF_ITERATIONS: u32 = 10000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Error, Debug)]
+pub enum PolyseedError {
+  #[error("invalid amount of words ({0})")]
+  InvalidWordCount(usize),
+  #[error("unknown word ({0})")]
+  UnknownWord(usize),
+  #[error("invalid checksum")]
+  InvalidChecksum,
+  #[error("unsupported features ({0})")]
+  UnsupportedFeatures(u8),
+  #[error("seed is encrypted")]
+  Encrypted
+}
+
+// Multiply by 2 within GF(2048), defined by x^11 + x^2 + 1
+fn gf_mul2(x: u16) -> u16 {
+  if x < 1024 {
+    2 * x
+  } else {
+    ((2 * x) ^ 0x805) & 0x7ff
+  }
+}
+
+// Evaluate the polynomial with the words as coefficients at x = 2
+fn gf_eval(words: &[u16; WORDS]) -> u16 {
+  words.iter().rev().skip(1).fold(words[WORDS - 1], |res, word| gf_mul2(res) ^ word)
+}
+
+pub(crate) fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+  let mac = Hmac::<Sha256>::new_from_slice(password).unwrap();
+  let mut round = mac.clone();
+  round.update(salt);
+  round.update(&1u32.to_be_bytes());
+  let mut u: [u8; 32] = round.finalize().into_bytes().into();
+
+  let mut res = u;
+  for _ in 1 .. iterations {
+    let mut round = mac.clone();
+    round.update(&u);
+    u = round.finalize().into_bytes().into();
+    for (res, u) in res.iter_mut().zip(u) {
+      *res ^= u;
+    }
+  }
+  res
+}
+
+/// A Polyseed, a 16 word mnemonic encoding a secret, the wallet's birthday, and feature bits
+#[derive(Clone, PartialEq, Eq)]
+pub struct Polyseed {
+  // The first 150 bits of the secret, with the rest zero
+  pub(crate) secret: [u8; SECRET_LEN],
+  birthday: u16,
+  features: u8
+}
+
+impl fmt::Debug for Polyseed {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Polyseed")
+      .field("birthday", &self.birthday)
+      .field("features", &self.features)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Polyseed {
+  /// Create a new seed, with the specified user features (the lowest 3 bits), for a wallet created
+  /// at the specified UNIX timestamp
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    features: u8,
+    time: u64
+  ) -> Result<Polyseed, PolyseedError> {
+    if (features & !USER_FEATURES_MASK) != 0 {
+      Err(PolyseedError::UnsupportedFeatures(features))?;
+    }
+
+    let mut secret = [0; SECRET_LEN];
+    rng.fill_bytes(&mut secret[.. SECRET_BITS.div_ceil(8)]);
+    secret[SECRET_BITS / 8] &= (1 << (SECRET_BITS % 8)) - 1;
+
+    let birthday = u16::try_from((time.saturating_sub(EPOCH) / TIME_STEP) & u64::from(DATE_MASK))
+      .unwrap();
+    Ok(Polyseed { secret, birthday, features })
+  }
+
+  /// The UNIX timestamp the wallet was created at, rounded down to a twelfth of a year
+  pub fn birthday(&self) -> u64 {
+    EPOCH + (u64::from(self.birthday) * TIME_STEP)
+  }
+
+  /// The user features set
+  pub fn features(&self) -> u8 {
+    self.features & USER_FEATURES_MASK
+  }
+
+  /// If this seed is encrypted with a password, which isn't supported
+  pub fn encrypted(&self) -> bool {
+    (self.features & ENCRYPTED_MASK) != 0
+  }
+
+  /// The private spend key, from which all other keys are derived
+  pub fn spend_key(&self) -> Result<Scalar, PolyseedError> {
+    if self.encrypted() {
+      Err(PolyseedError::Encrypted)?;
+    }
+
+    let mut salt = [0; 32];
+    salt[.. 12].copy_from_slice(b"POLYSEED key");
+    salt[13 .. 16].copy_from_slice(&[0xff; 3]);
+    salt[16 .. 20].copy_from_slice(&u32::from(COIN_MONERO).to_le_bytes());
+    salt[20 .. 24].copy_from_slice(&u32::from(self.birthday).to_le_bytes());
+    salt[24 .. 28].copy_from_slice(&u32::from(self.features).to_le_bytes());
+    Ok(Scalar::from_bytes_mod_order(pbkdf2_sha256(&self.secret, &salt, KDF_ITERATIONS)))
+  }
+
+  /// The private view key, derived from the spend key as Monero does
+  pub fn view_key(&self) -> Result<Scalar, PolyseedError> {
+    Ok(hash_to_scalar(&self.spend_key()?.to_bytes()))
+  }
+
+  // The secret's bits, most significant first, with the final partial byte's bits right aligned
+  fn secret_bits(&self) -> impl Iterator<Item = bool> + '_ {
+    (0 .. SECRET_BITS).map(|i| {
+      let (byte, bit) = (i / 8, i % 8);
+      let bits = if byte == (SECRET_BITS / 8) { SECRET_BITS % 8 } else { 8 };
+      ((self.secret[byte] >> (bits - 1 - bit)) & 1) == 1
+    })
+  }
+
+  fn to_indexes(&self) -> [u16; WORDS] {
+    // The birthday and features, spread across the lowest bit of each data word
+    let extra = (u16::from(self.features) << DATE_BITS) | self.birthday;
+
+    let mut words = [0; WORDS];
+    let mut secret = self.secret_bits();
+    for (i, word) in words.iter_mut().skip(1).enumerate() {
+      for _ in 0 .. DATA_WORD_BITS {
+        *word = (*word << 1) | u16::from(secret.next().unwrap());
+      }
+      *word = (*word << 1) | ((extra >> (WORDS - 2 - i)) & 1);
+    }
+
+    words[0] = gf_eval(&words);
+    words[1] ^= COIN_MONERO;
+    words
+  }
+
+  fn from_indexes(mut words: [u16; WORDS]) -> Result<Polyseed, PolyseedError> {
+    words[1] ^= COIN_MONERO;
+    if gf_eval(&words) != 0 {
+      Err(PolyseedError::InvalidChecksum)?;
+    }
+
+    let mut extra = 0;
+    let mut bits = Vec::with_capacity(SECRET_BITS);
+    for word in &words[1 ..] {
+      extra = (extra << 1) | (word & 1);
+      bits.extend((1 ..= DATA_WORD_BITS).rev().map(|bit| ((word >> bit) & 1) == 1));
+    }
+
+    let mut secret = [0; SECRET_LEN];
+    for (byte, bits) in secret.iter_mut().zip(bits.chunks(8)) {
+      *byte = bits.iter().fold(0, |byte, bit| (byte << 1) | u8::from(*bit));
+    }
+
+    let features = u8::try_from(extra >> DATE_BITS).unwrap();
+    if (features & RESERVED_FEATURES_MASK) != 0 {
+      Err(PolyseedError::UnsupportedFeatures(features))?;
+    }
+    Ok(Polyseed { secret, birthday: extra & DATE_MASK, features })
+  }
+}
+
+impl fmt::Display for Polyseed {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let words = self.to_indexes().map(|word| ENGLISH[usize::from(word)]);
+    write!(f, "{}", words.join(" "))
+  }
+}
+
+impl FromStr for Polyseed {
+  type Err = PolyseedError;
+
+  /// Parse an English seed, where words may be abbreviated to their first four characters
+  fn from_str(seed: &str) -> Result<Polyseed, PolyseedError> {
+    let words = seed.split_whitespace().collect::<Vec<_>>();
+    if words.len() != WORDS {
+      Err(PolyseedError::InvalidWordCount(words.len()))?;
+    }
+
+    let mut indexes = [0; WORDS];
+    for (i, word) in words.iter().enumerate() {
+      let word = word.to_lowercase();
+      indexes[i] = u16::try_from(
+        ENGLISH.iter().position(|candidate| {
+          (*candidate == word) || ((word.len() >= PREFIX_LEN) && candidate.starts_with(&word))
+        }).ok_or(PolyseedError::UnknownWord(i))?
+      ).unwrap();
+    }
+    Polyseed::from_indexes(indexes)
+  }
+}
@@ -0,0 +1,428 @@
+#![allow(non_snake_case)]
+
+use lazy_static::lazy_static;
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  constants::{ED25519_BASEPOINT_TABLE, ED25519_BASEPOINT_POINT},
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY}
+};
+
+#[cfg(feature = "multisig")]
+use transcript::{Transcript, RecommendedTranscript};
+#[cfg(feature = "multisig")]
+use frost::{curve::Ed25519, MultisigKeys, sign::AlgorithmMachine};
+
+use crate::{
+  hash, hash_to_scalar, generate_key_image, random_scalar,
+  ring_signature::RingSignature,
+  transaction::{Input, Transaction},
+  rpc::{RpcError, Rpc},
+  wallet::{
+    base58, AddressType, Address, SpendableOutput, TransactionError, Extra,
+    uniqueness, derivation_shared_key, amount_decryption
+  }
+};
+#[cfg(feature = "multisig")]
+use crate::ring_signature::RingSignatureMultisig;
+
+lazy_static! {
+  static ref TX_PROOF_SEPARATOR: [u8; 32] = hash(b"TXPROOF_V2");
+}
+
+const OUT_PROOF: &str = "OutProofV2";
+const IN_PROOF: &str = "InProofV2";
+const SPEND_PROOF: &str = "SpendProofV1";
+
+// Length of a base58 encoded key and signature
+const ENCODED_KEY_LEN: usize = 44;
+const ENCODED_SIGNATURE_LEN: usize = 88;
+
+// H(tx hash || message), which every proof signs
+fn proof_hash(tx: &Transaction, message: &[u8]) -> [u8; 32] {
+  let mut data = tx.hash().to_vec();
+  data.extend(message);
+  hash(&data)
+}
+
+// Subaddresses prove against their spend key, instead of G
+fn address_base(address: &Address) -> Option<EdwardsPoint> {
+  Some(address.spend).filter(|_| address.kind == AddressType::Subaddress)
+}
+
+fn tx_proof_challenge(
+  msg: &[u8; 32],
+  R: &EdwardsPoint,
+  A: &EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: &EdwardsPoint,
+  X: &EdwardsPoint,
+  Y: &EdwardsPoint
+) -> Scalar {
+  let mut to_hash = Vec::with_capacity(32 * 8);
+  to_hash.extend(msg);
+  for point in [D, X, Y] {
+    to_hash.extend(point.compress().to_bytes());
+  }
+  to_hash.extend(*TX_PROOF_SEPARATOR);
+  for point in [R, A] {
+    to_hash.extend(point.compress().to_bytes());
+  }
+  to_hash.extend(B.map(|B| B.compress().to_bytes()).unwrap_or([0; 32]));
+  hash_to_scalar(&to_hash)
+}
+
+// Prove R = secret * B (or G) and D = secret * A, as Monero's generate_tx_proof does
+pub(crate) fn sign_tx_proof<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  msg: &[u8; 32],
+  R: &EdwardsPoint,
+  A: &EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: &EdwardsPoint,
+  secret: &Scalar
+) -> (Scalar, Scalar) {
+  let k = random_scalar(rng);
+  let X = k * B.unwrap_or(ED25519_BASEPOINT_POINT);
+  let c = tx_proof_challenge(msg, R, A, B, D, &X, &(k * A));
+  (c, k - (c * secret))
+}
+
+pub(crate) fn verify_tx_proof(
+  msg: &[u8; 32],
+  R: &EdwardsPoint,
+  A: &EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: &EdwardsPoint,
+  (c, r): (Scalar, Scalar)
+) -> bool {
+  let X = (r * B.unwrap_or(ED25519_BASEPOINT_POINT)) + (c * R);
+  let Y = (r * A) + (c * D);
+  tx_proof_challenge(msg, R, A, B, D, &X, &Y) == c
+}
+
+/// A proof of what a transaction sent to an address, without revealing the address's view key
+/// Out proofs are created by the sender, with the transaction's private keys, while in proofs are
+/// created by the recipient, with their private view key
+#[derive(Clone, PartialEq, Debug)]
+pub struct TxProof {
+  /// If this proof was created by the sender
+  pub outgoing: bool,
+  /// The shared secret for each of the transaction's keys, with the (c, r) proving it
+  pub proofs: Vec<(EdwardsPoint, (Scalar, Scalar))>
+}
+
+impl TxProof {
+  /// Prove what a transaction we sent paid the address
+  /// keys are the transaction's private keys, in the order their public keys appear in its extra,
+  /// as returned by SignableTransaction::tx_keys
+  pub fn out_proof<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    tx: &Transaction,
+    message: &[u8],
+    keys: &[Scalar],
+    address: &Address
+  ) -> Result<TxProof, TransactionError> {
+    let pubkeys = Extra::deserialize(&tx.prefix.extra).keys();
+    if pubkeys.len() != keys.len() {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    let msg = proof_hash(tx, message);
+    let B = address_base(address);
+    let mut proofs = Vec::with_capacity(keys.len());
+    for (r, R) in keys.iter().zip(&pubkeys) {
+      if (r * B.unwrap_or(ED25519_BASEPOINT_POINT)) != *R {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      let D = r * address.view;
+      proofs.push((D, sign_tx_proof(rng, &msg, R, &address.view, B, &D, r)));
+    }
+    Ok(TxProof { outgoing: true, proofs })
+  }
+
+  /// Prove what a transaction paid our address, which must be derived from the view key
+  pub fn in_proof<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    tx: &Transaction,
+    message: &[u8],
+    view: &Scalar,
+    address: &Address
+  ) -> Result<TxProof, TransactionError> {
+    let B = address_base(address);
+    if (view * B.unwrap_or(ED25519_BASEPOINT_POINT)) != address.view {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    let msg = proof_hash(tx, message);
+    Ok(
+      TxProof {
+        outgoing: false,
+        proofs: Extra::deserialize(&tx.prefix.extra).keys().iter().map(|R| {
+          let D = view * R;
+          (D, sign_tx_proof(rng, &msg, &address.view, R, B, &D, view))
+        }).collect()
+      }
+    )
+  }
+
+  /// Verify this proof, returning the amount the transaction sent to the address
+  pub fn verify(&self, tx: &Transaction, message: &[u8], address: &Address) -> Option<u64> {
+    let pubkeys = Extra::deserialize(&tx.prefix.extra).keys();
+    if pubkeys.len() != self.proofs.len() {
+      None?;
+    }
+
+    let msg = proof_hash(tx, message);
+    let B = address_base(address);
+    for (R, (D, sig)) in pubkeys.iter().zip(&self.proofs) {
+      let valid = if self.outgoing {
+        verify_tx_proof(&msg, R, &address.view, B, D, *sig)
+      } else {
+        verify_tx_proof(&msg, &address.view, R, B, D, *sig)
+      };
+      if !valid {
+        None?;
+      }
+    }
+
+    // With the shared secrets proven, find the outputs sent to the address as a scan would
+    let uniqueness = uniqueness(&tx.prefix.inputs);
+    let mut received = 0;
+    for (o, output) in tx.prefix.outputs.iter().enumerate() {
+      for (D, _) in &self.proofs {
+        let derivation = D.mul_by_cofactor();
+        let shared_key = [None, Some(uniqueness)].iter().map(
+          |uniqueness| derivation_shared_key(*uniqueness, &derivation, o).1
+        ).find(|key| (output.key - (key * &ED25519_BASEPOINT_TABLE)) == address.spend);
+
+        if let Some(shared_key) = shared_key {
+          received += if output.amount != 0 {
+            output.amount
+          } else {
+            let commitment = tx.rct_signatures.base.ecdh_info.get(o)
+              .and_then(|amount| amount_decryption(amount, shared_key))?;
+            if Some(&commitment.calculate()) != tx.rct_signatures.base.commitments.get(o) {
+              None?;
+            }
+            commitment.amount
+          };
+          break;
+        }
+      }
+    }
+    Some(received)
+  }
+
+  pub fn serialize(&self) -> String {
+    let mut res = (if self.outgoing { OUT_PROOF } else { IN_PROOF }).to_string();
+    for (D, (c, r)) in &self.proofs {
+      res.push_str(&base58::encode(&D.compress().to_bytes()));
+      res.push_str(&base58::encode(&[c.to_bytes(), r.to_bytes()].concat()));
+    }
+    res
+  }
+
+  pub fn deserialize(proof: &str) -> Option<TxProof> {
+    let (outgoing, proofs) = if let Some(proofs) = proof.strip_prefix(OUT_PROOF) {
+      (true, proofs)
+    } else {
+      (false, proof.strip_prefix(IN_PROOF)?)
+    };
+
+    let proof_len = ENCODED_KEY_LEN + ENCODED_SIGNATURE_LEN;
+    if proofs.is_empty() || ((proofs.len() % proof_len) != 0) || (!proofs.is_ascii()) {
+      None?;
+    }
+    Some(
+      TxProof {
+        outgoing,
+        proofs: (0 .. (proofs.len() / proof_len)).map(|i| {
+          let proof = &proofs[(i * proof_len) .. ((i + 1) * proof_len)];
+          let D = base58::decode(&proof[.. ENCODED_KEY_LEN])?;
+          let sig: [u8; 64] = base58::decode(&proof[ENCODED_KEY_LEN ..])?.try_into().ok()?;
+          Some((
+            CompressedEdwardsY(D.try_into().ok()?).decompress()?,
+            (
+              Scalar::from_canonical_bytes(sig[.. 32].try_into().ok()?)?,
+              Scalar::from_canonical_bytes(sig[32 ..].try_into().ok()?)?
+            )
+          ))
+        }).collect::<Option<_>>()?
+      }
+    )
+  }
+}
+
+/// A proof a transaction was created by the holder of the spend key, by signing with every output
+/// it spent
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpendProof {
+  /// A signature for each input, over its ring
+  pub signatures: Vec<RingSignature>
+}
+
+// The key images of a transaction's inputs, if it only has inputs which spend outputs
+fn key_images(tx: &Transaction) -> Option<Vec<EdwardsPoint>> {
+  tx.prefix.inputs.iter().map(|input| match input {
+    Input::ToKey { key_image, .. } => Some(*key_image),
+    Input::Gen(_) => None
+  }).collect()
+}
+
+impl SpendProof {
+  /// Get the ring of every input within the transaction, as needed to create and verify proofs
+  pub async fn rings(rpc: &Rpc, tx: &Transaction) -> Result<Vec<Vec<EdwardsPoint>>, RpcError> {
+    let mut rings = Vec::with_capacity(tx.prefix.inputs.len());
+    for input in &tx.prefix.inputs {
+      match input {
+        Input::ToKey { amount, key_offsets, .. } => {
+          // Offsets are relative to the prior offset
+          let mut indexes = Vec::with_capacity(key_offsets.len());
+          let mut index = 0;
+          for offset in key_offsets {
+            index += offset;
+            indexes.push(index);
+          }
+          rings.push(rpc.get_output_keys(*amount, &indexes).await?);
+        },
+        Input::Gen(_) => {
+          Err(RpcError::InternalError("miner transactions have no rings".to_string()))?
+        }
+      }
+    }
+    Ok(rings)
+  }
+
+  /// The message every input's signature signs, as multisig signing must be provided with
+  pub fn signature_hash(tx: &Transaction, message: &[u8]) -> [u8; 32] {
+    proof_hash(tx, message)
+  }
+
+  /// Prove we created a transaction, where inputs are the outputs it spent in the order of its
+  /// inputs
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    tx: &Transaction,
+    message: &[u8],
+    rings: &[Vec<EdwardsPoint>],
+    spend: &Scalar,
+    inputs: &[SpendableOutput]
+  ) -> Result<SpendProof, TransactionError> {
+    let images = key_images(tx).ok_or(TransactionError::NoInputs)?;
+    if (rings.len() != images.len()) || (inputs.len() != images.len()) {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    let msg = proof_hash(tx, message);
+    let mut signatures = Vec::with_capacity(inputs.len());
+    for ((input, ring), image) in inputs.iter().zip(rings).zip(images) {
+      let key = spend + input.key_offset;
+      if ((&key * &ED25519_BASEPOINT_TABLE) != input.key) || (generate_key_image(&key) != image) {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      let i = ring.iter().position(|member| *member == input.key)
+        .ok_or(TransactionError::WrongPrivateKey)?;
+      signatures.push(RingSignature::sign(rng, &msg, ring, i, &key));
+    }
+    Ok(SpendProof { signatures })
+  }
+
+  /// Create the machines to prove the multisig created a transaction, one per input, where inputs
+  /// are the outputs it spent in the order of its inputs
+  /// Each machine is run as its own signing session, signing SpendProof::signature_hash, with the
+  /// resulting signatures forming the proof
+  #[cfg(feature = "multisig")]
+  pub fn multisig(
+    keys: &MultisigKeys<Ed25519>,
+    mut transcript: RecommendedTranscript,
+    tx: &Transaction,
+    rings: &[Vec<EdwardsPoint>],
+    inputs: &[SpendableOutput],
+    included: &[u16]
+  ) -> Result<Vec<AlgorithmMachine<Ed25519, RingSignatureMultisig>>, TransactionError> {
+    if (rings.len() != tx.prefix.inputs.len()) || (inputs.len() != tx.prefix.inputs.len()) {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    transcript.domain_separate(b"monero_spend_proof");
+    transcript.append_message(b"tx_hash", &tx.hash());
+
+    let mut machines = Vec::with_capacity(inputs.len());
+    for (input, ring) in inputs.iter().zip(rings) {
+      let offset = keys.offset(dalek_ff_group::Scalar(input.key_offset));
+      if offset.group_key().0 != input.key {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      let i = ring.iter().position(|member| *member == input.key)
+        .ok_or(TransactionError::WrongPrivateKey)?;
+
+      machines.push(
+        AlgorithmMachine::new(
+          RingSignatureMultisig::new(transcript.clone(), ring.clone(), i),
+          std::sync::Arc::new(offset),
+          included
+        ).map_err(|e| TransactionError::FrostError(e))?
+      );
+    }
+    Ok(machines)
+  }
+
+  pub fn verify(&self, tx: &Transaction, message: &[u8], rings: &[Vec<EdwardsPoint>]) -> bool {
+    let images = match key_images(tx) {
+      Some(images) => images,
+      None => return false
+    };
+    if (rings.len() != images.len()) || (self.signatures.len() != images.len()) {
+      return false;
+    }
+
+    let msg = proof_hash(tx, message);
+    self.signatures.iter().zip(rings).zip(&images).all(
+      |((signature, ring), image)| signature.verify(&msg, ring, image)
+    )
+  }
+
+  pub fn serialize(&self) -> String {
+    let mut res = SPEND_PROOF.to_string();
+    for signature in &self.signatures {
+      for (c, r) in &signature.sigs {
+        res.push_str(&base58::encode(&[c.to_bytes(), r.to_bytes()].concat()));
+      }
+    }
+    res
+  }
+
+  /// Deserialize a proof for the specified transaction, which determines the size of each ring
+  pub fn deserialize(proof: &str, tx: &Transaction) -> Option<SpendProof> {
+    let proof = proof.strip_prefix(SPEND_PROOF)?;
+    if (!proof.is_ascii()) || ((proof.len() % ENCODED_SIGNATURE_LEN) != 0) {
+      None?;
+    }
+
+    let mut sigs = vec![];
+    for i in 0 .. (proof.len() / ENCODED_SIGNATURE_LEN) {
+      let sig = base58::decode(
+        &proof[(i * ENCODED_SIGNATURE_LEN) .. ((i + 1) * ENCODED_SIGNATURE_LEN)]
+      )?;
+      sigs.push(RingSignature::deserialize(1, &mut sig.as_slice()).ok()?.sigs.swap_remove(0));
+    }
+
+    let mut signatures = vec![];
+    for input in &tx.prefix.inputs {
+      let len = match input {
+        Input::ToKey { key_offsets, .. } => key_offsets.len(),
+        Input::Gen(_) => None?
+      };
+      if sigs.len() < len {
+        None?;
+      }
+      signatures.push(RingSignature { sigs: sigs.drain(.. len).collect() });
+    }
+    if !sigs.is_empty() {
+      None?;
+    }
+    Some(SpendProof { signatures })
+  }
+}
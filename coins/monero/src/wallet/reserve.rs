@@ -0,0 +1,471 @@
+#![allow(non_snake_case)]
+
+use std::io::{self, Read, Write};
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY}
+};
+
+#[cfg(feature = "multisig")]
+use std::sync::Arc;
+#[cfg(feature = "multisig")]
+use transcript::{Transcript, RecommendedTranscript};
+#[cfg(feature = "multisig")]
+use frost::{
+  curve::Ed25519,
+  MultisigKeys,
+  algorithm::{Hram, Schnorr, SchnorrSignature},
+  sign::AlgorithmMachine
+};
+#[cfg(feature = "multisig")]
+use dalek_ff_group as dfg;
+
+use crate::{
+  hash, hash_to_scalar, generate_key_image, random_scalar,
+  serialize::*,
+  ring_signature::RingSignature,
+  transaction::Transaction,
+  rpc::{RpcError, Rpc},
+  wallet::{
+    base58, AddressType, Address, SpendableOutput, TransactionError, Extra,
+    uniqueness, derivation_shared_key, amount_decryption, subaddress_derivation,
+    proof::{sign_tx_proof, verify_tx_proof}
+  }
+};
+#[cfg(feature = "multisig")]
+use crate::ring_signature::RingSignatureMultisig;
+
+const RESERVE_PROOF: &str = "ReserveProofV2";
+
+/// An output proven to be held, with the key image which would be published if it were spent
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReserveProofEntry {
+  pub tx: [u8; 32],
+  pub o: u64,
+  /// The view key times the transaction key the output was derived from
+  pub shared_secret: EdwardsPoint,
+  pub key_image: EdwardsPoint,
+  /// Proof the shared secret is the view key times the transaction key
+  pub shared_secret_sig: (Scalar, Scalar),
+  /// A ring signature, with the output as the only member, proving the key image is the output's
+  pub key_image_sig: RingSignature
+}
+
+/// A proof an address holds the specified outputs, and by extension their amounts
+/// Verifying it solely requires the address and the transactions the outputs were created in
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReserveProof {
+  pub entries: Vec<ReserveProofEntry>,
+  /// A signature from the spend key of every subaddress which received a proven output
+  pub spend_keys: Vec<(EdwardsPoint, (Scalar, Scalar))>
+}
+
+// H(message || spend key || view key || key images), which every signature within the proof signs
+fn reserve_hash(
+  message: &[u8],
+  spend: &EdwardsPoint,
+  view: &EdwardsPoint,
+  images: &[EdwardsPoint]
+) -> [u8; 32] {
+  let mut data = message.to_vec();
+  for point in [spend, view].into_iter().chain(images) {
+    data.extend(point.compress().to_bytes());
+  }
+  hash(&data)
+}
+
+// The challenge of a CryptoNote signature, Hs(msg || A || R)
+fn signature_challenge(msg: &[u8], A: &EdwardsPoint, R: &EdwardsPoint) -> Scalar {
+  let mut data = msg.to_vec();
+  data.extend(A.compress().to_bytes());
+  data.extend(R.compress().to_bytes());
+  hash_to_scalar(&data)
+}
+
+fn verify_signature(msg: &[u8], A: &EdwardsPoint, (c, r): (Scalar, Scalar)) -> bool {
+  signature_challenge(msg, A, &EdwardsPoint::vartime_double_scalar_mul_basepoint(&c, A, &r)) == c
+}
+
+fn read_compressed<R: Read>(r: &mut R) -> io::Result<EdwardsPoint> {
+  CompressedEdwardsY(read_32(r)?).decompress()
+    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid point"))
+}
+
+// Create the entry for an output, without its key image signature, returning the scalar its
+// subaddress's spend key is offset by
+fn entry<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  msg: &[u8; 32],
+  view: &Scalar,
+  tx: &Transaction,
+  output: &SpendableOutput,
+  key_image: EdwardsPoint
+) -> Result<(ReserveProofEntry, Scalar), TransactionError> {
+  let o = usize::from(output.o);
+  if (tx.hash() != output.tx) ||
+    (tx.prefix.outputs.get(o).map(|out| out.key) != Some(output.key)) {
+    Err(TransactionError::WrongPrivateKey)?;
+  }
+
+  // Find the transaction key the output was derived from, as the one resulting in a key offset
+  // matching the output's subaddress
+  let offset = output.subaddress.map(|index| subaddress_derivation(view, index))
+    .unwrap_or_else(Scalar::zero);
+  let uniqueness = uniqueness(&tx.prefix.inputs);
+  let R = Extra::deserialize(&tx.prefix.extra).keys().into_iter().find(|R| {
+    let derivation = (view * R).mul_by_cofactor();
+    [None, Some(uniqueness)].iter().any(|uniqueness| {
+      (output.key_offset - derivation_shared_key(*uniqueness, &derivation, o).1) == offset
+    })
+  }).ok_or(TransactionError::WrongPrivateKey)?;
+
+  let shared_secret = view * R;
+  Ok((
+    ReserveProofEntry {
+      tx: output.tx,
+      o: output.o.into(),
+      shared_secret,
+      key_image,
+      shared_secret_sig: sign_tx_proof(
+        rng,
+        msg,
+        &(view * &ED25519_BASEPOINT_TABLE),
+        &R,
+        None,
+        &shared_secret,
+        view
+      ),
+      key_image_sig: RingSignature { sigs: vec![] }
+    },
+    offset
+  ))
+}
+
+impl ReserveProof {
+  /// Prove the wallet holds the specified outputs, each with the transaction it was created in
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    message: &[u8],
+    view: &Scalar,
+    spend: &Scalar,
+    outputs: &[(&Transaction, &SpendableOutput)]
+  ) -> Result<ReserveProof, TransactionError> {
+    let keys = outputs.iter().map(|(_, output)| spend + output.key_offset).collect::<Vec<_>>();
+    let images = keys.iter().map(generate_key_image).collect::<Vec<_>>();
+    let msg = reserve_hash(
+      message,
+      &(spend * &ED25519_BASEPOINT_TABLE),
+      &(view * &ED25519_BASEPOINT_TABLE),
+      &images
+    );
+
+    let mut entries = Vec::with_capacity(outputs.len());
+    let mut offsets = vec![];
+    for (((tx, output), key), image) in outputs.iter().zip(&keys).zip(images) {
+      let (mut entry, offset) = entry(rng, &msg, view, tx, output, image)?;
+      entry.key_image_sig = RingSignature::sign(rng, &msg, &[output.key], 0, key);
+      entries.push(entry);
+      if !offsets.contains(&offset) {
+        offsets.push(offset);
+      }
+    }
+
+    Ok(
+      ReserveProof {
+        entries,
+        spend_keys: offsets.iter().map(|offset| {
+          let key = spend + offset;
+          let A = &key * &ED25519_BASEPOINT_TABLE;
+          let nonce = random_scalar(rng);
+          let c = signature_challenge(&msg, &A, &(&nonce * &ED25519_BASEPOINT_TABLE));
+          (A, (c, nonce - (c * key)))
+        }).collect()
+      }
+    )
+  }
+
+  /// Verify this proof for the address's primary account, returning the amount of every output
+  /// txs are the transactions the proven outputs were created in, in the same order
+  pub fn amounts(
+    &self,
+    address: &Address,
+    message: &[u8],
+    txs: &[Transaction]
+  ) -> Option<Vec<u64>> {
+    if (address.kind == AddressType::Subaddress) || (txs.len() != self.entries.len()) {
+      None?;
+    }
+
+    let images = self.entries.iter().map(|entry| entry.key_image).collect::<Vec<_>>();
+    // Every output may only be counted once
+    for (i, image) in images.iter().enumerate() {
+      if images[.. i].contains(image) {
+        None?;
+      }
+    }
+
+    let msg = reserve_hash(message, &address.spend, &address.view, &images);
+    if !self.spend_keys.iter().all(|(key, sig)| verify_signature(&msg, key, *sig)) {
+      None?;
+    }
+
+    let mut amounts = Vec::with_capacity(self.entries.len());
+    for (entry, tx) in self.entries.iter().zip(txs) {
+      let o = usize::try_from(entry.o).ok()?;
+      let output = tx.prefix.outputs.get(o).filter(|_| tx.hash() == entry.tx)?;
+
+      if !Extra::deserialize(&tx.prefix.extra).keys().iter().any(|R| verify_tx_proof(
+        &msg,
+        &address.view,
+        R,
+        None,
+        &entry.shared_secret,
+        entry.shared_secret_sig
+      )) {
+        None?;
+      }
+      if !entry.key_image_sig.verify(&msg, &[output.key], &entry.key_image) {
+        None?;
+      }
+
+      // The output must have been sent to one of the signing spend keys
+      let derivation = entry.shared_secret.mul_by_cofactor();
+      let shared_key = [None, Some(uniqueness(&tx.prefix.inputs))].iter().map(
+        |uniqueness| derivation_shared_key(*uniqueness, &derivation, o).1
+      ).find(|key| {
+        let spend = output.key - (key * &ED25519_BASEPOINT_TABLE);
+        self.spend_keys.iter().any(|(signer, _)| *signer == spend)
+      })?;
+
+      amounts.push(
+        if output.amount != 0 {
+          output.amount
+        } else {
+          let commitment = tx.rct_signatures.base.ecdh_info.get(o)
+            .and_then(|amount| amount_decryption(amount, shared_key))?;
+          if Some(&commitment.calculate()) != tx.rct_signatures.base.commitments.get(o) {
+            None?;
+          }
+          commitment.amount
+        }
+      );
+    }
+    Some(amounts)
+  }
+
+  /// Verify this proof, returning the total amount proven
+  pub fn verify(&self, address: &Address, message: &[u8], txs: &[Transaction]) -> Option<u64> {
+    self.amounts(address, message, txs).map(|amounts| amounts.iter().sum())
+  }
+
+  /// Verify this proof against the daemon, returning the total amount proven and how much of it
+  /// has since been spent, or None if the proof is invalid
+  pub async fn check(
+    &self,
+    rpc: &Rpc,
+    address: &Address,
+    message: &[u8]
+  ) -> Result<Option<(u64, u64)>, RpcError> {
+    let txs = rpc.get_transactions(
+      &self.entries.iter().map(|entry| entry.tx).collect::<Vec<_>>()
+    ).await?;
+    let amounts = match self.amounts(address, message, &txs) {
+      Some(amounts) => amounts,
+      None => return Ok(None)
+    };
+
+    let spent = rpc.is_key_image_spent(
+      &self.entries.iter().map(|entry| entry.key_image).collect::<Vec<_>>()
+    ).await?;
+    Ok(Some((
+      amounts.iter().sum(),
+      amounts.iter().zip(spent).filter(|(_, spent)| *spent).map(|(amount, _)| amount).sum()
+    )))
+  }
+
+  pub fn serialize_raw<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(&self.entries.len().try_into().unwrap(), w)?;
+    for entry in &self.entries {
+      w.write_all(&entry.tx)?;
+      write_varint(&entry.o, w)?;
+      write_point(&entry.shared_secret, w)?;
+      write_point(&entry.key_image, w)?;
+      write_scalar(&entry.shared_secret_sig.0, w)?;
+      write_scalar(&entry.shared_secret_sig.1, w)?;
+      entry.key_image_sig.serialize(w)?;
+    }
+
+    write_varint(&self.spend_keys.len().try_into().unwrap(), w)?;
+    for (key, (c, r)) in &self.spend_keys {
+      write_point(key, w)?;
+      write_scalar(c, w)?;
+      write_scalar(r, w)?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> String {
+    let mut serialized = vec![];
+    self.serialize_raw(&mut serialized).unwrap();
+    RESERVE_PROOF.to_string() + &base58::encode(&serialized)
+  }
+
+  pub fn deserialize_raw<R: Read>(r: &mut R) -> io::Result<ReserveProof> {
+    Ok(
+      ReserveProof {
+        entries: read_vec(|r| Ok(ReserveProofEntry {
+          tx: read_32(r)?,
+          o: read_varint(r)?,
+          shared_secret: read_compressed(r)?,
+          key_image: read_point(r)?,
+          shared_secret_sig: (read_scalar(r)?, read_scalar(r)?),
+          key_image_sig: RingSignature::deserialize(1, r)?
+        }), r)?,
+        spend_keys: read_vec(|r| Ok((read_compressed(r)?, (read_scalar(r)?, read_scalar(r)?))), r)?
+      }
+    )
+  }
+
+  pub fn deserialize(proof: &str) -> Option<ReserveProof> {
+    let serialized = base58::decode(proof.strip_prefix(RESERVE_PROOF)?)?;
+    let mut reader = serialized.as_slice();
+    let proof = ReserveProof::deserialize_raw(&mut reader).ok()?;
+    Some(proof).filter(|_| reader.is_empty())
+  }
+}
+
+/// HRAM making FROST's Schnorr signatures CryptoNote signatures
+/// CryptoNote signatures use the response k - cx, so the challenge is negated to fit FROST's k + cx
+#[cfg(feature = "multisig")]
+#[derive(Clone)]
+pub struct ReserveProofHram;
+#[cfg(feature = "multisig")]
+impl Hram<Ed25519> for ReserveProofHram {
+  fn hram(R: &dfg::EdwardsPoint, A: &dfg::EdwardsPoint, m: &[u8]) -> dfg::Scalar {
+    dfg::Scalar(-signature_challenge(m, &A.0, &R.0))
+  }
+}
+
+/// Machines to create a reserve proof with a multisig, each run as its own signing session
+/// signing ReserveProofMultisig::hash
+#[cfg(feature = "multisig")]
+pub struct ReserveProofMachines {
+  /// Machines for each output's key image signature, in order
+  pub key_images: Vec<AlgorithmMachine<Ed25519, RingSignatureMultisig>>,
+  /// Machines for each receiving subaddress's spend key signature, in order
+  pub spend_keys: Vec<AlgorithmMachine<Ed25519, Schnorr<Ed25519, ReserveProofHram>>>
+}
+
+/// A reserve proof being created by a multisig
+/// As the proof signs every output's key image, they must already be known, such as via
+/// KeyImageMachine
+#[cfg(feature = "multisig")]
+#[derive(Clone, Debug)]
+pub struct ReserveProofMultisig {
+  msg: [u8; 32],
+  entries: Vec<ReserveProofEntry>,
+  spend_keys: Vec<EdwardsPoint>
+}
+
+#[cfg(feature = "multisig")]
+impl ReserveProofMultisig {
+  /// Start proving the multisig holds the specified outputs, each with the transaction it was
+  /// created in and its key image
+  #[allow(clippy::too_many_arguments)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    keys: &MultisigKeys<Ed25519>,
+    mut transcript: RecommendedTranscript,
+    view: &Scalar,
+    message: &[u8],
+    outputs: &[(&Transaction, &SpendableOutput, EdwardsPoint)],
+    included: &[u16]
+  ) -> Result<(ReserveProofMultisig, ReserveProofMachines), TransactionError> {
+    let images = outputs.iter().map(|(_, _, image)| *image).collect::<Vec<_>>();
+    let msg = reserve_hash(
+      message,
+      &keys.group_key().0,
+      &(view * &ED25519_BASEPOINT_TABLE),
+      &images
+    );
+
+    transcript.domain_separate(b"monero_reserve_proof");
+    transcript.append_message(b"message", &msg);
+
+    let mut entries = Vec::with_capacity(outputs.len());
+    let mut machines = ReserveProofMachines { key_images: vec![], spend_keys: vec![] };
+    let mut offsets = vec![];
+    for (tx, output, image) in outputs {
+      let (entry, offset) = entry(rng, &msg, view, tx, output, *image)?;
+      entries.push(entry);
+
+      let output_keys = keys.offset(dfg::Scalar(output.key_offset));
+      if output_keys.group_key().0 != output.key {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      machines.key_images.push(
+        AlgorithmMachine::new(
+          RingSignatureMultisig::new(transcript.clone(), vec![output.key], 0),
+          Arc::new(output_keys),
+          included
+        ).map_err(TransactionError::FrostError)?
+      );
+
+      if !offsets.contains(&offset) {
+        offsets.push(offset);
+      }
+    }
+
+    let mut spend_keys = Vec::with_capacity(offsets.len());
+    for offset in offsets {
+      let subaddress_keys = keys.offset(dfg::Scalar(offset));
+      spend_keys.push(subaddress_keys.group_key().0);
+      machines.spend_keys.push(
+        AlgorithmMachine::new(Schnorr::new(), Arc::new(subaddress_keys), included)
+          .map_err(TransactionError::FrostError)?
+      );
+    }
+
+    Ok((ReserveProofMultisig { msg, entries, spend_keys }, machines))
+  }
+
+  /// The message every machine signs
+  pub fn hash(&self) -> [u8; 32] {
+    self.msg
+  }
+
+  /// Complete the proof with the signatures produced by the machines, in the same order
+  pub fn complete(
+    self,
+    key_images: Vec<(RingSignature, EdwardsPoint)>,
+    spend_keys: Vec<SchnorrSignature<Ed25519>>
+  ) -> Result<ReserveProof, TransactionError> {
+    if (key_images.len() != self.entries.len()) || (spend_keys.len() != self.spend_keys.len()) {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+
+    let mut entries = self.entries;
+    for (entry, (sig, image)) in entries.iter_mut().zip(key_images) {
+      // The key images provided when this was created must be the ones which were signed for
+      if entry.key_image != image {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      entry.key_image_sig = sig;
+    }
+
+    let msg = self.msg;
+    Ok(
+      ReserveProof {
+        entries,
+        spend_keys: self.spend_keys.into_iter().zip(spend_keys).map(|(key, sig)| (
+          key,
+          (signature_challenge(&msg, &key, &sig.R.0), sig.s.0)
+        )).collect()
+      }
+    )
+  }
+}
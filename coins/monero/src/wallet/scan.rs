@@ -1,4 +1,4 @@
-use std::convert::TryFrom;
+use std::io::Read;
 
 use curve25519_dalek::{
   constants::ED25519_BASEPOINT_TABLE,
@@ -6,13 +6,14 @@ use curve25519_dalek::{
   edwards::EdwardsPoint
 };
 
-use monero::{consensus::deserialize, blockdata::transaction::ExtraField};
-
 use crate::{
   Commitment,
-  serialize::{write_varint, read_32, read_scalar, read_point},
+  serialize::{read_32, read_scalar, read_point},
   transaction::{Timelock, Transaction},
-  wallet::{uniqueness, shared_key, amount_decryption, commitment_mask}
+  wallet::{
+    Extra, uniqueness, shared_key, amount_decryption,
+    SubaddressIndex, subaddress_derivation, Subaddresses
+  }
 };
 
 #[derive(Clone, PartialEq, Debug)]
@@ -20,23 +21,39 @@ pub struct SpendableOutput {
   pub tx: [u8; 32],
   pub o: u8,
   pub key: EdwardsPoint,
+  // Includes the subaddress's derivation, if this was sent to a subaddress, so the key to spend
+  // this output is always the wallet's spend key plus this offset
   pub key_offset: Scalar,
-  pub commitment: Commitment
+  pub commitment: Commitment,
+  pub subaddress: Option<SubaddressIndex>
+}
+
+fn read_u32<R: Read>(r: &mut R) -> std::io::Result<u32> {
+  let mut res = [0; 4];
+  r.read_exact(&mut res)?;
+  Ok(u32::from_le_bytes(res))
 }
 
 impl SpendableOutput {
   pub fn serialize(&self) -> Vec<u8> {
-    let mut res = Vec::with_capacity(32 + 1 + 32 + 32 + 40);
+    let mut res = Vec::with_capacity(32 + 1 + 32 + 32 + 40 + 9);
     res.extend(&self.tx);
     res.push(self.o);
     res.extend(self.key.compress().to_bytes());
     res.extend(self.key_offset.to_bytes());
     res.extend(self.commitment.mask.to_bytes());
     res.extend(self.commitment.amount.to_le_bytes());
+    if let Some(index) = self.subaddress {
+      res.push(1);
+      res.extend(index.major.to_le_bytes());
+      res.extend(index.minor.to_le_bytes());
+    } else {
+      res.push(0);
+    }
     res
   }
 
-  pub fn deserialize<R: std::io::Read>(r: &mut R) -> std::io::Result<SpendableOutput> {
+  pub fn deserialize<R: Read>(r: &mut R) -> std::io::Result<SpendableOutput> {
     Ok(
       SpendableOutput {
         tx: read_32(r)?,
@@ -46,7 +63,16 @@ impl SpendableOutput {
         commitment: Commitment::new(
           read_scalar(r)?,
           { let mut amount = [0; 8]; r.read_exact(&mut amount)?; u64::from_le_bytes(amount) }
-        )
+        ),
+        subaddress: {
+          let mut flag = [0; 1];
+          r.read_exact(&mut flag)?;
+          match flag[0] {
+            0 => None,
+            1 => Some(SubaddressIndex::new(read_u32(r)?, read_u32(r)?)),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "invalid subaddress flag"))?
+          }
+        }
       }
     )
   }
@@ -58,25 +84,17 @@ impl Transaction {
     view: Scalar,
     spend: EdwardsPoint
   ) -> (Vec<SpendableOutput>, Timelock) {
-    let mut extra = vec![];
-    write_varint(&u64::try_from(self.prefix.extra.len()).unwrap(), &mut extra).unwrap();
-    extra.extend(&self.prefix.extra);
-    let extra = deserialize::<ExtraField>(&extra);
-
-    let pubkeys: Vec<EdwardsPoint>;
-    if let Ok(extra) = extra {
-      let mut m_pubkeys = vec![];
-      if let Some(key) = extra.tx_pubkey() {
-        m_pubkeys.push(key);
-      }
-      if let Some(keys) = extra.tx_additional_pubkeys() {
-        m_pubkeys.extend(&keys);
-      }
+    self.scan_subaddresses(view, spend, &Subaddresses::default())
+  }
 
-      pubkeys = m_pubkeys.iter().map(|key| key.point.decompress()).filter_map(|key| key).collect();
-    } else {
-      return (vec![], self.prefix.timelock);
-    };
+  /// Scan for outputs sent to the primary address or any of the specified subaddresses
+  pub fn scan_subaddresses(
+    &self,
+    view: Scalar,
+    spend: EdwardsPoint,
+    subaddresses: &Subaddresses
+  ) -> (Vec<SpendableOutput>, Timelock) {
+    let pubkeys = Extra::deserialize(&self.prefix.extra).keys();
 
     let mut res = vec![];
     for (o, output) in self.prefix.outputs.iter().enumerate() {
@@ -84,30 +102,45 @@ impl Transaction {
       for pubkey in &pubkeys {
         let mut commitment = Commitment::zero();
 
-        // P - shared == spend
-        let matches = |shared_key| (output.key - (&shared_key * &ED25519_BASEPOINT_TABLE)) == spend;
-        let test = |shared_key| Some(shared_key).filter(|shared_key| matches(*shared_key));
+        // P - shared == spend, or the spend key of one of our subaddresses
+        let test = |shared_key| {
+          let key = output.key - (&shared_key * &ED25519_BASEPOINT_TABLE);
+          if key == spend {
+            Some((shared_key, None))
+          } else {
+            subaddresses.get(&key).map(|index| (shared_key, Some(index)))
+          }
+        };
+
+        // The view tag doesn't depend on uniqueness, so if it doesn't match, neither key will
+        let (view_tag, traditional) = shared_key(None, view, pubkey, o);
+        if output.tag.map(|tag| tag != view_tag).unwrap_or(false) {
+          continue;
+        }
 
         // Get the traditional shared key and unique shared key, testing if either matches for this output
-        let traditional = test(shared_key(None, view, pubkey, o));
-        let unique = test(shared_key(Some(uniqueness(&self.prefix.inputs)), view, pubkey, o));
+        let traditional = test(traditional);
+        let unique = test(shared_key(Some(uniqueness(&self.prefix.inputs)), view, pubkey, o).1);
 
         // If either matches, grab it and decode the amount
-        if let Some(key_offset) = traditional.or(unique) {
+        if let Some((shared_key, subaddress)) = traditional.or(unique) {
           // Miner transaction
           if output.amount != 0 {
             commitment.amount = output.amount;
           // Regular transaction
           } else {
-            let amount = match self.rct_signatures.base.ecdh_info.get(o) {
-              Some(amount) => amount_decryption(*amount, key_offset),
+            commitment = match self.rct_signatures.base.ecdh_info.get(o).map(
+              |amount| amount_decryption(amount, shared_key)
+            ) {
+              Some(Some(commitment)) => commitment,
+              // An amount which doesn't fit in 64 bits is malicious, as is a commitment to it
+              Some(None) => break,
               // This should never happen, yet it may be possible with miner transactions?
               // Using get just decreases the possibility of a panic and lets us move on in that case
               None => continue
             };
 
-            // Rebuild the commitment to verify it
-            commitment = Commitment::new(commitment_mask(key_offset), amount);
+            // Verify the commitment
             // If this is a malicious commitment, move to the next output
             // Any other R value will calculate to a different spend key and are therefore ignorable
             if Some(&commitment.calculate()) != self.rct_signatures.base.commitments.get(o) {
@@ -120,8 +153,12 @@ impl Transaction {
               tx: self.hash(),
               o: o.try_into().unwrap(),
               key: output.key,
-              key_offset,
-              commitment
+              key_offset: shared_key + subaddress.map_or(
+                Scalar::zero(),
+                |index| subaddress_derivation(&view, index)
+              ),
+              commitment,
+              subaddress
             });
           }
           // Break to prevent public keys from being included multiple times, triggering multiple
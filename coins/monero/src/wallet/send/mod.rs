@@ -1,3 +1,5 @@
+use std::io;
+
 use thiserror::Error;
 
 use rand_core::{RngCore, CryptoRng};
@@ -9,12 +11,6 @@ use curve25519_dalek::{
   edwards::EdwardsPoint
 };
 
-use monero::{
-  consensus::Encodable,
-  util::{key::PublicKey, address::{AddressType, Address}},
-  blockdata::transaction::SubField
-};
-
 #[cfg(feature = "multisig")]
 use frost::FrostError;
 
@@ -22,18 +18,25 @@ use crate::{
   Commitment,
   random_scalar,
   generate_key_image,
+  serialize::{write_varint, write_vec, read_byte, read_varint, read_vec},
   ringct::{
     clsag::{ClsagError, ClsagInput, Clsag},
     bulletproofs::{MAX_OUTPUTS, Bulletproofs},
-    RctBase, RctPrunable, RctSignatures
+    EncryptedAmount, RctBase, RctPrunable, RctSignatures
   },
   transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
   rpc::{Rpc, RpcError},
-  wallet::{SpendableOutput, Decoys, key_image_sort, uniqueness, shared_key, commitment_mask, amount_encryption}
+  wallet::{
+    AddressType, Address,
+    SpendableOutput, Decoys, ExtraField, Extra, key_image_sort, uniqueness, shared_key, commitment_mask, amount_encryption
+  }
 };
 #[cfg(feature = "multisig")]
 use crate::frost::MultisigError;
 
+mod select;
+pub use select::{Candidate, InputSelector, LargestFirst, BranchAndBound, AgeAware};
+
 #[cfg(feature = "multisig")]
 mod multisig;
 #[cfg(feature = "multisig")]
@@ -42,7 +45,9 @@ pub use multisig::TransactionMachine;
 #[allow(non_snake_case)]
 #[derive(Clone, PartialEq, Debug)]
 struct SendOutput {
+  r: Scalar,
   R: EdwardsPoint,
+  view_tag: u8,
   dest: EdwardsPoint,
   commitment: Commitment,
   amount: [u8; 8]
@@ -56,20 +61,22 @@ impl SendOutput {
     o: usize
   ) -> SendOutput {
     let r = random_scalar(rng);
-    let shared_key = shared_key(
+    let (view_tag, shared_key) = shared_key(
       Some(unique).filter(|_| output.2),
       r,
-      &output.0.public_view.point.decompress().expect("SendOutput::new requires valid addresses"),
+      &output.0.view,
       o
     );
 
-    let spend = output.0.public_spend.point.decompress().expect("SendOutput::new requires valid addresses");
+    let spend = output.0.spend;
     SendOutput {
-      R: match output.0.addr_type {
+      r,
+      R: match output.0.kind {
         AddressType::Standard => &r * &ED25519_BASEPOINT_TABLE,
-        AddressType::SubAddress => &r * spend,
+        AddressType::Subaddress => &r * spend,
         AddressType::Integrated(_) => panic!("SendOutput::new doesn't support Integrated addresses")
       },
+      view_tag,
       dest: ((&shared_key * &ED25519_BASEPOINT_TABLE) + spend),
       commitment: Commitment::new(commitment_mask(shared_key), output.1),
       amount: amount_encryption(output.1, shared_key)
@@ -89,8 +96,12 @@ pub enum TransactionError {
   NoChange,
   #[error("too many outputs")]
   TooManyOutputs,
+  #[error("transaction too large")]
+  TooLarge,
   #[error("not enough funds (in {0}, out {1})")]
   NotEnoughFunds(u64, u64),
+  #[error("excess funds without a change address ({0})")]
+  ExcessFunds(u64),
   #[error("wrong spend private key")]
   WrongPrivateKey,
   #[error("rpc error ({0})")]
@@ -107,23 +118,21 @@ pub enum TransactionError {
   MultisigError(MultisigError)
 }
 
-async fn prepare_inputs<R: RngCore + CryptoRng>(
-  rng: &mut R,
-  rpc: &Rpc,
+fn prepare_inputs(
   inputs: &[SpendableOutput],
+  decoys: Vec<Decoys>,
   spend: &Scalar,
   tx: &mut Transaction
 ) -> Result<Vec<(Scalar, EdwardsPoint, ClsagInput)>, TransactionError> {
-  let mut signable = Vec::with_capacity(inputs.len());
-
-  // Select decoys
-  let decoys = Decoys::select(
-    rng,
-    rpc,
-    rpc.get_height().await.map_err(|e| TransactionError::RpcError(e))? - 10,
-    inputs
-  ).await.map_err(|e| TransactionError::RpcError(e))?;
+  if decoys.len() != inputs.len() {
+    Err(
+      TransactionError::ClsagError(
+        ClsagError::InternalError("decoys weren't selected for every input".to_string())
+      )
+    )?;
+  }
 
+  let mut signable = Vec::with_capacity(inputs.len());
   for (i, input) in inputs.iter().enumerate() {
     signable.push((
       spend + input.key_offset,
@@ -161,11 +170,69 @@ pub struct Fee {
 }
 
 impl Fee {
+  /// Calculate the exact fee for a transaction of the specified weight, rounded up to the
+  /// quantization mask as Monero requires
   pub fn calculate(&self, weight: usize) -> u64 {
-    ((((self.per_weight * u64::try_from(weight).unwrap()) - 1) / self.mask) + 1) * self.mask
+    // A mask of 0 would divide by zero, and is equivalent to no quantization
+    let mask = self.mask.max(1);
+    ((((self.per_weight * u64::try_from(weight).unwrap()) - 1) / mask) + 1) * mask
+  }
+
+  /// Scale the base fee, as returned by the RPC, by a priority
+  pub fn prioritized(&self, priority: Priority) -> Fee {
+    Fee { per_weight: self.per_weight * priority.multiplier(), mask: self.mask }
   }
 }
 
+/// Fee priorities, as offered by wallet2
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Priority {
+  Low,
+  Normal,
+  Elevated,
+  Priority
+}
+
+impl Priority {
+  // wallet2's multipliers for its current fee algorithm
+  pub(crate) fn multiplier(&self) -> u64 {
+    match self {
+      Priority::Low => 1,
+      Priority::Normal => 5,
+      Priority::Elevated => 25,
+      Priority::Priority => 1000
+    }
+  }
+
+  // Index into the fees returned by get_fee_estimate
+  pub(crate) fn index(&self) -> usize {
+    match self {
+      Priority::Low => 0,
+      Priority::Normal => 1,
+      Priority::Elevated => 2,
+      Priority::Priority => 3
+    }
+  }
+}
+
+// Monero's limit on a transaction's weight, being half the penalty free zone minus the space
+// reserved for the miner transaction
+const MAX_TX_WEIGHT: usize = (300_000 / 2) - 600;
+
+// Weight of a transaction with the specified amount of inputs and outputs
+fn weight(inputs: usize, outputs: usize) -> usize {
+  // Calculate the extra length.
+  // Type and value for the transaction key, then type, length, and a key per output for the
+  // additional keys
+  let extra = (1 + 32) + (1 + 1 + (outputs * 32));
+  Transaction::fee_weight(inputs, outputs, extra)
+}
+
+// Fee for a transaction with the specified amount of inputs and outputs
+fn fee(fee_rate: Fee, inputs: usize, outputs: usize) -> u64 {
+  fee_rate.calculate(weight(inputs, outputs))
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct SignableTransaction {
   inputs: Vec<SpendableOutput>,
@@ -181,19 +248,13 @@ impl SignableTransaction {
     change_address: Option<Address>,
     fee_rate: Fee
   ) -> Result<SignableTransaction, TransactionError> {
-    // Make sure all addresses are valid
+    // Make sure all addresses are supported
+    // Address's keys are already validated as points when decoded
     let test = |addr: Address| {
-      if !(
-        addr.public_view.point.decompress().is_some() &&
-        addr.public_spend.point.decompress().is_some()
-      ) {
-        Err(TransactionError::InvalidAddress)?;
-      }
-
-      match addr.addr_type {
+      match addr.kind {
         AddressType::Standard => Ok(()),
         AddressType::Integrated(..) => Err(TransactionError::InvalidAddress),
-        AddressType::SubAddress => Ok(())
+        AddressType::Subaddress => Ok(())
       }
     };
 
@@ -211,8 +272,6 @@ impl SignableTransaction {
       Err(TransactionError::NoOutputs)?;
     }
 
-    // TODO TX MAX SIZE
-
     // If we don't have two outputs, as required by Monero, add a second
     let mut change = payments.len() == 1;
     if change && change_address.is_none() {
@@ -220,12 +279,8 @@ impl SignableTransaction {
     }
     let mut outputs = payments.len() + (if change { 1 } else { 0 });
 
-    // Calculate the extra length.
-    // Type, length, value, with 1 field for the first key and 1 field for the rest
-    let extra = (outputs * (2 + 32)) - (outputs.saturating_sub(2) * 2);
-
     // Calculate the fee.
-    let mut fee = fee_rate.calculate(Transaction::fee_weight(inputs.len(), outputs, extra));
+    let mut fee = fee(fee_rate, inputs.len(), outputs);
 
     // Make sure we have enough funds
     let in_amount = inputs.iter().map(|input| input.commitment.amount).sum::<u64>();
@@ -235,9 +290,9 @@ impl SignableTransaction {
     }
 
     // If we have yet to add a change output, do so if it's economically viable
+    let change_fee = self::fee(fee_rate, inputs.len(), outputs + 1) - fee;
     if (!change) && change_address.is_some() && (in_amount != out_amount) {
       // Check even with the new fee, there's remaining funds
-      let change_fee = fee_rate.calculate(Transaction::fee_weight(inputs.len(), outputs + 1, extra)) - fee;
       if (out_amount + change_fee) < in_amount {
         change = true;
         outputs += 1;
@@ -246,9 +301,23 @@ impl SignableTransaction {
       }
     }
 
+    // Without a change output, any excess goes to the fee, as otherwise the transaction wouldn't
+    // balance
+    // Only dust, worth no more than a change output would cost, may be burnt this way
+    if !change {
+      let excess = in_amount - out_amount;
+      if excess > change_fee {
+        Err(TransactionError::ExcessFunds(excess))?;
+      }
+      fee += excess;
+    }
+
     if outputs > MAX_OUTPUTS {
       Err(TransactionError::TooManyOutputs)?;
     }
+    if weight(inputs.len(), outputs) > MAX_TX_WEIGHT {
+      Err(TransactionError::TooLarge)?;
+    }
 
     let mut payments = payments.iter().map(|(address, amount)| (*address, *amount, false)).collect::<Vec<_>>();
     if change {
@@ -267,6 +336,193 @@ impl SignableTransaction {
     )
   }
 
+  /// Create a transaction spending the candidates chosen by the selector
+  /// Only candidates spendable at the specified height, the height the transaction is expected to
+  /// be included at, and time are passed to the selector
+  pub fn select<S: InputSelector>(
+    selector: &S,
+    candidates: &[Candidate],
+    height: usize,
+    time: u64,
+    payments: Vec<(Address, u64)>,
+    change_address: Option<Address>,
+    fee_rate: Fee
+  ) -> Result<SignableTransaction, TransactionError> {
+    let candidates = candidates.iter()
+      .filter(|candidate| candidate.spendable_at(height, time))
+      .cloned()
+      .collect::<Vec<_>>();
+
+    let payment = payments.iter().map(|payment| payment.1).sum::<u64>();
+    let fee = |inputs, change: bool| {
+      // A single payment always has a change output, as Monero requires two outputs
+      // The cost of change is included even without a change address, as the selected inputs must
+      // then not be worth more than it, so any excess can go to the fee
+      let change = change || (payments.len() == 1);
+      self::fee(fee_rate, inputs, payments.len() + usize::from(change))
+    };
+
+    let selected = selector.select(&candidates, payment, &fee).ok_or_else(
+      || TransactionError::NotEnoughFunds(
+        candidates.iter().map(|candidate| candidate.output.commitment.amount).sum(),
+        payment + fee(candidates.len(), true)
+      )
+    )?;
+
+    SignableTransaction::new(
+      selected.iter().map(|i| candidates[*i].output.clone()).collect(),
+      payments,
+      change_address,
+      fee_rate
+    )
+  }
+
+  /// Create a transaction sending the entirety of the inputs, minus the fee, to the destination
+  /// As Monero requires two outputs, a zero-amount output is also sent to the change address
+  pub fn sweep(
+    inputs: Vec<SpendableOutput>,
+    destination: Address,
+    change_address: Address,
+    fee_rate: Fee
+  ) -> Result<SignableTransaction, TransactionError> {
+    if inputs.len() == 0 {
+      Err(TransactionError::NoInputs)?;
+    }
+
+    let in_amount = inputs.iter().map(|input| input.commitment.amount).sum::<u64>();
+    let fee = fee(fee_rate, inputs.len(), 2);
+    if in_amount <= fee {
+      Err(TransactionError::NotEnoughFunds(in_amount, fee + 1))?;
+    }
+
+    SignableTransaction::new(
+      inputs,
+      vec![(destination, in_amount - fee)],
+      Some(change_address),
+      fee_rate
+    )
+  }
+
+  /// Sweep a single output to the destination
+  pub fn sweep_single(
+    input: SpendableOutput,
+    destination: Address,
+    change_address: Address,
+    fee_rate: Fee
+  ) -> Result<SignableTransaction, TransactionError> {
+    SignableTransaction::sweep(vec![input], destination, change_address, fee_rate)
+  }
+
+  /// Sweep every candidate spendable at the specified height and time to the destination,
+  /// splitting them across as many transactions as needed to stay under Monero's weight limit
+  /// Candidates worth less than the fee to spend them are skipped, as they'd only reduce the
+  /// amount swept
+  pub fn sweep_all(
+    candidates: &[Candidate],
+    height: usize,
+    time: u64,
+    destination: Address,
+    change_address: Address,
+    fee_rate: Fee
+  ) -> Result<Vec<SignableTransaction>, TransactionError> {
+    let input_fee = fee(fee_rate, 2, 2) - fee(fee_rate, 1, 2);
+    let inputs = candidates.iter().filter(
+      |candidate| {
+        candidate.spendable_at(height, time) && (candidate.output.commitment.amount > input_fee)
+      }
+    ).map(|candidate| candidate.output.clone()).collect::<Vec<_>>();
+    if inputs.len() == 0 {
+      Err(TransactionError::NoInputs)?;
+    }
+
+    let max_inputs = (1 ..)
+      .take_while(|inputs| weight(*inputs, 2) <= MAX_TX_WEIGHT)
+      .last()
+      .unwrap();
+    // Spread the inputs evenly, instead of leaving the last transaction with just a few
+    let txs = (inputs.len() + max_inputs - 1) / max_inputs;
+    let per_tx = (inputs.len() + txs - 1) / txs;
+    inputs.chunks(per_tx).map(
+      |inputs| SignableTransaction::sweep(inputs.to_vec(), destination, change_address, fee_rate)
+    ).collect()
+  }
+
+  /// Outputs spent by this transaction
+  pub fn inputs(&self) -> &[SpendableOutput] {
+    &self.inputs
+  }
+
+  /// Payments made by this transaction, including any change
+  pub fn payments(&self) -> Vec<(Address, u64)> {
+    self.payments.iter().map(|(address, amount, _)| (*address, *amount)).collect()
+  }
+
+  /// Fee paid by this transaction
+  pub fn fee(&self) -> u64 {
+    self.fee
+  }
+
+  /// Private keys of the signed transaction, in the order their public keys appear in its extra
+  /// Needed to prove what was sent with TxProof::out_proof, and empty until signed
+  pub fn tx_keys(&self) -> Vec<Scalar> {
+    self.outputs.first().map(|first| {
+      std::iter::once(first.r).chain(self.outputs.iter().map(|output| output.r)).collect()
+    }).unwrap_or_default()
+  }
+
+  /// Serialize this transaction, which must have yet to be signed, as needed to sign it elsewhere
+  pub fn serialize<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    write_vec(|input, w| w.write_all(&input.serialize()), &self.inputs, w)?;
+    write_vec(
+      |(address, amount, unique), w| {
+        let address = address.to_string();
+        write_varint(&address.len().try_into().unwrap(), w)?;
+        w.write_all(address.as_bytes())?;
+        w.write_all(&amount.to_le_bytes())?;
+        w.write_all(&[u8::from(*unique)])
+      },
+      &self.payments,
+      w
+    )?;
+    w.write_all(&self.fee.to_le_bytes())
+  }
+
+  pub fn deserialize<R: io::Read>(r: &mut R) -> io::Result<SignableTransaction> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::Other, msg);
+    let read_u64 = |r: &mut R| -> io::Result<u64> {
+      let mut amount = [0; 8];
+      r.read_exact(&mut amount)?;
+      Ok(u64::from_le_bytes(amount))
+    };
+
+    let inputs = read_vec(SpendableOutput::deserialize, r)?;
+    let payments = read_vec(
+      |r| {
+        // Addresses are at most 106 characters, so anything longer is rejected before allocating
+        let len = read_varint(r)?;
+        if len > 128 {
+          Err(invalid("invalid address length"))?;
+        }
+        let mut address = vec![0; usize::try_from(len).unwrap()];
+        r.read_exact(&mut address)?;
+        let address = std::str::from_utf8(&address).ok().and_then(|address| address.parse().ok())
+          .ok_or_else(|| invalid("invalid address"))?;
+        let amount = read_u64(r)?;
+        let unique = match read_byte(r)? {
+          0 => false,
+          1 => true,
+          _ => Err(invalid("invalid uniqueness flag"))?
+        };
+        Ok((address, amount, unique))
+      },
+      r
+    )?;
+    if inputs.is_empty() || payments.is_empty() {
+      Err(invalid("transaction without inputs or payments"))?;
+    }
+    Ok(SignableTransaction { inputs, payments, outputs: vec![], fee: read_u64(r)? })
+  }
+
   fn prepare_outputs<R: RngCore + CryptoRng>(
     &mut self,
     rng: &mut R,
@@ -294,12 +550,12 @@ impl SignableTransaction {
     // Create the TX extra
     // TODO: Review this for canonicity with Monero
     let mut extra = vec![];
-    SubField::TxPublicKey(
-      PublicKey { point: self.outputs[0].R.compress() }
-    ).consensus_encode(&mut extra).unwrap();
-    SubField::AdditionalPublickKey(
-      self.outputs[1 ..].iter().map(|output| PublicKey { point: output.R.compress() }).collect()
-    ).consensus_encode(&mut extra).unwrap();
+    Extra(vec![
+      ExtraField::PublicKey(self.outputs[0].R.compress()),
+      // Every output has its own key, so they're all additional keys, indexed by output
+      // This is required for subaddresses, and is also how wallet2 expects them to be laid out
+      ExtraField::PublicKeys(self.outputs.iter().map(|output| output.R.compress()).collect())
+    ]).serialize(&mut extra).unwrap();
 
     let mut tx_outputs = Vec::with_capacity(self.outputs.len());
     let mut ecdh_info = Vec::with_capacity(self.outputs.len());
@@ -307,9 +563,9 @@ impl SignableTransaction {
       tx_outputs.push(Output {
         amount: 0,
         key: self.outputs[o].dest,
-        tag: None
+        tag: Some(self.outputs[o].view_tag)
       });
-      ecdh_info.push(self.outputs[o].amount);
+      ecdh_info.push(EncryptedAmount::Compact { amount: self.outputs[o].amount });
     }
 
     Transaction {
@@ -320,9 +576,11 @@ impl SignableTransaction {
         outputs: tx_outputs,
         extra
       },
+      signatures: vec![],
       rct_signatures: RctSignatures {
         base: RctBase {
           fee: self.fee,
+          pseudo_outs: vec![],
           ecdh_info,
           commitments: commitments.iter().map(|commitment| commitment.calculate()).collect()
         },
@@ -340,6 +598,22 @@ impl SignableTransaction {
     rng: &mut R,
     rpc: &Rpc,
     spend: &Scalar
+  ) -> Result<Transaction, TransactionError> {
+    let decoys = Decoys::select(
+      rng,
+      rpc,
+      rpc.get_height().await.map_err(|e| TransactionError::RpcError(e))? - 10,
+      &self.inputs
+    ).await.map_err(|e| TransactionError::RpcError(e))?;
+    self.sign_with_decoys(rng, decoys, spend)
+  }
+
+  // Sign with decoys which were already selected, as done by offline signers
+  pub(crate) fn sign_with_decoys<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    decoys: Vec<Decoys>,
+    spend: &Scalar
   ) -> Result<Transaction, TransactionError> {
     let mut images = Vec::with_capacity(self.inputs.len());
     for input in &self.inputs {
@@ -365,15 +639,15 @@ impl SignableTransaction {
 
     let mut tx = self.prepare_transaction(&commitments, Bulletproofs::new(rng, &commitments)?);
 
-    let signable = prepare_inputs(rng, rpc, &self.inputs, spend, &mut tx).await?;
+    let signable = prepare_inputs(&self.inputs, decoys, spend, &mut tx)?;
 
     let clsag_pairs = Clsag::sign(rng, &signable, mask_sum, tx.signature_hash());
     match tx.rct_signatures.prunable {
-      RctPrunable::Null => panic!("Signing for RctPrunable::Null"),
       RctPrunable::Clsag { ref mut clsags, ref mut pseudo_outs, .. } => {
         clsags.append(&mut clsag_pairs.iter().map(|clsag| clsag.0.clone()).collect::<Vec<_>>());
         pseudo_outs.append(&mut clsag_pairs.iter().map(|clsag| clsag.1.clone()).collect::<Vec<_>>());
-      }
+      },
+      _ => panic!("Signing for a RctPrunable other than Clsag")
     }
     Ok(tx)
   }
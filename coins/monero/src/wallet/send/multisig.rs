@@ -1,27 +1,36 @@
-use std::{sync::{Arc, RwLock}, collections::HashMap};
+use std::{io::{self, Read}, sync::{Arc, RwLock}, collections::{HashMap, BTreeMap}};
 
 use rand_core::{RngCore, CryptoRng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
 
-use curve25519_dalek::{traits::Identity, scalar::Scalar, edwards::{EdwardsPoint, CompressedEdwardsY}};
+use curve25519_dalek::{traits::Identity, scalar::Scalar, edwards::EdwardsPoint};
 
 use transcript::{Transcript, RecommendedTranscript};
 use frost::{
-  curve::Ed25519,
-  FrostError, MultisigKeys,
+  curve::{Curve, Ed25519},
+  FrostError, MultisigKeys, Writable, read_vec,
   sign::{
+    Preprocess, SignatureShare,
     PreprocessMachine, SignMachine, SignatureMachine,
     AlgorithmMachine, AlgorithmSignMachine, AlgorithmSignatureMachine
   }
 };
 
 use crate::{
-  random_scalar, ringct::{clsag::{ClsagInput, ClsagDetails, ClsagMultisig}, bulletproofs::Bulletproofs, RctPrunable},
+  random_scalar,
+  ringct::{
+    clsag::{ClsagError, ClsagInput, ClsagDetails, ClsagMultisig},
+    bulletproofs::Bulletproofs,
+    RctPrunable
+  },
   transaction::{Input, Transaction},
   rpc::Rpc,
   wallet::{TransactionError, SignableTransaction, Decoys, key_image_sort, uniqueness}
 };
 
+/// Machine to sign a transaction, running a CLSAG for every input within a single set of rounds
+/// Each round's messages are the Vec of every CLSAG's, and the pseudo-out masks are chosen to sum
+/// to the output masks so the commitments balance
 pub struct TransactionMachine {
   signable: SignableTransaction,
   i: u16,
@@ -45,11 +54,12 @@ pub struct TransactionSignMachine {
   inputs: Vec<Arc<RwLock<Option<ClsagDetails>>>>,
   clsags: Vec<AlgorithmSignMachine<Ed25519, ClsagMultisig>>,
 
-  our_preprocess: Vec<u8>
+  our_preprocess: Vec<Preprocess<Ed25519>>
 }
 
 pub struct TransactionSignatureMachine {
   tx: Transaction,
+  tx_keys: Vec<Scalar>,
   clsags: Vec<AlgorithmSignatureMachine<Ed25519, ClsagMultisig>>
 }
 
@@ -58,17 +68,55 @@ impl SignableTransaction {
     self,
     rpc: &Rpc,
     keys: MultisigKeys<Ed25519>,
-    mut transcript: RecommendedTranscript,
+    transcript: RecommendedTranscript,
     height: usize,
-    mut included: Vec<u16>
+    included: Vec<u16>
   ) -> Result<TransactionMachine, TransactionError> {
-    let mut inputs = vec![];
-    for _ in 0 .. self.inputs.len() {
-      // Doesn't resize as that will use a single Rc for the entire Vec
-      inputs.push(Arc::new(RwLock::new(None)));
+    let mut transcript = self.multisig_transcript(&keys, transcript, height);
+
+    // Select decoys
+    // Ideally, this would be done post entropy, instead of now, yet doing so would require sign
+    // to be async which isn't preferable. This should be suitably competent though
+    // While this inability means we can immediately create the input, moving it out of the
+    // Arc RwLock, keeping it within an Arc RwLock keeps our options flexible
+    let decoys = Decoys::select(
+      // Using a seeded RNG with a specific height, committed to above, should make these decoys
+      // committed to. They'll also be committed to later via the TX message as a whole
+      &mut ChaCha12Rng::from_seed(transcript.rng_seed(b"decoys")),
+      rpc,
+      height,
+      &self.inputs
+    ).await.map_err(|e| TransactionError::RpcError(e))?;
+
+    self.multisig_machine(keys, transcript, decoys, included)
+  }
+
+  // Create the machine with decoys which were already selected, as done by offline signers
+  // As the decoys weren't derived from the transcript, they're explicitly committed to
+  pub(crate) fn multisig_with_decoys(
+    self,
+    keys: MultisigKeys<Ed25519>,
+    transcript: RecommendedTranscript,
+    height: usize,
+    decoys: Vec<Decoys>,
+    included: Vec<u16>
+  ) -> Result<TransactionMachine, TransactionError> {
+    let mut transcript = self.multisig_transcript(&keys, transcript, height);
+    for decoys in &decoys {
+      transcript.append_message(
+        b"decoy_offsets",
+        &decoys.offsets.iter().flat_map(|offset| offset.to_le_bytes()).collect::<Vec<_>>()
+      );
     }
-    let mut clsags = vec![];
+    self.multisig_machine(keys, transcript, decoys, included)
+  }
 
+  fn multisig_transcript(
+    &self,
+    keys: &MultisigKeys<Ed25519>,
+    mut transcript: RecommendedTranscript,
+    height: usize
+  ) -> RecommendedTranscript {
     // Create a RNG out of the input shared keys, which either requires the view key or being every
     // sender, and the payments (address and amount), which a passive adversary may be able to know
     // depending on how these transactions are coordinated
@@ -94,10 +142,34 @@ impl SignableTransaction {
       transcript.append_message(b"input_shared_key", &input.key_offset.to_bytes());
     }
     for payment in &self.payments {
-      transcript.append_message(b"payment_address", &payment.0.as_bytes());
+      transcript.append_message(b"payment_address", payment.0.to_string().as_bytes());
       transcript.append_message(b"payment_amount", &payment.1.to_le_bytes());
       transcript.append_message(b"payment_unique", &(if payment.2 { [1] } else { [0] }));
     }
+    transcript
+  }
+
+  fn multisig_machine(
+    self,
+    keys: MultisigKeys<Ed25519>,
+    transcript: RecommendedTranscript,
+    decoys: Vec<Decoys>,
+    mut included: Vec<u16>
+  ) -> Result<TransactionMachine, TransactionError> {
+    if decoys.len() != self.inputs.len() {
+      Err(
+        TransactionError::ClsagError(
+          ClsagError::InternalError("decoys weren't selected for every input".to_string())
+        )
+      )?;
+    }
+
+    let mut inputs = vec![];
+    for _ in 0 .. self.inputs.len() {
+      // Doesn't resize as that will use a single Rc for the entire Vec
+      inputs.push(Arc::new(RwLock::new(None)));
+    }
+    let mut clsags = vec![];
 
     // Sort included before cloning it around
     included.sort_unstable();
@@ -121,20 +193,6 @@ impl SignableTransaction {
       );
     }
 
-    // Select decoys
-    // Ideally, this would be done post entropy, instead of now, yet doing so would require sign
-    // to be async which isn't preferable. This should be suitably competent though
-    // While this inability means we can immediately create the input, moving it out of the
-    // Arc RwLock, keeping it within an Arc RwLock keeps our options flexible
-    let decoys = Decoys::select(
-      // Using a seeded RNG with a specific height, committed to above, should make these decoys
-      // committed to. They'll also be committed to later via the TX message as a whole
-      &mut ChaCha12Rng::from_seed(transcript.rng_seed(b"decoys")),
-      rpc,
-      height,
-      &self.inputs
-    ).await.map_err(|e| TransactionError::RpcError(e))?;
-
     Ok(
       TransactionMachine {
         signable: self,
@@ -152,21 +210,22 @@ impl SignableTransaction {
 }
 
 impl PreprocessMachine for TransactionMachine {
+  type Preprocess = Vec<Preprocess<Ed25519>>;
   type Signature = Transaction;
   type SignMachine = TransactionSignMachine;
 
   fn preprocess<R: RngCore + CryptoRng>(
     mut self,
     rng: &mut R
-  ) -> (TransactionSignMachine, Vec<u8>) {
+  ) -> (TransactionSignMachine, Self::Preprocess) {
     // Iterate over each CLSAG calling preprocess
-    let mut serialized = Vec::with_capacity(self.clsags.len() * (64 + ClsagMultisig::serialized_len()));
+    let mut preprocesses = Vec::with_capacity(self.clsags.len());
     let clsags = self.clsags.drain(..).map(|clsag| {
       let (clsag, preprocess) = clsag.preprocess(rng);
-      serialized.extend(&preprocess);
+      preprocesses.push(preprocess);
       clsag
     }).collect();
-    let our_preprocess = serialized.clone();
+    let our_preprocess = preprocesses.clone();
 
     // We could add further entropy here, and previous versions of this library did so
     // As of right now, the multisig's key, the inputs being spent, and the FROST data itself
@@ -191,25 +250,27 @@ impl PreprocessMachine for TransactionMachine {
 
         our_preprocess,
       },
-      serialized
+      preprocesses
     )
   }
 }
 
 impl SignMachine<Transaction> for TransactionSignMachine {
+  type Preprocess = Vec<Preprocess<Ed25519>>;
+  type SignatureShare = Vec<SignatureShare<Ed25519>>;
   type SignatureMachine = TransactionSignatureMachine;
 
+  fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Self::Preprocess> {
+    read_vec(reader, |reader| self.clsags[0].read_preprocess(reader))
+  }
+
   fn sign(
     mut self,
-    mut commitments: HashMap<u16, Vec<u8>>,
+    mut commitments: HashMap<u16, Self::Preprocess>,
     msg: &[u8]
-  ) -> Result<(TransactionSignatureMachine, Vec<u8>), FrostError> {
+  ) -> Result<(TransactionSignatureMachine, Self::SignatureShare), FrostError> {
     if msg.len() != 0 {
-      Err(
-        FrostError::InternalError(
-          "message was passed to the TransactionMachine when it generates its own".to_string()
-        )
-      )?;
+      Err(FrostError::UnexpectedMessage)?;
     }
 
     // Add all commitments to the transcript for their entropy
@@ -217,20 +278,23 @@ impl SignMachine<Transaction> for TransactionSignMachine {
     // cloned from this TX's initial premise's transcript. For our TX transcript to have the CLSAG
     // data for entropy, it'll have to be added ourselves
     commitments.insert(self.i, self.our_preprocess);
+    // Iterate in index order so who's blamed doesn't depend on the HashMap's iteration order
+    for (l, preprocess) in commitments.iter().collect::<BTreeMap<_, _>>() {
+      if preprocess.len() != self.clsags.len() {
+        Err(FrostError::InvalidCommitment(*l))?;
+      }
+    }
     for l in &self.included {
       self.transcript.append_message(b"participant", &(*l).to_be_bytes());
       // FROST itself will error if this is None, so let it
       if let Some(preprocess) = commitments.get(l) {
-        self.transcript.append_message(b"preprocess", preprocess);
+        self.transcript.append_message(b"preprocess", &preprocess.serialize());
       }
     }
 
-    // FROST commitments, image, H commitments, and their proofs
-    let clsag_len = 64 + ClsagMultisig::serialized_len();
-
     // Convert the unified commitments to a Vec of the individual commitments
     let mut commitments = (0 .. self.clsags.len()).map(|_| commitments.iter_mut().map(
-      |(l, commitments)| (*l, commitments.drain(.. clsag_len).collect::<Vec<_>>())
+      |(l, commitments)| (*l, commitments.remove(0))
     ).collect::<HashMap<_, _>>()).collect::<Vec<_>>();
 
     // Calculate the key images
@@ -240,10 +304,11 @@ impl SignMachine<Transaction> for TransactionSignMachine {
     // to be unique)
     let mut images = vec![EdwardsPoint::identity(); self.clsags.len()];
     for c in 0 .. self.clsags.len() {
-      for (l, preprocess) in &commitments[c] {
-        images[c] += CompressedEdwardsY(
-          preprocess[64 .. 96].try_into().map_err(|_| FrostError::InvalidCommitment(*l))?
-        ).decompress().ok_or(FrostError::InvalidCommitment(*l))?;
+      for (l, preprocess) in commitments[c].iter().collect::<BTreeMap<_, _>>() {
+        // Rejects non-canonical and torsioned key image shares, as ClsagMultisig will
+        images[c] += Ed25519::G_from_slice(
+          preprocess.addendum().get(0 .. 32).ok_or(FrostError::InvalidCommitment(*l))?
+        ).map_err(|_| FrostError::InvalidCommitment(*l))?.0;
       }
     }
 
@@ -325,33 +390,54 @@ impl SignMachine<Transaction> for TransactionSignMachine {
     let msg = tx.signature_hash();
 
     // Iterate over each CLSAG calling sign
-    let mut serialized = Vec::with_capacity(self.clsags.len() * 32);
+    let mut shares = Vec::with_capacity(self.clsags.len());
     let clsags = self.clsags.drain(..).map(|clsag| {
       let (clsag, share) = clsag.sign(commitments.remove(0), &msg)?;
-      serialized.extend(&share);
+      shares.push(share);
       Ok(clsag)
     }).collect::<Result<_, _>>()?;
 
-    Ok((TransactionSignatureMachine { tx, clsags }, serialized))
+    let tx_keys = self.signable.tx_keys();
+    Ok((TransactionSignatureMachine { tx, tx_keys, clsags }, shares))
+  }
+}
+
+impl TransactionSignatureMachine {
+  /// Private keys of the transaction being signed, as SignableTransaction::tx_keys
+  pub fn tx_keys(&self) -> &[Scalar] {
+    &self.tx_keys
   }
 }
 
 impl SignatureMachine<Transaction> for TransactionSignatureMachine {
-  fn complete(self, mut shares: HashMap<u16, Vec<u8>>) -> Result<Transaction, FrostError> {
+  type SignatureShare = Vec<SignatureShare<Ed25519>>;
+
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<Self::SignatureShare> {
+    read_vec(reader, |reader| self.clsags[0].read_share(reader))
+  }
+
+  fn complete(self, mut shares: HashMap<u16, Self::SignatureShare>) -> Result<Transaction, FrostError> {
+    for (l, shares) in shares.iter().collect::<BTreeMap<_, _>>() {
+      if shares.len() != self.clsags.len() {
+        Err(FrostError::InvalidShare(*l))?;
+      }
+    }
+
+
     let mut tx = self.tx;
     match tx.rct_signatures.prunable {
-      RctPrunable::Null => panic!("Signing for RctPrunable::Null"),
       RctPrunable::Clsag { ref mut clsags, ref mut pseudo_outs, .. } => {
         for clsag in self.clsags {
           let (clsag, pseudo_out) = clsag.complete(
             shares.iter_mut().map(
-              |(l, shares)| (*l, shares.drain(.. 32).collect())
+              |(l, shares)| (*l, shares.remove(0))
             ).collect::<HashMap<_, _>>()
           )?;
           clsags.push(clsag);
           pseudo_outs.push(pseudo_out);
         }
-      }
+      },
+      _ => panic!("Signing for a RctPrunable other than Clsag")
     }
     Ok(tx)
   }
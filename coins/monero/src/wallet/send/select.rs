@@ -0,0 +1,189 @@
+use crate::{transaction::Timelock, wallet::SpendableOutput};
+
+// Blocks an output must be buried under before it can be spent
+const SPENDABLE_AGE: usize = 10;
+
+/// An output which may be spent, with the height of the block it was included in
+#[derive(Clone, PartialEq, Debug)]
+pub struct Candidate {
+  pub output: SpendableOutput,
+  pub height: usize,
+  /// Timelock of the transaction which created the output
+  pub timelock: Timelock
+}
+
+impl Candidate {
+  fn amount(&self) -> u64 {
+    self.output.commitment.amount
+  }
+
+  /// If this output can be spent by a transaction in the block at the specified height, having
+  /// both matured and unlocked, with the chain's current time being the specified UNIX timestamp
+  pub fn spendable_at(&self, height: usize, time: u64) -> bool {
+    ((self.height + SPENDABLE_AGE) <= height) && self.timelock.unlocked(height, time)
+  }
+}
+
+/// Strategy for choosing which outputs to spend
+pub trait InputSelector {
+  /// Select the candidates to spend, by index, such that they cover the payment and fee
+  /// fee returns the fee for a transaction with the specified amount of inputs, with or without a
+  /// change output. Returns None if the candidates can't cover the payment
+  fn select(
+    &self,
+    candidates: &[Candidate],
+    payment: u64,
+    fee: &dyn Fn(usize, bool) -> u64
+  ) -> Option<Vec<usize>>;
+}
+
+// Indexes of the candidates, largest first
+fn by_amount(candidates: &[Candidate]) -> Vec<usize> {
+  let mut indexes = (0 .. candidates.len()).collect::<Vec<_>>();
+  indexes.sort_by(|x, y| candidates[*y].amount().cmp(&candidates[*x].amount()));
+  indexes
+}
+
+/// Spend the largest outputs until the payment is covered, minimizing the amount of inputs
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct LargestFirst;
+
+impl InputSelector for LargestFirst {
+  fn select(
+    &self,
+    candidates: &[Candidate],
+    payment: u64,
+    fee: &dyn Fn(usize, bool) -> u64
+  ) -> Option<Vec<usize>> {
+    let mut selected = vec![];
+    let mut sum = 0;
+    for i in by_amount(candidates) {
+      selected.push(i);
+      sum += candidates[i].amount();
+      if sum >= (payment + fee(selected.len(), true)) {
+        return Some(selected);
+      }
+    }
+    None
+  }
+}
+
+/// Search for a set of outputs which covers the payment without needing a change output, picking
+/// the one which loses the least to the fee. Falls back to LargestFirst if there's no such set
+/// A set doesn't need change when its excess is less than the cost of adding a change output,
+/// in which case the excess is added to the fee
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BranchAndBound {
+  /// Maximum amount of branches to explore before giving up on finding a changeless set
+  pub max_tries: usize
+}
+
+impl Default for BranchAndBound {
+  fn default() -> BranchAndBound {
+    BranchAndBound { max_tries: 100_000 }
+  }
+}
+
+struct Search<'a> {
+  candidates: &'a [Candidate],
+  order: Vec<usize>,
+  // Sum of the amounts of order[i ..], used to prune branches which can't reach the target
+  remaining: Vec<u64>,
+  payment: u64,
+  fee: &'a dyn Fn(usize, bool) -> u64,
+  tries: usize,
+  selected: Vec<usize>,
+  best: Option<(u64, Vec<usize>)>
+}
+
+impl<'a> Search<'a> {
+  fn search(&mut self, i: usize, sum: u64) {
+    if self.tries == 0 {
+      return;
+    }
+    self.tries -= 1;
+
+    let low = self.payment + (self.fee)(self.selected.len(), false);
+    let high = self.payment + (self.fee)(self.selected.len(), true);
+    if (!self.selected.is_empty()) && (sum >= low) {
+      // Anything at or past high can afford a change output, and any further inputs only add more
+      if sum < high {
+        let excess = sum - low;
+        if self.best.as_ref().map(|best| excess < best.0).unwrap_or(true) {
+          self.best = Some((excess, self.selected.clone()));
+        }
+      }
+      return;
+    }
+
+    if (i == self.order.len()) || ((sum + self.remaining[i]) < low) {
+      return;
+    }
+
+    // Include this candidate, then try without it
+    self.selected.push(self.order[i]);
+    self.search(i + 1, sum + self.candidates[self.order[i]].amount());
+    self.selected.pop();
+    self.search(i + 1, sum);
+  }
+}
+
+impl InputSelector for BranchAndBound {
+  fn select(
+    &self,
+    candidates: &[Candidate],
+    payment: u64,
+    fee: &dyn Fn(usize, bool) -> u64
+  ) -> Option<Vec<usize>> {
+    let order = by_amount(candidates);
+    let mut remaining = vec![0; order.len() + 1];
+    for i in (0 .. order.len()).rev() {
+      remaining[i] = remaining[i + 1] + candidates[order[i]].amount();
+    }
+
+    let mut search = Search {
+      candidates,
+      order,
+      remaining,
+      payment,
+      fee,
+      tries: self.max_tries,
+      selected: vec![],
+      best: None
+    };
+    search.search(0, 0);
+
+    search.best.map(|best| best.1).or_else(|| LargestFirst.select(candidates, payment, fee))
+  }
+}
+
+/// Only spend outputs which are at least min_age blocks old, as spending an output soon after
+/// receiving it makes it stand out within its ring, where decoys are rarely so young
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AgeAware<S: InputSelector> {
+  pub selector: S,
+  /// Current height of the chain
+  pub height: usize,
+  pub min_age: usize
+}
+
+impl<S: InputSelector> AgeAware<S> {
+  pub fn new(selector: S, height: usize, min_age: usize) -> AgeAware<S> {
+    AgeAware { selector, height, min_age }
+  }
+}
+
+impl<S: InputSelector> InputSelector for AgeAware<S> {
+  fn select(
+    &self,
+    candidates: &[Candidate],
+    payment: u64,
+    fee: &dyn Fn(usize, bool) -> u64
+  ) -> Option<Vec<usize>> {
+    let (indexes, aged): (Vec<_>, Vec<_>) = candidates.iter().enumerate().filter(
+      |(_, candidate)| (candidate.height + self.min_age) <= self.height
+    ).map(|(i, candidate)| (i, candidate.clone())).unzip();
+
+    Some(self.selector.select(&aged, payment, fee)?.iter().map(|i| indexes[*i]).collect())
+  }
+}
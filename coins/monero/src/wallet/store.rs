@@ -0,0 +1,335 @@
+use std::{
+  io::{self, Read, Write},
+  path::{Path, PathBuf},
+  collections::{HashMap, BTreeMap}
+};
+
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  serialize::*,
+  transaction::{Input, Timelock, Transaction},
+  wallet::{SpendableOutput, Subaddresses, Candidate}
+};
+
+/// A transaction which received to or spent from the wallet
+#[derive(Clone, PartialEq, Debug)]
+pub struct WalletTransaction {
+  pub hash: [u8; 32],
+  pub height: usize,
+  /// Outputs received by this transaction can't be spent until this passes
+  pub timelock: Timelock,
+  /// Sum of the outputs received
+  pub received: u64,
+  /// Sum of the wallet's outputs spent, including any which were returned as change
+  pub spent: u64
+}
+
+/// Storage for a wallet's state, letting scanning and transaction building share it
+/// Outputs are identified by their transaction's hash and their index within it
+pub trait WalletStore {
+  /// Height the wallet has been scanned to, exclusive
+  fn scanned_height(&self) -> usize;
+  fn scanned_to_height(&mut self, height: usize);
+
+  /// Add a received output. Returns false if the output was already added
+  fn add_output(&mut self, output: Candidate) -> bool;
+  /// Set the key image of an owned output, which must be calculated with the spend key
+  fn set_key_image(&mut self, tx: [u8; 32], o: u8, key_image: EdwardsPoint);
+  /// The key image of an owned output, if it's been set
+  fn key_image(&self, tx: [u8; 32], o: u8) -> Option<EdwardsPoint>;
+  /// Mark the output with this key image as spent, returning it if it's owned
+  fn spend(&mut self, key_image: &EdwardsPoint) -> Option<Candidate>;
+  /// Outputs which haven't been spent, as candidates for SignableTransaction::select
+  fn unspent(&self) -> Vec<Candidate>;
+  /// Outputs which haven't been spent and can be spent at the specified height and time
+  fn spendable(&self, height: usize, time: u64) -> Vec<Candidate> {
+    self.unspent().into_iter().filter(|output| output.spendable_at(height, time)).collect()
+  }
+
+  /// Add a transaction to the history, replacing any prior record of it
+  fn add_transaction(&mut self, tx: WalletTransaction);
+  fn transactions(&self) -> Vec<WalletTransaction>;
+
+  /// Remove the outputs and transactions from blocks at or after the specified height, rewinding
+  /// the scanned height to it, as needed when those blocks are reorganized out of the chain
+  /// Returns the outputs removed. Outputs spent in those blocks remain spent, as spends aren't
+  /// recorded by height
+  fn revert(&mut self, height: usize) -> Vec<Candidate>;
+}
+
+/// Scan a transaction, included at the specified height, into the store
+/// Adds the outputs it sends to the wallet and marks spent any owned outputs it uses, recording
+/// it in the history if either happened
+pub fn scan_into<S: WalletStore>(
+  store: &mut S,
+  tx: &Transaction,
+  height: usize,
+  view: Scalar,
+  spend: EdwardsPoint,
+  subaddresses: &Subaddresses
+) -> Option<WalletTransaction> {
+  let mut spent = 0;
+  for input in &tx.prefix.inputs {
+    if let Input::ToKey { key_image, .. } = input {
+      if let Some(output) = store.spend(key_image) {
+        spent += output.output.commitment.amount;
+      }
+    }
+  }
+
+  let (outputs, timelock) = tx.scan_subaddresses(view, spend, subaddresses);
+  let mut received = 0;
+  for output in outputs {
+    received += output.commitment.amount;
+    store.add_output(Candidate { output, height, timelock });
+  }
+
+  if (spent == 0) && (received == 0) {
+    return None;
+  }
+  let tx = WalletTransaction { hash: tx.hash(), height, timelock, received, spent };
+  store.add_transaction(tx.clone());
+  Some(tx)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct OwnedOutput {
+  candidate: Candidate,
+  key_image: Option<EdwardsPoint>,
+  spent: bool
+}
+
+/// A WalletStore held in memory
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MemWalletStore {
+  scanned_height: usize,
+  outputs: BTreeMap<([u8; 32], u8), OwnedOutput>,
+  key_images: HashMap<[u8; 32], ([u8; 32], u8)>,
+  transactions: Vec<WalletTransaction>
+}
+
+impl MemWalletStore {
+  pub fn new() -> MemWalletStore {
+    MemWalletStore::default()
+  }
+
+  pub fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&u64::try_from(self.scanned_height).unwrap().to_le_bytes())?;
+
+    write_varint(&self.outputs.len().try_into().unwrap(), w)?;
+    for output in self.outputs.values() {
+      w.write_all(&output.candidate.output.serialize())?;
+      w.write_all(&u64::try_from(output.candidate.height).unwrap().to_le_bytes())?;
+      output.candidate.timelock.serialize(w)?;
+      if let Some(key_image) = output.key_image {
+        w.write_all(&[1])?;
+        write_point(&key_image, w)?;
+      } else {
+        w.write_all(&[0])?;
+      }
+      w.write_all(&[u8::from(output.spent)])?;
+    }
+
+    write_varint(&self.transactions.len().try_into().unwrap(), w)?;
+    for tx in &self.transactions {
+      w.write_all(&tx.hash)?;
+      w.write_all(&u64::try_from(tx.height).unwrap().to_le_bytes())?;
+      tx.timelock.serialize(w)?;
+      w.write_all(&tx.received.to_le_bytes())?;
+      w.write_all(&tx.spent.to_le_bytes())?;
+    }
+    Ok(())
+  }
+
+  pub fn deserialize<R: Read>(r: &mut R) -> io::Result<MemWalletStore> {
+    fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+      let mut res = [0; 8];
+      r.read_exact(&mut res)?;
+      Ok(u64::from_le_bytes(res))
+    }
+    fn read_usize<R: Read>(r: &mut R) -> io::Result<usize> {
+      usize::try_from(read_u64(r)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "height exceeds usize"))
+    }
+    fn read_bool<R: Read>(r: &mut R) -> io::Result<bool> {
+      match read_byte(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid flag"))
+      }
+    }
+
+    let mut store = MemWalletStore::new();
+    store.scanned_height = read_usize(r)?;
+
+    for _ in 0 .. read_varint(r)? {
+      let output = OwnedOutput {
+        candidate: Candidate {
+          output: SpendableOutput::deserialize(r)?,
+          height: read_usize(r)?,
+          timelock: Timelock::from_raw(read_varint(r)?)
+        },
+        key_image: if read_bool(r)? { Some(read_point(r)?) } else { None },
+        spent: read_bool(r)?
+      };
+      let id = (output.candidate.output.tx, output.candidate.output.o);
+      if let Some(key_image) = output.key_image {
+        store.key_images.insert(key_image.compress().to_bytes(), id);
+      }
+      store.outputs.insert(id, output);
+    }
+
+    for _ in 0 .. read_varint(r)? {
+      store.transactions.push(
+        WalletTransaction {
+          hash: read_32(r)?,
+          height: read_usize(r)?,
+          timelock: Timelock::from_raw(read_varint(r)?),
+          received: read_u64(r)?,
+          spent: read_u64(r)?
+        }
+      );
+    }
+
+    Ok(store)
+  }
+}
+
+impl WalletStore for MemWalletStore {
+  fn scanned_height(&self) -> usize {
+    self.scanned_height
+  }
+
+  fn scanned_to_height(&mut self, height: usize) {
+    self.scanned_height = height;
+  }
+
+  fn add_output(&mut self, output: Candidate) -> bool {
+    let id = (output.output.tx, output.output.o);
+    if self.outputs.contains_key(&id) {
+      return false;
+    }
+    self.outputs.insert(id, OwnedOutput { candidate: output, key_image: None, spent: false });
+    true
+  }
+
+  fn set_key_image(&mut self, tx: [u8; 32], o: u8, key_image: EdwardsPoint) {
+    if let Some(output) = self.outputs.get_mut(&(tx, o)) {
+      output.key_image = Some(key_image);
+      self.key_images.insert(key_image.compress().to_bytes(), (tx, o));
+    }
+  }
+
+  fn key_image(&self, tx: [u8; 32], o: u8) -> Option<EdwardsPoint> {
+    self.outputs.get(&(tx, o))?.key_image
+  }
+
+  fn spend(&mut self, key_image: &EdwardsPoint) -> Option<Candidate> {
+    let output = self.outputs.get_mut(self.key_images.get(&key_image.compress().to_bytes())?)?;
+    output.spent = true;
+    Some(output.candidate.clone())
+  }
+
+  fn unspent(&self) -> Vec<Candidate> {
+    self.outputs.values().filter(|output| !output.spent).map(
+      |output| output.candidate.clone()
+    ).collect()
+  }
+
+  fn add_transaction(&mut self, tx: WalletTransaction) {
+    self.transactions.retain(|existing| existing.hash != tx.hash);
+    self.transactions.push(tx);
+  }
+
+  fn transactions(&self) -> Vec<WalletTransaction> {
+    self.transactions.clone()
+  }
+
+  fn revert(&mut self, height: usize) -> Vec<Candidate> {
+    let mut removed = vec![];
+    self.outputs.retain(|_, output| {
+      let keep = output.candidate.height < height;
+      if !keep {
+        removed.push(output.candidate.clone());
+      }
+      keep
+    });
+    self.key_images.retain(|_, id| self.outputs.contains_key(id));
+    self.transactions.retain(|tx| tx.height < height);
+    self.scanned_height = self.scanned_height.min(height);
+    removed
+  }
+}
+
+/// A WalletStore persisted to a file
+/// Changes are held in memory until saved, so a crash only loses what was scanned since
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileWalletStore {
+  path: PathBuf,
+  store: MemWalletStore
+}
+
+impl FileWalletStore {
+  /// Open the store at the specified path, starting a new one if the file doesn't exist
+  pub fn open<P: AsRef<Path>>(path: P) -> io::Result<FileWalletStore> {
+    let path = path.as_ref().to_path_buf();
+    let store = match std::fs::read(&path) {
+      Ok(serialized) => MemWalletStore::deserialize(&mut serialized.as_slice())?,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => MemWalletStore::new(),
+      Err(e) => Err(e)?
+    };
+    Ok(FileWalletStore { path, store })
+  }
+
+  /// Write the store to its file, replacing the prior version atomically
+  pub fn save(&self) -> io::Result<()> {
+    let mut serialized = vec![];
+    self.store.serialize(&mut serialized)?;
+    let temp = self.path.with_extension("tmp");
+    std::fs::write(&temp, serialized)?;
+    std::fs::rename(temp, &self.path)
+  }
+}
+
+impl WalletStore for FileWalletStore {
+  fn scanned_height(&self) -> usize {
+    self.store.scanned_height()
+  }
+
+  fn scanned_to_height(&mut self, height: usize) {
+    self.store.scanned_to_height(height)
+  }
+
+  fn add_output(&mut self, output: Candidate) -> bool {
+    self.store.add_output(output)
+  }
+
+  fn set_key_image(&mut self, tx: [u8; 32], o: u8, key_image: EdwardsPoint) {
+    self.store.set_key_image(tx, o, key_image)
+  }
+
+  fn key_image(&self, tx: [u8; 32], o: u8) -> Option<EdwardsPoint> {
+    self.store.key_image(tx, o)
+  }
+
+  fn spend(&mut self, key_image: &EdwardsPoint) -> Option<Candidate> {
+    self.store.spend(key_image)
+  }
+
+  fn unspent(&self) -> Vec<Candidate> {
+    self.store.unspent()
+  }
+
+  fn add_transaction(&mut self, tx: WalletTransaction) {
+    self.store.add_transaction(tx)
+  }
+
+  fn transactions(&self) -> Vec<WalletTransaction> {
+    self.store.transactions()
+  }
+
+  fn revert(&mut self, height: usize) -> Vec<Candidate> {
+    self.store.revert(height)
+  }
+}
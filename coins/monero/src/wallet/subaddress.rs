@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY}
+};
+
+use crate::{hash_to_scalar, wallet::{Network, AddressType, Address}};
+
+/// Index of a subaddress, as a major (account) index and a minor index within that account
+/// (0, 0) is the primary address
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SubaddressIndex {
+  pub major: u32,
+  pub minor: u32
+}
+
+impl SubaddressIndex {
+  pub fn new(major: u32, minor: u32) -> SubaddressIndex {
+    SubaddressIndex { major, minor }
+  }
+
+  pub fn is_primary(&self) -> bool {
+    (self.major == 0) && (self.minor == 0)
+  }
+}
+
+// Hs("SubAddr\0" || a || major || minor)
+pub(crate) fn subaddress_derivation(view: &Scalar, index: SubaddressIndex) -> Scalar {
+  let mut data = b"SubAddr\0".to_vec();
+  data.extend(view.to_bytes());
+  data.extend(index.major.to_le_bytes());
+  data.extend(index.minor.to_le_bytes());
+  hash_to_scalar(&data)
+}
+
+/// Spend key of a subaddress, B + Hs(...)G
+pub fn subaddress_spend_key(view: &Scalar, spend: &EdwardsPoint, index: SubaddressIndex) -> EdwardsPoint {
+  if index.is_primary() {
+    return *spend;
+  }
+  spend + (&subaddress_derivation(view, index) * &ED25519_BASEPOINT_TABLE)
+}
+
+/// Address for the specified subaddress, or the standard address for the primary index
+pub fn subaddress(
+  network: Network,
+  view: &Scalar,
+  spend: &EdwardsPoint,
+  index: SubaddressIndex
+) -> Address {
+  if index.is_primary() {
+    return Address::standard(network, *spend, view * &ED25519_BASEPOINT_TABLE);
+  }
+
+  let spend = subaddress_spend_key(view, spend, index);
+  Address::new(network, AddressType::Subaddress, spend, view * spend)
+}
+
+/// Spend keys of a wallet's subaddresses, used to recognize outputs sent to them when scanning
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Subaddresses(HashMap<CompressedEdwardsY, SubaddressIndex>);
+
+impl Subaddresses {
+  pub fn new(view: &Scalar, spend: &EdwardsPoint, indexes: &[SubaddressIndex]) -> Subaddresses {
+    Subaddresses(
+      indexes.iter().filter(|index| !index.is_primary()).map(
+        |index| (subaddress_spend_key(view, spend, *index).compress(), *index)
+      ).collect()
+    )
+  }
+
+  /// Recognize these subaddresses as well
+  pub fn extend(&mut self, view: &Scalar, spend: &EdwardsPoint, indexes: &[SubaddressIndex]) {
+    self.0.extend(Subaddresses::new(view, spend, indexes).0);
+  }
+
+  pub(crate) fn get(&self, spend: &EdwardsPoint) -> Option<SubaddressIndex> {
+    self.0.get(&spend.compress()).copied()
+  }
+}
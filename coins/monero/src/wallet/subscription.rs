@@ -0,0 +1,153 @@
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::sleep;
+
+use crate::{
+  transaction::Transaction,
+  rpc::{RpcError, Rpc},
+  wallet::{Candidate, WalletStore, ScanningWallet}
+};
+
+// Time to wait before checking for a new block, when the wallet has scanned the entire chain
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// An output received by the wallet, with how many blocks have confirmed it
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReceivedOutput {
+  pub output: Candidate,
+  /// 1 for an output in the latest block
+  pub confirmations: usize
+}
+
+/// An event yielded by a PaymentSubscription
+#[derive(Clone, PartialEq, Debug)]
+pub enum PaymentEvent {
+  /// An output was received, or gained a confirmation
+  Received(ReceivedOutput),
+  /// An output was in a block reorganized out of the chain, and has been removed from the wallet
+  /// If it's included again, it'll be yielded as received again
+  Revoked(Candidate)
+}
+
+/// Scans blocks into a wallet as they're added to the chain, yielding received outputs and the
+/// confirmations they gain
+/// Each output is yielded once per block, starting with the block which included it, until it has
+/// the requested amount of confirmations. If a block within that window is reorganized out of the
+/// chain, the outputs received since are revoked and the new blocks are scanned
+/// Outputs with the requested amount of confirmations are considered final
+/// The outputs awaiting confirmations, and the hashes of the blocks scanned, are only held in
+/// memory. To resume after a restart, revert the wallet's store by the amount of confirmations
+/// before subscribing again, so the latest blocks are scanned and their outputs yielded again
+pub struct PaymentSubscription<'a, W: ScanningWallet> {
+  wallet: &'a mut W,
+  rpc: &'a Rpc,
+  confirmations: usize,
+  // Hashes of the latest blocks scanned, oldest first, covering the confirmation window
+  hashes: VecDeque<[u8; 32]>,
+  // Outputs which have yet to reach the requested amount of confirmations
+  pending: Vec<Candidate>,
+  events: VecDeque<PaymentEvent>
+}
+
+impl<'a, W: ScanningWallet> PaymentSubscription<'a, W> {
+  /// Subscribe to outputs received from the height the wallet has been scanned to onwards
+  pub fn new(wallet: &'a mut W, rpc: &'a Rpc, confirmations: usize) -> PaymentSubscription<'a, W> {
+    PaymentSubscription {
+      wallet,
+      rpc,
+      confirmations,
+      hashes: VecDeque::new(),
+      pending: vec![],
+      events: VecDeque::new()
+    }
+  }
+
+  // Scan the next block into the wallet, queueing events for outputs received and confirmations
+  // gained
+  // Returns false, without scanning it, if the block doesn't build on the last block scanned
+  pub(crate) fn scan_block(
+    &mut self,
+    hash: [u8; 32],
+    previous: [u8; 32],
+    txs: &[Transaction]
+  ) -> bool {
+    if self.hashes.back().map(|last| *last != previous).unwrap_or(false) {
+      return false;
+    }
+
+    let height = self.wallet.store().scanned_height();
+    for tx in txs {
+      if self.wallet.scan(tx, height).map(|recorded| recorded.received != 0).unwrap_or(false) {
+        let hash = tx.hash();
+        self.pending.extend(
+          self.wallet.store().unspent().into_iter().filter(|candidate| candidate.output.tx == hash)
+        );
+      }
+    }
+    self.wallet.store_mut().scanned_to_height(height + 1);
+
+    self.hashes.push_back(hash);
+    while self.hashes.len() > self.confirmations.max(1) {
+      self.hashes.pop_front();
+    }
+
+    for output in &self.pending {
+      self.events.push_back(
+        PaymentEvent::Received(
+          ReceivedOutput { output: output.clone(), confirmations: height + 1 - output.height }
+        )
+      );
+    }
+    let confirmations = self.confirmations;
+    self.pending.retain(|output| (height + 1 - output.height) < confirmations);
+    true
+  }
+
+  // Revert the wallet to the specified height, within the blocks scanned by this subscription,
+  // revoking the outputs received since
+  pub(crate) fn revert(&mut self, height: usize) {
+    let scanned = self.wallet.store().scanned_height();
+    self.hashes.truncate(self.hashes.len().saturating_sub(scanned.saturating_sub(height)));
+    self.pending.retain(|output| output.height < height);
+    for output in self.wallet.store_mut().revert(height) {
+      self.events.push_back(PaymentEvent::Revoked(output));
+    }
+  }
+
+  // Find the most recent block scanned which is still on the chain, reverting to just after it
+  async fn reorganize(&mut self) -> Result<(), RpcError> {
+    let mut height = self.wallet.store().scanned_height();
+    for hash in self.hashes.iter().rev() {
+      if self.rpc.get_block_hash(height - 1).await? == *hash {
+        break;
+      }
+      height -= 1;
+    }
+    self.revert(height);
+    Ok(())
+  }
+
+  /// Wait for the next output received, confirmation gained, or output revoked
+  pub async fn next(&mut self) -> Result<PaymentEvent, RpcError> {
+    loop {
+      if let Some(event) = self.events.pop_front() {
+        return Ok(event);
+      }
+
+      let scanned = self.wallet.store().scanned_height();
+      if scanned < self.rpc.get_height().await? {
+        // The hash is fetched before the block so if the block changes in between, the next block
+        // won't build on the hash recorded, and the reorganization will still be detected
+        let hash = self.rpc.get_block_hash(scanned).await?;
+        let block = self.rpc.get_block(scanned).await?;
+        let mut txs = vec![block.miner_tx];
+        txs.extend(self.rpc.get_transactions(&block.txs).await?);
+        if !self.scan_block(hash, block.header.previous, &txs) {
+          self.reorganize().await?;
+        }
+      } else {
+        sleep(POLL_INTERVAL).await;
+      }
+    }
+  }
+}
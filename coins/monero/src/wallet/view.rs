@@ -0,0 +1,263 @@
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  generate_key_image,
+  transaction::Transaction,
+  rpc::{RpcError, Rpc},
+  wallet::{
+    Network, AddressType, Address, SubaddressIndex, Subaddresses, Fee, TransactionError,
+    Candidate, InputSelector, SignableTransaction, WalletTransaction, WalletStore, scan_into,
+    SignedKeyImage, UnsignedTransaction, MempoolScanner
+  }
+};
+
+/// A wallet which can scan transactions into its store
+pub trait ScanningWallet {
+  type Store: WalletStore;
+
+  fn store(&self) -> &Self::Store;
+  fn store_mut(&mut self) -> &mut Self::Store;
+  /// Scan a transaction, included at the specified height, into the wallet's store
+  fn scan(&mut self, tx: &Transaction, height: usize) -> Option<WalletTransaction>;
+}
+
+/// A wallet with only the private view key, able to scan and build transactions yet not sign them
+/// As key images require the spend key, spends are only detected once the key images of the
+/// wallet's outputs are imported. Outputs spent before their key images were imported will
+/// remain unspent until the transactions spending them are rescanned
+#[derive(Clone, Debug)]
+pub struct ViewWallet<S: WalletStore> {
+  address: Address,
+  view: Scalar,
+  subaddresses: Subaddresses,
+  store: S
+}
+
+impl<S: WalletStore> ViewWallet<S> {
+  /// Create a view-only wallet for the standard address, which must have this view key
+  pub fn new(address: Address, view: Scalar, store: S) -> Result<ViewWallet<S>, TransactionError> {
+    if address.kind != AddressType::Standard {
+      Err(TransactionError::InvalidAddress)?;
+    }
+    if (&view * &ED25519_BASEPOINT_TABLE) != address.view {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+    Ok(ViewWallet { address, view, subaddresses: Subaddresses::default(), store })
+  }
+
+  /// The wallet's primary address, used for change
+  pub fn address(&self) -> Address {
+    self.address
+  }
+
+  /// Recognize outputs sent to these subaddresses when scanning
+  pub fn add_subaddresses(&mut self, indexes: &[SubaddressIndex]) {
+    self.subaddresses.extend(&self.view, &self.address.spend, indexes);
+  }
+
+  /// Scan a transaction, included at the specified height, into the wallet's store
+  pub fn scan(&mut self, tx: &Transaction, height: usize) -> Option<WalletTransaction> {
+    scan_into(&mut self.store, tx, height, self.view, self.address.spend, &self.subaddresses)
+  }
+
+  /// A scanner for outputs sent to this wallet, and its subaddresses, by pool transactions
+  /// Blocks from the store's scanned height onwards are checked for when they're mined
+  pub fn mempool_scanner(&self) -> MempoolScanner {
+    MempoolScanner::new(
+      self.view,
+      self.address.spend,
+      self.subaddresses.clone(),
+      self.store.scanned_height()
+    )
+  }
+
+  /// Scan every block the store hasn't been scanned to, returning the transactions recorded
+  pub async fn refresh(&mut self, rpc: &Rpc) -> Result<Vec<WalletTransaction>, RpcError> {
+    let mut res = vec![];
+    for height in self.store.scanned_height() .. rpc.get_height().await? {
+      for tx in rpc.get_block_transactions(height).await? {
+        res.extend(self.scan(&tx, height));
+      }
+      self.store.scanned_to_height(height + 1);
+    }
+    Ok(res)
+  }
+
+  /// Import the key images of the wallet's outputs, returning how many were imported
+  /// Key images which aren't validly signed, or which are for outputs the wallet doesn't have,
+  /// are ignored
+  pub fn import_key_images(&mut self, images: &[SignedKeyImage]) -> usize {
+    let mut imported = 0;
+    for candidate in self.store.unspent() {
+      let output = candidate.output;
+      if let Some(image) = images.iter().find(|image| image.key == output.key) {
+        if image.verify() {
+          self.store.set_key_image(output.tx, output.o, image.image);
+          imported += 1;
+        }
+      }
+    }
+    imported
+  }
+
+  /// Outputs which have a known key image and can be spent at the specified height and time
+  pub fn spendable(&self, height: usize, time: u64) -> Vec<Candidate> {
+    self.store.spendable(height, time).into_iter().filter(
+      |candidate| self.store.key_image(candidate.output.tx, candidate.output.o).is_some()
+    ).collect()
+  }
+
+  /// The balance spendable at the specified height and time, and the balance including outputs
+  /// which have yet to mature or unlock
+  /// Outputs without a known key image may have been spent, so the total balance is an upper bound
+  pub fn balance(&self, height: usize, time: u64) -> (u64, u64) {
+    let sum = |candidates: Vec<Candidate>| candidates.iter()
+      .map(|candidate| candidate.output.commitment.amount)
+      .sum();
+    (sum(self.spendable(height, time)), sum(self.store.unspent()))
+  }
+
+  /// Build a transaction, to be included at the specified height and time, spending outputs with
+  /// known key images and sending change to the primary address
+  pub fn transaction<T: InputSelector>(
+    &self,
+    selector: &T,
+    height: usize,
+    time: u64,
+    payments: Vec<(Address, u64)>,
+    fee_rate: Fee
+  ) -> Result<SignableTransaction, TransactionError> {
+    SignableTransaction::select(
+      selector,
+      &self.spendable(height, time),
+      height,
+      time,
+      payments,
+      Some(self.address),
+      fee_rate
+    )
+  }
+
+  /// Build a transaction and select its decoys, for a holder of the spend key to sign offline
+  pub async fn unsigned<R: RngCore + CryptoRng, T: InputSelector>(
+    &self,
+    rng: &mut R,
+    rpc: &Rpc,
+    selector: &T,
+    payments: Vec<(Address, u64)>,
+    fee_rate: Fee
+  ) -> Result<UnsignedTransaction, TransactionError> {
+    let height = rpc.get_height().await.map_err(TransactionError::RpcError)?;
+    let time = rpc.get_median_timestamp(height).await.map_err(TransactionError::RpcError)?;
+    let signable = self.transaction(selector, height, time, payments, fee_rate)?;
+    UnsignedTransaction::new(rng, rpc, signable, height.saturating_sub(10)).await
+  }
+}
+
+impl<S: WalletStore> ScanningWallet for ViewWallet<S> {
+  type Store = S;
+
+  fn store(&self) -> &S {
+    &self.store
+  }
+
+  fn store_mut(&mut self) -> &mut S {
+    &mut self.store
+  }
+
+  fn scan(&mut self, tx: &Transaction, height: usize) -> Option<WalletTransaction> {
+    ViewWallet::scan(self, tx, height)
+  }
+}
+
+/// A wallet with the private spend key, which calculates the key images of its outputs as it scans
+#[derive(Clone, Debug)]
+pub struct SpendWallet<S: WalletStore> {
+  view: ViewWallet<S>,
+  spend: Scalar
+}
+
+impl<S: WalletStore> SpendWallet<S> {
+  pub fn new(network: Network, spend: Scalar, view: Scalar, store: S) -> SpendWallet<S> {
+    let address = Address::standard(
+      network,
+      &spend * &ED25519_BASEPOINT_TABLE,
+      &view * &ED25519_BASEPOINT_TABLE
+    );
+    SpendWallet { view: ViewWallet::new(address, view, store).unwrap(), spend }
+  }
+
+  /// The view-only wallet underlying this wallet
+  pub fn view(&self) -> &ViewWallet<S> {
+    &self.view
+  }
+
+  /// Discard the spend key, leaving a view-only wallet
+  pub fn into_view_only(self) -> ViewWallet<S> {
+    self.view
+  }
+
+  pub fn add_subaddresses(&mut self, indexes: &[SubaddressIndex]) {
+    self.view.add_subaddresses(indexes)
+  }
+
+  /// Scan a transaction, included at the specified height, into the wallet's store
+  pub fn scan(&mut self, tx: &Transaction, height: usize) -> Option<WalletTransaction> {
+    let res = self.view.scan(tx, height)?;
+    if res.received != 0 {
+      for candidate in self.view.store.unspent() {
+        let output = candidate.output;
+        if self.view.store.key_image(output.tx, output.o).is_none() {
+          let image = generate_key_image(&(self.spend + output.key_offset));
+          self.view.store.set_key_image(output.tx, output.o, image);
+        }
+      }
+    }
+    Some(res)
+  }
+
+  /// Scan every block the store hasn't been scanned to, returning the transactions recorded
+  pub async fn refresh(&mut self, rpc: &Rpc) -> Result<Vec<WalletTransaction>, RpcError> {
+    let mut res = vec![];
+    for height in self.view.store.scanned_height() .. rpc.get_height().await? {
+      for tx in rpc.get_block_transactions(height).await? {
+        res.extend(self.scan(&tx, height));
+      }
+      self.view.store.scanned_to_height(height + 1);
+    }
+    Ok(res)
+  }
+
+  /// Build and sign a transaction, sending change to the primary address
+  pub async fn send<R: RngCore + CryptoRng, T: InputSelector>(
+    &self,
+    rng: &mut R,
+    rpc: &Rpc,
+    selector: &T,
+    payments: Vec<(Address, u64)>,
+    fee_rate: Fee
+  ) -> Result<Transaction, TransactionError> {
+    let height = rpc.get_height().await.map_err(TransactionError::RpcError)?;
+    let time = rpc.get_median_timestamp(height).await.map_err(TransactionError::RpcError)?;
+    self.view.transaction(selector, height, time, payments, fee_rate)?
+      .sign(rng, rpc, &self.spend).await
+  }
+}
+
+impl<S: WalletStore> ScanningWallet for SpendWallet<S> {
+  type Store = S;
+
+  fn store(&self) -> &S {
+    &self.view.store
+  }
+
+  fn store_mut(&mut self) -> &mut S {
+    &mut self.view.store
+  }
+
+  fn scan(&mut self, tx: &Transaction, height: usize) -> Option<WalletTransaction> {
+    SpendWallet::scan(self, tx, height)
+  }
+}
@@ -4,13 +4,12 @@ use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
 
 use serde_json::json;
 
-use monero::{
-  network::Network,
-  util::{key::PublicKey, address::Address}
+use monero_serai::{
+  random_scalar,
+  wallet::{Network, Address},
+  rpc::{EmptyResponse, RpcError, Rpc}
 };
 
-use monero_serai::{random_scalar, rpc::{EmptyResponse, RpcError, Rpc}};
-
 pub async fn rpc() -> Rpc {
   let rpc = Rpc::new("http://127.0.0.1:18081".to_string());
 
@@ -21,8 +20,8 @@ pub async fn rpc() -> Rpc {
 
   let addr = Address::standard(
     Network::Mainnet,
-    PublicKey { point: (&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE).compress() },
-    PublicKey { point: (&random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE).compress() }
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE,
+    &random_scalar(&mut OsRng) * &ED25519_BASEPOINT_TABLE
   ).to_string();
 
   // Mine 10 blocks so we have 10 decoys so decoy selection doesn't fail
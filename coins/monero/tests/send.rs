@@ -18,12 +18,7 @@ use transcript::RecommendedTranscript;
 #[cfg(feature = "multisig")]
 use frost::{curve::Ed25519, tests::{THRESHOLD, key_gen, sign}};
 
-use monero::{
-  network::Network,
-  util::{key::PublicKey, address::Address}
-};
-
-use monero_serai::{random_scalar, wallet::SignableTransaction};
+use monero_serai::{random_scalar, wallet::{Network, Address, SignableTransaction}};
 
 mod rpc;
 use crate::rpc::{rpc, mine_block};
@@ -73,11 +68,7 @@ async fn send_core(test: usize, multisig: bool) {
     }
   }
 
-  let addr = Address::standard(
-    Network::Mainnet,
-    PublicKey { point: spend_pub.compress() },
-    PublicKey { point: (&view * &ED25519_BASEPOINT_TABLE).compress() }
-  );
+  let addr = Address::standard(Network::Mainnet, spend_pub, &view * &ED25519_BASEPOINT_TABLE);
 
   let fee = rpc.get_fee().await.unwrap();
 
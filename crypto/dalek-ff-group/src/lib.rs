@@ -9,11 +9,13 @@ use digest::{consts::U64, Digest};
 
 use subtle::{Choice, CtOption, ConstantTimeEq, ConditionallySelectable};
 
+use zeroize::Zeroize;
+
 pub use curve25519_dalek as dalek;
 
 use dalek::{
   constants,
-  traits::Identity,
+  traits::{Identity, MultiscalarMul, VartimeMultiscalarMul},
   scalar::Scalar as DScalar,
   edwards::{
     EdwardsPoint as DEdwardsPoint,
@@ -27,7 +29,11 @@ use dalek::{
   }
 };
 
-use group::{ff::{Field, PrimeField}, Group};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de::Error};
+
+use ff::{Field, PrimeField, FieldBits, PrimeFieldBits, BatchInverter};
+use group::{Group, GroupEncoding, prime::PrimeGroup};
 
 macro_rules! deref_borrow {
   ($Source: ident, $Target: ident) => {
@@ -133,6 +139,17 @@ impl Scalar {
     output.copy_from_slice(&hash.finalize());
     Scalar(DScalar::from_bytes_mod_order_wide(&output))
   }
+
+  /// Invert every scalar in place with a single inversion, via Montgomery's trick
+  /// Zero scalars are left as zero. Returns the inverse of the product of all non-zero scalars
+  pub fn batch_invert(scalars: &mut [Scalar]) -> Scalar {
+    let mut scratch = vec![Scalar::one(); scalars.len()];
+    BatchInverter::invert_with_external_scratch(scalars, &mut scratch)
+  }
+}
+
+impl Zeroize for Scalar {
+  fn zeroize(&mut self) { self.0.zeroize() }
 }
 
 impl ConstantTimeEq for Scalar {
@@ -157,14 +174,27 @@ impl Field for Scalar {
   fn square(&self) -> Self { *self * self }
   fn double(&self) -> Self { *self + self }
   fn invert(&self) -> CtOption<Self> {
-    CtOption::new(Self(self.0.invert()), Choice::from(1 as u8))
+    // dalek returns 0 as the inverse of 0
+    CtOption::new(Self(self.0.invert()), !self.is_zero())
+  }
+  fn sqrt(&self) -> CtOption<Self> {
+    // As l = 5 mod 8, Atkin's algorithm is used
+    // pow_vartime is constant time as these exponents are fixed
+    let double = self.double();
+    let t = double.pow_vartime(SQRT_EXP);
+    let i = double * t.square();
+    let res = *self * t * (i - Self::one());
+    CtOption::new(res, res.square().ct_eq(self))
   }
-  fn sqrt(&self) -> CtOption<Self> { unimplemented!() }
   fn is_zero(&self) -> Choice { self.0.ct_eq(&DScalar::zero()) }
   fn cube(&self) -> Self { *self * self * self }
-  fn pow_vartime<S: AsRef<[u64]>>(&self, _exp: S) -> Self { unimplemented!() }
 }
 
+// (l - 5) / 8, as used by Atkin's square root algorithm
+const SQRT_EXP: [u64; 4] = [0xcb024c634b9eba7d, 0x029bdf3bd45ef39a, 0, 0x0200000000000000];
+// (l - 1) / 2^S, the exponent which maps the multiplicative generator to a 2^S root of unity
+const ROOT_OF_UNITY_EXP: [u64; 4] = [0x960498c6973d74fb, 0x0537be77a8bde735, 0, 0x0400000000000000];
+
 impl From<u64> for Scalar {
   fn from(a: u64) -> Scalar { Self(DScalar::from(a)) }
 }
@@ -175,17 +205,38 @@ impl PrimeField for Scalar {
   const CAPACITY: u32 = 252;
   fn from_repr(bytes: [u8; 32]) -> CtOption<Self> {
     let scalar = DScalar::from_canonical_bytes(bytes).map(|x| Scalar(x));
-    CtOption::new(
-      scalar.unwrap_or(Scalar::zero()),
-      Choice::from(if scalar.is_some() { 1 } else { 0 })
-    )
+    CtOption::new(scalar.unwrap_or(Scalar::zero()), Choice::from(u8::from(scalar.is_some())))
   }
   fn to_repr(&self) -> [u8; 32] { self.0.to_bytes() }
 
   const S: u32 = 2;
-  fn is_odd(&self) -> Choice { unimplemented!() }
+  fn is_odd(&self) -> Choice { Choice::from(self.to_repr()[0] & 1) }
   fn multiplicative_generator() -> Self { 2u64.into() }
-  fn root_of_unity() -> Self { unimplemented!() }
+  fn root_of_unity() -> Self { Self::multiplicative_generator().pow_vartime(ROOT_OF_UNITY_EXP) }
+}
+
+impl PrimeFieldBits for Scalar {
+  type ReprBits = [u8; 32];
+  fn to_le_bits(&self) -> FieldBits<Self::ReprBits> { FieldBits::new(self.to_repr()) }
+  fn char_le_bits() -> FieldBits<Self::ReprBits> {
+    FieldBits::new(constants::BASEPOINT_ORDER.to_bytes())
+  }
+}
+
+// Serialized as the canonical 32-byte encoding, rejecting non-canonical encodings when deserializing
+#[cfg(feature = "serde")]
+impl Serialize for Scalar {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.to_repr().serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Scalar {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+    Option::from(Scalar::from_repr(<[u8; 32]>::deserialize(deserializer)?))
+      .ok_or_else(|| D::Error::custom("non-canonical scalar"))
+  }
 }
 
 macro_rules! dalek_group {
@@ -209,6 +260,10 @@ macro_rules! dalek_group {
 
     pub const $BASEPOINT_POINT: $Point = $Point(constants::$BASEPOINT_POINT);
 
+    impl Zeroize for $Point {
+      fn zeroize(&mut self) { self.0.zeroize() }
+    }
+
     impl Sum<$Point> for $Point {
       fn sum<I: Iterator<Item = $Point>>(iter: I) -> $Point { Self($DPoint::sum(iter)) }
     }
@@ -245,6 +300,56 @@ macro_rules! dalek_group {
       pub fn compress(&self) -> $Compressed {
         $Compressed(self.0.compress())
       }
+
+      /// Multiexp via dalek's own implementation
+      pub fn multiscalar_mul(pairs: &[(Scalar, $Point)]) -> $Point {
+        Self($DPoint::multiscalar_mul(
+          pairs.iter().map(|(scalar, _)| scalar),
+          pairs.iter().map(|(_, point)| point)
+        ))
+      }
+
+      /// Variable time multiexp via dalek's own implementation
+      pub fn vartime_multiscalar_mul(pairs: &[(Scalar, $Point)]) -> $Point {
+        Self($DPoint::vartime_multiscalar_mul(
+          pairs.iter().map(|(scalar, _)| scalar),
+          pairs.iter().map(|(_, point)| point)
+        ))
+      }
+    }
+
+    impl GroupEncoding for $Point {
+      type Repr = [u8; 32];
+
+      fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        let point = $Compressed::new(*bytes).decompress();
+        // Ban points which weren't canonically encoded
+        let canonical = point.map(|point| point.compress().to_bytes() == *bytes).unwrap_or(false);
+        CtOption::new(point.unwrap_or($Point::identity()), Choice::from(u8::from(canonical)))
+      }
+
+      fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        Self::from_bytes(bytes)
+      }
+
+      fn to_bytes(&self) -> Self::Repr {
+        self.compress().to_bytes()
+      }
+    }
+
+    #[cfg(feature = "serde")]
+    impl Serialize for $Point {
+      fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_bytes().serialize(serializer)
+      }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> Deserialize<'de> for $Point {
+      fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$Point, D::Error> {
+        Option::from($Point::from_bytes(&<[u8; 32]>::deserialize(deserializer)?))
+          .ok_or_else(|| D::Error::custom("invalid or non-canonical point"))
+      }
     }
 
     pub struct $Table(pub $DTable);
@@ -291,3 +396,35 @@ dalek_group!(
   RISTRETTO_BASEPOINT_POINT,
   RISTRETTO_BASEPOINT_TABLE
 );
+
+impl PrimeGroup for RistrettoPoint {}
+
+/// Backend used for dalek's arithmetic
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Backend {
+  Serial,
+  Avx2
+}
+
+/// The backend dalek's arithmetic was built to use
+/// The AVX2 backend is used when built with the simd feature and AVX2 enabled as a target feature
+pub fn backend() -> Backend {
+  if cfg!(all(feature = "simd", target_feature = "avx2")) {
+    Backend::Avx2
+  } else {
+    Backend::Serial
+  }
+}
+
+/// If this CPU supports the backend dalek was built to use
+/// Binaries built for AVX2 will fault on CPUs without it, so deployments should check this before
+/// performing any operations
+pub fn backend_supported() -> bool {
+  match backend() {
+    Backend::Serial => true,
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Backend::Avx2 => is_x86_feature_detected!("avx2"),
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    Backend::Avx2 => false
+  }
+}
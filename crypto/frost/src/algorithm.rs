@@ -1,21 +1,30 @@
 use core::{marker::PhantomData, fmt::Debug};
+use std::{io::{self, Read}, collections::BTreeMap};
 
 use rand_core::{RngCore, CryptoRng};
 
 use transcript::Transcript;
 
 use crate::{Curve, FrostError, MultisigView, schnorr};
-pub use schnorr::SchnorrSignature;
+pub use schnorr::{SchnorrSignature, SignatureBatch};
 
 /// Algorithm to use FROST with
-pub trait Algorithm<C: Curve>: Clone {
+/// Resuming a suspended AlgorithmSignatureMachine restores the algorithm's state by replaying
+/// preprocess_addendum and sign_share with zero nonces, discarding their output, while every
+/// participant's actual addendum is passed to process_addendum again. State derived from our own
+/// nonces must therefore not be retained by the algorithm, as it'd be restored incorrectly
+pub trait Algorithm<C: Curve>: Clone + Send + Sync {
   type Transcript: Transcript + Clone + Debug;
   /// The resulting type of the signatures this algorithm will produce
   type Signature: Clone + PartialEq + Debug;
 
+  /// Transcript shared with FROST, which appends every participant's commitments and addendum,
+  /// in order, before deriving the binding factor from it
+  /// Algorithms should derive any randomness they need from this transcript
   fn transcript(&mut self) -> &mut Self::Transcript;
 
   /// Generate an addendum to FROST"s preprocessing stage
+  /// Any state set here must not depend on the nonces, which are zero when resuming
   fn preprocess_addendum<R: RngCore + CryptoRng>(
     &mut self,
     rng: &mut R,
@@ -23,6 +32,12 @@ pub trait Algorithm<C: Curve>: Clone {
     nonces: &[C::F; 2],
   ) -> Vec<u8>;
 
+  /// Length of the addendums generated by preprocess_addendum
+  fn addendum_len() -> usize;
+
+  /// Read an addendum, as generated by preprocess_addendum, from another participant
+  fn read_addendum<R: Read>(&self, reader: &mut R) -> io::Result<Vec<u8>>;
+
   /// Proccess the addendum for the specified participant. Guaranteed to be ordered
   fn process_addendum(
     &mut self,
@@ -36,9 +51,13 @@ pub trait Algorithm<C: Curve>: Clone {
   /// The secret will already have been its lagrange coefficient applied so it is the necessary
   /// key share
   /// The nonce will already have been processed into the combined form d + (e * p)
+  /// The commitments are every participant's, keyed by participant index, and iterate in index
+  /// order
+  /// Any state set here must not depend on the nonce, which is zero when resuming
   fn sign_share(
     &mut self,
     params: &MultisigView<C>,
+    commitments: &BTreeMap<u16, [C::G; 2]>,
     nonce_sum: C::G,
     binding: C::F,
     nonce: C::F,
@@ -78,7 +97,7 @@ impl Transcript for IetfTranscript {
 }
 
 
-pub trait Hram<C: Curve>: Clone {
+pub trait Hram<C: Curve>: Clone + Send + Sync {
   /// HRAM function to generate a challenge
   /// H2 from the IETF draft despite having a different argument set (not pre-formatted)
   #[allow(non_snake_case)]
@@ -120,6 +139,14 @@ impl<C: Curve, H: Hram<C>> Algorithm<C> for Schnorr<C, H> {
     vec![]
   }
 
+  fn addendum_len() -> usize {
+    0
+  }
+
+  fn read_addendum<R: Read>(&self, _: &mut R) -> io::Result<Vec<u8>> {
+    Ok(vec![])
+  }
+
   fn process_addendum(
     &mut self,
     _: &MultisigView<C>,
@@ -133,6 +160,7 @@ impl<C: Curve, H: Hram<C>> Algorithm<C> for Schnorr<C, H> {
   fn sign_share(
     &mut self,
     params: &MultisigView<C>,
+    _: &BTreeMap<u16, [C::G; 2]>,
     nonce_sum: C::G,
     _: C::F,
     nonce: C::F,
@@ -0,0 +1,69 @@
+use rand_core::{RngCore, CryptoRng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use sha2::{Digest, Sha256, Sha512};
+
+use transcript::{Transcript, DigestTranscript};
+
+/// Seed for an auditable ceremony, from which every participant's randomness is derived
+/// Running key generation and signing with the RNGs derived from a seed produces byte-identical
+/// messages across runs, letting auditors replay a ceremony once its seed is revealed. Signing
+/// sessions must set their session ID, with AlgorithmMachine::with_session, to be replayed. Anyone
+/// who learns the seed learns every participant's secrets, so this must never be used for keys
+/// which will actually hold value
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AuditSeed([u8; 32]);
+
+impl AuditSeed {
+  pub fn new(seed: [u8; 32]) -> AuditSeed {
+    AuditSeed(seed)
+  }
+
+  /// Commitment to this seed, to be published before the ceremony so the seed can't be chosen
+  /// after the fact
+  pub fn commitment(&self) -> [u8; 32] {
+    let mut hash = Sha256::new();
+    hash.update(b"FROST Audit Seed Commitment");
+    hash.update(self.0);
+    hash.finalize().into()
+  }
+
+  /// Check a revealed seed against its published commitment
+  pub fn verify(&self, commitment: &[u8; 32]) -> bool {
+    &self.commitment() == commitment
+  }
+
+  /// RNG for the specified participant and purpose, such as a key generation or signing session
+  /// Every purpose must use its own label, as reusing an RNG's output will reuse nonces
+  pub fn rng(&self, i: u16, label: &[u8]) -> AuditRng {
+    let mut transcript = DigestTranscript::<Sha512>::new(b"FROST Audit RNG");
+    transcript.append_message(b"seed", &self.0);
+    transcript.append_message(b"participant", &i.to_be_bytes());
+    transcript.append_message(b"label", label);
+    AuditRng(ChaCha20Rng::from_seed(transcript.rng_seed(b"rng")))
+  }
+}
+
+/// Deterministic RNG derived from an AuditSeed
+#[derive(Clone, Debug)]
+pub struct AuditRng(ChaCha20Rng);
+
+impl RngCore for AuditRng {
+  fn next_u32(&mut self) -> u32 {
+    self.0.next_u32()
+  }
+
+  fn next_u64(&mut self) -> u64 {
+    self.0.next_u64()
+  }
+
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    self.0.fill_bytes(dest)
+  }
+
+  fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    self.0.try_fill_bytes(dest)
+  }
+}
+
+impl CryptoRng for AuditRng {}
@@ -0,0 +1,122 @@
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  curve::Curve,
+  FrostError, DeserializeError, MultisigParams, MultisigKeys,
+  lagrange_batch,
+  key_gen::polynomial
+};
+
+/// Piece of a Shamir backup of a single participant's secret share
+/// This backup is independent of the multisig itself, enabling restoring a participant's keys
+/// from any k of their m backups without involving the other participants
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BackupShare<C: Curve> {
+  // The backup's own threshold, piece count, and this piece's index
+  backup: MultisigParams,
+  // The participant's keys, with this piece in place of their secret share
+  keys: MultisigKeys<C>,
+}
+
+impl<C: Curve> MultisigKeys<C> {
+  /// Split the secret share into m backups, any k of which restore these keys
+  /// Any offset applied to these keys isn't backed up, matching MultisigKeys::serialize
+  pub fn backup<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    k: u16,
+    m: u16
+  ) -> Result<Vec<BackupShare<C>>, FrostError> {
+    MultisigParams::new(k, m, 1)?;
+
+    let mut coefficients = vec![self.secret_share];
+    for _ in 1 .. k {
+      coefficients.push(C::F::random(&mut *rng));
+    }
+
+    Ok(
+      (1 ..= m).map(|i| {
+        let mut keys = self.clone();
+        keys.secret_share = polynomial(&coefficients, i);
+        keys.offset = None;
+        keys.context = None;
+        BackupShare { backup: MultisigParams::new(k, m, i).unwrap(), keys }
+      }).collect()
+    )
+  }
+}
+
+impl<C: Curve> BackupShare<C> {
+  /// Parameters of the backup itself, its threshold, piece count, and this piece's index
+  pub fn backup_params(&self) -> MultisigParams {
+    self.backup
+  }
+
+  /// Parameters of the multisig the backed up participant is in
+  pub fn params(&self) -> MultisigParams {
+    self.keys.params
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = vec![];
+    serialized.extend(self.backup.t.to_be_bytes());
+    serialized.extend(self.backup.n.to_be_bytes());
+    serialized.extend(self.backup.i.to_be_bytes());
+    serialized.extend(self.keys.serialize());
+    serialized
+  }
+
+  pub fn deserialize(serialized: &[u8]) -> Result<BackupShare<C>, FrostError> {
+    if serialized.len() < 6 {
+      Err(FrostError::DeserializeError(DeserializeError::Truncated))?;
+    }
+    let read = |i: usize| u16::from_be_bytes(serialized[i .. (i + 2)].try_into().unwrap());
+    let (k, m, i) = (read(0), read(2), read(4));
+    Ok(
+      BackupShare {
+        backup: MultisigParams::new(k, m, i).map_err(
+          |_| FrostError::DeserializeError(DeserializeError::InvalidParameters(k, m, i))
+        )?,
+        keys: MultisigKeys::deserialize(&serialized[6 ..])?
+      }
+    )
+  }
+}
+
+/// Restore a participant's keys from at least k of their backups
+/// Errors with InvalidShare, for the backed up participant, if the restored secret share doesn't
+/// match their verification share
+pub fn restore<C: Curve>(shares: &[BackupShare<C>]) -> Result<MultisigKeys<C>, FrostError> {
+  let first = shares.first().ok_or(FrostError::NotEnoughSigners(1, 0))?;
+  let k = first.backup.t;
+  if shares.len() < usize::from(k) {
+    Err(FrostError::NotEnoughSigners(k, shares.len()))?;
+  }
+
+  let mut included = vec![];
+  for share in shares {
+    // Every piece must be of the same backup, and have the same public keys
+    if (share.backup.t != k) || (share.backup.n != first.backup.n) ||
+      (share.keys.params != first.keys.params) ||
+      (share.keys.group_key != first.keys.group_key) ||
+      (share.keys.verification_shares != first.keys.verification_shares) {
+      Err(FrostError::InvalidShare(share.backup.i))?;
+    }
+    if included.contains(&share.backup.i) {
+      Err(FrostError::DuplicatedIndex(share.backup.i.into()))?;
+    }
+    included.push(share.backup.i);
+  }
+
+  let mut keys = first.keys.clone();
+  keys.secret_share = shares.iter().zip(lagrange_batch::<C::F>(&included, &included)).fold(
+    C::F::zero(),
+    |secret, (share, coefficient)| secret + (share.keys.secret_share * coefficient)
+  );
+  if (C::generator_table() * keys.secret_share) != keys.verification_shares[&keys.params.i] {
+    Err(FrostError::InvalidShare(keys.params.i))?;
+  }
+  Ok(keys)
+}
@@ -0,0 +1,106 @@
+// Bech32m, as specified by BIP 350, implemented natively as it's only used for displaying keys and
+// signatures
+// Unlike BIP 173, the length isn't limited to 90 characters, as some curves' signatures exceed it
+
+use crate::curve::CurveError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const BECH32M: u32 = 0x2bc830a3;
+const CHECKSUM_LEN: usize = 6;
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+  let mut checksum = 1;
+  for value in values {
+    let top = checksum >> 25;
+    checksum = ((checksum & 0x1ffffff) << 5) ^ u32::from(value);
+    for (i, generator) in GENERATOR.iter().enumerate() {
+      if ((top >> i) & 1) == 1 {
+        checksum ^= generator;
+      }
+    }
+  }
+  checksum
+}
+
+fn expand_hrp(hrp: &[u8]) -> impl '_ + Iterator<Item = u8> {
+  hrp.iter().map(|c| c >> 5).chain(core::iter::once(0)).chain(hrp.iter().map(|c| c & 31))
+}
+
+// The human-readable part must be printable ASCII, and is always used in lower case
+fn hrp(hrp: &str) -> Result<Vec<u8>, CurveError> {
+  if hrp.is_empty() || !hrp.bytes().all(|c| (33 ..= 126).contains(&c)) {
+    Err(CurveError::InvalidBech32)?;
+  }
+  Ok(hrp.to_ascii_lowercase().into_bytes())
+}
+
+pub(crate) fn encode(hrp: &str, data: &[u8]) -> Result<String, CurveError> {
+  let hrp = self::hrp(hrp)?;
+
+  let mut values = Vec::with_capacity((data.len() * 8).div_ceil(5));
+  let (mut accum, mut bits) = (0u32, 0);
+  for byte in data {
+    accum = (accum << 8) | u32::from(*byte);
+    bits += 8;
+    while bits >= 5 {
+      bits -= 5;
+      values.push(u8::try_from((accum >> bits) & 31).unwrap());
+    }
+  }
+  if bits != 0 {
+    values.push(u8::try_from((accum << (5 - bits)) & 31).unwrap());
+  }
+
+  let checksum = polymod(
+    expand_hrp(&hrp).chain(values.iter().copied()).chain([0; CHECKSUM_LEN])
+  ) ^ BECH32M;
+  values.extend(
+    (0 .. CHECKSUM_LEN).map(|i| u8::try_from((checksum >> (5 * (5 - i))) & 31).unwrap())
+  );
+
+  let mut res = String::from_utf8(hrp).unwrap();
+  res.push('1');
+  res.extend(values.iter().map(|value| char::from(CHARSET[usize::from(*value)])));
+  Ok(res)
+}
+
+pub(crate) fn decode(hrp: &str, encoded: &str) -> Result<Vec<u8>, CurveError> {
+  let hrp = self::hrp(hrp)?;
+
+  // Either case is allowed, yet not a mix of both
+  if (encoded.to_ascii_lowercase() != encoded) && (encoded.to_ascii_uppercase() != encoded) {
+    Err(CurveError::InvalidBech32)?;
+  }
+  let encoded = encoded.to_ascii_lowercase();
+
+  let (encoded_hrp, data) = encoded.rsplit_once('1').ok_or(CurveError::InvalidBech32)?;
+  if (encoded_hrp.as_bytes() != hrp) || (data.len() < CHECKSUM_LEN) {
+    Err(CurveError::InvalidBech32)?;
+  }
+  let values = data.bytes().map(
+    |c| CHARSET.iter().position(|charset| *charset == c)
+      .map(|value| u8::try_from(value).unwrap())
+      .ok_or(CurveError::InvalidBech32)
+  ).collect::<Result<Vec<_>, _>>()?;
+  if polymod(expand_hrp(&hrp).chain(values.iter().copied())) != BECH32M {
+    Err(CurveError::InvalidBech32)?;
+  }
+
+  let values = &values[.. values.len() - CHECKSUM_LEN];
+  let mut res = Vec::with_capacity((values.len() * 5) / 8);
+  let (mut accum, mut bits) = (0u32, 0);
+  for value in values {
+    accum = (accum << 5) | u32::from(*value);
+    bits += 5;
+    if bits >= 8 {
+      bits -= 8;
+      res.push(u8::try_from((accum >> bits) & 255).unwrap());
+    }
+  }
+  // Any padding must be less than a value and zero, so every encoding is canonical
+  if (bits >= 5) || ((accum & ((1 << bits) - 1)) != 0) {
+    Err(CurveError::InvalidBech32)?;
+  }
+  Ok(res)
+}
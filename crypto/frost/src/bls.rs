@@ -0,0 +1,174 @@
+use std::{sync::Arc, io::{self, Read, Write}, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::Group;
+
+use bls12_381::{
+  G1Affine, G1Projective, G2Affine, G2Projective, G2Prepared, multi_miller_loop,
+  hash_to_curve::{HashToCurve, ExpandMsgXmd}
+};
+
+use crate::{
+  curve::Bls12381,
+  FrostError, MultisigKeys, MultisigView, Writable,
+  sign::{PreprocessMachine, SignMachine, SignatureMachine},
+  validate_map
+};
+
+/// Domain separation tag for hashing messages to G2, as specified by the BLS signature draft for
+/// its basic, minimal public key size, ciphersuite
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+#[allow(non_snake_case)]
+pub fn hash_to_G2(msg: &[u8]) -> G2Projective {
+  <G2Projective as HashToCurve<ExpandMsgXmd<sha2_9::Sha256>>>::hash_to_curve(msg, DST)
+}
+
+// e(G, signature) == e(public_key, H(m))
+#[allow(non_snake_case)]
+fn verify_pairing(public_key: G1Projective, H: G2Projective, signature: G2Projective) -> bool {
+  bool::from(
+    multi_miller_loop(
+      &[
+        (&G1Affine::from(-G1Projective::generator()), &G2Prepared::from(G2Affine::from(signature))),
+        (&G1Affine::from(public_key), &G2Prepared::from(G2Affine::from(H)))
+      ]
+    ).final_exponentiation().is_identity()
+  )
+}
+
+/// BLS signature, with public keys in G1 and signatures in G2
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlsSignature(pub G2Projective);
+
+impl BlsSignature {
+  pub fn verify(&self, public_key: G1Projective, msg: &[u8]) -> bool {
+    verify_pairing(public_key, hash_to_G2(msg), self.0)
+  }
+
+  pub fn deserialize(serialized: &[u8]) -> Option<BlsSignature> {
+    let point = G2Affine::from_compressed(serialized.try_into().ok()?);
+    if point.is_none().into() {
+      return None;
+    }
+    Some(BlsSignature(point.unwrap().into()))
+  }
+
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<BlsSignature> {
+    let mut serialized = [0; 96];
+    reader.read_exact(&mut serialized)?;
+    BlsSignature::deserialize(&serialized)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid signature"))
+  }
+}
+
+impl Writable for BlsSignature {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&G2Affine::from(self.0).to_compressed())
+  }
+}
+
+/// State machine which produces threshold BLS signatures
+/// BLS signing doesn't require preprocessing, so the preprocess round produces empty messages
+/// which are still expected to be exchanged to confirm the signing set
+/// Signature shares are themselves BLS signatures, under the participant's verification share
+pub struct BlsMachine {
+  keys: Arc<MultisigKeys<Bls12381>>,
+  view: MultisigView<Bls12381>,
+}
+
+pub struct BlsSignMachine {
+  keys: Arc<MultisigKeys<Bls12381>>,
+  view: MultisigView<Bls12381>,
+}
+
+#[allow(non_snake_case)]
+pub struct BlsSignatureMachine {
+  keys: Arc<MultisigKeys<Bls12381>>,
+  view: MultisigView<Bls12381>,
+  H: G2Projective,
+  share: G2Projective,
+}
+
+impl BlsMachine {
+  /// Creates a new machine to sign with the specified keys as part of the specified signing set
+  pub fn new(
+    keys: Arc<MultisigKeys<Bls12381>>,
+    included: &[u16]
+  ) -> Result<BlsMachine, FrostError> {
+    let view = keys.view(included)?;
+    Ok(BlsMachine { keys, view })
+  }
+}
+
+impl PreprocessMachine for BlsMachine {
+  type Preprocess = ();
+  type Signature = BlsSignature;
+  type SignMachine = BlsSignMachine;
+
+  fn preprocess<R: RngCore + CryptoRng>(self, _: &mut R) -> (Self::SignMachine, ()) {
+    (BlsSignMachine { keys: self.keys, view: self.view }, ())
+  }
+}
+
+impl SignMachine<BlsSignature> for BlsSignMachine {
+  type Preprocess = ();
+  type SignatureShare = BlsSignature;
+  type SignatureMachine = BlsSignatureMachine;
+
+  fn read_preprocess<R: Read>(&self, _: &mut R) -> io::Result<()> {
+    Ok(())
+  }
+
+  #[allow(non_snake_case)]
+  fn sign(
+    self,
+    mut commitments: HashMap<u16, ()>,
+    msg: &[u8]
+  ) -> Result<(Self::SignatureMachine, BlsSignature), FrostError> {
+    validate_map(&mut commitments, &self.view.included(), (self.keys.params().i(), ()))?;
+
+    let H = hash_to_G2(msg);
+    let share = H * self.view.secret_share();
+    Ok((BlsSignatureMachine { keys: self.keys, view: self.view, H, share }, BlsSignature(share)))
+  }
+}
+
+impl SignatureMachine<BlsSignature> for BlsSignatureMachine {
+  type SignatureShare = BlsSignature;
+
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<BlsSignature> {
+    BlsSignature::read(reader)
+  }
+
+  fn complete(self, mut shares: HashMap<u16, BlsSignature>) -> Result<BlsSignature, FrostError> {
+    validate_map(
+      &mut shares,
+      &self.view.included(),
+      (self.keys.params().i(), BlsSignature(self.share))
+    )?;
+
+    let mut responses = HashMap::new();
+    let mut sum = G2Projective::identity();
+    for l in &self.view.included() {
+      let share = shares[l].0;
+      sum += share;
+      responses.insert(*l, share);
+    }
+
+    // As with FROST, verify the signature before verifying individual shares
+    if verify_pairing(self.view.group_key(), self.H, sum) {
+      return Ok(BlsSignature(sum));
+    }
+
+    for l in &self.view.included() {
+      if !verify_pairing(self.view.verification_share(*l), self.H, responses[l]) {
+        Err(FrostError::InvalidShare(*l))?;
+      }
+    }
+
+    Err(FrostError::InvalidSignature)
+  }
+}
+
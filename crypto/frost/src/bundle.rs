@@ -0,0 +1,175 @@
+use core::any::Any;
+use std::{sync::Arc, collections::{HashMap, BTreeMap}};
+
+use rand_core::{RngCore, CryptoRng};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+  curve::Curve,
+  FrostError, MultisigParams, MultisigKeys,
+  key_gen::{KeyGenVariant, KeyGenMachine, KeyMachine, SecretShare},
+  validate_map
+};
+
+// The context for a curve's key generation, binding it to the bundle's session
+fn curve_context<C: Curve>(context: &str) -> String {
+  format!("{} {}", context, hex::encode(C::ID))
+}
+
+/// Keys for a single participant across multiple curves, such as ed25519 for Monero and secp256k1
+/// for Bitcoin, all under the same multisig parameters
+/// Keys are identified by their curve's ID, so a bundle holds at most one set of keys per curve
+#[derive(Clone)]
+pub struct KeyBundle {
+  params: MultisigParams,
+  keys: BTreeMap<&'static [u8], Arc<dyn Any + Send + Sync>>,
+}
+
+impl KeyBundle {
+  /// Create an empty bundle for the specified participant
+  pub fn new(params: MultisigParams) -> KeyBundle {
+    KeyBundle { params, keys: BTreeMap::new() }
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// IDs of the curves keys are held for, sorted
+  pub fn curves(&self) -> Vec<&'static [u8]> {
+    self.keys.keys().copied().collect()
+  }
+
+  /// Add keys for a curve, which must have the bundle's parameters
+  /// Errors if the bundle already holds keys for this curve
+  pub fn insert<C: Curve + 'static>(
+    &mut self,
+    keys: Arc<MultisigKeys<C>>
+  ) -> Result<(), FrostError> {
+    if keys.params() != self.params {
+      Err(FrostError::MismatchedParameters)?;
+    }
+    if self.keys.contains_key(C::ID) {
+      Err(FrostError::DuplicatedCurve)?;
+    }
+    self.keys.insert(C::ID, keys);
+    Ok(())
+  }
+
+  /// Keys for the specified curve, if held
+  pub fn keys<C: Curve + 'static>(&self) -> Option<Arc<MultisigKeys<C>>> {
+    self.keys.get(C::ID).and_then(|keys| keys.clone().downcast().ok())
+  }
+}
+
+/// Driver for generating a KeyBundle, running a key generation per curve
+/// Every curve's key generation uses a context derived from the bundle's, and the bundle's session
+/// hash commits to every curve's session hash, so a single comparison confirms all of them
+pub struct KeyBundleMachine {
+  params: MultisigParams,
+  context: String,
+  variant: KeyGenVariant,
+  started: Vec<&'static [u8]>,
+  hashes: BTreeMap<&'static [u8], Vec<u8>>,
+  bundle: KeyBundle,
+}
+
+impl KeyBundleMachine {
+  /// Creates a new driver for the specified multisig
+  // The context string must be unique among multisigs
+  pub fn new(
+    params: MultisigParams,
+    context: String,
+    variant: KeyGenVariant
+  ) -> KeyBundleMachine {
+    KeyBundleMachine {
+      params,
+      context,
+      variant,
+      started: vec![],
+      hashes: BTreeMap::new(),
+      bundle: KeyBundle::new(params)
+    }
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// Start key generation for the specified curve, returning the machine to run it with
+  /// Each curve may only be started once
+  pub fn key_gen<C: Curve + 'static>(&mut self) -> Result<KeyGenMachine<C>, FrostError> {
+    if self.started.contains(&C::ID) {
+      Err(FrostError::DuplicatedCurve)?;
+    }
+    self.started.push(C::ID);
+    Ok(KeyGenMachine::new(self.params, curve_context::<C>(&self.context), self.variant))
+  }
+
+  /// Complete key generation for the specified curve, adding the resulting keys to the bundle
+  /// The machine must descend from one returned by key_gen
+  pub fn complete<R: RngCore + CryptoRng, C: Curve + 'static>(
+    &mut self,
+    rng: &mut R,
+    machine: KeyMachine<C>,
+    shares: HashMap<u16, SecretShare<C>>
+  ) -> Result<Arc<MultisigKeys<C>>, FrostError> {
+    if !self.started.contains(&C::ID) || (machine.context != curve_context::<C>(&self.context)) {
+      Err(FrostError::UnknownCurve)?;
+    }
+    if self.hashes.contains_key(C::ID) {
+      Err(FrostError::DuplicatedCurve)?;
+    }
+
+    let hash = machine.session_hash();
+    let keys = Arc::new(machine.complete(rng, shares)?);
+    self.bundle.insert(keys.clone())?;
+    self.hashes.insert(C::ID, hash);
+    Ok(keys)
+  }
+
+  /// Digest of this bundle's session, covering the context, parameters, and every completed
+  /// curve's session hash
+  /// Participants should compare this over a secondary channel before trusting any of the keys
+  pub fn session_hash(&self) -> Vec<u8> {
+    let mut transcript = Sha256::new();
+    transcript.update(b"FROST Key Bundle Session Hash");
+    transcript.update(u64::try_from(self.context.len()).unwrap().to_be_bytes());
+    transcript.update(self.context.as_bytes());
+    transcript.update(self.params.t().to_be_bytes());
+    transcript.update(self.params.n().to_be_bytes());
+    for (curve, hash) in &self.hashes {
+      transcript.update(u64::try_from(curve.len()).unwrap().to_be_bytes());
+      transcript.update(curve);
+      transcript.update(u64::try_from(hash.len()).unwrap().to_be_bytes());
+      transcript.update(hash);
+    }
+    transcript.finalize().to_vec()
+  }
+
+  /// Verify everyone else's bundle session hashes match ours
+  pub fn verify_session_hashes(&self, mut hashes: HashMap<u16, Vec<u8>>) -> Result<(), FrostError> {
+    let ours = self.session_hash();
+    validate_map(
+      &mut hashes,
+      &(1 ..= self.params.n()).collect::<Vec<_>>(),
+      (self.params.i(), ours.clone())
+    )?;
+    for l in 1 ..= self.params.n() {
+      if hashes[&l] != ours {
+        trace_event!(warn, participant = l, "inconsistent bundle");
+        Err(FrostError::InconsistentCommitments(l))?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Finish generating the bundle, which requires every started curve to have completed
+  pub fn finish(self) -> Result<KeyBundle, FrostError> {
+    if self.hashes.len() != self.started.len() {
+      Err(FrostError::MissingCurve)?;
+    }
+    Ok(self.bundle)
+  }
+}
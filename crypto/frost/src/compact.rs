@@ -0,0 +1,257 @@
+use std::{marker::PhantomData, io::{self, Read, Write}, time::Duration, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+  curve::Curve,
+  FrostError, MultisigParams, MultisigKeys, Writable,
+  key_gen::{
+    KeyGenVariant, Commitments, SecretShare, commitments_len, share_len,
+    generate_key_r1, verify_r1, polynomial, complete_r2
+  },
+  round::Round,
+  validate_map
+};
+
+/// Length of a serialized commitments digest
+pub const DIGEST_LEN: usize = 32;
+
+/// Digest of a participant's commitments, broadcast during round 1 in place of the commitments
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CommitmentsDigest([u8; DIGEST_LEN]);
+
+impl CommitmentsDigest {
+  fn new<C: Curve>(context: &str, l: u16, commitments: &Commitments<C>) -> CommitmentsDigest {
+    let mut digest = Sha256::new();
+    digest.update(b"FROST Commitments Digest");
+    digest.update(u64::try_from(C::ID.len()).unwrap().to_be_bytes());
+    digest.update(C::ID);
+    digest.update(u64::try_from(context.len()).unwrap().to_be_bytes());
+    digest.update(context.as_bytes());
+    digest.update(l.to_be_bytes());
+    digest.update(commitments.serialize());
+    CommitmentsDigest(digest.finalize().into())
+  }
+
+  /// Read a digest, as written by Writable
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<CommitmentsDigest> {
+    let mut digest = [0; DIGEST_LEN];
+    reader.read_exact(&mut digest)?;
+    Ok(CommitmentsDigest(digest))
+  }
+}
+
+impl Writable for CommitmentsDigest {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.0)
+  }
+}
+
+/// Key generation which broadcasts a fixed size digest of each participant's commitments, instead
+/// of the t points and proof of knowledge, making the broadcast independent of the threshold
+/// The commitments are then revealed alongside the secret shares, and checked against the
+/// broadcasted digests before being used. As the digests are broadcast before anything is
+/// revealed, commitments still can't be chosen with knowledge of anyone else's
+/// This lowers what must be carried by the broadcast channel, such as a consensus layer, for
+/// configurations with large thresholds, while the commitments only travel point to point
+/// Not compatible with KeyGenMachine, as the round 1 messages differ
+pub struct CompactKeyGenMachine<C: Curve> {
+  variant: KeyGenVariant,
+  params: MultisigParams,
+  context: String,
+  _curve: PhantomData<C>,
+}
+
+pub struct CompactSecretShareMachine<C: Curve> {
+  variant: KeyGenVariant,
+  params: MultisigParams,
+  context: String,
+  coefficients: Vec<C::F>,
+  our_commitments: Commitments<C>,
+}
+
+pub struct CompactKeyMachine<C: Curve> {
+  variant: KeyGenVariant,
+  params: MultisigParams,
+  context: String,
+  coefficients: Vec<C::F>,
+  our_commitments: Commitments<C>,
+  digests: HashMap<u16, CommitmentsDigest>,
+}
+
+impl<C: Curve> CompactKeyGenMachine<C> {
+  /// Creates a new machine to generate a key for the specified curve in the specified multisig
+  // The context string must be unique among multisigs
+  pub fn new(
+    params: MultisigParams,
+    context: String,
+    variant: KeyGenVariant
+  ) -> CompactKeyGenMachine<C> {
+    CompactKeyGenMachine { variant, params, context, _curve: PhantomData }
+  }
+
+  /// Start generating a key
+  /// Returns the digest to be broadcast to all parties. If any party submits multiple digests,
+  /// they MUST be treated as malicious
+  pub fn generate_coefficients<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R
+  ) -> (CompactSecretShareMachine<C>, CommitmentsDigest) {
+    trace_span!("compact_key_gen", round = "digest", i = self.params.i());
+    let (coefficients, commitments) = generate_key_r1::<R, C>(
+      rng,
+      self.variant,
+      &self.params,
+      &self.context
+    );
+    let digest = CommitmentsDigest::new(&self.context, self.params.i(), &commitments);
+    trace_event!(debug, "generated commitments digest");
+    (
+      CompactSecretShareMachine {
+        variant: self.variant,
+        params: self.params,
+        context: self.context,
+        coefficients,
+        our_commitments: commitments
+      },
+      digest
+    )
+  }
+}
+
+// Machine, commitments to reveal, and secret shares for every other participant
+type SecretShares<C> = (CompactKeyMachine<C>, Commitments<C>, HashMap<u16, SecretShare<C>>);
+
+impl<C: Curve> CompactSecretShareMachine<C> {
+  /// Start the deadline for the messages this machine awaits, everyone else's digests
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    let i = self.params.i();
+    Round::new((1 ..= self.params.n()).filter(|l| *l != i).collect(), timeout)
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// Length of the messages this machine awaits, everyone else's digests
+  pub fn message_len(&self) -> usize {
+    DIGEST_LEN
+  }
+
+  /// Read another participant's digest
+  pub fn read_digest<R: Read>(&self, reader: &mut R) -> io::Result<CommitmentsDigest> {
+    CommitmentsDigest::read(reader)
+  }
+
+  /// Continue generating a key
+  /// Takes in everyone else's digests, keyed by participant index. Returns our commitments, to be
+  /// sent to every other participant, and a secret share for each other participant which should
+  /// be encrypted before sending
+  pub fn generate_secret_shares(
+    self,
+    mut digests: HashMap<u16, CommitmentsDigest>,
+  ) -> Result<SecretShares<C>, FrostError> {
+    trace_span!("compact_key_gen", round = "secret_shares", i = self.params.i());
+    validate_map(
+      &mut digests,
+      &(1 ..= self.params.n()).collect::<Vec<_>>(),
+      (
+        self.params.i(),
+        CommitmentsDigest::new(&self.context, self.params.i(), &self.our_commitments)
+      )
+    )?;
+
+    // The shares can't be verified until the commitments are revealed, yet they're solely for
+    // their recipients, who will verify them against the commitments they're sent
+    let shares = (1 ..= self.params.n()).filter(|l| *l != self.params.i()).map(
+      |l| (l, SecretShare(polynomial(&self.coefficients, l)))
+    ).collect::<HashMap<_, _>>();
+    trace_event!(debug, shares = shares.len(), "generated secret shares");
+
+    let commitments = self.our_commitments.clone();
+    Ok((
+      CompactKeyMachine {
+        variant: self.variant,
+        params: self.params,
+        context: self.context,
+        coefficients: self.coefficients,
+        our_commitments: self.our_commitments,
+        digests
+      },
+      commitments,
+      shares
+    ))
+  }
+}
+
+impl<C: Curve> CompactKeyMachine<C> {
+  /// Start the deadline for the messages this machine awaits, everyone else's commitments and
+  /// secret shares
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    let i = self.params.i();
+    Round::new((1 ..= self.params.n()).filter(|l| *l != i).collect(), timeout)
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// Length of the messages this machine awaits, everyone else's commitments and secret share
+  pub fn message_len(&self) -> usize {
+    commitments_len::<C>(&self.params) + share_len::<C>()
+  }
+
+  /// Read another participant's commitments
+  pub fn read_commitments<R: Read>(&self, reader: &mut R) -> io::Result<Commitments<C>> {
+    Commitments::read(reader, self.params)
+  }
+
+  /// Read a secret share sent to us by another participant
+  pub fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SecretShare<C>> {
+    SecretShare::read(reader)
+  }
+
+  /// Complete key generation
+  /// Takes in everyone else's commitments and the shares they submitted to us, keyed by
+  /// participant index. Commitments which don't match their broadcast digest are rejected. As
+  /// with KeyMachine::complete, all participants should report completion before the key is used
+  pub fn complete<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    commitments: HashMap<u16, Commitments<C>>,
+    shares: HashMap<u16, SecretShare<C>>,
+  ) -> Result<MultisigKeys<C>, FrostError> {
+    trace_span!("compact_key_gen", round = "complete", i = self.params.i());
+    for l in (1 ..= self.params.n()).filter(|l| *l != self.params.i()) {
+      let these_commitments = commitments.get(&l).ok_or(FrostError::MissingParticipant(l))?;
+      if CommitmentsDigest::new(&self.context, l, these_commitments) != self.digests[&l] {
+        trace_event!(warn, participant = l, "commitments don't match their digest");
+        Err(FrostError::InvalidCommitment(l))?;
+      }
+    }
+
+    let commitments = verify_r1::<R, C>(
+      rng,
+      self.variant,
+      &self.params,
+      &self.context,
+      self.our_commitments,
+      commitments
+    )?;
+    let keys = complete_r2(
+      rng,
+      self.params,
+      polynomial(&self.coefficients, self.params.i()),
+      commitments,
+      shares
+    )?;
+    trace_event!(
+      info,
+      group_key = hex::encode(C::G_to_bytes(&keys.group_key())).as_str(),
+      "completed key generation"
+    );
+    Ok(keys)
+  }
+}
@@ -0,0 +1,269 @@
+use std::io::{self, Read, Write};
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  curve::Curve,
+  Writable,
+  schnorr::{self, SchnorrSignature},
+  key_gen::{Commitments, SecretShare}
+};
+
+// Domain separation tag for every hash in this module
+const DST: &[u8] = b"FROST Share Complaint";
+
+fn hash<C: Curve>(
+  context: &str,
+  label: &[u8],
+  participants: [u16; 2],
+  points: &[C::G],
+  scalars: &[C::F]
+) -> C::F {
+  // Uses hash_msg to get a fixed size value out of the context string
+  let mut transcript = C::hash_msg(context.as_bytes());
+  transcript.extend(label);
+  transcript.extend(participants[0].to_be_bytes());
+  transcript.extend(participants[1].to_be_bytes());
+  for point in points {
+    transcript.extend(C::G_to_bytes(point));
+  }
+  for scalar in scalars {
+    transcript.extend(C::F_to_bytes(scalar));
+  }
+  C::hash_to_F(DST, &transcript)
+}
+
+// Mask for a share sent from sender to recipient, derived from the Diffie-Hellman key of the
+// share's ephemeral key and the recipient's key
+#[allow(non_snake_case)]
+fn mask<C: Curve>(context: &str, sender: u16, recipient: u16, ephemeral: C::G, K: C::G) -> C::F {
+  hash::<C>(context, b"mask", [sender, recipient], &[ephemeral, K], &[])
+}
+
+fn signature_challenge<C: Curve>(
+  context: &str,
+  sender: u16,
+  recipient: u16,
+  sender_key: C::G,
+  ephemeral: C::G,
+  ciphertext: C::F,
+  nonce: C::G
+) -> C::F {
+  hash::<C>(
+    context,
+    b"share",
+    [sender, recipient],
+    &[sender_key, ephemeral, nonce],
+    &[ciphertext]
+  )
+}
+
+/// A secret share encrypted from its sender to its recipient, and signed by its sender
+/// Participants are identified by long-term encryption keys, known to everyone, which sign shares.
+/// Each share is encrypted to its recipient's key with a fresh ephemeral key, so revealing the
+/// key for one share, as a Complaint does, doesn't reveal any other. If the share is invalid, its
+/// recipient can create a Complaint which anyone can adjudicate
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EncryptedSecretShare<C: Curve> {
+  ephemeral: C::G,
+  ciphertext: C::F,
+  sig: SchnorrSignature<C>,
+}
+
+impl<C: Curve> EncryptedSecretShare<C> {
+  /// Encrypt the share sent by sender to recipient
+  #[allow(non_snake_case)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    context: &str,
+    sender: u16,
+    sender_key: C::F,
+    recipient: u16,
+    recipient_key: C::G,
+    share: &SecretShare<C>
+  ) -> EncryptedSecretShare<C> {
+    let ephemeral_key = C::F::random(&mut *rng);
+    let ephemeral = C::generator_table() * ephemeral_key;
+    let K = recipient_key * ephemeral_key;
+    let ciphertext = share.0 + mask::<C>(context, sender, recipient, ephemeral, K);
+
+    let nonce = C::F::random(&mut *rng);
+    let challenge = signature_challenge::<C>(
+      context,
+      sender,
+      recipient,
+      C::generator_table() * sender_key,
+      ephemeral,
+      ciphertext,
+      C::generator_table() * nonce
+    );
+    EncryptedSecretShare {
+      ephemeral,
+      ciphertext,
+      sig: schnorr::sign::<C>(sender_key, nonce, challenge)
+    }
+  }
+
+  /// Read an encrypted share, as written by Writable
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<EncryptedSecretShare<C>> {
+    let ephemeral = C::read_G(reader)?;
+    let ciphertext = C::read_F(reader)?;
+    #[allow(non_snake_case)]
+    let R = C::read_G(reader)?;
+    Ok(
+      EncryptedSecretShare {
+        ephemeral,
+        ciphertext,
+        sig: SchnorrSignature { R, s: C::read_F(reader)? }
+      }
+    )
+  }
+
+  /// Verify this was signed by the sender
+  pub fn verify(&self, context: &str, sender: u16, sender_key: C::G, recipient: u16) -> bool {
+    schnorr::verify::<C>(
+      sender_key,
+      signature_challenge::<C>(
+        context,
+        sender,
+        recipient,
+        sender_key,
+        self.ephemeral,
+        self.ciphertext,
+        self.sig.R
+      ),
+      &self.sig
+    )
+  }
+
+  /// Decrypt the share sent by sender to recipient, returning None if it wasn't signed by sender
+  #[allow(non_snake_case)]
+  pub fn decrypt(
+    &self,
+    context: &str,
+    sender: u16,
+    sender_key: C::G,
+    recipient: u16,
+    recipient_key: C::F
+  ) -> Option<SecretShare<C>> {
+    if !self.verify(context, sender, sender_key, recipient) {
+      return None;
+    }
+    let K = self.ephemeral * recipient_key;
+    Some(SecretShare(self.ciphertext - mask::<C>(context, sender, recipient, self.ephemeral, K)))
+  }
+}
+
+impl<C: Curve> Writable for EncryptedSecretShare<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&C::G_to_bytes(&self.ephemeral))?;
+    writer.write_all(&C::F_to_bytes(&self.ciphertext))?;
+    writer.write_all(&self.sig.serialize())
+  }
+}
+
+/// A complaint that a participant sent an encrypted share which doesn't match their commitments
+/// This reveals the Diffie-Hellman key between the share's ephemeral key and the accuser's key,
+/// and therefore the disputed share, alongside a DLEq proof the key is correct. Anyone can then
+/// decrypt the share and check it against the accused's commitments. As every share has its own
+/// ephemeral key, no other share is revealed
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Complaint<C: Curve> {
+  share: EncryptedSecretShare<C>,
+  K: C::G,
+  c: C::F,
+  s: C::F,
+}
+
+impl<C: Curve> Complaint<C> {
+  /// Complain about the share the accused sent to the accuser
+  #[allow(non_snake_case)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    context: &str,
+    accuser: u16,
+    accuser_key: C::F,
+    accused: u16,
+    share: EncryptedSecretShare<C>
+  ) -> Complaint<C> {
+    let K = share.ephemeral * accuser_key;
+    let nonce = C::F::random(rng);
+    let c = hash::<C>(
+      context,
+      b"complaint",
+      [accused, accuser],
+      &[
+        C::generator_table() * accuser_key,
+        share.ephemeral,
+        K,
+        C::generator_table() * nonce,
+        share.ephemeral * nonce
+      ],
+      &[]
+    );
+    Complaint { share, K, c, s: nonce + (c * accuser_key) }
+  }
+
+  /// Read a complaint, as written by Writable
+  #[allow(non_snake_case)]
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<Complaint<C>> {
+    let share = EncryptedSecretShare::read(reader)?;
+    let K = C::read_G(reader)?;
+    Ok(Complaint { share, K, c: C::read_F(reader)?, s: C::read_F(reader)? })
+  }
+
+  /// Adjudicate this complaint, given both parties' encryption keys and the accused's round 1
+  /// commitments, returning the participant at fault
+  /// The accuser is at fault if the share wasn't signed by the accused, if the revealed key is
+  /// incorrect, or if the share is actually valid
+  pub fn adjudicate(
+    &self,
+    context: &str,
+    accuser: u16,
+    accuser_key: C::G,
+    accused: u16,
+    accused_key: C::G,
+    commitments: &Commitments<C>
+  ) -> u16 {
+    if !self.share.verify(context, accused, accused_key, accuser) {
+      return accuser;
+    }
+
+    let c = hash::<C>(
+      context,
+      b"complaint",
+      [accused, accuser],
+      &[
+        accuser_key,
+        self.share.ephemeral,
+        self.K,
+        (C::generator_table() * self.s) - (accuser_key * self.c),
+        (self.share.ephemeral * self.s) - (self.K * self.c)
+      ],
+      &[]
+    );
+    if c != self.c {
+      return accuser;
+    }
+
+    let share = self.share.ciphertext -
+      mask::<C>(context, accused, accuser, self.share.ephemeral, self.K);
+    if (C::generator_table() * share) == commitments.evaluate(accuser) {
+      accuser
+    } else {
+      accused
+    }
+  }
+}
+
+impl<C: Curve> Writable for Complaint<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    self.share.write(writer)?;
+    writer.write_all(&C::G_to_bytes(&self.K))?;
+    writer.write_all(&C::F_to_bytes(&self.c))?;
+    writer.write_all(&C::F_to_bytes(&self.s))
+  }
+}
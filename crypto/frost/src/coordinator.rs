@@ -0,0 +1,284 @@
+use std::{io::{self, Read}, time::Duration, collections::{HashMap, BTreeMap}};
+
+use group::ff::Field;
+
+use crate::{
+  curve::Curve,
+  FrostError, MultisigParams, MultisigView,
+  algorithm::Algorithm,
+  round::Round,
+  sign::{Preprocess, SignatureShare, Progress, process_preprocesses, read_preprocess}
+};
+
+/// Signing package, the signing set's preprocesses, to be sent to every member of the signing set
+pub type SigningPackage<C> = HashMap<u16, Preprocess<C>>;
+
+/// Signature aggregator, as described in the FROST paper, for a multisig it holds no share of
+/// It collects preprocesses, selects the signing set, distributes the signing package, and then
+/// verifies and aggregates the signature shares, solely using the multisig's public keys
+pub struct Coordinator<C: Curve, A: Algorithm<C>> {
+  algorithm: A,
+  params: MultisigParams,
+  group_key: C::G,
+  verification_shares: HashMap<u16, C::G>,
+  offset: Option<C::F>,
+  context: Option<Vec<u8>>,
+  preprocesses: HashMap<u16, Preprocess<C>>,
+}
+
+/// Coordinator which has distributed the signing package and is collecting signature shares
+#[allow(non_snake_case)]
+pub struct CoordinatorSignatureMachine<C: Curve, A: Algorithm<C>> {
+  algorithm: A,
+  view: MultisigView<C>,
+  B: BTreeMap<u16, [C::G; 2]>,
+  binding: C::F,
+  R: C::G,
+  shares: HashMap<u16, C::F>,
+}
+
+impl<C: Curve, A: Algorithm<C>> Coordinator<C, A> {
+  /// Creates a new coordinator for the multisig with the specified threshold, group key, and
+  /// verification shares
+  pub fn new(
+    algorithm: A,
+    t: u16,
+    group_key: C::G,
+    verification_shares: HashMap<u16, C::G>
+  ) -> Result<Coordinator<C, A>, FrostError> {
+    let n = u16::try_from(verification_shares.len())
+      .map_err(|_| FrostError::TooManyParticipants(verification_shares.len(), u16::MAX))?;
+    // The coordinator isn't a participant, yet this validates t and n
+    let params = MultisigParams::new(t, n, 1)?;
+    for l in 1 ..= n {
+      if !verification_shares.contains_key(&l) {
+        Err(FrostError::MissingParticipant(l))?;
+      }
+    }
+
+    Ok(
+      Coordinator {
+        algorithm,
+        params,
+        group_key,
+        verification_shares,
+        offset: None,
+        context: None,
+        preprocesses: HashMap::new()
+      }
+    )
+  }
+
+  /// Offset the multisig's keys, as done with MultisigKeys::offset by the participants
+  pub fn offset(mut self, offset: C::F) -> Coordinator<C, A> {
+    self.offset = Some(offset + self.offset.unwrap_or(C::F::zero()));
+    self.group_key += C::generator_table() * offset;
+    self
+  }
+
+  /// Mix a deployment context into signing, as done with MultisigKeys::with_context by the
+  /// participants
+  pub fn with_context(mut self, context: &[u8]) -> Coordinator<C, A> {
+    self.context = Some(context.to_vec());
+    self
+  }
+
+  /// Read a preprocess sent by a participant
+  pub fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Preprocess<C>> {
+    read_preprocess(&self.algorithm, reader)
+  }
+
+  /// Add a participant's preprocess
+  pub fn add_preprocess(&mut self, l: u16, preprocess: Preprocess<C>) -> Result<(), FrostError> {
+    if (l == 0) || (l > self.params.n()) {
+      Err(FrostError::InvalidParticipantIndex(self.params.n(), l))?;
+    }
+    if self.preprocesses.contains_key(&l) {
+      Err(FrostError::DuplicatedIndex(l.into()))?;
+    }
+    self.preprocesses.insert(l, preprocess);
+    trace_event!(debug, participant = l, "added preprocess");
+    Ok(())
+  }
+
+  /// Start the deadline for the messages this coordinator awaits, the preprocesses yet to be added
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    Round::new(
+      (1 ..= self.params.n()).filter(|l| !self.preprocesses.contains_key(l)).collect(),
+      timeout
+    )
+  }
+
+  /// Participants who have sent a preprocess, sorted
+  pub fn responded(&self) -> Vec<u16> {
+    let mut responded = self.preprocesses.keys().cloned().collect::<Vec<_>>();
+    responded.sort_unstable();
+    responded
+  }
+
+  /// Verify a participant's signature share, as an auditor reviewing a past signing session would
+  /// This solely uses public data, the signing package distributed to the signing set, which
+  /// contains the participant's preprocess, the message, and the multisig's keys this coordinator
+  /// was created with. The preprocesses added to this coordinator are neither used nor modified
+  pub fn verify_share(
+    &self,
+    package: &SigningPackage<C>,
+    msg: &[u8],
+    l: u16,
+    share: SignatureShare<C>
+  ) -> Result<(), FrostError> {
+    let mut included = package.keys().cloned().collect::<Vec<_>>();
+    included.sort_unstable();
+    for i in &included {
+      if (*i == 0) || (*i > self.params.n()) {
+        Err(FrostError::InvalidParticipantIndex(self.params.n(), *i))?;
+      }
+    }
+    if !included.contains(&l) {
+      Err(FrostError::NotIncluded(l))?;
+    }
+    if included.len() < usize::from(self.params.t()) {
+      Err(FrostError::NotEnoughSigners(self.params.t(), included.len()))?;
+    }
+
+    let view = MultisigView::public(
+      self.group_key,
+      included,
+      &self.verification_shares,
+      self.offset
+    );
+    // Replay the algorithm from its initial state, as the signing set did
+    let mut algorithm = self.algorithm.clone();
+    #[allow(non_snake_case)]
+    let (B, binding, R) = process_preprocesses(
+      &mut algorithm,
+      &view,
+      self.offset,
+      self.context.as_deref(),
+      package.clone(),
+      msg
+    )?;
+    algorithm.sign_share(&view, &B, R, binding, C::F::zero(), msg);
+
+    if !algorithm.verify_share(
+      view.verification_share(l),
+      B[&l][0] + (B[&l][1] * binding),
+      share.0
+    ) {
+      trace_event!(warn, participant = l, "invalid signature share");
+      Err(FrostError::InvalidShare(l))?;
+    }
+    Ok(())
+  }
+
+  /// Select the signing set and start signing the specified message
+  /// Returns the signing package, which must be sent to the signing set alongside the message
+  pub fn sign(
+    mut self,
+    included: &[u16],
+    msg: &[u8]
+  ) -> Result<(CoordinatorSignatureMachine<C, A>, SigningPackage<C>), FrostError> {
+    trace_span!("coordinator", round = "sign");
+    let mut included = included.to_vec();
+    included.sort_unstable();
+    if included.len() < usize::from(self.params.t()) {
+      Err(FrostError::NotEnoughSigners(self.params.t(), included.len()))?;
+    }
+    for i in 0 .. included.len() {
+      if (i != 0) && (included[i - 1] == included[i]) {
+        Err(FrostError::DuplicatedIndex(included[i].into()))?;
+      }
+      if !self.preprocesses.contains_key(&included[i]) {
+        Err(FrostError::MissingParticipant(included[i]))?;
+      }
+    }
+
+    let package = included.iter().map(
+      |l| (*l, self.preprocesses.remove(l).unwrap())
+    ).collect::<HashMap<_, _>>();
+    let view = MultisigView::public(
+      self.group_key,
+      included,
+      &self.verification_shares,
+      self.offset
+    );
+
+    #[allow(non_snake_case)]
+    let (B, binding, R) = process_preprocesses(
+      &mut self.algorithm,
+      &view,
+      self.offset,
+      self.context.as_deref(),
+      package.clone(),
+      msg
+    )?;
+    // Algorithms derive their challenge when signing, so sign with the public view and a zero
+    // nonce, discarding the resulting share. The view's secret share is solely public data
+    self.algorithm.sign_share(&view, &B, R, binding, C::F::zero(), msg);
+    trace_event!(debug, included = ?view.included, "selected signing set");
+
+    Ok((
+      CoordinatorSignatureMachine {
+        algorithm: self.algorithm,
+        view,
+        B,
+        binding,
+        R,
+        shares: HashMap::new()
+      },
+      package
+    ))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> CoordinatorSignatureMachine<C, A> {
+  /// Read a signature share sent by a participant
+  pub fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
+    Ok(SignatureShare(C::read_F(reader)?))
+  }
+
+  /// Start the deadline for the messages this coordinator awaits, the shares yet to be added
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    Round::new(self.missing(), timeout)
+  }
+
+  /// Participants whose shares have yet to be added
+  pub fn missing(&self) -> Vec<u16> {
+    self.view.included.iter().filter(|l| !self.shares.contains_key(l)).cloned().collect()
+  }
+
+  /// Add a share from the specified participant, verifying it on arrival
+  pub fn add_share(&mut self, l: u16, share: SignatureShare<C>) -> Result<Progress, FrostError> {
+    if !self.view.included.contains(&l) {
+      Err(FrostError::NotIncluded(l))?;
+    }
+    if self.shares.contains_key(&l) {
+      Err(FrostError::DuplicatedIndex(l.into()))?;
+    }
+
+    if !self.algorithm.verify_share(
+      self.view.verification_share(l),
+      self.B[&l][0] + (self.B[&l][1] * self.binding),
+      share.0
+    ) {
+      trace_event!(warn, participant = l, "invalid signature share");
+      Err(FrostError::InvalidShare(l))?;
+    }
+    self.shares.insert(l, share.0);
+
+    let missing = self.missing();
+    trace_event!(debug, participant = l, missing = ?missing, "added signature share");
+    Ok(if missing.is_empty() { Progress::Complete } else { Progress::Missing(missing) })
+  }
+
+  /// Aggregate the signature once every share has been added
+  pub fn complete(self) -> Result<A::Signature, FrostError> {
+    if let Some(l) = self.missing().first() {
+      Err(FrostError::MissingParticipant(*l))?;
+    }
+
+    let sum = self.shares.values().fold(C::F::zero(), |sum, share| sum + share);
+    // Every share was already verified, so this should never fail
+    self.algorithm.verify(self.view.group_key, self.R, sum).ok_or(FrostError::InvalidSignature)
+  }
+}
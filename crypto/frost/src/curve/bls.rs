@@ -0,0 +1,116 @@
+use core::convert::TryInto;
+
+use sha2::{Digest, Sha256};
+
+use group::ff::PrimeField;
+
+use bls12_381::{
+  Scalar, G1Affine, G1Projective,
+  hash_to_curve::{HashToField, HashToCurve, ExpandMsgXmd}
+};
+
+use crate::{curve::{CurveError, Curve}, algorithm::Hram};
+
+const CONTEXT: &[u8] = b"FROST-BLS12381G1-SHA256-v5";
+
+/// BLS12-381's G1, with signatures (as relevant) in G2
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Bls12381;
+impl Curve for Bls12381 {
+  type F = Scalar;
+  type G = G1Projective;
+  type T = G1Projective;
+
+  const ID: &'static [u8] = b"BLS12-381";
+
+  fn generator() -> Self::G {
+    G1Projective::generator()
+  }
+
+  fn generator_table() -> Self::T {
+    G1Projective::generator()
+  }
+
+
+  fn hash_msg(msg: &[u8]) -> Vec<u8> {
+    Sha256::new()
+      .chain_update(CONTEXT)
+      .chain_update(b"digest")
+      .chain_update(msg)
+      .finalize()
+      .to_vec()
+  }
+
+  fn hash_binding_factor(binding: &[u8]) -> Self::F {
+    Self::hash_to_F(&[CONTEXT, b"rho"].concat(), binding)
+  }
+
+  fn hash_to_F(dst: &[u8], msg: &[u8]) -> Self::F {
+    // bls12_381 uses an older version of digest, requiring the older version of sha2 here
+    let mut res = [Scalar::zero()];
+    Scalar::hash_to_field::<ExpandMsgXmd<sha2_9::Sha256>>(msg, dst, &mut res);
+    res[0]
+  }
+
+  fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G {
+    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_9::Sha256>>>::hash_to_curve(msg, dst)
+  }
+
+  fn F_len() -> usize {
+    32
+  }
+
+  fn G_len() -> usize {
+    48
+  }
+
+  fn F_from_slice(slice: &[u8]) -> Result<Self::F, CurveError> {
+    let bytes: [u8; 32] = slice.try_into()
+      .map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
+
+    let scalar = Self::F::from_repr(bytes);
+    if scalar.is_none().into() {
+      Err(CurveError::InvalidScalar)?;
+    }
+
+    Ok(scalar.unwrap())
+  }
+
+  fn G_from_slice(slice: &[u8]) -> Result<Self::G, CurveError> {
+    let bytes: [u8; 48] = slice.try_into()
+      .map_err(|_| CurveError::InvalidLength(48, slice.len()))?;
+
+    // from_compressed checks the point is canonically encoded and within the prime order subgroup
+    let point = G1Affine::from_compressed(&bytes);
+    if point.is_none().into() {
+      Err(CurveError::InvalidPoint)?;
+    }
+
+    let point = G1Projective::from(point.unwrap());
+    if point.is_identity().into() {
+      Err(CurveError::InvalidPoint)?;
+    }
+
+    Ok(point)
+  }
+
+  fn F_to_bytes(f: &Self::F) -> Vec<u8> {
+    f.to_bytes().to_vec()
+  }
+
+  fn G_to_bytes(g: &Self::G) -> Vec<u8> {
+    G1Affine::from(g).to_compressed().to_vec()
+  }
+}
+
+#[derive(Clone)]
+pub struct NonIetfBls12381Hram;
+impl Hram<Bls12381> for NonIetfBls12381Hram {
+  #[allow(non_snake_case)]
+  fn hram(R: &G1Projective, A: &G1Projective, m: &[u8]) -> Scalar {
+    Bls12381::hash_to_F(
+      &[CONTEXT, b"chal"].concat(),
+      &[&Bls12381::G_to_bytes(R), &Bls12381::G_to_bytes(A), m].concat()
+    )
+  }
+}
@@ -1,6 +1,6 @@
 use core::convert::TryInto;
-
-use rand_core::{RngCore, CryptoRng};
+#[cfg(feature = "ed25519")]
+use std::io::{self, Read};
 
 use sha2::{Digest, Sha512};
 
@@ -9,6 +9,14 @@ use group::{ff::PrimeField, Group};
 use dalek_ff_group::Scalar;
 
 use crate::{curve::{CurveError, Curve}, algorithm::Hram};
+#[cfg(feature = "ed25519")]
+use num_bigint::BigUint;
+
+#[cfg(feature = "ed25519")]
+use tiny_keccak::{Hasher, Keccak};
+
+#[cfg(feature = "ed25519")]
+use crate::MultisigKeys;
 
 macro_rules! dalek_curve {
   (
@@ -22,6 +30,7 @@ macro_rules! dalek_curve {
     $TABLE: ident,
 
     $torsioned: expr,
+    $hash_to_G: expr,
 
     $ID:      literal,
     $CONTEXT: literal,
@@ -39,18 +48,15 @@ macro_rules! dalek_curve {
 
       const ID: &'static [u8] = $ID;
 
-      const GENERATOR: Self::G = $POINT;
-      const GENERATOR_TABLE: Self::T = &$TABLE;
-
-      const LITTLE_ENDIAN: bool = true;
+      fn generator() -> Self::G {
+        $POINT
+      }
 
-      fn random_nonce<R: RngCore + CryptoRng>(secret: Self::F, rng: &mut R) -> Self::F {
-        let mut seed = vec![0; 32];
-        rng.fill_bytes(&mut seed);
-        seed.extend(&secret.to_bytes());
-        Self::hash_to_F(b"nonce", &seed)
+      fn generator_table() -> Self::T {
+        &$TABLE
       }
 
+
       fn hash_msg(msg: &[u8]) -> Vec<u8> {
         Sha512::new()
           .chain_update($CONTEXT)
@@ -68,6 +74,10 @@ macro_rules! dalek_curve {
         Scalar::from_hash(Sha512::new().chain_update($CONTEXT).chain_update(dst).chain_update(msg))
       }
 
+      fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G {
+        $hash_to_G(dst, msg)
+      }
+
       fn F_len() -> usize {
         32
       }
@@ -90,17 +100,18 @@ macro_rules! dalek_curve {
         let bytes = slice.try_into().map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
         let point = $Compressed::new(bytes).decompress().ok_or(CurveError::InvalidPoint)?;
 
+        // Ban points which weren't canonically encoded
+        if point.compress().to_bytes() != bytes {
+          Err(CurveError::NonCanonicalPoint)?;
+        }
         // Ban identity
         if point.is_identity().into() {
-          Err(CurveError::InvalidPoint)?;
+          Err(CurveError::IdentityPoint)?;
         }
         // Ban torsioned points to meet the prime order group requirement
+        // This also bans every other small order point, as they're solely torsion
         if $torsioned(point) {
-          Err(CurveError::InvalidPoint)?;
-        }
-        // Ban points which weren't canonically encoded
-        if point.compress().to_bytes() != bytes {
-          Err(CurveError::InvalidPoint)?;
+          Err(CurveError::TorsionedPoint)?;
         }
         Ok(point)
       }
@@ -135,6 +146,16 @@ dalek_curve!(
   RISTRETTO_BASEPOINT_POINT,
   RISTRETTO_BASEPOINT_TABLE,
   |_| false,
+  |dst: &[u8], msg: &[u8]| RistrettoPoint(
+    dalek_ff_group::dalek::ristretto::RistrettoPoint::from_uniform_bytes(
+      &Sha512::new()
+        .chain_update(b"FROST-RISTRETTO255-SHA512-v5")
+        .chain_update(dst)
+        .chain_update(msg)
+        .finalize()
+        .into()
+    )
+  ),
   b"ristretto",
   b"FROST-RISTRETTO255-SHA512-v5",
   b"chal",
@@ -151,8 +172,115 @@ dalek_curve!(
   ED25519_BASEPOINT_POINT,
   ED25519_BASEPOINT_TABLE,
   |point: EdwardsPoint| !bool::from(point.is_torsion_free()),
+  |dst: &[u8], msg: &[u8]| {
+    let mut keccak = Keccak::v256();
+    keccak.update(dst);
+    keccak.update(msg);
+    let mut hash = [0; 32];
+    keccak.finalize(&mut hash);
+    monero_map_to_point(hash)
+  },
   b"edwards25519",
   b"",
   b"",
   b"",
 );
+
+// Monero's ge_fromfe_frombytes_vartime, followed by clearing the cofactor as done by hash_to_ec
+// This calculates the y coordinate and the sign of the x coordinate, letting decompression
+// calculate the x coordinate. Variable time, as it's only used with public data
+#[cfg(feature = "ed25519")]
+#[allow(non_snake_case)]
+fn monero_map_to_point(bytes: [u8; 32]) -> EdwardsPoint {
+  let zero = BigUint::from(0u8);
+  let one = BigUint::from(1u8);
+  let p = (&one << 255u8) - 19u8;
+  let pow = |base: &BigUint, exp: &BigUint| base.modpow(exp, &p);
+  let neg = |value: &BigUint| (&p - (value % &p)) % &p;
+
+  let A = BigUint::from(486662u32);
+
+  // All 256 bits are used, without clearing the highest bit as fe_frombytes would
+  let u = BigUint::from_bytes_le(&bytes);
+  let v = (u.pow(2) * 2u8) % &p;
+  let w = (&v + 1u8) % &p;
+  let x = (w.pow(2) + neg(&(A.pow(2) * &v))) % &p;
+
+  // (w / x)^((p + 3) / 8), as calculated by fe_divpowm1
+  let X = {
+    let x3 = pow(&x, &BigUint::from(3u8));
+    let x7 = pow(&x, &BigUint::from(7u8));
+    (&w * x3 * pow(&((&w * x7) % &p), &((&p - 5u8) >> 3u8))) % &p
+  };
+  let x = (X.pow(2) * x) % &p;
+  let sign = (((&w + neg(&x)) % &p) != zero) && (((&w + &x) % &p) != zero);
+
+  let z = neg(&A) * (if sign { one } else { v });
+  let Y = ((&z + neg(&w)) * pow(&((z + w) % &p), &(&p - 2u8))) % &p;
+
+  let mut bytes = [0; 32];
+  let y = Y.to_bytes_le();
+  bytes[.. y.len()].copy_from_slice(&y);
+  bytes[31] |= u8::from(sign) << 7;
+  let point = CompressedEdwardsY::new(bytes).decompress().expect("mapped to an invalid point");
+  EdwardsPoint(point.mul_by_cofactor())
+}
+
+/// RFC 8032's dom2 prefix for Ed25519ph, with an empty context
+#[cfg(feature = "ed25519")]
+pub(crate) const ED25519PH_DOM2: &[u8] = b"SigEd25519 no Ed25519 collisions\x01\x00";
+
+/// Ed25519ph HRAM, as specified by RFC 8032, which signs the SHA-512 hash of a message
+/// The message passed when signing must be the 64-byte pre-hash returned by Ed25519::prehash,
+/// enabling messages too large to keep in memory to be signed
+#[cfg(feature = "ed25519")]
+#[derive(Copy, Clone)]
+pub struct IetfEd25519phHram;
+#[cfg(feature = "ed25519")]
+impl Hram<Ed25519> for IetfEd25519phHram {
+  #[allow(non_snake_case)]
+  fn hram(R: &EdwardsPoint, A: &EdwardsPoint, m: &[u8]) -> Scalar {
+    Ed25519::hash_to_F(
+      ED25519PH_DOM2,
+      &[&R.compress().to_bytes(), &A.compress().to_bytes(), m].concat()
+    )
+  }
+}
+
+#[cfg(feature = "ed25519")]
+impl Ed25519 {
+  /// Hash a message for Ed25519ph, streaming it from the reader instead of loading it into memory
+  pub fn prehash<R: Read>(reader: &mut R) -> io::Result<[u8; 64]> {
+    let mut hasher = Sha512::new();
+    let mut buf = [0; 8192];
+    loop {
+      let read = match reader.read(&mut buf) {
+        Ok(0) => break,
+        Ok(read) => read,
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+        Err(e) => Err(e)?
+      };
+      hasher.update(&buf[.. read]);
+    }
+    Ok(hasher.finalize().into())
+  }
+
+  /// Encode a point as canonical ed25519 public key bytes
+  pub fn to_ed25519_bytes(point: &EdwardsPoint) -> [u8; 32] {
+    point.compress().to_bytes()
+  }
+
+  /// Decode ed25519 public key bytes, rejecting non-canonical encodings, the identity, and points
+  /// with a torsion component
+  pub fn from_ed25519_bytes(bytes: &[u8]) -> Result<EdwardsPoint, CurveError> {
+    Self::G_from_slice(bytes)
+  }
+}
+
+#[cfg(feature = "ed25519")]
+impl MultisigKeys<Ed25519> {
+  /// The group key as ed25519 public key bytes
+  pub fn ed25519_group_key(&self) -> [u8; 32] {
+    Ed25519::to_ed25519_bytes(&self.group_key())
+  }
+}
@@ -0,0 +1,136 @@
+use core::convert::TryInto;
+
+use blake2b_simd::Params;
+
+use group::{ff::PrimeField, Group, GroupEncoding, cofactor::CofactorGroup};
+
+use jubjub::{Fr, ExtendedPoint, SubgroupPoint};
+
+use crate::{curve::{CurveError, Curve}, algorithm::Hram};
+
+const CONTEXT: &[u8] = b"FROST-Jubjub-BLAKE2b-v5";
+
+// Sapling's spend authorization basepoint, as encoded by the Zcash protocol specification
+const SPEND_AUTH_BASEPOINT: [u8; 32] = [
+  48, 181, 242, 170, 173, 50, 86, 48, 188, 221, 219, 206, 77, 103, 101, 109,
+  5, 253, 28, 194, 208, 55, 187, 83, 117, 182, 233, 109, 158, 1, 161, 215
+];
+
+// BLAKE2b-512 with the specified personalization, reduced into the scalar field
+fn hash_to_scalar(personal: &[u8], data: &[&[u8]]) -> Fr {
+  let mut state = Params::new().hash_length(64).personal(personal).to_state();
+  for data in data {
+    state.update(data);
+  }
+  Fr::from_bytes_wide(state.finalize().as_array())
+}
+
+/// Jubjub's prime order subgroup, using Sapling's spend authorization basepoint as the generator
+/// Keys generated over this curve are therefore valid Sapling spend authorizing keys
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Jubjub;
+impl Curve for Jubjub {
+  type F = Fr;
+  type G = SubgroupPoint;
+  type T = SubgroupPoint;
+
+  const ID: &'static [u8] = b"Jubjub";
+
+  fn generator() -> Self::G {
+    SubgroupPoint::from_bytes(&SPEND_AUTH_BASEPOINT).unwrap()
+  }
+
+  fn generator_table() -> Self::T {
+    Self::generator()
+  }
+
+
+  fn hash_msg(msg: &[u8]) -> Vec<u8> {
+    let mut state = Params::new().hash_length(64).to_state();
+    state.update(CONTEXT).update(b"digest").update(msg);
+    state.finalize().as_bytes().to_vec()
+  }
+
+  fn hash_binding_factor(binding: &[u8]) -> Self::F {
+    Self::hash_to_F(b"rho", binding)
+  }
+
+  fn hash_to_F(dst: &[u8], msg: &[u8]) -> Self::F {
+    // BLAKE2b's personalization is limited to 16 bytes, so the context is hashed in instead
+    hash_to_scalar(b"FROST_Jubjub_H2F", &[CONTEXT, dst, msg])
+  }
+
+  fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G {
+    // Try and increment, hashing with a counter until the hash is a valid encoding, then clearing
+    // the cofactor. Variable time, yet only over public data
+    for counter in 0u32 .. {
+      let mut state = Params::new().hash_length(32).personal(b"FROST_Jubjub_H2G").to_state();
+      state.update(CONTEXT).update(dst).update(msg).update(&counter.to_le_bytes());
+      let point = ExtendedPoint::from_bytes(state.finalize().as_bytes().try_into().unwrap());
+      if point.is_some().into() {
+        let point = point.unwrap().clear_cofactor();
+        if !bool::from(point.is_identity()) {
+          return point;
+        }
+      }
+    }
+    unreachable!()
+  }
+
+  fn F_len() -> usize {
+    32
+  }
+
+  fn G_len() -> usize {
+    32
+  }
+
+  fn F_from_slice(slice: &[u8]) -> Result<Self::F, CurveError> {
+    let bytes: [u8; 32] = slice.try_into()
+      .map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
+
+    let scalar = Self::F::from_repr(bytes);
+    if scalar.is_none().into() {
+      Err(CurveError::InvalidScalar)?;
+    }
+
+    Ok(scalar.unwrap())
+  }
+
+  fn G_from_slice(slice: &[u8]) -> Result<Self::G, CurveError> {
+    let bytes: [u8; 32] = slice.try_into()
+      .map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
+
+    // SubgroupPoint's from_bytes checks the point is within the prime order subgroup
+    let point = Self::G::from_bytes(&bytes);
+    if point.is_none().into() || point.unwrap().is_identity().into() {
+      Err(CurveError::InvalidPoint)?;
+    }
+
+    // Ban points which weren't canonically encoded
+    let point = point.unwrap();
+    if point.to_bytes() != bytes {
+      Err(CurveError::InvalidPoint)?;
+    }
+
+    Ok(point)
+  }
+
+  fn F_to_bytes(f: &Self::F) -> Vec<u8> {
+    f.to_repr().to_vec()
+  }
+
+  fn G_to_bytes(g: &Self::G) -> Vec<u8> {
+    g.to_bytes().to_vec()
+  }
+}
+
+/// RedJubjub's H*, making Schnorr signatures over Jubjub valid RedJubjub signatures
+#[derive(Clone)]
+pub struct RedJubjubHram;
+impl Hram<Jubjub> for RedJubjubHram {
+  #[allow(non_snake_case)]
+  fn hram(R: &SubgroupPoint, A: &SubgroupPoint, m: &[u8]) -> Fr {
+    hash_to_scalar(b"Zcash_RedJubjubH", &[&R.to_bytes(), &A.to_bytes(), m])
+  }
+}
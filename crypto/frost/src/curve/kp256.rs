@@ -1,18 +1,26 @@
 use core::convert::TryInto;
 
-use rand_core::{RngCore, CryptoRng};
-
 use sha2::{digest::Update, Digest, Sha256};
 
 use group::{ff::{Field, PrimeField}, Group, GroupEncoding};
 
-use elliptic_curve::{bigint::{Encoding, U384}, hash2curve::{Expander, ExpandMsg, ExpandMsgXmd}};
+use elliptic_curve::{
+  bigint::{Encoding, U384},
+  hash2curve::{Expander, ExpandMsg, ExpandMsgXmd, GroupDigest}
+};
+
+#[cfg(feature = "secp256k1")]
+use elliptic_curve::sec1::ToEncodedPoint;
+
+#[cfg(feature = "secp256k1")]
+use tiny_keccak::{Hasher, Keccak};
 
-use crate::{curve::{CurveError, Curve}, algorithm::Hram};
+use crate::{curve::{CurveError, Curve}, algorithm::Hram, MultisigKeys};
 
 macro_rules! kp_curve {
   (
     $lib:   ident,
+    $Group: ident,
     $Curve: ident,
     $Hram:  ident,
 
@@ -28,18 +36,15 @@ macro_rules! kp_curve {
 
       const ID: &'static [u8] = $ID;
 
-      const GENERATOR: Self::G = $lib::ProjectivePoint::GENERATOR;
-      const GENERATOR_TABLE: Self::G = $lib::ProjectivePoint::GENERATOR;
-
-      const LITTLE_ENDIAN: bool = false;
+      fn generator() -> Self::G {
+        $lib::ProjectivePoint::GENERATOR
+      }
 
-      fn random_nonce<R: RngCore + CryptoRng>(secret: Self::F, rng: &mut R) -> Self::F {
-        let mut seed = vec![0; 32];
-        rng.fill_bytes(&mut seed);
-        seed.extend(secret.to_bytes());
-        Self::hash_to_F(&[$CONTEXT as &[u8], b"nonce"].concat(), &seed)
+      fn generator_table() -> Self::T {
+        $lib::ProjectivePoint::GENERATOR
       }
 
+
       fn hash_msg(msg: &[u8]) -> Vec<u8> {
         (&Sha256::new()
           .chain($CONTEXT)
@@ -78,6 +83,17 @@ macro_rules! kp_curve {
         ).unwrap()
       }
 
+      fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G {
+        let mut dst = dst;
+        let oversize = Sha256::digest([b"H2C-OVERSIZE-DST-", dst].concat());
+        if dst.len() > 255 {
+          dst = &oversize;
+        }
+
+        // SSWU, as specified by the hash to curve RFC, with the ro (random oracle) encoding
+        $lib::$Group::hash_from_bytes::<ExpandMsgXmd<Sha256>>(&[msg], dst).unwrap()
+      }
+
       fn F_len() -> usize {
         32
       }
@@ -119,6 +135,25 @@ macro_rules! kp_curve {
       }
     }
 
+    impl $Curve {
+      /// Encode a point as SEC1 compressed, the form used by G_to_bytes
+      pub fn to_sec1_compressed(point: &$lib::ProjectivePoint) -> [u8; 33] {
+        point.to_bytes().as_slice().try_into().unwrap()
+      }
+
+      /// Decode a SEC1 compressed point, rejecting the identity and points not on the curve
+      pub fn from_sec1_compressed(bytes: &[u8]) -> Result<$lib::ProjectivePoint, CurveError> {
+        Self::G_from_slice(bytes)
+      }
+    }
+
+    impl MultisigKeys<$Curve> {
+      /// The group key, SEC1 compressed
+      pub fn sec1_group_key(&self) -> [u8; 33] {
+        $Curve::to_sec1_compressed(&self.group_key())
+      }
+    }
+
     #[derive(Clone)]
     pub struct $Hram;
     impl Hram<$Curve> for $Hram {
@@ -136,6 +171,7 @@ macro_rules! kp_curve {
 #[cfg(feature = "p256")]
 kp_curve!(
   p256,
+  NistP256,
   P256,
   IetfP256Hram,
   b"P-256",
@@ -146,7 +182,57 @@ kp_curve!(
 kp_curve!(
   k256,
   Secp256k1,
+  Secp256k1,
   NonIetfSecp256k1Hram,
   b"secp256k1",
   b"FROST-secp256k1-SHA256-v5"
 );
+
+#[cfg(feature = "secp256k1")]
+impl Secp256k1 {
+  /// Encode a point as a BIP-340 x-only key
+  /// BIP-340 keys implicitly have an even y coordinate, so points with an odd y coordinate are
+  /// rejected, as signatures under them wouldn't verify against their x-only key
+  pub fn to_bip340(point: &k256::ProjectivePoint) -> Result<[u8; 32], CurveError> {
+    if point.is_identity().into() {
+      Err(CurveError::InvalidPoint)?;
+    }
+    let bytes = point.to_bytes();
+    if bytes[0] != 2 {
+      Err(CurveError::OddY)?;
+    }
+    Ok(bytes[1 ..].try_into().unwrap())
+  }
+
+  /// Decode a BIP-340 x-only key into the point with that x coordinate and an even y coordinate
+  pub fn from_bip340(bytes: &[u8]) -> Result<k256::ProjectivePoint, CurveError> {
+    if bytes.len() != 32 {
+      Err(CurveError::InvalidLength(32, bytes.len()))?;
+    }
+    Self::G_from_slice(&[&[2], bytes].concat())
+  }
+
+  /// The Ethereum address for a point, the last 20 bytes of the Keccak-256 hash of its
+  /// uncompressed encoding, without the SEC1 tag
+  pub fn to_ethereum_address(point: &k256::ProjectivePoint) -> [u8; 20] {
+    let encoded = k256::AffinePoint::from(*point).to_encoded_point(false);
+    let mut keccak = Keccak::v256();
+    keccak.update(&encoded.as_bytes()[1 ..]);
+    let mut hash = [0; 32];
+    keccak.finalize(&mut hash);
+    hash[12 ..].try_into().unwrap()
+  }
+}
+
+#[cfg(feature = "secp256k1")]
+impl MultisigKeys<Secp256k1> {
+  /// The group key as a BIP-340 x-only key, if it has an even y coordinate
+  pub fn bip340_group_key(&self) -> Result<[u8; 32], CurveError> {
+    Secp256k1::to_bip340(&self.group_key())
+  }
+
+  /// The Ethereum address for the group key
+  pub fn ethereum_address(&self) -> [u8; 20] {
+    Secp256k1::to_ethereum_address(&self.group_key())
+  }
+}
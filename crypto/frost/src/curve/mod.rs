@@ -1,17 +1,27 @@
-use core::{ops::Mul, fmt::Debug};
+use core::{ops::Mul, fmt::{self, Debug}, str::FromStr};
+use std::{
+  io::{self, Read},
+  sync::atomic::{AtomicU64, Ordering},
+  time::{SystemTime, UNIX_EPOCH}
+};
 
 use thiserror::Error;
 
 use rand_core::{RngCore, CryptoRng};
 
-use group::{ff::PrimeField, Group, GroupOps};
+use sha2::Sha512;
+use hmac::{Mac, Hmac};
+
+use group::{ff::{PrimeField, PrimeFieldBits}, Group, GroupOps};
 
 #[cfg(any(test, feature = "dalek"))]
 mod dalek;
 #[cfg(any(test, feature = "ristretto"))]
 pub use dalek::{Ristretto, IetfRistrettoHram};
 #[cfg(feature = "ed25519")]
-pub use dalek::{Ed25519, IetfEd25519Hram};
+pub use dalek::{Ed25519, IetfEd25519Hram, IetfEd25519phHram};
+#[cfg(feature = "ed25519")]
+pub(crate) use dalek::ED25519PH_DOM2;
 
 #[cfg(feature = "kp256")]
 mod kp256;
@@ -20,6 +30,24 @@ pub use kp256::{Secp256k1, NonIetfSecp256k1Hram};
 #[cfg(feature = "p256")]
 pub use kp256::{P256, IetfP256Hram};
 
+#[cfg(feature = "bls12_381")]
+mod bls;
+#[cfg(feature = "bls12_381")]
+pub use bls::{Bls12381, NonIetfBls12381Hram};
+
+// The pasta feature alone enables neither curve
+#[cfg(any(feature = "pallas", feature = "vesta"))]
+mod pasta;
+#[cfg(feature = "pallas")]
+pub use pasta::{Pallas, NonIetfPallasHram};
+#[cfg(feature = "vesta")]
+pub use pasta::{Vesta, NonIetfVestaHram};
+
+#[cfg(feature = "jubjub")]
+mod jubjub;
+#[cfg(feature = "jubjub")]
+pub use self::jubjub::{Jubjub, RedJubjubHram};
+
 /// Set of errors for curve-related operations, namely encoding and decoding
 #[derive(Clone, Error, Debug)]
 pub enum CurveError {
@@ -29,6 +57,50 @@ pub enum CurveError {
   InvalidScalar,
   #[error("invalid point")]
   InvalidPoint,
+  #[error("point was the identity")]
+  IdentityPoint,
+  #[error("point wasn't canonically encoded")]
+  NonCanonicalPoint,
+  #[error("point has a torsion component")]
+  TorsionedPoint,
+  #[error("point has an odd y coordinate")]
+  OddY,
+  #[error("invalid hex")]
+  InvalidHex,
+  #[cfg(feature = "bech32")]
+  #[error("invalid bech32")]
+  InvalidBech32,
+}
+
+// RFC 6979's HMAC-DRBG, instantiated with HMAC-SHA512, with the RNG's output as the additional
+// data allowed by its section 3.6. The secret and entropy are fixed length, so the concatenation
+// is unambiguous
+fn hmac_drbg(secret: &[u8], msg: &[u8], entropy: &[u8]) -> [u8; 64] {
+  let hmac = |key: &[u8], data: &[&[u8]]| -> [u8; 64] {
+    let mut hmac = Hmac::<Sha512>::new_from_slice(key).unwrap();
+    for data in data {
+      hmac.update(data);
+    }
+    hmac.finalize().into_bytes().into()
+  };
+
+  let mut k = hmac(&[0; 64], &[&[1; 64], &[0], secret, msg, entropy]);
+  let mut v = hmac(&k, &[&[1; 64]]);
+  k = hmac(&k, &[&v, &[1], secret, msg, entropy]);
+  v = hmac(&k, &[&v]);
+  hmac(&k, &[&v])
+}
+
+// Session data unique to this call, for when the caller doesn't specify a session
+// Combines a process-wide counter with the current time, so sessions are distinct across calls
+// and, unless the clock is reset, across restarts
+pub(crate) fn unique_session() -> [u8; 24] {
+  static COUNTER: AtomicU64 = AtomicU64::new(0);
+  let mut res = [0; 24];
+  res[.. 8].copy_from_slice(&COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+  let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos());
+  res[8 ..].copy_from_slice(&time.unwrap_or(0).to_le_bytes());
+  res
 }
 
 /// Unified trait to manage a field/group
@@ -37,10 +109,10 @@ pub enum CurveError {
 // elliptic-curve exists, yet it doesn't really serve the same role, nor does it use &[u8]/Vec<u8>
 // It uses GenericArray which will hopefully be deprecated as Rust evolves and doesn't offer enough
 // advantages in the modern day to be worth the hassle -- Kayaba
-pub trait Curve: Clone + Copy + PartialEq + Eq + Debug {
+pub trait Curve: Clone + Copy + PartialEq + Eq + Debug + Send + Sync {
   /// Scalar field element type
   // This is available via G::Scalar yet `C::G::Scalar` is ambiguous, forcing horrific accesses
-  type F: PrimeField;
+  type F: PrimeField + PrimeFieldBits;
   /// Group element type
   type G: Group<Scalar = Self::F> + GroupOps;
   /// Precomputed table type
@@ -51,17 +123,36 @@ pub trait Curve: Clone + Copy + PartialEq + Eq + Debug {
 
   /// Generator for the group
   // While group does provide this in its API, privacy coins will want to use a custom basepoint
-  const GENERATOR: Self::G;
+  // This isn't a constant as not every curve library is able to construct points in const contexts
+  fn generator() -> Self::G;
 
   /// Table for the generator for the group
   /// If there isn't a precomputed table available, the generator itself should be used
-  const GENERATOR_TABLE: Self::T;
+  fn generator_table() -> Self::T;
 
-  /// If little endian is used for the scalar field's Repr
-  const LITTLE_ENDIAN: bool;
+  /// Securely generate the pair of nonces for a signing session. H4 from the IETF draft
+  /// Hedged, as with hedged_nonce, with the session and each nonce's index in place of the
+  /// message, as FROST commits to nonces before the message is known
+  /// If the RNG is broken, the nonces are only unique if the session is, so a session must never
+  /// be reused. The secret is the only other input, so this doesn't degrade as gracefully as
+  /// hedged_nonce does
+  fn random_nonces<R: RngCore + CryptoRng>(
+    secret: Self::F,
+    session: &[u8],
+    rng: &mut R
+  ) -> [Self::F; 2] {
+    [0, 1].map(|i| Self::hedged_nonce(secret, &[session, &[i]].concat(), &mut *rng))
+  }
 
-  /// Securely generate a random nonce. H4 from the IETF draft
-  fn random_nonce<R: RngCore + CryptoRng>(secret: Self::F, rng: &mut R) -> Self::F;
+  /// Generate a nonce from the secret, the message, and 32 bytes of randomness, via RFC 6979's
+  /// HMAC-DRBG with the randomness as additional data, then hash_to_F
+  /// If the RNG is weak or entirely broken, this degrades to RFC 6979's deterministic nonces
+  /// instead of to predictable or repeated nonces, which would reveal the secret
+  fn hedged_nonce<R: RngCore + CryptoRng>(secret: Self::F, msg: &[u8], rng: &mut R) -> Self::F {
+    let mut entropy = [0; 32];
+    rng.fill_bytes(&mut entropy);
+    Self::hash_to_F(b"nonce", &hmac_drbg(secret.to_repr().as_ref(), msg, &entropy))
+  }
 
   /// Hash the message for the binding factor. H3 from the IETF draft
   // This doesn't actually need to be part of Curve as it does nothing with the curve
@@ -86,6 +177,11 @@ pub trait Curve: Clone + Copy + PartialEq + Eq + Debug {
   #[allow(non_snake_case)]
   fn hash_to_F(dst: &[u8], msg: &[u8]) -> Self::F;
 
+  /// Group element from hash, with no known discrete logarithm relative to the generator. Used by
+  /// algorithms which need additional generators, such as CLSAG for its key images
+  #[allow(non_snake_case)]
+  fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G;
+
   /// Constant size of a serialized scalar field element
   // The alternative way to grab this would be either serializing a junk element and getting its
   // length or doing a naive division of its BITS property by 8 and assuming a lack of padding
@@ -118,4 +214,53 @@ pub trait Curve: Clone + Copy + PartialEq + Eq + Debug {
   /// Obtain a vector of the byte encoding of G
   #[allow(non_snake_case)]
   fn G_to_bytes(g: &Self::G) -> Vec<u8>;
+
+  /// Read a field element from a reader, as written by F_to_bytes
+  #[allow(non_snake_case)]
+  fn read_F<R: Read>(reader: &mut R) -> io::Result<Self::F> {
+    let mut bytes = vec![0; Self::F_len()];
+    reader.read_exact(&mut bytes)?;
+    Self::F_from_slice(&bytes)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid scalar"))
+  }
+
+  /// Read a group element from a reader, as written by G_to_bytes
+  #[allow(non_snake_case)]
+  fn read_G<R: Read>(reader: &mut R) -> io::Result<Self::G> {
+    let mut bytes = vec![0; Self::G_len()];
+    reader.read_exact(&mut bytes)?;
+    Self::G_from_slice(&bytes)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid point"))
+  }
+}
+
+/// Public key, such as a group key or verification share, displayed as the hex of its encoding
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PublicKey<C: Curve>(pub C::G);
+
+impl<C: Curve> fmt::Display for PublicKey<C> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", hex::encode(C::G_to_bytes(&self.0)))
+  }
+}
+
+impl<C: Curve> FromStr for PublicKey<C> {
+  type Err = CurveError;
+
+  fn from_str(s: &str) -> Result<PublicKey<C>, CurveError> {
+    Ok(PublicKey(C::G_from_slice(&hex::decode(s).map_err(|_| CurveError::InvalidHex)?)?))
+  }
+}
+
+#[cfg(feature = "bech32")]
+impl<C: Curve> PublicKey<C> {
+  /// Encode this key as bech32m, under the specified human-readable part
+  pub fn to_bech32(&self, hrp: &str) -> Result<String, CurveError> {
+    crate::bech32::encode(hrp, &C::G_to_bytes(&self.0))
+  }
+
+  /// Decode a key encoded by to_bech32, which must use the specified human-readable part
+  pub fn from_bech32(hrp: &str, encoded: &str) -> Result<PublicKey<C>, CurveError> {
+    Ok(PublicKey(C::G_from_slice(&crate::bech32::decode(hrp, encoded)?)?))
+  }
 }
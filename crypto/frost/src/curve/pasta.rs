@@ -0,0 +1,148 @@
+use core::convert::TryInto;
+
+use sha2::{Digest, Sha512};
+
+use group::{ff::PrimeField, Group, GroupEncoding};
+
+use pasta_curves::arithmetic::{CurveExt, FieldExt};
+
+use crate::{curve::{CurveError, Curve}, algorithm::Hram};
+
+macro_rules! pasta_curve {
+  (
+    $lib:   ident,
+    $Curve: ident,
+    $Hram:  ident,
+
+    $ID:      literal,
+    $CONTEXT: literal
+  ) => {
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct $Curve;
+    impl Curve for $Curve {
+      type F = pasta_curves::$lib::Scalar;
+      type G = pasta_curves::$lib::Point;
+      type T = pasta_curves::$lib::Point;
+
+      const ID: &'static [u8] = $ID;
+
+      fn generator() -> Self::G {
+        pasta_curves::$lib::Point::generator()
+      }
+
+      fn generator_table() -> Self::T {
+        pasta_curves::$lib::Point::generator()
+      }
+
+
+      fn hash_msg(msg: &[u8]) -> Vec<u8> {
+        Sha512::new()
+          .chain_update($CONTEXT)
+          .chain_update(b"digest")
+          .chain_update(msg)
+          .finalize()
+          .to_vec()
+      }
+
+      fn hash_binding_factor(binding: &[u8]) -> Self::F {
+        Self::hash_to_F(b"rho", binding)
+      }
+
+      fn hash_to_F(dst: &[u8], msg: &[u8]) -> Self::F {
+        // Wide reduction of a 512-bit hash, as done for Ristretto/Ed25519
+        Self::F::from_bytes_wide(
+          &Sha512::new()
+            .chain_update($CONTEXT)
+            .chain_update(dst)
+            .chain_update(msg)
+            .finalize()
+            .into()
+        )
+      }
+
+      fn hash_to_G(dst: &[u8], msg: &[u8]) -> Self::G {
+        // pasta_curves' simplified SWU map, which takes its domain separator as a string
+        let domain = hex::encode([$CONTEXT as &[u8], dst].concat());
+        let point = pasta_curves::$lib::Point::hash_to_curve(&domain)(msg);
+        point
+      }
+
+      fn F_len() -> usize {
+        32
+      }
+
+      fn G_len() -> usize {
+        32
+      }
+
+      fn F_from_slice(slice: &[u8]) -> Result<Self::F, CurveError> {
+        let bytes: [u8; 32] = slice.try_into()
+          .map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
+
+        let scalar = Self::F::from_repr(bytes);
+        if scalar.is_none().into() {
+          Err(CurveError::InvalidScalar)?;
+        }
+
+        Ok(scalar.unwrap())
+      }
+
+      fn G_from_slice(slice: &[u8]) -> Result<Self::G, CurveError> {
+        let bytes: [u8; 32] = slice.try_into()
+          .map_err(|_| CurveError::InvalidLength(32, slice.len()))?;
+
+        let point = Self::G::from_bytes(&bytes);
+        if point.is_none().into() || point.unwrap().is_identity().into() {
+          Err(CurveError::InvalidPoint)?;
+        }
+
+        // Ban points which weren't canonically encoded
+        let point = point.unwrap();
+        if point.to_bytes() != bytes {
+          Err(CurveError::InvalidPoint)?;
+        }
+
+        Ok(point)
+      }
+
+      fn F_to_bytes(f: &Self::F) -> Vec<u8> {
+        f.to_repr().to_vec()
+      }
+
+      fn G_to_bytes(g: &Self::G) -> Vec<u8> {
+        g.to_bytes().to_vec()
+      }
+    }
+
+    #[derive(Clone)]
+    pub struct $Hram;
+    impl Hram<$Curve> for $Hram {
+      #[allow(non_snake_case)]
+      fn hram(
+        R: &pasta_curves::$lib::Point,
+        A: &pasta_curves::$lib::Point,
+        m: &[u8]
+      ) -> pasta_curves::$lib::Scalar {
+        $Curve::hash_to_F(b"chal", &[&$Curve::G_to_bytes(R), &$Curve::G_to_bytes(A), m].concat())
+      }
+    }
+  }
+}
+
+#[cfg(feature = "pallas")]
+pasta_curve!(
+  pallas,
+  Pallas,
+  NonIetfPallasHram,
+  b"Pallas",
+  b"FROST-Pallas-SHA512-v5"
+);
+
+#[cfg(feature = "vesta")]
+pasta_curve!(
+  vesta,
+  Vesta,
+  NonIetfVestaHram,
+  b"Vesta",
+  b"FROST-Vesta-SHA512-v5"
+);
@@ -0,0 +1,84 @@
+use sha2::{Digest, Sha512};
+
+use group::Group;
+
+use dalek_ff_group::{Scalar, EdwardsPoint, CompressedEdwardsY, ED25519_BASEPOINT_POINT};
+
+use crate::curve::ED25519PH_DOM2;
+
+/// Which RFC 8032 verification equation to use
+/// RFC 8032 permits either, with the cofactored equation accepting a superset of the signatures
+/// the cofactorless equation does. Signatures produced by FROST will verify under both
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verification {
+  /// [8][s]B == [8]R + [8][k]A, as specified by RFC 8032
+  Cofactored,
+  /// [s]B == R + [k]A, as done by most ed25519 libraries
+  Cofactorless,
+}
+
+fn decompress(bytes: &[u8]) -> Option<EdwardsPoint> {
+  let bytes: [u8; 32] = bytes.try_into().ok()?;
+  let point = CompressedEdwardsY::new(bytes).decompress()?;
+  // Ban points which weren't canonically encoded
+  if point.compress().to_bytes() != bytes {
+    return None;
+  }
+  Some(point)
+}
+
+#[allow(non_snake_case)]
+fn verify_with_prefix(
+  prefix: &[u8],
+  public_key: &[u8],
+  msg: &[u8],
+  signature: &[u8],
+  verification: Verification
+) -> bool {
+  if signature.len() != 64 {
+    return false;
+  }
+
+  let (A, R) = match (decompress(public_key), decompress(&signature[.. 32])) {
+    (Some(A), Some(R)) => (A, R),
+    _ => return false
+  };
+  let s = match Scalar::from_canonical_bytes(signature[32 ..].try_into().unwrap()) {
+    Some(s) => s,
+    None => return false
+  };
+
+  // k = SHA-512(prefix || R || A || M), hashing the encodings as received
+  let k = Scalar::from_hash(
+    Sha512::new()
+      .chain_update(prefix)
+      .chain_update(&signature[.. 32])
+      .chain_update(public_key)
+      .chain_update(msg)
+  );
+
+  let check = (ED25519_BASEPOINT_POINT * s) - R - (A * k);
+  match verification {
+    Verification::Cofactored => bool::from((check * Scalar::from(8u64)).is_identity()),
+    Verification::Cofactorless => bool::from(check.is_identity())
+  }
+}
+
+/// Verify an RFC 8032 Ed25519 signature, as produced by signing with Ed25519 and IetfEd25519Hram
+/// Unlike the rest of this library, this operates over encodings so it can verify signatures
+/// produced elsewhere, including those whose public keys or nonces have a torsion component
+pub fn verify(public_key: &[u8], msg: &[u8], signature: &[u8], verification: Verification) -> bool {
+  verify_with_prefix(&[], public_key, msg, signature, verification)
+}
+
+/// Verify an RFC 8032 Ed25519ph signature, with an empty context, as produced by signing with
+/// Ed25519 and IetfEd25519phHram
+/// The pre-hash is Ed25519::prehash of the message
+pub fn verify_prehashed(
+  public_key: &[u8],
+  prehash: &[u8; 64],
+  signature: &[u8],
+  verification: Verification
+) -> bool {
+  verify_with_prefix(ED25519PH_DOM2, public_key, prehash, signature, verification)
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::{ff::{Field, PrimeFieldBits}, Group};
+
+use crate::{curve::Curve, FrostError, DeserializeError, key_gen::Commitments};
+
+// Domain separation tag for every challenge in this module
+const DST: &[u8] = b"FROST Escrowed Share";
+
+fn challenge<C: Curve>(context: &str, escrow_key: C::G, label: &[u8], points: &[C::G]) -> C::F {
+  // Uses hash_msg to get a fixed size value out of the context string
+  let mut transcript = C::hash_msg(context.as_bytes());
+  transcript.extend(C::G_to_bytes(&escrow_key));
+  transcript.extend(label);
+  for point in points {
+    transcript.extend(C::G_to_bytes(point));
+  }
+  C::hash_to_F(DST, &transcript)
+}
+
+// Bits of a scalar, least significant first
+fn decompose<C: Curve>(scalar: &C::F) -> Vec<bool> {
+  scalar.to_le_bits().iter().map(|bit| *bit).collect()
+}
+
+/// ElGamal encryption of a single bit, with a proof it's either 0 or 1
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct EncryptedBit<C: Curve> {
+  R: C::G,
+  M: C::G,
+  // Challenges and responses for the 0 and 1 branches of the disjunctive proof
+  c: [C::F; 2],
+  s: [C::F; 2],
+}
+
+impl<C: Curve> EncryptedBit<C> {
+  // The messages each branch is proven for, where branch b claims M - bG = rE
+  #[allow(non_snake_case)]
+  fn branches(&self) -> [C::G; 2] {
+    [self.M, self.M - C::generator()]
+  }
+
+  #[allow(non_snake_case)]
+  fn nonces(&self, escrow_key: C::G) -> Vec<C::G> {
+    let mut res = vec![self.R, self.M];
+    for (b, M) in self.branches().iter().enumerate() {
+      res.push((C::generator_table() * self.s[b]) - (self.R * self.c[b]));
+      res.push((escrow_key * self.s[b]) - (*M * self.c[b]));
+    }
+    res
+  }
+
+  #[allow(non_snake_case)]
+  fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    context: &str,
+    escrow_key: C::G,
+    label: &[u8],
+    bit: bool,
+    r: C::F
+  ) -> EncryptedBit<C> {
+    let b = usize::from(bit);
+    let mut res = EncryptedBit {
+      R: C::generator_table() * r,
+      M: (escrow_key * r) + if bit { C::generator() } else { C::G::identity() },
+      c: [C::F::zero(); 2],
+      s: [C::F::zero(); 2],
+    };
+
+    // Simulate the branch for the bit we don't have
+    res.c[1 - b] = C::F::random(&mut *rng);
+    res.s[1 - b] = C::F::random(&mut *rng);
+    let M = res.branches();
+    let simulated = [
+      (C::generator_table() * res.s[1 - b]) - (res.R * res.c[1 - b]),
+      (escrow_key * res.s[1 - b]) - (M[1 - b] * res.c[1 - b])
+    ];
+
+    let nonce = C::F::random(&mut *rng);
+    let real = [C::generator_table() * nonce, escrow_key * nonce];
+
+    let mut points = vec![res.R, res.M];
+    if bit {
+      points.extend(simulated);
+      points.extend(real);
+    } else {
+      points.extend(real);
+      points.extend(simulated);
+    }
+
+    res.c[b] = challenge::<C>(context, escrow_key, label, &points) - res.c[1 - b];
+    res.s[b] = nonce + (res.c[b] * r);
+    res
+  }
+
+  fn verify(&self, context: &str, escrow_key: C::G, label: &[u8]) -> bool {
+    (self.c[0] + self.c[1]) == challenge::<C>(context, escrow_key, label, &self.nonces(escrow_key))
+  }
+
+  fn decrypt(&self, escrow_secret: C::F) -> Option<bool> {
+    let decrypted = self.M - (self.R * escrow_secret);
+    if bool::from(decrypted.is_identity()) {
+      Some(false)
+    } else if decrypted == C::generator() {
+      Some(true)
+    } else {
+      None
+    }
+  }
+}
+
+/// A secret share, verifiably encrypted to an escrow's public key
+/// Anyone can verify the ciphertext decrypts to the share committed to by a participant's
+/// round 1 commitments, without the ability to decrypt it. The share is encrypted bit by bit,
+/// making this notably larger than the share itself
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EscrowedShare<C: Curve> {
+  bits: Vec<EncryptedBit<C>>,
+  // Proof the bits sum to the share, as a DLEq proof the difference between their sum and the
+  // share's commitment is an encryption of 0
+  c: C::F,
+  s: C::F,
+}
+
+impl<C: Curve> EscrowedShare<C> {
+  // Sum of the bits' R and M, weighted by their position
+  #[allow(non_snake_case)]
+  fn sum(&self) -> (C::G, C::G) {
+    let mut R = C::G::identity();
+    let mut M = C::G::identity();
+    for bit in self.bits.iter().rev() {
+      R = R.double() + bit.R;
+      M = M.double() + bit.M;
+    }
+    (R, M)
+  }
+
+  fn bit_label(l: u16, b: usize) -> Vec<u8> {
+    let mut label = l.to_be_bytes().to_vec();
+    label.extend(u16::try_from(b).unwrap().to_be_bytes());
+    label
+  }
+
+  /// Encrypt the share for participant l to the escrow key
+  #[allow(non_snake_case)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    context: &str,
+    escrow_key: C::G,
+    l: u16,
+    share: C::F
+  ) -> EscrowedShare<C> {
+    let mut r = C::F::zero();
+    let mut bits = vec![];
+    for (b, bit) in decompose::<C>(&share).drain(..).enumerate().rev() {
+      let r_b = C::F::random(&mut *rng);
+      r = r.double() + r_b;
+      bits.push(
+        EncryptedBit::new(&mut *rng, context, escrow_key, &Self::bit_label(l, b), bit, r_b)
+      );
+    }
+    bits.reverse();
+
+    let mut res = EscrowedShare { bits, c: C::F::zero(), s: C::F::zero() };
+    let (R, M) = res.sum();
+    let commitment = C::generator_table() * share;
+
+    let nonce = C::F::random(&mut *rng);
+    res.c = challenge::<C>(
+      context,
+      escrow_key,
+      &l.to_be_bytes(),
+      &[commitment, R, M, C::generator_table() * nonce, escrow_key * nonce]
+    );
+    res.s = nonce + (res.c * r);
+    res
+  }
+
+  /// Verify this decrypts to the share for participant l with the specified commitment
+  #[allow(non_snake_case)]
+  pub fn verify(&self, context: &str, escrow_key: C::G, l: u16, commitment: C::G) -> bool {
+    if self.bits.len() != (C::F_len() * 8) {
+      return false;
+    }
+
+    for (b, bit) in self.bits.iter().enumerate() {
+      if !bit.verify(context, escrow_key, &Self::bit_label(l, b)) {
+        return false;
+      }
+    }
+
+    let (R, M) = self.sum();
+    self.c == challenge::<C>(
+      context,
+      escrow_key,
+      &l.to_be_bytes(),
+      &[
+        commitment,
+        R,
+        M,
+        (C::generator_table() * self.s) - (R * self.c),
+        (escrow_key * self.s) - ((M - commitment) * self.c)
+      ]
+    )
+  }
+
+  /// Decrypt the share with the escrow's private key
+  pub fn decrypt(&self, escrow_secret: C::F) -> Option<C::F> {
+    let mut share = C::F::zero();
+    for bit in self.bits.iter().rev() {
+      share = share.double() + if bit.decrypt(escrow_secret)? { C::F::one() } else { C::F::zero() };
+    }
+    Some(share)
+  }
+
+  pub fn serialized_len() -> usize {
+    (C::F_len() * 8 * ((2 * C::G_len()) + (4 * C::F_len()))) + (2 * C::F_len())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut res = Vec::with_capacity(Self::serialized_len());
+    for bit in &self.bits {
+      res.extend(C::G_to_bytes(&bit.R));
+      res.extend(C::G_to_bytes(&bit.M));
+      for b in 0 .. 2 {
+        res.extend(C::F_to_bytes(&bit.c[b]));
+        res.extend(C::F_to_bytes(&bit.s[b]));
+      }
+    }
+    res.extend(C::F_to_bytes(&self.c));
+    res.extend(C::F_to_bytes(&self.s));
+    res
+  }
+
+  #[allow(non_snake_case)]
+  pub fn deserialize(serialized: &[u8]) -> Result<EscrowedShare<C>, FrostError> {
+    if serialized.len() != Self::serialized_len() {
+      Err(
+        FrostError::DeserializeError(
+          DeserializeError::InvalidLength(Self::serialized_len(), serialized.len())
+        )
+      )?;
+    }
+
+    let mut cursor = 0;
+    let read_G = |cursor: &mut usize| {
+      *cursor += C::G_len();
+      C::G_from_slice(&serialized[(*cursor - C::G_len()) .. *cursor])
+        .map_err(|_| FrostError::DeserializeError(DeserializeError::InvalidPoint))
+    };
+    let read_F = |cursor: &mut usize| {
+      *cursor += C::F_len();
+      C::F_from_slice(&serialized[(*cursor - C::F_len()) .. *cursor])
+        .map_err(|_| FrostError::DeserializeError(DeserializeError::InvalidScalar))
+    };
+
+    let mut bits = Vec::with_capacity(C::F_len() * 8);
+    for _ in 0 .. (C::F_len() * 8) {
+      let (R, M) = (read_G(&mut cursor)?, read_G(&mut cursor)?);
+      let (c0, s0) = (read_F(&mut cursor)?, read_F(&mut cursor)?);
+      let (c1, s1) = (read_F(&mut cursor)?, read_F(&mut cursor)?);
+      bits.push(EncryptedBit { R, M, c: [c0, c1], s: [s0, s1] });
+    }
+
+    Ok(EscrowedShare { bits, c: read_F(&mut cursor)?, s: read_F(&mut cursor)? })
+  }
+}
+
+/// Verify every share sent by participant l was correctly escrowed, given their round 1
+/// commitments, requiring a share for each of the n participants
+/// This only checks the escrowed shares are consistent with the commitments, not that the
+/// commitments themselves were accepted by the other participants
+pub fn verify_escrowed_shares<C: Curve>(
+  context: &str,
+  escrow_key: C::G,
+  n: u16,
+  l: u16,
+  commitments: &Commitments<C>,
+  shares: &HashMap<u16, EscrowedShare<C>>
+) -> Result<(), FrostError> {
+  // Without a share for every participant, the escrow couldn't recover the missing participants'
+  // keys
+  if shares.len() != usize::from(n) {
+    Err(FrostError::InvalidParticipantQuantity(usize::from(n), shares.len()))?;
+  }
+  for i in 1 ..= n {
+    if !shares.contains_key(&i) {
+      Err(FrostError::MissingParticipant(i))?;
+    }
+  }
+
+  for (i, share) in shares {
+    if !share.verify(context, escrow_key, *i, commitments.evaluate(*i)) {
+      Err(FrostError::InvalidShare(l))?;
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,103 @@
+use core::marker::PhantomData;
+use std::io::{self, Read, Write};
+
+use thiserror::Error;
+
+use crate::{curve::Curve, Writable};
+
+/// Version of the protocol messages written by this library
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Reasons a message's header was rejected
+/// Returned within the io::Error of the read, retrievable with HeaderError::from_io
+#[derive(Clone, PartialEq, Eq, Error, Debug)]
+pub enum HeaderError {
+  #[error("unsupported protocol version ({0})")]
+  UnsupportedVersion(u8),
+  #[error("message is for a different curve")]
+  CurveMismatch,
+  #[error("message is for a different session")]
+  SessionMismatch,
+}
+
+impl HeaderError {
+  /// The header error an io::Error was raised for, if it was raised for one
+  pub fn from_io(error: &io::Error) -> Option<&HeaderError> {
+    error.get_ref().and_then(|error| error.downcast_ref::<HeaderError>())
+  }
+}
+
+fn header_error(error: HeaderError) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Header prefixed to key generation and signing messages, identifying the protocol version,
+/// curve, and session they're for
+/// This lets mixed-version cohorts, as present during rolling upgrades, identify why a message
+/// was rejected instead of failing to parse it or blaming its sender for an invalid commitment
+/// For key generation, the session is expected to be the context string
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Header<C: Curve> {
+  session: Vec<u8>,
+  _curve: PhantomData<C>,
+}
+
+impl<C: Curve> Header<C> {
+  /// Header for the specified session, whose ID must be at most 65535 bytes
+  pub fn new(session: &[u8]) -> Header<C> {
+    assert!(session.len() <= u16::MAX.into(), "session ID was too long");
+    Header { session: session.to_vec(), _curve: PhantomData }
+  }
+
+  pub fn session(&self) -> &[u8] {
+    &self.session
+  }
+
+  /// Write the message prefixed with this header
+  pub fn wrap<M: Writable>(&self, message: &M) -> Vec<u8> {
+    let mut res = self.serialize();
+    // Message writes only fail if the underlying writer does
+    message.write(&mut res).unwrap();
+    res
+  }
+
+  /// Read a header, rejecting it if it isn't for this version, curve, and session
+  /// On success, the reader is left at the start of the message
+  pub fn read<R: Read>(&self, reader: &mut R) -> io::Result<()> {
+    let mut version = [0];
+    reader.read_exact(&mut version)?;
+    if version[0] != PROTOCOL_VERSION {
+      Err(header_error(HeaderError::UnsupportedVersion(version[0])))?;
+    }
+
+    let mut len = [0];
+    reader.read_exact(&mut len)?;
+    let mut curve = vec![0; len[0].into()];
+    reader.read_exact(&mut curve)?;
+    if curve != C::ID {
+      Err(header_error(HeaderError::CurveMismatch))?;
+    }
+
+    let mut len = [0; 2];
+    reader.read_exact(&mut len)?;
+    let mut session = vec![0; u16::from_be_bytes(len).into()];
+    reader.read_exact(&mut session)?;
+    if session != self.session {
+      Err(header_error(HeaderError::SessionMismatch))?;
+    }
+    Ok(())
+  }
+}
+
+impl<C: Curve> Writable for Header<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    let curve_len = u8::try_from(C::ID.len())
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "curve ID was too long"))?;
+    let session_len = u16::try_from(self.session.len())
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "session ID was too long"))?;
+    writer.write_all(&[PROTOCOL_VERSION, curve_len])?;
+    writer.write_all(C::ID)?;
+    writer.write_all(&session_len.to_be_bytes())?;
+    writer.write_all(&self.session)
+  }
+}
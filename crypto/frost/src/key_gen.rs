@@ -1,135 +1,239 @@
-use std::{marker::PhantomData, collections::HashMap};
+use std::{marker::PhantomData, io::{self, Read, Write}, time::Duration, collections::HashMap};
 
 use rand_core::{RngCore, CryptoRng};
 
-use group::ff::{Field, PrimeField};
+use group::{ff::{Field, PrimeField}, Group};
 
-use multiexp::{multiexp_vartime, BatchVerifier};
+use multiexp::{Precomputed, BatchVerifier};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use crate::{
   curve::Curve,
-  FrostError, MultisigParams, MultisigKeys,
+  FrostError, MultisigParams, MultisigKeys, Writable,
   schnorr::{self, SchnorrSignature},
+  escrow::EscrowedShare,
+  round::Round,
   validate_map
 };
 
-#[allow(non_snake_case)]
-fn challenge<C: Curve>(context: &str, l: u16, R: &[u8], Am: &[u8]) -> C::F {
-  const DST: &'static [u8] = b"FROST Schnorr Proof of Knowledge";
+/// A participant's round 1 message, their commitments to their polynomial and a proof of
+/// knowledge for its constant term
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Commitments<C: Curve> {
+  pub(crate) commitments: Vec<C::G>,
+  sig: SchnorrSignature<C>,
+}
+
+impl<C: Curve> Commitments<C> {
+  /// Read commitments for a multisig with the specified parameters
+  #[allow(non_snake_case)]
+  pub fn read<R: Read>(reader: &mut R, params: MultisigParams) -> io::Result<Commitments<C>> {
+    let mut commitments = Vec::with_capacity(usize::from(params.t()));
+    for _ in 0 .. params.t() {
+      commitments.push(C::read_G(reader)?);
+    }
+    let R = C::read_G(reader)?;
+    let s = C::read_F(reader)?;
+    Ok(Commitments { commitments, sig: SchnorrSignature { R, s } })
+  }
+
+  // Evaluate the committed polynomial at i, the commitment to the share for participant i
+  pub(crate) fn evaluate(&self, i: u16) -> C::G {
+    let i = C::F::from(u64::from(i));
+    self.commitments.iter().rev().fold(
+      C::G::identity(),
+      |accum, commitment| (accum * i) + commitment
+    )
+  }
+}
+
+// The serialized commitments, without the proof of knowledge, as used in its challenge
+fn serialize_commitments<C: Curve>(commitments: &[C::G]) -> Vec<u8> {
+  commitments.iter().flat_map(|commitment| C::G_to_bytes(commitment)).collect()
+}
+
+impl<C: Curve> Writable for Commitments<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&serialize_commitments::<C>(&self.commitments))?;
+    writer.write_all(&self.sig.serialize())
+  }
+}
+
+/// Length of a participant's serialized commitments for a multisig with the specified parameters
+pub fn commitments_len<C: Curve>(params: &MultisigParams) -> usize {
+  (usize::from(params.t()) * C::G_len()) + C::G_len() + C::F_len()
+}
+
+/// Length of a serialized secret share, prior to any encryption
+pub fn share_len<C: Curve>() -> usize {
+  C::F_len()
+}
+
+/// A secret share sent from one participant to another during round 2
+/// This must be encrypted before being sent, such as with complaint::EncryptedSecretShare, which
+/// enables proving a share was invalid to third parties
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretShare<C: Curve>(pub(crate) C::F);
+
+impl<C: Curve> SecretShare<C> {
+  /// Read a secret share, as written by Writable
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<SecretShare<C>> {
+    Ok(SecretShare(C::read_F(reader)?))
+  }
+}
+
+impl<C: Curve> Writable for SecretShare<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&C::F_to_bytes(&self.0))
+  }
+}
+
+/// Variant of key generation to perform, which decides the shape of the proof of knowledge
+/// All participants must use the same variant
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyGenVariant {
+  /// The proof of knowledge from the FROST paper
+  Frost,
+  /// A proof of possession as described by PedPoP, additionally binding the curve and the
+  /// multisig's parameters
+  PedPop,
+}
 
+#[allow(non_snake_case)]
+fn challenge<C: Curve>(
+  variant: KeyGenVariant,
+  params: &MultisigParams,
+  context: &str,
+  l: u16,
+  R: &[u8],
+  Am: &[u8]
+) -> C::F {
   // Uses hash_msg to get a fixed size value out of the context string
   let mut transcript = C::hash_msg(context.as_bytes());
+  let dst: &[u8] = match variant {
+    KeyGenVariant::Frost => b"FROST Schnorr Proof of Knowledge",
+    KeyGenVariant::PedPop => {
+      transcript.extend(u64::try_from(C::ID.len()).unwrap().to_be_bytes());
+      transcript.extend(C::ID);
+      transcript.extend(params.t().to_be_bytes());
+      transcript.extend(params.n().to_be_bytes());
+      b"FROST PedPoP Proof of Possession"
+    }
+  };
   transcript.extend(l.to_be_bytes());
   transcript.extend(R);
   transcript.extend(Am);
-  C::hash_to_F(DST, &transcript)
+  C::hash_to_F(dst, &transcript)
 }
 
-// Implements steps 1 through 3 of round 1 of FROST DKG. Returns the coefficients, commitments, and
-// the serialized commitments to be broadcasted over an authenticated channel to all parties
-fn generate_key_r1<R: RngCore + CryptoRng, C: Curve>(
+// Implements steps 1 through 3 of round 1 of FROST DKG. Returns the coefficients and the
+// commitments to be broadcasted over an authenticated channel to all parties
+pub(crate) fn generate_key_r1<R: RngCore + CryptoRng, C: Curve>(
   rng: &mut R,
+  variant: KeyGenVariant,
   params: &MultisigParams,
   context: &str,
-) -> (Vec<C::F>, Vec<u8>) {
+) -> (Vec<C::F>, Commitments<C>) {
   let t = usize::from(params.t);
   let mut coefficients = Vec::with_capacity(t);
   let mut commitments = Vec::with_capacity(t);
-  let mut serialized = Vec::with_capacity((C::G_len() * t) + C::G_len() + C::F_len());
 
   for i in 0 .. t {
     // Step 1: Generate t random values to form a polynomial with
     coefficients.push(C::F::random(&mut *rng));
     // Step 3: Generate public commitments
-    commitments.push(C::GENERATOR_TABLE * coefficients[i]);
-    // Serialize them for publication
-    serialized.extend(&C::G_to_bytes(&commitments[i]));
+    commitments.push(C::generator_table() * coefficients[i]);
   }
 
   // Step 2: Provide a proof of knowledge
   let r = C::F::random(rng);
-  serialized.extend(
-    schnorr::sign::<C>(
-      coefficients[0],
-      // This could be deterministic as the PoK is a singleton never opened up to cooperative
-      // discussion
-      // There's no reason to spend the time and effort to make this deterministic besides a
-      // general obsession with canonicity and determinism though
-      r,
-      challenge::<C>(
-        context,
-        params.i(),
-        &C::G_to_bytes(&(C::GENERATOR_TABLE * r)),
-        &serialized
-      )
-    ).serialize()
+  let sig = schnorr::sign::<C>(
+    coefficients[0],
+    // This could be deterministic as the PoK is a singleton never opened up to cooperative
+    // discussion
+    // There's no reason to spend the time and effort to make this deterministic besides a
+    // general obsession with canonicity and determinism though
+    r,
+    challenge::<C>(
+      variant,
+      params,
+      context,
+      params.i(),
+      &C::G_to_bytes(&(C::generator_table() * r)),
+      &serialize_commitments::<C>(&commitments)
+    )
   );
 
   // Step 4: Broadcast
-  (coefficients, serialized)
+  (coefficients, Commitments { commitments, sig })
 }
 
 // Verify the received data from the first round of key generation
-fn verify_r1<R: RngCore + CryptoRng, C: Curve>(
+pub(crate) fn verify_r1<R: RngCore + CryptoRng, C: Curve>(
   rng: &mut R,
+  variant: KeyGenVariant,
   params: &MultisigParams,
   context: &str,
-  our_commitments: Vec<u8>,
-  mut serialized: HashMap<u16, Vec<u8>>,
+  our_commitments: Commitments<C>,
+  mut received: HashMap<u16, Commitments<C>>,
 ) -> Result<HashMap<u16, Vec<C::G>>, FrostError> {
   validate_map(
-    &mut serialized,
+    &mut received,
     &(1 ..= params.n()).into_iter().collect::<Vec<_>>(),
     (params.i(), our_commitments)
   )?;
 
-  let commitments_len = usize::from(params.t()) * C::G_len();
+  // Process the commitments in index order, so the batch verification, and therefore who's
+  // blamed, doesn't depend on the HashMap's iteration order
+  let received = (1 ..= params.n()).map(|l| (l, received.remove(&l).unwrap())).collect::<Vec<_>>();
 
   let mut commitments = HashMap::new();
-
-  #[allow(non_snake_case)]
-  let R_bytes = |l| &serialized[&l][commitments_len .. commitments_len + C::G_len()];
-  #[allow(non_snake_case)]
-  let R = |l| C::G_from_slice(R_bytes(l)).map_err(|_| FrostError::InvalidProofOfKnowledge(l));
-  #[allow(non_snake_case)]
-  let Am = |l| &serialized[&l][0 .. commitments_len];
-
-  let s = |l| C::F_from_slice(
-    &serialized[&l][commitments_len + C::G_len() ..]
-  ).map_err(|_| FrostError::InvalidProofOfKnowledge(l));
-
   let mut signatures = Vec::with_capacity(usize::from(params.n() - 1));
-  for l in 1 ..= params.n() {
-    let mut these_commitments = vec![];
-    for c in 0 .. usize::from(params.t()) {
-      these_commitments.push(
-        C::G_from_slice(
-          &serialized[&l][(c * C::G_len()) .. ((c + 1) * C::G_len())]
-        ).map_err(|_| FrostError::InvalidCommitment(l.try_into().unwrap()))?
-      );
+  for (l, these_commitments, signature) in iter!(received).map(|(l, these_commitments)| {
+    // Commitments read for a different threshold
+    if these_commitments.commitments.len() != usize::from(params.t()) {
+      trace_event!(warn, participant = l, "commitments for a different threshold");
+      Err(FrostError::InvalidCommitment(l))?;
     }
 
     // Don't bother validating our own proof of knowledge
+    let mut signature = None;
     if l != params.i() {
       // Step 5: Validate each proof of knowledge
       // This is solely the prep step for the latter batch verification
-      signatures.push((
+      signature = Some((
         l,
-        these_commitments[0],
-        challenge::<C>(context, l, R_bytes(l), Am(l)),
-        SchnorrSignature::<C> { R: R(l)?, s: s(l)? }
+        these_commitments.commitments[0],
+        challenge::<C>(
+          variant,
+          params,
+          context,
+          l,
+          &C::G_to_bytes(&these_commitments.sig.R),
+          &serialize_commitments::<C>(&these_commitments.commitments)
+        ),
+        these_commitments.sig
       ));
     }
 
+    Ok((l, these_commitments.commitments, signature))
+  }).collect::<Result<Vec<_>, FrostError>>()? {
+    signatures.extend(signature);
     commitments.insert(l, these_commitments);
   }
 
-  schnorr::batch_verify(rng, &signatures).map_err(|l| FrostError::InvalidProofOfKnowledge(l))?;
+  schnorr::batch_verify(rng, &signatures).map_err(|l| {
+    trace_event!(warn, participant = l, "invalid proof of knowledge");
+    FrostError::InvalidProofOfKnowledge(l)
+  })?;
+  trace_event!(debug, proofs = signatures.len(), "verified proofs of knowledge");
 
   Ok(commitments)
 }
 
-fn polynomial<F: PrimeField>(
+pub(crate) fn polynomial<F: PrimeField>(
   coefficients: &[F],
   l: u16
 ) -> F {
@@ -149,24 +253,32 @@ fn polynomial<F: PrimeField>(
 // counterparty to receive
 fn generate_key_r2<R: RngCore + CryptoRng, C: Curve>(
   rng: &mut R,
+  variant: KeyGenVariant,
   params: &MultisigParams,
   context: &str,
   coefficients: Vec<C::F>,
-  our_commitments: Vec<u8>,
-  commitments: HashMap<u16, Vec<u8>>,
-) -> Result<(C::F, HashMap<u16, Vec<C::G>>, HashMap<u16, Vec<u8>>), FrostError> {
-  let commitments = verify_r1::<R, C>(rng, params, context, our_commitments, commitments)?;
+  our_commitments: Commitments<C>,
+  commitments: HashMap<u16, Commitments<C>>,
+) -> Result<(C::F, HashMap<u16, Vec<C::G>>, HashMap<u16, SecretShare<C>>), FrostError> {
+  let commitments = verify_r1::<R, C>(
+    rng,
+    variant,
+    params,
+    context,
+    our_commitments,
+    commitments
+  )?;
 
   // Step 1: Generate secret shares for all other parties
   let mut res = HashMap::new();
   for l in 1 ..= params.n() {
-    // Don't insert our own shares to the byte buffer which is meant to be sent around
+    // Don't insert our own shares to the messages which are meant to be sent around
     // An app developer could accidentally send it. Best to keep this black boxed
     if l == params.i() {
       continue;
     }
 
-    res.insert(l, C::F_to_bytes(&polynomial(&coefficients, l)));
+    res.insert(l, SecretShare(polynomial(&coefficients, l)));
   }
 
   // Calculate our own share
@@ -188,77 +300,77 @@ fn generate_key_r2<R: RngCore + CryptoRng, C: Curve>(
 /// issue, yet simply confirming protocol completion without issue is enough to confirm the same
 /// key was generated as long as a lack of duplicated commitments was also confirmed when they were
 /// broadcasted initially
-fn complete_r2<R: RngCore + CryptoRng, C: Curve>(
+pub(crate) fn complete_r2<R: RngCore + CryptoRng, C: Curve>(
   rng: &mut R,
   params: MultisigParams,
   mut secret_share: C::F,
   commitments: HashMap<u16, Vec<C::G>>,
-  // Vec to preserve ownership
-  mut serialized: HashMap<u16, Vec<u8>>,
+  mut shares: HashMap<u16, SecretShare<C>>,
 ) -> Result<MultisigKeys<C>, FrostError> {
   validate_map(
-    &mut serialized,
+    &mut shares,
     &(1 ..= params.n()).into_iter().collect::<Vec<_>>(),
-    (params.i(), C::F_to_bytes(&secret_share))
+    (params.i(), SecretShare(secret_share))
   )?;
 
-  // Step 2. Verify each share
-  let mut shares = HashMap::new();
-  for (l, share) in serialized {
-    shares.insert(l, C::F_from_slice(&share).map_err(|_| FrostError::InvalidShare(l))?);
-  }
-
   // Calculate the exponent for a given participant and apply it to a series of commitments
   // Initially used with the actual commitments to verify the secret share, later used with stripes
   // to generate the verification shares
-  let exponential = |i: u16, values: &[_]| {
+  let exponents = |i: u16| {
     let i = C::F::from(i.into());
     let mut res = Vec::with_capacity(params.t().into());
     (0 .. usize::from(params.t())).into_iter().fold(
       C::F::one(),
-      |exp, l| {
-        res.push((exp, values[l]));
+      |exp, _| {
+        res.push(exp);
         exp * i
       }
     );
     res
   };
+  let exponential = |i: u16, values: &[_]| {
+    exponents(i).into_iter().zip(values.iter().cloned()).collect::<Vec<_>>()
+  };
 
-  let mut batch = BatchVerifier::new(shares.len(), C::LITTLE_ENDIAN);
-  for (l, share) in &shares {
-    if *l == params.i() {
-      continue;
-    }
-
+  let mut batch = BatchVerifier::new(shares.len());
+  // This can be insecurely linearized from n * t to just n using the below sums for a given
+  // stripe. Doing so uses naive addition which is subject to malleability. The only way to
+  // ensure that malleability isn't present is to use this n * t algorithm, which runs
+  // per sender and not as an aggregate of all senders, which also enables blame
+  // Shares are queued in index order so the batch, and therefore who's blamed, is reproducible
+  let others = (1 ..= params.n()).filter(|l| *l != params.i()).collect::<Vec<_>>();
+  for (l, share, values) in iter!(others).map(|l| {
+    let share = shares[&l].0;
+    let mut values = exponential(params.i, &commitments[&l]);
+    values.push((-share, C::generator()));
+    (l, share, values)
+  }).collect::<Vec<_>>() {
     secret_share += share;
-
-    // This can be insecurely linearized from n * t to just n using the below sums for a given
-    // stripe. Doing so uses naive addition which is subject to malleability. The only way to
-    // ensure that malleability isn't present is to use this n * t algorithm, which runs
-    // per sender and not as an aggregate of all senders, which also enables blame
-    let mut values = exponential(params.i, &commitments[l]);
-    values.push((-*share, C::GENERATOR));
-    batch.queue(rng, *l, values);
+    batch.queue(rng, l, values);
   }
-  batch.verify_with_vartime_blame().map_err(|l| FrostError::InvalidCommitment(l))?;
+  batch.verify_with_vartime_blame().map_err(|l| {
+    trace_event!(warn, participant = l, "secret share doesn't match commitments");
+    FrostError::InvalidCommitment(l)
+  })?;
+  trace_event!(debug, shares = shares.len() - 1, "verified secret shares");
 
   // Stripe commitments per t and sum them in advance. Calculating verification shares relies on
   // these sums so preprocessing them is a massive speedup
-  // If these weren't just sums, yet the tables used in multiexp, this would be further optimized
-  // As of right now, each multiexp will regenerate them
+  // The stripes are further precomputed into multiexp tables, which are then reused by every
+  // verification share's multiexp
   let mut stripes = Vec::with_capacity(usize::from(params.t()));
   for t in 0 .. usize::from(params.t()) {
     stripes.push(commitments.values().map(|commitments| commitments[t]).sum());
   }
+  let tables = Precomputed::new(&stripes);
 
   // Calculate each user's verification share
-  let mut verification_shares = HashMap::new();
-  for i in 1 ..= params.n() {
-    verification_shares.insert(i, multiexp_vartime(&exponential(i, &stripes), C::LITTLE_ENDIAN));
-  }
-  debug_assert_eq!(C::GENERATOR_TABLE * secret_share, verification_shares[&params.i()]);
+  let verification_shares = iter!(1 ..= params.n()).map(
+    |i| (i, tables.multiexp_vartime(&exponents(i)))
+  ).collect::<HashMap<_, _>>();
+  debug_assert_eq!(C::generator_table() * secret_share, verification_shares[&params.i()]);
 
-  // TODO: Clear serialized and shares
+  // TODO: Clear shares
 
   Ok(
     MultisigKeys {
@@ -266,26 +378,73 @@ fn complete_r2<R: RngCore + CryptoRng, C: Curve>(
       secret_share,
       group_key: stripes[0],
       verification_shares,
-      offset: None
+      offset: None,
+      context: None
     }
   )
 }
 
+/// Split an existing private key into keys for a multisig via a local, trusted dealer
+/// The resulting group key is the public key for the specified private key, enabling funds held by
+/// a single key to move under the multisig's control without moving on-chain. The dealer learns
+/// every share, and the private key must still be deleted after the keys are distributed
+pub fn deal<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  t: u16,
+  n: u16,
+  private_key: C::F
+) -> Result<HashMap<u16, MultisigKeys<C>>, FrostError> {
+  // Validate the parameters
+  MultisigParams::new(t, n, 1)?;
+
+  let mut coefficients = Vec::with_capacity(usize::from(t));
+  coefficients.push(private_key);
+  for _ in 1 .. t {
+    coefficients.push(C::F::random(&mut *rng));
+  }
+
+  let shares = (1 ..= n).map(|l| (l, polynomial(&coefficients, l))).collect::<HashMap<_, _>>();
+  let verification_shares = shares.iter().map(
+    |(l, share)| (*l, C::generator_table() * *share)
+  ).collect::<HashMap<_, _>>();
+  let group_key = C::generator_table() * private_key;
+
+  // TODO: Zero out the coefficients
+  let mut keys = HashMap::new();
+  for (l, share) in shares {
+    keys.insert(
+      l,
+      MultisigKeys {
+        params: MultisigParams::new(t, n, l)?,
+        secret_share: share,
+        group_key,
+        verification_shares: verification_shares.clone(),
+        offset: None,
+        context: None
+      }
+    );
+  }
+  Ok(keys)
+}
+
 pub struct KeyGenMachine<C: Curve> {
+  variant: KeyGenVariant,
   params: MultisigParams,
   context: String,
   _curve: PhantomData<C>,
 }
 
 pub struct SecretShareMachine<C: Curve> {
+  variant: KeyGenVariant,
   params: MultisigParams,
   context: String,
   coefficients: Vec<C::F>,
-  our_commitments: Vec<u8>,
+  our_commitments: Commitments<C>,
 }
 
 pub struct KeyMachine<C: Curve> {
   params: MultisigParams,
+  pub(crate) context: String,
   secret: C::F,
   commitments: HashMap<u16, Vec<C::G>>,
 }
@@ -293,65 +452,183 @@ pub struct KeyMachine<C: Curve> {
 impl<C: Curve> KeyGenMachine<C> {
   /// Creates a new machine to generate a key for the specified curve in the specified multisig
   // The context string must be unique among multisigs
-  pub fn new(params: MultisigParams, context: String) -> KeyGenMachine<C> {
-    KeyGenMachine { params, context, _curve: PhantomData }
+  pub fn new(
+    params: MultisigParams,
+    context: String,
+    variant: KeyGenVariant
+  ) -> KeyGenMachine<C> {
+    KeyGenMachine { variant, params, context, _curve: PhantomData }
   }
 
   /// Start generating a key according to the FROST DKG spec
-  /// Returns a serialized list of commitments to be sent to all parties over an authenticated
-  /// channel. If any party submits multiple sets of commitments, they MUST be treated as malicious
+  /// Returns the commitments to be sent to all parties over an authenticated channel. If any party
+  /// submits multiple sets of commitments, they MUST be treated as malicious
   pub fn generate_coefficients<R: RngCore + CryptoRng>(
     self,
     rng: &mut R
-  ) -> (SecretShareMachine<C>, Vec<u8>) {
-    let (coefficients, serialized) = generate_key_r1::<R, C>(rng, &self.params, &self.context);
+  ) -> (SecretShareMachine<C>, Commitments<C>) {
+    trace_span!("key_gen", round = "commitments", i = self.params.i());
+    let (coefficients, commitments) = generate_key_r1::<R, C>(
+      rng,
+      self.variant,
+      &self.params,
+      &self.context
+    );
+    trace_event!(debug, "generated commitments");
     (
       SecretShareMachine {
+        variant: self.variant,
         params: self.params,
         context: self.context,
         coefficients,
-        our_commitments: serialized.clone()
+        our_commitments: commitments.clone()
       },
-      serialized,
+      commitments,
     )
   }
 }
 
 impl<C: Curve> SecretShareMachine<C> {
+  /// Start the deadline for the messages this machine awaits, everyone else's commitments
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    let i = self.params.i();
+    Round::new((1 ..= self.params.n()).filter(|l| *l != i).collect(), timeout)
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// Length of the messages this machine awaits, everyone else's commitments
+  pub fn message_len(&self) -> usize {
+    commitments_len::<C>(&self.params)
+  }
+
+  /// Read another participant's commitments
+  pub fn read_commitments<R: Read>(&self, reader: &mut R) -> io::Result<Commitments<C>> {
+    Commitments::read(reader, self.params)
+  }
+
   /// Continue generating a key
-  /// Takes in everyone else's commitments, which are expected to be in a Vec where participant
-  /// index = Vec index. An empty vector is expected at index 0 to allow for this. An empty vector
-  /// is also expected at index i which is locally handled. Returns a byte vector representing a
-  /// secret share for each other participant which should be encrypted before sending
+  /// Takes in everyone else's commitments, keyed by participant index. Returns a secret share for
+  /// each other participant which should be encrypted before sending
   pub fn generate_secret_shares<R: RngCore + CryptoRng>(
     self,
     rng: &mut R,
-    commitments: HashMap<u16, Vec<u8>>,
-  ) -> Result<(KeyMachine<C>, HashMap<u16, Vec<u8>>), FrostError> {
+    commitments: HashMap<u16, Commitments<C>>,
+  ) -> Result<(KeyMachine<C>, HashMap<u16, SecretShare<C>>), FrostError> {
+    trace_span!("key_gen", round = "secret_shares", i = self.params.i());
+    trace_event!(debug, commitments = commitments.len(), "received commitments");
     let (secret, commitments, shares) = generate_key_r2::<R, C>(
       rng,
+      self.variant,
       &self.params,
       &self.context,
       self.coefficients,
       self.our_commitments,
       commitments,
     )?;
-    Ok((KeyMachine { params: self.params, secret, commitments }, shares))
+    trace_event!(debug, shares = shares.len(), "generated secret shares");
+    Ok((KeyMachine { params: self.params, context: self.context, secret, commitments }, shares))
+  }
+
+  /// Verifiably encrypt the secret share for every participant, including ourselves, to an
+  /// escrow's public key. These can be published, and verified by anyone, alongside the round 1
+  /// commitments, enabling the escrow to recover any participant's key by summing the shares it
+  /// was sent for them
+  pub fn escrow_secret_shares<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    escrow_key: C::G
+  ) -> HashMap<u16, EscrowedShare<C>> {
+    (1 ..= self.params.n()).map(|l| (
+      l,
+      EscrowedShare::new(&mut *rng, &self.context, escrow_key, l, polynomial(&self.coefficients, l))
+    )).collect()
   }
 }
 
 impl<C: Curve> KeyMachine<C> {
+  /// Start the deadline for the messages this machine awaits, everyone else's secret shares
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    let i = self.params.i();
+    Round::new((1 ..= self.params.n()).filter(|l| *l != i).collect(), timeout)
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  /// Length of the messages this machine awaits, everyone else's secret shares
+  pub fn message_len(&self) -> usize {
+    share_len::<C>()
+  }
+
+  /// Digest of this key generation session, covering the context, parameters, every
+  /// participant's commitments, and the resulting group key
+  /// Once complete succeeds, participants should compare this over a secondary channel before
+  /// trusting the key, as matching digests confirm everyone completed with the same view
+  pub fn session_hash(&self) -> Vec<u8> {
+    let mut transcript = C::hash_msg(self.context.as_bytes());
+    transcript.extend(self.params.t().to_be_bytes());
+    transcript.extend(self.params.n().to_be_bytes());
+    for l in 1 ..= self.params.n() {
+      transcript.extend(l.to_be_bytes());
+      for commitment in &self.commitments[&l] {
+        transcript.extend(C::G_to_bytes(commitment));
+      }
+    }
+    transcript.extend(
+      C::G_to_bytes(&self.commitments.values().map(|commitments| commitments[0]).sum())
+    );
+    C::F_to_bytes(&C::hash_to_F(b"FROST DKG Session Hash", &transcript))
+  }
+
+  /// Verify everyone else's session hashes match ours, as an optional echo-broadcast round
+  /// Without a broadcast channel, a malicious participant can send different commitments to
+  /// different participants. Sending session_hash to everyone once secret shares were generated,
+  /// and verifying the received hashes before completing, detects this. As either the sender of
+  /// the commitments or the participant echoing them may be malicious, the blamed participant is
+  /// solely the one whose view differs from ours
+  pub fn verify_session_hashes(&self, mut hashes: HashMap<u16, Vec<u8>>) -> Result<(), FrostError> {
+    let ours = self.session_hash();
+    validate_map(
+      &mut hashes,
+      &(1 ..= self.params.n()).collect::<Vec<_>>(),
+      (self.params.i(), ours.clone())
+    )?;
+    for l in 1 ..= self.params.n() {
+      if hashes[&l] != ours {
+        trace_event!(warn, participant = l, "inconsistent commitments");
+        Err(FrostError::InconsistentCommitments(l))?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Read a secret share sent to us by another participant
+  pub fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SecretShare<C>> {
+    SecretShare::read(reader)
+  }
+
   /// Complete key generation
-  /// Takes in everyone elses' shares submitted to us as a Vec, expecting participant index =
-  /// Vec index with an empty vector at index 0 and index i. Returns a byte vector representing the
-  /// group's public key, while setting a valid secret share inside the machine. > t participants
-  /// must report completion without issue before this key can be considered usable, yet you should
-  /// wait for all participants to report as such
+  /// Takes in everyone elses' shares submitted to us, keyed by participant index. Returns the
+  /// keys for this multisig. > t participants must report completion without issue before this
+  /// key can be considered usable, yet you should wait for all participants to report as such. The
+  /// session hash is intended for such reports
   pub fn complete<R: RngCore + CryptoRng>(
     self,
     rng: &mut R,
-    shares: HashMap<u16, Vec<u8>>,
+    shares: HashMap<u16, SecretShare<C>>,
   ) -> Result<MultisigKeys<C>, FrostError> {
-    complete_r2(rng, self.params, self.secret, self.commitments, shares)
+    trace_span!("key_gen", round = "complete", i = self.params.i());
+    trace_event!(debug, shares = shares.len(), "received secret shares");
+    let keys = complete_r2(rng, self.params, self.secret, self.commitments, shares)?;
+    trace_event!(
+      info,
+      group_key = hex::encode(C::G_to_bytes(&keys.group_key)).as_str(),
+      "completed key generation"
+    );
+    Ok(keys)
   }
 }
@@ -0,0 +1,151 @@
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::Zeroizing;
+
+use argon2::{Algorithm, Version, Params, Argon2};
+use chacha20poly1305::{aead::{Aead, KeyInit, Payload}, ChaCha20Poly1305, Key, Nonce};
+
+use crate::{curve::Curve, FrostError, DeserializeError, MultisigKeys};
+
+/// Version of the keystore format written by this library
+pub const KEYSTORE_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+// Version, the three Argon2 parameters, the salt, and the nonce
+const HEADER_LEN: usize = 1 + (3 * 4) + SALT_LEN + NONCE_LEN;
+
+/// Argon2id parameters used to derive the encryption key from a passphrase
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeystoreParams {
+  m_cost: u32,
+  t_cost: u32,
+  p_cost: u32,
+}
+
+impl Default for KeystoreParams {
+  /// Argon2's recommended defaults, 19 MiB of memory over 2 iterations
+  fn default() -> KeystoreParams {
+    KeystoreParams {
+      m_cost: Params::DEFAULT_M_COST,
+      t_cost: Params::DEFAULT_T_COST,
+      p_cost: Params::DEFAULT_P_COST
+    }
+  }
+}
+
+impl KeystoreParams {
+  /// Memory cost in KiB, iteration count, and parallelism, if Argon2 considers them valid
+  pub fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> Option<KeystoreParams> {
+    Params::new(m_cost, t_cost, p_cost, None).ok()?;
+    Some(KeystoreParams { m_cost, t_cost, p_cost })
+  }
+
+  fn derive(&self, passphrase: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, FrostError> {
+    // Parameters read from a keystore may be invalid, which is indistinguishable from corruption
+    let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+      .map_err(|_| FrostError::DeserializeError(DeserializeError::DecryptionFailed))?;
+    let mut key = Zeroizing::new([0; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+      .hash_password_into(passphrase, salt, key.as_mut())
+      .map_err(|_| FrostError::DeserializeError(DeserializeError::DecryptionFailed))?;
+    Ok(key)
+  }
+}
+
+fn encrypt_raw<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  params: KeystoreParams,
+  passphrase: &[u8],
+  plaintext: &[u8]
+) -> Vec<u8> {
+  let mut salt = [0; SALT_LEN];
+  rng.fill_bytes(&mut salt);
+  let mut nonce = [0; NONCE_LEN];
+  rng.fill_bytes(&mut nonce);
+
+  let mut encrypted = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+  encrypted.push(KEYSTORE_VERSION);
+  encrypted.extend(params.m_cost.to_be_bytes());
+  encrypted.extend(params.t_cost.to_be_bytes());
+  encrypted.extend(params.p_cost.to_be_bytes());
+  encrypted.extend(salt);
+  encrypted.extend(nonce);
+
+  // KeystoreParams are validated on construction, so derivation can't fail
+  let key = params.derive(passphrase, &salt).unwrap();
+  // The header is authenticated as associated data
+  let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref())).encrypt(
+    Nonce::from_slice(&nonce),
+    Payload { msg: plaintext, aad: &encrypted }
+  ).unwrap();
+  encrypted.extend(ciphertext);
+  encrypted
+}
+
+fn decrypt_raw(encrypted: &[u8], passphrase: &[u8]) -> Result<Zeroizing<Vec<u8>>, FrostError> {
+  if encrypted.is_empty() {
+    Err(FrostError::DeserializeError(DeserializeError::Truncated))?;
+  }
+  if encrypted[0] != KEYSTORE_VERSION {
+    Err(FrostError::DeserializeError(DeserializeError::UnsupportedVersion(encrypted[0])))?;
+  }
+  if encrypted.len() < HEADER_LEN {
+    Err(FrostError::DeserializeError(DeserializeError::Truncated))?;
+  }
+
+  let cost = |i: usize| u32::from_be_bytes(
+    encrypted[(1 + (i * 4)) .. (5 + (i * 4))].try_into().unwrap()
+  );
+  let params = KeystoreParams { m_cost: cost(0), t_cost: cost(1), p_cost: cost(2) };
+  let salt = &encrypted[13 .. (13 + SALT_LEN)];
+  let nonce = &encrypted[(13 + SALT_LEN) .. HEADER_LEN];
+
+  let key = params.derive(passphrase, salt)?;
+  ChaCha20Poly1305::new(Key::from_slice(key.as_ref())).decrypt(
+    Nonce::from_slice(nonce),
+    Payload { msg: &encrypted[HEADER_LEN ..], aad: &encrypted[.. HEADER_LEN] }
+  )
+    .map(Zeroizing::new)
+    .map_err(|_| FrostError::DeserializeError(DeserializeError::DecryptionFailed))
+}
+
+/// Encrypt keys under a passphrase, using the default KeystoreParams
+pub fn encrypt<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  keys: &MultisigKeys<C>,
+  passphrase: &[u8]
+) -> Vec<u8> {
+  encrypt_with_params(rng, KeystoreParams::default(), keys, passphrase)
+}
+
+/// Encrypt keys under a passphrase, using the specified KeystoreParams
+/// Any offset applied to the keys isn't stored, matching MultisigKeys::serialize
+pub fn encrypt_with_params<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  params: KeystoreParams,
+  keys: &MultisigKeys<C>,
+  passphrase: &[u8]
+) -> Vec<u8> {
+  encrypt_raw(rng, params, passphrase, &Zeroizing::new(keys.serialize()))
+}
+
+/// Decrypt keys encrypted by encrypt or encrypt_with_params
+pub fn decrypt<C: Curve>(
+  encrypted: &[u8],
+  passphrase: &[u8]
+) -> Result<MultisigKeys<C>, FrostError> {
+  MultisigKeys::deserialize(&decrypt_raw(encrypted, passphrase)?)
+}
+
+/// Re-encrypt keys under a new passphrase, with a fresh salt and nonce, using the specified
+/// KeystoreParams
+pub fn change_passphrase<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  encrypted: &[u8],
+  old: &[u8],
+  new: &[u8],
+  params: KeystoreParams
+) -> Result<Vec<u8>, FrostError> {
+  Ok(encrypt_raw(rng, params, new, &decrypt_raw(encrypted, old)?))
+}
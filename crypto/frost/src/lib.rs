@@ -1,17 +1,76 @@
 use core::fmt::Debug;
-use std::collections::HashMap;
+use std::{io::{self, Read, Write}, collections::HashMap};
 
 use thiserror::Error;
 
-use group::ff::{Field, PrimeField};
+use group::{ff::{Field, PrimeField, BatchInverter}, Group};
+
+// Iterate over a collection, in parallel when the parallel feature is enabled
+// Users must import rayon's prelude under the same feature
+#[cfg(feature = "parallel")]
+macro_rules! iter {
+  ($collection: expr) => { rayon::iter::IntoParallelIterator::into_par_iter($collection) }
+}
+#[cfg(not(feature = "parallel"))]
+macro_rules! iter {
+  ($collection: expr) => { IntoIterator::into_iter($collection) }
+}
+
+// Emit a tracing event, or enter a span until the end of the current scope, when the tracing
+// feature is enabled. Both compile to nothing otherwise
+// Only indices, counts, and outcomes may be recorded. Secrets must never be passed
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+  ($level: ident, $($args: tt)*) => { tracing::$level!($($args)*) }
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+  ($level: ident, $($args: tt)*) => {}
+}
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+  ($($args: tt)*) => { let _span = tracing::debug_span!($($args)*).entered(); }
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+  ($($args: tt)*) => {}
+}
 
 mod schnorr;
+#[cfg(feature = "bech32")]
+mod bech32;
 
 pub mod curve;
 use curve::Curve;
 pub mod key_gen;
+pub mod compact;
+pub mod header;
+pub mod escrow;
+pub mod complaint;
 pub mod algorithm;
 pub mod sign;
+pub mod weighted;
+pub mod nested;
+pub mod coordinator;
+pub mod round;
+pub mod backup;
+pub mod migration;
+pub mod sessions;
+pub mod bundle;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "lowmem")]
+pub mod lowmem;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "bls12_381")]
+pub mod bls;
+#[cfg(feature = "jubjub")]
+pub mod redjubjub;
+#[cfg(feature = "sr25519")]
+pub mod schnorrkel;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
 
 pub mod tests;
 
@@ -64,24 +123,78 @@ pub enum FrostError {
   InvalidRequiredQuantity(u16, u16),
   #[error("invalid participant index (0 < index <= {0}, yet index is {1})")]
   InvalidParticipantIndex(u16, u16),
+  #[error("participant had a weight of 0 ({0})")]
+  ZeroWeight(u16),
 
-  #[error("invalid signing set ({0})")]
-  InvalidSigningSet(String),
+  #[error("not enough signers (required {0}, got {1})")]
+  NotEnoughSigners(u16, usize),
+  #[error("participant isn't included in the signing set ({0})")]
+  NotIncluded(u16),
   #[error("invalid participant quantity (expected {0}, got {1})")]
   InvalidParticipantQuantity(usize, usize),
   #[error("duplicated participant index ({0})")]
   DuplicatedIndex(usize),
   #[error("missing participant {0}")]
   MissingParticipant(u16),
+  #[error("round timed out (missing {missing:?})")]
+  Timeout { missing: Vec<u16> },
   #[error("invalid commitment (participant {0})")]
   InvalidCommitment(u16),
+  #[error("participant received different commitments ({0})")]
+  InconsistentCommitments(u16),
   #[error("invalid proof of knowledge (participant {0})")]
   InvalidProofOfKnowledge(u16),
   #[error("invalid share (participant {0})")]
   InvalidShare(u16),
+  #[error("nested group key doesn't match its verification share (participant {0})")]
+  InvalidNestedKey(u16),
+
+  #[error("every share was valid yet the signature was invalid")]
+  InvalidSignature,
+  #[error("a message was provided to a machine which generates its own")]
+  UnexpectedMessage,
+  #[error("the preprocess was already used to sign")]
+  ReusedPreprocess,
+  #[error("the session ID was already used")]
+  DuplicatedSession,
+  #[error("no session with this ID is at this round")]
+  UnknownSession,
+  #[error("too many sessions (max {0})")]
+  TooManySessions(usize),
+  #[error("the curve was already included")]
+  DuplicatedCurve,
+  #[error("the curve isn't part of this bundle")]
+  UnknownCurve,
+  #[error("a curve's key generation wasn't completed")]
+  MissingCurve,
+  #[error("keys have different parameters than the bundle")]
+  MismatchedParameters,
+  #[error("buffer was too small (required {0}, got {1})")]
+  BufferTooSmall(usize, usize),
+
+  #[error("couldn't deserialize ({0})")]
+  DeserializeError(DeserializeError),
+}
 
-  #[error("internal error ({0})")]
-  InternalError(String),
+/// Errors from deserializing keys and other persisted data
+#[derive(Clone, Error, Debug)]
+pub enum DeserializeError {
+  #[error("serialization was truncated")]
+  Truncated,
+  #[error("invalid length for serialization (expected {0}, got {1})")]
+  InvalidLength(usize, usize),
+  #[error("curve is distinct between serialization and deserialization")]
+  CurveMismatch,
+  #[error("invalid scalar")]
+  InvalidScalar,
+  #[error("invalid point")]
+  InvalidPoint,
+  #[error("invalid parameters (required {0}, participants {1}, index {2})")]
+  InvalidParameters(u16, u16, u16),
+  #[error("couldn't decrypt the serialization")]
+  DecryptionFailed,
+  #[error("unsupported serialization version ({0})")]
+  UnsupportedVersion(u8),
 }
 
 // View of keys passable to algorithm implementations
@@ -98,6 +211,7 @@ impl<C: Curve> MultisigView<C> {
     self.group_key
   }
 
+  /// The signing set, sorted and without duplicates
   pub fn included(&self) -> Vec<u16> {
     self.included.clone()
   }
@@ -109,13 +223,36 @@ impl<C: Curve> MultisigView<C> {
   pub fn verification_share(&self, l: u16) -> C::G {
     self.verification_shares[&l]
   }
+
+  // View for a signing set without a secret share, as needed by parties who solely coordinate
+  // The secret share is solely the signing set's share of the offset
+  pub(crate) fn public(
+    group_key: C::G,
+    included: Vec<u16>,
+    verification_shares: &HashMap<u16, C::G>,
+    offset: Option<C::F>
+  ) -> MultisigView<C> {
+    let offset = offset.unwrap_or(C::F::zero());
+    let offset_share = offset * C::F::from(included.len().try_into().unwrap()).invert().unwrap();
+
+    let indices = verification_shares.keys().copied().collect::<Vec<_>>();
+    let coefficients = lagrange_batch::<C::F>(&indices, &included);
+    MultisigView {
+      group_key,
+      secret_share: offset_share,
+      verification_shares: indices.iter().zip(coefficients).map(
+        |(l, coefficient)| (
+          *l,
+          (verification_shares[l] * coefficient) + (C::generator_table() * offset_share)
+        )
+      ).collect(),
+      included,
+    }
+  }
 }
 
-/// Calculate the lagrange coefficient for a signing set
-pub fn lagrange<F: PrimeField>(
-  i: u16,
-  included: &[u16],
-) -> F {
+// Numerator and denominator of the lagrange coefficient for a signing set
+fn lagrange_fraction<F: PrimeField>(i: u16, included: &[u16]) -> (F, F) {
   let mut num = F::one();
   let mut denom = F::one();
   for l in included {
@@ -127,12 +264,31 @@ pub fn lagrange<F: PrimeField>(
     num *= share;
     denom *= share - F::from(u64::try_from(i).unwrap());
   }
+  (num, denom)
+}
 
+/// Calculate the lagrange coefficient for a signing set
+pub fn lagrange<F: PrimeField>(
+  i: u16,
+  included: &[u16],
+) -> F {
+  let (num, denom) = lagrange_fraction::<F>(i, included);
   // Safe as this will only be 0 if we're part of the above loop
   // (which we have an if case to avoid)
   num * denom.invert().unwrap()
 }
 
+/// Calculate the lagrange coefficients of multiple participants for a signing set
+/// Performs a single inversion, via Montgomery's trick, instead of one per participant
+pub fn lagrange_batch<F: PrimeField>(indices: &[u16], included: &[u16]) -> Vec<F> {
+  let (nums, mut denoms): (Vec<F>, Vec<F>) = indices.iter().map(
+    |i| lagrange_fraction::<F>(*i, included)
+  ).unzip();
+  let mut scratch = vec![F::one(); denoms.len()];
+  BatchInverter::invert_with_external_scratch(&mut denoms, &mut scratch);
+  nums.iter().zip(denoms.iter()).map(|(num, denom)| *num * denom).collect()
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct MultisigKeys<C: Curve> {
   /// Multisig Parameters
@@ -147,9 +303,65 @@ pub struct MultisigKeys<C: Curve> {
 
   /// Offset applied to these keys
   offset: Option<C::F>,
+  /// Deployment context mixed into signing
+  context: Option<Vec<u8>>,
 }
 
 impl<C: Curve> MultisigKeys<C> {
+  /// Build keys from a secret share and every participant's verification share, as produced by an
+  /// external DKG or dealer
+  /// The secret share must match our verification share and the verification shares must lie on a
+  /// single polynomial of degree t - 1, whose evaluation at 0 is taken as the group key
+  pub fn new(
+    params: MultisigParams,
+    secret_share: C::F,
+    verification_shares: HashMap<u16, C::G>
+  ) -> Result<MultisigKeys<C>, FrostError> {
+    let n = usize::from(params.n);
+    if verification_shares.len() != n {
+      Err(FrostError::InvalidParticipantQuantity(n, verification_shares.len()))?;
+    }
+    for l in 1 ..= params.n {
+      if !verification_shares.contains_key(&l) {
+        Err(FrostError::MissingParticipant(l))?;
+      }
+    }
+
+    if (C::generator_table() * secret_share) != verification_shares[&params.i] {
+      Err(FrostError::InvalidShare(params.i))?;
+    }
+
+    let interpolate = |included: &[u16]| included.iter().zip(
+      lagrange_batch::<C::F>(included, included)
+    ).fold(
+      C::G::identity(),
+      |accum, (l, coefficient)| accum + (verification_shares[l] * coefficient)
+    );
+
+    // Interpolate the group key from the first t shares, then check every other share produces
+    // the same group key when swapped in for the t-th. This holds if and only if every share lies
+    // on the polynomial defined by the first t
+    let mut included = (1 ..= params.t).collect::<Vec<_>>();
+    let group_key = interpolate(&included);
+    for l in (params.t + 1) ..= params.n {
+      *included.last_mut().unwrap() = l;
+      if interpolate(&included) != group_key {
+        Err(FrostError::InvalidShare(l))?;
+      }
+    }
+
+    Ok(
+      MultisigKeys {
+        params,
+        secret_share,
+        group_key,
+        verification_shares,
+        offset: None,
+        context: None
+      }
+    )
+  }
+
   /// Offset the keys by a given scalar to allow for account and privacy schemes
   /// This offset is ephemeral and will not be included when these keys are serialized
   /// Keys offset multiple times will form a new offset of their sum
@@ -160,10 +372,27 @@ impl<C: Curve> MultisigKeys<C> {
     // Enables schemes like Monero's subaddresses which have a per-subaddress offset and then a
     // one-time-key offset
     res.offset = Some(offset + res.offset.unwrap_or(C::F::zero()));
-    res.group_key += C::GENERATOR_TABLE * offset;
+    res.group_key += C::generator_table() * offset;
+    res
+  }
+
+  /// Mix a deployment-level context into the binding factor of every signing session, so
+  /// preprocesses and shares from one deployment are never valid in another
+  /// Every signer, and any coordinator, must use the same context. The resulting signatures are
+  /// still verified as usual, so deployments should also domain separate their messages
+  /// This context is ephemeral and will not be included when these keys are serialized
+  /// Not IETF compliant
+  pub fn with_context(&self, context: &[u8]) -> MultisigKeys<C> {
+    let mut res = self.clone();
+    res.context = Some(context.to_vec());
     res
   }
 
+  /// The deployment context mixed into signing, if one was set
+  pub fn context(&self) -> Option<&[u8]> {
+    self.context.as_deref()
+  }
+
   pub fn params(&self) -> MultisigParams {
     self.params
   }
@@ -176,30 +405,37 @@ impl<C: Curve> MultisigKeys<C> {
     self.group_key
   }
 
-  fn verification_shares(&self) -> HashMap<u16, C::G> {
+  /// Every participant's verification share, their secret share multiplied by the generator,
+  /// keyed by participant index
+  pub fn verification_shares(&self) -> HashMap<u16, C::G> {
     self.verification_shares.clone()
   }
 
+  /// The specified participant's verification share, if they're a participant
+  pub fn verification_share(&self, l: u16) -> Option<C::G> {
+    self.verification_shares.get(&l).copied()
+  }
+
+  /// View of these keys for the specified signing set, which must include us
+  /// The signing set is canonicalized, being sorted, and rejected if it has duplicated or
+  /// out-of-range indices
   pub fn view(&self, included: &[u16]) -> Result<MultisigView<C>, FrostError> {
-    if (included.len() < self.params.t.into()) || (usize::from(self.params.n) < included.len()) {
-      Err(FrostError::InvalidSigningSet("invalid amount of participants included".to_string()))?;
+    let included = validate_included(self.params, included)?;
+    if included.len() < self.params.t.into() {
+      Err(FrostError::NotEnoughSigners(self.params.t, included.len()))?;
+    }
+    if usize::from(self.params.n) < included.len() {
+      Err(FrostError::TooManyParticipants(included.len(), self.params.n))?;
     }
 
-    let secret_share = self.secret_share * lagrange::<C::F>(self.params.i, &included);
-    let offset = self.offset.unwrap_or(C::F::zero());
-    let offset_share = offset * C::F::from(included.len().try_into().unwrap()).invert().unwrap();
-
-    Ok(MultisigView {
-      group_key: self.group_key,
-      secret_share: secret_share + offset_share,
-      verification_shares: self.verification_shares.iter().map(
-        |(l, share)| (
-          *l,
-          (*share * lagrange::<C::F>(*l, &included)) + (C::GENERATOR_TABLE * offset_share)
-        )
-      ).collect(),
-      included: included.to_vec(),
-    })
+    let mut view = MultisigView::public(
+      self.group_key,
+      included,
+      &self.verification_shares,
+      self.offset
+    );
+    view.secret_share += self.secret_share * lagrange::<C::F>(self.params.i, &view.included);
+    Ok(view)
   }
 
   pub fn serialized_len(n: u16) -> usize {
@@ -227,18 +463,10 @@ impl<C: Curve> MultisigKeys<C> {
     let mut cursor = start.len();
 
     if serialized.len() < (cursor + 4) {
-      Err(
-        FrostError::InternalError(
-          "MultisigKeys serialization is missing its curve/participant quantities".to_string()
-        )
-      )?;
+      Err(FrostError::DeserializeError(DeserializeError::Truncated))?;
     }
     if &start != &serialized[.. cursor] {
-      Err(
-        FrostError::InternalError(
-          "curve is distinct between serialization and deserialization".to_string()
-        )
-      )?;
+      Err(FrostError::DeserializeError(DeserializeError::CurveMismatch))?;
     }
 
     let t = u16::from_be_bytes(serialized[cursor .. (cursor + 2)].try_into().unwrap());
@@ -247,17 +475,21 @@ impl<C: Curve> MultisigKeys<C> {
     let n = u16::from_be_bytes(serialized[cursor .. (cursor + 2)].try_into().unwrap());
     cursor += 2;
     if serialized.len() != MultisigKeys::<C>::serialized_len(n) {
-      Err(FrostError::InternalError("incorrect serialization length".to_string()))?;
+      Err(
+        FrostError::DeserializeError(
+          DeserializeError::InvalidLength(MultisigKeys::<C>::serialized_len(n), serialized.len())
+        )
+      )?;
     }
 
     let i = u16::from_be_bytes(serialized[cursor .. (cursor + 2)].try_into().unwrap());
     cursor += 2;
 
     let secret_share = C::F_from_slice(&serialized[cursor .. (cursor + C::F_len())])
-      .map_err(|_| FrostError::InternalError("invalid secret share".to_string()))?;
+      .map_err(|_| FrostError::DeserializeError(DeserializeError::InvalidScalar))?;
     cursor += C::F_len();
     let group_key = C::G_from_slice(&serialized[cursor .. (cursor + C::G_len())])
-      .map_err(|_| FrostError::InternalError("invalid group key".to_string()))?;
+      .map_err(|_| FrostError::DeserializeError(DeserializeError::InvalidPoint))?;
     cursor += C::G_len();
 
     let mut verification_shares = HashMap::new();
@@ -265,7 +497,7 @@ impl<C: Curve> MultisigKeys<C> {
       verification_shares.insert(
         l,
         C::G_from_slice(&serialized[cursor .. (cursor + C::G_len())])
-          .map_err(|_| FrostError::InternalError("invalid verification share".to_string()))?
+          .map_err(|_| FrostError::DeserializeError(DeserializeError::InvalidPoint))?
       );
       cursor += C::G_len();
     }
@@ -273,16 +505,86 @@ impl<C: Curve> MultisigKeys<C> {
     Ok(
       MultisigKeys {
         params: MultisigParams::new(t, n, i)
-          .map_err(|_| FrostError::InternalError("invalid parameters".to_string()))?,
+          .map_err(
+            |_| FrostError::DeserializeError(DeserializeError::InvalidParameters(t, n, i))
+          )?,
         secret_share,
         group_key,
         verification_shares,
-        offset: None
+        offset: None,
+        context: None
       }
     )
   }
 }
 
+/// A protocol message which can be written to a buffer for transmission
+/// Messages are read back by the machine receiving them, as reading may require its context
+pub trait Writable {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+  fn serialize(&self) -> Vec<u8> {
+    let mut buf = vec![];
+    self.write(&mut buf).unwrap();
+    buf
+  }
+}
+
+// Used by machines without a preprocess
+impl Writable for () {
+  fn write<W: Write>(&self, _: &mut W) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+// Vectors are prefixed with their length, enabling reading them without further context
+impl<T: Writable> Writable for Vec<T> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(
+      &u16::try_from(self.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many messages"))?
+        .to_be_bytes()
+    )?;
+    for message in self {
+      message.write(writer)?;
+    }
+    Ok(())
+  }
+}
+
+/// Read a vector of messages, as written by Writable, using the specified function per message
+pub fn read_vec<R: Read, T, F: FnMut(&mut R) -> io::Result<T>>(
+  reader: &mut R,
+  mut read: F
+) -> io::Result<Vec<T>> {
+  let mut len = [0; 2];
+  reader.read_exact(&mut len)?;
+  (0 .. u16::from_be_bytes(len)).map(|_| read(reader)).collect()
+}
+
+// Validate a signing set contains valid, unique indexes, including our own, returning it sorted
+pub(crate) fn validate_included(
+  params: MultisigParams,
+  included: &[u16]
+) -> Result<Vec<u16>, FrostError> {
+  let mut included = included.to_vec();
+  included.sort_unstable();
+
+  for i in 0 .. included.len() {
+    if (included[i] == 0) || (included[i] > params.n()) {
+      Err(FrostError::InvalidParticipantIndex(params.n(), included[i]))?;
+    }
+    if (i != 0) && (included[i - 1] == included[i]) {
+      Err(FrostError::DuplicatedIndex(included[i].into()))?;
+    }
+  }
+  if !included.contains(&params.i()) {
+    Err(FrostError::NotIncluded(params.i()))?;
+  }
+
+  Ok(included)
+}
+
 // Validate a map of serialized values to have the expected included participants
 pub(crate) fn validate_map<T>(
   map: &mut HashMap<u16, T>,
@@ -292,11 +594,18 @@ pub(crate) fn validate_map<T>(
   map.insert(ours.0, ours.1);
 
   if map.len() != included.len() {
+    trace_event!(
+      warn,
+      expected = included.len(),
+      received = map.len(),
+      "invalid participant quantity"
+    );
     Err(FrostError::InvalidParticipantQuantity(included.len(), map.len()))?;
   }
 
   for included in included {
     if !map.contains_key(included) {
+      trace_event!(warn, participant = included, "missing participant");
       Err(FrostError::MissingParticipant(*included))?;
     }
   }
@@ -0,0 +1,187 @@
+use core::marker::PhantomData;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::{ff::Field, Group};
+
+use crate::{
+  curve::{Curve, unique_session},
+  FrostError, MultisigParams, MultisigKeys,
+  lagrange,
+  algorithm::Hram,
+  sign::{SignatureShare, hash_msg}
+};
+
+/// Signer for Schnorr signatures which doesn't allocate collections, for constrained devices
+/// The signing set is held in a fixed-capacity array of N indices and the binding factor is
+/// calculated within a caller-provided buffer. The only remaining allocations are the byte
+/// encodings returned by the Curve trait, which are bounded by the size of a single element
+/// Produces the same signature shares as an AlgorithmMachine using the Schnorr algorithm, letting
+/// it sign alongside regular machines
+pub struct LowmemMachine<C: Curve, H: Hram<C>, const N: usize> {
+  i: u16,
+  included: [u16; N],
+  len: usize,
+  group_key: C::G,
+  offset: Option<C::F>,
+  context: Option<Vec<u8>>,
+  // Secret share for this signing set, with the lagrange coefficient and share of the offset
+  secret_share: C::F,
+  _hram: PhantomData<H>,
+}
+
+/// Machine which has preprocessed and is ready to sign
+pub struct LowmemSignMachine<C: Curve, H: Hram<C>, const N: usize> {
+  machine: LowmemMachine<C, H, N>,
+  nonces: [C::F; 2],
+  commitments: [C::G; 2],
+}
+
+impl<C: Curve, H: Hram<C>, const N: usize> LowmemMachine<C, H, N> {
+  /// Create a machine for the specified signing set, which must include us
+  pub fn new(
+    keys: &MultisigKeys<C>,
+    included: &[u16]
+  ) -> Result<LowmemMachine<C, H, N>, FrostError> {
+    let mut machine = LowmemMachine::from_share(
+      keys.params,
+      keys.secret_share,
+      keys.group_key,
+      included
+    )?;
+    if let Some(offset) = keys.offset {
+      machine.offset = Some(offset);
+      let len = u64::try_from(machine.len).unwrap();
+      machine.secret_share += offset * C::F::from(len).invert().unwrap();
+    }
+    machine.context = keys.context.clone();
+    Ok(machine)
+  }
+
+  /// Create a machine from solely a secret share and the group key, for devices which don't
+  /// store every verification share
+  pub fn from_share(
+    params: MultisigParams,
+    secret_share: C::F,
+    group_key: C::G,
+    included: &[u16]
+  ) -> Result<LowmemMachine<C, H, N>, FrostError> {
+    if included.len() > N {
+      let max = u16::try_from(N).unwrap_or(u16::MAX);
+      Err(FrostError::TooManyParticipants(included.len(), max))?;
+    }
+    if included.len() < params.t().into() {
+      Err(FrostError::NotEnoughSigners(params.t(), included.len()))?;
+    }
+
+    // Canonicalize the signing set as MultisigKeys::view does
+    let mut sorted = [0; N];
+    sorted[.. included.len()].copy_from_slice(included);
+    let set = &mut sorted[.. included.len()];
+    set.sort_unstable();
+    for l in 0 .. set.len() {
+      if (set[l] == 0) || (set[l] > params.n()) {
+        Err(FrostError::InvalidParticipantIndex(params.n(), set[l]))?;
+      }
+      if (l != 0) && (set[l - 1] == set[l]) {
+        Err(FrostError::DuplicatedIndex(set[l].into()))?;
+      }
+    }
+    if !set.contains(&params.i()) {
+      Err(FrostError::NotIncluded(params.i()))?;
+    }
+
+    Ok(
+      LowmemMachine {
+        i: params.i(),
+        secret_share: secret_share * lagrange::<C::F>(params.i(), set),
+        included: sorted,
+        len: included.len(),
+        group_key,
+        offset: None,
+        context: None,
+        _hram: PhantomData
+      }
+    )
+  }
+
+  /// Mix a deployment context into signing, as done with MultisigKeys::with_context
+  pub fn with_context(mut self, context: &[u8]) -> LowmemMachine<C, H, N> {
+    self.context = Some(context.to_vec());
+    self
+  }
+
+  /// The signing set, sorted
+  pub fn included(&self) -> &[u16] {
+    &self.included[.. self.len]
+  }
+
+  /// Generate nonces, returning the commitments to broadcast
+  pub fn preprocess<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R
+  ) -> (LowmemSignMachine<C, H, N>, [C::G; 2]) {
+    let nonces = C::random_nonces(self.secret_share, &unique_session(), &mut *rng);
+    let commitments = [C::generator_table() * nonces[0], C::generator_table() * nonces[1]];
+    (LowmemSignMachine { machine: self, nonces, commitments }, commitments)
+  }
+}
+
+impl<C: Curve, H: Hram<C>, const N: usize> LowmemSignMachine<C, H, N> {
+  /// Length of the buffer needed to sign the specified message
+  pub fn buffer_len(&self, msg: &[u8]) -> usize {
+    self.machine.offset.map(|_| C::F_len()).unwrap_or(0) +
+      (self.machine.len * (2 + (2 * C::G_len()))) +
+      hash_msg::<C>(self.machine.context.as_deref(), msg).len()
+  }
+
+  /// Sign the message, given every other participant's commitments, using buffer to calculate
+  /// the binding factor
+  #[allow(non_snake_case)]
+  pub fn sign(
+    self,
+    commitments: &[(u16, [C::G; 2])],
+    msg: &[u8],
+    buffer: &mut [u8]
+  ) -> Result<SignatureShare<C>, FrostError> {
+    let machine = &self.machine;
+    let included = machine.included();
+    if (commitments.len() + 1) != included.len() {
+      Err(FrostError::InvalidParticipantQuantity(included.len(), commitments.len() + 1))?;
+    }
+    let needed = self.buffer_len(msg);
+    if buffer.len() < needed {
+      Err(FrostError::BufferTooSmall(needed, buffer.len()))?;
+    }
+
+    // Serialize the binding factor's input identically to the IETF transcript
+    let mut cursor = 0;
+    let mut write = |bytes: &[u8]| {
+      buffer[cursor .. (cursor + bytes.len())].copy_from_slice(bytes);
+      cursor += bytes.len();
+    };
+    if let Some(offset) = machine.offset {
+      write(&C::F_to_bytes(&offset));
+    }
+    let mut D = C::G::identity();
+    let mut E = C::G::identity();
+    for l in included {
+      let these = if *l == machine.i {
+        self.commitments
+      } else {
+        commitments.iter().find(|(i, _)| i == l).ok_or(FrostError::MissingParticipant(*l))?.1
+      };
+      write(&l.to_be_bytes());
+      write(&C::G_to_bytes(&these[0]));
+      write(&C::G_to_bytes(&these[1]));
+      D += these[0];
+      E += these[1];
+    }
+    write(&hash_msg::<C>(machine.context.as_deref(), msg));
+
+    let binding = C::hash_binding_factor(&buffer[.. needed]);
+    let R = D + (E * binding);
+    let c = H::hram(&R, &machine.group_key, msg);
+    Ok(SignatureShare(self.nonces[0] + (self.nonces[1] * binding) + (machine.secret_share * c)))
+  }
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+  curve::Curve,
+  MultisigKeys, Writable,
+  schnorr::{self, SchnorrSignature},
+  algorithm::Hram
+};
+
+// Domain separation tag prefixed to the signed statement
+const DST: &[u8] = b"FROST Context Migration";
+
+fn read_context<R: Read>(reader: &mut R) -> io::Result<String> {
+  let mut len = [0; 4];
+  reader.read_exact(&mut len)?;
+  let mut context = vec![];
+  reader.by_ref().take(u32::from_be_bytes(len).into()).read_to_end(&mut context)?;
+  if context.len() != usize::try_from(u32::from_be_bytes(len)).unwrap() {
+    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "context was truncated"))?;
+  }
+  String::from_utf8(context)
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid context"))
+}
+
+fn write_context<W: Write>(writer: &mut W, context: &str) -> io::Result<()> {
+  let len = u32::try_from(context.len())
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "context was too long"))?;
+  writer.write_all(&len.to_be_bytes())?;
+  writer.write_all(context.as_bytes())
+}
+
+/// Statement endorsing the continued use of a group key under a new protocol context
+/// MultisigKeys don't contain any context-bound material, the context only binding the DKG's
+/// proofs of knowledge and the signing protocol, so keys are migrated without redoing the DKG
+/// Instead, the group signs this statement, under the old context, with the usual Schnorr
+/// algorithm, letting third parties learn the new context from a key they already trust
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Migration<C: Curve> {
+  group_key: C::G,
+  t: u16,
+  n: u16,
+  from: String,
+  to: String,
+}
+
+impl<C: Curve> Migration<C> {
+  /// Statement migrating the specified keys from one context to another
+  pub fn new(keys: &MultisigKeys<C>, from: &str, to: &str) -> Migration<C> {
+    Migration {
+      group_key: keys.group_key(),
+      t: keys.params().t(),
+      n: keys.params().n(),
+      from: from.to_string(),
+      to: to.to_string()
+    }
+  }
+
+  pub fn group_key(&self) -> C::G {
+    self.group_key
+  }
+
+  pub fn from(&self) -> &str {
+    &self.from
+  }
+
+  pub fn to(&self) -> &str {
+    &self.to
+  }
+
+  /// Read a statement, as written by Writable
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<Migration<C>> {
+    let mut id = vec![0; C::ID.len()];
+    reader.read_exact(&mut id)?;
+    if id != C::ID {
+      Err(io::Error::new(io::ErrorKind::InvalidData, "statement is for a different curve"))?;
+    }
+
+    let group_key = C::read_G(reader)?;
+    let mut params = [0; 4];
+    reader.read_exact(&mut params)?;
+    Ok(
+      Migration {
+        group_key,
+        t: u16::from_be_bytes(params[.. 2].try_into().unwrap()),
+        n: u16::from_be_bytes(params[2 ..].try_into().unwrap()),
+        from: read_context(reader)?,
+        to: read_context(reader)?
+      }
+    )
+  }
+
+  /// The message the group should sign to endorse this statement
+  pub fn message(&self) -> Vec<u8> {
+    let mut message = DST.to_vec();
+    // Only fails for contexts over 4 GB
+    self.write(&mut message).unwrap();
+    message
+  }
+
+  /// Verify the group signed this statement with the Schnorr algorithm using the specified HRAM
+  pub fn verify<H: Hram<C>>(&self, signature: &SchnorrSignature<C>) -> bool {
+    schnorr::verify::<C>(
+      self.group_key,
+      H::hram(&signature.R, &self.group_key, &self.message()),
+      signature
+    )
+  }
+}
+
+impl<C: Curve> Writable for Migration<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(C::ID)?;
+    writer.write_all(&C::G_to_bytes(&self.group_key))?;
+    writer.write_all(&self.t.to_be_bytes())?;
+    writer.write_all(&self.n.to_be_bytes())?;
+    write_context(writer, &self.from)?;
+    write_context(writer, &self.to)
+  }
+}
@@ -0,0 +1,374 @@
+use std::{sync::Arc, io::{self, Read, Write}, collections::{HashMap, BTreeMap}};
+
+use rand_core::{RngCore, CryptoRng};
+
+use sha2::{Digest, Sha256};
+
+use group::Group;
+
+use crate::{
+  curve::{Curve, unique_session},
+  FrostError, MultisigParams, MultisigKeys, Writable, lagrange,
+  algorithm::Algorithm,
+  compact::DIGEST_LEN,
+  sign::{
+    Preprocess, SignatureShare, PreprocessPackage, SignMachine,
+    AlgorithmMachine, AlgorithmSignMachine, AlgorithmSignatureMachine
+  },
+  validate_map, validate_included
+};
+
+/// Keys for a participant in an outer multisig whose share is held by an inner multisig
+/// The inner multisig's group key must be the outer participant's verification share
+/// Only algorithms without preprocess addendums can be nested, as addendums can't be aggregated
+/// without knowledge of the algorithm. Offsets are also not supported for the outer multisig
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NestedKeys<C: Curve> {
+  /// Keys for the inner multisig
+  inner: Arc<MultisigKeys<C>>,
+  /// Parameters of the outer multisig, where i is the participant the inner multisig acts as
+  params: MultisigParams,
+  /// Group key of the outer multisig
+  group_key: C::G,
+  /// Verification shares of the outer multisig
+  verification_shares: HashMap<u16, C::G>,
+}
+
+impl<C: Curve> NestedKeys<C> {
+  pub fn new(
+    inner: Arc<MultisigKeys<C>>,
+    params: MultisigParams,
+    group_key: C::G,
+    verification_shares: HashMap<u16, C::G>
+  ) -> Result<NestedKeys<C>, FrostError> {
+    if verification_shares.len() != usize::from(params.n()) {
+      Err(FrostError::InvalidParticipantQuantity(params.n().into(), verification_shares.len()))?;
+    }
+    for l in 1 ..= params.n() {
+      if !verification_shares.contains_key(&l) {
+        Err(FrostError::MissingParticipant(l))?;
+      }
+    }
+
+    if inner.group_key() != verification_shares[&params.i()] {
+      Err(FrostError::InvalidNestedKey(params.i()))?;
+    }
+
+    Ok(NestedKeys { inner, params, group_key, verification_shares })
+  }
+
+  pub fn inner(&self) -> Arc<MultisigKeys<C>> {
+    self.inner.clone()
+  }
+
+  pub fn params(&self) -> MultisigParams {
+    self.params
+  }
+
+  pub fn group_key(&self) -> C::G {
+    self.group_key
+  }
+
+  // The outer keys for a specific inner signing set, holding our share of the outer secret share
+  fn outer(&self, inner_included: &[u16]) -> Result<MultisigKeys<C>, FrostError> {
+    Ok(
+      MultisigKeys {
+        params: self.params,
+        secret_share: self.inner.view(inner_included)?.secret_share(),
+        group_key: self.group_key,
+        verification_shares: self.verification_shares.clone(),
+        offset: None,
+        context: None
+      }
+    )
+  }
+}
+
+/// Digest of an inner member's preprocess, shared with the rest of the inner signing set before
+/// the preprocess itself
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NestedDigest([u8; DIGEST_LEN]);
+
+impl NestedDigest {
+  fn new<C: Curve>(l: u16, commitments: &[C::G; 2]) -> NestedDigest {
+    let mut digest = Sha256::new();
+    digest.update(b"FROST Nested Preprocess Digest");
+    digest.update(u64::try_from(C::ID.len()).unwrap().to_be_bytes());
+    digest.update(C::ID);
+    digest.update(l.to_be_bytes());
+    digest.update(C::G_to_bytes(&commitments[0]));
+    digest.update(C::G_to_bytes(&commitments[1]));
+    NestedDigest(digest.finalize().into())
+  }
+
+  /// Read a digest, as written by Writable
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<NestedDigest> {
+    let mut digest = [0; DIGEST_LEN];
+    reader.read_exact(&mut digest)?;
+    Ok(NestedDigest(digest))
+  }
+}
+
+impl Writable for NestedDigest {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.0)
+  }
+}
+
+/// State machine for a member of an inner multisig signing on behalf of an outer participant
+/// Each round produces a message for the rest of the inner signing set, which is then aggregated
+/// into the outer participant's message. Every member of the inner signing set will produce the
+/// same aggregate, so any of them may transmit it to the outer signing set
+/// The outer binding factor only binds the aggregate commitments, so inner members first share
+/// digests of their preprocesses. Without this, the last inner member to reveal could choose
+/// their commitments to cancel out everyone else's, leaving their nonces unbound
+/// Inner members' shares are individually verified before being aggregated, so an invalid share
+/// is attributed to the inner member who sent it, not the outer participant as a whole
+pub struct NestedMachine<C: Curve, A: Algorithm<C>> {
+  params: MultisigParams,
+  included: Vec<u16>,
+  secret: C::F,
+  verification_shares: HashMap<u16, C::G>,
+  machine: AlgorithmMachine<C, A>,
+}
+
+pub struct NestedDigestMachine<C: Curve, A: Algorithm<C>> {
+  params: MultisigParams,
+  included: Vec<u16>,
+  verification_shares: HashMap<u16, C::G>,
+  nonces: [C::F; 2],
+  commitments: [C::G; 2],
+  machine: AlgorithmMachine<C, A>,
+}
+
+pub struct NestedCommitmentMachine<C: Curve, A: Algorithm<C>> {
+  params: MultisigParams,
+  included: Vec<u16>,
+  verification_shares: HashMap<u16, C::G>,
+  digests: HashMap<u16, NestedDigest>,
+  nonces: [C::F; 2],
+  commitments: [C::G; 2],
+  machine: AlgorithmMachine<C, A>,
+}
+
+pub struct NestedSignMachine<C: Curve, A: Algorithm<C>> {
+  params: MultisigParams,
+  included: Vec<u16>,
+  verification_shares: HashMap<u16, C::G>,
+  commitments: HashMap<u16, [C::G; 2]>,
+  machine: AlgorithmSignMachine<C, A>,
+}
+
+pub struct NestedShareMachine<C: Curve, A: Algorithm<C>> {
+  params: MultisigParams,
+  included: Vec<u16>,
+  verification_shares: HashMap<u16, C::G>,
+  commitments: HashMap<u16, [C::G; 2]>,
+  share: C::F,
+  machine: AlgorithmSignatureMachine<C, A>,
+}
+
+impl<C: Curve, A: Algorithm<C>> NestedMachine<C, A> {
+  /// Creates a new machine for the inner signing set inner_included to sign on behalf of the
+  /// outer participant as part of the outer signing set outer_included
+  pub fn new(
+    algorithm: A,
+    keys: &NestedKeys<C>,
+    inner_included: &[u16],
+    outer_included: &[u16],
+  ) -> Result<NestedMachine<C, A>, FrostError> {
+    let params = keys.inner.params();
+    let included = validate_included(params, inner_included)?;
+    let outer = keys.outer(&included)?;
+
+    // Each inner member holds their share of the outer participant's share, so their share of the
+    // outer participant's verification share is their inner verification share, scaled by the
+    // outer participant's lagrange coefficient
+    let outer_included = validate_included(keys.params, outer_included)?;
+    let outer_lagrange = lagrange::<C::F>(keys.params.i(), &outer_included);
+    let inner = keys.inner.view(&included)?;
+    let verification_shares = included.iter().map(
+      |l| (*l, inner.verification_share(*l) * outer_lagrange)
+    ).collect();
+
+    Ok(
+      NestedMachine {
+        params,
+        secret: outer.secret_share,
+        verification_shares,
+        machine: AlgorithmMachine::new(algorithm, Arc::new(outer), &outer_included)?,
+        included
+      }
+    )
+  }
+
+  /// Perform the preprocessing round for this inner participant
+  /// Returns a digest of the preprocess, which must be transmitted to the rest of the inner
+  /// signing set before the preprocess itself
+  pub fn preprocess<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R
+  ) -> (NestedDigestMachine<C, A>, NestedDigest) {
+    let nonces = C::random_nonces(self.secret, &unique_session(), &mut *rng);
+    let commitments = [C::generator_table() * nonces[0], C::generator_table() * nonces[1]];
+
+    (
+      NestedDigestMachine {
+        params: self.params,
+        included: self.included,
+        verification_shares: self.verification_shares,
+        nonces,
+        commitments,
+        machine: self.machine
+      },
+      NestedDigest::new::<C>(self.params.i(), &commitments)
+    )
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> NestedDigestMachine<C, A> {
+  /// Read a digest from another member of the inner signing set
+  pub fn read_digest<R: Read>(&self, reader: &mut R) -> io::Result<NestedDigest> {
+    NestedDigest::read(reader)
+  }
+
+  /// Reveal our preprocess, once every other member of the inner signing set has committed to
+  /// theirs
+  /// Returns a preprocess which must be transmitted to the rest of the inner signing set
+  pub fn reveal(
+    self,
+    mut digests: HashMap<u16, NestedDigest>
+  ) -> Result<(NestedCommitmentMachine<C, A>, Preprocess<C>), FrostError> {
+    validate_map(
+      &mut digests,
+      &self.included,
+      (self.params.i(), NestedDigest::new::<C>(self.params.i(), &self.commitments))
+    )?;
+
+    Ok((
+      NestedCommitmentMachine {
+        params: self.params,
+        included: self.included,
+        verification_shares: self.verification_shares,
+        digests,
+        nonces: self.nonces,
+        commitments: self.commitments,
+        machine: self.machine
+      },
+      Preprocess { commitments: self.commitments, addendum: vec![] }
+    ))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> NestedCommitmentMachine<C, A> {
+  /// Read a preprocess from another member of the inner signing set
+  pub fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Preprocess<C>> {
+    Ok(Preprocess { commitments: [C::read_G(reader)?, C::read_G(reader)?], addendum: vec![] })
+  }
+
+  /// Aggregate the inner signing set's commitments
+  /// Preprocesses which don't match their member's digest are rejected. Returns the outer
+  /// participant's preprocess, which must be transmitted to the outer signing set
+  pub fn aggregate(
+    self,
+    mut commitments: HashMap<u16, Preprocess<C>>
+  ) -> Result<(NestedSignMachine<C, A>, Preprocess<C>), FrostError> {
+    validate_map(
+      &mut commitments,
+      &self.included,
+      (self.params.i(), Preprocess { commitments: self.commitments, addendum: vec![] })
+    )?;
+
+    let mut sum = [C::G::identity(), C::G::identity()];
+    let mut inner = HashMap::new();
+    // Iterate in index order so who's blamed doesn't depend on the HashMap's iteration order
+    for (l, preprocess) in commitments.into_iter().collect::<BTreeMap<_, _>>() {
+      if (!preprocess.addendum.is_empty()) ||
+        (NestedDigest::new::<C>(l, &preprocess.commitments) != self.digests[&l]) {
+        Err(FrostError::InvalidCommitment(l))?;
+      }
+      sum[0] += preprocess.commitments[0];
+      sum[1] += preprocess.commitments[1];
+      inner.insert(l, preprocess.commitments);
+    }
+
+    let (machine, preprocess) = self.machine.unsafe_override_preprocess(
+      PreprocessPackage {
+        nonces: self.nonces,
+        preprocess: Preprocess { commitments: sum, addendum: vec![] }
+      }
+    );
+    Ok((
+      NestedSignMachine {
+        params: self.params,
+        included: self.included,
+        verification_shares: self.verification_shares,
+        commitments: inner,
+        machine
+      },
+      preprocess
+    ))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> NestedSignMachine<C, A> {
+  /// Read a preprocess from a participant in the outer signing set
+  pub fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Preprocess<C>> {
+    self.machine.read_preprocess(reader)
+  }
+
+  /// Sign a message
+  /// Takes in the outer signing set's preprocesses. Returns a share which must be transmitted to
+  /// the rest of the inner signing set
+  pub fn sign(
+    self,
+    commitments: HashMap<u16, Preprocess<C>>,
+    msg: &[u8]
+  ) -> Result<(NestedShareMachine<C, A>, SignatureShare<C>), FrostError> {
+    let (machine, share) = self.machine.sign(commitments, msg)?;
+    Ok((
+      NestedShareMachine {
+        params: self.params,
+        included: self.included,
+        verification_shares: self.verification_shares,
+        commitments: self.commitments,
+        share: share.0,
+        machine
+      },
+      share
+    ))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> NestedShareMachine<C, A> {
+  /// Read a share from another member of the inner signing set
+  pub fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
+    Ok(SignatureShare(C::read_F(reader)?))
+  }
+
+  /// Aggregate the inner signing set's shares
+  /// Each share is verified against its member's commitments and verification share, blaming the
+  /// lowest index with an invalid share. Returns the outer participant's share, which must be
+  /// transmitted to the outer signing set, and a machine to complete the outer signature with
+  pub fn aggregate(
+    self,
+    mut shares: HashMap<u16, SignatureShare<C>>
+  ) -> Result<(AlgorithmSignatureMachine<C, A>, SignatureShare<C>), FrostError> {
+    validate_map(&mut shares, &self.included, (self.params.i(), SignatureShare(self.share)))?;
+    shares.remove(&self.params.i());
+
+    let mut sum = self.share;
+    for (l, share) in shares.into_iter().collect::<BTreeMap<_, _>>() {
+      if !self.machine.verify_partial_share(
+        self.verification_shares[&l],
+        self.commitments[&l],
+        share.0
+      ) {
+        Err(FrostError::InvalidShare(l))?;
+      }
+      sum += share.0;
+    }
+
+    Ok((self.machine.unsafe_override_share(sum), SignatureShare(sum)))
+  }
+}
@@ -0,0 +1,21 @@
+use jubjub::{Fr, SubgroupPoint};
+
+use crate::{
+  curve::{Jubjub, RedJubjubHram},
+  MultisigKeys, schnorr,
+  algorithm::{Hram, Schnorr, SchnorrSignature}
+};
+
+/// RedJubjub signing, as used for Sapling spend authorization
+pub type RedJubjub = Schnorr<Jubjub, RedJubjubHram>;
+
+/// Rerandomize keys by the spend authorization randomizer α, producing keys for the randomized
+/// verification key rk = ak + [α]G
+pub fn rerandomize(keys: &MultisigKeys<Jubjub>, randomizer: Fr) -> MultisigKeys<Jubjub> {
+  keys.offset(randomizer)
+}
+
+/// Verify a RedJubjub signature under the specified (possibly randomized) verification key
+pub fn verify(verification_key: SubgroupPoint, msg: &[u8], sig: &SchnorrSignature<Jubjub>) -> bool {
+  schnorr::verify(verification_key, RedJubjubHram::hram(&sig.R, &verification_key, msg), sig)
+}
@@ -0,0 +1,65 @@
+use std::{time::{Duration, Instant}, collections::HashSet};
+
+use crate::FrostError;
+
+/// Deadline for a protocol round, tracking which of its expected participants have responded
+#[derive(Clone, Debug)]
+pub struct Round {
+  started: Instant,
+  timeout: Duration,
+  expected: Vec<u16>,
+  received: HashSet<u16>,
+}
+
+impl Round {
+  /// Start a round, expecting a message from each of the specified participants before the
+  /// timeout elapses
+  pub fn new(mut expected: Vec<u16>, timeout: Duration) -> Round {
+    expected.sort_unstable();
+    expected.dedup();
+    Round { started: Instant::now(), timeout, expected, received: HashSet::new() }
+  }
+
+  /// When this round started
+  pub fn started(&self) -> Instant {
+    self.started
+  }
+
+  /// When this round's messages are due
+  pub fn deadline(&self) -> Instant {
+    self.started + self.timeout
+  }
+
+  /// If this round's deadline has passed
+  pub fn expired(&self) -> bool {
+    self.started.elapsed() >= self.timeout
+  }
+
+  /// Record the specified participant's message was received
+  pub fn received(&mut self, l: u16) -> Result<(), FrostError> {
+    if !self.expected.contains(&l) {
+      Err(FrostError::NotIncluded(l))?;
+    }
+    self.received.insert(l);
+    Ok(())
+  }
+
+  /// Participants whose messages have yet to be received
+  pub fn missing(&self) -> Vec<u16> {
+    self.expected.iter().filter(|l| !self.received.contains(l)).cloned().collect()
+  }
+
+  /// Participants who failed to send their message before the deadline. Empty until it passes
+  pub fn late(&self) -> Vec<u16> {
+    if self.expired() { self.missing() } else { vec![] }
+  }
+
+  /// Abort, listing the late participants, if the deadline passed without every message
+  pub fn check(&self) -> Result<(), FrostError> {
+    let missing = self.late();
+    if !missing.is_empty() {
+      Err(FrostError::Timeout { missing })?;
+    }
+    Ok(())
+  }
+}
@@ -1,10 +1,13 @@
+use core::{fmt, str::FromStr};
+use std::io::{self, Read};
+
 use rand_core::{RngCore, CryptoRng};
 
-use group::ff::Field;
+use group::{ff::Field, Group};
 
 use multiexp::BatchVerifier;
 
-use crate::Curve;
+use crate::{curve::{CurveError, Curve}, algorithm::Hram};
 
 #[allow(non_snake_case)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -20,6 +23,61 @@ impl<C: Curve> SchnorrSignature<C> {
     res.extend(C::F_to_bytes(&self.s));
     res
   }
+
+  /// Parse a signature, as serialized, requiring an exact length, canonical encodings, and a
+  /// non-identity nonce
+  #[allow(non_snake_case)]
+  pub fn deserialize(bytes: &[u8]) -> Result<SchnorrSignature<C>, CurveError> {
+    if bytes.len() != (C::G_len() + C::F_len()) {
+      Err(CurveError::InvalidLength(C::G_len() + C::F_len(), bytes.len()))?;
+    }
+
+    let R = C::G_from_slice(&bytes[.. C::G_len()])?;
+    if bool::from(R.is_identity()) {
+      Err(CurveError::IdentityPoint)?;
+    }
+    let s = C::F_from_slice(&bytes[C::G_len() ..])?;
+    // F_from_slice isn't required to reject non-canonical encodings
+    if C::F_to_bytes(&s) != bytes[C::G_len() ..] {
+      Err(CurveError::InvalidScalar)?;
+    }
+    Ok(SchnorrSignature { R, s })
+  }
+
+  /// Read a signature, as serialized, with the same validation as deserialize
+  pub fn read<R: Read>(reader: &mut R) -> io::Result<SchnorrSignature<C>> {
+    let mut bytes = vec![0; C::G_len() + C::F_len()];
+    reader.read_exact(&mut bytes)?;
+    SchnorrSignature::deserialize(&bytes)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+impl<C: Curve> fmt::Display for SchnorrSignature<C> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", hex::encode(self.serialize()))
+  }
+}
+
+impl<C: Curve> FromStr for SchnorrSignature<C> {
+  type Err = CurveError;
+
+  fn from_str(s: &str) -> Result<SchnorrSignature<C>, CurveError> {
+    SchnorrSignature::deserialize(&hex::decode(s).map_err(|_| CurveError::InvalidHex)?)
+  }
+}
+
+#[cfg(feature = "bech32")]
+impl<C: Curve> SchnorrSignature<C> {
+  /// Encode this signature as bech32m, under the specified human-readable part
+  pub fn to_bech32(&self, hrp: &str) -> Result<String, CurveError> {
+    crate::bech32::encode(hrp, &self.serialize())
+  }
+
+  /// Decode a signature encoded by to_bech32, which must use the specified human-readable part
+  pub fn from_bech32(hrp: &str, encoded: &str) -> Result<SchnorrSignature<C>, CurveError> {
+    SchnorrSignature::deserialize(&crate::bech32::decode(hrp, encoded)?)
+  }
 }
 
 pub(crate) fn sign<C: Curve>(
@@ -28,7 +86,7 @@ pub(crate) fn sign<C: Curve>(
   challenge: C::F
 ) -> SchnorrSignature<C> {
   SchnorrSignature {
-    R: C::GENERATOR_TABLE * nonce,
+    R: C::generator_table() * nonce,
     s: nonce + (private_key * challenge)
   }
 }
@@ -38,15 +96,18 @@ pub(crate) fn verify<C: Curve>(
   challenge: C::F,
   signature: &SchnorrSignature<C>
 ) -> bool {
-  (C::GENERATOR_TABLE * signature.s) == (signature.R + (public_key * challenge))
+  (C::generator_table() * signature.s) == (signature.R + (public_key * challenge))
 }
 
-pub(crate) fn batch_verify<C: Curve, R: RngCore + CryptoRng>(
+// Identifier, public key, challenge, and signature
+pub(crate) type Triplet<Id, C> = (Id, <C as Curve>::G, <C as Curve>::F, SchnorrSignature<C>);
+
+pub(crate) fn batch_verify<C: Curve, R: RngCore + CryptoRng, Id: Copy>(
   rng: &mut R,
-  triplets: &[(u16, C::G, C::F, SchnorrSignature<C>)]
-) -> Result<(), u16> {
-  let mut values = [(C::F::one(), C::GENERATOR); 3];
-  let mut batch = BatchVerifier::new(triplets.len(), C::LITTLE_ENDIAN);
+  triplets: &[Triplet<Id, C>]
+) -> Result<(), Id> {
+  let mut values = [(C::F::one(), C::generator()); 3];
+  let mut batch = BatchVerifier::new(triplets.len());
   for triple in triplets {
     // s = r + ca
     // sG == R + cA
@@ -64,3 +125,46 @@ pub(crate) fn batch_verify<C: Curve, R: RngCore + CryptoRng>(
 
   batch.verify_vartime_with_vartime_blame()
 }
+
+/// Batch of Schnorr signatures, such as those produced by FROST, verified with a single multiexp
+/// If the batch is invalid, the first invalid signature, in the order they were queued, is
+/// identified
+#[derive(Clone, Debug)]
+pub struct SignatureBatch<C: Curve>(Vec<Triplet<usize, C>>);
+
+impl<C: Curve> Default for SignatureBatch<C> {
+  fn default() -> SignatureBatch<C> {
+    SignatureBatch(vec![])
+  }
+}
+
+impl<C: Curve> SignatureBatch<C> {
+  pub fn new() -> SignatureBatch<C> {
+    SignatureBatch::default()
+  }
+
+  /// Queue a signature for the message under the key, with the challenge as defined by the Hram
+  /// Returns the signature's index within the batch
+  pub fn queue<H: Hram<C>>(&mut self, key: C::G, msg: &[u8], sig: SchnorrSignature<C>) -> usize {
+    let i = self.0.len();
+    self.0.push((i, key, H::hram(&sig.R, &key, msg), sig));
+    i
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Verify every queued signature, returning the index of the first invalid one on failure
+  pub fn verify<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), usize> {
+    // multiexp doesn't support empty inputs
+    if self.0.is_empty() {
+      return Ok(());
+    }
+    batch_verify(rng, &self.0)
+  }
+}
@@ -0,0 +1,144 @@
+use std::{io::{self, Read}, collections::BTreeMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use transcript::{Transcript, MerlinTranscript};
+
+use dalek_ff_group::{Scalar, RistrettoPoint};
+
+use crate::{
+  curve::Ristretto,
+  FrostError, MultisigView, schnorr,
+  algorithm::{Algorithm, SchnorrSignature}
+};
+
+/// sr25519 signature, as produced by schnorrkel
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SchnorrkelSignature {
+  pub R: RistrettoPoint,
+  pub s: Scalar,
+}
+
+impl SchnorrkelSignature {
+  /// Serialize as schnorrkel does, marking the signature with the high bit of s
+  pub fn serialize(&self) -> [u8; 64] {
+    let mut res = [0; 64];
+    res[.. 32].copy_from_slice(&self.R.compress().to_bytes());
+    res[32 ..].copy_from_slice(&self.s.to_bytes());
+    res[63] |= 1 << 7;
+    res
+  }
+
+  /// Verify this signature for the specified signing context and message
+  pub fn verify(&self, public_key: RistrettoPoint, context: &[u8], msg: &[u8]) -> bool {
+    schnorr::verify::<Ristretto>(
+      public_key,
+      challenge(context, msg, public_key, self.R),
+      &SchnorrSignature { R: self.R, s: self.s }
+    )
+  }
+}
+
+// schnorrkel's challenge, a Merlin transcript over the signing context, message, key, and nonce
+#[allow(non_snake_case)]
+fn challenge(context: &[u8], msg: &[u8], A: RistrettoPoint, R: RistrettoPoint) -> Scalar {
+  let mut transcript = MerlinTranscript::new(b"SigningContext");
+  transcript.append_message(b"", context);
+  transcript.append_message(b"sign-bytes", msg);
+  transcript.append_message(b"proto-name", b"Schnorr-sig");
+  transcript.append_message(b"sign:pk", &A.compress().to_bytes());
+  transcript.append_message(b"sign:R", &R.compress().to_bytes());
+  Scalar::from_bytes_mod_order_wide(&transcript.challenge(b"sign:c").try_into().unwrap())
+}
+
+/// sr25519-compatible Schnorr signatures, as used by Substrate, under a fixed signing context
+#[derive(Clone)]
+pub struct Schnorrkel {
+  context: &'static [u8],
+  transcript: MerlinTranscript,
+  c: Option<Scalar>,
+}
+
+impl Schnorrkel {
+  pub fn new(context: &'static [u8]) -> Schnorrkel {
+    Schnorrkel { context, transcript: MerlinTranscript::new(b"FROST Schnorrkel"), c: None }
+  }
+}
+
+impl Algorithm<Ristretto> for Schnorrkel {
+  type Transcript = MerlinTranscript;
+  type Signature = SchnorrkelSignature;
+
+  fn transcript(&mut self) -> &mut Self::Transcript {
+    &mut self.transcript
+  }
+
+  fn preprocess_addendum<R: RngCore + CryptoRng>(
+    &mut self,
+    _: &mut R,
+    _: &MultisigView<Ristretto>,
+    _: &[Scalar; 2],
+  ) -> Vec<u8> {
+    vec![]
+  }
+
+  fn addendum_len() -> usize {
+    0
+  }
+
+  fn read_addendum<R: Read>(&self, _: &mut R) -> io::Result<Vec<u8>> {
+    Ok(vec![])
+  }
+
+  fn process_addendum(
+    &mut self,
+    _: &MultisigView<Ristretto>,
+    _: u16,
+    _: &[RistrettoPoint; 2],
+    _: &[u8],
+  ) -> Result<(), FrostError> {
+    Ok(())
+  }
+
+  fn sign_share(
+    &mut self,
+    params: &MultisigView<Ristretto>,
+    _: &BTreeMap<u16, [RistrettoPoint; 2]>,
+    nonce_sum: RistrettoPoint,
+    _: Scalar,
+    nonce: Scalar,
+    msg: &[u8],
+  ) -> Scalar {
+    let c = challenge(self.context, msg, params.group_key(), nonce_sum);
+    self.c = Some(c);
+    schnorr::sign::<Ristretto>(params.secret_share(), nonce, c).s
+  }
+
+  fn verify(
+    &self,
+    group_key: RistrettoPoint,
+    nonce: RistrettoPoint,
+    sum: Scalar
+  ) -> Option<Self::Signature> {
+    let sig = SchnorrSignature { R: nonce, s: sum };
+    if schnorr::verify::<Ristretto>(group_key, self.c.unwrap(), &sig) {
+      Some(SchnorrkelSignature { R: sig.R, s: sig.s })
+    } else {
+      None
+    }
+  }
+
+  fn verify_share(
+    &self,
+    verification_share: RistrettoPoint,
+    nonce: RistrettoPoint,
+    share: Scalar,
+  ) -> bool {
+    schnorr::verify::<Ristretto>(
+      verification_share,
+      self.c.unwrap(),
+      &SchnorrSignature { R: nonce, s: share }
+    )
+  }
+}
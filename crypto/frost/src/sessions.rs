@@ -0,0 +1,131 @@
+use std::{io::{self, Read}, sync::Arc, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  curve::Curve,
+  FrostError, MultisigKeys,
+  algorithm::Algorithm,
+  sign::{
+    NonceRegistry, Preprocess, SignatureShare,
+    PreprocessMachine, SignMachine, SignatureMachine,
+    AlgorithmMachine, AlgorithmSignMachine, AlgorithmSignatureMachine
+  }
+};
+
+enum Session<C: Curve, A: Algorithm<C>> {
+  Preprocessed(AlgorithmSignMachine<C, A>),
+  Signed(AlgorithmSignatureMachine<C, A>),
+}
+
+/// Manager for many concurrent signing sessions with the same keys
+/// Every session has its own machine, and therefore its own nonces, and is identified by a
+/// session ID which may only ever be used once. Session IDs and nonce commitments are registered
+/// with the provided registry, which should be persisted for this to hold across restarts
+pub struct Sessions<C: Curve, A: Algorithm<C>> {
+  keys: Arc<MultisigKeys<C>>,
+  registry: Arc<dyn NonceRegistry>,
+  max: usize,
+  sessions: HashMap<Vec<u8>, Session<C, A>>,
+}
+
+impl<C: Curve, A: Algorithm<C>> Sessions<C, A> {
+  /// Create a manager running at most max sessions at once
+  pub fn new(
+    keys: Arc<MultisigKeys<C>>,
+    registry: Arc<dyn NonceRegistry>,
+    max: usize
+  ) -> Sessions<C, A> {
+    Sessions { keys, registry, max, sessions: HashMap::new() }
+  }
+
+  /// IDs of the sessions currently running
+  pub fn active(&self) -> Vec<Vec<u8>> {
+    self.sessions.keys().cloned().collect()
+  }
+
+  /// Start a session, returning our preprocess for it
+  pub fn preprocess<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    id: &[u8],
+    algorithm: A,
+    included: &[u16]
+  ) -> Result<Preprocess<C>, FrostError> {
+    if self.sessions.contains_key(id) {
+      Err(FrostError::DuplicatedSession)?;
+    }
+    if self.sessions.len() >= self.max {
+      Err(FrostError::TooManySessions(self.max))?;
+    }
+    let machine = AlgorithmMachine::new(algorithm, self.keys.clone(), included)?
+      .with_registry(self.registry.clone());
+    // Domain separated from the nonce commitments registered when signing
+    if !self.registry.register(&[b"FROST session".as_ref(), id].concat()) {
+      Err(FrostError::DuplicatedSession)?;
+    }
+
+    let (machine, preprocess) = machine.preprocess(rng);
+    self.sessions.insert(id.to_vec(), Session::Preprocessed(machine));
+    Ok(preprocess)
+  }
+
+  /// Read a preprocess sent by another participant for the specified session
+  pub fn read_preprocess<R: Read>(&self, id: &[u8], reader: &mut R) -> io::Result<Preprocess<C>> {
+    match self.sessions.get(id) {
+      Some(Session::Preprocessed(machine)) => machine.read_preprocess(reader),
+      _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "no session awaiting preprocesses"))
+    }
+  }
+
+  /// Sign the message for the specified session, returning our signature share
+  /// If signing fails, the session is ended
+  pub fn sign(
+    &mut self,
+    id: &[u8],
+    commitments: HashMap<u16, Preprocess<C>>,
+    msg: &[u8]
+  ) -> Result<SignatureShare<C>, FrostError> {
+    let machine = match self.sessions.remove(id) {
+      Some(Session::Preprocessed(machine)) => machine,
+      Some(session) => {
+        self.sessions.insert(id.to_vec(), session);
+        Err(FrostError::UnknownSession)?
+      },
+      None => Err(FrostError::UnknownSession)?
+    };
+    let (machine, share) = machine.sign(commitments, msg)?;
+    self.sessions.insert(id.to_vec(), Session::Signed(machine));
+    Ok(share)
+  }
+
+  /// Read a signature share sent by another participant for the specified session
+  pub fn read_share<R: Read>(&self, id: &[u8], reader: &mut R) -> io::Result<SignatureShare<C>> {
+    match self.sessions.get(id) {
+      Some(Session::Signed(machine)) => machine.read_share(reader),
+      _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "no session awaiting shares"))
+    }
+  }
+
+  /// Complete the specified session, ending it
+  pub fn complete(
+    &mut self,
+    id: &[u8],
+    shares: HashMap<u16, SignatureShare<C>>
+  ) -> Result<A::Signature, FrostError> {
+    match self.sessions.remove(id) {
+      Some(Session::Signed(machine)) => machine.complete(shares),
+      Some(session) => {
+        self.sessions.insert(id.to_vec(), session);
+        Err(FrostError::UnknownSession)
+      },
+      None => Err(FrostError::UnknownSession)
+    }
+  }
+
+  /// End the specified session without completing it, returning if it was running
+  /// Its ID remains used, and its nonces are discarded
+  pub fn abort(&mut self, id: &[u8]) -> bool {
+    self.sessions.remove(id).is_some()
+  }
+}
@@ -1,26 +1,57 @@
 use core::fmt;
-use std::{sync::Arc, collections::HashMap};
+use std::{
+  sync::{Arc, Mutex},
+  io::{self, Read, Write},
+  time::Duration,
+  collections::{HashSet, HashMap, BTreeMap}
+};
 
 use rand_core::{RngCore, CryptoRng};
 
-use group::ff::Field;
+use subtle::{Choice, ConstantTimeEq};
+
+use group::{ff::Field, Group};
 
 use transcript::Transcript;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use crate::{
-  curve::Curve,
-  FrostError,
-  MultisigParams, MultisigKeys, MultisigView,
+  curve::{CurveError, Curve, unique_session},
+  FrostError, DeserializeError,
+  MultisigParams, MultisigKeys, MultisigView, Writable,
   algorithm::Algorithm,
+  round::Round,
   validate_map
 };
 
+/// Registry of the nonce commitments which have been signed with, used to refuse signing with
+/// the same preprocess twice, which would reveal the secret share
+/// Implementations may persist registered commitments to maintain this protection across restarts
+pub trait NonceRegistry: Send + Sync {
+  /// Register the serialized nonce commitments as used, returning false if they already were
+  fn register(&self, commitments: &[u8]) -> bool;
+}
+
+/// NonceRegistry which solely keeps registered commitments in memory
+#[derive(Default)]
+pub struct MemoryNonceRegistry(Mutex<HashSet<Vec<u8>>>);
+
+impl NonceRegistry for MemoryNonceRegistry {
+  fn register(&self, commitments: &[u8]) -> bool {
+    self.0.lock().unwrap().insert(commitments.to_vec())
+  }
+}
+
 /// Pairing of an Algorithm with a MultisigKeys instance and this specific signing set
 #[derive(Clone)]
 pub struct Params<C: Curve, A: Algorithm<C>> {
   algorithm: A,
   keys: Arc<MultisigKeys<C>>,
   view: MultisigView<C>,
+  registry: Option<Arc<dyn NonceRegistry>>,
+  session: Option<Vec<u8>>,
 }
 
 // Currently public to enable more complex operations as desired, yet solely used in testing
@@ -30,34 +61,8 @@ impl<C: Curve, A: Algorithm<C>> Params<C, A> {
     keys: Arc<MultisigKeys<C>>,
     included: &[u16],
   ) -> Result<Params<C, A>, FrostError> {
-    let mut included = included.to_vec();
-    (&mut included).sort_unstable();
-
-    // Included < threshold
-    if included.len() < usize::from(keys.params.t) {
-      Err(FrostError::InvalidSigningSet("not enough signers".to_string()))?;
-    }
-    // Invalid index
-    if included[0] == 0 {
-      Err(FrostError::InvalidParticipantIndex(included[0], keys.params.n))?;
-    }
-    // OOB index
-    if included[included.len() - 1] > keys.params.n {
-      Err(FrostError::InvalidParticipantIndex(included[included.len() - 1], keys.params.n))?;
-    }
-    // Same signer included multiple times
-    for i in 0 .. included.len() - 1 {
-      if included[i] == included[i + 1] {
-        Err(FrostError::DuplicatedIndex(included[i].into()))?;
-      }
-    }
-    // Not included
-    if !included.contains(&keys.params.i) {
-      Err(FrostError::InvalidSigningSet("signing despite not being included".to_string()))?;
-    }
-
     // Out of order arguments to prevent additional cloning
-    Ok(Params { algorithm, view: keys.view(&included).unwrap(), keys })
+    Ok(Params { algorithm, view: keys.view(included)?, keys, registry: None, session: None })
   }
 
   pub fn multisig_params(&self) -> MultisigParams {
@@ -69,9 +74,120 @@ impl<C: Curve, A: Algorithm<C>> Params<C, A> {
   }
 }
 
+/// Length of a serialized preprocess for the specified algorithm
+pub fn preprocess_len<C: Curve, A: Algorithm<C>>() -> usize {
+  (2 * C::G_len()) + A::addendum_len()
+}
+
+/// Length of a serialized signature share
+pub fn share_len<C: Curve>() -> usize {
+  C::F_len()
+}
+
+/// A participant's preprocess message, their nonce commitments and the algorithm's addendum
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Preprocess<C: Curve> {
+  pub(crate) commitments: [C::G; 2],
+  pub(crate) addendum: Vec<u8>,
+}
+
+impl<C: Curve> Preprocess<C> {
+  pub fn commitments(&self) -> [C::G; 2] {
+    self.commitments
+  }
+
+  pub fn addendum(&self) -> &[u8] {
+    &self.addendum
+  }
+}
+
+impl<C: Curve> Writable for Preprocess<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&C::G_to_bytes(&self.commitments[0]))?;
+    writer.write_all(&C::G_to_bytes(&self.commitments[1]))?;
+    writer.write_all(&self.addendum)
+  }
+}
+
+/// A participant's share of the signature
+/// Compared in constant time, so aggregators don't leak which shares match
+#[derive(Clone, Copy, Debug)]
+pub struct SignatureShare<C: Curve>(pub(crate) C::F);
+
+impl<C: Curve> SignatureShare<C> {
+  /// Decode a share, as encoded by to_bytes, rejecting invalid scalars
+  pub fn from_bytes(bytes: &[u8]) -> Result<SignatureShare<C>, CurveError> {
+    C::F_from_slice(bytes).map(SignatureShare)
+  }
+
+  /// Encode this share
+  pub fn to_bytes(&self) -> Vec<u8> {
+    C::F_to_bytes(&self.0)
+  }
+}
+
+impl<C: Curve> ConstantTimeEq for SignatureShare<C> {
+  fn ct_eq(&self, other: &SignatureShare<C>) -> Choice {
+    self.0.ct_eq(&other.0)
+  }
+}
+
+impl<C: Curve> PartialEq for SignatureShare<C> {
+  fn eq(&self, other: &SignatureShare<C>) -> bool {
+    self.ct_eq(other).into()
+  }
+}
+impl<C: Curve> Eq for SignatureShare<C> {}
+
+impl<C: Curve> Writable for SignatureShare<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.to_bytes())
+  }
+}
+
 pub(crate) struct PreprocessPackage<C: Curve> {
   pub(crate) nonces: [C::F; 2],
-  pub(crate) serialized: Vec<u8>,
+  pub(crate) preprocess: Preprocess<C>,
+}
+
+// Serialization of a preprocess package, with its nonces encrypted under the specified key
+// It's a random 32-byte salt, each nonce plus a mask derived from the key and salt, and then the
+// preprocess. Signing with a duplicate of this will reveal the secret share, so whoever reads it
+// must enforce it's only used once
+impl<C: Curve> PreprocessPackage<C> {
+  fn write_encrypted<R: RngCore + CryptoRng>(&self, rng: &mut R, key: &[u8], writer: &mut Vec<u8>) {
+    let mut salt = [0; 32];
+    rng.fill_bytes(&mut salt);
+    writer.extend(salt);
+    for (i, nonce) in self.nonces.iter().enumerate() {
+      writer.extend(
+        C::F_to_bytes(&(*nonce + suspension_mask::<C>(key, &salt, u8::try_from(i).unwrap())))
+      );
+    }
+    self.preprocess.write(writer).unwrap();
+  }
+
+  fn read_encrypted<R: Read, A: Algorithm<C>>(
+    algorithm: &A,
+    key: &[u8],
+    reader: &mut R
+  ) -> Result<PreprocessPackage<C>, FrostError> {
+    let mut salt = [0; 32];
+    reader.read_exact(&mut salt).map_err(|e| suspension_error(e, DeserializeError::Truncated))?;
+    let mut nonces = [C::F::zero(); 2];
+    for (i, nonce) in nonces.iter_mut().enumerate() {
+      let masked = C::read_F(reader)
+        .map_err(|e| suspension_error(e, DeserializeError::InvalidScalar))?;
+      *nonce = masked - suspension_mask::<C>(key, &salt, u8::try_from(i).unwrap());
+    }
+    let preprocess = read_preprocess(algorithm, reader)
+      .map_err(|e| suspension_error(e, DeserializeError::InvalidPoint))?;
+    // Also rejects the wrong key, as the decrypted nonces won't match their commitments
+    if preprocess.commitments != nonces.map(|nonce| C::generator_table() * nonce) {
+      Err(FrostError::DeserializeError(DeserializeError::DecryptionFailed))?;
+    }
+    Ok(PreprocessPackage { nonces, preprocess })
+  }
 }
 
 // This library unifies the preprocessing step with signing due to security concerns and to provide
@@ -80,113 +196,141 @@ fn preprocess<R: RngCore + CryptoRng, C: Curve, A: Algorithm<C>>(
   rng: &mut R,
   params: &mut Params<C, A>,
 ) -> PreprocessPackage<C> {
-  let nonces = [
-    C::random_nonce(params.view().secret_share(), &mut *rng),
-    C::random_nonce(params.view().secret_share(), &mut *rng)
-  ];
-  let commitments = [C::GENERATOR_TABLE * nonces[0], C::GENERATOR_TABLE * nonces[1]];
-  let mut serialized = C::G_to_bytes(&commitments[0]);
-  serialized.extend(&C::G_to_bytes(&commitments[1]));
-
-  serialized.extend(
-    &params.algorithm.preprocess_addendum(
-      rng,
-      &params.view,
-      &nonces
-    )
-  );
+  let session = params.session.clone().unwrap_or_else(|| unique_session().to_vec());
+  let nonces = C::random_nonces(params.view().secret_share(), &session, &mut *rng);
+  let commitments = [C::generator_table() * nonces[0], C::generator_table() * nonces[1]];
+  let addendum = params.algorithm.preprocess_addendum(rng, &params.view, &nonces);
+
+  PreprocessPackage { nonces, preprocess: Preprocess { commitments, addendum } }
+}
 
-  PreprocessPackage { nonces, serialized }
+// Read a preprocess, using the algorithm to read its addendum
+pub(crate) fn read_preprocess<R: Read, C: Curve, A: Algorithm<C>>(
+  algorithm: &A,
+  reader: &mut R
+) -> io::Result<Preprocess<C>> {
+  let commitments = [C::read_G(reader)?, C::read_G(reader)?];
+  let addendum = algorithm.read_addendum(reader)?;
+  Ok(Preprocess { commitments, addendum })
 }
 
 #[allow(non_snake_case)]
 struct Package<C: Curve> {
-  B: HashMap<u16, [C::G; 2]>,
+  // Retained, along with the message, to enable suspending signing
+  preprocesses: HashMap<u16, Preprocess<C>>,
+  msg: Vec<u8>,
+  B: BTreeMap<u16, [C::G; 2]>,
   binding: C::F,
   R: C::G,
-  share: Vec<u8>
+  share: C::F
 }
 
-// Has every signer perform the role of the signature aggregator
-// Step 1 was already deprecated by performing nonce generation as needed
-// Step 2 is simply the broadcast round from step 1
-fn sign_with_share<C: Curve, A: Algorithm<C>>(
-  params: &mut Params<C, A>,
-  our_preprocess: PreprocessPackage<C>,
-  mut commitments: HashMap<u16, Vec<u8>>,
-  msg: &[u8],
-) -> Result<(Package<C>, Vec<u8>), FrostError> {
-  let multisig_params = params.multisig_params();
-  validate_map(
-    &mut commitments,
-    &params.view.included,
-    (multisig_params.i, our_preprocess.serialized)
-  )?;
+// Hash the message for the binding factor, mixing in the deployment context if one is set
+// The context is hashed first so it's of a fixed length, preventing any ambiguity with the message
+pub(crate) fn hash_msg<C: Curve>(context: Option<&[u8]>, msg: &[u8]) -> Vec<u8> {
+  match context {
+    Some(context) => C::hash_msg(&[C::hash_msg(context).as_ref(), msg].concat()),
+    None => C::hash_msg(msg)
+  }
+}
+
+// Commitments, binding factor, and group commitment for a signing set
+type Binding<C> = (BTreeMap<u16, [<C as Curve>::G; 2]>, <C as Curve>::F, <C as Curve>::G);
 
+// Calculate the binding factor and group commitment for a signing set, processing addendums
+// Behaves identically for participants and a coordinator, as it only uses public data
+#[allow(non_snake_case)]
+pub(crate) fn process_preprocesses<C: Curve, A: Algorithm<C>>(
+  algorithm: &mut A,
+  view: &MultisigView<C>,
+  offset: Option<C::F>,
+  context: Option<&[u8]>,
+  mut preprocesses: HashMap<u16, Preprocess<C>>,
+  msg: &[u8],
+) -> Result<Binding<C>, FrostError> {
   {
-    let transcript = params.algorithm.transcript();
+    let transcript = algorithm.transcript();
     // Domain separate FROST
     transcript.domain_separate(b"FROST");
     // Include the offset, if one exists
-    if let Some(offset) = params.keys.offset {
+    if let Some(offset) = offset {
       transcript.append_message(b"offset", &C::F_to_bytes(&offset));
     }
   }
 
-  #[allow(non_snake_case)]
-  let mut B = HashMap::<u16, _>::with_capacity(params.view.included.len());
+  let mut B = BTreeMap::<u16, _>::new();
 
   // Get the binding factor
   let mut addendums = HashMap::new();
   let binding = {
-    let transcript = params.algorithm.transcript();
+    let transcript = algorithm.transcript();
     // Parse the commitments
-    for l in &params.view.included {
+    for l in &view.included {
       transcript.append_message(b"participant", &l.to_be_bytes());
 
-      let commitments = commitments.remove(l).unwrap();
-      let mut read_commitment = |c, label| {
-        let commitment = &commitments[c .. (c + C::G_len())];
-        transcript.append_message(label, commitment);
-        C::G_from_slice(commitment).map_err(|_| FrostError::InvalidCommitment(*l))
-      };
+      let preprocess = preprocesses.remove(l).unwrap();
+      transcript.append_message(b"commitment_D", &C::G_to_bytes(&preprocess.commitments[0]));
+      transcript.append_message(b"commitment_E", &C::G_to_bytes(&preprocess.commitments[1]));
+      // Algorithms can then rely on the transcript without rebuilding it from their addendums
+      transcript.append_message(b"addendum", &preprocess.addendum);
 
-      #[allow(non_snake_case)]
-      let mut read_D_E = || Ok(
-        [read_commitment(0, b"commitment_D")?, read_commitment(C::G_len(), b"commitment_E")?]
-      );
-
-      B.insert(*l, read_D_E()?);
-      addendums.insert(*l, commitments[(C::G_len() * 2) ..].to_vec());
+      B.insert(*l, preprocess.commitments);
+      addendums.insert(*l, preprocess.addendum);
     }
 
     // Append the message to the transcript
-    transcript.append_message(b"message", &C::hash_msg(&msg));
+    transcript.append_message(b"message", &hash_msg::<C>(context, msg));
 
     // Calculate the binding factor
     C::hash_binding_factor(&transcript.challenge(b"binding"))
   };
 
   // Process the addendums
-  for l in &params.view.included {
-    params.algorithm.process_addendum(&params.view, *l, &B[l], &addendums[l])?;
+  for l in &view.included {
+    algorithm.process_addendum(view, *l, &B[l], &addendums[l])?;
   }
 
-  #[allow(non_snake_case)]
   let R = {
     B.values().map(|B| B[0]).sum::<C::G>() + (B.values().map(|B| B[1]).sum::<C::G>() * binding)
   };
-  let share = C::F_to_bytes(
-    &params.algorithm.sign_share(
-      &params.view,
-      R,
-      binding,
-      our_preprocess.nonces[0] + (our_preprocess.nonces[1] * binding),
-      msg
-    )
+  Ok((B, binding, R))
+}
+
+// Has every signer perform the role of the signature aggregator
+// Step 1 was already deprecated by performing nonce generation as needed
+// Step 2 is simply the broadcast round from step 1
+fn sign_with_share<C: Curve, A: Algorithm<C>>(
+  params: &mut Params<C, A>,
+  our_preprocess: PreprocessPackage<C>,
+  mut preprocesses: HashMap<u16, Preprocess<C>>,
+  msg: &[u8],
+) -> Result<(Package<C>, SignatureShare<C>), FrostError> {
+  let multisig_params = params.multisig_params();
+  validate_map(
+    &mut preprocesses,
+    &params.view.included,
+    (multisig_params.i, our_preprocess.preprocess)
+  )?;
+
+  #[allow(non_snake_case)]
+  let (B, binding, R) = process_preprocesses(
+    &mut params.algorithm,
+    &params.view,
+    params.keys.offset,
+    params.keys.context(),
+    preprocesses.clone(),
+    msg
+  )?;
+  let share = params.algorithm.sign_share(
+    &params.view,
+    &B,
+    R,
+    binding,
+    our_preprocess.nonces[0] + (our_preprocess.nonces[1] * binding),
+    msg
   );
 
-  Ok((Package { B, binding, R, share: share.clone() }, share))
+  Ok((Package { preprocesses, msg: msg.to_vec(), B, binding, R, share }, SignatureShare(share)))
 }
 
 // This doesn't check the signing set is as expected and unexpected changes can cause false blames
@@ -195,15 +339,15 @@ fn sign_with_share<C: Curve, A: Algorithm<C>>(
 fn complete<C: Curve, A: Algorithm<C>>(
   sign_params: &Params<C, A>,
   sign: Package<C>,
-  mut shares: HashMap<u16, Vec<u8>>,
+  mut shares: HashMap<u16, SignatureShare<C>>,
 ) -> Result<A::Signature, FrostError> {
   let params = sign_params.multisig_params();
-  validate_map(&mut shares, &sign_params.view.included, (params.i(), sign.share))?;
+  validate_map(&mut shares, &sign_params.view.included, (params.i(), SignatureShare(sign.share)))?;
 
   let mut responses = HashMap::new();
   let mut sum = C::F::zero();
   for l in &sign_params.view.included {
-    let part = C::F_from_slice(&shares[l]).map_err(|_| FrostError::InvalidShare(*l))?;
+    let part = shares[l].0;
     sum += part;
     responses.insert(*l, part);
   }
@@ -213,63 +357,105 @@ fn complete<C: Curve, A: Algorithm<C>>(
   // It also acts as an integrity check of this library's signing function
   let res = sign_params.algorithm.verify(sign_params.view.group_key, sign.R, sum);
   if let Some(res) = res {
+    trace_event!(info, "completed signing");
     return Ok(res);
   }
+  trace_event!(warn, "aggregate signature failed to verify, verifying individual shares");
 
   // Find out who misbehaved. It may be beneficial to randomly sort this to have detection be
   // within n / 2 on average, and not gameable to n, though that should be minor
-  for l in &sign_params.view.included {
-    if !sign_params.algorithm.verify_share(
-      sign_params.view.verification_share(*l),
-      sign.B[l][0] + (sign.B[l][1] * sign.binding),
-      responses[l]
-    ) {
-      Err(FrostError::InvalidShare(*l))?;
-    }
+  // Blames the lowest index with an invalid share, regardless of if this is done in parallel
+  let invalid = iter!(&sign_params.view.included).filter(|l| !sign_params.algorithm.verify_share(
+    sign_params.view.verification_share(**l),
+    sign.B[*l][0] + (sign.B[*l][1] * sign.binding),
+    responses[*l]
+  )).min();
+  if let Some(l) = invalid {
+    trace_event!(warn, participant = l, "invalid signature share");
+    Err(FrostError::InvalidShare(*l))?;
   }
 
   // If everyone has a valid share and there were enough participants, this should've worked
-  Err(
-    FrostError::InternalError(
-      "everyone had a valid share yet the signature was still invalid".to_string()
-    )
+  trace_event!(error, "every signature share was valid yet the signature wasn't");
+  Err(FrostError::InvalidSignature)
+}
+
+// Mask for a nonce suspended at rest, derived from the caller's key and a random salt
+fn suspension_mask<C: Curve>(key: &[u8], salt: &[u8; 32], i: u8) -> C::F {
+  C::hash_to_F(b"FROST suspended nonce", &[key, salt, &[i]].concat())
+}
+
+// Map an error from reading suspended state, which is either truncated or invalid
+fn suspension_error(error: io::Error, invalid: DeserializeError) -> FrostError {
+  FrostError::DeserializeError(
+    if error.kind() == io::ErrorKind::UnexpectedEof { DeserializeError::Truncated } else { invalid }
   )
 }
 
+fn write_included<W: Write>(included: &[u16], writer: &mut W) -> io::Result<()> {
+  writer.write_all(&u16::try_from(included.len()).unwrap().to_be_bytes())?;
+  for l in included {
+    writer.write_all(&l.to_be_bytes())?;
+  }
+  Ok(())
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> Result<u16, FrostError> {
+  let mut bytes = [0; 2];
+  reader.read_exact(&mut bytes).map_err(|e| suspension_error(e, DeserializeError::Truncated))?;
+  Ok(u16::from_be_bytes(bytes))
+}
+
+fn read_included<R: Read>(reader: &mut R) -> Result<Vec<u16>, FrostError> {
+  (0 .. read_u16(reader)?).map(|_| read_u16(reader)).collect()
+}
+
 pub trait PreprocessMachine {
+  /// Preprocess message for this machine
+  type Preprocess: Clone + PartialEq + Writable;
   type Signature: Clone + PartialEq + fmt::Debug;
-  type SignMachine: SignMachine<Self::Signature>;
+  type SignMachine: SignMachine<Self::Signature, Preprocess = Self::Preprocess>;
 
   /// Perform the preprocessing round required in order to sign
-  /// Returns a byte vector which must be transmitted to all parties selected for this signing
-  /// process, over an authenticated channel
+  /// Returns a preprocess message which must be transmitted to all parties selected for this
+  /// signing process, over an authenticated channel
   fn preprocess<R: RngCore + CryptoRng>(
     self,
     rng: &mut R
-  ) -> (Self::SignMachine, Vec<u8>);
+  ) -> (Self::SignMachine, Self::Preprocess);
 }
 
 pub trait SignMachine<S> {
-  type SignatureMachine: SignatureMachine<S>;
+  /// Preprocess message for this machine
+  type Preprocess: Clone + PartialEq + Writable;
+  /// SignatureShare message for this machine
+  type SignatureShare: Clone + PartialEq + Writable;
+  type SignatureMachine: SignatureMachine<S, SignatureShare = Self::SignatureShare>;
+
+  /// Read a preprocess message sent by another participant
+  fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Self::Preprocess>;
 
   /// Sign a message
-  /// Takes in the participant's commitments, which are expected to be in a Vec where participant
-  /// index = Vec index. None is expected at index 0 to allow for this. None is also expected at
-  /// index i which is locally handled. Returns a byte vector representing a share of the signature
-  /// for every other participant to receive, over an authenticated channel
+  /// Takes in the participants' preprocess messages, keyed by participant index. Returns a share
+  /// of the signature for every other participant to receive, over an authenticated channel
   fn sign(
     self,
-    commitments: HashMap<u16, Vec<u8>>,
+    commitments: HashMap<u16, Self::Preprocess>,
     msg: &[u8],
-  ) -> Result<(Self::SignatureMachine, Vec<u8>), FrostError>;
+  ) -> Result<(Self::SignatureMachine, Self::SignatureShare), FrostError>;
 }
 
 pub trait SignatureMachine<S> {
+  /// SignatureShare message for this machine
+  type SignatureShare: Clone + PartialEq + Writable;
+
+  /// Read a signature share sent by another participant
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<Self::SignatureShare>;
+
   /// Complete signing
-  /// Takes in everyone elses' shares submitted to us as a Vec, expecting participant index =
-  /// Vec index with None at index 0 and index i. Returns a byte vector representing the serialized
+  /// Takes in everyone elses' shares submitted to us, keyed by participant index. Returns the
   /// signature
-  fn complete(self, shares: HashMap<u16, Vec<u8>>) -> Result<S, FrostError>;
+  fn complete(self, shares: HashMap<u16, Self::SignatureShare>) -> Result<S, FrostError>;
 }
 
 /// State machine which manages signing for an arbitrary signature algorithm
@@ -285,6 +471,17 @@ pub struct AlgorithmSignMachine<C: Curve, A: Algorithm<C>> {
 pub struct AlgorithmSignatureMachine<C: Curve, A: Algorithm<C>> {
   params: Params<C, A>,
   sign: Package<C>,
+  // Shares received via add_share, which have already been verified
+  shares: HashMap<u16, C::F>,
+}
+
+/// Progress of incrementally aggregating signature shares
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Progress {
+  /// Shares are still needed from the specified participants
+  Missing(Vec<u16>),
+  /// Every share has been received, and the signature can be finalized
+  Complete,
 }
 
 impl<C: Curve, A: Algorithm<C>> AlgorithmMachine<C, A> {
@@ -297,41 +494,344 @@ impl<C: Curve, A: Algorithm<C>> AlgorithmMachine<C, A> {
     Ok(AlgorithmMachine { params: Params::new(algorithm, keys, included)? })
   }
 
+  /// Register the nonce commitments of the preprocess this machine signs with, refusing to sign
+  /// if they were already registered
+  pub fn with_registry(mut self, registry: Arc<dyn NonceRegistry>) -> AlgorithmMachine<C, A> {
+    self.params.registry = Some(registry);
+    self
+  }
+
+  /// Set the ID of this signing session, which is mixed into the nonces alongside the RNG's output
+  /// If the RNG is broken, nonces are only unique if the session ID is, so an ID must never be
+  /// reused by these keys. Without an ID, data unique to the preprocess call is used instead, which
+  /// prevents reproducing a session from a deterministic RNG, as an auditable ceremony does
+  pub fn with_session(mut self, session: &[u8]) -> AlgorithmMachine<C, A> {
+    self.params.session = Some(session.to_vec());
+    self
+  }
+
+  /// Resume a machine suspended with AlgorithmSignMachine::suspend
+  /// This machine must be created with the same algorithm, keys, and signing set as the suspended
+  /// one. The registry is used to refuse signing with the suspended state more than once, and
+  /// should be persisted for this to hold across restarts
+  pub fn resume<R: Read, RNG: RngCore + CryptoRng>(
+    self,
+    rng: &mut RNG,
+    registry: Arc<dyn NonceRegistry>,
+    key: &[u8],
+    reader: &mut R
+  ) -> Result<AlgorithmSignMachine<C, A>, FrostError> {
+    let mut params = self.params;
+    let included = read_included(reader)?;
+    let preprocess = PreprocessPackage::read_encrypted(&params.algorithm, key, reader)?;
+
+    // Replay the algorithm's preprocessing, discarding its output, to restore any state it sets
+    params.algorithm.preprocess_addendum(rng, &params.view, &preprocess.nonces);
+    params.registry = Some(registry);
+    AlgorithmSignMachine { params, preprocess }.select(&included)
+  }
+
+  /// Resume a machine suspended with AlgorithmSignatureMachine::suspend
+  /// This machine must be created with the same algorithm, keys, and signing set as the suspended
+  /// one. The algorithm's state is restored by replaying it with zero nonces, which algorithms
+  /// must support as documented by the Algorithm trait
+  pub fn resume_signature<R: Read, RNG: RngCore + CryptoRng>(
+    self,
+    rng: &mut RNG,
+    reader: &mut R
+  ) -> Result<AlgorithmSignatureMachine<C, A>, FrostError> {
+    // Replay the algorithm's preprocessing with dummy nonces, as no output of the algorithm which
+    // depends on our nonces is used from here on, and the Algorithm trait requires its state not
+    // depend on them
+    let mut params = self.params;
+    params.algorithm.preprocess_addendum(rng, &params.view, &[C::F::zero(); 2]);
+    let mut machine = AlgorithmSignMachine {
+      params,
+      preprocess: PreprocessPackage {
+        nonces: [C::F::zero(); 2],
+        preprocess: Preprocess { commitments: [C::G::identity(); 2], addendum: vec![] }
+      }
+    }.select(&read_included(reader)?)?;
+    let params = &mut machine.params;
+
+    let mut len = [0; 4];
+    reader.read_exact(&mut len).map_err(|e| suspension_error(e, DeserializeError::Truncated))?;
+    let len = u32::from_be_bytes(len);
+    let mut msg = vec![];
+    reader.by_ref().take(len.into()).read_to_end(&mut msg)
+      .map_err(|e| suspension_error(e, DeserializeError::Truncated))?;
+    if msg.len() != usize::try_from(len).unwrap() {
+      Err(FrostError::DeserializeError(DeserializeError::Truncated))?;
+    }
+
+    let mut preprocesses = HashMap::new();
+    for l in &params.view.included {
+      preprocesses.insert(
+        *l,
+        read_preprocess(&params.algorithm, reader)
+          .map_err(|e| suspension_error(e, DeserializeError::InvalidPoint))?
+      );
+    }
+    let share = C::read_F(reader)
+      .map_err(|e| suspension_error(e, DeserializeError::InvalidScalar))?;
+
+    #[allow(non_snake_case)]
+    let (B, binding, R) = process_preprocesses(
+      &mut params.algorithm,
+      &params.view,
+      params.keys.offset,
+      params.keys.context(),
+      preprocesses.clone(),
+      &msg
+    )?;
+    // Restore the algorithm's signing state, discarding the share as ours was already created
+    params.algorithm.sign_share(&params.view, &B, R, binding, C::F::zero(), &msg);
+
+    let mut machine = AlgorithmSignatureMachine {
+      params: machine.params,
+      sign: Package { preprocesses, msg, B, binding, R, share },
+      shares: HashMap::new()
+    };
+    for _ in 0 .. read_u16(reader)? {
+      let l = read_u16(reader)?;
+      let share = machine.read_share(reader)
+        .map_err(|e| suspension_error(e, DeserializeError::InvalidScalar))?;
+      machine.add_share(l, share)?;
+    }
+    Ok(machine)
+  }
+
+  // Signing with a preprocess which was already signed with reveals the secret share. Callers
+  // must guarantee the provided preprocess is freshly generated and never used again
   pub(crate) fn unsafe_override_preprocess(
     self,
     preprocess: PreprocessPackage<C>
-  ) -> (AlgorithmSignMachine<C, A>, Vec<u8>) {
-    let serialized = preprocess.serialized.clone();
-    (AlgorithmSignMachine { params: self.params, preprocess }, serialized)
+  ) -> (AlgorithmSignMachine<C, A>, Preprocess<C>) {
+    let message = preprocess.preprocess.clone();
+    (AlgorithmSignMachine { params: self.params, preprocess }, message)
   }
 }
 
 impl<C: Curve, A: Algorithm<C>> PreprocessMachine for AlgorithmMachine<C, A> {
+  type Preprocess = Preprocess<C>;
   type Signature = A::Signature;
   type SignMachine = AlgorithmSignMachine<C, A>;
 
   fn preprocess<R: RngCore + CryptoRng>(
     self,
     rng: &mut R
-  ) -> (Self::SignMachine, Vec<u8>) {
+  ) -> (Self::SignMachine, Preprocess<C>) {
+    trace_span!("sign", round = "preprocess", i = self.params.multisig_params().i());
     let mut params = self.params;
     let preprocess = preprocess::<R, C, A>(rng, &mut params);
-    let serialized = preprocess.serialized.clone();
-    (AlgorithmSignMachine { params, preprocess }, serialized)
+    trace_event!(debug, included = ?params.view.included, "generated preprocess");
+    let message = preprocess.preprocess.clone();
+    (AlgorithmSignMachine { params, preprocess }, message)
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> AlgorithmSignMachine<C, A> {
+  /// Start the deadline for the messages this machine awaits, the rest of the signing set's
+  /// preprocesses
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    let i = self.params.multisig_params().i();
+    Round::new(self.params.view.included.iter().filter(|l| **l != i).cloned().collect(), timeout)
+  }
+
+  /// The signing set, sorted
+  pub fn included(&self) -> &[u16] {
+    &self.params.view.included
+  }
+
+  /// Length of the messages this machine awaits, the rest of the signing set's preprocesses
+  pub fn message_len(&self) -> usize {
+    preprocess_len::<C, A>()
+  }
+
+  /// Suspend this machine, serializing its state so signing can be resumed after a restart, or
+  /// by another process. The nonces are encrypted under the specified key, which must be kept
+  /// secret. Signing with this state more than once reveals the secret share. Don't copy it, and
+  /// resume it with a registry shared by every process which may sign with it
+  pub fn suspend<R: RngCore + CryptoRng>(self, rng: &mut R, key: &[u8]) -> Vec<u8> {
+    let mut serialized = vec![];
+    write_included(&self.params.view.included, &mut serialized).unwrap();
+    self.preprocess.write_encrypted(rng, key, &mut serialized);
+    serialized
+  }
+
+  /// Narrow the signing set to a subset of the one this machine was created with
+  /// This enables preprocessing with every participant who may be available, then signing with
+  /// those who actually responded. Algorithms whose addendums depend on the signing set, such as
+  /// by committing to the lagranged secret share, don't support having their signing set narrowed
+  pub fn select(self, included: &[u16]) -> Result<AlgorithmSignMachine<C, A>, FrostError> {
+    let mut params = self.params;
+    let view = params.keys.view(included)?;
+    for l in &view.included {
+      if !params.view.included.contains(l) {
+        Err(FrostError::NotIncluded(*l))?;
+      }
+    }
+    params.view = view;
+    Ok(AlgorithmSignMachine { params, preprocess: self.preprocess })
+  }
+
+  /// Rerandomize the key signed for to the group key plus the randomizer times the generator, as
+  /// required by RedDSA's spend authorization signatures
+  /// Every signer must be supplied the same randomizer for this signature, and a coordinator must
+  /// offset itself by it. Like select, this isn't supported by algorithms whose addendums depend
+  /// on the group key. The randomizer isn't suspended, so it must be reapplied after resuming
+  pub fn randomize(self, randomizer: C::F) -> AlgorithmSignMachine<C, A> {
+    let mut params = self.params;
+    params.keys = Arc::new(params.keys.offset(randomizer));
+    // The signing set was already validated when creating this view
+    params.view = params.keys.view(&params.view.included).unwrap();
+    AlgorithmSignMachine { params, preprocess: self.preprocess }
   }
 }
 
 impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachine<C, A> {
+  type Preprocess = Preprocess<C>;
+  type SignatureShare = SignatureShare<C>;
   type SignatureMachine = AlgorithmSignatureMachine<C, A>;
 
+  fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Preprocess<C>> {
+    read_preprocess(&self.params.algorithm, reader)
+  }
+
   fn sign(
     self,
-    commitments: HashMap<u16, Vec<u8>>,
+    commitments: HashMap<u16, Preprocess<C>>,
     msg: &[u8]
-  ) -> Result<(Self::SignatureMachine, Vec<u8>), FrostError> {
+  ) -> Result<(Self::SignatureMachine, SignatureShare<C>), FrostError> {
+    trace_span!("sign", round = "sign", i = self.params.multisig_params().i());
+    trace_event!(debug, preprocesses = commitments.len(), "received preprocesses");
     let mut params = self.params;
-    let (sign, serialized) = sign_with_share(&mut params, self.preprocess, commitments, msg)?;
-    Ok((AlgorithmSignatureMachine { params, sign }, serialized))
+    if let Some(registry) = params.registry.as_ref() {
+      let ours = self.preprocess.preprocess.commitments;
+      if !registry.register(&[C::G_to_bytes(&ours[0]), C::G_to_bytes(&ours[1])].concat()) {
+        trace_event!(warn, "refusing to sign with a reused preprocess");
+        Err(FrostError::ReusedPreprocess)?;
+      }
+    }
+    let (sign, share) = sign_with_share(&mut params, self.preprocess, commitments, msg)?;
+    trace_event!(debug, included = ?params.view.included, "generated signature share");
+    Ok((AlgorithmSignatureMachine { params, sign, shares: HashMap::new() }, share))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> AlgorithmSignatureMachine<C, A> {
+  // The share is solely used for our own aggregation, so an invalid share only causes our own
+  // aggregation to fail
+  pub(crate) fn unsafe_override_share(self, share: C::F) -> AlgorithmSignatureMachine<C, A> {
+    let mut sign = self.sign;
+    sign.share = share;
+    AlgorithmSignatureMachine { params: self.params, sign, shares: self.shares }
+  }
+
+  // Verify a share from a signer who isn't a participant, yet holds part of a participant's share,
+  // against their own commitments and verification share
+  pub(crate) fn verify_partial_share(
+    &self,
+    verification_share: C::G,
+    commitments: [C::G; 2],
+    share: C::F
+  ) -> bool {
+    self.params.algorithm.verify_share(
+      verification_share,
+      commitments[0] + (commitments[1] * self.sign.binding),
+      share
+    )
+  }
+
+  /// Suspend this machine, serializing its state so signing can be resumed after a restart
+  /// This contains no secret material, as our share was already created
+  pub fn suspend(self) -> Vec<u8> {
+    let mut serialized = vec![];
+    write_included(&self.params.view.included, &mut serialized).unwrap();
+    serialized.extend(u32::try_from(self.sign.msg.len()).unwrap().to_be_bytes());
+    serialized.extend(&self.sign.msg);
+    for l in &self.params.view.included {
+      self.sign.preprocesses[l].write(&mut serialized).unwrap();
+    }
+    serialized.extend(C::F_to_bytes(&self.sign.share));
+
+    let mut shares = self.shares.iter().collect::<Vec<_>>();
+    shares.sort_by_key(|(l, _)| **l);
+    serialized.extend(u16::try_from(shares.len()).unwrap().to_be_bytes());
+    for (l, share) in shares {
+      serialized.extend(l.to_be_bytes());
+      serialized.extend(C::F_to_bytes(share));
+    }
+    serialized
+  }
+
+  /// The signing set, sorted
+  pub fn included(&self) -> &[u16] {
+    &self.params.view.included
+  }
+
+  /// Length of the messages this machine awaits, the rest of the signing set's shares
+  pub fn message_len(&self) -> usize {
+    share_len::<C>()
+  }
+
+  /// Start the deadline for the messages this machine awaits, the shares yet to be added
+  pub fn start_round(&self, timeout: Duration) -> Round {
+    Round::new(self.missing(), timeout)
+  }
+
+  /// Participants whose shares have yet to be added
+  pub fn missing(&self) -> Vec<u16> {
+    let i = self.params.multisig_params().i();
+    self.params.view.included.iter().filter(
+      |l| (**l != i) && !self.shares.contains_key(l)
+    ).cloned().collect()
+  }
+
+  /// Add a share from the specified participant, verifying it on arrival
+  /// Unlike complete, this immediately identifies a participant who sent an invalid share, at the
+  /// cost of verifying every share individually
+  pub fn add_share(&mut self, l: u16, share: SignatureShare<C>) -> Result<Progress, FrostError> {
+    if !self.params.view.included.contains(&l) {
+      Err(FrostError::NotIncluded(l))?;
+    }
+    // Our own share is already known
+    if (l == self.params.multisig_params().i()) || self.shares.contains_key(&l) {
+      Err(FrostError::DuplicatedIndex(l.into()))?;
+    }
+
+    let share = share.0;
+    if !self.params.algorithm.verify_share(
+      self.params.view.verification_share(l),
+      self.sign.B[&l][0] + (self.sign.B[&l][1] * self.sign.binding),
+      share
+    ) {
+      trace_event!(warn, participant = l, "invalid signature share");
+      Err(FrostError::InvalidShare(l))?;
+    }
+    self.shares.insert(l, share);
+
+    let missing = self.missing();
+    trace_event!(debug, participant = l, missing = ?missing, "added signature share");
+    Ok(if missing.is_empty() { Progress::Complete } else { Progress::Missing(missing) })
+  }
+
+  /// Complete signing once every share has been added
+  pub fn finalize(self) -> Result<A::Signature, FrostError> {
+    if let Some(l) = self.missing().first() {
+      Err(FrostError::MissingParticipant(*l))?;
+    }
+
+    let i = self.params.multisig_params().i();
+    let sum = self.shares.values().fold(self.sign.share, |sum, share| sum + share);
+    if let Some(sig) = self.params.algorithm.verify(self.params.view.group_key, self.sign.R, sum) {
+      trace_event!(info, "completed signing");
+      return Ok(sig);
+    }
+
+    // Every other share was already verified, so only our own can be invalid
+    trace_event!(error, participant = i, "our own signature share was invalid");
+    Err(FrostError::InvalidShare(i))
   }
 }
 
@@ -339,7 +839,15 @@ impl<
   C: Curve,
   A: Algorithm<C>
 > SignatureMachine<A::Signature> for AlgorithmSignatureMachine<C, A> {
-  fn complete(self, shares: HashMap<u16, Vec<u8>>) -> Result<A::Signature, FrostError> {
+  type SignatureShare = SignatureShare<C>;
+
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
+    Ok(SignatureShare(C::read_F(reader)?))
+  }
+
+  fn complete(self, shares: HashMap<u16, SignatureShare<C>>) -> Result<A::Signature, FrostError> {
+    trace_span!("sign", round = "complete", i = self.params.multisig_params().i());
+    trace_event!(debug, shares = shares.len(), "received signature shares");
     complete(&self.params, self.sign, shares)
   }
 }
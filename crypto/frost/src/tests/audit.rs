@@ -0,0 +1,98 @@
+use std::{sync::Arc, collections::{HashMap, BTreeMap}};
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, MultisigParams, Writable,
+  algorithm::Schnorr,
+  key_gen::{KeyGenVariant, KeyGenMachine},
+  sign::{PreprocessMachine, SignMachine, AlgorithmMachine},
+  audit::AuditSeed,
+  tests::{PARTICIPANTS, THRESHOLD, clone_without, schnorr::TestHram}
+};
+
+// Run a key generation and a signing session from the seed, returning every message sent
+fn ceremony<C: Curve>(seed: AuditSeed) -> Vec<Vec<u8>> {
+  let mut transcript = vec![];
+
+  let mut commitments = BTreeMap::new();
+  let machines = (1 ..= PARTICIPANTS).map(|i| {
+    let mut rng = seed.rng(i, b"key_gen");
+    let (machine, these_commitments) = KeyGenMachine::<C>::new(
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+      "FROST Test audit".to_string(),
+      KeyGenVariant::Frost
+    ).generate_coefficients(&mut rng);
+    commitments.insert(i, these_commitments.serialize());
+    (i, (rng, machine))
+  }).collect::<BTreeMap<_, _>>();
+  transcript.extend(commitments.values().cloned());
+
+  let commitments = commitments.into_iter().collect::<HashMap<_, _>>();
+  let mut shares = BTreeMap::new();
+  let machines = machines.into_iter().map(|(i, (mut rng, machine))| {
+    let received = clone_without(&commitments, &i).drain().map(
+      |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let (machine, these_shares) = machine.generate_secret_shares(&mut rng, received).unwrap();
+    shares.insert(i, these_shares.into_iter().collect::<BTreeMap<_, _>>());
+    (i, (rng, machine))
+  }).collect::<BTreeMap<_, _>>();
+  for these_shares in shares.values() {
+    transcript.extend(these_shares.values().map(|share| share.serialize()));
+  }
+
+  let mut keys = BTreeMap::new();
+  for (i, (mut rng, machine)) in machines {
+    let our_shares = shares.iter().filter(|(l, _)| **l != i).map(
+      |(l, shares)| (*l, shares[&i].clone())
+    ).collect();
+    keys.insert(i, Arc::new(machine.complete(&mut rng, our_shares).unwrap()));
+  }
+
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let mut preprocesses = HashMap::new();
+  let machines = included.iter().map(|i| {
+    let (machine, preprocess) = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      keys[i].clone(),
+      &included
+    ).unwrap().with_session(b"sign").preprocess(&mut seed.rng(*i, b"sign"));
+    preprocesses.insert(*i, preprocess);
+    (*i, machine)
+  }).collect::<BTreeMap<_, _>>();
+  for i in &included {
+    transcript.push(preprocesses[i].serialize());
+  }
+
+  for (i, machine) in machines {
+    let (_, share) = machine.sign(clone_without(&preprocesses, &i), b"Hello, World!").unwrap();
+    transcript.push(share.serialize());
+  }
+
+  transcript
+}
+
+pub fn test_audit<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let mut seed = [0; 32];
+  rng.fill_bytes(&mut seed);
+  let seed = AuditSeed::new(seed);
+  assert!(seed.verify(&seed.commitment()));
+  assert!(!AuditSeed::new([0; 32]).verify(&seed.commitment()));
+
+  // The same seed replays the same ceremony, byte for byte
+  let transcript = ceremony::<C>(seed);
+  assert_eq!(transcript, ceremony::<C>(seed));
+
+  // Distinct seeds produce distinct ceremonies
+  let mut other = [0; 32];
+  rng.fill_bytes(&mut other);
+  assert!(transcript != ceremony::<C>(AuditSeed::new(other)));
+
+  // Participants' RNGs are independent of each other
+  let mut first = [0; 32];
+  seed.rng(1, b"key_gen").fill_bytes(&mut first);
+  let mut second = [0; 32];
+  seed.rng(2, b"key_gen").fill_bytes(&mut second);
+  assert!(first != second);
+}
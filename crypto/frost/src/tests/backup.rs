@@ -0,0 +1,37 @@
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{Curve, FrostError, backup::{BackupShare, restore}, tests::key_gen};
+
+pub fn test_backup<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  assert!(keys[&1].backup(&mut *rng, 6, 5).is_err());
+
+  let backups = keys[&1].backup(&mut *rng, 3, 5).unwrap();
+  for backup in &backups {
+    assert_eq!(backup.params(), keys[&1].params());
+    assert_eq!(&BackupShare::<C>::deserialize(&backup.serialize()).unwrap(), backup);
+  }
+
+  // Any 3 backups restore the keys
+  let subset = [backups[0].clone(), backups[2].clone(), backups[4].clone()];
+  assert_eq!(&restore(&subset).unwrap(), &*keys[&1]);
+  assert_eq!(&restore(&backups).unwrap(), &*keys[&1]);
+
+  assert!(matches!(restore(&backups[.. 2]), Err(FrostError::NotEnoughSigners(3, 2))));
+  assert!(matches!(
+    restore(&[backups[0].clone(), backups[1].clone(), backups[1].clone()]),
+    Err(FrostError::DuplicatedIndex(2))
+  ));
+
+  // Backups of another participant, or of another backup, can't be mixed in
+  let other = keys[&2].backup(&mut *rng, 3, 5).unwrap();
+  assert!(matches!(
+    restore(&[backups[0].clone(), backups[1].clone(), other[2].clone()]),
+    Err(FrostError::InvalidShare(3))
+  ));
+  let again = keys[&1].backup(&mut *rng, 3, 5).unwrap();
+  assert!(matches!(
+    restore(&[backups[0].clone(), backups[1].clone(), again[2].clone()]),
+    Err(FrostError::InvalidShare(1))
+  ));
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, FrostError, MultisigParams, Writable,
+  key_gen::{KeyGenVariant, KeyGenMachine, SecretShareMachine, SecretShare},
+  tests::{PARTICIPANTS, THRESHOLD, clone_without}
+};
+
+const CONTEXT: &str = "FROST Test blame";
+
+fn key_gen_machine<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  context: &str,
+  i: u16
+) -> (SecretShareMachine<C>, Vec<u8>) {
+  let (machine, commitments) = KeyGenMachine::<C>::new(
+    MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+    context.to_string(),
+    KeyGenVariant::Frost
+  ).generate_coefficients(rng);
+  (machine, commitments.serialize())
+}
+
+// When multiple participants misbehave, the lowest index is blamed, regardless of the order
+// their messages were iterated in
+// HashMaps are randomly seeded per instance, so each case is run several times
+pub fn test_blame<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  // Proofs of knowledge for another context are invalid
+  for _ in 0 .. 4 {
+    let (machine, _) = key_gen_machine::<_, C>(&mut *rng, CONTEXT, 1);
+    let commitments = (2 ..= PARTICIPANTS).map(|l| {
+      let context = if (l == 3) || (l == PARTICIPANTS) {
+        "FROST Test blame (invalid)"
+      } else {
+        CONTEXT
+      };
+      let (_, commitments) = key_gen_machine::<_, C>(&mut *rng, context, l);
+      (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    }).collect();
+    assert!(matches!(
+      machine.generate_secret_shares(&mut *rng, commitments),
+      Err(FrostError::InvalidProofOfKnowledge(3))
+    ));
+  }
+
+  // Secret shares which don't match their sender's commitments
+  for _ in 0 .. 2 {
+    let mut machines = HashMap::new();
+    let mut commitments = HashMap::new();
+    for i in 1 ..= PARTICIPANTS {
+      let (machine, these_commitments) = key_gen_machine::<_, C>(&mut *rng, CONTEXT, i);
+      machines.insert(i, machine);
+      commitments.insert(i, these_commitments);
+    }
+
+    let mut shares = HashMap::new();
+    let mut machines = machines.drain().map(|(i, machine)| {
+      let received = clone_without(&commitments, &i).drain().map(
+        |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+      ).collect();
+      let (machine, mut these_shares) =
+        machine.generate_secret_shares(&mut *rng, received).unwrap();
+      shares.insert(i, these_shares.remove(&1));
+      (i, machine)
+    }).collect::<HashMap<_, _>>();
+
+    let mut received = (2 ..= PARTICIPANTS).map(
+      |l| (l, shares.remove(&l).unwrap().unwrap())
+    ).collect::<HashMap<_, _>>();
+    for l in [PARTICIPANTS - 1, 2] {
+      received.insert(l, SecretShare(C::F::random(&mut *rng)));
+    }
+    assert!(matches!(
+      machines.remove(&1).unwrap().complete(&mut *rng, received),
+      Err(FrostError::InvalidCommitment(2))
+    ));
+  }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Writable,
+  curve::Bls12381,
+  bls::{BlsSignature, BlsMachine},
+  tests::{THRESHOLD, key_gen, sign}
+};
+
+pub fn test_bls<R: RngCore + CryptoRng>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, Bls12381>(&mut *rng);
+  let group_key = keys[&1].group_key();
+
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let machines = included.iter().map(
+    |i| (*i, BlsMachine::new(keys[i].clone(), &included).unwrap())
+  ).collect::<HashMap<_, _>>();
+
+  let sig = sign(&mut *rng, machines, MESSAGE);
+  assert!(sig.verify(group_key, MESSAGE));
+  assert!(!sig.verify(group_key, b"Goodbye, World!"));
+  assert_eq!(BlsSignature::deserialize(&sig.serialize()).unwrap(), sig);
+}
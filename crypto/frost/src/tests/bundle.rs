@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, FrostError, MultisigParams, Writable,
+  key_gen::KeyGenVariant,
+  bundle::KeyBundleMachine,
+  tests::{PARTICIPANTS, THRESHOLD, clone_without}
+};
+
+// Run key generation for the specified curve under every participant's driver
+fn key_gen<R: RngCore + CryptoRng, C: Curve + 'static>(
+  rng: &mut R,
+  drivers: &mut HashMap<u16, KeyBundleMachine>
+) {
+  let mut commitments = HashMap::new();
+  let mut machines = drivers.iter_mut().map(|(i, driver)| {
+    let (machine, these_commitments) = driver.key_gen::<C>().unwrap().generate_coefficients(rng);
+    commitments.insert(*i, these_commitments.serialize());
+    (*i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut shares = HashMap::new();
+  let machines = machines.drain().map(|(i, machine)| {
+    let commitments = clone_without(&commitments, &i).drain().map(
+      |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let (machine, these_shares) = machine.generate_secret_shares(rng, commitments).unwrap();
+    shares.insert(i, these_shares);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut group_key = None;
+  for (i, machine) in machines {
+    let our_shares = shares.iter().filter(|(l, _)| **l != i).map(
+      |(l, shares)| (*l, shares[&i].clone())
+    ).collect();
+    let keys = drivers.get_mut(&i).unwrap().complete(rng, machine, our_shares).unwrap();
+    if group_key.is_none() {
+      group_key = Some(keys.group_key());
+    }
+    assert_eq!(group_key.unwrap(), keys.group_key());
+  }
+}
+
+pub fn test_bundle<R: RngCore + CryptoRng, C1: Curve + 'static, C2: Curve + 'static>(
+  rng: &mut R
+) {
+  let mut drivers = (1 ..= PARTICIPANTS).map(|i| (
+    i,
+    KeyBundleMachine::new(
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+      "FROST Test bundle".to_string(),
+      KeyGenVariant::Frost
+    )
+  )).collect::<HashMap<_, _>>();
+
+  key_gen::<_, C1>(&mut *rng, &mut drivers);
+  // Each curve may only be generated once per bundle
+  assert!(matches!(drivers.get_mut(&1).unwrap().key_gen::<C1>(), Err(FrostError::DuplicatedCurve)));
+  key_gen::<_, C2>(&mut *rng, &mut drivers);
+
+  // Every participant agrees on the bundle
+  let hashes = drivers.iter().map(|(i, driver)| (*i, driver.session_hash())).collect();
+  for (i, driver) in &drivers {
+    driver.verify_session_hashes(clone_without(&hashes, i)).unwrap();
+  }
+
+  let mut group_keys = None;
+  for (i, driver) in drivers {
+    let bundle = driver.finish().unwrap();
+    assert_eq!(bundle.params().i(), i);
+    assert_eq!(bundle.curves().len(), 2);
+    let keys = (bundle.keys::<C1>().unwrap().group_key(), bundle.keys::<C2>().unwrap().group_key());
+    if group_keys.is_none() {
+      group_keys = Some(keys);
+    }
+    assert_eq!(group_keys.unwrap(), keys);
+  }
+
+  // A started curve must be completed before the bundle can be finished
+  let mut driver = KeyBundleMachine::new(
+    MultisigParams::new(THRESHOLD, PARTICIPANTS, 1).unwrap(),
+    "FROST Test bundle".to_string(),
+    KeyGenVariant::Frost
+  );
+  driver.key_gen::<C1>().unwrap();
+  assert!(matches!(driver.finish(), Err(FrostError::MissingCurve)));
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, FrostError, MultisigParams, Writable,
+  key_gen::{KeyGenVariant, commitments_len},
+  compact::{DIGEST_LEN, CompactKeyGenMachine},
+  tests::{PARTICIPANTS, THRESHOLD, clone_without}
+};
+
+pub fn test_compact<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let mut machines = HashMap::new();
+  let mut digests = HashMap::new();
+  for i in 1 ..= PARTICIPANTS {
+    let (machine, digest) = CompactKeyGenMachine::<C>::new(
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+      "FROST Test compact".to_string(),
+      KeyGenVariant::Frost
+    ).generate_coefficients(&mut *rng);
+    assert_eq!(digest.serialize().len(), DIGEST_LEN);
+    assert_eq!(machine.message_len(), DIGEST_LEN);
+    machines.insert(i, machine);
+    digests.insert(i, digest.serialize());
+  }
+
+  let mut commitments = HashMap::new();
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let digests = clone_without(&digests, &i).drain().map(
+      |(l, digest)| (l, machine.read_digest(&mut digest.as_slice()).unwrap())
+    ).collect();
+    let (machine, these_commitments, these_shares) =
+      machine.generate_secret_shares(digests).unwrap();
+    assert_eq!(
+      these_commitments.serialize().len() + these_shares.values().next().unwrap().serialize().len(),
+      machine.message_len()
+    );
+    commitments.insert(i, these_commitments.serialize());
+    shares.insert(i, these_shares);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Commitments which don't match their digest are rejected, even if otherwise valid
+  {
+    let machine = machines.remove(&1).unwrap();
+    let mut these_commitments = clone_without(&commitments, &1).drain().map(
+      |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect::<HashMap<_, _>>();
+    these_commitments.insert(2, these_commitments[&3].clone());
+    let these_shares = (2 ..= PARTICIPANTS).map(|l| (l, shares[&l][&1].clone())).collect();
+    assert!(matches!(
+      machine.complete(&mut *rng, these_commitments, these_shares),
+      Err(FrostError::InvalidCommitment(2))
+    ));
+  }
+
+  let mut group_key = None;
+  for (i, machine) in machines {
+    let these_commitments = clone_without(&commitments, &i).drain().map(
+      |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let these_shares = shares.iter().filter(|(l, _)| **l != i).map(
+      |(l, shares)| (*l, shares[&i].clone())
+    ).collect();
+    let keys = machine.complete(&mut *rng, these_commitments, these_shares).unwrap();
+    if group_key.is_none() {
+      group_key = Some(keys.group_key());
+    }
+    assert_eq!(group_key.unwrap(), keys.group_key());
+  }
+
+  // The broadcast no longer grows with the threshold
+  let params = MultisigParams::new(THRESHOLD, PARTICIPANTS, 1).unwrap();
+  assert!(DIGEST_LEN < commitments_len::<C>(&params));
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, MultisigParams, Writable,
+  key_gen::{KeyGenVariant, KeyGenMachine, Commitments, SecretShare},
+  complaint::{EncryptedSecretShare, Complaint},
+  tests::{PARTICIPANTS, THRESHOLD, clone_without}
+};
+
+const CONTEXT: &str = "FROST Test complaint";
+
+pub fn test_complaint<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let encryption_keys = (1 ..= PARTICIPANTS).map(
+    |i| (i, C::F::random(&mut *rng))
+  ).collect::<HashMap<_, _>>();
+  let public = |i: u16| C::generator_table() * encryption_keys[&i];
+
+  let mut machines = HashMap::new();
+  let mut commitments = HashMap::new();
+  for i in 1 ..= PARTICIPANTS {
+    let (machine, these_commitments) = KeyGenMachine::<C>::new(
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+      CONTEXT.to_string(),
+      KeyGenVariant::Frost
+    ).generate_coefficients(&mut *rng);
+    machines.insert(i, machine);
+    commitments.insert(i, these_commitments);
+  }
+
+  // Participant 1 generates shares, sending an encrypted share to participant 2
+  let machine = machines.remove(&1).unwrap();
+  let (_, mut shares) = machine.generate_secret_shares(
+    &mut *rng,
+    clone_without(&commitments, &1)
+  ).unwrap();
+  let share = shares.remove(&2).unwrap();
+  let encrypt = |rng: &mut R, share: &SecretShare<C>| EncryptedSecretShare::new(
+    &mut *rng, CONTEXT, 1, encryption_keys[&1], 2, public(2), share
+  );
+  let encrypted = encrypt(&mut *rng, &share);
+  assert_eq!(
+    EncryptedSecretShare::<C>::read(&mut encrypted.serialize().as_slice()).unwrap(),
+    encrypted
+  );
+  assert!(
+    encrypted.decrypt(CONTEXT, 1, public(1), 2, encryption_keys[&2]).unwrap() == share
+  );
+  // Only the sender's key will verify
+  assert!(encrypted.decrypt(CONTEXT, 3, public(3), 2, encryption_keys[&2]).is_none());
+
+  let adjudicate = |complaint: &Complaint<C>, commitments: &Commitments<C>| {
+    let complaint = Complaint::<C>::read(&mut complaint.serialize().as_slice()).unwrap();
+    complaint.adjudicate(CONTEXT, 2, public(2), 1, public(1), commitments)
+  };
+  let complain = |rng: &mut R, encrypted| Complaint::new(
+    &mut *rng, CONTEXT, 2, encryption_keys[&2], 1, encrypted
+  );
+
+  // Complaining about a valid share blames the accuser
+  assert_eq!(adjudicate(&complain(&mut *rng, encrypted), &commitments[&1]), 2);
+
+  // Complaining about an invalid share blames the accused
+  let invalid = encrypt(&mut *rng, &SecretShare(share.0 + C::F::one()));
+  let complaint = complain(&mut *rng, invalid);
+  assert_eq!(adjudicate(&complaint, &commitments[&1]), 1);
+
+  // A share not signed by the accused, or an invalid proof, blames the accuser
+  let forged = EncryptedSecretShare::new(
+    &mut *rng, CONTEXT, 1, encryption_keys[&3], 2, public(2), &SecretShare(C::F::one())
+  );
+  assert_eq!(adjudicate(&complain(&mut *rng, forged), &commitments[&1]), 2);
+  let wrong_key = Complaint::new(&mut *rng, CONTEXT, 2, encryption_keys[&3], 1, invalid);
+  assert_eq!(adjudicate(&wrong_key, &commitments[&1]), 2);
+
+  // Every share is encrypted with its own ephemeral key, so the key revealed by a complaint
+  // doesn't decrypt any other share, even one sent between the same pair under the same context
+  let other = encrypt(&mut *rng, &share);
+  assert!(other != encrypted);
+  let mut revealed = complaint.serialize();
+  let other = other.serialize();
+  revealed[.. other.len()].copy_from_slice(&other);
+  let revealed = Complaint::<C>::read(&mut revealed.as_slice()).unwrap();
+  assert_eq!(adjudicate(&revealed, &commitments[&1]), 2);
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, FrostError, Writable, schnorr,
+  algorithm::{Hram, Schnorr},
+  coordinator::Coordinator,
+  sign::{SignatureShare, PreprocessMachine, SignMachine, AlgorithmMachine, Progress},
+  tests::{THRESHOLD, PARTICIPANTS, key_gen, schnorr::TestHram}
+};
+
+pub fn test_coordinator<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+  let mut coordinator = Coordinator::new(
+    Schnorr::<C, TestHram<C>>::new(),
+    THRESHOLD,
+    group_key,
+    keys[&1].verification_shares()
+  ).unwrap();
+
+  // Every participant preprocesses, and the coordinator selects the signing set afterwards
+  let mut machines = HashMap::new();
+  let potential = (1 ..= PARTICIPANTS).collect::<Vec<_>>();
+  for i in 1 ..= PARTICIPANTS {
+    let machine = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      keys[&i].clone(),
+      &potential
+    ).unwrap();
+    let (machine, preprocess) = machine.preprocess(&mut *rng);
+    let preprocess = coordinator.read_preprocess(&mut preprocess.serialize().as_slice()).unwrap();
+    coordinator.add_preprocess(i, preprocess.clone()).unwrap();
+    assert!(
+      matches!(coordinator.add_preprocess(i, preprocess), Err(FrostError::DuplicatedIndex(_)))
+    );
+    machines.insert(i, machine);
+  }
+  assert_eq!(coordinator.responded(), potential);
+
+  // An auditor, holding none of the signing session's state, can verify individual shares
+  let auditor = Coordinator::new(
+    Schnorr::<C, TestHram<C>>::new(),
+    THRESHOLD,
+    group_key,
+    keys[&1].verification_shares()
+  ).unwrap();
+
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let (mut coordinator, package) = coordinator.sign(&included, MESSAGE).unwrap();
+  let mut shares = HashMap::new();
+  for i in &included {
+    let machine = machines.remove(i).unwrap().select(&included).unwrap();
+    let (_, share) = machine.sign(package.clone(), MESSAGE).unwrap();
+    shares.insert(*i, coordinator.read_share(&mut share.serialize().as_slice()).unwrap());
+  }
+
+  for i in &included {
+    auditor.verify_share(&package, MESSAGE, *i, shares[i]).unwrap();
+  }
+  assert!(matches!(
+    auditor.verify_share(&package, b"Goodbye, World!", 1, shares[&1]),
+    Err(FrostError::InvalidShare(1))
+  ));
+  assert!(matches!(
+    auditor.verify_share(&package, MESSAGE, 1, shares[&2]),
+    Err(FrostError::InvalidShare(1))
+  ));
+  assert!(matches!(
+    auditor.verify_share(&package, MESSAGE, PARTICIPANTS, shares[&1]),
+    Err(FrostError::NotIncluded(PARTICIPANTS))
+  ));
+
+  // Invalid shares are identified on arrival
+  let invalid = SignatureShare(shares[&1].0 + C::F::one());
+  assert!(matches!(coordinator.add_share(1, invalid), Err(FrostError::InvalidShare(1))));
+
+  for i in 1 ..= THRESHOLD {
+    let progress = coordinator.add_share(i, shares[&i]).unwrap();
+    if i == THRESHOLD {
+      assert_eq!(progress, Progress::Complete);
+    } else {
+      assert_eq!(progress, Progress::Missing(((i + 1) ..= THRESHOLD).collect()));
+    }
+  }
+
+  let sig = coordinator.complete().unwrap();
+  assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), &sig));
+}
@@ -1,8 +1,25 @@
 use rand_core::{RngCore, CryptoRng};
 
+#[cfg(test)]
+use sha2::Sha512;
+
 use group::{ff::Field, Group};
+#[cfg(test)]
+use group::GroupEncoding;
+
+#[cfg(test)]
+use transcript::{Transcript, DigestTranscript};
 
-use crate::{Curve, MultisigKeys, tests::key_gen};
+#[cfg(test)]
+use multiexp::BatchVerifier;
+
+use crate::{
+  Curve, FrostError, DeserializeError, MultisigKeys, lagrange, lagrange_batch,
+  curve::{CurveError, PublicKey},
+  algorithm::Schnorr,
+  sign::{PreprocessMachine, AlgorithmMachine},
+  tests::{PARTICIPANTS, THRESHOLD, key_gen, schnorr::TestHram}
+};
 
 // Test generation of FROST keys
 fn key_generation<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
@@ -13,13 +30,263 @@ fn key_generation<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
 // Test serialization of generated keys
 fn keys_serialization<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   for (_, keys) in key_gen::<_, C>(rng) {
-    assert_eq!(&MultisigKeys::<C>::deserialize(&keys.serialize()).unwrap(), &*keys);
+    let serialized = keys.serialize();
+    assert_eq!(&MultisigKeys::<C>::deserialize(&serialized).unwrap(), &*keys);
+
+    // Corrupted serializations should be identifiable as such
+    assert!(matches!(
+      MultisigKeys::<C>::deserialize(&serialized[.. serialized.len() - 1]),
+      Err(FrostError::DeserializeError(DeserializeError::InvalidLength(..)))
+    ));
+    let mut wrong_curve = serialized.clone();
+    wrong_curve[8] ^= 1;
+    assert!(matches!(
+      MultisigKeys::<C>::deserialize(&wrong_curve),
+      Err(FrostError::DeserializeError(DeserializeError::CurveMismatch))
+    ));
+  }
+}
+
+// Test keys can be rebuilt from their shares, as if they'd been generated externally
+fn keys_from_shares<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen::<_, C>(rng);
+  let params = keys[&1].params();
+  let verification_shares = keys[&1].verification_shares();
+  for keys in keys.values() {
+    assert_eq!(
+      &MultisigKeys::<C>::new(keys.params(), keys.secret_share(), verification_shares.clone())
+        .unwrap(),
+      &**keys
+    );
+  }
+
+  // A secret share which doesn't match its verification share
+  assert!(matches!(
+    MultisigKeys::<C>::new(params, keys[&2].secret_share(), verification_shares.clone()),
+    Err(FrostError::InvalidShare(1))
+  ));
+
+  // Missing verification shares
+  let mut missing = verification_shares.clone();
+  missing.remove(&PARTICIPANTS);
+  assert!(matches!(
+    MultisigKeys::<C>::new(params, keys[&1].secret_share(), missing.clone()),
+    Err(FrostError::InvalidParticipantQuantity(..))
+  ));
+  missing.insert(PARTICIPANTS + 1, C::generator());
+  assert!(matches!(
+    MultisigKeys::<C>::new(params, keys[&1].secret_share(), missing),
+    Err(FrostError::MissingParticipant(PARTICIPANTS))
+  ));
+
+  // A verification share which isn't on the same polynomial as the rest
+  let mut inconsistent = verification_shares;
+  *inconsistent.get_mut(&PARTICIPANTS).unwrap() += C::generator();
+  assert!(matches!(
+    MultisigKeys::<C>::new(params, keys[&1].secret_share(), inconsistent),
+    Err(FrostError::InvalidShare(PARTICIPANTS))
+  ));
+}
+
+// Test signing sets are canonicalized, or rejected, when creating views
+fn keys_view<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen::<_, C>(rng);
+  let view = keys[&1].view(&[3, 1, 2]).unwrap();
+  assert_eq!(view.included(), vec![1, 2, 3]);
+  assert_eq!(view.secret_share(), keys[&1].view(&[1, 2, 3]).unwrap().secret_share());
+
+  assert!(matches!(keys[&1].view(&[1, 2, 2, 3]), Err(FrostError::DuplicatedIndex(2))));
+  assert!(matches!(
+    keys[&1].view(&[1, 2, PARTICIPANTS + 1]),
+    Err(FrostError::InvalidParticipantIndex(_, _))
+  ));
+  assert!(matches!(keys[&1].view(&[0, 1, 2]), Err(FrostError::InvalidParticipantIndex(_, _))));
+  assert!(matches!(keys[&1].view(&[2, 3, 4]), Err(FrostError::NotIncluded(1))));
+}
+
+// Test batched lagrange coefficients match those calculated individually
+fn lagrange_coefficients<C: Curve>() {
+  let included = [1, 3, 4];
+  let indices = (1 ..= PARTICIPANTS).collect::<Vec<_>>();
+  assert_eq!(
+    lagrange_batch::<C::F>(&indices, &included),
+    indices.iter().map(|i| lagrange::<C::F>(*i, &included)).collect::<Vec<_>>()
+  );
+  assert!(lagrange_batch::<C::F>(&[], &included).is_empty());
+}
+
+// Test the human-readable encodings of group keys and verification shares
+fn public_key_strings<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen::<_, C>(rng);
+  let mut points = vec![keys[&1].group_key()];
+  points.extend(keys[&1].verification_shares().values());
+  for point in points {
+    let string = PublicKey::<C>(point).to_string();
+    assert_eq!(string, hex::encode(C::G_to_bytes(&point)));
+    assert_eq!(string.parse::<PublicKey<C>>().unwrap(), PublicKey(point));
   }
+
+  assert!(matches!("zz".parse::<PublicKey<C>>(), Err(CurveError::InvalidHex)));
+  assert!(matches!("".parse::<PublicKey<C>>(), Err(CurveError::InvalidLength(..))));
+
+  #[cfg(feature = "bech32")]
+  {
+    let key = PublicKey::<C>(keys[&1].group_key());
+    let encoded = key.to_bech32("frost").unwrap();
+    assert!(encoded.starts_with("frost1"));
+    assert_eq!(PublicKey::<C>::from_bech32("frost", &encoded).unwrap(), key);
+    assert_eq!(PublicKey::<C>::from_bech32("frost", &encoded.to_uppercase()).unwrap(), key);
+    // The human-readable part is enforced
+    assert!(matches!(
+      PublicKey::<C>::from_bech32("other", &encoded),
+      Err(CurveError::InvalidBech32)
+    ));
+    // As is the checksum
+    let mut corrupted = encoded.into_bytes();
+    let last = corrupted.len() - 1;
+    corrupted[last] = if corrupted[last] == b'q' { b'p' } else { b'q' };
+    assert!(matches!(
+      PublicKey::<C>::from_bech32("frost", &String::from_utf8(corrupted).unwrap()),
+      Err(CurveError::InvalidBech32)
+    ));
+  }
+}
+
+// Test hashing to the group is deterministic, domain separated, and yields valid elements
+fn hash_to_group<C: Curve>() {
+  let point = C::hash_to_G(b"test", b"message");
+  assert_eq!(point, C::hash_to_G(b"test", b"message"));
+  assert!(!bool::from(point.is_identity()));
+  assert!(point != C::generator());
+  // G_from_slice rejects elements outside of the prime order subgroup
+  assert_eq!(C::G_from_slice(&C::G_to_bytes(&point)).unwrap(), point);
+
+  assert!(point != C::hash_to_G(b"other", b"message"));
+  assert!(point != C::hash_to_G(b"test", b"other"));
+}
+
+// RNG which is entirely broken, always yielding zeroes
+struct BrokenRng;
+impl RngCore for BrokenRng {
+  fn next_u32(&mut self) -> u32 {
+    0
+  }
+  fn next_u64(&mut self) -> u64 {
+    0
+  }
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    dest.fill(0);
+  }
+  fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    dest.fill(0);
+    Ok(())
+  }
+}
+impl CryptoRng for BrokenRng {}
+
+// Test nonces remain unique, if deterministic, when the RNG is broken
+fn hedged_nonces<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let secret = C::F::random(&mut *rng);
+  let nonce = C::hedged_nonce(secret, b"message", &mut BrokenRng);
+  assert_eq!(nonce, C::hedged_nonce(secret, b"message", &mut BrokenRng));
+  assert!(nonce != C::hedged_nonce(secret, b"other", &mut BrokenRng));
+  assert!(nonce != C::hedged_nonce(C::F::random(&mut *rng), b"message", &mut BrokenRng));
+
+  // A session's nonces are distinct from each other, and from every other session's
+  let nonces = C::random_nonces(secret, b"session", &mut BrokenRng);
+  assert!(nonces[0] != nonces[1]);
+  assert_eq!(nonces, C::random_nonces(secret, b"session", &mut BrokenRng));
+  let other = C::random_nonces(secret, b"other", &mut BrokenRng);
+  assert!((other[0] != nonces[0]) && (other[1] != nonces[1]));
+
+  // Sessions without an ID use data unique to each session
+  let keys = key_gen::<_, C>(&mut *rng);
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let preprocess = |session: Option<&[u8]>| {
+    let machine = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      keys[&1].clone(),
+      &included
+    ).unwrap();
+    match session {
+      Some(session) => machine.with_session(session),
+      None => machine
+    }.preprocess(&mut BrokenRng).1.commitments()
+  };
+  assert!(preprocess(None) != preprocess(None));
+  assert_eq!(preprocess(Some(b"session")), preprocess(Some(b"session")));
+
+  // With a working RNG, nonces are random
+  assert!(C::hedged_nonce(secret, b"message", &mut *rng) != nonce);
+  assert!(
+    C::random_nonces(secret, b"session", &mut *rng) !=
+      C::random_nonces(secret, b"session", &mut *rng)
+  );
+}
+
+// Test the batch verifier, with both RNG and transcript derived scalar factors, blames the first
+// invalid set, regardless of if blame is constant time or not
+// Only available to this crate's own tests, as transcript derived factors are a dev-dependency
+// feature of multiexp
+#[cfg(test)]
+pub fn test_batch_verifier<R: RngCore + CryptoRng, C: Curve>(rng: &mut R)
+  where C::G: GroupEncoding {
+  // Each set claims xG - X = 0, with the specified sets having an incorrect X
+  let sets = |rng: &mut R, invalid: &[usize]| (0 .. 5).map(|i| {
+    let x = C::F::random(&mut *rng);
+    let mut key = C::generator() * x;
+    if invalid.contains(&i) {
+      key += C::generator();
+    }
+    vec![(x, C::generator()), (-C::F::one(), key)]
+  }).collect::<Vec<_>>();
+
+  for invalid in [vec![], vec![3], vec![1, 4]] {
+    let sets = sets(&mut *rng, &invalid);
+
+    let mut batch = BatchVerifier::new(sets.len());
+    let mut transcript = DigestTranscript::<Sha512>::new(b"FROST Test Batch Verifier");
+    let mut transcripted = BatchVerifier::new(sets.len());
+    for (i, set) in sets.iter().enumerate() {
+      batch.queue(&mut *rng, i, set.clone());
+      transcripted.queue_transcript(&mut transcript, i, set.clone());
+    }
+
+    for batch in [batch, transcripted] {
+      let expected = invalid.first().copied();
+      assert_eq!(batch.verify(), expected.is_none());
+      assert_eq!(batch.verify_vartime(), expected.is_none());
+      assert_eq!(batch.blame(), expected);
+      assert_eq!(batch.blame_vartime(), expected);
+      assert_eq!(batch.verify_with_blame(), expected.map_or(Ok(()), Err));
+    }
+  }
+
+  // Transcript derived factors are reproducible
+  let sets = sets(&mut *rng, &[]);
+  let transcript = |sets: &[Vec<(C::F, C::G)>]| {
+    let mut transcript = DigestTranscript::<Sha512>::new(b"FROST Test Batch Verifier");
+    let mut batch = BatchVerifier::<_, C::G>::new(sets.len());
+    for (i, set) in sets.iter().enumerate() {
+      batch.queue_transcript(&mut transcript, i, set.clone());
+    }
+    transcript.challenge(b"state")
+  };
+  assert_eq!(transcript(&sets), transcript(&sets));
+  assert!(transcript(&sets) != transcript(&sets[1 ..]));
 }
 
 pub fn test_curve<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   // TODO: Test the Curve functions themselves
 
+  // The crossover is measured once, then cached
+  #[cfg(test)]
+  {
+    let crossover = multiexp::calibrate::<C::G>();
+    assert!((2 ..= 512).contains(&crossover));
+    assert_eq!(multiexp::calibrate::<C::G>(), crossover);
+  }
+
   // Test successful multiexp, with enough pairs to trigger its variety of algorithms
   // TODO: This should probably be under multiexp
   {
@@ -27,15 +294,30 @@ pub fn test_curve<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
     let mut sum = C::G::identity();
     for _ in 0 .. 10 {
       for _ in 0 .. 100 {
-        pairs.push((C::F::random(&mut *rng), C::GENERATOR * C::F::random(&mut *rng)));
+        pairs.push((C::F::random(&mut *rng), C::generator() * C::F::random(&mut *rng)));
         sum += pairs[pairs.len() - 1].1 * pairs[pairs.len() - 1].0;
       }
-      assert_eq!(multiexp::multiexp(&pairs, C::LITTLE_ENDIAN), sum);
-      assert_eq!(multiexp::multiexp_vartime(&pairs, C::LITTLE_ENDIAN), sum);
+      assert_eq!(multiexp::multiexp(&pairs), sum);
+      assert_eq!(multiexp::multiexp_vartime(&pairs), sum);
     }
+
+    // Precomputed tables produce the same result, and can be reused across scalars
+    let points = pairs.iter().map(|(_, point)| *point).collect::<Vec<_>>();
+    let tables = multiexp::Precomputed::new(&points);
+    let scalars = pairs.iter().map(|(scalar, _)| *scalar).collect::<Vec<_>>();
+    assert_eq!(tables.multiexp(&scalars), sum);
+    assert_eq!(tables.multiexp_vartime(&scalars), sum);
+    let scalars = scalars.iter().map(|scalar| scalar.double()).collect::<Vec<_>>();
+    assert_eq!(tables.multiexp_vartime(&scalars), sum.double());
   }
 
   // Test FROST key generation and serialization of MultisigKeys works as expected
   key_generation::<_, C>(rng);
   keys_serialization::<_, C>(rng);
+  keys_from_shares::<_, C>(rng);
+  keys_view::<_, C>(rng);
+  lagrange_coefficients::<C>();
+  public_key_strings::<_, C>(rng);
+  hash_to_group::<C>();
+  hedged_nonces::<_, C>(rng);
 }
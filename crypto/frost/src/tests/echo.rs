@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, FrostError, MultisigParams, Writable,
+  key_gen::{KeyGenVariant, KeyGenMachine, SecretShareMachine},
+  tests::{PARTICIPANTS, THRESHOLD, clone_without}
+};
+
+fn machine<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  i: u16
+) -> (SecretShareMachine<C>, Vec<u8>) {
+  let (machine, commitments) = KeyGenMachine::<C>::new(
+    MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+    "FROST Test echo".to_string(),
+    KeyGenVariant::Frost
+  ).generate_coefficients(rng);
+  (machine, commitments.serialize())
+}
+
+pub fn test_echo<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let mut machines = HashMap::new();
+  let mut commitments = HashMap::new();
+  for i in 1 ..= PARTICIPANTS {
+    let (machine, these_commitments) = machine::<_, C>(&mut *rng, i);
+    machines.insert(i, machine);
+    commitments.insert(i, these_commitments);
+  }
+  // Participant 2 sends different commitments to participant 1
+  let (_, equivocated) = machine::<_, C>(&mut *rng, 2);
+
+  let mut hashes = HashMap::new();
+  let machines = machines.drain().map(|(i, machine)| {
+    let mut received = clone_without(&commitments, &i);
+    if i == 1 {
+      received.insert(2, equivocated.clone());
+    }
+    let received = received.drain().map(
+      |(l, commitments)| (l, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let (machine, _) = machine.generate_secret_shares(&mut *rng, received).unwrap();
+    hashes.insert(i, machine.session_hash());
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Participant 1 has a different view than everyone else
+  assert!(matches!(
+    machines[&1].verify_session_hashes(clone_without(&hashes, &1)),
+    Err(FrostError::InconsistentCommitments(2))
+  ));
+  for i in 2 ..= PARTICIPANTS {
+    assert!(matches!(
+      machines[&i].verify_session_hashes(clone_without(&hashes, &i)),
+      Err(FrostError::InconsistentCommitments(1))
+    ));
+
+    // Excluding participant 1, everyone agrees
+    let mut agreeing = clone_without(&hashes, &i);
+    agreeing.insert(1, hashes[&2].clone());
+    machines[&i].verify_session_hashes(agreeing).unwrap();
+  }
+
+  // Every other participant's hash is required
+  let mut missing = clone_without(&hashes, &2);
+  missing.remove(&3);
+  assert!(machines[&2].verify_session_hashes(missing).is_err());
+}
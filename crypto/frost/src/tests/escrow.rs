@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, MultisigParams, FrostError,
+  key_gen::{KeyGenVariant, KeyGenMachine},
+  escrow::{EscrowedShare, verify_escrowed_shares},
+  tests::clone_without
+};
+
+// Escrowing is expensive, so this uses a smaller multisig than the rest of the tests
+const THRESHOLD: u16 = 2;
+const PARTICIPANTS: u16 = 3;
+
+pub fn test_escrow<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const CONTEXT: &str = "FROST Test escrow";
+
+  let escrow_secret = C::F::random(&mut *rng);
+  let escrow_key = C::generator_table() * escrow_secret;
+
+  let mut machines = HashMap::new();
+  let mut commitments = HashMap::new();
+  for i in 1 ..= PARTICIPANTS {
+    let machine = KeyGenMachine::<C>::new(
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+      CONTEXT.to_string(),
+      KeyGenVariant::Frost
+    );
+    let (machine, these_commitments) = machine.generate_coefficients(rng);
+    machines.insert(i, machine);
+    commitments.insert(i, these_commitments);
+  }
+
+  // Escrow every share, having a third party verify them against the commitments
+  let mut escrowed = HashMap::new();
+  for (i, machine) in &machines {
+    let shares = machine.escrow_secret_shares(&mut *rng, escrow_key);
+    verify_escrowed_shares(CONTEXT, escrow_key, PARTICIPANTS, *i, &commitments[i], &shares)
+      .unwrap();
+    escrowed.insert(*i, shares);
+  }
+
+  // Shares should survive serialization
+  let share = &escrowed[&1][&2];
+  assert_eq!(&EscrowedShare::<C>::deserialize(&share.serialize()).unwrap(), share);
+
+  // Shares shouldn't verify for another participant, nor under another sender's commitments
+  let mut wrong = escrowed[&1].clone();
+  wrong.insert(3, share.clone());
+  assert!(
+    verify_escrowed_shares(CONTEXT, escrow_key, PARTICIPANTS, 1, &commitments[&1], &wrong).is_err()
+  );
+  let wrong = escrowed[&1].clone();
+  assert!(
+    verify_escrowed_shares(CONTEXT, escrow_key, PARTICIPANTS, 2, &commitments[&2], &wrong).is_err()
+  );
+
+  // Every participant must have a share escrowed
+  let mut missing = escrowed[&1].clone();
+  missing.remove(&3);
+  assert!(matches!(
+    verify_escrowed_shares(CONTEXT, escrow_key, PARTICIPANTS, 1, &commitments[&1], &missing),
+    Err(FrostError::InvalidParticipantQuantity(3, 2))
+  ));
+  let mut replaced = missing.clone();
+  replaced.insert(4, escrowed[&1][&3].clone());
+  assert!(matches!(
+    verify_escrowed_shares(CONTEXT, escrow_key, PARTICIPANTS, 1, &commitments[&1], &replaced),
+    Err(FrostError::MissingParticipant(3))
+  ));
+
+  let mut secret_shares = HashMap::new();
+  let mut machines = machines.drain().map(|(l, machine)| {
+    let (machine, shares) = machine.generate_secret_shares(
+      rng,
+      clone_without(&commitments, &l)
+    ).unwrap();
+    secret_shares.insert(l, shares);
+    (l, machine)
+  }).collect::<HashMap<_, _>>();
+
+  for (i, machine) in machines.drain() {
+    let mut our_secret_shares = HashMap::new();
+    for (l, shares) in &secret_shares {
+      if i == *l {
+        continue;
+      }
+      our_secret_shares.insert(*l, shares[&i].clone());
+    }
+    let keys = machine.complete(rng, our_secret_shares).unwrap();
+
+    // The escrow should be able to recover this participant's secret share
+    let recovered = escrowed.values().fold(
+      C::F::zero(),
+      |accum, shares| accum + shares[&i].decrypt(escrow_secret).unwrap()
+    );
+    assert_eq!(recovered, keys.secret_share());
+  }
+}
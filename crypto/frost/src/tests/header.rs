@@ -0,0 +1,44 @@
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve,
+  header::{PROTOCOL_VERSION, HeaderError, Header},
+  algorithm::{Hram, Schnorr},
+  sign::{PreprocessMachine, SignMachine},
+  tests::{key_gen, algorithm_machines}
+};
+
+pub fn test_header<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  let header = Header::<C>::new(b"session");
+  let mut machines = algorithm_machines(&mut *rng, Schnorr::<C, H>::new(), &keys);
+  let i = *machines.keys().next().unwrap();
+  let (machine, preprocess) = machines.remove(&i).unwrap().preprocess(&mut *rng);
+
+  let wrapped = header.wrap(&preprocess);
+  assert_eq!(wrapped[0], PROTOCOL_VERSION);
+  let mut reader = wrapped.as_slice();
+  header.read(&mut reader).unwrap();
+  assert_eq!(machine.read_preprocess(&mut reader).unwrap(), preprocess);
+  assert!(reader.is_empty());
+
+  let rejected = |wrapped: &[u8]| HeaderError::from_io(
+    &header.read(&mut &*wrapped).unwrap_err()
+  ).cloned();
+
+  let mut future = wrapped.clone();
+  future[0] += 1;
+  assert_eq!(rejected(&future), Some(HeaderError::UnsupportedVersion(PROTOCOL_VERSION + 1)));
+
+  let mut curve = wrapped.clone();
+  curve[2] ^= 1;
+  assert_eq!(rejected(&curve), Some(HeaderError::CurveMismatch));
+
+  assert_eq!(
+    rejected(&Header::<C>::new(b"other session").wrap(&preprocess)),
+    Some(HeaderError::SessionMismatch)
+  );
+
+  // Truncated headers aren't a mismatch
+  assert_eq!(rejected(&wrapped[.. 3]), None);
+}
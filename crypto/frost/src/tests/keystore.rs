@@ -0,0 +1,44 @@
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, FrostError, DeserializeError,
+  keystore::{KEYSTORE_VERSION, KeystoreParams, encrypt_with_params, decrypt, change_passphrase},
+  tests::key_gen
+};
+
+pub fn test_keystore<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  // Minimal parameters, as the defaults are needlessly slow for tests
+  let params = KeystoreParams::new(8, 1, 1).unwrap();
+  assert!(KeystoreParams::new(0, 0, 0).is_none());
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let encrypted = encrypt_with_params(&mut *rng, params, &keys[&1], b"passphrase");
+  assert_eq!(encrypted[0], KEYSTORE_VERSION);
+  assert_eq!(&decrypt::<C>(&encrypted, b"passphrase").unwrap(), &*keys[&1]);
+
+  fn failed<T>(res: Result<T, FrostError>) -> bool {
+    matches!(res, Err(FrostError::DeserializeError(DeserializeError::DecryptionFailed)))
+  }
+  assert!(failed(decrypt::<C>(&encrypted, b"wrong passphrase")));
+  // Both the header, here its salt, and the ciphertext are authenticated
+  for i in [13, encrypted.len() - 1] {
+    let mut corrupted = encrypted.clone();
+    corrupted[i] ^= 1;
+    assert!(failed(decrypt::<C>(&corrupted, b"passphrase")));
+  }
+  let mut version = encrypted.clone();
+  version[0] += 1;
+  assert!(matches!(
+    decrypt::<C>(&version, b"passphrase"),
+    Err(FrostError::DeserializeError(DeserializeError::UnsupportedVersion(_)))
+  ));
+  assert!(matches!(
+    decrypt::<C>(&encrypted[.. 10], b"passphrase"),
+    Err(FrostError::DeserializeError(DeserializeError::Truncated))
+  ));
+
+  let changed = change_passphrase(&mut *rng, &encrypted, b"passphrase", b"new", params).unwrap();
+  assert_eq!(&decrypt::<C>(&changed, b"new").unwrap(), &*keys[&1]);
+  assert!(failed(decrypt::<C>(&changed, b"passphrase")));
+  assert!(failed(change_passphrase(&mut *rng, &encrypted, b"wrong", b"new", params)));
+}
@@ -0,0 +1,23 @@
+use crate::{curve::CurveError, bech32::{encode, decode}};
+
+// Valid bech32m strings from BIP 350, whose data is byte aligned
+#[test]
+fn bech32m_vectors() {
+  assert_eq!(encode("a", &[]).unwrap(), "a1lqfn3a");
+  assert_eq!(decode("a", "A1LQFN3A").unwrap(), Vec::<u8>::new());
+
+  let data = hex::decode("ffbbcdeb38bdab49ca307b9ac5a928398a418820").unwrap();
+  let encoded = "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx";
+  assert_eq!(encode("abcdef", &data).unwrap(), encoded);
+  assert_eq!(decode("abcdef", encoded).unwrap(), data);
+
+  // Bech32, as opposed to bech32m, isn't accepted
+  assert!(matches!(decode("a", "a12uel5l"), Err(CurveError::InvalidBech32)));
+  // Nor are mixed case strings, invalid characters, or a missing separator
+  assert!(matches!(decode("a", "a1LQFN3A"), Err(CurveError::InvalidBech32)));
+  assert!(matches!(decode("a", "a1lqfn3b"), Err(CurveError::InvalidBech32)));
+  assert!(matches!(decode("a", "alqfn3a"), Err(CurveError::InvalidBech32)));
+  // Nor are human-readable parts outside of printable ASCII
+  assert!(matches!(encode("", &[]), Err(CurveError::InvalidBech32)));
+  assert!(matches!(encode("a b", &[]), Err(CurveError::InvalidBech32)));
+}
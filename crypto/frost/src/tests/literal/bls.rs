@@ -0,0 +1,13 @@
+use rand::rngs::OsRng;
+
+use crate::{
+  curve::Bls12381,
+  tests::{curve::test_curve, schnorr::test_schnorr, bls::test_bls}
+};
+
+#[test]
+fn bls12_381() {
+  test_curve::<_, Bls12381>(&mut OsRng);
+  test_schnorr::<_, Bls12381>(&mut OsRng);
+  test_bls(&mut OsRng);
+}
@@ -1,77 +1,330 @@
 use rand::rngs::OsRng;
 
-use crate::{curve, tests::vectors::{Vectors, test_with_vectors}};
+use crate::{
+  curve,
+  tests::{
+    curve::test_batch_verifier,
+    vectors::{Vectors, test_with_vectors},
+    escrow::test_escrow
+  }
+};
+#[cfg(feature = "json_vectors")]
+use super::json_vectors;
 
 #[cfg(any(test, feature = "ristretto"))]
 #[test]
 fn ristretto_vectors() {
-  test_with_vectors::<_, curve::Ristretto, curve::IetfRistrettoHram>(
-    &mut OsRng,
-    Vectors {
-      threshold: 2,
-      shares: &[
-        "5c3430d391552f6e60ecdc093ff9f6f4488756aa6cebdbad75a768010b8f830e",
-        "b06fc5eac20b4f6e1b271d9df2343d843e1e1fb03c4cbb673f2872d459ce6f01",
-        "f17e505f0e2581c6acfe54d3846a622834b5e7b50cad9a2109a97ba7a80d5c04"
-      ],
-      group_secret: "1b25a55e463cfd15cf14a5d3acc3d15053f08da49c8afcf3ab265f2ebc4f970b",
-      group_key: "e2a62f39eede11269e3bd5a7d97554f5ca384f9f6d3dd9c3c0d05083c7254f57",
-
-      msg: "74657374",
-      included: &[1, 3],
-      nonces: &[
-        [
-          "b358743151e33d84bf00c12f71808f4103957c3e2cabab7b895c436b5e70f90c",
-          "7bd112153b9ae1ab9b31f5e78f61f5c4ca9ee67b7ea6d1181799c409d14c350c"
-        ],
-        [
-          "22acad88478e0d0373a991092a322ebd1b9a2dad90451a976d0db3215426af0e",
-          "9155e3d7bcf7cd468b980c7e20b2c77cbdfbe33a1dcae031fd8bc6b1403f4b04"
-        ]
-      ],
-      sig_shares: &[
-        "ff801b4e0839faa67f16dee4127b9f7fbcf5fd007900257b0e2bbc02cbe5e709",
-        "afdf5481023c855bf3411a5c8a5fafa92357296a078c3b80dc168f294cb4f504"
+  let vectors = Vectors {
+    threshold: 2,
+    shares: &[
+      "5c3430d391552f6e60ecdc093ff9f6f4488756aa6cebdbad75a768010b8f830e",
+      "b06fc5eac20b4f6e1b271d9df2343d843e1e1fb03c4cbb673f2872d459ce6f01",
+      "f17e505f0e2581c6acfe54d3846a622834b5e7b50cad9a2109a97ba7a80d5c04"
+    ],
+    group_secret: "1b25a55e463cfd15cf14a5d3acc3d15053f08da49c8afcf3ab265f2ebc4f970b",
+    group_key: "e2a62f39eede11269e3bd5a7d97554f5ca384f9f6d3dd9c3c0d05083c7254f57",
+
+    msg: "74657374",
+    included: &[1, 3],
+    nonces: &[
+      [
+        "b358743151e33d84bf00c12f71808f4103957c3e2cabab7b895c436b5e70f90c",
+        "7bd112153b9ae1ab9b31f5e78f61f5c4ca9ee67b7ea6d1181799c409d14c350c"
       ],
-      sig: "deae61af10e8ee48ba492573592fba547f5debeff6bd6e2024e8673584746f5e".to_owned() +
-           "ae6070cf0a757f027358f8409dda4e29e04c276b808c60fbea414b2c179add0e"
-    }
-  );
+      [
+        "22acad88478e0d0373a991092a322ebd1b9a2dad90451a976d0db3215426af0e",
+        "9155e3d7bcf7cd468b980c7e20b2c77cbdfbe33a1dcae031fd8bc6b1403f4b04"
+      ]
+    ],
+    sig_shares: &[
+      "ff801b4e0839faa67f16dee4127b9f7fbcf5fd007900257b0e2bbc02cbe5e709",
+      "afdf5481023c855bf3411a5c8a5fafa92357296a078c3b80dc168f294cb4f504"
+    ],
+    sig: "deae61af10e8ee48ba492573592fba547f5debeff6bd6e2024e8673584746f5e".to_owned() +
+         "ae6070cf0a757f027358f8409dda4e29e04c276b808c60fbea414b2c179add0e"
+  };
+  #[cfg(feature = "json_vectors")]
+  assert_eq!(json_vectors("frost-ristretto255-sha512.json"), vectors);
+  test_with_vectors::<_, curve::Ristretto, curve::IetfRistrettoHram>(&mut OsRng, vectors);
 }
 
 #[cfg(feature = "ed25519")]
 #[test]
 fn ed25519_vectors() {
-  test_with_vectors::<_, curve::Ed25519, curve::IetfEd25519Hram>(
-    &mut OsRng,
-    Vectors {
-      threshold: 2,
-      shares: &[
-        "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
-        "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
-        "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02"
-      ],
-      group_secret: "7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304",
-      group_key: "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673",
-
-      msg: "74657374",
-      included: &[1, 3],
-      nonces: &[
-        [
-          "8c76af04340e83bb5fc427c117d38347fc8ef86d5397feea9aa6412d96c05b0a",
-          "14a37ddbeae8d9e9687369e5eb3c6d54f03dc19d76bb54fb5425131bc37a600b"
-        ],
-        [
-          "5ca39ebab6874f5e7b5089f3521819a2aa1e2cf738bae6974ee80555de2ef70e",
-          "0afe3650c4815ff37becd3c6948066e906e929ea9b8f546c74e10002dbcc150c"
-        ]
-      ],
-      sig_shares: &[
-        "4369474a398aa10357b60d683da91ea6a767dcf53fd541a8ed6b4d780827ea0a",
-        "32fcc690d926075e45d2dfb746bab71447943cddbefe80d122c39174aa2e1004"
+  let vectors = Vectors {
+    threshold: 2,
+    shares: &[
+      "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
+      "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
+      "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02"
+    ],
+    group_secret: "7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304",
+    group_key: "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673",
+
+    msg: "74657374",
+    included: &[1, 3],
+    nonces: &[
+      [
+        "8c76af04340e83bb5fc427c117d38347fc8ef86d5397feea9aa6412d96c05b0a",
+        "14a37ddbeae8d9e9687369e5eb3c6d54f03dc19d76bb54fb5425131bc37a600b"
       ],
-      sig: "2b8d9c6995333c5990e3a3dd6568785539d3322f7f0376452487ea35cfda587b".to_owned() +
-           "75650edb12b1a8619c88ed1f8463d6baeefb18d3fed3c279102fdfecb255fa0e"
-    }
+      [
+        "5ca39ebab6874f5e7b5089f3521819a2aa1e2cf738bae6974ee80555de2ef70e",
+        "0afe3650c4815ff37becd3c6948066e906e929ea9b8f546c74e10002dbcc150c"
+      ]
+    ],
+    sig_shares: &[
+      "4369474a398aa10357b60d683da91ea6a767dcf53fd541a8ed6b4d780827ea0a",
+      "32fcc690d926075e45d2dfb746bab71447943cddbefe80d122c39174aa2e1004"
+    ],
+    sig: "2b8d9c6995333c5990e3a3dd6568785539d3322f7f0376452487ea35cfda587b".to_owned() +
+         "75650edb12b1a8619c88ed1f8463d6baeefb18d3fed3c279102fdfecb255fa0e"
+  };
+  #[cfg(feature = "json_vectors")]
+  assert_eq!(json_vectors("frost-ed25519-sha512.json"), vectors);
+  test_with_vectors::<_, curve::Ed25519, curve::IetfEd25519Hram>(&mut OsRng, vectors);
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn ed25519_compatibility() {
+  use crate::{
+    algorithm::Schnorr,
+    ed25519::{Verification, verify},
+    tests::{key_gen, algorithm_machines, sign}
+  };
+
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, curve::Ed25519>(&mut OsRng);
+  let sig = sign(
+    &mut OsRng,
+    algorithm_machines(
+      &mut OsRng,
+      Schnorr::<curve::Ed25519, curve::IetfEd25519Hram>::new(),
+      &keys
+    ),
+    MESSAGE
+  ).serialize();
+  let key = keys[&1].ed25519_group_key();
+
+  for verification in [Verification::Cofactored, Verification::Cofactorless] {
+    assert!(verify(&key, MESSAGE, &sig, verification));
+    assert!(!verify(&key, b"Goodbye, World!", &sig, verification));
+  }
+
+  // Verify with a stock ed25519 implementation
+  let public_key = ed25519_dalek::PublicKey::from_bytes(&key).unwrap();
+  let sig = ed25519_dalek::Signature::try_from(sig.as_ref()).unwrap();
+  public_key.verify_strict(MESSAGE, &sig).unwrap();
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn ed25519ph() {
+  use std::io::Read;
+
+  use sha2::{Digest, Sha512};
+
+  use crate::{
+    algorithm::Schnorr,
+    curve::Ed25519,
+    ed25519::{Verification, verify_prehashed},
+    tests::{key_gen, algorithm_machines, sign}
+  };
+
+  // RFC 8032's Ed25519ph test vector
+  let key = hex::decode(
+    "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf"
+  ).unwrap();
+  let sig = hex::decode(
+    "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41".to_owned() +
+    "31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406"
+  ).unwrap();
+  let prehash = Ed25519::prehash(&mut b"abc".as_ref()).unwrap();
+  assert!(verify_prehashed(&key, &prehash, &sig, Verification::Cofactored));
+  let prehash = Ed25519::prehash(&mut b"abd".as_ref()).unwrap();
+  assert!(!verify_prehashed(&key, &prehash, &sig, Verification::Cofactored));
+
+  // Sign a message streamed in chunks larger than the prehash buffer
+  let message = || std::io::repeat(0x42).take(100_000);
+  let prehash = Ed25519::prehash(&mut message()).unwrap();
+  let mut buffered = vec![];
+  message().read_to_end(&mut buffered).unwrap();
+  assert_eq!(prehash.as_ref(), Sha512::digest(&buffered).as_slice());
+
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let sig = sign(
+    &mut OsRng,
+    algorithm_machines(&mut OsRng, Schnorr::<Ed25519, curve::IetfEd25519phHram>::new(), &keys),
+    &prehash
+  ).serialize();
+  let key = keys[&1].ed25519_group_key();
+  for verification in [Verification::Cofactored, Verification::Cofactorless] {
+    assert!(verify_prehashed(&key, &prehash, &sig, verification));
+  }
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn ed25519_encodings() {
+  use crate::curve::{Curve, CurveError, Ed25519};
+
+  let generator = Ed25519::generator();
+  let bytes = hex::decode(
+    "5866666666666666666666666666666666666666666666666666666666666666"
+  ).unwrap();
+  assert_eq!(Ed25519::to_ed25519_bytes(&generator).to_vec(), bytes);
+  assert_eq!(Ed25519::from_ed25519_bytes(&bytes).unwrap(), generator);
+
+  // The identity, canonically and non-canonically encoded as y = p + 1
+  let mut identity = [0; 32];
+  identity[0] = 1;
+  assert!(matches!(Ed25519::from_ed25519_bytes(&identity), Err(CurveError::IdentityPoint)));
+  let mut non_canonical = [0xff; 32];
+  non_canonical[0] = 0xee;
+  non_canonical[31] = 0x7f;
+  assert!(matches!(
+    Ed25519::from_ed25519_bytes(&non_canonical),
+    Err(CurveError::NonCanonicalPoint)
+  ));
+
+  // y = 0 is a point of order 4, which is entirely torsion
+  let torsion = [0; 32];
+  assert!(matches!(Ed25519::from_ed25519_bytes(&torsion), Err(CurveError::TorsionedPoint)));
+  // The generator with a torsion component added
+  let torsioned = generator + dalek_ff_group::CompressedEdwardsY::new(torsion).decompress().unwrap();
+  assert!(matches!(
+    Ed25519::from_ed25519_bytes(&Ed25519::to_ed25519_bytes(&torsioned)),
+    Err(CurveError::TorsionedPoint)
+  ));
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn ed25519_hash_to_point() {
+  use crate::curve::{Curve, Ed25519};
+
+  // Monero's hash_to_ec, applied to the generator's encoding
+  let point = Ed25519::hash_to_G(
+    b"",
+    &hex::decode("5866666666666666666666666666666666666666666666666666666666666666").unwrap()
   );
+  assert_eq!(
+    hex::encode(Ed25519::G_to_bytes(&point)),
+    "d6329b5b1f7c0805b5c345f4957554002a2f557845f64d7645dae0e051a6498a"
+  );
+}
+
+#[cfg(any(test, feature = "ristretto"))]
+#[test]
+fn ristretto_escrow() {
+  test_escrow::<_, curve::Ristretto>(&mut OsRng);
+}
+
+// dalek-ff-group's points are usable by anything generic over GroupEncoding
+#[test]
+fn dalek_group_encoding() {
+  use group::{Group, GroupEncoding};
+  use dalek_ff_group::{EdwardsPoint, RistrettoPoint};
+
+  let point = EdwardsPoint::random(&mut OsRng);
+  assert_eq!(EdwardsPoint::from_bytes(&point.to_bytes()).unwrap(), point);
+  let point = RistrettoPoint::random(&mut OsRng);
+  assert_eq!(RistrettoPoint::from_bytes(&point.to_bytes()).unwrap(), point);
+
+  // y = p, a non-canonical encoding of y = 0
+  let mut non_canonical = [0xff; 32];
+  non_canonical[0] = 0xed;
+  non_canonical[31] = 0x7f;
+  assert!(bool::from(EdwardsPoint::from_bytes(&non_canonical).is_none()));
+  assert!(bool::from(RistrettoPoint::from_bytes(&non_canonical).is_none()));
+
+  test_batch_verifier::<_, curve::Ristretto>(&mut OsRng);
+}
+
+#[test]
+fn dalek_zeroize() {
+  use zeroize::Zeroize;
+  use group::{ff::Field, Group};
+  use dalek_ff_group::{Scalar, EdwardsPoint, RistrettoPoint};
+
+  let mut scalar = Scalar::random(&mut OsRng);
+  scalar.zeroize();
+  assert_eq!(scalar, Scalar::zero());
+
+  let mut point = EdwardsPoint::random(&mut OsRng);
+  point.zeroize();
+  assert!(bool::from(point.is_identity()));
+  let mut point = RistrettoPoint::random(&mut OsRng);
+  point.zeroize();
+  assert!(bool::from(point.is_identity()));
+}
+
+#[test]
+fn dalek_serde() {
+  use group::{ff::Field, Group};
+  use dalek_ff_group::{Scalar, EdwardsPoint, RistrettoPoint};
+
+  let scalar = Scalar::random(&mut OsRng);
+  let serialized = serde_json::to_string(&scalar).unwrap();
+  assert_eq!(serde_json::from_str::<Scalar>(&serialized).unwrap(), scalar);
+  // l, which isn't a canonical scalar encoding
+  let l = serde_json::to_string(&dalek_ff_group::dalek::constants::BASEPOINT_ORDER.to_bytes());
+  assert!(serde_json::from_str::<Scalar>(&l.unwrap()).is_err());
+
+  let point = EdwardsPoint::random(&mut OsRng);
+  let serialized = serde_json::to_string(&point).unwrap();
+  assert_eq!(serde_json::from_str::<EdwardsPoint>(&serialized).unwrap(), point);
+  let point = RistrettoPoint::random(&mut OsRng);
+  let serialized = serde_json::to_string(&point).unwrap();
+  assert_eq!(serde_json::from_str::<RistrettoPoint>(&serialized).unwrap(), point);
+
+  // y = p, a non-canonical encoding of y = 0
+  let mut non_canonical = [0xff; 32];
+  non_canonical[0] = 0xed;
+  non_canonical[31] = 0x7f;
+  let non_canonical = serde_json::to_string(&non_canonical).unwrap();
+  assert!(serde_json::from_str::<EdwardsPoint>(&non_canonical).is_err());
+  assert!(serde_json::from_str::<RistrettoPoint>(&non_canonical).is_err());
+}
+
+#[test]
+fn dalek_backend() {
+  #[cfg(not(feature = "simd"))]
+  assert_eq!(dalek_ff_group::backend(), dalek_ff_group::Backend::Serial);
+  assert!(dalek_ff_group::backend_supported());
+}
+
+// dalek-ff-group's scalar implements the entirety of the ff API
+#[test]
+fn dalek_field() {
+  use group::ff::{Field, PrimeField};
+  use dalek_ff_group::Scalar;
+
+  assert!(bool::from(Scalar::zero().invert().is_none()));
+  let scalar = Scalar::random(&mut OsRng);
+  assert_eq!(scalar * scalar.invert().unwrap(), Scalar::one());
+
+  let root = scalar.square().sqrt().unwrap();
+  assert!((root == scalar) || (root == -scalar));
+  assert_eq!(Scalar::zero().sqrt().unwrap(), Scalar::zero());
+  // The multiplicative generator is a non-residue
+  assert!(bool::from(Scalar::multiplicative_generator().sqrt().is_none()));
+
+  // The root of unity has order 2^S, where S = 2
+  let root = Scalar::root_of_unity();
+  assert!(root.square() != Scalar::one());
+  assert_eq!(root.square().square(), Scalar::one());
+
+  let scalars = [Scalar::random(&mut OsRng), Scalar::zero(), Scalar::random(&mut OsRng)];
+  let mut inverses = scalars;
+  let product = Scalar::batch_invert(&mut inverses);
+  assert_eq!(inverses[0], scalars[0].invert().unwrap());
+  assert_eq!(inverses[1], Scalar::zero());
+  assert_eq!(inverses[2], scalars[2].invert().unwrap());
+  assert_eq!(product, (scalars[0] * scalars[2]).invert().unwrap());
+
+  assert!(bool::from(Scalar::one().is_odd()));
+  assert!(bool::from(Scalar::from(2u64).is_even()));
 }
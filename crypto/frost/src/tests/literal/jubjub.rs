@@ -0,0 +1,48 @@
+use std::{sync::Arc, collections::HashMap};
+
+use rand::rngs::OsRng;
+
+use group::ff::Field;
+
+use crate::{
+  Curve,
+  curve::Jubjub,
+  redjubjub::{RedJubjub, rerandomize, verify},
+  tests::{curve::test_curve, schnorr::test_schnorr, key_gen, algorithm_machines, sign}
+};
+
+#[test]
+fn jubjub() {
+  test_curve::<_, Jubjub>(&mut OsRng);
+  test_schnorr::<_, Jubjub>(&mut OsRng);
+}
+
+#[test]
+fn redjubjub() {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, Jubjub>(&mut OsRng);
+  // Every participant must use the same randomizer, which is public
+  let randomizer = jubjub::Fr::random(&mut OsRng);
+  let randomized = keys.iter().map(
+    |(i, keys)| (*i, Arc::new(rerandomize(keys, randomizer)))
+  ).collect::<HashMap<_, _>>();
+  let rk = randomized[&1].group_key();
+  assert_eq!(rk, keys[&1].group_key() + (Jubjub::generator() * randomizer));
+
+  let sig = sign(
+    &mut OsRng,
+    algorithm_machines(&mut OsRng, RedJubjub::new(), &randomized),
+    MESSAGE
+  );
+  assert!(verify(rk, MESSAGE, &sig));
+
+  // Verify with the Zcash Foundation's RedJubjub implementation
+  let rk = redjubjub::VerificationKey::<redjubjub::SpendAuth>::try_from(
+    <[u8; 32]>::try_from(Jubjub::G_to_bytes(&rk)).unwrap()
+  ).unwrap();
+  let sig = redjubjub::Signature::<redjubjub::SpendAuth>::from(
+    <[u8; 64]>::try_from(sig.serialize()).unwrap()
+  );
+  rk.verify(MESSAGE, &sig).unwrap();
+}
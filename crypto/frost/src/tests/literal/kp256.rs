@@ -1,55 +1,108 @@
 use rand::rngs::OsRng;
 
 #[cfg(feature = "secp256k1")]
-use crate::tests::{curve::test_curve, schnorr::test_schnorr};
+use crate::tests::{
+  curve::{test_curve, test_batch_verifier},
+  schnorr::test_schnorr,
+  weighted::test_weighted,
+  nested::test_nested
+};
 #[cfg(feature = "secp256k1")]
-use crate::curve::Secp256k1;
+use crate::curve::{Curve, Secp256k1};
 
 #[cfg(feature = "p256")]
 use crate::tests::vectors::{Vectors, test_with_vectors};
 #[cfg(feature = "p256")]
 use crate::curve::{P256, IetfP256Hram};
+#[cfg(all(feature = "p256", feature = "json_vectors"))]
+use super::json_vectors;
 
 #[cfg(feature = "secp256k1")]
 #[test]
 fn secp256k1_non_ietf() {
   test_curve::<_, Secp256k1>(&mut OsRng);
+  test_batch_verifier::<_, Secp256k1>(&mut OsRng);
   test_schnorr::<_, Secp256k1>(&mut OsRng);
+  test_weighted::<_, Secp256k1>(&mut OsRng);
+  test_nested::<_, Secp256k1>(&mut OsRng);
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn secp256k1_encodings() {
+  use crate::curve::CurveError;
+
+  let generator = Secp256k1::generator();
+  let x = hex::decode("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap();
+
+  assert_eq!(Secp256k1::to_sec1_compressed(&generator).to_vec(), [&[2], x.as_slice()].concat());
+  assert_eq!(Secp256k1::from_sec1_compressed(&[&[2], x.as_slice()].concat()).unwrap(), generator);
+  assert!(Secp256k1::from_sec1_compressed(&x).is_err());
+
+  assert_eq!(Secp256k1::to_bip340(&generator).unwrap().to_vec(), x);
+  assert!(matches!(Secp256k1::to_bip340(&-generator), Err(CurveError::OddY)));
+  assert!(matches!(
+    Secp256k1::to_bip340(&k256::ProjectivePoint::IDENTITY),
+    Err(CurveError::InvalidPoint)
+  ));
+  assert_eq!(Secp256k1::from_bip340(&x).unwrap(), generator);
+  assert!(matches!(Secp256k1::from_bip340(&[0xff; 32]), Err(CurveError::InvalidPoint)));
+  assert!(matches!(Secp256k1::from_bip340(&x[1 ..]), Err(CurveError::InvalidLength(32, 31))));
+
+  // The address for the private key 1
+  assert_eq!(
+    hex::encode(Secp256k1::to_ethereum_address(&generator)),
+    "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+  );
 }
 
 #[cfg(feature = "p256")]
 #[test]
 fn p256_vectors() {
-  test_with_vectors::<_, P256, IetfP256Hram>(
-    &mut OsRng,
-    Vectors {
-      threshold: 2,
-      shares: &[
-        "0c9c1a0fe806c184add50bbdcac913dda73e482daf95dcb9f35dbb0d8a9f7731",
-        "8d8e787bef0ff6c2f494ca45f4dad198c6bee01212d6c84067159c52e1863ad5",
-        "0e80d6e8f6192c003b5488ce1eec8f5429587d48cf001541e713b2d53c09d928"
-      ],
-      group_secret: "8ba9bba2e0fd8c4767154d35a0b7562244a4aaf6f36c8fb8735fa48b301bd8de",
-      group_key: "023a309ad94e9fe8a7ba45dfc58f38bf091959d3c99cfbd02b4dc00585ec45ab70",
-
-      msg: "74657374",
-      included: &[1, 3],
-      nonces: &[
-        [
-          "081617b24375e069b39f649d4c4ce2fba6e38b73e7c16759de0b6079a22c4c7e",
-          "4de5fb77d99f03a2491a83a6a4cb91ca3c82a3f34ce94cec939174f47c9f95dd"
-        ],
-        [
-          "d186ea92593f83ea83181b184d41aa93493301ac2bc5b4b1767e94d2db943e38",
-          "486e2ee25a3fbc8e6399d748b077a2755fde99fa85cc24fa647ea4ebf5811a15"
-        ]
-      ],
-      sig_shares: &[
-        "9e4d8865faf8c7b3193a3b35eda3d9e12118447114b1e7d5b4809ea28067f8a9",
-        "b7d094eab6305ae74daeed1acd31abba9ab81f638d38b72c132cb25a5dfae1fc"
+  let vectors = Vectors {
+    threshold: 2,
+    shares: &[
+      "0c9c1a0fe806c184add50bbdcac913dda73e482daf95dcb9f35dbb0d8a9f7731",
+      "8d8e787bef0ff6c2f494ca45f4dad198c6bee01212d6c84067159c52e1863ad5",
+      "0e80d6e8f6192c003b5488ce1eec8f5429587d48cf001541e713b2d53c09d928"
+    ],
+    group_secret: "8ba9bba2e0fd8c4767154d35a0b7562244a4aaf6f36c8fb8735fa48b301bd8de",
+    group_key: "023a309ad94e9fe8a7ba45dfc58f38bf091959d3c99cfbd02b4dc00585ec45ab70",
+
+    msg: "74657374",
+    included: &[1, 3],
+    nonces: &[
+      [
+        "081617b24375e069b39f649d4c4ce2fba6e38b73e7c16759de0b6079a22c4c7e",
+        "4de5fb77d99f03a2491a83a6a4cb91ca3c82a3f34ce94cec939174f47c9f95dd"
       ],
-      sig: "0342c14c77f9d4ef9b8bd64fb0d7bbfdb9f8216a44e5f7bbe6ac0f3ed5e1a57367".to_owned() +
-        "561e1d51b129229966e92850bad5859bfee96926fad3007cd3f38639e1ffb554"
-    }
+      [
+        "d186ea92593f83ea83181b184d41aa93493301ac2bc5b4b1767e94d2db943e38",
+        "486e2ee25a3fbc8e6399d748b077a2755fde99fa85cc24fa647ea4ebf5811a15"
+      ]
+    ],
+    sig_shares: &[
+      "9e4d8865faf8c7b3193a3b35eda3d9e12118447114b1e7d5b4809ea28067f8a9",
+      "b7d094eab6305ae74daeed1acd31abba9ab81f638d38b72c132cb25a5dfae1fc"
+    ],
+    sig: "0342c14c77f9d4ef9b8bd64fb0d7bbfdb9f8216a44e5f7bbe6ac0f3ed5e1a57367".to_owned() +
+      "561e1d51b129229966e92850bad5859bfee96926fad3007cd3f38639e1ffb554"
+  };
+  #[cfg(feature = "json_vectors")]
+  assert_eq!(json_vectors("frost-p256-sha256.json"), vectors);
+  test_with_vectors::<_, P256, IetfP256Hram>(&mut OsRng, vectors);
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn p256_hash_to_curve() {
+  use crate::curve::Curve;
+
+  // Vector from the hash to curve RFC's P256_XMD:SHA-256_SSWU_RO_ suite, with an empty message
+  assert_eq!(
+    hex::encode(
+      P256::G_to_bytes(&P256::hash_to_G(b"QUUX-V01-CS02-with-P256_XMD:SHA-256_SSWU_RO_", b""))
+    ),
+    "032c15230b26dbc6fc9a37051158c95b79656e17a1a920b11394ca91c44247d3e4"
   );
 }
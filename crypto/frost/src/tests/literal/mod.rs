@@ -1,4 +1,34 @@
+#[cfg(feature = "json_vectors")]
+use crate::tests::vectors::Vectors;
+
 #[cfg(any(test, feature = "dalek"))]
 mod dalek;
 #[cfg(feature = "kp256")]
 mod kp256;
+#[cfg(feature = "bls12_381")]
+mod bls;
+#[cfg(any(feature = "pallas", feature = "vesta"))]
+mod pasta;
+#[cfg(feature = "jubjub")]
+mod jubjub;
+#[cfg(feature = "sr25519")]
+mod schnorrkel;
+#[cfg(feature = "bech32")]
+mod bech32;
+
+// Load the IETF vector file with the specified name, stored alongside these literal tests
+#[cfg(feature = "json_vectors")]
+fn json_vectors(file: &str) -> Vectors {
+  Vectors::load(
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/literal/vectors").join(file)
+  )
+}
+
+// Serai's signers hold keys for Monero and Bitcoin under one bundle
+#[cfg(all(feature = "ed25519", feature = "secp256k1"))]
+#[test]
+fn bundle() {
+  crate::tests::bundle::test_bundle::<_, crate::curve::Ed25519, crate::curve::Secp256k1>(
+    &mut rand::rngs::OsRng
+  );
+}
@@ -0,0 +1,21 @@
+use rand::rngs::OsRng;
+
+use crate::tests::{curve::test_curve, schnorr::test_schnorr};
+#[cfg(feature = "pallas")]
+use crate::curve::Pallas;
+#[cfg(feature = "vesta")]
+use crate::curve::Vesta;
+
+#[cfg(feature = "pallas")]
+#[test]
+fn pallas() {
+  test_curve::<_, Pallas>(&mut OsRng);
+  test_schnorr::<_, Pallas>(&mut OsRng);
+}
+
+#[cfg(feature = "vesta")]
+#[test]
+fn vesta() {
+  test_curve::<_, Vesta>(&mut OsRng);
+  test_schnorr::<_, Vesta>(&mut OsRng);
+}
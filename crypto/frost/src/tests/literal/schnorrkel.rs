@@ -0,0 +1,24 @@
+use rand::rngs::OsRng;
+
+use crate::{schnorrkel::Schnorrkel, tests::{key_gen, algorithm_machines, sign}};
+
+#[test]
+fn sr25519() {
+  const CONTEXT: &[u8] = b"substrate";
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen(&mut OsRng);
+  let group_key = keys[&1].group_key();
+  let sig = sign(
+    &mut OsRng,
+    algorithm_machines(&mut OsRng, Schnorrkel::new(CONTEXT), &keys),
+    MESSAGE
+  );
+  assert!(sig.verify(group_key, CONTEXT, MESSAGE));
+  assert!(!sig.verify(group_key, b"polkadot", MESSAGE));
+
+  // Verify with schnorrkel itself
+  let public_key = schnorrkel::PublicKey::from_bytes(&group_key.compress().to_bytes()).unwrap();
+  let sig = schnorrkel::Signature::from_bytes(&sig.serialize()).unwrap();
+  public_key.verify_simple(CONTEXT, MESSAGE, &sig).unwrap();
+}
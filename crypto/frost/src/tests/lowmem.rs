@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, FrostError, Writable,
+  algorithm::{Hram, Schnorr},
+  sign::{PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine},
+  lowmem::LowmemMachine,
+  tests::{THRESHOLD, key_gen, clone_without}
+};
+
+pub fn test_lowmem<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let included = (1 ..= THRESHOLD).rev().collect::<Vec<_>>();
+
+  assert!(matches!(
+    LowmemMachine::<C, H, 2>::new(&keys[&1], &included),
+    Err(FrostError::TooManyParticipants(..))
+  ));
+  assert!(matches!(
+    LowmemMachine::<C, H, 8>::new(&keys[&1], &(2 ..= (THRESHOLD + 1)).collect::<Vec<_>>()),
+    Err(FrostError::NotIncluded(1))
+  ));
+
+  {
+    let (lowmem, _) = LowmemMachine::<C, H, 8>::new(&keys[&1], &included)
+      .unwrap()
+      .preprocess(&mut *rng);
+    let mut buffer = vec![0; lowmem.buffer_len(MESSAGE) - 1];
+    let others = (2 ..= THRESHOLD).map(|l| (l, [C::generator(); 2])).collect::<Vec<_>>();
+    assert!(matches!(
+      lowmem.sign(&others, MESSAGE, &mut buffer),
+      Err(FrostError::BufferTooSmall(..))
+    ));
+  }
+
+  // Sign with an offset and a context, with participant 1 using the lowmem machine and everyone
+  // else using a regular machine
+  let offset = C::F::random(&mut *rng);
+  let lowmem = LowmemMachine::<C, H, 8>::new(
+    &keys[&1].offset(offset).with_context(b"lowmem"),
+    &included
+  ).unwrap();
+  assert_eq!(lowmem.included(), (1 ..= THRESHOLD).collect::<Vec<_>>());
+  let (lowmem, ours) = lowmem.preprocess(&mut *rng);
+
+  let mut preprocesses = HashMap::new();
+  preprocesses.insert(1, [C::G_to_bytes(&ours[0]), C::G_to_bytes(&ours[1])].concat());
+  let mut others = vec![];
+  let mut machines = (2 ..= THRESHOLD).map(|i| {
+    let (machine, preprocess) = AlgorithmMachine::new(
+      Schnorr::<C, H>::new(),
+      keys[&i].offset(offset).with_context(b"lowmem").into(),
+      &included
+    ).unwrap().preprocess(&mut *rng);
+    others.push((i, preprocess.commitments()));
+    preprocesses.insert(i, preprocess.serialize());
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut buffer = vec![0; lowmem.buffer_len(MESSAGE)];
+  let mut shares = HashMap::new();
+  shares.insert(1, lowmem.sign(&others, MESSAGE, &mut buffer).unwrap().serialize());
+  let machines = machines.drain().map(|(i, machine)| {
+    let preprocesses = clone_without(&preprocesses, &i).drain().map(
+      |(l, preprocess)| (l, machine.read_preprocess(&mut preprocess.as_slice()).unwrap())
+    ).collect();
+    let (machine, share) = machine.sign(preprocesses, MESSAGE).unwrap();
+    shares.insert(i, share.serialize());
+    (i, machine)
+  }).collect::<Vec<_>>();
+
+  // Every regular machine accepts the lowmem machine's share
+  for (i, machine) in machines {
+    let shares = clone_without(&shares, &i).drain().map(
+      |(l, share)| (l, machine.read_share(&mut share.as_slice()).unwrap())
+    ).collect();
+    machine.complete(shares).unwrap();
+  }
+}
@@ -0,0 +1,27 @@
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, Writable,
+  algorithm::{Hram, Schnorr},
+  migration::Migration,
+  tests::{key_gen, algorithm_machines, sign}
+};
+
+pub fn test_migration<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  let migration = Migration::new(&keys[&1], "FROST v1", "FROST v2");
+  assert_eq!(migration.group_key(), keys[&1].group_key());
+  assert_eq!((migration.from(), migration.to()), ("FROST v1", "FROST v2"));
+
+  let serialized = migration.serialize();
+  assert_eq!(Migration::<C>::read(&mut serialized.as_slice()).unwrap(), migration);
+  assert!(Migration::<C>::read(&mut &serialized[.. serialized.len() - 1]).is_err());
+
+  let machines = algorithm_machines(&mut *rng, Schnorr::<C, H>::new(), &keys);
+  let sig = sign(&mut *rng, machines, &migration.message());
+  assert!(migration.verify::<H>(&sig));
+
+  // The signature doesn't endorse any other migration
+  assert!(!Migration::new(&keys[&1], "FROST v1", "FROST v3").verify::<H>(&sig));
+  assert!(!Migration::new(&keys[&1], "FROST v0", "FROST v2").verify::<H>(&sig));
+}
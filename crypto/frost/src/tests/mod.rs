@@ -6,9 +6,9 @@ use group::ff::Field;
 
 use crate::{
   Curve,
-  MultisigParams, MultisigKeys,
+  MultisigParams, MultisigKeys, Writable,
   lagrange,
-  key_gen::KeyGenMachine,
+  key_gen::{KeyGenVariant, KeyGenMachine},
   algorithm::Algorithm,
   sign::{PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine}
 };
@@ -17,6 +17,28 @@ use crate::{
 pub mod curve;
 pub mod schnorr;
 pub mod vectors;
+pub mod header;
+pub mod escrow;
+pub mod complaint;
+pub mod weighted;
+pub mod nested;
+pub mod coordinator;
+pub mod round;
+pub mod backup;
+pub mod migration;
+pub mod echo;
+pub mod blame;
+pub mod compact;
+pub mod sessions;
+pub mod bundle;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "lowmem")]
+pub mod lowmem;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "bls12_381")]
+pub mod bls;
 
 // Literal test definitions to run during `cargo test`
 #[cfg(test)]
@@ -36,40 +58,68 @@ pub fn clone_without<K: Clone + std::cmp::Eq + std::hash::Hash, V: Clone>(
 
 pub fn key_gen<R: RngCore + CryptoRng, C: Curve>(
   rng: &mut R
+) -> HashMap<u16, Arc<MultisigKeys<C>>> {
+  key_gen_with_variant(rng, KeyGenVariant::Frost)
+}
+
+pub fn key_gen_with_variant<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  variant: KeyGenVariant
 ) -> HashMap<u16, Arc<MultisigKeys<C>>> {
   let mut machines = HashMap::new();
   let mut commitments = HashMap::new();
   for i in 1 ..= PARTICIPANTS {
     let machine = KeyGenMachine::<C>::new(
       MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
-      "FROST Test key_gen".to_string()
+      "FROST Test key_gen".to_string(),
+      variant
     );
     let (machine, these_commitments) = machine.generate_coefficients(rng);
+    assert_eq!(these_commitments.serialize().len(), machine.message_len());
     machines.insert(i, machine);
-    commitments.insert(i, these_commitments);
+    commitments.insert(i, these_commitments.serialize());
   }
 
   let mut secret_shares = HashMap::new();
   let mut machines = machines.drain().map(|(l, machine)| {
-    let (machine, shares) = machine.generate_secret_shares(
+    // clone_without isn't necessary, as this machine's own data will be inserted without
+    // conflict, yet using it ensures the machine's own data is actually inserted as expected
+    let commitments = clone_without(&commitments, &l).drain().map(
+      |(i, commitments)| (i, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let (machine, mut shares) = machine.generate_secret_shares(
       rng,
-      // clone_without isn't necessary, as this machine's own data will be inserted without
-      // conflict, yet using it ensures the machine's own data is actually inserted as expected
-      clone_without(&commitments, &l)
+      commitments
     ).unwrap();
-    secret_shares.insert(l, shares);
+    for share in shares.values() {
+      assert_eq!(share.serialize().len(), machine.message_len());
+    }
+    secret_shares.insert(
+      l,
+      shares.drain().map(|(i, share)| (i, share.serialize())).collect::<HashMap<_, _>>()
+    );
     (l, machine)
   }).collect::<HashMap<_, _>>();
 
+  let mut session_hash = None;
   let mut verification_shares = None;
   let mut group_key = None;
   machines.drain().map(|(i, machine)| {
+    // Verify the session hashes are agreed upon
+    if session_hash.is_none() {
+      session_hash = Some(machine.session_hash());
+    }
+    assert_eq!(session_hash.as_ref().unwrap(), &machine.session_hash());
+    machine.verify_session_hashes(
+      (1 ..= PARTICIPANTS).filter(|l| *l != i).map(|l| (l, machine.session_hash())).collect()
+    ).unwrap();
+
     let mut our_secret_shares = HashMap::new();
     for (l, shares) in &secret_shares {
       if i == *l {
         continue;
       }
-      our_secret_shares.insert(*l, shares[&i].clone());
+      our_secret_shares.insert(*l, machine.read_share(&mut shares[&i].as_slice()).unwrap());
     }
     let these_keys = machine.complete(rng, our_secret_shares).unwrap();
 
@@ -78,6 +128,11 @@ pub fn key_gen<R: RngCore + CryptoRng, C: Curve>(
       verification_shares = Some(these_keys.verification_shares());
     }
     assert_eq!(verification_shares.as_ref().unwrap(), &these_keys.verification_shares());
+    assert_eq!(
+      these_keys.verification_share(i),
+      Some(C::generator_table() * these_keys.secret_share())
+    );
+    assert_eq!(these_keys.verification_share(PARTICIPANTS + 1), None);
 
     // Verify the group keys are agreed upon
     if group_key.is_none() {
@@ -98,7 +153,7 @@ pub fn recover<C: Curve>(keys: &HashMap<u16, MultisigKeys<C>>) -> C::F {
     C::F::zero(),
     |accum, (i, keys)| accum + (keys.secret_share() * lagrange::<C::F>(*i, &included))
   );
-  assert_eq!(C::GENERATOR_TABLE * group_private, first.group_key(), "failed to recover keys");
+  assert_eq!(C::generator_table() * group_private, first.group_key(), "failed to recover keys");
   group_private
 }
 
@@ -140,20 +195,26 @@ pub fn sign<R: RngCore + CryptoRng, M: PreprocessMachine>(
   let mut commitments = HashMap::new();
   let mut machines = machines.drain().map(|(i, machine)| {
     let (machine, preprocess) = machine.preprocess(rng);
-    commitments.insert(i, preprocess);
+    commitments.insert(i, preprocess.serialize());
     (i, machine)
   }).collect::<HashMap<_, _>>();
 
   let mut shares = HashMap::new();
   let mut machines = machines.drain().map(|(i, machine)| {
-    let (machine, share) = machine.sign(clone_without(&commitments, &i), msg).unwrap();
-    shares.insert(i, share);
+    let commitments = clone_without(&commitments, &i).drain().map(
+      |(l, preprocess)| (l, machine.read_preprocess(&mut preprocess.as_slice()).unwrap())
+    ).collect();
+    let (machine, share) = machine.sign(commitments, msg).unwrap();
+    shares.insert(i, share.serialize());
     (i, machine)
   }).collect::<HashMap<_, _>>();
 
   let mut signature = None;
   for (i, machine) in machines.drain() {
-    let sig = machine.complete(clone_without(&shares, &i)).unwrap();
+    let shares = clone_without(&shares, &i).drain().map(
+      |(l, share)| (l, machine.read_share(&mut share.as_slice()).unwrap())
+    ).collect();
+    let sig = machine.complete(shares).unwrap();
     if signature.is_none() {
       signature = Some(sig.clone());
     }
@@ -0,0 +1,193 @@
+use std::{sync::Arc, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use group::ff::Field;
+
+use crate::{
+  Curve, FrostError, MultisigParams, MultisigKeys, schnorr,
+  algorithm::{Hram, Schnorr},
+  sign::{PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine},
+  nested::{NestedKeys, NestedMachine},
+  tests::{THRESHOLD, PARTICIPANTS, clone_without, key_gen, recover, schnorr::TestHram}
+};
+
+// Create an outer multisig whose first participant's share is the specified secret
+fn outer_key_gen<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R,
+  secret: C::F
+) -> HashMap<u16, Arc<MultisigKeys<C>>> {
+  // Randomly sample a polynomial, setting its constant term so it evaluates to the secret at 1
+  let mut coefficients = vec![C::F::zero()];
+  for _ in 1 .. THRESHOLD {
+    coefficients.push(C::F::random(&mut *rng));
+  }
+  coefficients[0] = coefficients.iter().fold(secret, |secret, coefficient| secret - coefficient);
+  let group_key = C::generator_table() * coefficients[0];
+
+  let secrets = (1 ..= PARTICIPANTS).map(|i| {
+    let i = C::F::from(u64::from(i));
+    coefficients.iter().rev().fold(C::F::zero(), |accum, coefficient| (accum * i) + coefficient)
+  }).collect::<Vec<_>>();
+  assert_eq!(secrets[0], secret);
+
+  let verification_shares = (1 ..= PARTICIPANTS).map(
+    |l| (l, C::generator_table() * secrets[usize::from(l) - 1])
+  ).collect::<HashMap<_, _>>();
+
+  (1 ..= PARTICIPANTS).map(|i| (
+    i,
+    Arc::new(
+      MultisigKeys {
+        params: MultisigParams::new(THRESHOLD, PARTICIPANTS, i).unwrap(),
+        secret_share: secrets[usize::from(i) - 1],
+        group_key,
+        verification_shares: verification_shares.clone(),
+        offset: None,
+        context: None
+      }
+    )
+  )).collect()
+}
+
+pub fn test_nested<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let inner = key_gen::<_, C>(&mut *rng);
+  let inner_secret = recover(
+    &inner.iter().map(|(i, keys)| (*i, MultisigKeys::clone(keys))).collect()
+  );
+  let outer = outer_key_gen::<_, C>(&mut *rng, inner_secret);
+  let group_key = outer[&1].group_key();
+
+  let nested = inner.iter().map(|(i, keys)| (
+    *i,
+    NestedKeys::new(
+      keys.clone(),
+      outer[&1].params(),
+      group_key,
+      outer[&1].verification_shares()
+    ).unwrap()
+  )).collect::<HashMap<_, _>>();
+  assert_eq!(nested[&1].group_key(), group_key);
+
+  // Nested keys must actually be for the specified outer participant
+  assert!(
+    NestedKeys::new(
+      nested[&1].inner(),
+      MultisigParams::new(THRESHOLD, PARTICIPANTS, 2).unwrap(),
+      group_key,
+      outer[&2].verification_shares()
+    ).is_err()
+  );
+
+  let inner_included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let outer_included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+
+  let mut inner_machines = inner_included.iter().map(|i| (
+    *i,
+    NestedMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      &nested[i],
+      &inner_included,
+      &outer_included
+    ).unwrap()
+  )).collect::<HashMap<_, _>>();
+  let mut outer_machines = outer_included[1 ..].iter().map(|i| (
+    *i,
+    AlgorithmMachine::new(Schnorr::<C, TestHram<C>>::new(), outer[i].clone(), &outer_included)
+      .unwrap()
+  )).collect::<HashMap<_, _>>();
+
+  // Inner preprocess, committed to via digests, then aggregated into the first outer participant's
+  // preprocess
+  let mut digests = HashMap::new();
+  let mut inner_machines = inner_machines.drain().map(|(i, machine)| {
+    let (machine, digest) = machine.preprocess(&mut *rng);
+    digests.insert(i, digest);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut commitments = HashMap::new();
+  let mut inner_machines = inner_machines.drain().map(|(i, machine)| {
+    let (machine, commitment) = machine.reveal(clone_without(&digests, &i)).unwrap();
+    commitments.insert(i, commitment);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Preprocesses which don't match their digest are rejected, as they may have been chosen after
+  // seeing everyone else's
+  {
+    let machine = NestedMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      &nested[&1],
+      &inner_included,
+      &outer_included
+    ).unwrap();
+    let (machine, _) = machine.preprocess(&mut *rng);
+    let (machine, _) = machine.reveal(clone_without(&digests, &1)).unwrap();
+    let mut altered = clone_without(&commitments, &1);
+    altered.get_mut(&2).unwrap().commitments[0] += C::generator();
+    assert!(matches!(machine.aggregate(altered), Err(FrostError::InvalidCommitment(2))));
+  }
+
+  let mut outer_commitments = HashMap::new();
+  let mut inner_machines = inner_machines.drain().map(|(i, machine)| {
+    let (machine, preprocess) = machine.aggregate(clone_without(&commitments, &i)).unwrap();
+    if let Some(existing) = outer_commitments.insert(1, preprocess.clone()) {
+      assert_eq!(existing, preprocess);
+    }
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut outer_machines = outer_machines.drain().map(|(i, machine)| {
+    let (machine, preprocess) = machine.preprocess(&mut *rng);
+    outer_commitments.insert(i, preprocess);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Inner signing, aggregated into the first outer participant's share
+  let mut partial_shares = HashMap::new();
+  let mut inner_machines = inner_machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&outer_commitments, &1), MESSAGE).unwrap();
+    partial_shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Invalid inner shares are attributed to the inner member who sent them
+  let mut altered = clone_without(&partial_shares, &1);
+  altered.get_mut(&2).unwrap().0 += C::F::one();
+  assert!(matches!(
+    inner_machines.remove(&1).unwrap().aggregate(altered),
+    Err(FrostError::InvalidShare(2))
+  ));
+
+  let mut shares = HashMap::new();
+  let mut inner_machines = inner_machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.aggregate(clone_without(&partial_shares, &i)).unwrap();
+    if let Some(existing) = shares.insert(1, share) {
+      assert_eq!(existing, share);
+    }
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut outer_machines = outer_machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&outer_commitments, &i), MESSAGE).unwrap();
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut signatures = vec![];
+  for (_, machine) in inner_machines.drain() {
+    signatures.push(machine.complete(clone_without(&shares, &1)).unwrap());
+  }
+  for (i, machine) in outer_machines.drain() {
+    signatures.push(machine.complete(clone_without(&shares, &i)).unwrap());
+  }
+
+  for sig in &signatures {
+    assert_eq!(sig, &signatures[0]);
+  }
+  let sig = &signatures[0];
+  assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), sig));
+}
@@ -0,0 +1,70 @@
+use std::{time::Duration, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, MultisigParams, FrostError,
+  algorithm::Schnorr,
+  key_gen::{KeyGenVariant, KeyGenMachine},
+  sign::{PreprocessMachine, SignMachine, AlgorithmMachine},
+  tests::{THRESHOLD, PARTICIPANTS, clone_without, key_gen, schnorr::TestHram}
+};
+
+// Test late participants are identified once a round's deadline passes
+pub fn test_round<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let (machine, _) = KeyGenMachine::<C>::new(
+    MultisigParams::new(THRESHOLD, PARTICIPANTS, 1).unwrap(),
+    "FROST Test round".to_string(),
+    KeyGenVariant::Frost
+  ).generate_coefficients(&mut *rng);
+
+  // Rounds which haven't expired don't report anyone as late
+  let round = machine.start_round(Duration::from_secs(3600));
+  assert!(!round.expired());
+  assert_eq!(round.missing(), (2 ..= PARTICIPANTS).collect::<Vec<_>>());
+  assert!(round.late().is_empty());
+  round.check().unwrap();
+
+  let mut round = machine.start_round(Duration::ZERO);
+  assert!(round.expired());
+  assert!(matches!(round.received(1), Err(FrostError::NotIncluded(1))));
+  round.received(2).unwrap();
+  assert!(matches!(
+    round.check(),
+    Err(FrostError::Timeout { missing }) if missing == (3 ..= PARTICIPANTS).collect::<Vec<_>>()
+  ));
+  for l in 3 ..= PARTICIPANTS {
+    round.received(l).unwrap();
+  }
+  round.check().unwrap();
+
+  // Signing machines expect the rest of the signing set
+  let keys = key_gen::<_, C>(&mut *rng);
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let mut commitments = HashMap::new();
+  let mut machines = included.iter().map(|i| {
+    let (machine, preprocess) = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      keys[i].clone(),
+      &included
+    ).unwrap().preprocess(&mut *rng);
+    commitments.insert(*i, preprocess);
+    (*i, machine)
+  }).collect::<HashMap<_, _>>();
+  assert_eq!(
+    machines[&1].start_round(Duration::ZERO).late(),
+    (2 ..= THRESHOLD).collect::<Vec<_>>()
+  );
+
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+  let mut machine = machines.remove(&1).unwrap();
+  machine.add_share(2, shares[&2]).unwrap();
+  assert_eq!(machine.start_round(Duration::ZERO).late(), (3 ..= THRESHOLD).collect::<Vec<_>>());
+}
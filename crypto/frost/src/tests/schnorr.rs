@@ -2,11 +2,21 @@ use std::{marker::PhantomData, sync::Arc, collections::HashMap};
 
 use rand_core::{RngCore, CryptoRng};
 
-use group::ff::Field;
+use group::{ff::Field, Group};
 
 use crate::{
-  Curve, MultisigKeys, schnorr::{self, SchnorrSignature}, algorithm::{Hram, Schnorr},
-  tests::{key_gen, algorithm_machines, sign as sign_test}
+  Curve, MultisigKeys, FrostError, DeserializeError, Writable,
+  curve::CurveError,
+  schnorr::{self, SchnorrSignature}, algorithm::{Hram, Schnorr, SignatureBatch},
+  key_gen::{KeyGenVariant, deal},
+  sign::{
+    Preprocess, SignatureShare, PreprocessPackage, NonceRegistry, MemoryNonceRegistry,
+    PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine, Progress
+  },
+  tests::{
+    THRESHOLD, PARTICIPANTS,
+    clone_without, key_gen, key_gen_with_variant, recover, algorithm_machines, sign as sign_test
+  }
 };
 
 pub(crate) fn core_sign<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
@@ -15,7 +25,7 @@ pub(crate) fn core_sign<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   let challenge = C::F::random(rng); // Doesn't bother to craft an HRAM
   assert!(
     schnorr::verify::<C>(
-      C::GENERATOR_TABLE * private_key,
+      C::generator_table() * private_key,
       challenge,
       &schnorr::sign(private_key, nonce, challenge)
     )
@@ -28,13 +38,75 @@ pub(crate) fn core_sign<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
 pub(crate) fn core_verify<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   assert!(
     !schnorr::verify::<C>(
-      C::GENERATOR_TABLE * C::F::random(&mut *rng),
+      C::generator_table() * C::F::random(&mut *rng),
       C::F::random(rng),
-      &SchnorrSignature { R: C::GENERATOR_TABLE * C::F::zero(), s: C::F::zero() }
+      &SchnorrSignature { R: C::generator_table() * C::F::zero(), s: C::F::zero() }
     )
   );
 }
 
+pub(crate) fn core_signature_string<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let sig = schnorr::sign::<C>(
+    C::F::random(&mut *rng),
+    C::F::random(&mut *rng),
+    C::F::random(&mut *rng)
+  );
+  let string = sig.to_string();
+  assert_eq!(string, hex::encode(sig.serialize()));
+  assert_eq!(string.parse::<SchnorrSignature<C>>().unwrap(), sig);
+  assert!(string[.. string.len() - 2].parse::<SchnorrSignature<C>>().is_err());
+
+  #[cfg(feature = "bech32")]
+  {
+    let encoded = sig.to_bech32("sig").unwrap();
+    assert_eq!(SchnorrSignature::<C>::from_bech32("sig", &encoded).unwrap(), sig);
+    assert!(SchnorrSignature::<C>::from_bech32("sig", &encoded[.. encoded.len() - 1]).is_err());
+  }
+}
+
+pub(crate) fn core_signature_deserialize<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let sig = schnorr::sign::<C>(
+    C::F::random(&mut *rng),
+    C::F::random(&mut *rng),
+    C::F::random(&mut *rng)
+  );
+  let serialized = sig.serialize();
+  assert_eq!(SchnorrSignature::<C>::deserialize(&serialized).unwrap(), sig);
+  assert_eq!(SchnorrSignature::<C>::read(&mut serialized.as_slice()).unwrap(), sig);
+
+  let len = serialized.len();
+  assert!(matches!(
+    SchnorrSignature::<C>::deserialize(&serialized[.. len - 1]),
+    Err(CurveError::InvalidLength(expected, actual)) if (expected == len) && (actual == (len - 1))
+  ));
+  assert!(matches!(
+    SchnorrSignature::<C>::deserialize(&[serialized.clone(), vec![0]].concat()),
+    Err(CurveError::InvalidLength(..))
+  ));
+  assert!(SchnorrSignature::<C>::read(&mut &serialized[.. len - 1]).is_err());
+
+  // Curves may already refuse to decode the identity
+  let identity = SchnorrSignature::<C> { R: C::G::identity(), s: sig.s };
+  assert!(matches!(
+    SchnorrSignature::<C>::deserialize(&identity.serialize()),
+    Err(CurveError::IdentityPoint | CurveError::InvalidPoint)
+  ));
+  assert!(matches!(
+    identity.to_string().parse::<SchnorrSignature<C>>(),
+    Err(CurveError::IdentityPoint | CurveError::InvalidPoint)
+  ));
+
+  // A scalar of all 0xff bytes is above the modulus for every supported curve
+  let mut unreduced = serialized;
+  for byte in &mut unreduced[C::G_len() ..] {
+    *byte = 0xff;
+  }
+  assert!(matches!(
+    SchnorrSignature::<C>::deserialize(&unreduced),
+    Err(CurveError::InvalidScalar)
+  ));
+}
+
 pub(crate) fn core_batch_verify<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   // Create 5 signatures
   let mut keys = vec![];
@@ -48,7 +120,7 @@ pub(crate) fn core_batch_verify<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
 
   // Batch verify
   let triplets = (0 .. 5).map(
-    |i| (u16::try_from(i + 1).unwrap(), C::GENERATOR_TABLE * keys[i], challenges[i], sigs[i])
+    |i| (u16::try_from(i + 1).unwrap(), C::generator_table() * keys[i], challenges[i], sigs[i])
   ).collect::<Vec<_>>();
   schnorr::batch_verify(rng, &triplets).unwrap();
 
@@ -100,11 +172,71 @@ impl<C: Curve> Hram<C> for TestHram<C> {
   }
 }
 
+fn signature_share_encoding<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let share = SignatureShare::<C>(C::F::random(&mut *rng));
+  assert_eq!(SignatureShare::<C>::from_bytes(&share.to_bytes()).unwrap(), share);
+  assert_eq!(share.to_bytes(), share.serialize());
+  assert!(share != SignatureShare(share.0 + C::F::one()));
+  assert!(SignatureShare::<C>::from_bytes(&vec![0xff; C::F_len()]).is_err());
+  assert!(SignatureShare::<C>::from_bytes(&share.to_bytes()[1 ..]).is_err());
+}
+
 fn sign<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   let keys = key_gen::<_, C>(&mut *rng);
   sign_core(rng, keys[&1].group_key(), &keys);
 }
 
+fn sign_batch<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  // Signatures from two distinct multisigs
+  let keys = [key_gen::<_, C>(&mut *rng), key_gen::<_, C>(&mut *rng)];
+  let messages: [&[u8]; 3] = [b"first", b"second", b"third"];
+
+  let mut signed = vec![];
+  for (i, msg) in messages.iter().enumerate() {
+    let keys = &keys[i % 2];
+    let machines = algorithm_machines(&mut *rng, Schnorr::<C, TestHram<C>>::new(), keys);
+    signed.push((keys[&1].group_key(), *msg, sign_test(&mut *rng, machines, msg)));
+  }
+
+  let mut batch = SignatureBatch::<C>::new();
+  assert!(batch.is_empty());
+  batch.verify(&mut *rng).unwrap();
+  for (i, (key, msg, sig)) in signed.iter().enumerate() {
+    assert_eq!(batch.queue::<TestHram<C>>(*key, msg, *sig), i);
+  }
+  assert_eq!(batch.len(), 3);
+  batch.verify(&mut *rng).unwrap();
+
+  // Each signature is blamed when it's invalid, including when it's only valid for another key
+  // or message
+  for i in 0 .. 3 {
+    let mut batch = SignatureBatch::<C>::new();
+    for (l, (key, msg, sig)) in signed.iter().enumerate() {
+      if i == l {
+        batch.queue::<TestHram<C>>(*key, messages[(i + 1) % 3], *sig);
+      } else {
+        batch.queue::<TestHram<C>>(*key, msg, *sig);
+      }
+    }
+    assert_eq!(batch.verify(&mut *rng), Err(i));
+  }
+}
+
+fn sign_with_pedpop<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen_with_variant::<_, C>(&mut *rng, KeyGenVariant::PedPop);
+  sign_core(rng, keys[&1].group_key(), &keys);
+}
+
+fn sign_with_dealt<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let private_key = C::F::random(&mut *rng);
+  let mut keys = deal::<_, C>(&mut *rng, THRESHOLD, PARTICIPANTS, private_key).unwrap();
+  assert_eq!(recover(&keys), private_key);
+
+  let group_key = C::generator_table() * private_key;
+  let keys = keys.drain().map(|(i, keys)| (i, Arc::new(keys))).collect();
+  sign_core(rng, group_key, &keys);
+}
+
 fn sign_with_offset<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   let mut keys = key_gen::<_, C>(&mut *rng);
   let group_key = keys[&1].group_key();
@@ -113,19 +245,345 @@ fn sign_with_offset<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   for i in 1 ..= u16::try_from(keys.len()).unwrap() {
     keys.insert(i, Arc::new(keys[&i].offset(offset)));
   }
-  let offset_key = group_key + (C::GENERATOR_TABLE * offset);
+  let offset_key = group_key + (C::generator_table() * offset);
 
   sign_core(rng, offset_key, &keys);
 }
 
+fn sign_with_context<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+  let with_context = |context: &[u8]| keys.iter().map(
+    |(i, keys)| (*i, Arc::new(keys.with_context(context)))
+  ).collect::<HashMap<_, _>>();
+  assert_eq!(with_context(b"Network A")[&1].context(), Some(b"Network A".as_ref()));
+  sign_core(&mut *rng, group_key, &with_context(b"Network A"));
+
+  // A signer using another deployment's context is blamed by everyone else
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let mut contextual = with_context(b"Network A");
+  contextual.insert(1, with_context(b"Network B")[&1].clone());
+  let mut commitments = HashMap::new();
+  let mut machines = included.iter().map(|i| {
+    let (machine, preprocess) = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      contextual[i].clone(),
+      &included
+    ).unwrap().preprocess(&mut *rng);
+    commitments.insert(*i, preprocess);
+    (*i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+  for (i, machine) in machines.drain() {
+    let sig = machine.complete(clone_without(&shares, &i));
+    if i == 1 {
+      assert!(sig.is_err());
+    } else {
+      assert!(matches!(sig, Err(FrostError::InvalidShare(1))));
+    }
+  }
+}
+
+fn sign_incrementally<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+  let mut machines = algorithm_machines(rng, Schnorr::<C, TestHram<C>>::new(), &keys);
+
+  let mut included = machines.keys().cloned().collect::<Vec<_>>();
+  included.sort();
+
+  let mut commitments = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let (machine, preprocess) = machine.preprocess(&mut *rng);
+    // Every machine expects messages of the length it itself sends
+    assert_eq!(preprocess.serialize().len(), machine.message_len());
+    assert_eq!(machine.included(), included);
+    commitments.insert(i, preprocess);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+    assert_eq!(share.serialize().len(), machine.message_len());
+    assert_eq!(machine.included(), included);
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  for (i, mut machine) in machines.drain() {
+    let mut missing = included.iter().filter(|l| **l != i).cloned().collect::<Vec<_>>();
+    assert_eq!(machine.missing(), missing);
+
+    let first = missing[0];
+    // Invalid shares are identified on arrival
+    let invalid = SignatureShare(shares[&first].0 + C::F::one());
+    assert!(
+      matches!(machine.add_share(first, invalid), Err(FrostError::InvalidShare(l)) if l == first)
+    );
+    // Our own share is already known
+    assert!(matches!(machine.add_share(i, shares[&i]), Err(FrostError::DuplicatedIndex(_))));
+
+    while !missing.is_empty() {
+      let l = missing.remove(0);
+      let progress = machine.add_share(l, shares[&l]).unwrap();
+      if missing.is_empty() {
+        assert_eq!(progress, Progress::Complete);
+      } else {
+        assert_eq!(progress, Progress::Missing(missing.clone()));
+      }
+    }
+    assert!(
+      matches!(machine.add_share(first, shares[&first]), Err(FrostError::DuplicatedIndex(_)))
+    );
+
+    let sig = machine.finalize().unwrap();
+    assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), &sig));
+  }
+}
+
+fn sign_with_subset<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+
+  // Preprocess with every participant but the last, and then have the first fail to respond
+  let potential = (1 .. PARTICIPANTS).collect::<Vec<_>>();
+  let mut commitments = HashMap::new();
+  let mut machines = potential.iter().map(|i| {
+    let machine = AlgorithmMachine::new(
+      Schnorr::<C, TestHram<C>>::new(),
+      keys[i].clone(),
+      &potential
+    ).unwrap();
+    let (machine, preprocess) = machine.preprocess(&mut *rng);
+    commitments.insert(*i, preprocess);
+    (*i, machine)
+  }).collect::<HashMap<_, _>>();
+  machines.remove(&1);
+  commitments.remove(&1);
+
+  let mut included = machines.keys().cloned().collect::<Vec<_>>();
+  included.sort();
+  assert_eq!(included.len(), usize::from(THRESHOLD));
+
+  // The signing set can only be narrowed to participants who were preprocessed for
+  let mut invalid = included.clone();
+  invalid[0] = PARTICIPANTS;
+  let (machine, _) = AlgorithmMachine::new(
+    Schnorr::<C, TestHram<C>>::new(),
+    keys[&invalid[1]].clone(),
+    &potential
+  ).unwrap().preprocess(&mut *rng);
+  assert!(matches!(machine.select(&invalid), Err(FrostError::NotIncluded(PARTICIPANTS))));
+
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let machine = machine.select(&included).unwrap();
+    let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  for (i, machine) in machines.drain() {
+    let sig = machine.complete(clone_without(&shares, &i)).unwrap();
+    assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), &sig));
+  }
+}
+
+fn sign_with_randomizer<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let randomizer = C::F::random(&mut *rng);
+  let randomized_key = keys[&1].group_key() + (C::generator_table() * randomizer);
+
+  // Randomize after preprocessing, as the randomizer may be chosen per signature
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let sign = |rng: &mut R, randomized: &[u16]| {
+    let mut commitments = HashMap::new();
+    let mut machines = included.iter().map(|i| {
+      let (machine, preprocess) = AlgorithmMachine::new(
+        Schnorr::<C, TestHram<C>>::new(),
+        keys[i].clone(),
+        &included
+      ).unwrap().preprocess(&mut *rng);
+      commitments.insert(*i, preprocess);
+      (*i, if randomized.contains(i) { machine.randomize(randomizer) } else { machine })
+    }).collect::<HashMap<_, _>>();
+
+    let mut shares = HashMap::new();
+    let mut machines = machines.drain().map(|(i, machine)| {
+      let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+      shares.insert(i, share);
+      (i, machine)
+    }).collect::<HashMap<_, _>>();
+    machines.drain().map(
+      |(i, machine)| (i, machine.complete(clone_without(&shares, &i)))
+    ).collect::<HashMap<_, _>>()
+  };
+
+  for (_, sig) in sign(&mut *rng, &included) {
+    let sig = sig.unwrap();
+    assert!(schnorr::verify(
+      randomized_key,
+      TestHram::<C>::hram(&sig.R, &randomized_key, MESSAGE),
+      &sig
+    ));
+  }
+
+  // A signer who wasn't supplied the randomizer is blamed by everyone who was
+  for (i, sig) in sign(&mut *rng, &included[1 ..]) {
+    if i == 1 {
+      assert!(sig.is_err());
+    } else {
+      assert!(matches!(sig, Err(FrostError::InvalidShare(1))));
+    }
+  }
+}
+
+fn sign_with_registry<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let registry: Arc<dyn NonceRegistry> = Arc::new(MemoryNonceRegistry::default());
+  let machine = |i: u16| AlgorithmMachine::new(
+    Schnorr::<C, TestHram<C>>::new(),
+    keys[&i].clone(),
+    &included
+  ).unwrap().with_registry(registry.clone());
+
+  let mut commitments = HashMap::new();
+  for i in 2 ..= THRESHOLD {
+    commitments.insert(i, machine(i).preprocess(&mut *rng).1);
+  }
+
+  // Reuse a preprocess, as a signer which cached one may
+  let nonces = [C::F::random(&mut *rng), C::F::random(&mut *rng)];
+  let package = || PreprocessPackage {
+    nonces,
+    preprocess: Preprocess {
+      commitments: [C::generator() * nonces[0], C::generator() * nonces[1]],
+      addendum: vec![]
+    }
+  };
+  assert!(
+    machine(1).unsafe_override_preprocess(package()).0.sign(commitments.clone(), MESSAGE).is_ok()
+  );
+  assert!(matches!(
+    machine(1).unsafe_override_preprocess(package()).0.sign(commitments.clone(), MESSAGE),
+    Err(FrostError::ReusedPreprocess)
+  ));
+
+  // Fresh preprocesses remain usable
+  assert!(machine(1).preprocess(&mut *rng).0.sign(commitments, MESSAGE).is_ok());
+}
+
+fn sign_with_suspension<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+  const KEY: &[u8] = b"FROST Test sign_with_suspension";
+
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+  let machine = |i: u16| AlgorithmMachine::new(
+    Schnorr::<C, TestHram<C>>::new(),
+    keys[&i].clone(),
+    &included
+  ).unwrap();
+
+  let mut commitments = HashMap::new();
+  let mut machines = included.iter().map(|i| {
+    let (machine, preprocess) = machine(*i).preprocess(&mut *rng);
+    commitments.insert(*i, preprocess);
+    (*i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // Suspend and resume the first participant between preprocessing and signing
+  let suspended = machines.remove(&1).unwrap().suspend(&mut *rng, KEY);
+  let registry: Arc<dyn NonceRegistry> = Arc::new(MemoryNonceRegistry::default());
+  assert!(matches!(
+    machine(1).resume(&mut *rng, registry.clone(), b"wrong key", &mut suspended.as_slice()),
+    Err(FrostError::DeserializeError(DeserializeError::DecryptionFailed))
+  ));
+  assert!(matches!(
+    machine(1).resume(
+      &mut *rng,
+      registry.clone(),
+      KEY,
+      &mut &suspended[.. suspended.len() - 1]
+    ),
+    Err(FrostError::DeserializeError(DeserializeError::Truncated))
+  ));
+  machines.insert(
+    1,
+    machine(1).resume(&mut *rng, registry.clone(), KEY, &mut suspended.as_slice()).unwrap()
+  );
+
+  let mut shares = HashMap::new();
+  let mut machines = machines.drain().map(|(i, machine)| {
+    let (machine, share) = machine.sign(clone_without(&commitments, &i), MESSAGE).unwrap();
+    shares.insert(i, share);
+    (i, machine)
+  }).collect::<HashMap<_, _>>();
+
+  // The suspended state can only be signed with once
+  assert!(matches!(
+    machine(1)
+      .resume(&mut *rng, registry, KEY, &mut suspended.as_slice())
+      .unwrap()
+      .sign(clone_without(&commitments, &1), MESSAGE),
+    Err(FrostError::ReusedPreprocess)
+  ));
+
+  // Suspend and resume the first participant while collecting shares
+  let mut first = machines.remove(&1).unwrap();
+  first.add_share(2, shares[&2]).unwrap();
+  let mut first = machine(1).resume_signature(&mut *rng, &mut first.suspend().as_slice()).unwrap();
+  assert_eq!(first.missing(), (3 ..= THRESHOLD).collect::<Vec<_>>());
+  for l in 3 ..= THRESHOLD {
+    first.add_share(l, shares[&l]).unwrap();
+  }
+  let sig = first.finalize().unwrap();
+  assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), &sig));
+
+  for (i, machine) in machines.drain() {
+    assert_eq!(machine.complete(clone_without(&shares, &i)).unwrap(), sig);
+  }
+}
+
 pub fn test_schnorr<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
   // Test Schnorr signatures work as expected
   // This is a bit unnecessary, as they should for any valid curve, yet this establishes sanity
   core_sign::<_, C>(rng);
   core_verify::<_, C>(rng);
   core_batch_verify::<_, C>(rng);
+  core_signature_string::<_, C>(rng);
+  core_signature_deserialize::<_, C>(rng);
 
   // Test Schnorr signatures under FROST
+  signature_share_encoding::<_, C>(rng);
   sign::<_, C>(rng);
+  sign_batch::<_, C>(rng);
+  sign_with_pedpop::<_, C>(rng);
+  sign_with_dealt::<_, C>(rng);
   sign_with_offset::<_, C>(rng);
+  sign_with_context::<_, C>(rng);
+  sign_incrementally::<_, C>(rng);
+  sign_with_subset::<_, C>(rng);
+  sign_with_randomizer::<_, C>(rng);
+  sign_with_registry::<_, C>(rng);
+  sign_with_suspension::<_, C>(rng);
 }
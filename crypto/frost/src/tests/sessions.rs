@@ -0,0 +1,86 @@
+use std::{sync::Arc, collections::HashMap};
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, FrostError,
+  schnorr, algorithm::{Hram, Schnorr},
+  sign::MemoryNonceRegistry,
+  sessions::Sessions,
+  tests::{THRESHOLD, clone_without, key_gen, schnorr::TestHram}
+};
+
+pub fn test_sessions<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+  let included = (1 ..= THRESHOLD).collect::<Vec<_>>();
+
+  let mut sessions = included.iter().map(|i| (
+    *i,
+    Sessions::<C, Schnorr<C, TestHram<C>>>::new(
+      keys[i].clone(),
+      Arc::new(MemoryNonceRegistry::default()),
+      2
+    )
+  )).collect::<HashMap<_, _>>();
+
+  // Run two sessions at once, interleaving their rounds
+  let ids: [&[u8]; 2] = [b"first", b"second"];
+  let mut preprocesses = HashMap::new();
+  for id in ids {
+    for (i, sessions) in sessions.iter_mut() {
+      let preprocess = sessions.preprocess(&mut *rng, id, Schnorr::new(), &included).unwrap();
+      preprocesses.entry(id).or_insert_with(HashMap::new).insert(*i, preprocess);
+    }
+  }
+
+  // The first session is still running, and only two sessions may run at once
+  assert!(matches!(
+    sessions.get_mut(&1).unwrap().preprocess(&mut *rng, ids[0], Schnorr::new(), &included),
+    Err(FrostError::DuplicatedSession)
+  ));
+  assert!(matches!(
+    sessions.get_mut(&1).unwrap().preprocess(&mut *rng, b"third", Schnorr::new(), &included),
+    Err(FrostError::TooManySessions(2))
+  ));
+  // Shares can't be read, nor signatures completed, before signing
+  assert!(sessions[&1].read_share(ids[0], &mut [0; 64].as_ref()).is_err());
+  assert!(matches!(
+    sessions.get_mut(&1).unwrap().complete(ids[0], HashMap::new()),
+    Err(FrostError::UnknownSession)
+  ));
+
+  let mut shares = HashMap::new();
+  for id in ids.iter().rev() {
+    for (i, sessions) in sessions.iter_mut() {
+      let share = sessions.sign(id, clone_without(&preprocesses[id], i), id).unwrap();
+      shares.entry(*id).or_insert_with(HashMap::new).insert(*i, share);
+    }
+  }
+
+  for id in ids {
+    for (i, sessions) in sessions.iter_mut() {
+      let sig = sessions.complete(id, clone_without(&shares[id], i)).unwrap();
+      assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, id), &sig));
+    }
+  }
+
+  // Completed sessions free their slot, yet their IDs can't be reused
+  let sessions = sessions.get_mut(&1).unwrap();
+  assert!(sessions.active().is_empty());
+  assert!(matches!(
+    sessions.preprocess(&mut *rng, ids[0], Schnorr::new(), &included),
+    Err(FrostError::DuplicatedSession)
+  ));
+  assert!(matches!(
+    sessions.sign(ids[0], HashMap::new(), ids[0]),
+    Err(FrostError::UnknownSession)
+  ));
+
+  // Aborted sessions also free their slot
+  sessions.preprocess(&mut *rng, b"third", Schnorr::new(), &included).unwrap();
+  assert_eq!(sessions.active(), vec![b"third".to_vec()]);
+  assert!(sessions.abort(b"third"));
+  assert!(!sessions.abort(b"third"));
+  assert!(sessions.active().is_empty());
+}
@@ -3,12 +3,18 @@ use std::{sync::Arc, collections::HashMap};
 use rand_core::{RngCore, CryptoRng};
 
 use crate::{
-  Curve, MultisigKeys,
+  Curve, MultisigKeys, Writable,
   algorithm::{Schnorr, Hram},
-  sign::{PreprocessPackage, SignMachine, SignatureMachine, AlgorithmMachine},
-  tests::{curve::test_curve, schnorr::test_schnorr, recover}
+  sign::{Preprocess, PreprocessPackage, SignMachine, SignatureMachine, AlgorithmMachine},
+  tests::{
+    curve::test_curve, schnorr::test_schnorr, weighted::test_weighted, nested::test_nested,
+    coordinator::test_coordinator, round::test_round, backup::test_backup, echo::test_echo,
+    blame::test_blame, compact::test_compact, sessions::test_sessions, complaint::test_complaint,
+    migration::test_migration, header::test_header, recover
+  }
 };
 
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Vectors {
   pub threshold: u16,
   pub shares: &'static [&'static str],
@@ -22,13 +28,71 @@ pub struct Vectors {
   pub sig: String
 }
 
+#[cfg(feature = "json_vectors")]
+impl Vectors {
+  /// Parse vectors from the JSON format the IETF draft publishes them in
+  /// The parsed strings are leaked, as Vectors is designed around static literals
+  pub fn from_json(json: &str) -> Vectors {
+    use serde_json::Value;
+
+    fn leak(value: &Value) -> &'static str {
+      Box::leak(value.as_str().unwrap().to_string().into_boxed_str())
+    }
+
+    // The IETF's files encode every number as a string
+    fn number(value: &Value) -> u16 {
+      value.as_str().map(|value| value.parse().unwrap()).or(
+        value.as_u64().map(|value| u16::try_from(value).unwrap())
+      ).unwrap()
+    }
+
+    let json: Value = serde_json::from_str(json).unwrap();
+    let n = number(&json["config"]["MAX_PARTICIPANTS"]);
+    let included = json["round_one_outputs"]["participants"].as_str().unwrap()
+      .split(',').map(|l| l.trim().parse().unwrap()).collect::<Vec<u16>>();
+
+    let round_one = &json["round_one_outputs"]["signers"];
+    let round_two = &json["round_two_outputs"]["signers"];
+    Vectors {
+      threshold: number(&json["config"]["MIN_PARTICIPANTS"]),
+      shares: Box::leak(
+        (1 ..= n).map(
+          |i| leak(&json["inputs"]["signers"][i.to_string()]["signer_share"])
+        ).collect::<Vec<_>>().into_boxed_slice()
+      ),
+      group_secret: leak(&json["inputs"]["group_secret_key"]),
+      group_key: leak(&json["inputs"]["group_public_key"]),
+
+      msg: leak(&json["inputs"]["message"]),
+      nonces: Box::leak(
+        included.iter().map(|l| {
+          let signer = &round_one[l.to_string()];
+          [leak(&signer["hiding_nonce"]), leak(&signer["binding_nonce"])]
+        }).collect::<Vec<_>>().into_boxed_slice()
+      ),
+      sig_shares: Box::leak(
+        included.iter().map(
+          |l| leak(&round_two[l.to_string()]["sig_share"])
+        ).collect::<Vec<_>>().into_boxed_slice()
+      ),
+      included: Box::leak(included.into_boxed_slice()),
+      sig: json["final_output"]["sig"].as_str().unwrap().to_string()
+    }
+  }
+
+  /// Load vectors from a JSON file published by the IETF draft
+  pub fn load<P: AsRef<std::path::Path>>(path: P) -> Vectors {
+    Vectors::from_json(&std::fs::read_to_string(path).unwrap())
+  }
+}
+
 // Load these vectors into MultisigKeys using a custom serialization it'll deserialize
 fn vectors_to_multisig_keys<C: Curve>(vectors: &Vectors) -> HashMap<u16, MultisigKeys<C>> {
   let shares = vectors.shares.iter().map(
     |secret| C::F_from_slice(&hex::decode(secret).unwrap()).unwrap()
   ).collect::<Vec<_>>();
   let verification_shares = shares.iter().map(
-    |secret| C::GENERATOR * secret
+    |secret| C::generator() * secret
   ).collect::<Vec<_>>();
 
   let mut keys = HashMap::new();
@@ -64,13 +128,31 @@ pub fn test_with_vectors<
 >(rng: &mut R, vectors: Vectors) {
   // Do basic tests before trying the vectors
   test_curve::<_, C>(&mut *rng);
-  test_schnorr::<_, C>(rng);
+  test_schnorr::<_, C>(&mut *rng);
+  test_weighted::<_, C>(&mut *rng);
+  test_nested::<_, C>(&mut *rng);
+  test_coordinator::<_, C>(&mut *rng);
+  test_round::<_, C>(&mut *rng);
+  test_backup::<_, C>(&mut *rng);
+  test_echo::<_, C>(&mut *rng);
+  test_blame::<_, C>(&mut *rng);
+  test_compact::<_, C>(&mut *rng);
+  test_sessions::<_, C>(&mut *rng);
+  test_complaint::<_, C>(&mut *rng);
+  test_migration::<_, C, H>(&mut *rng);
+  test_header::<_, C, H>(&mut *rng);
+  #[cfg(feature = "keystore")]
+  crate::tests::keystore::test_keystore::<_, C>(&mut *rng);
+  #[cfg(feature = "audit")]
+  crate::tests::audit::test_audit::<_, C>(&mut *rng);
+  #[cfg(feature = "lowmem")]
+  crate::tests::lowmem::test_lowmem::<_, C, H>(rng);
 
   // Test against the vectors
   let keys = vectors_to_multisig_keys::<C>(&vectors);
   let group_key = C::G_from_slice(&hex::decode(vectors.group_key).unwrap()).unwrap();
   assert_eq!(
-    C::GENERATOR * C::F_from_slice(&hex::decode(vectors.group_secret).unwrap()).unwrap(),
+    C::generator() * C::F_from_slice(&hex::decode(vectors.group_secret).unwrap()).unwrap(),
     group_key
   );
   assert_eq!(
@@ -99,14 +181,17 @@ pub fn test_with_vectors<
     ];
     c += 1;
 
-    let mut serialized = C::G_to_bytes(&(C::GENERATOR * nonces[0]));
-    serialized.extend(&C::G_to_bytes(&(C::GENERATOR * nonces[1])));
-
-    let (machine, serialized) = machine.unsafe_override_preprocess(
-      PreprocessPackage { nonces, serialized: serialized.clone() }
+    let (machine, preprocess) = machine.unsafe_override_preprocess(
+      PreprocessPackage {
+        nonces,
+        preprocess: Preprocess {
+          commitments: [C::generator() * nonces[0], C::generator() * nonces[1]],
+          addendum: vec![]
+        }
+      }
     );
 
-    commitments.insert(i, serialized);
+    commitments.insert(i, preprocess);
     (i, machine)
   }).collect::<Vec<_>>();
 
@@ -118,7 +203,7 @@ pub fn test_with_vectors<
       &hex::decode(vectors.msg).unwrap()
     ).unwrap();
 
-    assert_eq!(share, hex::decode(vectors.sig_shares[c]).unwrap());
+    assert_eq!(share.serialize(), hex::decode(vectors.sig_shares[c]).unwrap());
     c += 1;
 
     shares.insert(i, share);
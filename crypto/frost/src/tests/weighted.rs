@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  Curve, MultisigKeys, Writable, schnorr,
+  key_gen::KeyGenVariant,
+  algorithm::{Hram, Schnorr},
+  weighted::{Weights, WeightedKeys, WeightedKeyGenMachine, WeightedMachine},
+  tests::{clone_without, recover, sign, schnorr::TestHram}
+};
+
+pub const WEIGHTS: &[u16] = &[1, 2, 3];
+pub const WEIGHTED_THRESHOLD: u16 = 4;
+
+pub fn weighted_key_gen<R: RngCore + CryptoRng, C: Curve>(
+  rng: &mut R
+) -> HashMap<u16, WeightedKeys<C>> {
+  let weights = Weights::new(WEIGHTS.to_vec()).unwrap();
+
+  let mut machines = HashMap::new();
+  let mut commitments = HashMap::new();
+  for i in 1 ..= weights.identities() {
+    let machine = WeightedKeyGenMachine::<C>::new(
+      weights.clone(),
+      WEIGHTED_THRESHOLD,
+      i,
+      "FROST Test weighted_key_gen".to_string(),
+      KeyGenVariant::Frost
+    ).unwrap();
+    let (machine, these_commitments) = machine.generate_coefficients(rng);
+    machines.insert(i, machine);
+    commitments.insert(i, these_commitments.serialize());
+  }
+
+  let mut secret_shares = HashMap::new();
+  let mut machines = machines.drain().map(|(l, machine)| {
+    let commitments = clone_without(&commitments, &l).drain().map(
+      |(i, commitments)| (i, machine.read_commitments(&mut commitments.as_slice()).unwrap())
+    ).collect();
+    let (machine, mut shares) = machine.generate_secret_shares(
+      rng,
+      commitments
+    ).unwrap();
+    secret_shares.insert(
+      l,
+      shares.drain().map(|(i, shares)| (i, shares.serialize())).collect::<HashMap<_, _>>()
+    );
+    (l, machine)
+  }).collect::<HashMap<_, _>>();
+
+  let mut group_key = None;
+  machines.drain().map(|(i, machine)| {
+    let mut our_secret_shares = HashMap::new();
+    for (l, shares) in &secret_shares {
+      if i == *l {
+        continue;
+      }
+      our_secret_shares.insert(*l, machine.read_shares(&mut shares[&i].as_slice()).unwrap());
+    }
+    let these_keys = machine.complete(rng, our_secret_shares).unwrap();
+    assert_eq!(these_keys.keys().len(), usize::from(weights.weight(i)));
+
+    if group_key.is_none() {
+      group_key = Some(these_keys.group_key());
+    }
+    assert_eq!(group_key.unwrap(), these_keys.group_key());
+
+    (i, these_keys)
+  }).collect()
+}
+
+pub fn test_weighted<R: RngCore + CryptoRng, C: Curve>(rng: &mut R) {
+  const MESSAGE: &[u8] = b"Hello, World!";
+
+  let weights = Weights::new(WEIGHTS.to_vec()).unwrap();
+  assert_eq!(weights.n(), 6);
+  assert_eq!(weights.indices(2), vec![2, 3]);
+  assert_eq!(weights.identity(6), Some(3));
+  assert_eq!(weights.identity(7), None);
+  assert!(Weights::new(vec![1, 0]).is_err());
+
+  let keys = weighted_key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&1].group_key();
+
+  // Every share, regardless of which identity holds it, should be part of the same multisig
+  let shares = keys.values().flat_map(|keys| keys.keys().iter().map(
+    |keys| (keys.params().i(), MultisigKeys::clone(keys))
+  )).collect::<HashMap<_, _>>();
+  assert_eq!(shares.len(), usize::from(weights.n()));
+  recover(&shares);
+
+  // Identities 2 and 3 meet the threshold of 4 on their own, despite being only 2 identities
+  let included = [2, 3];
+  let machines = included.iter().map(|i| (
+    *i,
+    WeightedMachine::new(Schnorr::<C, TestHram<C>>::new(), &keys[i], &included).unwrap()
+  )).collect::<HashMap<_, _>>();
+  let sig = sign(&mut *rng, machines, MESSAGE);
+  assert!(schnorr::verify(group_key, TestHram::<C>::hram(&sig.R, &group_key, MESSAGE), &sig));
+
+  // Identities 1 and 2 don't
+  assert!(
+    WeightedMachine::new(Schnorr::<C, TestHram<C>>::new(), &keys[&1], &[1, 2]).is_err()
+  );
+}
@@ -0,0 +1,504 @@
+use std::{sync::Arc, io::{self, Read}, collections::{HashMap, BTreeMap}};
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{
+  curve::Curve,
+  FrostError, MultisigParams, MultisigKeys, read_vec,
+  key_gen::{KeyGenVariant, Commitments, SecretShare, KeyGenMachine, SecretShareMachine, KeyMachine},
+  algorithm::Algorithm,
+  sign::{
+    Preprocess, SignatureShare,
+    PreprocessMachine, SignMachine, SignatureMachine,
+    AlgorithmMachine, AlgorithmSignMachine, AlgorithmSignatureMachine
+  },
+  validate_map
+};
+
+/// Weights for a multisig where identities may hold multiple shares
+/// An identity with weight w holds w consecutive share indices, with identity 1 starting at
+/// index 1. The threshold is denoted in shares, not identities
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Weights(Vec<u16>);
+
+impl Weights {
+  pub fn new(weights: Vec<u16>) -> Result<Weights, FrostError> {
+    if weights.len() > usize::from(u16::MAX) {
+      Err(FrostError::TooManyParticipants(weights.len(), u16::MAX))?;
+    }
+
+    let mut n = 0usize;
+    for (i, weight) in weights.iter().enumerate() {
+      if *weight == 0 {
+        Err(FrostError::ZeroWeight(u16::try_from(i + 1).unwrap()))?;
+      }
+      n += usize::from(*weight);
+    }
+    if n > usize::from(u16::MAX) {
+      Err(FrostError::TooManyParticipants(n, u16::MAX))?;
+    }
+
+    Ok(Weights(weights))
+  }
+
+  /// Amount of identities
+  pub fn identities(&self) -> u16 {
+    u16::try_from(self.0.len()).unwrap()
+  }
+
+  /// Total amount of shares
+  pub fn n(&self) -> u16 {
+    self.0.iter().sum()
+  }
+
+  pub fn weight(&self, identity: u16) -> u16 {
+    self.0[usize::from(identity) - 1]
+  }
+
+  /// Share indices held by the specified identity
+  pub fn indices(&self, identity: u16) -> Vec<u16> {
+    let start = self.0[.. usize::from(identity - 1)].iter().sum::<u16>() + 1;
+    (start .. (start + self.weight(identity))).collect()
+  }
+
+  /// Identity holding the specified share index
+  pub fn identity(&self, index: u16) -> Option<u16> {
+    let mut end = 0;
+    for (i, weight) in self.0.iter().enumerate() {
+      end += weight;
+      if (index != 0) && (index <= end) {
+        return Some(u16::try_from(i + 1).unwrap());
+      }
+    }
+    None
+  }
+
+  fn check(&self, identity: u16) -> Result<(), FrostError> {
+    if (identity == 0) || (identity > self.identities()) {
+      Err(FrostError::InvalidParticipantIndex(self.identities(), identity))?;
+    }
+    Ok(())
+  }
+
+  // Errors from the underlying machines are per share index. Rewrite them to blame the identity
+  fn blame(&self, err: FrostError) -> FrostError {
+    let identity = |l| self.identity(l).unwrap_or(l);
+    match err {
+      FrostError::MissingParticipant(l) => FrostError::MissingParticipant(identity(l)),
+      FrostError::InvalidCommitment(l) => FrostError::InvalidCommitment(identity(l)),
+      FrostError::InvalidProofOfKnowledge(l) => FrostError::InvalidProofOfKnowledge(identity(l)),
+      FrostError::InvalidShare(l) => FrostError::InvalidShare(identity(l)),
+      _ => err
+    }
+  }
+}
+
+/// Keys for every share held by an identity in a weighted multisig
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WeightedKeys<C: Curve> {
+  weights: Weights,
+  identity: u16,
+  keys: Vec<Arc<MultisigKeys<C>>>,
+}
+
+impl<C: Curve> WeightedKeys<C> {
+  pub fn weights(&self) -> &Weights {
+    &self.weights
+  }
+
+  pub fn identity(&self) -> u16 {
+    self.identity
+  }
+
+  pub fn group_key(&self) -> C::G {
+    self.keys[0].group_key()
+  }
+
+  /// Keys for each held share, ordered by share index
+  pub fn keys(&self) -> &[Arc<MultisigKeys<C>>] {
+    &self.keys
+  }
+}
+
+pub struct WeightedKeyGenMachine<C: Curve> {
+  weights: Weights,
+  identity: u16,
+  machines: Vec<(u16, KeyGenMachine<C>)>,
+}
+
+pub struct WeightedSecretShareMachine<C: Curve> {
+  weights: Weights,
+  identity: u16,
+  machines: Vec<(u16, SecretShareMachine<C>)>,
+  our_commitments: HashMap<u16, Commitments<C>>,
+}
+
+pub struct WeightedKeyMachine<C: Curve> {
+  weights: Weights,
+  identity: u16,
+  machines: Vec<(u16, KeyMachine<C>)>,
+  our_shares: HashMap<u16, HashMap<u16, SecretShare<C>>>,
+}
+
+impl<C: Curve> WeightedKeyGenMachine<C> {
+  /// Creates a new machine to generate keys for every share held by the specified identity
+  /// t is the amount of shares needed to sign
+  pub fn new(
+    weights: Weights,
+    t: u16,
+    identity: u16,
+    context: String,
+    variant: KeyGenVariant
+  ) -> Result<WeightedKeyGenMachine<C>, FrostError> {
+    weights.check(identity)?;
+    let mut machines = vec![];
+    for i in weights.indices(identity) {
+      machines.push(
+        (i, KeyGenMachine::new(MultisigParams::new(t, weights.n(), i)?, context.clone(), variant))
+      );
+    }
+    Ok(WeightedKeyGenMachine { weights, identity, machines })
+  }
+
+  /// Start generating keys according to the FROST DKG spec, once per held share
+  /// Returns the commitments for each held share, which are to be handled exactly as the
+  /// commitments from KeyGenMachine are
+  pub fn generate_coefficients<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R
+  ) -> (WeightedSecretShareMachine<C>, Vec<Commitments<C>>) {
+    let mut all = vec![];
+    let mut our_commitments = HashMap::new();
+    let machines = self.machines.into_iter().map(|(i, machine)| {
+      let (machine, commitments) = machine.generate_coefficients(&mut *rng);
+      all.push(commitments.clone());
+      our_commitments.insert(i, commitments);
+      (i, machine)
+    }).collect();
+
+    (
+      WeightedSecretShareMachine {
+        weights: self.weights,
+        identity: self.identity,
+        machines,
+        our_commitments
+      },
+      all
+    )
+  }
+}
+
+// Machine, and the secret shares for every share held by every other identity
+type SecretShares<C> = (WeightedKeyMachine<C>, HashMap<u16, Vec<SecretShare<C>>>);
+
+impl<C: Curve> WeightedSecretShareMachine<C> {
+  /// Read another identity's commitments
+  pub fn read_commitments<R: Read>(&self, reader: &mut R) -> io::Result<Vec<Commitments<C>>> {
+    read_vec(reader, |reader| self.machines[0].1.read_commitments(reader))
+  }
+
+  /// Continue generating keys
+  /// Takes in every other identity's commitments. Returns the secret shares for each other
+  /// identity, which should be encrypted before sending
+  pub fn generate_secret_shares<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    mut commitments: HashMap<u16, Vec<Commitments<C>>>,
+  ) -> Result<SecretShares<C>, FrostError> {
+    let weights = self.weights;
+    validate_map(
+      &mut commitments,
+      &(1 ..= weights.identities()).collect::<Vec<_>>(),
+      (self.identity, vec![])
+    )?;
+    commitments.remove(&self.identity);
+
+    let mut all = self.our_commitments;
+    // Iterate in identity order so who's blamed doesn't depend on the HashMap's iteration order
+    for (l, commitments) in commitments.into_iter().collect::<BTreeMap<_, _>>() {
+      let indices = weights.indices(l);
+      if commitments.len() != indices.len() {
+        Err(FrostError::InvalidCommitment(l))?;
+      }
+      all.extend(indices.into_iter().zip(commitments));
+    }
+
+    let mut shares = HashMap::new();
+    let mut machines = vec![];
+    for (i, machine) in self.machines {
+      let mut these_commitments = all.clone();
+      these_commitments.remove(&i);
+      let (machine, these_shares) = machine.generate_secret_shares(&mut *rng, these_commitments)
+        .map_err(|e| weights.blame(e))?;
+      shares.insert(i, these_shares);
+      machines.push((i, machine));
+    }
+
+    // Shares between our own indices are kept locally
+    let ours = weights.indices(self.identity);
+    let mut our_shares = HashMap::new();
+    for b in &ours {
+      our_shares.insert(
+        *b,
+        ours.iter().filter(|a| *a != b).map(|a| (*a, shares[a][b].clone())).collect()
+      );
+    }
+
+    // Every other identity receives the shares from each of our indices (in order) for each of
+    // their indices (in order)
+    let mut res = HashMap::new();
+    for l in (1 ..= weights.identities()).filter(|l| *l != self.identity) {
+      let mut these_shares = vec![];
+      for a in &ours {
+        for b in weights.indices(l) {
+          these_shares.push(shares[a][&b].clone());
+        }
+      }
+      res.insert(l, these_shares);
+    }
+
+    Ok((WeightedKeyMachine { weights, identity: self.identity, machines, our_shares }, res))
+  }
+}
+
+impl<C: Curve> WeightedKeyMachine<C> {
+  /// Read the secret shares another identity sent to us
+  pub fn read_shares<R: Read>(&self, reader: &mut R) -> io::Result<Vec<SecretShare<C>>> {
+    read_vec(reader, |reader| self.machines[0].1.read_share(reader))
+  }
+
+  /// Complete key generation
+  /// Takes in every other identity's shares submitted to us
+  pub fn complete<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    mut shares: HashMap<u16, Vec<SecretShare<C>>>,
+  ) -> Result<WeightedKeys<C>, FrostError> {
+    let weights = self.weights;
+    validate_map(
+      &mut shares,
+      &(1 ..= weights.identities()).collect::<Vec<_>>(),
+      (self.identity, vec![])
+    )?;
+    shares.remove(&self.identity);
+
+    let ours = weights.indices(self.identity);
+    let mut received = self.our_shares;
+    for (l, parts) in shares.into_iter().collect::<BTreeMap<_, _>>() {
+      let theirs = weights.indices(l);
+      if parts.len() != (theirs.len() * ours.len()) {
+        Err(FrostError::InvalidShare(l))?;
+      }
+      let mut parts = parts.into_iter();
+      for a in &theirs {
+        for b in &ours {
+          received.get_mut(b).unwrap().insert(*a, parts.next().unwrap());
+        }
+      }
+    }
+
+    let mut keys = vec![];
+    for (i, machine) in self.machines {
+      let these_keys = machine.complete(&mut *rng, received.remove(&i).unwrap())
+        .map_err(|e| weights.blame(e))?;
+      keys.push(Arc::new(these_keys));
+    }
+
+    Ok(WeightedKeys { weights, identity: self.identity, keys })
+  }
+}
+
+/// State machine which signs with every share held by an identity
+pub struct WeightedMachine<C: Curve, A: Algorithm<C>> {
+  weights: Weights,
+  identity: u16,
+  included: Vec<u16>,
+  machines: Vec<(u16, AlgorithmMachine<C, A>)>,
+}
+
+pub struct WeightedSignMachine<C: Curve, A: Algorithm<C>> {
+  weights: Weights,
+  identity: u16,
+  included: Vec<u16>,
+  machines: Vec<(u16, AlgorithmSignMachine<C, A>)>,
+  our_preprocesses: HashMap<u16, Preprocess<C>>,
+}
+
+pub struct WeightedSignatureMachine<C: Curve, A: Algorithm<C>> {
+  weights: Weights,
+  identity: u16,
+  included: Vec<u16>,
+  machines: Vec<(u16, AlgorithmSignatureMachine<C, A>)>,
+  our_shares: HashMap<u16, SignatureShare<C>>,
+}
+
+impl<C: Curve, A: Algorithm<C>> WeightedMachine<C, A> {
+  /// Creates a new machine to sign with every share held by this identity
+  /// included is the list of signing identities, whose total weight must meet the threshold
+  pub fn new(
+    algorithm: A,
+    keys: &WeightedKeys<C>,
+    included: &[u16],
+  ) -> Result<WeightedMachine<C, A>, FrostError> {
+    let weights = keys.weights.clone();
+    let mut included = included.to_vec();
+    included.sort_unstable();
+    for i in 0 .. included.len() {
+      weights.check(included[i])?;
+      if (i != 0) && (included[i - 1] == included[i]) {
+        Err(FrostError::DuplicatedIndex(included[i].into()))?;
+      }
+    }
+    if !included.contains(&keys.identity) {
+      Err(FrostError::NotIncluded(keys.identity))?;
+    }
+
+    let indices = included.iter().flat_map(|l| weights.indices(*l)).collect::<Vec<_>>();
+    let mut machines = vec![];
+    for keys in &keys.keys {
+      machines.push((
+        keys.params().i(),
+        AlgorithmMachine::new(algorithm.clone(), keys.clone(), &indices)
+          .map_err(|e| weights.blame(e))?
+      ));
+    }
+
+    Ok(WeightedMachine { weights, identity: keys.identity, included, machines })
+  }
+}
+
+// Take the messages from each identity and convert them to the messages for each share index
+// Our own messages are additionally included, yet each machine's own message is left for it to
+// handle
+fn expand<T: Clone>(
+  weights: &Weights,
+  identity: u16,
+  included: &[u16],
+  mut messages: HashMap<u16, Vec<T>>,
+  ours: &HashMap<u16, T>,
+  err: fn(u16) -> FrostError
+) -> Result<HashMap<u16, T>, FrostError> {
+  validate_map(&mut messages, included, (identity, vec![]))?;
+  messages.remove(&identity);
+
+  let mut res = ours.clone();
+  for (l, messages) in messages.into_iter().collect::<BTreeMap<_, _>>() {
+    let indices = weights.indices(l);
+    if messages.len() != indices.len() {
+      Err(err(l))?;
+    }
+    res.extend(indices.into_iter().zip(messages));
+  }
+  Ok(res)
+}
+
+impl<C: Curve, A: Algorithm<C>> PreprocessMachine for WeightedMachine<C, A> {
+  type Preprocess = Vec<Preprocess<C>>;
+  type Signature = A::Signature;
+  type SignMachine = WeightedSignMachine<C, A>;
+
+  fn preprocess<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R
+  ) -> (Self::SignMachine, Vec<Preprocess<C>>) {
+    let mut preprocesses = vec![];
+    let mut our_preprocesses = HashMap::new();
+    let machines = self.machines.into_iter().map(|(i, machine)| {
+      let (machine, preprocess) = machine.preprocess(&mut *rng);
+      preprocesses.push(preprocess.clone());
+      our_preprocesses.insert(i, preprocess);
+      (i, machine)
+    }).collect();
+
+    (
+      WeightedSignMachine {
+        weights: self.weights,
+        identity: self.identity,
+        included: self.included,
+        machines,
+        our_preprocesses
+      },
+      preprocesses
+    )
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for WeightedSignMachine<C, A> {
+  type Preprocess = Vec<Preprocess<C>>;
+  type SignatureShare = Vec<SignatureShare<C>>;
+  type SignatureMachine = WeightedSignatureMachine<C, A>;
+
+  fn read_preprocess<R: Read>(&self, reader: &mut R) -> io::Result<Vec<Preprocess<C>>> {
+    read_vec(reader, |reader| self.machines[0].1.read_preprocess(reader))
+  }
+
+  fn sign(
+    self,
+    commitments: HashMap<u16, Vec<Preprocess<C>>>,
+    msg: &[u8]
+  ) -> Result<(Self::SignatureMachine, Vec<SignatureShare<C>>), FrostError> {
+    let weights = self.weights;
+    let commitments = expand(
+      &weights,
+      self.identity,
+      &self.included,
+      commitments,
+      &self.our_preprocesses,
+      FrostError::InvalidCommitment
+    )?;
+
+    let mut shares = vec![];
+    let mut our_shares = HashMap::new();
+    let mut machines = vec![];
+    for (i, machine) in self.machines {
+      let mut these_commitments = commitments.clone();
+      these_commitments.remove(&i);
+      let (machine, share) = machine.sign(these_commitments, msg).map_err(|e| weights.blame(e))?;
+      shares.push(share);
+      our_shares.insert(i, share);
+      machines.push((i, machine));
+    }
+
+    Ok((
+      WeightedSignatureMachine {
+        weights,
+        identity: self.identity,
+        included: self.included,
+        machines,
+        our_shares
+      },
+      shares
+    ))
+  }
+}
+
+impl<
+  C: Curve,
+  A: Algorithm<C>
+> SignatureMachine<A::Signature> for WeightedSignatureMachine<C, A> {
+  type SignatureShare = Vec<SignatureShare<C>>;
+
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<Vec<SignatureShare<C>>> {
+    read_vec(reader, |reader| self.machines[0].1.read_share(reader))
+  }
+
+  fn complete(
+    mut self,
+    shares: HashMap<u16, Vec<SignatureShare<C>>>
+  ) -> Result<A::Signature, FrostError> {
+    let weights = self.weights;
+    let mut shares = expand(
+      &weights,
+      self.identity,
+      &self.included,
+      shares,
+      &self.our_shares,
+      FrostError::InvalidShare
+    )?;
+
+    // Every held share produces the same signature, so only one machine needs to complete
+    let (i, machine) = self.machines.swap_remove(0);
+    shares.remove(&i);
+    machine.complete(shares).map_err(|e| weights.blame(e))
+  }
+}
@@ -1,18 +1,50 @@
+use alloc::vec::Vec;
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "rand")]
 use rand_core::{RngCore, CryptoRng};
+#[cfg(feature = "transcript")]
+use rand_core::SeedableRng;
+#[cfg(feature = "transcript")]
+use rand_chacha::ChaCha20Rng;
+
+#[cfg(feature = "transcript")]
+use transcript::Transcript;
 
-use group::{ff::Field, Group};
+use ff::{Field, PrimeFieldBits};
+#[cfg(feature = "transcript")]
+use ff::PrimeField;
+use group::Group;
+#[cfg(feature = "transcript")]
+use group::GroupEncoding;
 
 use crate::{multiexp, multiexp_vartime};
 
+// Overwrite the scalars with zero, in a manner which won't be optimized out, as not every field
+// implements Zeroize
+fn wipe<G: Group>(pairs: &mut [(G::Scalar, G)]) {
+  for pair in pairs.iter_mut() {
+    unsafe { core::ptr::write_volatile(&mut pair.0, G::Scalar::zero()); }
+  }
+  core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
 #[cfg(feature = "batch")]
-pub struct BatchVerifier<Id: Copy, G: Group>(Vec<(Id, Vec<(G::Scalar, G)>)>, bool);
+pub struct BatchVerifier<Id: Copy, G: Group>(Vec<(Id, Vec<(G::Scalar, G)>)>);
 
 #[cfg(feature = "batch")]
-impl<Id: Copy, G: Group> BatchVerifier<Id, G> {
-  pub fn new(capacity: usize, endian: bool) -> BatchVerifier<Id, G> {
-    BatchVerifier(Vec::with_capacity(capacity), endian)
+impl<Id: Copy, G: Group> BatchVerifier<Id, G> where G::Scalar: PrimeFieldBits {
+  pub fn new(capacity: usize) -> BatchVerifier<Id, G> {
+    BatchVerifier(Vec::with_capacity(capacity))
   }
 
+  #[cfg(any(feature = "rand", feature = "transcript"))]
+  fn push<I: IntoIterator<Item = (G::Scalar, G)>>(&mut self, u: G::Scalar, id: Id, pairs: I) {
+    self.0.push((id, pairs.into_iter().map(|(scalar, point)| (scalar * u, point)).collect()));
+  }
+
+  /// Queue a set of pairs, with their scalar factor sampled from the RNG
+  #[cfg(feature = "rand")]
   pub fn queue<
     R: RngCore + CryptoRng,
     I: IntoIterator<Item = (G::Scalar, G)>
@@ -23,32 +55,77 @@ impl<Id: Copy, G: Group> BatchVerifier<Id, G> {
     } else {
       G::Scalar::random(rng)
     };
-    self.0.push((id, pairs.into_iter().map(|(scalar, point)| (scalar * u, point)).collect()));
+    self.push(u, id, pairs);
+  }
+
+  /// Queue a set of pairs, deriving their scalar factor from the transcript instead of an RNG
+  /// The pairs are appended to the transcript before the factor is derived, binding it to every
+  /// set queued so far. The transcript should already be bound to the context of what's being
+  /// verified. This makes verification deterministic and reproducible, as needed in consensus
+  #[cfg(feature = "transcript")]
+  pub fn queue_transcript<
+    T: Transcript,
+    I: IntoIterator<Item = (G::Scalar, G)>
+  >(&mut self, transcript: &mut T, id: Id, pairs: I) where G: GroupEncoding {
+    let pairs = pairs.into_iter().collect::<Vec<_>>();
+    transcript.domain_separate(b"batch_verifier");
+    transcript.append_message(b"pairs", &u64::try_from(pairs.len()).unwrap().to_le_bytes());
+    for (scalar, point) in &pairs {
+      transcript.append_message(b"scalar", scalar.to_repr().as_ref());
+      transcript.append_message(b"point", point.to_bytes().as_ref());
+    }
+
+    let u = if self.0.is_empty() {
+      G::Scalar::one()
+    } else {
+      G::Scalar::random(&mut ChaCha20Rng::from_seed(transcript.rng_seed(b"factor")))
+    };
+    self.push(u, id, pairs);
   }
 
   pub fn verify(&self) -> bool {
-    multiexp(
-      &self.0.iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>(),
-      self.1
-    ).is_identity().into()
+    let mut pairs = self.0.iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>();
+    let res = multiexp(&pairs).is_identity().into();
+    wipe(&mut pairs);
+    res
   }
 
   pub fn verify_vartime(&self) -> bool {
-    multiexp_vartime(
-      &self.0.iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>(),
-      self.1
-    ).is_identity().into()
+    let mut pairs = self.0.iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>();
+    let res = multiexp_vartime(&pairs).is_identity().into();
+    wipe(&mut pairs);
+    res
   }
 
-  // A constant time variant may be beneficial for robust protocols
+  /// Identify the first invalid set, without timing revealing which sets are invalid
+  /// Every set is individually checked, making this notably slower than blame_vartime
+  pub fn blame(&self) -> Option<Id> {
+    let mut found = Choice::from(0);
+    let mut blamed = 0u64;
+    for (i, (_, pairs)) in self.0.iter().enumerate() {
+      let invalid = !multiexp(pairs).is_identity();
+      blamed.conditional_assign(&u64::try_from(i).unwrap(), invalid & !found);
+      found |= invalid;
+    }
+
+    if found.into() {
+      Some(self.0[usize::try_from(blamed).unwrap()].0)
+    } else {
+      None
+    }
+  }
+
+  /// Identify the first invalid set via bisection, performing O(log n) multiexps of halving size
+  /// If the first half of the remaining sets is valid, the invalid set must be in the second half
   pub fn blame_vartime(&self) -> Option<Id> {
     let mut slice = self.0.as_slice();
     while slice.len() > 1 {
       let split = slice.len() / 2;
-      if multiexp_vartime(
-        &slice[.. split].iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>(),
-        self.1
-      ).is_identity().into() {
+      let mut pairs =
+        slice[.. split].iter().flat_map(|pairs| pairs.1.iter()).cloned().collect::<Vec<_>>();
+      let valid = multiexp_vartime(&pairs).is_identity().into();
+      wipe(&mut pairs);
+      if valid {
         slice = &slice[split ..];
       } else {
         slice = &slice[.. split];
@@ -56,10 +133,18 @@ impl<Id: Copy, G: Group> BatchVerifier<Id, G> {
     }
 
     slice.get(0).filter(
-      |(_, value)| !bool::from(multiexp_vartime(value, self.1).is_identity())
+      |(_, value)| !bool::from(multiexp_vartime(value).is_identity())
     ).map(|(id, _)| *id)
   }
 
+  pub fn verify_with_blame(&self) -> Result<(), Id> {
+    if self.verify() {
+      Ok(())
+    } else {
+      Err(self.blame().unwrap())
+    }
+  }
+
   pub fn verify_with_vartime_blame(&self) -> Result<(), Id> {
     if self.verify() {
       Ok(())
@@ -76,3 +161,12 @@ impl<Id: Copy, G: Group> BatchVerifier<Id, G> {
     }
   }
 }
+
+#[cfg(feature = "batch")]
+impl<Id: Copy, G: Group> Drop for BatchVerifier<Id, G> {
+  fn drop(&mut self) {
+    for (_, pairs) in self.0.iter_mut() {
+      wipe::<G>(pairs);
+    }
+  }
+}
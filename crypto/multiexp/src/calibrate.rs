@@ -0,0 +1,99 @@
+extern crate std;
+
+use core::{any::TypeId, hint::black_box};
+use std::{
+  sync::{Arc, Mutex, PoisonError, OnceLock},
+  cell::RefCell,
+  collections::HashMap,
+  time::Instant
+};
+
+use alloc::vec::Vec;
+
+use rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use ff::{Field, PrimeField, PrimeFieldBits};
+use group::Group;
+
+use crate::{straus, pippenger, window};
+
+// Largest amount of pairs measured. If Straus is still faster, Pippenger is used from here on, as
+// it'll eventually win given its lower asymptotic cost
+const MAX_PAIRS: usize = 512;
+
+// Crossovers measured or being measured, by group
+// The lock is only held to find a group's entry, not while measuring, so measuring one group
+// doesn't block multiexps over any other
+static CROSSOVERS: OnceLock<Mutex<HashMap<TypeId, Arc<OnceLock<usize>>>>> = OnceLock::new();
+
+std::thread_local! {
+  // Crossovers this thread has already retrieved, so multiexps don't synchronize at all once a
+  // group has been calibrated
+  static CACHED: RefCell<HashMap<TypeId, usize>> = RefCell::new(HashMap::new());
+}
+
+// Fastest of several runs, to filter out noise from the rest of the system
+fn time<G: Group, F: Fn() -> G>(f: F) -> u128 {
+  (0 .. 3).map(|_| {
+    let start = Instant::now();
+    black_box(f());
+    start.elapsed().as_nanos()
+  }).min().unwrap()
+}
+
+fn pippenger_faster<G: Group>(pairs: &[(G::Scalar, G)]) -> bool where G::Scalar: PrimeFieldBits {
+  let window = window(usize::try_from(G::Scalar::NUM_BITS).unwrap(), pairs.len());
+  time(|| pippenger(pairs, window)) < time(|| straus(pairs))
+}
+
+// Find the first power of two where Pippenger is faster, then bisect below it until within 1/8th
+fn measure<G: Group>() -> usize where G::Scalar: PrimeFieldBits {
+  // Deterministic as the values themselves don't matter, only their timing does
+  let mut rng = ChaCha20Rng::from_seed([0xff; 32]);
+  let pairs = (0 .. MAX_PAIRS).map(
+    |_| (G::Scalar::random(&mut rng), G::random(&mut rng))
+  ).collect::<Vec<_>>();
+
+  let mut high = 2;
+  while !pippenger_faster(&pairs[.. high]) {
+    if high == MAX_PAIRS {
+      return MAX_PAIRS;
+    }
+    high *= 2;
+  }
+
+  let mut low = high / 2;
+  while (high - low) > (high / 8) {
+    let mid = (low + high) / 2;
+    if pippenger_faster(&pairs[.. mid]) {
+      high = mid;
+    } else {
+      low = mid;
+    }
+  }
+  high
+}
+
+/// Measure the amount of pairs from which Pippenger outperforms Straus on this host, caching the
+/// result for all further multiexps over this group
+/// multiexp calls this on its first use with each group. Calling it ahead of time avoids that
+/// multiexp taking notably longer
+pub fn calibrate<G: Group>() -> usize where G::Scalar: PrimeFieldBits {
+  let id = TypeId::of::<G>();
+  if let Some(crossover) = CACHED.with(|cached| cached.borrow().get(&id).copied()) {
+    return crossover;
+  }
+
+  // The map is only ever inserted into, so it remains consistent even if a holder panicked
+  let crossover = CROSSOVERS.get_or_init(|| Mutex::new(HashMap::new()))
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .entry(id)
+    .or_default()
+    .clone();
+  // Concurrent callers for the same group wait for a single measurement
+  let crossover = *crossover.get_or_init(measure::<G>);
+  CACHED.with(|cached| cached.borrow_mut().insert(id, crossover));
+  crossover
+}
@@ -1,49 +1,133 @@
+#![no_std]
+
+extern crate alloc;
+
+use ff::{PrimeField, PrimeFieldBits};
 use group::Group;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 mod straus;
 use straus::*;
 
 mod pippenger;
 use pippenger::*;
 
+mod precomputed;
+pub use precomputed::Precomputed;
+
+#[cfg(feature = "dalek")]
+mod dalek;
+
+#[cfg(feature = "calibrate")]
+mod calibrate;
+#[cfg(feature = "calibrate")]
+pub use calibrate::calibrate;
+
 #[cfg(feature = "batch")]
 mod batch;
 #[cfg(feature = "batch")]
 pub use batch::BatchVerifier;
 
+// Largest window Pippenger will use, bounding its buckets to 2^16 points
+const MAX_WINDOW: u8 = 16;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum Algorithm {
   Straus,
-  Pippenger
+  Pippenger(u8)
+}
+
+// Estimated additions for Straus, which builds a table of 15 additions per point before
+// performing an addition per point per nibble
+#[cfg(not(feature = "calibrate"))]
+fn straus_cost(bits: usize, pairs: usize) -> usize {
+  pairs * (15 + bits.div_ceil(4))
 }
 
-fn algorithm(pairs: usize) -> Algorithm {
-  // TODO: Replace this with an actual formula determining which will use less additions
-  // Right now, Straus is used until 600, instead of the far more accurate 300, as Pippenger
-  // operates per byte instead of per nibble, and therefore requires a much longer series to be
-  // performant
-  // Technically, 800 is dalek's number for when to use byte Pippenger, yet given Straus's own
-  // implementation limitations...
-  if pairs < 600 {
+// Estimated additions for Pippenger with the specified window, which performs an addition per
+// point per window, then two additions per bucket per window when summing the buckets
+fn pippenger_cost(bits: usize, pairs: usize, window: u8) -> usize {
+  bits.div_ceil(usize::from(window)) * (pairs + (2 << window))
+}
+
+fn window(bits: usize, pairs: usize) -> u8 {
+  (1 ..= MAX_WINDOW).min_by_key(|window| pippenger_cost(bits, pairs, *window)).unwrap()
+}
+
+// Doublings are ignored as both algorithms perform one per bit
+// With the calibrate feature, the crossover between the two is instead measured on this host
+fn algorithm<G: Group>(pairs: usize) -> Algorithm where G::Scalar: PrimeFieldBits {
+  let bits = usize::try_from(G::Scalar::NUM_BITS).unwrap();
+  let window = window(bits, pairs);
+
+  #[cfg(feature = "calibrate")]
+  let straus = pairs < calibrate::<G>();
+  #[cfg(not(feature = "calibrate"))]
+  let straus = straus_cost(bits, pairs) <= pippenger_cost(bits, pairs, window);
+
+  if straus {
     Algorithm::Straus
   } else {
-    Algorithm::Pippenger
+    Algorithm::Pippenger(window)
   }
 }
 
-// Performs a multiexp, automatically selecting the optimal algorithm based on amount of pairs
-// Takes in an iterator of scalars and points, with a boolean for if the scalars are little endian
-// encoded in their Reprs or not
-pub fn multiexp<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  match algorithm(pairs.len()) {
-    Algorithm::Straus => straus(pairs, little),
-    Algorithm::Pippenger => pippenger(pairs, little)
+fn serial<G: Group>(pairs: &[(G::Scalar, G)]) -> G where G::Scalar: PrimeFieldBits {
+  #[cfg(feature = "dalek")]
+  if let Some(res) = dalek::native(pairs, false) {
+    return res;
+  }
+
+  match algorithm::<G>(pairs.len()) {
+    Algorithm::Straus => straus(pairs),
+    Algorithm::Pippenger(window) => pippenger(pairs, window)
+  }
+}
+
+fn serial_vartime<G: Group>(pairs: &[(G::Scalar, G)]) -> G where G::Scalar: PrimeFieldBits {
+  #[cfg(feature = "dalek")]
+  if let Some(res) = dalek::native(pairs, true) {
+    return res;
+  }
+
+  match algorithm::<G>(pairs.len()) {
+    Algorithm::Straus => straus_vartime(pairs),
+    Algorithm::Pippenger(window) => pippenger_vartime(pairs, window)
   }
 }
 
-pub fn multiexp_vartime<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  match algorithm(pairs.len()) {
-    Algorithm::Straus => straus_vartime(pairs, little),
-    Algorithm::Pippenger => pippenger_vartime(pairs, little)
+type Multiexp<G> = fn(&[(<G as Group>::Scalar, G)]) -> G;
+
+// Minimum amount of pairs handled per thread, below which the overhead isn't worth it
+#[cfg(feature = "parallel")]
+const MIN_CHUNK: usize = 256;
+
+// Splits the pairs across threads, each performing their own multiexp, then sums the results
+// The sum of the partial multiexps is the multiexp of the whole
+#[cfg(feature = "parallel")]
+fn split<G: Group>(pairs: &[(G::Scalar, G)], multiexp: Multiexp<G>) -> G {
+  let threads = rayon::current_num_threads().min(pairs.len() / MIN_CHUNK);
+  if threads <= 1 {
+    return multiexp(pairs);
   }
+
+  pairs.par_chunks(pairs.len().div_ceil(threads)).map(multiexp).reduce(G::identity, |a, b| a + b)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn split<G: Group>(pairs: &[(G::Scalar, G)], multiexp: Multiexp<G>) -> G {
+  multiexp(pairs)
+}
+
+// Performs a multiexp, automatically selecting the optimal algorithm based on amount of pairs
+// Scalars are decomposed via PrimeFieldBits, so their Repr's encoding is irrelevant
+// With the parallel feature, large multiexps are split across threads
+pub fn multiexp<G: Group>(pairs: &[(G::Scalar, G)]) -> G where G::Scalar: PrimeFieldBits {
+  split(pairs, serial)
+}
+
+pub fn multiexp_vartime<G: Group>(pairs: &[(G::Scalar, G)]) -> G where G::Scalar: PrimeFieldBits {
+  split(pairs, serial_vartime)
 }
@@ -1,23 +1,24 @@
-use group::{ff::PrimeField, Group};
+use alloc::{vec, vec::Vec};
+
+use zeroize::Zeroize;
+
+use ff::PrimeFieldBits;
+use group::Group;
+
+// Split each scalar into windows of the specified amount of bits, least significant first
+fn prep<G: Group>(pairs: &[(G::Scalar, G)], window: u8) -> (Vec<Vec<usize>>, Vec<G>)
+  where G::Scalar: PrimeFieldBits {
+  let window = usize::from(window);
 
-fn prep<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> (Vec<Vec<u8>>, Vec<G>) {
   let mut res = vec![];
   let mut points = vec![];
   for pair in pairs {
-    let p = res.len();
-    res.push(vec![]);
-    {
-      let mut repr = pair.0.to_repr();
-      let bytes = repr.as_mut();
-      if !little {
-        bytes.reverse();
-      }
-
-      res[p].resize(bytes.len(), 0);
-      for i in 0 .. bytes.len() {
-        res[p][i] = bytes[i];
-      }
+    let bits = pair.0.to_le_bits();
+    let mut windows = vec![0; bits.len().div_ceil(window)];
+    for (i, bit) in bits.iter().enumerate() {
+      windows[i / window] |= usize::from(*bit) << (i % window);
     }
+    res.push(windows);
 
     points.push(pair.1);
   }
@@ -25,18 +26,19 @@ fn prep<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> (Vec<Vec<u8>>, Vec<
   (res, points)
 }
 
-pub(crate) fn pippenger<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  let (bytes, points) = prep(pairs, little);
+pub(crate) fn pippenger<G: Group>(pairs: &[(G::Scalar, G)], window: u8) -> G
+  where G::Scalar: PrimeFieldBits {
+  let (mut windows, points) = prep(pairs, window);
 
   let mut res = G::identity();
-  for n in (0 .. bytes[0].len()).rev() {
-    for _ in 0 .. 8 {
+  for n in (0 .. windows[0].len()).rev() {
+    for _ in 0 .. window {
       res = res.double();
     }
 
-    let mut buckets = [G::identity(); 256];
-    for p in 0 .. bytes.len() {
-      buckets[usize::from(bytes[p][n])] += points[p];
+    let mut buckets = vec![G::identity(); 1 << window];
+    for p in 0 .. windows.len() {
+      buckets[windows[p][n]] += points[p];
     }
 
     let mut intermediate_sum = G::identity();
@@ -46,25 +48,27 @@ pub(crate) fn pippenger<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
     }
   }
 
+  windows.zeroize();
   res
 }
 
-pub(crate) fn pippenger_vartime<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  let (bytes, points) = prep(pairs, little);
+pub(crate) fn pippenger_vartime<G: Group>(pairs: &[(G::Scalar, G)], window: u8) -> G
+  where G::Scalar: PrimeFieldBits {
+  let (mut windows, points) = prep(pairs, window);
 
   let mut res = G::identity();
-  for n in (0 .. bytes[0].len()).rev() {
-    if n != (bytes[0].len() - 1) {
-      for _ in 0 .. 8 {
+  for n in (0 .. windows[0].len()).rev() {
+    if n != (windows[0].len() - 1) {
+      for _ in 0 .. window {
         res = res.double();
       }
     }
 
-    let mut buckets = [G::identity(); 256];
-    for p in 0 .. bytes.len() {
-      let nibble = usize::from(bytes[p][n]);
-      if nibble != 0 {
-        buckets[nibble] += points[p];
+    let mut buckets = vec![G::identity(); 1 << window];
+    for p in 0 .. windows.len() {
+      let value = windows[p][n];
+      if value != 0 {
+        buckets[value] += points[p];
       }
     }
 
@@ -75,5 +79,6 @@ pub(crate) fn pippenger_vartime<G: Group>(pairs: &[(G::Scalar, G)], little: bool
     }
   }
 
+  windows.zeroize();
   res
 }
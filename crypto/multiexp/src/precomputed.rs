@@ -0,0 +1,54 @@
+use alloc::vec::Vec;
+
+use zeroize::Zeroize;
+
+use ff::PrimeFieldBits;
+use group::Group;
+
+use crate::straus::{nibbles, table, straus_tables, straus_tables_vartime};
+
+/// Straus tables for a series of fixed bases, such as a generator, calculated once and reused
+/// across multiexps over those bases
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Precomputed<G: Group>(Vec<[G; 16]>);
+
+impl<G: Group> Precomputed<G> where G::Scalar: PrimeFieldBits {
+  pub fn new(bases: &[G]) -> Precomputed<G> {
+    Precomputed(bases.iter().map(|base| table(*base)).collect())
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  fn nibbles(&self, scalars: &[G::Scalar]) -> Vec<Vec<u8>> {
+    assert_eq!(scalars.len(), self.0.len(), "amount of scalars doesn't match amount of bases");
+    scalars.iter().map(nibbles).collect()
+  }
+
+  /// Performs a multiexp of the scalars against these bases, in order
+  /// Panics if the amount of scalars doesn't match the amount of bases
+  pub fn multiexp(&self, scalars: &[G::Scalar]) -> G {
+    if self.is_empty() {
+      return G::identity();
+    }
+    let mut nibbles = self.nibbles(scalars);
+    let res = straus_tables(&nibbles, &self.0);
+    nibbles.zeroize();
+    res
+  }
+
+  pub fn multiexp_vartime(&self, scalars: &[G::Scalar]) -> G {
+    if self.is_empty() {
+      return G::identity();
+    }
+    let mut nibbles = self.nibbles(scalars);
+    let res = straus_tables_vartime(&nibbles, &self.0);
+    nibbles.zeroize();
+    res
+  }
+}
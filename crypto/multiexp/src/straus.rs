@@ -1,39 +1,44 @@
-use group::{ff::PrimeField, Group};
+use alloc::{vec, vec::Vec};
 
-fn prep<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> (Vec<Vec<u8>>, Vec<[G; 16]>) {
-  let mut nibbles = vec![];
-  let mut tables = vec![];
-  for pair in pairs {
-    let p = nibbles.len();
-    nibbles.push(vec![]);
-    {
-      let mut repr = pair.0.to_repr();
-      let bytes = repr.as_mut();
-      if !little {
-        bytes.reverse();
-      }
+use zeroize::Zeroize;
 
-      nibbles[p].resize(bytes.len() * 2, 0);
-      for i in 0 .. bytes.len() {
-        nibbles[p][i * 2] = bytes[i] & 0b1111;
-        nibbles[p][(i * 2) + 1] = (bytes[i] >> 4) & 0b1111;
-      }
-    }
+use ff::PrimeFieldBits;
+use group::Group;
 
-    tables.push([G::identity(); 16]);
-    let mut accum = G::identity();
-    for i in 1 .. 16 {
-      accum += pair.1;
-      tables[p][i] = accum;
-    }
+// Split a scalar into nibbles, least significant first
+pub(crate) fn nibbles<F: PrimeFieldBits>(scalar: &F) -> Vec<u8> {
+  let bits = scalar.to_le_bits();
+  let mut nibbles = vec![0; bits.len().div_ceil(4)];
+  for (i, bit) in bits.iter().enumerate() {
+    nibbles[i / 4] |= u8::from(*bit) << (i % 4);
   }
+  nibbles
+}
 
-  (nibbles, tables)
+// Table of the first 16 multiples of a point, starting from the identity
+pub(crate) fn table<G: Group>(point: G) -> [G; 16] {
+  let mut table = [G::identity(); 16];
+  let mut accum = G::identity();
+  for i in 1 .. 16 {
+    accum += point;
+    table[i] = accum;
+  }
+  table
 }
 
-pub(crate) fn straus<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  let (nibbles, tables) = prep(pairs, little);
+fn prep<G: Group>(pairs: &[(G::Scalar, G)]) -> (Vec<Vec<u8>>, Vec<[G; 16]>)
+  where G::Scalar: PrimeFieldBits {
+  let mut nibbles = vec![];
+  let mut tables = vec![];
+  for pair in pairs {
+    nibbles.push(self::nibbles(&pair.0));
+    tables.push(table(pair.1));
+  }
+  (nibbles, tables)
+}
 
+// Straus given already calculated nibbles and tables, enabling tables to be reused
+pub(crate) fn straus_tables<G: Group>(nibbles: &[Vec<u8>], tables: &[[G; 16]]) -> G {
   let mut res = G::identity();
   for b in (0 .. nibbles[0].len()).rev() {
     for _ in 0 .. 4 {
@@ -47,9 +52,7 @@ pub(crate) fn straus<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
   res
 }
 
-pub(crate) fn straus_vartime<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -> G {
-  let (nibbles, tables) = prep(pairs, little);
-
+pub(crate) fn straus_tables_vartime<G: Group>(nibbles: &[Vec<u8>], tables: &[[G; 16]]) -> G {
   let mut res = G::identity();
   for b in (0 .. nibbles[0].len()).rev() {
     if b != (nibbles[0].len() - 1) {
@@ -67,3 +70,18 @@ pub(crate) fn straus_vartime<G: Group>(pairs: &[(G::Scalar, G)], little: bool) -
 
   res
 }
+
+pub(crate) fn straus<G: Group>(pairs: &[(G::Scalar, G)]) -> G where G::Scalar: PrimeFieldBits {
+  let (mut nibbles, tables) = prep(pairs);
+  let res = straus_tables(&nibbles, &tables);
+  nibbles.zeroize();
+  res
+}
+
+pub(crate) fn straus_vartime<G: Group>(pairs: &[(G::Scalar, G)]) -> G
+  where G::Scalar: PrimeFieldBits {
+  let (mut nibbles, tables) = prep(pairs);
+  let res = straus_tables_vartime(&nibbles, &tables);
+  nibbles.zeroize();
+  res
+}
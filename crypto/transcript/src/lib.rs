@@ -1,8 +1,13 @@
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+
 use core::fmt::Debug;
 
-#[cfg(features = "merlin")]
+#[cfg(feature = "merlin")]
 mod merlin;
-#[cfg(features = "merlin")]
+#[cfg(feature = "merlin")]
 pub use merlin::MerlinTranscript;
 
 use digest::{typenum::type_operators::IsGreaterOrEqual, consts::U256, Digest};
@@ -2,17 +2,19 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
 
 use dalek_ff_group as dfg;
 use transcript::RecommendedTranscript;
 use frost::{curve::Ed25519, MultisigKeys};
 
-use monero::{PublicKey, network::Network, util::address::Address};
 use monero_serai::{
   transaction::{Timelock, Transaction},
   rpc::Rpc,
-  wallet::{Fee, SpendableOutput, SignableTransaction as MSignableTransaction, TransactionMachine}
+  wallet::{
+    Network, Address,
+    Fee, SpendableOutput, SignableTransaction as MSignableTransaction, TransactionMachine
+  }
 };
 
 use crate::{coin::{CoinError, Output as OutputTrait, Coin}, view_key};
@@ -60,7 +62,7 @@ pub struct SignableTransaction(
 pub struct Monero {
   pub(crate) rpc: Rpc,
   view: Scalar,
-  view_pub: PublicKey
+  view_pub: EdwardsPoint
 }
 
 impl Monero {
@@ -69,7 +71,7 @@ impl Monero {
     Monero {
       rpc: Rpc::new(url),
       view,
-      view_pub: PublicKey { point: (&view * &ED25519_BASEPOINT_TABLE).compress() }
+      view_pub: &view * &ED25519_BASEPOINT_TABLE
     }
   }
 }
@@ -100,7 +102,7 @@ impl Coin for Monero {
   const MAX_OUTPUTS: usize = 16;
 
   fn address(&self, key: dfg::EdwardsPoint) -> Self::Address {
-    Address::standard(Network::Mainnet, PublicKey { point: key.compress().0 }, self.view_pub)
+    Address::standard(Network::Mainnet, key.0, self.view_pub)
   }
 
   async fn get_height(&self) -> Result<usize, CoinError> {
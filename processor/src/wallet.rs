@@ -4,7 +4,10 @@ use rand_core::OsRng;
 
 use transcript::{Transcript, RecommendedTranscript};
 
-use frost::{curve::Curve, MultisigKeys, sign::{PreprocessMachine, SignMachine, SignatureMachine}};
+use frost::{
+  curve::Curve, FrostError, MultisigKeys, Writable,
+  sign::{PreprocessMachine, SignMachine, SignatureMachine}
+};
 
 use crate::{coin::{CoinError, Output, Coin}, SignError, Network};
 
@@ -349,11 +352,23 @@ impl<D: CoinDb, C: Coin> Wallet<D, C> {
       &included
     ).await.map_err(|e| SignError::CoinError(e))?;
 
-    let (attempt, commitments) = attempt.preprocess(&mut OsRng);
-    let commitments = network.round(commitments).await.map_err(|e| SignError::NetworkError(e))?;
+    let (attempt, preprocess) = attempt.preprocess(&mut OsRng);
+    let commitments = network.round(
+      preprocess.serialize()
+    ).await.map_err(|e| SignError::NetworkError(e))?.drain().map(|(l, c)| Ok((
+      l,
+      attempt.read_preprocess(&mut c.as_slice())
+        .map_err(|_| SignError::FrostError(FrostError::InvalidCommitment(l)))?
+    ))).collect::<Result<_, _>>()?;
 
     let (attempt, share) = attempt.sign(commitments, b"").map_err(|e| SignError::FrostError(e))?;
-    let shares = network.round(share).await.map_err(|e| SignError::NetworkError(e))?;
+    let shares = network.round(
+      share.serialize()
+    ).await.map_err(|e| SignError::NetworkError(e))?.drain().map(|(l, s)| Ok((
+      l,
+      attempt.read_share(&mut s.as_slice())
+        .map_err(|_| SignError::FrostError(FrostError::InvalidShare(l)))?
+    ))).collect::<Result<_, _>>()?;
 
     let tx = attempt.complete(shares).map_err(|e| SignError::FrostError(e))?;
 